@@ -0,0 +1,545 @@
+project-new = Neues Projekt
+project-open = Projekt öffnen
+project-open-dialog = Projekt öffnen...
+project-save = Projekt speichern
+output = Ausgabe:
+untitled = Unbenannt
+frame-rate = Bildrate:
+language = Sprache:
+stats = Statistik
+stats-tooltip = Leistungsstatistik über der Leinwand anzeigen (F3)
+copy-diagnostics = Diagnose kopieren
+copy-diagnostics-tooltip = App-Version und aktuelles Protokoll für einen Fehlerbericht kopieren
+preview-detach = Vorschau abtrennen
+preview-attach = Vorschaufenster schließen
+preview-detach-tooltip = Zeigt die Leinwand in einem eigenen Fenster, z. B. auf einem zweiten Bildschirm. F11 schaltet auf Vollbild.
+preview-window-title = roygbiv-Vorschau
+status-zoom = Zoom { $percent } %
+status-analyzing = Audio wird analysiert…
+status-decoding = Bilder werden dekodiert: { $count }
+empty-title = Beginne mit Audio oder einem Bild
+empty-open-audio = Audio öffnen
+empty-add-layer = Ebene hinzufügen
+empty-open-project = Projekt öffnen
+empty-drop-hint = oder Audio-, Bild- oder Projektdateien irgendwo ins Fenster ziehen
+cancel = Abbrechen
+
+play = Abspielen
+pause = Pause
+mute = Stummschalten
+unmute = Ton einschalten
+record-keyframes = Jede Änderung als Keyframe aufnehmen
+stop-recording = Keyframe-Aufnahme beenden
+previous-frame = Vorheriges Bild (Pfeil links)
+next-frame = Nächstes Bild (Pfeil rechts)
+time-format-tooltip = Zwischen Sekunden, Bildern und Timecode wechseln
+preview-speed = Vorschaugeschwindigkeit
+preserve-pitch = Tonhöhe beibehalten
+
+audio-select = Audiodatei auswählen
+audio-remove = Audiodatei entfernen
+audio-key = Tonart: { $key }
+audio-peak = Spitze { $level } dB
+audio-range = Spur: { $start } – { $end }
+trim-silence = Stille abschneiden
+trim-silence-tooltip = Die Stille am Anfang und Ende der Datei abschneiden
+trim-start-here = Hier beginnen
+trim-end-here = Hier enden
+trim-whole-file = Ganze Datei
+track-remove = Entfernen
+track-gain = Pegel: { $gain } dB
+track-starts-at = beginnt bei: { $time }
+track-start-at-playhead = An der Abspielposition beginnen
+track-add = Audiospur hinzufügen
+output-default = Systemstandard
+
+layers = Ebenen
+layer-settings = Ebeneneinstellungen
+layer-add = Neue Ebene hinzufügen
+layer-delete = Ebene löschen
+layer-filter = Ebenen filtern
+layer-category-all = Alle Ebenen
+layer-category-images = Bilder
+layer-category-text = Text
+layer-category-overlays = Overlays
+overlay-add = Overlay hinzufügen...
+
+assets = Medien
+assets-empty = Keine importierten Medien
+asset-image = Bild
+asset-audio = Audio
+asset-unused = unbenutzt
+asset-one-use = 1 Verwendung
+asset-uses =
+    { $count ->
+        [one] { $count } Verwendung
+       *[other] { $count } Verwendungen
+    }
+asset-replace = Datei ersetzen
+asset-remove = Aus dem Projekt entfernen
+memory = Speicher
+memory-of-budget = { $used } von { $budget }
+memory-breakdown = Dateien { $files } · Vorschauen { $previews } · gerendert { $renders } · Audioanalyse { $analysis }
+memory-budget-tooltip = Darüber hinaus werden gerenderte Bilder verworfen und Vorschauen verkleinert
+swatches = Farbfelder
+swatches-empty = Noch keine Farbfelder. Eines hinzufügen, die Farbe einer Ebene speichern oder Farben eines Bildes unter Medien übernehmen.
+swatches-pick = Farbfelder des Projekts
+swatch-remove = Farbfeld entfernen
+swatch-add = Farbfeld hinzufügen
+swatch-save = Als Farbfeld speichern
+swatch-name = Farbe { $number }
+swatch-name-placeholder = Name
+seed-randomize = Zufälligen Startwert ausprobieren
+safe-area-tooltip = Zeigt, wo eine Plattform das Video mit eigenen Schaltflächen und Beschriftungen verdeckt. Nur im Editor sichtbar.
+post-fx = Post-FX
+post-fx-tooltip = Effekte über dem fertigen Bild: Letterbox-Balken, ein Kaleidoskop aller Ebenen zusammen, Bloom, chromatische Aberration, Schärfen und eine Farbkorrektur
+post-fx-rendered = Mit jedem Effekt außer der Letterbox wird die Leinwand in Software gerendert, was langsamer ist
+orientation-fit = Ebenen anpassen
+orientation-fit-tooltip = Verschiebt alle Ebenen und passt ihre Größe an, wenn die Leinwand gedreht wird. Sonst folgen ihr nur Ebenen mit Größen in Prozent.
+audiogram-wizard = Audiogramm-Assistent…
+audiogram-description = Erstellt ein Projekt für eine Podcastfolge, mit Cover, Titel, Wellenform und Fortschrittsbalken.
+audiogram-audio = Folge
+audiogram-cover = Cover
+audiogram-title = Titel
+audiogram-title-placeholder = Titel der Folge
+audiogram-format = Format
+audiogram-none = Nichts ausgewählt
+audiogram-choose = Auswählen…
+audiogram-create = Projekt erstellen
+markers = Marker
+marker-add = Am Abspielkopf hinzufügen
+marker-name = Marker { $number }
+marker-name-placeholder = Name
+marker-remove = Marker entfernen
+markers-import = Importieren…
+markers-import-tooltip = Liest Kapitel aus einer Liste von Zeiten und Namen, eine pro Zeile, oder aus den Kapiteln einer MP3-, M4A- oder M4B-Datei.
+markers-from-audio = Aus dem Audio
+markers-imported = { $count } Marker importiert
+markers-none-found = Keine neuen Kapitel gefunden
+scenes = Szenen
+scenes-tooltip = Jede Szene hat ihre eigenen Ebenen und dauert, bis die nächste beginnt, die sie mit ihrem Übergang ablöst. Ebenen ohne Szene sind in allen zu sehen.
+scene-add = Am Abspielkopf hinzufügen
+scenes-from-markers = Aus Markern
+scene-name = Szene { $number }
+scene-name-placeholder = Name
+scene-remove = Szene entfernen
+thumbnail-render = Vorschaubild rendern
+thumbnail-render-tooltip = Speichert das Bild am Abspielkopf, gerendert in der gewählten Größe. Ebenen lassen sich nur in Vorschaubildern oder überall außer dort anzeigen
+thumbnail-saved = Vorschaubild unter { $path } gespeichert
+snapshots = Stände
+snapshots-empty = Noch keine Stände. Halte das Projekt so fest, wie es jetzt ist, um später dorthin zurückzukehren. Stände werden mit dem Projekt gespeichert.
+snapshot-name-placeholder = Name des Stands
+snapshot-default-name = Stand { $count }
+snapshot-take = Stand festhalten
+snapshot-restore = Wiederherstellen
+snapshot-remove = Stand löschen
+snapshot-before-restore = Vor Wiederherstellen von { $name }
+snapshot-restored = { $name } wiederhergestellt
+snapshot-just-now = Gerade eben
+snapshot-minutes-ago = Vor { $count } Min.
+snapshot-hours-ago = Vor { $count } Std.
+snapshot-days-ago = Vor { $count } T.
+compare = Vergleichen
+compare-pin = Als A festhalten
+compare-pin-tooltip = Hält die Leinwand so, wie sie jetzt ist, als „A“ fest, um spätere Änderungen, „B“, nebeneinander oder im Wechsel damit zu vergleichen
+compare-repin = B als A festhalten
+compare-repin-tooltip = Vergleicht spätere Änderungen stattdessen mit der Leinwand, wie sie jetzt ist
+compare-stop = Vergleich beenden
+audio-loading = Audiodatei wird gelesen…
+audio-analyzing = Wird analysiert…
+layer-arrangement-tooltip = Wie mehrere auf einmal hinzugefügte oder abgelegte Bilder angeordnet werden
+asset-animation = Animation
+lottie-export = Lottie exportieren
+lottie-export-tooltip = Speichert die Ebenen als Lottie-Animation für das Web und für Apps. Bilder werden eingebettet, aus dem Audio gezeichnete Ebenen weggelassen
+lottie-exported = Animation unter { $path } exportiert
+lottie-left-out = { $layers } weggelassen, da sie dem Audio oder der Uhr folgen
+palette-extract = Farbpalette übernehmen
+palette-extracted =
+    { $count ->
+        [one] { $count } Farbe zu den Farbfeldern hinzugefügt
+       *[other] { $count } Farben zu den Farbfeldern hinzugefügt
+    }
+palette-failed = Die Farben des Bildes konnten nicht gelesen werden
+
+relink-title = Fehlende Dateien
+relink-description = Einige Dateien dieses Projekts wurden nicht gefunden. Suche sie, oder öffne das Projekt ohne sie.
+relink-browse = Durchsuchen...
+relink-skip = Fehlende Dateien überspringen
+
+command-placeholder = Befehl eingeben...
+
+section-transform = Transformation
+section-appearance = Aussehen
+section-effects = Effekte
+section-audio-reactivity = Reaktion auf Audio
+section-timing = Timing
+error-dialog-closed = der Dialog wurde geschlossen
+error-project-format = kein gültiges Projekt ({ $error })
+error-clipboard = Fehler der Zwischenablage ({ $error })
+error-audio-decode = das Audio konnte nicht dekodiert werden ({ $error })
+error-image-decode = das Bild konnte nicht gelesen werden ({ $error })
+error-cancelled = abgebrochen
+error-file-too-large = die Datei ist { $size } groß, mehr als die { $limit }, die geöffnet werden können
+error-clipboard-image = ungültige Bilddaten
+dialog-import-font = Schrift importieren...
+filter-font = Schriftdatei
+dialog-open-audio = Audiodatei öffnen...
+filter-audio = Audio- oder Videodatei
+dialog-add-images = Bilder hinzufügen...
+dialog-add-lottie = Lottie-Animation hinzufügen...
+filter-lottie = Lottie-Animation
+dialog-load-lut = LUT laden...
+filter-lut = 3D-LUT
+dialog-open-image = Bilddatei öffnen...
+dialog-import-chapters = Kapitel importieren...
+filter-image = Bilddatei
+filter-chapters = Kapitelliste oder Audiodatei
+error-open-audio = Die Audiodatei konnte nicht geöffnet werden
+error-open-image = Das Bild konnte nicht geöffnet werden
+error-open-images = Die Bilder konnten nicht geöffnet werden
+error-open-an-image = Ein Bild konnte nicht geöffnet werden
+error-open-animation = Die Animation konnte nicht geöffnet werden
+error-import-psd = Die Photoshop-Datei konnte nicht importiert werden
+error-save-preferences = Die Einstellungen konnten nicht gespeichert werden
+error-save-thumbnail = Das Vorschaubild konnte nicht gespeichert werden
+error-export-animation = Die Animation konnte nicht exportiert werden
+diagnostics-copied = Diagnosedaten in die Zwischenablage kopiert
+error-import-chapters = Die Kapitel konnten nicht importiert werden
+error-open-project = Das Projekt konnte nicht geöffnet werden
+error-save-project = Das Projekt konnte nicht gespeichert werden
+error-load-project-files = Die Dateien des Projekts konnten nicht geladen werden
+error-restore-snapshot = Der Schnappschuss konnte nicht wiederhergestellt werden
+error-replace-file = Die Datei konnte nicht ersetzt werden
+error-import-font = Die Schrift konnte nicht importiert werden
+error-load-font = Eine Schrift konnte nicht geladen werden
+error-open-lut = Die LUT konnte nicht geöffnet werden
+camera-none-found = Es wurde keine Kamera gefunden
+memory-downscaled = Bildvorschauen wurden verkleinert, um im Speicherbudget zu bleiben
+error-analyze-audio = Das Audio konnte nicht analysiert werden: { $error }
+error-read-file = { $path } konnte nicht gelesen werden ({ $error })
+error-capture-screen = Der Bildschirm konnte nicht aufgenommen werden: { $error }
+error-find-camera = Es konnte keine Kamera gefunden werden: { $error }
+error-capture = { $source } konnte nicht aufgenommen werden: { $error }
+error-decode = { $name } konnte nicht dekodiert werden
+project-saved = { $path } gespeichert
+error-not-a-font = { $path } ist keine Schriftdatei
+error-unsupported-image = { $path } kann nicht geöffnet werden: { $format }-Bilder werden noch nicht unterstützt, exportiere es zuerst als JPEG oder PNG
+error-unsupported-file = { $path } kann nicht geöffnet werden: nicht unterstützter Dateityp
+error-read-image = { $name } konnte nicht als Bild gelesen werden
+layer-name-backdrop = Hintergrundunschärfe
+layer-name-screen-capture = Bildschirmaufnahme
+layer-name-image-backdrop = { $name } Hintergrund
+
+command-add-image = Bildebene hinzufügen
+command-paste-image = Bild als Ebene einfügen
+command-import-psd = Photoshop-Ebenen importieren
+command-add-lottie = Lottie-Animation hinzufügen
+command-add-screen-capture = Bildschirmaufnahme hinzufügen
+command-add-camera = Kamera hinzufügen
+command-add-backdrop = Ebene mit Hintergrundunschärfe hinzufügen
+command-previous-frame = Vorheriges Bild
+command-next-frame = Nächstes Bild
+command-time-format = Zeitformat wechseln
+command-take-snapshot = Schnappschuss machen
+command-audiogram = Audiogramm erstellen
+command-render-thumbnail = Vorschaubild rendern
+command-export-lottie = Lottie-Animation exportieren
+command-pin-comparison = Leinwand als A zum Vergleichen festhalten
+command-switch-comparison = Zwischen A und B wechseln
+command-toggle-stats = Leistungsstatistik ein-/ausblenden
+command-toggle-post-fx = Post-FX ein-/ausblenden
+command-focus-next-panel = Nächsten Bereich fokussieren
+command-delete-layer = Ausgewählte Ebene löschen
+command-fit-layer = Ausgewählte Ebene an Leinwand anpassen
+command-copy-style = Ebenenstil kopieren
+command-add-blurred-backdrop = Unscharfen Hintergrund aus ausgewähltem Bild hinzufügen
+command-paste-style = Ebenenstil einfügen
+command-show-layers = Ebenen anzeigen
+command-show-assets = Medien anzeigen
+command-show-swatches = Farbfelder anzeigen
+command-show-snapshots = Schnappschüsse anzeigen
+command-landscape = Leinwand ins Querformat drehen
+command-portrait = Leinwand ins Hochformat drehen
+command-square = Leinwand quadratisch machen
+command-record-keyframes = Keyframes aufnehmen
+command-add-overlay = Overlay { $overlay } hinzufügen
+command-preview-speed = Vorschaugeschwindigkeit: { $speed }
+command-frame-rate = Bildrate: { $rate }
+
+transform-reset = Transformation zurücksetzen
+width-label = Breite:
+height-label = Höhe:
+width = Breite
+height = Höhe
+aspect-unlock = Seitenverhältnis freigeben
+aspect-lock = Seitenverhältnis sperren
+flip-horizontal = Horizontal spiegeln
+flip-vertical = Vertikal spiegeln
+sizing-label = Größe:
+image-replace = Bild ersetzen...
+image-choose = Bild wählen...
+fit-to-canvas = An Leinwand anpassen
+crop-done = Zuschneiden beenden
+crop = Zuschneiden
+crop-reset = Zuschnitt zurücksetzen
+corner-radius = Eckenradius: { $radius }
+border-width = Rahmen: { $width }
+border-follows-pitch = Farbton folgt der Tonhöhe
+tint-label = Tönung:
+kaleidoscope-image = Kaleidoskop (nur dieses Bild)
+animation-label = Animation:
+reacts-to = reagiert auf:
+motion-copy = Bewegung kopieren
+paste = Einfügen
+paste-reversed = Umgekehrt einfügen
+timing-shown = sichtbar: { $start } – { $end }
+timing-end = Ende
+timing-always = Immer
+timing-marker = Während einer Marke zeigen…
+timing-scene = In jeder Szene
+timing-every-scene = Jede Szene
+timing-offset = Zeitversatz: { $offset } s
+transition-label = Übergang:
+duration-seconds = Dauer: { $value } s
+playback-speed = Wiedergabegeschwindigkeit: { $speed }×
+playback-ping-pong = Hin und zurück
+style-copy = Stil kopieren
+style-copy-tooltip = Rahmen, Ecken, Tönung, Deckkraft und Audioreaktion kopieren
+style-paste = Stil einfügen
+layer-none-selected = Keine Ebene ausgewählt
+
+property-linked = verknüpft mit { $layer }
+property-unlink = Verknüpfung lösen
+property-reset = Auf Standard zurücksetzen
+property-stop-animating = Animation beenden
+property-animate = Mit Keyframes animieren
+graph-show = Im Kurveneditor zeigen
+keyframes-copy = Keyframes kopieren
+keyframes-paste = Keyframes am Abspielkopf einfügen
+keyframes-paste-reversed = Keyframes umgekehrt einfügen
+property-link = Verknüpfen
+property-link-tooltip = Dieser Eigenschaft einer anderen Ebene folgen
+graph-title = Keyframes: { $property }
+graph-hint = Keyframes und Kurvengriffe ziehen, Rechtsklick zum Löschen
+close = Schließen
+chroma-key = Farbstanze
+chroma-tolerance = Toleranz: { $value }
+chroma-spill = Farbsaumunterdrückung: { $value }
+chroma-feather = Kantenweichheit: { $value }
+capture-label = Aufnahme:
+capture-refresh = Fenster und Kameras erneut suchen
+resolution-label = Auflösung:
+size-value = Größe: { $value }
+softness-value = Weichheit: { $value }
+amount-value = Stärke: { $value }
+speed-per-second = Tempo: { $value }/s
+speed-value = Tempo: { $value }
+density-value = Dichte: { $value }
+depth-value = Tiefe: { $value }
+twinkle-value = Funkeln: { $value }
+reactivity-value = Audioreaktion: { $value }×
+decimals-value = Nachkommastellen: { $value }
+span-value = Zeitspanne: { $value } s
+bar-width-value = Balkenbreite: { $value }
+readout-show-label = Beschriftung zeigen
+wave-mirrored = Von der Mitte aus spiegeln
+progress-played = gespielt:
+progress-rest = Rest:
+progress-rounded = Abgerundete Enden
+timer-target = zählt herunter bis: { $time }
+timer-target-here = Bis hierher herunterzählen
+text-placeholder = Text
+text-markup-hint = Setze Wörter in *Sternchen* für Kursiv oder in **zwei** für Fett.
+letter-spacing-value = Laufweite: { $value }
+line-height-value = Zeilenhöhe: { $value }×
+outline-value = Kontur: { $value }
+text-background = Hintergrundkasten
+padding-value = Innenabstand: { $value }
+delay-value = Verzögerung: { $value } s
+stagger-value = Versatz: { $value } s
+font-default = Standard
+font-imported = { $family } (Projekt)
+font-label = Schrift:
+font-search = Schriften suchen
+font-import = Importieren...
+font-italic = Kursiv
+
+bloom-value = Leuchten: { $value }
+threshold-value = Schwelle: { $value }
+glow-value = Schein: { $value }px
+aberration-value = Aberration: { $value }px
+sharpen-value = Schärfen: { $value }
+kaleidoscope = Kaleidoskop
+window-title = { $project }{ $dirty } — roygbiv
+stats-fps = { $fps } fps
+stats-frame = Frame: { $update } ms Update, { $draw } ms Zeichnen
+stats-draw-calls = Zeichenaufrufe: { $count }
+stats-layers = Ebenen: { $drawn } gezeichnet, { $cached } aus dem Cache
+stats-renders = Bildrenderings: { $percent } % aus dem Cache
+stats-renders-none = Bildrenderings: noch keine
+stats-analysis = Audioanalyse: { $time } ms
+stats-analysis-none = Audioanalyse: noch keine
+stats-memory = Speicher: { $size }
+distortion-label = Verzerrung:
+amount-px = Stärke: { $value }px
+scale-px = Maßstab: { $value }px
+distortion-follows-bass = Stärke folgt dem Bass
+lut-load = LUT laden...
+lut-name = LUT: { $name }
+lut-name-amount = LUT: { $name } ({ $amount })
+replace = Ersetzen
+remove = Entfernen
+backdrop-beneath = unter der Ebene:
+backdrop-image = dieses Bild:
+blur-px = Weichzeichnen: { $value }px
+darken-value = Abdunkeln: { $value }
+backdrop-add = Weichgezeichneten Hintergrund hinzufügen
+segments-value = Segmente: { $value }
+rotation-value = Drehung: { $value }°
+center-value = Mitte: { $x }, { $y }
+glitch = Glitch
+glitch-split = RGB-Versatz: { $value }px
+glitch-blocks = Blöcke verschieben: { $value }
+glitch-scanlines = Zeilen: { $value }
+glitch-intensity = Intensität:
+seed-label = Startwert:
+
+align-left = Links
+align-center = Zentriert
+align-right = Rechts
+text-animation-none = Keine Textanimation
+text-animation-typewriter = Schreibmaschine
+text-animation-fade = Einblenden
+text-animation-bounce = Im Takt hüpfen
+text-unit-letter = Buchstabenweise
+text-unit-word = Wortweise
+source-main = Hauptspur
+source-track = Spur { $number }
+channel-mid = Beide Kanäle
+channel-left = Linker Kanal
+channel-right = Rechter Kanal
+channel-side = Stereodifferenz
+thumbnail-canvas-size = Leinwandgröße
+thumbnail-save-dialog = Vorschaubild speichern...
+project-save-dialog = Projekt speichern...
+filter-project = roygbiv-Projekt
+relink-dialog = Fehlende Datei suchen...
+lottie-export-dialog = Lottie-Animation exportieren...
+psd-import-dialog = Photoshop-Ebenen importieren...
+filter-psd = Photoshop-Datei
+visibility-everywhere = Überall sichtbar
+visibility-output = Nur in der Ausgabe
+visibility-editor = Nur im Editor
+visibility-thumbnail = Nur in Vorschaubildern
+visibility-not-thumbnail = Überall außer in Vorschaubildern
+arrangement-grid = Im Raster
+arrangement-stack = Gestapelt
+safe-area-none = Keine Hilfslinien
+safe-area-broadcast = Titelsicherer Bereich
+safe-area-youtube-thumbnail = YouTube-Vorschaubild
+none = Keine
+tint-multiply = Tönen
+tint-colorize = Einfärben
+tint-duotone = Duplex
+sizing-fit = Einpassen
+sizing-fill = Füllen
+sizing-stretch = Strecken
+sizing-tile = Kacheln
+capture-region = Teil des Bildschirms
+weight-thin = Dünn
+weight-extra-light = Extraleicht
+weight-light = Leicht
+weight-regular = Normal
+weight-medium = Mittel
+weight-semibold = Halbfett
+weight-bold = Fett
+weight-extra-bold = Extrafett
+weight-black = Schwarz
+procedural-vignette = Vignette
+procedural-grain = Filmkorn
+procedural-starfield = Sternenfeld
+procedural-readout = Anzeige
+procedural-text = Text
+procedural-timer = Timer
+procedural-wave = Wellenform
+procedural-progress = Fortschrittsbalken
+reading-level = Pegel (dB)
+reading-note = Note (Hz)
+reading-elapsed = Verstrichene Zeit
+reading-tempo = Tempo (BPM)
+reading-chapter = Kapitel
+counting-elapsed = Verstrichene Zeit
+counting-remaining = Verbleibende Zeit
+counting-countdown = Countdown
+timer-seconds = Sekunden
+wave-bars = Balken
+wave-line = Linie
+distortion-wave = Welle
+distortion-ripple = Kräuseln
+distortion-displace = Durch Textur verschieben
+preset-bounce = Im Takt hüpfen
+preset-breathe = Mit dem Bass atmen
+preset-drift = Langsam treiben
+preset-spin = Drehen
+transition-crossfade = Überblenden
+transition-slide = Schieben
+transition-zoom = Zoomen
+easing-linear = Linear
+easing-in = Langsam beginnen
+easing-out = Langsam enden
+easing-in-out = Langsam beginnen und enden
+property-scale = Skalierung
+property-opacity = Deckkraft
+key-major = { $tonic }-Dur
+key-minor = { $tonic }-Moll
+glitch-bass = Mit dem Bass
+glitch-loudness = Mit der Lautstärke
+glitch-beat = Auf jeden Schlag
+letterbox-none = Kein Letterbox
+
+error-capture-x11 = die Bildschirmaufnahme braucht eine X11-Sitzung, oder XWayland für X11-Programme
+error-capture-disconnected = nicht mit dem X-Server verbunden
+error-capture-camera = { $camera } ist nicht auf dem Bildschirm
+error-capture-window = es gibt kein Fenster namens { $window }
+error-capture-hidden = der Bereich liegt außerhalb des Bildschirms, oder das Fenster ist verborgen
+error-capture-depth = Bildschirme mit der Farbtiefe { $depth } können nicht aufgenommen werden
+error-capture-unsupported = die Bildschirmaufnahme gibt es vorerst nur unter Linux
+error-camera-no-frames = die Kamera hat keine Bilder gesendet
+error-camera-format = die Kamera sendet Bilder in einem unbekannten Format
+error-camera-buffer = die Kamera hat einen Puffer zurückgegeben, den sie nicht bekommen hat
+error-camera-size = die Kamera hat ein Bild mit falscher Größe gesendet
+error-camera-unsupported = Kameras gibt es vorerst nur unter Linux
+error-no-audio-track = keine Tonspur
+error-unknown-sample-rate = unbekannte Abtastrate
+error-psd-unsupported = nur Photoshop-Dateien mit 8 Bit RGB oder Graustufen können gelesen werden
+error-psd-no-layers = die Datei hat keine sichtbaren Ebenen
+error-not-lottie = keine Lottie-Animation
+error-lottie-no-layers = die Animation hat keine Ebenen
+expression-unexpected = unerwartetes „{ $token }“
+expression-expected = „{ $symbol }“ erwartet
+expression-end = unerwartetes Ende
+expression-unknown-name = unbekannter Name „{ $name }“
+expression-arguments =
+    { $count ->
+        [one] { $name } nimmt { $count } Argument
+       *[other] { $name } nimmt { $count } Argumente
+    }
+
+template-blank = Leer
+template-music-visualizer = Musikvisualisierung
+template-podcast-audiogram = Podcast-Audiogramm
+template-lyric-video = Lyrics-Video
+template-vertical-reel = Hochformat-Reel
+template-blank-description = Leere Leinwand mit 1280×720
+template-music-visualizer-description = 1920×1080 mit Hintergrund und mittigem Albumcover
+template-podcast-audiogram-description = Quadrat mit 1080×1080, Cover und Sendungslogo
+template-lyric-video-description = 1920×1080 mit bildfüllendem Hintergrund
+template-vertical-reel-description = 1080×1920 für Storys und Reels, mit mittigem Bild
+template-layer-background = Hintergrund
+template-layer-album-art = Albumcover
+template-layer-cover-art = Cover
+template-layer-show-logo = Sendungslogo
+template-layer-artwork = Bild
+template-layer-title = Titel
+
+marker-chapter = Kapitel { $number }
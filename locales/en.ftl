@@ -0,0 +1,552 @@
+# UI strings in Fluent, with `{ $name }` for arguments and selectors on
+# `$count` for plural forms.
+
+project-new = New project
+project-open = Open project
+project-open-dialog = Open project...
+project-save = Save project
+output = output:
+untitled = Untitled
+frame-rate = frame rate:
+language = language:
+stats = Stats
+stats-tooltip = Show performance stats over the canvas (F3)
+copy-diagnostics = Copy diagnostics
+copy-diagnostics-tooltip = Copy the app version and the recent log, to attach to a bug report
+preview-detach = Pop out preview
+preview-attach = Close preview window
+preview-detach-tooltip = Show the canvas in a window of its own, e.g. on a second display. F11 makes it fullscreen.
+preview-window-title = roygbiv preview
+status-zoom = zoom { $percent }%
+status-analyzing = analyzing audio…
+status-decoding = images decoding: { $count }
+empty-title = Start with some audio or a picture
+empty-open-audio = Open audio
+empty-add-layer = Add layer
+empty-open-project = Open project
+empty-drop-hint = or drop audio, image or project files anywhere in the window
+cancel = Cancel
+
+play = Play
+pause = Pause
+mute = Mute
+unmute = Unmute
+record-keyframes = Record keyframes for every change
+stop-recording = Stop recording keyframes
+previous-frame = Previous frame (left arrow)
+next-frame = Next frame (right arrow)
+time-format-tooltip = Switch between seconds, frames and timecode
+preview-speed = Preview speed
+preserve-pitch = Keep pitch
+
+audio-select = Select audio file
+audio-remove = Remove audio file
+audio-key = key: { $key }
+audio-peak = peak { $level } dB
+audio-range = track: { $start } – { $end }
+trim-silence = Trim silence
+trim-silence-tooltip = Cut off the silence at the start and end of the file
+trim-start-here = Start here
+trim-end-here = End here
+trim-whole-file = Whole file
+track-remove = Remove
+track-gain = gain: { $gain } dB
+track-starts-at = starts at: { $time }
+track-start-at-playhead = Start at playhead
+track-add = Add audio track
+output-default = System default
+
+layers = Layers
+layer-settings = Layer settings
+layer-add = Add new layer
+layer-delete = Delete layer
+layer-filter = Filter layers
+layer-category-all = All layers
+layer-category-images = Images
+layer-category-text = Text
+layer-category-overlays = Overlays
+overlay-add = Add overlay...
+
+assets = Assets
+assets-empty = No imported media
+asset-image = Image
+asset-audio = Audio
+asset-unused = unused
+asset-one-use = 1 use
+asset-uses =
+    { $count ->
+        [one] { $count } use
+       *[other] { $count } uses
+    }
+asset-replace = Replace file
+asset-remove = Remove from project
+memory = Memory
+memory-of-budget = { $used } of { $budget }
+memory-breakdown = files { $files } · previews { $previews } · rendered { $renders } · audio analysis { $analysis }
+memory-budget-tooltip = Past this, rendered images are dropped and previews downscaled
+swatches = Swatches
+swatches-empty = No swatches yet. Add one, save a layer's color, or pick colors out of an image in Assets.
+swatches-pick = Project swatches
+swatch-remove = Remove swatch
+swatch-add = Add a swatch
+swatch-save = Save as a swatch
+swatch-name = Color { $number }
+swatch-name-placeholder = Name
+seed-randomize = Try a random seed
+safe-area-tooltip = Shows where a platform covers the video with its own buttons and captions. Only shown in the editor.
+post-fx = Post FX
+post-fx-tooltip = Effects over the finished frame: letterbox bars, a kaleidoscope of every layer together, bloom, chromatic aberration, sharpening and a color grade
+post-fx-rendered = The canvas is rendered in software while any effect but the letterbox is on, which is slower
+orientation-fit = Fit layers
+orientation-fit-tooltip = Moves and resizes every layer along with the canvas when it is turned. Otherwise only layers sized in percent follow it.
+audiogram-wizard = Audiogram wizard…
+audiogram-description = Makes a project for a podcast episode, with its cover, title, a waveform and a progress bar.
+audiogram-audio = Episode
+audiogram-cover = Cover art
+audiogram-title = Title
+audiogram-title-placeholder = Episode title
+audiogram-format = Format
+audiogram-none = None chosen
+audiogram-choose = Choose…
+audiogram-create = Create project
+markers = Markers
+marker-add = Add at playhead
+marker-name = Marker { $number }
+marker-name-placeholder = Name
+marker-remove = Remove the marker
+markers-import = Import…
+markers-import-tooltip = Reads chapters from a list of times and names, one per line, or from the chapters of an MP3, M4A or M4B file.
+markers-from-audio = From audio
+markers-imported =
+    { $count ->
+        [one] Imported { $count } marker
+       *[other] Imported { $count } markers
+    }
+markers-none-found = No new chapters found
+scenes = Scenes
+scenes-tooltip = Each scene has its own layers and lasts until the next one starts, replacing the one before it with its transition. Layers in no scene are shown in all of them.
+scene-add = Add at playhead
+scenes-from-markers = From markers
+scene-name = Scene { $number }
+scene-name-placeholder = Name
+scene-remove = Remove the scene
+thumbnail-render = Render thumbnail
+thumbnail-render-tooltip = Saves the frame at the playhead as an image, rendered at the size picked. Layers can be set to only show, or to not show, in thumbnails
+thumbnail-saved = Saved the thumbnail to { $path }
+snapshots = Snapshots
+snapshots-empty = No snapshots yet. Take one to keep the project as it is now, and come back to it later. Snapshots are saved with the project.
+snapshot-name-placeholder = Snapshot name
+snapshot-default-name = Snapshot { $count }
+snapshot-take = Take snapshot
+snapshot-restore = Restore
+snapshot-remove = Delete snapshot
+snapshot-before-restore = Before restoring { $name }
+snapshot-restored = Restored { $name }
+snapshot-just-now = Just now
+snapshot-minutes-ago = { $count } min ago
+snapshot-hours-ago = { $count } h ago
+snapshot-days-ago = { $count } d ago
+compare = Compare
+compare-pin = Pin as A
+compare-pin-tooltip = Keeps the canvas as it is now as "A", so later edits, "B", can be compared with it side by side or by switching between them
+compare-repin = Pin B as A
+compare-repin-tooltip = Compares later edits with the canvas as it is now instead
+compare-stop = Stop comparing
+audio-loading = Reading the audio file…
+audio-analyzing = Analyzing…
+layer-arrangement-tooltip = How several images added or dropped at once are laid out
+asset-animation = Animation
+lottie-export = Export Lottie
+lottie-export-tooltip = Saves the layers as a Lottie animation to reuse on the web and in apps. Images are embedded, and layers drawn from the audio are left out
+lottie-exported = Exported the animation to { $path }
+lottie-left-out = Left out { $layers }, which follow the audio or the clock
+palette-extract = Extract a palette
+palette-extracted =
+    { $count ->
+        [one] Added { $count } color to the swatches
+       *[other] Added { $count } colors to the swatches
+    }
+palette-failed = Could not read the colors of the image
+
+relink-title = Missing files
+relink-description = Some files used by this project could not be found. Locate them, or open the project without them.
+relink-browse = Browse...
+relink-skip = Skip missing files
+
+command-placeholder = Type a command...
+
+section-transform = Transform
+section-appearance = Appearance
+section-effects = Effects
+section-audio-reactivity = Audio reactivity
+section-timing = Timing
+error-dialog-closed = the dialog was closed
+error-project-format = not a valid project ({ $error })
+error-clipboard = clipboard error ({ $error })
+error-audio-decode = could not decode the audio ({ $error })
+error-image-decode = could not read the image ({ $error })
+error-cancelled = cancelled
+error-file-too-large = the file is { $size }, more than the { $limit } that can be opened
+error-clipboard-image = invalid image data
+dialog-import-font = Import font...
+filter-font = Font file
+dialog-open-audio = Open audio file...
+filter-audio = Audio or video file
+dialog-add-images = Add images...
+dialog-add-lottie = Add Lottie animation...
+filter-lottie = Lottie animation
+dialog-load-lut = Load LUT...
+filter-lut = 3D LUT
+dialog-open-image = Open image file...
+dialog-import-chapters = Import chapters...
+filter-image = Image file
+filter-chapters = Chapter list or audio file
+error-open-audio = Could not open the audio file
+error-open-image = Could not open the image
+error-open-images = Could not open the images
+error-open-an-image = Could not open an image
+error-open-animation = Could not open the animation
+error-import-psd = Could not import the Photoshop file
+error-save-preferences = Could not save preferences
+error-save-thumbnail = Could not save the thumbnail
+error-export-animation = Could not export the animation
+diagnostics-copied = Copied diagnostics to the clipboard
+error-import-chapters = Could not import the chapters
+error-open-project = Could not open the project
+error-save-project = Could not save the project
+error-load-project-files = Could not load the project's files
+error-restore-snapshot = Could not restore the snapshot
+error-replace-file = Could not replace the file
+error-import-font = Could not import the font
+error-load-font = Could not load a font
+error-open-lut = Could not open the LUT
+camera-none-found = No camera was found
+memory-downscaled = Image previews were downscaled to stay within the memory budget
+error-analyze-audio = Could not analyze the audio: { $error }
+error-read-file = Could not read { $path } ({ $error })
+error-capture-screen = Could not capture the screen: { $error }
+error-find-camera = Could not find a camera: { $error }
+error-capture = Could not capture { $source }: { $error }
+error-decode = Could not decode { $name }
+project-saved = Saved { $path }
+error-not-a-font = { $path } is not a font file
+error-unsupported-image = Cannot open { $path }: { $format } images aren't supported yet, export it as a JPEG or PNG first
+error-unsupported-file = Cannot open { $path }: unsupported file type
+error-read-image = Could not read { $name } as an image
+layer-name-backdrop = Backdrop blur
+layer-name-screen-capture = Screen capture
+layer-name-image-backdrop = { $name } backdrop
+
+command-add-image = Add image layer
+command-paste-image = Paste image as a layer
+command-import-psd = Import Photoshop layers
+command-add-lottie = Add Lottie animation
+command-add-screen-capture = Add screen capture
+command-add-camera = Add camera
+command-add-backdrop = Add backdrop blur layer
+command-previous-frame = Previous frame
+command-next-frame = Next frame
+command-time-format = Switch time format
+command-take-snapshot = Take a snapshot
+command-audiogram = Make an audiogram
+command-render-thumbnail = Render a thumbnail
+command-export-lottie = Export Lottie animation
+command-pin-comparison = Pin the canvas as A to compare
+command-switch-comparison = Switch between A and B
+command-toggle-stats = Toggle performance stats
+command-toggle-post-fx = Toggle post FX
+command-focus-next-panel = Focus next panel
+command-delete-layer = Delete selected layer
+command-fit-layer = Fit selected layer to canvas
+command-copy-style = Copy layer style
+command-add-blurred-backdrop = Add blurred backdrop from selected image
+command-paste-style = Paste layer style
+command-show-layers = Show layers
+command-show-assets = Show assets
+command-show-swatches = Show swatches
+command-show-snapshots = Show snapshots
+command-landscape = Make the canvas landscape
+command-portrait = Make the canvas portrait
+command-square = Make the canvas square
+command-record-keyframes = Record keyframes
+command-add-overlay = Add { $overlay } overlay
+command-preview-speed = Preview speed: { $speed }
+command-frame-rate = Frame rate: { $rate }
+
+transform-reset = Reset transform
+width-label = width:
+height-label = height:
+width = width
+height = height
+aspect-unlock = Unlock aspect ratio
+aspect-lock = Lock aspect ratio
+flip-horizontal = Flip horizontally
+flip-vertical = Flip vertically
+sizing-label = sizing:
+image-replace = Replace image...
+image-choose = Choose image...
+fit-to-canvas = Fit to canvas
+crop-done = Done cropping
+crop = Crop
+crop-reset = Reset crop
+corner-radius = corner radius: { $radius }
+border-width = border: { $width }
+border-follows-pitch = Hue follows pitch
+tint-label = tint:
+kaleidoscope-image = Kaleidoscope (this image only)
+animation-label = animation:
+reacts-to = reacts to:
+motion-copy = Copy motion
+paste = Paste
+paste-reversed = Paste reversed
+timing-shown = shown: { $start } – { $end }
+timing-end = end
+timing-always = Always
+timing-marker = Show during a marker…
+timing-scene = In every scene
+timing-every-scene = Every scene
+timing-offset = time offset: { $offset }s
+transition-label = transition:
+duration-seconds = duration: { $value }s
+playback-speed = playback speed: { $speed }×
+playback-ping-pong = Ping-pong
+style-copy = Copy style
+style-copy-tooltip = Copy the border, corners, tint, opacity and audio reaction
+style-paste = Paste style
+layer-none-selected = No layer selected
+
+property-linked = linked to { $layer }
+property-unlink = Unlink
+property-reset = Reset to default
+property-stop-animating = Stop animating
+property-animate = Animate with keyframes
+graph-show = Show in graph editor
+keyframes-copy = Copy keyframes
+keyframes-paste = Paste keyframes at playhead
+keyframes-paste-reversed = Paste keyframes reversed
+property-link = Link
+property-link-tooltip = Follow this property of another layer
+graph-title = { $property } keyframes
+graph-hint = drag keyframes and easing handles, right-click to delete
+close = Close
+chroma-key = Chroma key
+chroma-tolerance = tolerance: { $value }
+chroma-spill = spill suppression: { $value }
+chroma-feather = edge feather: { $value }
+capture-label = capture:
+capture-refresh = Find windows and cameras again
+resolution-label = resolution:
+size-value = size: { $value }
+softness-value = softness: { $value }
+amount-value = amount: { $value }
+speed-per-second = speed: { $value }/s
+speed-value = speed: { $value }
+density-value = density: { $value }
+depth-value = depth: { $value }
+twinkle-value = twinkle: { $value }
+reactivity-value = audio reactivity: { $value }×
+decimals-value = decimals: { $value }
+span-value = span: { $value } s
+bar-width-value = bar width: { $value }
+readout-show-label = Show label
+wave-mirrored = Mirror from the middle
+progress-played = played:
+progress-rest = rest:
+progress-rounded = Rounded ends
+timer-target = counts down to: { $time }
+timer-target-here = Count down to here
+text-placeholder = Text
+text-markup-hint = Wrap words in *asterisks* for italics, or **two** for bold.
+letter-spacing-value = letter spacing: { $value }
+line-height-value = line height: { $value }×
+outline-value = outline: { $value }
+text-background = Background box
+padding-value = padding: { $value }
+delay-value = delay: { $value }s
+stagger-value = stagger: { $value }s
+font-default = Default
+font-imported = { $family } (project)
+font-label = font:
+font-search = Search fonts
+font-import = Import...
+font-italic = Italic
+
+bloom-value = bloom: { $value }
+threshold-value = threshold: { $value }
+glow-value = glow: { $value }px
+aberration-value = aberration: { $value }px
+sharpen-value = sharpen: { $value }
+kaleidoscope = Kaleidoscope
+window-title = { $project }{ $dirty } — roygbiv
+stats-fps = { $fps } fps
+stats-frame = frame: { $update } ms update, { $draw } ms draw
+stats-draw-calls = draw calls: { $count }
+stats-layers = layers: { $drawn } drawn, { $cached } from cache
+stats-renders = image renders: { $percent }% cached
+stats-renders-none = image renders: none yet
+stats-analysis = audio analysis: { $time } ms
+stats-analysis-none = audio analysis: none yet
+stats-memory = memory: { $size }
+distortion-label = distortion:
+amount-px = amount: { $value }px
+scale-px = scale: { $value }px
+distortion-follows-bass = Amount follows bass
+lut-load = Load LUT...
+lut-name = LUT: { $name }
+lut-name-amount = LUT: { $name } ({ $amount })
+replace = Replace
+remove = Remove
+backdrop-beneath = beneath the layer:
+backdrop-image = this image:
+blur-px = blur: { $value }px
+darken-value = darken: { $value }
+backdrop-add = Add blurred backdrop
+segments-value = segments: { $value }
+rotation-value = rotation: { $value }°
+center-value = center: { $x }, { $y }
+glitch = Glitch
+glitch-split = RGB split: { $value }px
+glitch-blocks = block shuffle: { $value }
+glitch-scanlines = scanlines: { $value }
+glitch-intensity = intensity:
+seed-label = seed:
+
+align-left = Left
+align-center = Center
+align-right = Right
+text-animation-none = No text animation
+text-animation-typewriter = Typewriter
+text-animation-fade = Fade in
+text-animation-bounce = Bounce on beat
+text-unit-letter = By letter
+text-unit-word = By word
+source-main = Main track
+source-track = Track { $number }
+channel-mid = Both channels
+channel-left = Left channel
+channel-right = Right channel
+channel-side = Stereo difference
+thumbnail-canvas-size = Canvas size
+thumbnail-save-dialog = Save thumbnail...
+project-save-dialog = Save project...
+filter-project = roygbiv project
+relink-dialog = Locate missing file...
+lottie-export-dialog = Export Lottie animation...
+psd-import-dialog = Import Photoshop layers...
+filter-psd = Photoshop file
+visibility-everywhere = Shown everywhere
+visibility-output = Only in the output
+visibility-editor = Only in the editor
+visibility-thumbnail = Only in thumbnails
+visibility-not-thumbnail = Everywhere but thumbnails
+arrangement-grid = In a grid
+arrangement-stack = Stacked
+safe-area-none = No guides
+safe-area-broadcast = Title safe
+safe-area-youtube-thumbnail = YouTube thumbnail
+none = None
+tint-multiply = Tint
+tint-colorize = Colorize
+tint-duotone = Duotone
+sizing-fit = Fit
+sizing-fill = Fill
+sizing-stretch = Stretch
+sizing-tile = Tile
+capture-region = Part of the screen
+weight-thin = Thin
+weight-extra-light = Extra light
+weight-light = Light
+weight-regular = Regular
+weight-medium = Medium
+weight-semibold = Semibold
+weight-bold = Bold
+weight-extra-bold = Extra bold
+weight-black = Black
+procedural-vignette = Vignette
+procedural-grain = Film grain
+procedural-starfield = Starfield
+procedural-readout = Readout
+procedural-text = Text
+procedural-timer = Timer
+procedural-wave = Waveform
+procedural-progress = Progress bar
+reading-level = Level (dB)
+reading-note = Note (Hz)
+reading-elapsed = Elapsed time
+reading-tempo = Tempo (BPM)
+reading-chapter = Chapter
+counting-elapsed = Time elapsed
+counting-remaining = Time remaining
+counting-countdown = Countdown
+timer-seconds = Seconds
+wave-bars = Bars
+wave-line = Line
+distortion-wave = Wave
+distortion-ripple = Ripple
+distortion-displace = Displace by texture
+preset-bounce = Bounce on beat
+preset-breathe = Breathe with bass
+preset-drift = Slow drift
+preset-spin = Spin
+transition-crossfade = Crossfade
+transition-slide = Slide
+transition-zoom = Zoom
+easing-linear = Linear
+easing-in = Ease in
+easing-out = Ease out
+easing-in-out = Ease in and out
+property-scale = scale
+property-opacity = opacity
+key-major = { $tonic } major
+key-minor = { $tonic } minor
+glitch-bass = With the bass
+glitch-loudness = With the loudness
+glitch-beat = On every beat
+letterbox-none = No letterbox
+
+error-capture-x11 = screen capture needs an X11 session, or XWayland for X11 programs
+error-capture-disconnected = not connected to the X server
+error-capture-camera = { $camera } isn't on the screen
+error-capture-window = there is no window called { $window }
+error-capture-hidden = the region is off the screen, or the window is hidden
+error-capture-depth = screens of depth { $depth } can't be captured
+error-capture-unsupported = screen capture is only available on Linux for now
+error-camera-no-frames = the camera sent no frames
+error-camera-format = the camera sends frames in an unknown format
+error-camera-buffer = the camera handed back a buffer it wasn't given
+error-camera-size = the camera sent a frame of the wrong size
+error-camera-unsupported = cameras are only available on Linux for now
+error-no-audio-track = no audio track
+error-unknown-sample-rate = unknown sample rate
+error-psd-unsupported = only 8-bit RGB and grayscale Photoshop files can be read
+error-psd-no-layers = the file has no visible layers
+error-not-lottie = not a Lottie animation
+error-lottie-no-layers = the animation has no layers
+expression-unexpected = unexpected "{ $token }"
+expression-expected = expected "{ $symbol }"
+expression-end = unexpected end
+expression-unknown-name = unknown name "{ $name }"
+expression-arguments =
+    { $count ->
+        [one] { $name } takes { $count } argument
+       *[other] { $name } takes { $count } arguments
+    }
+
+template-blank = Blank
+template-music-visualizer = Music visualizer
+template-podcast-audiogram = Podcast audiogram
+template-lyric-video = Lyric video
+template-vertical-reel = Vertical reel
+template-blank-description = Empty 1280×720 canvas
+template-music-visualizer-description = 1920×1080 with a background and centered album art
+template-podcast-audiogram-description = 1080×1080 square with cover art and a show logo
+template-lyric-video-description = 1920×1080 with a full-frame background
+template-vertical-reel-description = 1080×1920 for stories and reels, with centered artwork
+template-layer-background = Background
+template-layer-album-art = Album art
+template-layer-cover-art = Cover art
+template-layer-show-logo = Show logo
+template-layer-artwork = Artwork
+template-layer-title = Title
+
+marker-chapter = Chapter { $number }
@@ -0,0 +1,414 @@
+use std::{f32::consts::TAU, fmt::Display, time::Duration};
+
+use iced::{widget::canvas, Rectangle, Vector};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::{ImageFrame, Playhead},
+    i18n::t,
+};
+
+/// A ready-made motion applied on top of a layer's placement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Animation {
+    pub preset: Preset,
+    /// How pronounced the motion is, 1 being the preset's usual strength.
+    pub amount: f32,
+}
+
+impl Animation {
+    pub const NONE: Animation = Animation {
+        preset: Preset::None,
+        amount: 1.,
+    };
+
+    /// How far the layer is moved from its resting placement at the playhead.
+    pub fn motion(&self, playhead: &Playhead) -> Motion {
+        let time = playhead.time.as_secs_f32();
+        let amount = self.amount;
+
+        match self.preset {
+            Preset::None => Motion::NONE,
+            Preset::Bounce => {
+                let pulse = playhead
+                    .since_beat()
+                    .map_or(0., |since| (-since * 8.).exp());
+
+                Motion {
+                    scale: 1. + 0.15 * amount * pulse,
+                    ..Motion::NONE
+                }
+            }
+            Preset::Breathe => Motion {
+                scale: 1. + 0.1 * amount * playhead.bass(),
+                ..Motion::NONE
+            },
+            Preset::Drift => Motion {
+                offset: Vector::new((time * 0.31).sin(), (time * 0.23).cos() - 1.) * 20. * amount,
+                ..Motion::NONE
+            },
+            Preset::Spin => Motion {
+                rotation: time * TAU * 0.1 * amount,
+                ..Motion::NONE
+            },
+        }
+    }
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation::NONE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Preset {
+    #[default]
+    None,
+    /// Pops the layer out on every beat.
+    Bounce,
+    /// Grows and shrinks the layer with the bass.
+    Breathe,
+    /// Slowly wanders around the layer's position.
+    Drift,
+    /// Rotates the layer continuously.
+    Spin,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Preset::None,
+        Preset::Bounce,
+        Preset::Breathe,
+        Preset::Drift,
+        Preset::Spin,
+    ];
+
+    pub fn is_audio_reactive(self) -> bool {
+        matches!(self, Preset::Bounce | Preset::Breathe)
+    }
+}
+
+impl Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Preset::None => t("none"),
+            Preset::Bounce => t("preset-bounce"),
+            Preset::Breathe => t("preset-breathe"),
+            Preset::Drift => t("preset-drift"),
+            Preset::Spin => t("preset-spin"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    pub offset: Vector,
+    /// Scale around the center of the layer.
+    pub scale: f32,
+    /// Rotation around the center of the layer, in radians.
+    pub rotation: f32,
+    /// Multiplies the layer's opacity.
+    pub opacity: f32,
+}
+
+impl Motion {
+    pub const NONE: Motion = Motion {
+        offset: Vector::ZERO,
+        scale: 1.,
+        rotation: 0.,
+        opacity: 1.,
+    };
+
+    /// Both motions applied on top of each other.
+    pub fn and(self, other: Motion) -> Motion {
+        Motion {
+            offset: self.offset + other.offset,
+            scale: self.scale * other.scale,
+            rotation: self.rotation + other.rotation,
+            opacity: self.opacity * other.opacity,
+        }
+    }
+
+    /// Transforms `frame` so that whatever is drawn within `bounds` moves
+    /// along with the layer.
+    pub fn apply(&self, frame: &mut canvas::Frame, bounds: Rectangle) {
+        let still = Motion {
+            opacity: 1.,
+            ..*self
+        };
+        if still == Motion::NONE {
+            return;
+        }
+
+        let center = Vector::new(bounds.center_x(), bounds.center_y());
+        frame.translate(center + self.offset);
+        frame.rotate(self.rotation);
+        frame.scale(self.scale);
+        frame.translate(-center);
+    }
+}
+
+/// When a layer is shown during the track.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timing {
+    /// Seconds into the track at which the layer appears.
+    pub start: f32,
+    /// Seconds into the track at which the layer disappears, if it does.
+    pub end: Option<f32>,
+    /// Seconds added to the playhead when animating the layer, to shift its
+    /// motion relative to other layers.
+    pub offset: f32,
+    /// How the layer enters at `start` and leaves at `end`.
+    pub transition: Transition,
+    /// The scene the layer is shown in, by its index, or `None` for every
+    /// scene.
+    pub scene: Option<usize>,
+}
+
+impl Timing {
+    pub const ALWAYS: Timing = Timing {
+        start: 0.,
+        end: None,
+        offset: 0.,
+        transition: Transition::NONE,
+        scene: None,
+    };
+
+    pub fn is_visible(&self, time: Duration) -> bool {
+        let time = time.as_secs_f32();
+
+        time >= self.start && self.end.is_none_or(|end| time < end)
+    }
+
+    /// The layer's transition in or out at `time`, if it is in the middle of
+    /// one.
+    pub fn motion(&self, time: Duration, bounds: Rectangle) -> Motion {
+        let Transition {
+            kind,
+            duration,
+            easing,
+        } = self.transition;
+        if kind == TransitionKind::None || duration <= 0. {
+            return Motion::NONE;
+        }
+
+        let time = time.as_secs_f32();
+        let entering = (time - self.start) / duration;
+        let leaving = self
+            .end
+            .map_or(f32::INFINITY, |end| (end - time) / duration);
+        // Slides continue in the same direction when leaving.
+        let (progress, direction) = if entering <= leaving {
+            (entering, -1.)
+        } else {
+            (leaving, 1.)
+        };
+        if progress >= 1. {
+            return Motion::NONE;
+        }
+        let shown = easing.apply(progress.clamp(0., 1.));
+
+        match kind {
+            TransitionKind::None => Motion::NONE,
+            TransitionKind::Crossfade => Motion {
+                opacity: shown,
+                ..Motion::NONE
+            },
+            TransitionKind::Slide => Motion {
+                offset: Vector::new(direction * (1. - shown) * bounds.width, 0.),
+                ..Motion::NONE
+            },
+            TransitionKind::Zoom => Motion {
+                scale: shown,
+                opacity: shown,
+                ..Motion::NONE
+            },
+            TransitionKind::Glitch => {
+                // Jumps to a new random state 30 times a second.
+                let step = (time * 30.).floor();
+                let jitter = noise(step) * 2. - 1.;
+
+                Motion {
+                    offset: Vector::new(jitter * (1. - shown) * bounds.width * 0.1, 0.),
+                    opacity: if noise(step + 0.5) < shown { 1. } else { 0. },
+                    ..Motion::NONE
+                }
+            }
+        }
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing::ALWAYS
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    /// Length of the transition in seconds.
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl Transition {
+    pub const NONE: Transition = Transition {
+        kind: TransitionKind::None,
+        duration: 0.5,
+        easing: Easing::EaseInOut,
+    };
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Transition::NONE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TransitionKind {
+    #[default]
+    None,
+    Crossfade,
+    Slide,
+    Zoom,
+    Glitch,
+}
+
+impl TransitionKind {
+    pub const ALL: [TransitionKind; 5] = [
+        TransitionKind::None,
+        TransitionKind::Crossfade,
+        TransitionKind::Slide,
+        TransitionKind::Zoom,
+        TransitionKind::Glitch,
+    ];
+}
+
+impl Display for TransitionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TransitionKind::None => t("none"),
+            TransitionKind::Crossfade => t("transition-crossfade"),
+            TransitionKind::Slide => t("transition-slide"),
+            TransitionKind::Zoom => t("transition-zoom"),
+            TransitionKind::Glitch => t("glitch"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    /// Maps linear progress between 0 and 1 onto the curve.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1. - (1. - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(3) / 2.
+                }
+            }
+        }
+    }
+}
+
+impl Display for Easing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Easing::Linear => t("easing-linear"),
+            Easing::EaseIn => t("easing-in"),
+            Easing::EaseOut => t("easing-out"),
+            Easing::EaseInOut => t("easing-in-out"),
+        })
+    }
+}
+
+/// How an animated image layer plays back, on its own clock rather than in
+/// sync with the audio.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Playback {
+    /// Multiplier of the image's own frame rate.
+    pub speed: f32,
+    /// Plays the frames backwards after reaching the last one instead of
+    /// starting over.
+    pub ping_pong: bool,
+}
+
+impl Playback {
+    pub const NORMAL: Playback = Playback {
+        speed: 1.,
+        ping_pong: false,
+    };
+
+    /// The frame of an animated image shown at `time`.
+    pub fn frame<'a>(&self, frames: &'a [ImageFrame], time: Duration) -> Option<&'a ImageFrame> {
+        let length: Duration = frames.iter().map(|frame| frame.delay).sum();
+        if frames.len() < 2 || length.is_zero() {
+            return frames.first();
+        }
+
+        let time = self.position(length.as_secs_f32(), time.as_secs_f32());
+        let mut shown_until = 0.;
+        frames
+            .iter()
+            .find(|frame| {
+                shown_until += frame.delay.as_secs_f32();
+                time < shown_until
+            })
+            .or(frames.last())
+    }
+}
+
+impl Playback {
+    /// Seconds into an animation `length` seconds long that is shown
+    /// `time` seconds after it started.
+    pub fn position(&self, length: f32, time: f32) -> f32 {
+        if length <= 0. {
+            return 0.;
+        }
+        let cycle = if self.ping_pong { length * 2. } else { length };
+        let time = (time * self.speed).rem_euclid(cycle);
+
+        if time >= length {
+            cycle - time
+        } else {
+            time
+        }
+    }
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback::NORMAL
+    }
+}
+
+/// Cheap repeatable pseudo-random number between 0 and 1.
+fn noise(seed: f32) -> f32 {
+    ((seed * 12.9898).sin() * 43758.547).rem_euclid(1.)
+}
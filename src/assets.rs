@@ -0,0 +1,256 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetId(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Image,
+    Audio,
+    /// A Lottie animation.
+    Animation,
+}
+
+/// The bytes of an imported file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Contents {
+    /// Kept in memory, for images which are decoded again whenever a layer
+    /// showing them changes.
+    Loaded(Arc<Vec<u8>>),
+    /// Left on disk and read from the asset's path when needed, for audio
+    /// which is only ever streamed through the decoder and can be far larger
+    /// than any image.
+    Streamed { size: u64, hash: u64 },
+}
+
+impl Contents {
+    /// Reads `path` in chunks to describe it without keeping it in memory.
+    /// Fails once `progress` is cancelled.
+    pub fn stream(path: &Path, progress: &LoadProgress) -> io::Result<Contents> {
+        let mut file = File::open(path)?;
+        progress.set_total(file.metadata()?.len());
+        let mut chunk = vec![0; 64 * 1024];
+        let mut size = 0;
+        let mut hash = FNV_OFFSET;
+
+        loop {
+            if progress.is_cancelled() {
+                return Err(io::Error::other("cancelled"));
+            }
+            let read = file.read(&mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            size += read as u64;
+            hash = fnv(hash, &chunk[..read]);
+            progress.set_read(size);
+        }
+
+        Ok(Contents::Streamed { size, hash })
+    }
+
+    pub fn hash(&self) -> u64 {
+        match self {
+            Contents::Loaded(bytes) => content_hash(bytes),
+            Contents::Streamed { hash, .. } => *hash,
+        }
+    }
+}
+
+/// How much of a file has been worked through, shared between the task
+/// reading it and the view showing it, which can also call the task off.
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    read: AtomicU64,
+    total: AtomicU64,
+    is_cancelled: AtomicBool,
+}
+
+impl LoadProgress {
+    /// From 0 to 1, or 0 until the size of the file is known.
+    pub fn fraction(&self) -> f32 {
+        match self.total.load(Ordering::Relaxed) {
+            0 => 0.,
+            total => (self.read.load(Ordering::Relaxed) as f64 / total as f64).min(1.) as f32,
+        }
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn set_read(&self, read: u64) {
+        self.read.store(read, Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+pub struct Asset {
+    pub id: AssetId,
+    pub kind: AssetKind,
+    pub path: PathBuf,
+    pub contents: Contents,
+    pub hash: u64,
+    /// Downscaled copy of a large image, drawn on the canvas in place of the
+    /// full resolution file.
+    pub preview: Option<Arc<image::RgbaImage>>,
+}
+
+impl Asset {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or(self.path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn size(&self) -> usize {
+        match &self.contents {
+            Contents::Loaded(bytes) => bytes.len(),
+            Contents::Streamed { size, .. } => *size as usize,
+        }
+    }
+
+    /// The bytes of the file, if they are kept in memory.
+    pub fn bytes(&self) -> Option<&Arc<Vec<u8>>> {
+        match &self.contents {
+            Contents::Loaded(bytes) => Some(bytes),
+            Contents::Streamed { .. } => None,
+        }
+    }
+}
+
+/// Every media file imported into the project. Files with identical contents
+/// are stored once, no matter how many times or from where they were imported.
+#[derive(Debug, Default)]
+pub struct Assets {
+    assets: Vec<Asset>,
+    next_id: u64,
+}
+
+impl Assets {
+    /// Adds a file to the project, returning the id of an existing asset
+    /// instead if the same bytes have already been imported.
+    pub fn import(&mut self, kind: AssetKind, path: PathBuf, contents: Contents) -> AssetId {
+        let hash = contents.hash();
+
+        if let Some(existing) = self
+            .assets
+            .iter()
+            .find(|asset| asset.kind == kind && asset.hash == hash && asset.contents == contents)
+        {
+            return existing.id;
+        }
+
+        let id = AssetId(self.next_id);
+        self.next_id += 1;
+
+        self.assets.push(Asset {
+            id,
+            kind,
+            path,
+            contents,
+            hash,
+            preview: None,
+        });
+
+        id
+    }
+
+    pub fn get(&self, id: AssetId) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Asset> {
+        self.assets.iter()
+    }
+
+    pub fn replace(&mut self, id: AssetId, path: PathBuf, contents: Contents) {
+        if let Some(asset) = self.assets.iter_mut().find(|asset| asset.id == id) {
+            asset.hash = contents.hash();
+            asset.path = path;
+            asset.contents = contents;
+            asset.preview = None;
+        }
+    }
+
+    pub fn set_preview(&mut self, id: AssetId, preview: Option<Arc<image::RgbaImage>>) {
+        if let Some(asset) = self.assets.iter_mut().find(|asset| asset.id == id) {
+            asset.preview = preview;
+        }
+    }
+
+    pub fn remove(&mut self, id: AssetId) {
+        self.assets.retain(|asset| asset.id != id);
+    }
+
+    /// Bytes of the files kept in memory.
+    pub fn file_memory(&self) -> usize {
+        self.assets
+            .iter()
+            .filter_map(Asset::bytes)
+            .map(|bytes| bytes.len())
+            .sum()
+    }
+
+    /// Pixel bytes of every preview proxy.
+    pub fn preview_memory(&self) -> usize {
+        self.assets
+            .iter()
+            .filter_map(|asset| asset.preview.as_ref())
+            .map(|preview| preview.as_raw().len())
+            .sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.assets.clear();
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+/// 64-bit FNV-1a. Stable across runs and platforms, unlike `DefaultHasher`.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    fnv(FNV_OFFSET, bytes)
+}
+
+/// Continues an FNV-1a hash with more bytes.
+fn fnv(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn format_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,1011 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error as DecodeError,
+    formats::{FormatOptions, FormatReader},
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::LoadProgress,
+    chroma::{Chroma, ChromaRecorder, Key, Pitch},
+    i18n::{t, t_args},
+    Error,
+};
+
+/// Loudness of a track over time, used to drive audio-reactive layers.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Analysis {
+    pub duration: Duration,
+    /// Loudest sample of each slice, relative to the loudest sample of the
+    /// track.
+    peaks: Vec<f32>,
+    /// What audio-reactive layers follow, for each of [`Channel::ALL`].
+    signals: Vec<Signal>,
+    /// Loudness of each channel of the track, as sample amplitudes rather
+    /// than relative to the loudest slice.
+    meters: Vec<Meter>,
+    /// Sorted by time.
+    silences: Vec<Silence>,
+    /// Pitches heard in every `1 / ChromaRecorder::FRAMES_PER_SECOND` of the
+    /// track.
+    pitches: Vec<Pitch>,
+    key: Option<Key>,
+}
+
+/// A stretch of the track where every channel stays below
+/// [`Analysis::SILENCE_THRESHOLD`], in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Silence {
+    pub start: f32,
+    pub end: f32,
+}
+
+/// The part of the audio file used as the project's track, for skipping a
+/// long intro or cutting the track short.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Trim {
+    /// Seconds into the file at which the track starts.
+    pub start: f32,
+    /// Seconds into the file at which the track ends, if before the end.
+    pub end: Option<f32>,
+}
+
+impl Trim {
+    pub const NONE: Trim = Trim {
+        start: 0.,
+        end: None,
+    };
+
+    pub fn start(&self) -> Duration {
+        Duration::from_secs_f32(self.start.max(0.))
+    }
+
+    /// Length of the track cut out of a file `duration` long.
+    pub fn length(&self, duration: Duration) -> Duration {
+        let end = self
+            .end
+            .map_or(duration, |end| Duration::from_secs_f32(end.max(0.)))
+            .min(duration);
+
+        end.saturating_sub(self.start())
+    }
+}
+
+impl Default for Trim {
+    fn default() -> Self {
+        Trim::NONE
+    }
+}
+
+/// How an additional audio track, e.g. a voiceover, is layered over the main
+/// one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Mix {
+    /// In decibels.
+    pub gain: f32,
+    /// Seconds into the timeline at which the track starts.
+    pub offset: f32,
+}
+
+impl Mix {
+    pub const NONE: Mix = Mix {
+        gain: 0.,
+        offset: 0.,
+    };
+
+    /// The gain as a factor applied to the track's amplitude.
+    pub fn amplitude(&self) -> f32 {
+        10_f32.powf(self.gain / 20.)
+    }
+}
+
+impl Default for Mix {
+    fn default() -> Self {
+        Mix::NONE
+    }
+}
+
+/// The audio track followed by an audio-reactive layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Source {
+    /// The track the timeline follows.
+    #[default]
+    Main,
+    /// One of the additional tracks, by index.
+    Track(usize),
+}
+
+impl Source {
+    /// Position of the track among every track, the main one first.
+    pub fn index(self) -> usize {
+        match self {
+            Source::Main => 0,
+            Source::Track(index) => index + 1,
+        }
+    }
+
+    /// The same source once the additional track at `removed` is gone,
+    /// falling back to the main track if it was this one.
+    pub fn without(self, removed: usize) -> Source {
+        match self {
+            Source::Track(index) if index == removed => Source::Main,
+            Source::Track(index) if index > removed => Source::Track(index - 1),
+            source => source,
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Main => f.write_str(t("source-main")),
+            Source::Track(index) => {
+                f.write_str(&t_args("source-track", &[("number", &(index + 2))]))
+            }
+        }
+    }
+}
+
+/// An analyzed audio track as placed on the timeline.
+#[derive(Debug, Clone)]
+pub struct Placement {
+    pub analysis: Arc<Analysis>,
+    /// Seconds into the file at the start of the timeline, negative for a
+    /// track starting later.
+    pub start: f32,
+    /// Factor applied to the track's loudness.
+    pub amplitude: f32,
+}
+
+impl Placement {
+    /// Where the playhead at `time` falls in the file, unless the track has
+    /// yet to start.
+    pub fn audio_time(&self, time: Duration) -> Option<Duration> {
+        let seconds = self.start + time.as_secs_f32();
+
+        (seconds >= 0.).then(|| Duration::from_secs_f32(seconds))
+    }
+}
+
+/// The part of a stereo track that an audio-reactive layer follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Channel {
+    /// Both channels mixed together.
+    #[default]
+    Mid,
+    Left,
+    Right,
+    /// The difference between the channels, which is silent for mono audio.
+    Side,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [Channel::Mid, Channel::Left, Channel::Right, Channel::Side];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// This channel's sample of `frame`, given the average of every channel.
+    fn sample(self, frame: &[f32], mid: f32) -> f32 {
+        let left = frame.first().copied().unwrap_or(0.);
+        let right = frame.get(1).copied().unwrap_or(left);
+
+        match self {
+            Channel::Mid => mid,
+            Channel::Left => left,
+            Channel::Right => right,
+            Channel::Side => (left - right) / 2.,
+        }
+    }
+}
+
+impl Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Channel::Mid => t("channel-mid"),
+            Channel::Left => t("channel-left"),
+            Channel::Right => t("channel-right"),
+            Channel::Side => t("channel-side"),
+        })
+    }
+}
+
+/// Loudness of one channel of the track, relative to the loudest slice of any
+/// channel so that the channels stay comparable.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Signal {
+    /// RMS loudness of the low end of each slice.
+    bass: Vec<f32>,
+    /// Times at which a beat was detected in the low end, in seconds.
+    beats: Vec<f32>,
+    /// Running total of the RMS loudness of each `1 / LEVELS_PER_SECOND`
+    /// slice of the track. Animations driven by the loudness can be evaluated
+    /// at any time from it without replaying the track.
+    accumulated: Vec<f32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Meter {
+    rms: Vec<f32>,
+    peaks: Vec<f32>,
+}
+
+/// Loudness of a channel around a point in the track, as sample amplitudes
+/// between 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Level {
+    pub rms: f32,
+    /// The loudest sample of the last [`Analysis::PEAK_HOLD`].
+    pub peak: f32,
+}
+
+impl Level {
+    /// Decibels relative to full scale of an amplitude.
+    pub fn decibels(amplitude: f32) -> f32 {
+        20. * amplitude.max(1e-6).log10()
+    }
+}
+
+impl Analysis {
+    pub const LEVELS_PER_SECOND: f32 = 100.;
+
+    /// How long the peak shown by level meters is held.
+    pub const PEAK_HOLD: Duration = Duration::from_millis(500);
+
+    /// Frequency below which the signal counts as bass.
+    const BASS_CUTOFF: f32 = 150.;
+
+    /// Peak level, in decibels, below which the track counts as silent.
+    pub const SILENCE_THRESHOLD: f32 = -50.;
+
+    /// Shortest quiet stretch counted as silence, so that pauses between
+    /// notes are not.
+    const MIN_SILENCE: Duration = Duration::from_millis(500);
+
+    fn new(recorder: Recorder, pitches: Vec<Pitch>, duration: Duration) -> Analysis {
+        let Recorder {
+            mut peaks,
+            signals,
+            meters,
+            ..
+        } = recorder;
+        normalize(&mut peaks);
+
+        let loudest = |levels: fn(&SignalRecorder) -> &Vec<f32>| {
+            signals
+                .iter()
+                .flat_map(|signal| levels(signal).iter().copied())
+                .fold(0., f32::max)
+        };
+        let loudest_level = loudest(|signal| &signal.levels);
+        let loudest_bass = loudest(|signal| &signal.bass_levels);
+
+        let signals = signals
+            .into_iter()
+            .map(|signal| {
+                let bass: Vec<f32> = signal
+                    .bass_levels
+                    .iter()
+                    .map(|level| relative(*level, loudest_bass))
+                    .collect();
+                let accumulated = signal
+                    .levels
+                    .iter()
+                    .scan(0., |total, level| {
+                        *total += relative(*level, loudest_level) / Self::LEVELS_PER_SECOND;
+                        Some(*total)
+                    })
+                    .collect();
+
+                Signal {
+                    beats: detect_beats(&bass),
+                    bass,
+                    accumulated,
+                }
+            })
+            .collect();
+
+        Analysis {
+            duration,
+            peaks,
+            signals,
+            silences: detect_silences(&meters, duration),
+            meters,
+            key: Key::detect(&pitches.iter().fold(Chroma::default(), |total, pitch| {
+                std::array::from_fn(|class| total[class] + pitch.chroma[class])
+            })),
+            pitches,
+        }
+    }
+
+    fn signal(&self, channel: Channel) -> &Signal {
+        &self.signals[channel.index()]
+    }
+
+    fn index(&self, time: Duration) -> usize {
+        (time.as_secs_f32() * Self::LEVELS_PER_SECOND) as usize
+    }
+
+    /// The peak of every `1 / LEVELS_PER_SECOND` slice of the track.
+    pub fn peaks(&self) -> &[f32] {
+        &self.peaks
+    }
+
+    pub fn key(&self) -> Option<Key> {
+        self.key
+    }
+
+    /// Bytes held by the analysis' buffers.
+    pub fn memory(&self) -> usize {
+        let floats = self.peaks.len()
+            + self
+                .signals
+                .iter()
+                .map(|signal| signal.bass.len() + signal.beats.len() + signal.accumulated.len())
+                .sum::<usize>()
+            + self
+                .meters
+                .iter()
+                .map(|meter| meter.rms.len() + meter.peaks.len())
+                .sum::<usize>();
+
+        floats * size_of::<f32>()
+            + self.silences.len() * size_of::<Silence>()
+            + self.pitches.len() * size_of::<Pitch>()
+    }
+
+    /// The pitches heard at `time`.
+    pub fn pitch(&self, time: Duration) -> Pitch {
+        let index = (time.as_secs_f32() * ChromaRecorder::FRAMES_PER_SECOND) as usize;
+
+        self.pitches.get(index).copied().unwrap_or_default()
+    }
+
+    /// Beats per minute of `channel`, from the typical time between its
+    /// beats, folded into the range most music is written in.
+    pub fn tempo(&self, channel: Channel) -> Option<f32> {
+        let beats = &self.signal(channel).beats;
+        let mut intervals: Vec<f32> = beats.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        if intervals.is_empty() {
+            return None;
+        }
+        intervals.sort_by(f32::total_cmp);
+
+        let mut tempo = 60. / intervals[intervals.len() / 2];
+        while tempo < 70. {
+            tempo *= 2.;
+        }
+        while tempo > 180. {
+            tempo /= 2.;
+        }
+
+        Some(tempo)
+    }
+
+    pub fn silences(&self) -> &[Silence] {
+        &self.silences
+    }
+
+    /// The trim that cuts off the silence at the start and end of the track,
+    /// if there is any.
+    pub fn silence_trim(&self) -> Trim {
+        let duration = self.duration.as_secs_f32();
+        let leading = self.silences.first().filter(|silence| silence.start <= 0.);
+        let trailing = self
+            .silences
+            .last()
+            .filter(|silence| silence.end >= duration && silence.start > 0.);
+
+        Trim {
+            start: leading.map_or(0., |silence| silence.end),
+            end: trailing.map(|silence| silence.start),
+        }
+    }
+
+    /// Loudness of every channel at `time`.
+    pub fn levels(&self, time: Duration) -> Vec<Level> {
+        let index = self.index(time);
+        let hold = self.index(Self::PEAK_HOLD);
+
+        self.meters
+            .iter()
+            .map(|meter| Level {
+                rms: meter.rms.get(index).copied().unwrap_or(0.),
+                peak: meter
+                    .peaks
+                    .get(index.saturating_sub(hold)..=index)
+                    .unwrap_or_default()
+                    .iter()
+                    .copied()
+                    .fold(0., f32::max),
+            })
+            .collect()
+    }
+
+    /// Loudness of the bass of `channel` at `time`, between 0 and 1.
+    pub fn bass(&self, channel: Channel, time: Duration) -> f32 {
+        self.signal(channel)
+            .bass
+            .get(self.index(time))
+            .copied()
+            .unwrap_or(0.)
+    }
+
+    /// Seconds since the last beat in `channel` before `time`.
+    pub fn since_beat(&self, channel: Channel, time: Duration) -> Option<f32> {
+        let beats = &self.signal(channel).beats;
+        let time = time.as_secs_f32();
+        let next = beats.partition_point(|beat| *beat <= time);
+
+        next.checked_sub(1).map(|last| time - beats[last])
+    }
+
+    /// Integral of the loudness of `channel`, between 0 and 1, from the start
+    /// of the track to `time`.
+    pub fn accumulated_level(&self, channel: Channel, time: Duration) -> f32 {
+        let accumulated = &self.signal(channel).accumulated;
+
+        match self.index(time).checked_sub(1) {
+            Some(index) => accumulated
+                .get(index)
+                .or(accumulated.last())
+                .copied()
+                .unwrap_or(0.),
+            None => 0.,
+        }
+    }
+}
+
+fn normalize(levels: &mut [f32]) {
+    let loudest = levels.iter().copied().fold(0., f32::max);
+    for level in levels {
+        *level = relative(*level, loudest);
+    }
+}
+
+fn relative(level: f32, loudest: f32) -> f32 {
+    if loudest > 0. {
+        level / loudest
+    } else {
+        level
+    }
+}
+
+/// Finds the stretches where no channel peaks above the silence threshold.
+fn detect_silences(meters: &[Meter], duration: Duration) -> Vec<Silence> {
+    let slices = meters
+        .iter()
+        .map(|meter| meter.peaks.len())
+        .max()
+        .unwrap_or(0);
+    let is_silent = |index: usize| {
+        meters.iter().all(|meter| {
+            Level::decibels(meter.peaks.get(index).copied().unwrap_or(0.))
+                < Analysis::SILENCE_THRESHOLD
+        })
+    };
+    let time =
+        |index: usize| (index as f32 / Analysis::LEVELS_PER_SECOND).min(duration.as_secs_f32());
+
+    let mut silences = vec![];
+    let mut start = None;
+    for index in 0..=slices {
+        match (start, index < slices && is_silent(index)) {
+            (None, true) => start = Some(index),
+            (Some(first), false) => {
+                let silence = Silence {
+                    start: time(first),
+                    end: time(index),
+                };
+                if silence.end - silence.start >= Analysis::MIN_SILENCE.as_secs_f32() {
+                    silences.push(silence);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    silences
+}
+
+/// Finds the slices where the bass jumps well above its recent average.
+fn detect_beats(bass: &[f32]) -> Vec<f32> {
+    const WINDOW: usize = (Analysis::LEVELS_PER_SECOND / 2.) as usize;
+    const MIN_GAP: usize = (Analysis::LEVELS_PER_SECOND / 4.) as usize;
+
+    let mut beats = vec![];
+    let mut last: Option<usize> = None;
+
+    for (index, level) in bass.iter().enumerate() {
+        let recent = &bass[index.saturating_sub(WINDOW)..index];
+        let average = recent.iter().sum::<f32>() / recent.len().max(1) as f32;
+        let rested = last.is_none_or(|last| index - last >= MIN_GAP);
+
+        if rested && *level > 0.1 && *level > average * 1.4 {
+            beats.push(index as f32 / Analysis::LEVELS_PER_SECOND);
+            last = Some(index);
+        }
+    }
+
+    beats
+}
+
+/// Analyzes the audio file at `path`, reusing the analysis cached at `cache`
+/// if there is one and caching it there otherwise. How much of the file has
+/// been decoded is kept in `progress`, and the analysis stops once it is
+/// cancelled.
+pub async fn analyze(
+    path: PathBuf,
+    cache: PathBuf,
+    progress: Arc<LoadProgress>,
+) -> Result<Arc<Analysis>, Error> {
+    tokio::task::spawn_blocking(move || {
+        if let Some(analysis) = read_cache(&cache) {
+            return Ok(analysis);
+        }
+
+        let analysis = analyze_blocking(path, progress)?;
+        if let Err(error) = write_cache(&cache, &analysis) {
+            tracing::warn!("could not cache audio analysis: {:?}", error);
+        }
+
+        Ok(analysis)
+    })
+    .await
+    .map_err(|error| Error::AudioDecode(error.to_string()))?
+    .map(Arc::new)
+}
+
+/// Bumped whenever what the analysis holds changes, so that older cache files
+/// are no longer picked up.
+const CACHE_VERSION: u32 = 3;
+
+/// Where the analysis of an audio file whose contents hash to `hash` is
+/// cached: next to the project file if it has been saved, in the user's cache
+/// directory otherwise.
+pub fn cache_path(project: Option<&Path>, hash: u64) -> PathBuf {
+    let directory = match project.and_then(Path::parent) {
+        Some(directory) => directory.join(".roygbiv-cache"),
+        None => dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("roygbiv")
+            .join("analysis"),
+    };
+
+    directory.join(format!("{hash:016x}-v{CACHE_VERSION}.ron"))
+}
+
+fn read_cache(path: &Path) -> Option<Analysis> {
+    let contents = std::fs::read_to_string(path).ok()?;
+
+    ron::from_str(&contents).ok()
+}
+
+fn write_cache(path: &Path, analysis: &Analysis) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    let contents = ron::to_string(analysis).map_err(io::Error::other)?;
+
+    std::fs::write(path, contents)
+}
+
+/// Sums up the samples of a signal over one slice of the track.
+#[derive(Debug, Default, Clone, Copy)]
+struct Slice {
+    sum_of_squares: f32,
+    peak: f32,
+}
+
+impl Slice {
+    fn add(&mut self, sample: f32) {
+        self.sum_of_squares += sample * sample;
+        self.peak = self.peak.max(sample.abs());
+    }
+
+    /// The RMS loudness and peak of the slice, starting the next one.
+    fn finish(&mut self, frames: usize) -> (f32, f32) {
+        let slice = std::mem::take(self);
+
+        ((slice.sum_of_squares / frames as f32).sqrt(), slice.peak)
+    }
+}
+
+/// Collects the loudness of every slice while the track is decoded.
+#[derive(Debug, Default)]
+struct Recorder {
+    /// Frames added to the current slice.
+    frames: usize,
+    waveform: Slice,
+    peaks: Vec<f32>,
+    signals: [SignalRecorder; 4],
+    channels: Vec<Slice>,
+    meters: Vec<Meter>,
+}
+
+#[derive(Debug, Default)]
+struct SignalRecorder {
+    /// State of the one-pole low-pass filter separating the bass.
+    filtered: f32,
+    level: Slice,
+    bass: Slice,
+    levels: Vec<f32>,
+    bass_levels: Vec<f32>,
+}
+
+impl Recorder {
+    fn ensure_channels(&mut self, count: usize) {
+        if self.channels.len() < count {
+            let slices = self.peaks.len();
+
+            self.channels.resize(count, Slice::default());
+            // Channels that join partway through were silent until then.
+            self.meters.resize_with(count, || Meter {
+                rms: vec![0.; slices],
+                peaks: vec![0.; slices],
+            });
+        }
+    }
+
+    fn add(&mut self, frame: &[f32], bass_smoothing: f32) {
+        let mid = frame.iter().sum::<f32>() / frame.len() as f32;
+
+        self.waveform.add(mid);
+        for (channel, signal) in Channel::ALL.into_iter().zip(&mut self.signals) {
+            let sample = channel.sample(frame, mid);
+            signal.filtered += (sample - signal.filtered) * bass_smoothing;
+            signal.level.add(sample);
+            signal.bass.add(signal.filtered);
+        }
+        for (slice, sample) in self.channels.iter_mut().zip(frame) {
+            slice.add(*sample);
+        }
+        self.frames += 1;
+    }
+
+    fn finish_slice(&mut self) {
+        let frames = std::mem::take(&mut self.frames);
+
+        self.peaks.push(self.waveform.finish(frames).1);
+        for signal in &mut self.signals {
+            signal.levels.push(signal.level.finish(frames).0);
+            signal.bass_levels.push(signal.bass.finish(frames).0);
+        }
+        for (slice, meter) in self.channels.iter_mut().zip(&mut self.meters) {
+            let (rms, peak) = slice.finish(frames);
+            meter.rms.push(rms);
+            meter.peaks.push(peak);
+        }
+    }
+}
+
+/// The audio track of a file, opened for decoding.
+pub struct Track {
+    pub format: Box<dyn FormatReader>,
+    pub decoder: Box<dyn Decoder>,
+    pub id: u32,
+    pub sample_rate: u32,
+}
+
+impl Track {
+    pub fn open(path: &Path) -> Result<Track, Error> {
+        let file = File::open(path).map_err(|error| Error::IoError(error.kind()))?;
+
+        Track::read(path, Box::new(file))
+    }
+
+    /// Reads the track from `source`, guessing its format from the
+    /// extension of `path` first.
+    pub fn read(path: &Path, source: Box<dyn MediaSource>) -> Result<Track, Error> {
+        let decode_error = |error: DecodeError| Error::AudioDecode(error.to_string());
+
+        let source = MediaSourceStream::new(source, Default::default());
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension() {
+            hint.with_extension(&extension.to_string_lossy());
+        }
+        let format = symphonia::default::get_probe()
+            .format(
+                &hint,
+                source,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .map_err(decode_error)?
+            .format;
+
+        // Video files list their video tracks too, which cannot be decoded.
+        let track = format
+            .tracks()
+            .iter()
+            .find(|track| {
+                track.codec_params.codec != CODEC_TYPE_NULL
+                    && track.codec_params.sample_rate.is_some()
+            })
+            .ok_or_else(|| Error::AudioDecode(t("error-no-audio-track").to_string()))?;
+        let id = track.id;
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .ok_or_else(|| Error::AudioDecode(t("error-unknown-sample-rate").to_string()))?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(decode_error)?;
+
+        Ok(Track {
+            format,
+            decoder,
+            id,
+            sample_rate,
+        })
+    }
+}
+
+/// Decodes the whole track as it is read from disk, keeping only the loudness
+/// of each slice rather than the file or the samples themselves.
+fn analyze_blocking(path: PathBuf, progress: Arc<LoadProgress>) -> Result<Analysis, Error> {
+    let decode_error = |error: DecodeError| match progress.is_cancelled() {
+        true => Error::Cancelled,
+        false => Error::AudioDecode(error.to_string()),
+    };
+
+    let file =
+        TrackedFile::open(&path, progress.clone()).map_err(|error| Error::IoError(error.kind()))?;
+    let Track {
+        mut format,
+        mut decoder,
+        id: track_id,
+        sample_rate,
+    } = Track::read(&path, Box::new(file)).map_err(|error| match progress.is_cancelled() {
+        true => Error::Cancelled,
+        false => error,
+    })?;
+
+    let slice_length = (sample_rate as f32 / Analysis::LEVELS_PER_SECOND)
+        .round()
+        .max(1.) as usize;
+    let bass_smoothing =
+        1. - (-std::f32::consts::TAU * Analysis::BASS_CUTOFF / sample_rate as f32).exp();
+    let mut recorder = Recorder::default();
+    let mut chroma = ChromaRecorder::new(sample_rate);
+    let mut total_frames: u64 = 0;
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(error)) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(error) => return Err(decode_error(error)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet only loses a few milliseconds of audio.
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(error) => return Err(decode_error(error)),
+        };
+        let spec = *decoded.spec();
+        let channels = spec.channels.count();
+
+        let buffer = match &mut buffer {
+            Some(buffer) if buffer.capacity() >= decoded.capacity() * channels => buffer,
+            buffer => buffer.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+        buffer.copy_interleaved_ref(decoded);
+        recorder.ensure_channels(channels);
+
+        for frame in buffer.samples().chunks_exact(channels) {
+            recorder.add(frame, bass_smoothing);
+            chroma.add(frame.iter().sum::<f32>() / frame.len() as f32);
+            total_frames += 1;
+
+            if recorder.frames == slice_length {
+                recorder.finish_slice();
+            }
+        }
+    }
+
+    if recorder.frames > 0 {
+        recorder.finish_slice();
+    }
+
+    Ok(Analysis::new(
+        recorder,
+        chroma.finish(),
+        Duration::from_secs_f64(total_frames as f64 / sample_rate as f64),
+    ))
+}
+
+/// An audio file being decoded, which keeps how far into it reading has got
+/// in a [`LoadProgress`], and fails to read once that is cancelled.
+struct TrackedFile {
+    file: File,
+    len: u64,
+    position: u64,
+    progress: Arc<LoadProgress>,
+}
+
+impl TrackedFile {
+    fn open(path: &Path, progress: Arc<LoadProgress>) -> io::Result<TrackedFile> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        progress.set_total(len);
+
+        Ok(TrackedFile {
+            file,
+            len,
+            position: 0,
+            progress,
+        })
+    }
+}
+
+impl Read for TrackedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.progress.is_cancelled() {
+            return Err(io::Error::other("cancelled"));
+        }
+        let read = self.file.read(buf)?;
+        self.position += read as u64;
+        self.progress.set_read(self.position);
+
+        Ok(read)
+    }
+}
+
+impl Seek for TrackedFile {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.position = self.file.seek(position)?;
+        self.progress.set_read(self.position);
+
+        Ok(self.position)
+    }
+}
+
+impl MediaSource for TrackedFile {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.len)
+    }
+}
+
+/// Speed at which the preview plays, for going slowly through fast sections
+/// or quickly through a whole track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackRate {
+    Quarter,
+    Half,
+    ThreeQuarters,
+    #[default]
+    Normal,
+    OneAndAHalf,
+    Double,
+}
+
+impl PlaybackRate {
+    pub const ALL: [PlaybackRate; 6] = [
+        PlaybackRate::Quarter,
+        PlaybackRate::Half,
+        PlaybackRate::ThreeQuarters,
+        PlaybackRate::Normal,
+        PlaybackRate::OneAndAHalf,
+        PlaybackRate::Double,
+    ];
+
+    pub fn factor(self) -> f32 {
+        match self {
+            PlaybackRate::Quarter => 0.25,
+            PlaybackRate::Half => 0.5,
+            PlaybackRate::ThreeQuarters => 0.75,
+            PlaybackRate::Normal => 1.,
+            PlaybackRate::OneAndAHalf => 1.5,
+            PlaybackRate::Double => 2.,
+        }
+    }
+}
+
+impl Display for PlaybackRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}×", self.factor())
+    }
+}
+
+/// Keeps track of the preview position while it plays or is paused.
+#[derive(Debug)]
+pub struct Transport {
+    position: Duration,
+    /// When playback last started, if playing.
+    resumed: Option<Instant>,
+    rate: PlaybackRate,
+}
+
+impl Transport {
+    /// Starts paused, so nothing ticks until there's something to play.
+    pub fn new() -> Transport {
+        Transport {
+            position: Duration::ZERO,
+            resumed: None,
+            rate: PlaybackRate::Normal,
+        }
+    }
+
+    pub fn rate(&self) -> PlaybackRate {
+        self.rate
+    }
+
+    /// Changes the speed from the current position onwards.
+    pub fn set_rate(&mut self, rate: PlaybackRate, length: Option<Duration>) {
+        self.position = self.position(length);
+        if self.resumed.is_some() {
+            self.resumed = Some(Instant::now());
+        }
+        self.rate = rate;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.resumed.is_some()
+    }
+
+    /// The current position, looping over `length` if it is known.
+    pub fn position(&self, length: Option<Duration>) -> Duration {
+        let position = match self.resumed {
+            Some(resumed) => self.position + resumed.elapsed().mul_f32(self.rate.factor()),
+            None => self.position,
+        };
+
+        match length {
+            Some(length) if !length.is_zero() => {
+                Duration::from_secs_f64(position.as_secs_f64() % length.as_secs_f64())
+            }
+            _ => position,
+        }
+    }
+
+    pub fn play(&mut self) {
+        self.resumed.get_or_insert_with(Instant::now);
+    }
+
+    pub fn pause(&mut self, length: Option<Duration>) {
+        self.position = self.position(length);
+        self.resumed = None;
+    }
+
+    pub fn seek(&mut self, position: Duration) {
+        self.position = position;
+        if self.resumed.is_some() {
+            self.resumed = Some(Instant::now());
+        }
+    }
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,683 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// A webcam shown by a capture layer, and the size of the frames asked of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Camera {
+    /// Where the device is found, e.g. `/dev/video0`.
+    pub device: String,
+    /// What the camera calls itself.
+    pub name: String,
+    pub resolution: Resolution,
+}
+
+/// The size of a camera's frames, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}×{}", self.width, self.height)
+    }
+}
+
+/// A camera that can be captured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CameraDevice {
+    pub device: String,
+    pub name: String,
+    /// The frame sizes it offers, largest first.
+    pub resolutions: Vec<Resolution>,
+}
+
+impl CameraDevice {
+    /// Frames larger than this are slow to carry over USB without being
+    /// compressed, so the largest size up to it is asked for at first.
+    const PREFERRED: Resolution = Resolution {
+        width: 1280,
+        height: 720,
+    };
+
+    /// The camera at the size a new layer shows it at.
+    pub fn camera(&self) -> Camera {
+        let fits = |resolution: &&Resolution| {
+            resolution.width <= Self::PREFERRED.width && resolution.height <= Self::PREFERRED.height
+        };
+        let resolution = self
+            .resolutions
+            .iter()
+            .find(fits)
+            .or(self.resolutions.last())
+            .copied()
+            .unwrap_or(Self::PREFERRED);
+
+        Camera {
+            device: self.device.clone(),
+            name: self.name.clone(),
+            resolution,
+        }
+    }
+}
+
+/// The cameras that can be captured.
+pub async fn devices() -> Result<Vec<CameraDevice>, String> {
+    tokio::task::spawn_blocking(platform::devices)
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+/// The latest frame of `camera`, which is started if it isn't already
+/// running. Cameras that aren't asked for frames for a while are stopped.
+pub fn frame(camera: &Camera) -> Result<image::RgbaImage, String> {
+    platform::frame(camera)
+}
+
+/// Capture through Video4Linux, streaming each camera from its own thread so
+/// that frames are ready whenever the layers want one.
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{
+        ffi::CString,
+        sync::{Arc, Condvar, Mutex, PoisonError},
+        time::{Duration, Instant},
+    };
+
+    use super::{Camera, CameraDevice, Resolution};
+    use crate::i18n::t;
+
+    /// How long to wait for a camera's first frame.
+    const STARTUP: Duration = Duration::from_secs(3);
+    /// How long a camera keeps running without being asked for frames.
+    const IDLE: Duration = Duration::from_secs(2);
+
+    /// The frames of a running camera.
+    struct Stream {
+        camera: Camera,
+        /// The latest frame, or why the camera stopped.
+        frame: Mutex<Option<Result<image::RgbaImage, String>>>,
+        arrived: Condvar,
+        asked: Mutex<Instant>,
+    }
+
+    static STREAMS: Mutex<Vec<Arc<Stream>>> = Mutex::new(vec![]);
+
+    pub fn devices() -> Result<Vec<CameraDevice>, String> {
+        let mut paths: Vec<_> = std::fs::read_dir("/dev")
+            .map_err(|error| error.to_string())?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("video"))
+            })
+            .collect();
+        paths.sort();
+
+        Ok(paths
+            .into_iter()
+            .filter_map(|path| {
+                let device = path.to_string_lossy().into_owned();
+                let file = v4l2::Device::open(&device).ok()?;
+                let name = file.capture_name()?;
+                let mut resolutions = file.resolutions();
+                resolutions.sort_by_key(|size| std::cmp::Reverse(size.width * size.height));
+                resolutions.dedup();
+
+                (!resolutions.is_empty()).then_some(CameraDevice {
+                    device,
+                    name,
+                    resolutions,
+                })
+            })
+            .collect())
+    }
+
+    pub fn frame(camera: &Camera) -> Result<image::RgbaImage, String> {
+        let stream = {
+            let mut streams = STREAMS.lock().unwrap_or_else(PoisonError::into_inner);
+            match streams.iter().find(|stream| stream.camera == *camera) {
+                Some(stream) => stream.clone(),
+                None => {
+                    let stream = Arc::new(Stream {
+                        camera: camera.clone(),
+                        frame: Mutex::new(None),
+                        arrived: Condvar::new(),
+                        asked: Mutex::new(Instant::now()),
+                    });
+                    streams.push(stream.clone());
+                    let running = stream.clone();
+                    std::thread::spawn(move || run(running));
+
+                    stream
+                }
+            }
+        };
+        *stream.asked.lock().unwrap_or_else(PoisonError::into_inner) = Instant::now();
+
+        let frame = stream.frame.lock().unwrap_or_else(PoisonError::into_inner);
+        let (frame, _) = stream
+            .arrived
+            .wait_timeout_while(frame, STARTUP, |frame| frame.is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+
+        match &*frame {
+            Some(Ok(image)) => Ok(image.clone()),
+            Some(Err(error)) => Err(error.clone()),
+            None => Err(t("error-camera-no-frames").to_string()),
+        }
+    }
+
+    /// Streams `stream`'s camera until it is no longer asked for frames or
+    /// fails.
+    fn run(stream: Arc<Stream>) {
+        let result = v4l2::Device::open(&stream.camera.device).and_then(|device| {
+            let mut capture = device.start(stream.camera.resolution)?;
+            loop {
+                let is_idle = stream
+                    .asked
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .elapsed()
+                    > IDLE;
+                if is_idle {
+                    return Ok(());
+                }
+                if let Some(image) = capture.next()? {
+                    *stream.frame.lock().unwrap_or_else(PoisonError::into_inner) = Some(Ok(image));
+                    stream.arrived.notify_all();
+                }
+            }
+        });
+
+        STREAMS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|running| !Arc::ptr_eq(running, &stream));
+        if let Err(error) = result {
+            *stream.frame.lock().unwrap_or_else(PoisonError::into_inner) = Some(Err(error));
+            stream.arrived.notify_all();
+        }
+    }
+
+    /// Just enough of the Video4Linux API to stream frames from a camera
+    /// into memory shared with the driver.
+    mod v4l2 {
+        use std::{ffi::c_void, mem::size_of};
+
+        use super::{CString, Resolution};
+        use crate::i18n::t;
+
+        const fn ioctl(direction: u64, number: u64, size: usize) -> u64 {
+            (direction << 30) | ((size as u64) << 16) | ((b'V' as u64) << 8) | number
+        }
+        const WRITE: u64 = 1;
+        const READ: u64 = 2;
+        const QUERYCAP: u64 = ioctl(READ, 0, size_of::<Capability>());
+        const S_FMT: u64 = ioctl(READ | WRITE, 5, size_of::<Format>());
+        const REQBUFS: u64 = ioctl(READ | WRITE, 8, size_of::<RequestBuffers>());
+        const QUERYBUF: u64 = ioctl(READ | WRITE, 9, size_of::<Buffer>());
+        const QBUF: u64 = ioctl(READ | WRITE, 15, size_of::<Buffer>());
+        const DQBUF: u64 = ioctl(READ | WRITE, 17, size_of::<Buffer>());
+        const STREAMON: u64 = ioctl(WRITE, 18, size_of::<libc::c_int>());
+        const STREAMOFF: u64 = ioctl(WRITE, 19, size_of::<libc::c_int>());
+        const ENUM_FRAMESIZES: u64 = ioctl(READ | WRITE, 74, size_of::<FrameSizes>());
+
+        const VIDEO_CAPTURE: u32 = 1;
+        const MEMORY_MMAP: u32 = 1;
+        const CAP_VIDEO_CAPTURE: u32 = 0x1;
+        const CAP_STREAMING: u32 = 0x0400_0000;
+        const CAP_DEVICE_CAPS: u32 = 0x8000_0000;
+        const FRAMESIZE_DISCRETE: u32 = 1;
+        const YUYV: u32 = u32::from_le_bytes(*b"YUYV");
+        const MJPEG: u32 = u32::from_le_bytes(*b"MJPG");
+        /// Sizes offered by cameras that take any size within a range.
+        const COMMON: [Resolution; 3] = [
+            Resolution {
+                width: 640,
+                height: 480,
+            },
+            Resolution {
+                width: 1280,
+                height: 720,
+            },
+            Resolution {
+                width: 1920,
+                height: 1080,
+            },
+        ];
+        const BUFFERS: u32 = 4;
+
+        #[repr(C)]
+        struct Capability {
+            driver: [u8; 16],
+            card: [u8; 32],
+            bus_info: [u8; 32],
+            version: u32,
+            capabilities: u32,
+            device_caps: u32,
+            reserved: [u32; 3],
+        }
+
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        struct PixFormat {
+            width: u32,
+            height: u32,
+            pixel_format: u32,
+            field: u32,
+            bytes_per_line: u32,
+            size_image: u32,
+            color_space: u32,
+            private: u32,
+            flags: u32,
+            ycbcr_encoding: u32,
+            quantization: u32,
+            transfer_function: u32,
+        }
+
+        /// The kernel's union also holds structs with pointers, which align
+        /// it to them.
+        #[repr(C)]
+        union FormatData {
+            pix: PixFormat,
+            raw: [u8; 200],
+            align: [usize; 0],
+        }
+
+        #[repr(C)]
+        struct Format {
+            kind: u32,
+            data: FormatData,
+        }
+
+        #[repr(C)]
+        struct RequestBuffers {
+            count: u32,
+            kind: u32,
+            memory: u32,
+            capabilities: u32,
+            flags: u8,
+            reserved: [u8; 3],
+        }
+
+        #[repr(C)]
+        struct Timecode {
+            kind: u32,
+            flags: u32,
+            frames: u8,
+            seconds: u8,
+            minutes: u8,
+            hours: u8,
+            user_bits: [u8; 4],
+        }
+
+        #[repr(C)]
+        union BufferLocation {
+            offset: u32,
+            user_pointer: libc::c_ulong,
+            planes: *mut c_void,
+            fd: i32,
+        }
+
+        #[repr(C)]
+        struct Buffer {
+            index: u32,
+            kind: u32,
+            bytes_used: u32,
+            flags: u32,
+            field: u32,
+            timestamp: libc::timeval,
+            timecode: Timecode,
+            sequence: u32,
+            memory: u32,
+            location: BufferLocation,
+            length: u32,
+            reserved: u32,
+            request_fd: u32,
+        }
+
+        #[repr(C)]
+        struct FrameSizes {
+            index: u32,
+            pixel_format: u32,
+            kind: u32,
+            /// The width and height of a discrete size, or the smallest and
+            /// largest width and height and the steps between them.
+            sizes: [u32; 6],
+            reserved: [u32; 2],
+        }
+
+        /// The structs are plain old data, for which zeroes are fine.
+        fn zeroed<T>() -> T {
+            // SAFETY: only used with the `repr(C)` structs above, made of
+            // integers, arrays of them and nullable pointers.
+            unsafe { std::mem::zeroed() }
+        }
+
+        pub struct Device {
+            fd: libc::c_int,
+        }
+
+        impl Device {
+            pub fn open(path: &str) -> Result<Device, String> {
+                let path = CString::new(path).map_err(|error| error.to_string())?;
+                // SAFETY: the path is a valid C string.
+                let fd = unsafe { libc::open(path.as_ptr(), libc::O_RDWR | libc::O_NONBLOCK) };
+                if fd < 0 {
+                    return Err(std::io::Error::last_os_error().to_string());
+                }
+
+                Ok(Device { fd })
+            }
+
+            fn ioctl<T>(&self, request: u64, argument: &mut T) -> Result<(), String> {
+                self.try_ioctl(request, argument)
+                    .map_err(|error| error.to_string())
+            }
+
+            fn try_ioctl<T>(&self, request: u64, argument: &mut T) -> std::io::Result<()> {
+                loop {
+                    // SAFETY: each request is only made with the struct its
+                    // number was made from.
+                    let result = unsafe { libc::ioctl(self.fd, request as _, argument as *mut T) };
+                    if result >= 0 {
+                        return Ok(());
+                    }
+                    let error = std::io::Error::last_os_error();
+                    if error.kind() != std::io::ErrorKind::Interrupted {
+                        return Err(error);
+                    }
+                }
+            }
+
+            /// The camera's name, if the device captures video. Cameras
+            /// also have devices for their metadata, which don't.
+            pub fn capture_name(&self) -> Option<String> {
+                let mut capability: Capability = zeroed();
+                self.ioctl(QUERYCAP, &mut capability).ok()?;
+                let caps = if capability.capabilities & CAP_DEVICE_CAPS != 0 {
+                    capability.device_caps
+                } else {
+                    capability.capabilities
+                };
+                if caps & CAP_VIDEO_CAPTURE == 0 || caps & CAP_STREAMING == 0 {
+                    return None;
+                }
+                let card = &capability.card;
+                let length = card
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .unwrap_or(card.len());
+
+                Some(String::from_utf8_lossy(&card[..length]).into_owned())
+            }
+
+            /// The frame sizes offered in the formats that can be read.
+            pub fn resolutions(&self) -> Vec<Resolution> {
+                let mut resolutions = vec![];
+                for pixel_format in [YUYV, MJPEG] {
+                    for index in 0.. {
+                        let mut sizes = FrameSizes {
+                            index,
+                            pixel_format,
+                            ..zeroed()
+                        };
+                        if self.ioctl(ENUM_FRAMESIZES, &mut sizes).is_err() {
+                            break;
+                        }
+                        let [width, height, ..] = sizes.sizes;
+                        if sizes.kind == FRAMESIZE_DISCRETE {
+                            resolutions.push(Resolution { width, height });
+                        } else {
+                            let [min_width, max_width, _, min_height, max_height, _] = sizes.sizes;
+                            resolutions.extend(COMMON.into_iter().filter(|size| {
+                                (min_width..=max_width).contains(&size.width)
+                                    && (min_height..=max_height).contains(&size.height)
+                            }));
+                            break;
+                        }
+                    }
+                }
+
+                resolutions
+            }
+
+            /// Starts streaming frames of about `resolution`, uncompressed if
+            /// the camera can.
+            pub fn start(self, resolution: Resolution) -> Result<Capture, String> {
+                let mut format = None;
+                for pixel_format in [YUYV, MJPEG] {
+                    let mut wanted = Format {
+                        kind: VIDEO_CAPTURE,
+                        data: FormatData {
+                            pix: PixFormat {
+                                width: resolution.width,
+                                height: resolution.height,
+                                pixel_format,
+                                ..zeroed()
+                            },
+                        },
+                    };
+                    self.ioctl(S_FMT, &mut wanted)?;
+                    // SAFETY: the driver fills the union in as a pixel
+                    // format for video capture.
+                    let pix = unsafe { wanted.data.pix };
+                    // Drivers pick a format of their own when they don't
+                    // have the one asked for.
+                    if pix.pixel_format == pixel_format {
+                        format = Some(pix);
+                        break;
+                    }
+                }
+                let format = format.ok_or(t("error-camera-format"))?;
+
+                let mut request = RequestBuffers {
+                    count: BUFFERS,
+                    kind: VIDEO_CAPTURE,
+                    memory: MEMORY_MMAP,
+                    ..zeroed()
+                };
+                self.ioctl(REQBUFS, &mut request)?;
+                let mut capture = Capture {
+                    device: self,
+                    format,
+                    buffers: vec![],
+                };
+                for index in 0..request.count {
+                    let mut buffer = capture.buffer(index);
+                    capture.device.ioctl(QUERYBUF, &mut buffer)?;
+                    let length = buffer.length as usize;
+                    // SAFETY: maps the buffer the driver has just described,
+                    // which is unmapped when the capture is dropped.
+                    let pointer = unsafe {
+                        libc::mmap(
+                            std::ptr::null_mut(),
+                            length,
+                            libc::PROT_READ | libc::PROT_WRITE,
+                            libc::MAP_SHARED,
+                            capture.device.fd,
+                            buffer.location.offset as libc::off_t,
+                        )
+                    };
+                    if pointer == libc::MAP_FAILED {
+                        return Err(std::io::Error::last_os_error().to_string());
+                    }
+                    capture.buffers.push((pointer, length));
+                    capture.device.ioctl(QBUF, &mut buffer)?;
+                }
+                let mut kind = VIDEO_CAPTURE as libc::c_int;
+                capture.device.ioctl(STREAMON, &mut kind)?;
+
+                Ok(capture)
+            }
+        }
+
+        impl Drop for Device {
+            fn drop(&mut self) {
+                // SAFETY: the descriptor was opened by `open` and is closed
+                // only here.
+                unsafe { libc::close(self.fd) };
+            }
+        }
+
+        pub struct Capture {
+            device: Device,
+            format: PixFormat,
+            /// Where each of the driver's buffers is mapped, and its length.
+            buffers: Vec<(*mut c_void, usize)>,
+        }
+
+        impl Capture {
+            fn buffer(&self, index: u32) -> Buffer {
+                Buffer {
+                    index,
+                    kind: VIDEO_CAPTURE,
+                    memory: MEMORY_MMAP,
+                    ..zeroed()
+                }
+            }
+
+            /// The next frame, or `None` if none arrived in a moment. A
+            /// frame that can't be decoded is skipped, as the next one is
+            /// usually fine.
+            pub fn next(&mut self) -> Result<Option<image::RgbaImage>, String> {
+                let mut poll = libc::pollfd {
+                    fd: self.device.fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                // SAFETY: polls the one descriptor passed in.
+                if unsafe { libc::poll(&mut poll, 1, 250) } <= 0 {
+                    return Ok(None);
+                }
+
+                let mut buffer = self.buffer(0);
+                match self.device.try_ioctl(DQBUF, &mut buffer) {
+                    Ok(()) => {}
+                    Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                        return Ok(None)
+                    }
+                    Err(error) => return Err(error.to_string()),
+                }
+                let Some(&(pointer, length)) = self.buffers.get(buffer.index as usize) else {
+                    return Err(t("error-camera-buffer").to_string());
+                };
+                // SAFETY: the buffer is mapped, and the driver leaves it
+                // alone until it is queued again below.
+                let data = unsafe {
+                    std::slice::from_raw_parts(
+                        pointer as *const u8,
+                        (buffer.bytes_used as usize).min(length),
+                    )
+                };
+                let image = self.decode(data);
+                self.device.ioctl(QBUF, &mut buffer)?;
+
+                match image {
+                    Ok(image) => Ok(Some(image)),
+                    Err(error) => {
+                        tracing::warn!("skipped a camera frame: {}", error);
+                        Ok(None)
+                    }
+                }
+            }
+
+            fn decode(&self, data: &[u8]) -> Result<image::RgbaImage, String> {
+                let PixFormat {
+                    width,
+                    height,
+                    bytes_per_line,
+                    ..
+                } = self.format;
+
+                if self.format.pixel_format == MJPEG {
+                    return image::load_from_memory_with_format(data, image::ImageFormat::Jpeg)
+                        .map(|image| image.to_rgba8())
+                        .map_err(|error| error.to_string());
+                }
+
+                // Two pixels in every four bytes, sharing their color.
+                let stride = (bytes_per_line as usize).max(width as usize * 2);
+                let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+                for row in data.chunks_exact(stride).take(height as usize) {
+                    for pair in row[..width as usize * 2].chunks_exact(4) {
+                        let [y0, u, y1, v] = [pair[0], pair[1], pair[2], pair[3]];
+                        pixels.extend(rgb(y0, u, v));
+                        pixels.extend(rgb(y1, u, v));
+                    }
+                }
+
+                image::RgbaImage::from_raw(width, height, pixels)
+                    .ok_or_else(|| t("error-camera-size").to_string())
+            }
+        }
+
+        impl Drop for Capture {
+            fn drop(&mut self) {
+                let mut kind = VIDEO_CAPTURE as libc::c_int;
+                let _ = self.device.ioctl(STREAMOFF, &mut kind);
+                for &(pointer, length) in &self.buffers {
+                    // SAFETY: each buffer was mapped by `start` with its
+                    // length, and isn't used after the capture.
+                    unsafe { libc::munmap(pointer, length) };
+                }
+            }
+        }
+
+        /// A pixel from its brightness and color, as cameras send them in
+        /// the range for video.
+        fn rgb(y: u8, u: u8, v: u8) -> [u8; 4] {
+            let (c, d, e) = (y as i32 - 16, u as i32 - 128, v as i32 - 128);
+            let channel = |value: i32| ((value + 128) >> 8).clamp(0, 255) as u8;
+
+            [
+                channel(298 * c + 409 * e),
+                channel(298 * c - 100 * d - 208 * e),
+                channel(298 * c + 516 * d),
+                255,
+            ]
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+
+            #[test]
+            #[cfg(target_pointer_width = "64")]
+            fn structs_are_the_kernels_size() {
+                assert_eq!(size_of::<Capability>(), 104);
+                assert_eq!(size_of::<Format>(), 208);
+                assert_eq!(size_of::<RequestBuffers>(), 20);
+                assert_eq!(size_of::<Buffer>(), 88);
+                assert_eq!(size_of::<FrameSizes>(), 44);
+                assert_eq!(QUERYCAP, 0x8068_5600);
+                assert_eq!(DQBUF, 0xc058_5611);
+            }
+
+            #[test]
+            fn converts_video_range_colors() {
+                assert_eq!(rgb(16, 128, 128), [0, 0, 0, 255]);
+                assert_eq!(rgb(235, 128, 128), [255, 255, 255, 255]);
+                assert_eq!(rgb(81, 90, 240), [255, 0, 0, 255]);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use super::{Camera, CameraDevice};
+    use crate::i18n::t;
+
+    pub fn devices() -> Result<Vec<CameraDevice>, String> {
+        Err(t("error-camera-unsupported").to_string())
+    }
+
+    pub fn frame(_camera: &Camera) -> Result<image::RgbaImage, String> {
+        Err(t("error-camera-unsupported").to_string())
+    }
+}
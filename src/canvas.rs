@@ -0,0 +1,2976 @@
+use std::{
+    cell::Cell,
+    fmt::Display,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use iced::{
+    mouse,
+    widget::{
+        canvas::{self, Path, Stroke, Text},
+        image::Handle,
+    },
+    Color, Point, Rectangle, Renderer, Size, Theme,
+};
+
+use image::{
+    metadata::Orientation as ExifOrientation, AnimationDecoder, GenericImageView, ImageDecoder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::{Animation, Motion, Playback, Timing},
+    assets::AssetId,
+    audio::{Analysis, Channel, Level, Placement, Source},
+    capture::{self, CaptureSource},
+    chroma::{self, Pitch},
+    color_profile::Profile,
+    distortion::Distortion,
+    glitch::Glitch,
+    i18n::t,
+    keyframes::{Keyframes, Property},
+    lottie::Lottie,
+    lut::Lut,
+    markers::{self, Marker},
+    offscreen,
+    post_fx::PostFx,
+    procedural::Procedural,
+    scenes::{self, Scene},
+    Message,
+};
+
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    pub kind: LayerKind,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub scale: f32,
+    pub opacity: f32,
+    pub flip: Flip,
+    pub appearance: Appearance,
+    pub animation: Animation,
+    pub timing: Timing,
+    pub playback: Playback,
+    pub keyframes: Keyframes,
+    /// The part of the audio that audio-reactive motion follows.
+    pub audio_channel: Channel,
+    /// The audio track that audio-reactive motion follows.
+    pub audio_source: Source,
+    pub units: Units,
+    /// Whether changing the width or height changes the other with it.
+    pub is_aspect_locked: bool,
+    pub visibility: Visibility,
+}
+
+/// Which views a layer is drawn in, so that e.g. a watermark is left out of
+/// the editor, or a guide out of the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Visibility {
+    #[default]
+    Everywhere,
+    /// Only in the preview window, which is what gets presented or captured.
+    OutputOnly,
+    /// Only on the canvas in the editor.
+    EditorOnly,
+    /// In rendered thumbnails, and in the editor to be laid out, e.g. a big
+    /// title for the video's poster.
+    ThumbnailOnly,
+    /// Everywhere but in rendered thumbnails, e.g. a progress bar.
+    NotInThumbnail,
+}
+
+impl Visibility {
+    pub const ALL: [Visibility; 5] = [
+        Visibility::Everywhere,
+        Visibility::OutputOnly,
+        Visibility::EditorOnly,
+        Visibility::ThumbnailOnly,
+        Visibility::NotInThumbnail,
+    ];
+
+    fn is_in_editor(self) -> bool {
+        self != Visibility::OutputOnly
+    }
+
+    /// Whether the layer is drawn in the preview window, or in rendered
+    /// thumbnails if `is_thumbnail`.
+    pub fn is_in_output(self, is_thumbnail: bool) -> bool {
+        match self {
+            Visibility::Everywhere | Visibility::OutputOnly => true,
+            Visibility::EditorOnly => false,
+            Visibility::ThumbnailOnly => is_thumbnail,
+            Visibility::NotInThumbnail => !is_thumbnail,
+        }
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Visibility::Everywhere => t("visibility-everywhere"),
+            Visibility::OutputOnly => t("visibility-output"),
+            Visibility::EditorOnly => t("visibility-editor"),
+            Visibility::ThumbnailOnly => t("visibility-thumbnail"),
+            Visibility::NotInThumbnail => t("visibility-not-thumbnail"),
+        })
+    }
+}
+
+/// The shape of the canvas, for making versions of a project for other
+/// platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 3] = [
+        Orientation::Landscape,
+        Orientation::Portrait,
+        Orientation::Square,
+    ];
+
+    /// The orientation of a canvas of this size, unless it's some other
+    /// shape.
+    pub fn of(size: Size) -> Option<Orientation> {
+        Orientation::ALL
+            .into_iter()
+            .find(|orientation| orientation.size(size) == size)
+    }
+
+    /// A canvas in this orientation as wide or tall as `size` is on its
+    /// shorter side, so turning the canvas back and forth gets it back to
+    /// its size.
+    pub fn size(self, size: Size) -> Size {
+        let short = size.width.min(size.height);
+        let long = (short * 16. / 9.).round();
+
+        match self {
+            Orientation::Landscape => Size::new(long, short),
+            Orientation::Portrait => Size::new(short, long),
+            Orientation::Square => Size::new(short, short),
+        }
+    }
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Orientation::Landscape => "16:9",
+            Orientation::Portrait => "9:16",
+            Orientation::Square => "1:1",
+        })
+    }
+}
+
+/// How several images added at once are laid out on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Arrangement {
+    /// Side by side, each fit into a cell.
+    #[default]
+    Grid,
+    /// On top of each other, each moved down and right from the one below.
+    Stack,
+}
+
+impl Arrangement {
+    pub const ALL: [Arrangement; 2] = [Arrangement::Grid, Arrangement::Stack];
+
+    /// Where the image at `index` of `count` images, of size `image`, goes
+    /// on a canvas of size `canvas`. Images are scaled down to fit, but
+    /// never up.
+    pub fn place(self, index: usize, count: usize, image: Size, canvas: Size) -> Rectangle {
+        /// How far each image in a stack is moved from the one below.
+        const OFFSET: f32 = 40.;
+
+        let fit = |area: Size| {
+            image
+                * (area.width / image.width)
+                    .min(area.height / image.height)
+                    .min(1.)
+        };
+
+        match self {
+            Arrangement::Grid => {
+                let columns = (count as f32).sqrt().ceil().max(1.) as usize;
+                let rows = count.div_ceil(columns);
+                let cell = Size::new(canvas.width / columns as f32, canvas.height / rows as f32);
+                let size = fit(cell);
+                let (column, row) = (index % columns, index / columns);
+
+                Rectangle::new(
+                    Point::new(
+                        column as f32 * cell.width + (cell.width - size.width) / 2.,
+                        row as f32 * cell.height + (cell.height - size.height) / 2.,
+                    ),
+                    size,
+                )
+            }
+            Arrangement::Stack => {
+                let spread = OFFSET * count.saturating_sub(1) as f32;
+                let size = fit(Size::new(
+                    (canvas.width - spread).max(1.),
+                    (canvas.height - spread).max(1.),
+                ));
+                let offset = OFFSET * index as f32;
+
+                Rectangle::new(
+                    Point::new(
+                        (canvas.width - size.width - spread) / 2. + offset,
+                        (canvas.height - size.height - spread) / 2. + offset,
+                    ),
+                    size,
+                )
+            }
+        }
+    }
+}
+
+impl Display for Arrangement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Arrangement::Grid => t("arrangement-grid"),
+            Arrangement::Stack => t("arrangement-stack"),
+        })
+    }
+}
+
+/// Parts of the frame that a platform covers with its own interface, shown
+/// over the canvas in the editor so nothing important ends up under them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SafeArea {
+    #[default]
+    None,
+    /// The action and title safe margins of broadcast video.
+    Broadcast,
+    YouTubeThumbnail,
+    /// The interface of the short-form video apps, made for a vertical
+    /// canvas.
+    TikTok,
+    Reels,
+    Shorts,
+}
+
+impl SafeArea {
+    pub const ALL: [SafeArea; 6] = [
+        SafeArea::None,
+        SafeArea::Broadcast,
+        SafeArea::YouTubeThumbnail,
+        SafeArea::TikTok,
+        SafeArea::Reels,
+        SafeArea::Shorts,
+    ];
+
+    /// What the platform puts over the frame and where, as fractions of the
+    /// canvas. Measured from the apps, so approximate.
+    fn covered(self) -> Vec<(&'static str, Rectangle)> {
+        fn area(x: f32, y: f32, width: f32, height: f32) -> Rectangle {
+            Rectangle {
+                x,
+                y,
+                width,
+                height,
+            }
+        }
+
+        match self {
+            SafeArea::None | SafeArea::Broadcast => vec![],
+            SafeArea::YouTubeThumbnail => vec![
+                ("Watch later", area(0.86, 0.03, 0.11, 0.2)),
+                ("Duration", area(0.82, 0.84, 0.15, 0.12)),
+                ("Progress", area(0., 0.97, 1., 0.03)),
+            ],
+            SafeArea::TikTok => vec![
+                ("Tabs", area(0., 0., 1., 0.08)),
+                ("Buttons", area(0.87, 0.36, 0.13, 0.5)),
+                ("Caption", area(0., 0.77, 0.87, 0.23)),
+                ("Caption", area(0.87, 0.86, 0.13, 0.14)),
+            ],
+            SafeArea::Reels => vec![
+                ("Header", area(0., 0., 1., 0.11)),
+                ("Buttons", area(0.88, 0.5, 0.12, 0.4)),
+                ("Caption", area(0., 0.8, 0.88, 0.2)),
+                ("Caption", area(0.88, 0.9, 0.12, 0.1)),
+            ],
+            SafeArea::Shorts => vec![
+                ("Header", area(0., 0., 1., 0.08)),
+                ("Buttons", area(0.86, 0.45, 0.14, 0.45)),
+                ("Caption", area(0., 0.83, 0.86, 0.17)),
+                ("Caption", area(0.86, 0.9, 0.14, 0.1)),
+            ],
+        }
+    }
+}
+
+impl Display for SafeArea {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SafeArea::None => t("safe-area-none"),
+            SafeArea::Broadcast => t("safe-area-broadcast"),
+            SafeArea::YouTubeThumbnail => t("safe-area-youtube-thumbnail"),
+            SafeArea::TikTok => "TikTok",
+            SafeArea::Reels => "Instagram Reels",
+            SafeArea::Shorts => "YouTube Shorts",
+        })
+    }
+}
+
+/// Which version of the composition the canvas in the editor shows while one
+/// is pinned to compare the edits against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompareView {
+    /// The version that was pinned.
+    A,
+    /// The version being edited.
+    #[default]
+    B,
+    /// The pinned version left of the split, the edited one right of it.
+    Split,
+}
+
+impl CompareView {
+    pub const ALL: [CompareView; 3] = [CompareView::A, CompareView::B, CompareView::Split];
+}
+
+impl Display for CompareView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CompareView::A => "A",
+            CompareView::B => "B",
+            CompareView::Split => "A | B",
+        })
+    }
+}
+
+/// The layers as they were when pinned, to draw in place of or next to the
+/// ones being edited. They still animate with the playhead, but expressions
+/// aren't worked out for them again.
+#[derive(Debug)]
+pub struct Comparison {
+    layers: Vec<Layer>,
+    layer_caches: Vec<LayerCache>,
+    pub view: CompareView,
+    /// Where the canvas is split, as a fraction of its width.
+    pub split: f32,
+}
+
+/// Styling drawn on top of, or baked into, a layer's content.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub corner_radius: f32,
+    /// Radius of the blur over an image layer, or over what is beneath a
+    /// backdrop layer, in canvas pixels.
+    pub blur: f32,
+    /// How much darker an image layer, or what is beneath a backdrop layer,
+    /// is made, from 0 to 1.
+    pub darken: f32,
+    pub border: Border,
+    pub tint: Tint,
+    pub chroma_key: ChromaKey,
+    pub distortion: Distortion,
+    pub glitch: Glitch,
+    pub kaleidoscope: Kaleidoscope,
+}
+
+/// A color applied over an image layer's pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tint {
+    pub mode: TintMode,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    /// Color the darkest parts of the image are mapped to in duotone mode,
+    /// `color` being used for the brightest.
+    #[serde(with = "ColorDef")]
+    pub shadow: Color,
+    pub amount: f32,
+}
+
+impl Tint {
+    pub const NONE: Tint = Tint {
+        mode: TintMode::None,
+        color: Color::from_rgb(1., 0.5, 0.),
+        shadow: Color::from_rgb(0.1, 0., 0.3),
+        amount: 1.,
+    };
+
+    fn is_visible(&self) -> bool {
+        self.mode != TintMode::None && self.amount > 0.
+    }
+
+    fn apply(&self, image: &mut image::RgbaImage) {
+        let color = [self.color.r, self.color.g, self.color.b];
+        let shadow = [self.shadow.r, self.shadow.g, self.shadow.b];
+
+        for pixel in image.pixels_mut() {
+            let rgb = [pixel[0], pixel[1], pixel[2]].map(|channel| channel as f32 / 255.);
+            let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+
+            for channel in 0..3 {
+                let tinted = match self.mode {
+                    TintMode::None => rgb[channel],
+                    TintMode::Multiply => rgb[channel] * color[channel],
+                    TintMode::Colorize => luma * color[channel],
+                    TintMode::Duotone => {
+                        shadow[channel] + (color[channel] - shadow[channel]) * luma
+                    }
+                };
+                let mixed = rgb[channel] + (tinted - rgb[channel]) * self.amount;
+
+                pixel[channel] = (mixed.clamp(0., 1.) * 255.).round() as u8;
+            }
+        }
+    }
+}
+
+impl Default for Tint {
+    fn default() -> Self {
+        Tint::NONE
+    }
+}
+
+/// Makes the parts of a layer close to a color transparent, e.g. the green
+/// screen behind someone filmed in front of it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChromaKey {
+    pub is_enabled: bool,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    /// How far a color's hue and saturation can be from `color` and still
+    /// be keyed out.
+    pub tolerance: f32,
+    /// How much of `color` is taken out of what's kept, e.g. the green cast
+    /// the screen throws onto hair and shoulders.
+    pub spill: f32,
+    /// How far past `tolerance` colors fade from transparent to opaque,
+    /// softening the edges.
+    pub feather: f32,
+}
+
+impl ChromaKey {
+    pub const NONE: ChromaKey = ChromaKey {
+        is_enabled: false,
+        color: Color::from_rgb(0., 1., 0.),
+        tolerance: 0.2,
+        spill: 0.5,
+        feather: 0.1,
+    };
+
+    pub fn is_visible(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn apply(&self, image: &mut image::RgbaImage) {
+        // Colors are compared by chroma alone, in YCbCr, so that the
+        // shadows and highlights on the screen are keyed like the rest of it.
+        let chroma = |[r, g, b]: [f32; 3]| {
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+
+            (luma, (b - luma) * 0.564, (r - luma) * 0.713)
+        };
+        let (_, key_cb, key_cr) = chroma([self.color.r, self.color.g, self.color.b]);
+        let key_length = key_cb.hypot(key_cr);
+        let direction = (key_length > 0.).then(|| (key_cb / key_length, key_cr / key_length));
+
+        for pixel in image.pixels_mut() {
+            let rgb = [pixel[0], pixel[1], pixel[2]].map(|channel| channel as f32 / 255.);
+            let (luma, mut cb, mut cr) = chroma(rgb);
+
+            let distance = (cb - key_cb).hypot(cr - key_cr);
+            let opacity = if distance <= self.tolerance {
+                0.
+            } else if distance >= self.tolerance + self.feather {
+                1.
+            } else {
+                let t = (distance - self.tolerance) / self.feather;
+                t * t * (3. - 2. * t)
+            };
+            pixel[3] = (pixel[3] as f32 * opacity).round() as u8;
+
+            if let Some((along_cb, along_cr)) = direction.filter(|_| self.spill > 0.) {
+                let spill = (cb * along_cb + cr * along_cr).max(0.) * self.spill;
+                cb -= spill * along_cb;
+                cr -= spill * along_cr;
+
+                let rgb = [
+                    luma + 1.403 * cr,
+                    luma - 0.344 * cb - 0.714 * cr,
+                    luma + 1.773 * cb,
+                ];
+                for (channel, value) in rgb.into_iter().enumerate() {
+                    pixel[channel] = (value.clamp(0., 1.) * 255.).round() as u8;
+                }
+            }
+        }
+    }
+}
+
+impl Default for ChromaKey {
+    fn default() -> Self {
+        ChromaKey::NONE
+    }
+}
+
+/// Mirrors a slice of an image around a center, like the view through a
+/// kaleidoscope. On an image layer it mirrors only that layer's image; in
+/// the post FX it mirrors the whole frame.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Kaleidoscope {
+    pub is_enabled: bool,
+    /// How many slices the image is cut into, every other one mirrored.
+    pub segments: u32,
+    /// Turns the slices around the center, in degrees.
+    pub rotation: f32,
+    /// Where the slices meet, as fractions of the image's width and height.
+    pub center: (f32, f32),
+}
+
+impl Kaleidoscope {
+    pub const NONE: Kaleidoscope = Kaleidoscope {
+        is_enabled: false,
+        segments: 8,
+        rotation: 0.,
+        center: (0.5, 0.5),
+    };
+
+    pub fn is_visible(&self) -> bool {
+        self.is_enabled && self.segments >= 2
+    }
+
+    pub fn apply(&self, image: &mut image::RgbaImage) {
+        let source = image.clone();
+        let (width, height) = image.dimensions();
+        let center = iced::Vector::new(self.center.0 * width as f32, self.center.1 * height as f32);
+        let slice = std::f32::consts::TAU / self.segments as f32;
+        let rotation = self.rotation.to_radians();
+
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let from_center = iced::Vector::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+            let distance = from_center.x.hypot(from_center.y);
+            // Folded into the first slice, mirroring every other one.
+            let angle = (from_center.y.atan2(from_center.x) - rotation).rem_euclid(2. * slice);
+            let angle = if angle > slice {
+                2. * slice - angle
+            } else {
+                angle
+            } + rotation;
+
+            let source_x = (center.x + distance * angle.cos()).floor();
+            let source_y = (center.y + distance * angle.sin()).floor();
+            *pixel = if source_x >= 0.
+                && source_y >= 0.
+                && source_x < width as f32
+                && source_y < height as f32
+            {
+                *source.get_pixel(source_x as u32, source_y as u32)
+            } else {
+                image::Rgba([0; 4])
+            };
+        }
+    }
+}
+
+impl Default for Kaleidoscope {
+    fn default() -> Self {
+        Kaleidoscope::NONE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TintMode {
+    #[default]
+    None,
+    /// Multiplies the image by the color, keeping its shading and hues.
+    Multiply,
+    /// Replaces the hues with the color, keeping the brightness.
+    Colorize,
+    /// Maps the brightness onto a gradient between two colors.
+    Duotone,
+}
+
+impl TintMode {
+    pub const ALL: [TintMode; 4] = [
+        TintMode::None,
+        TintMode::Multiply,
+        TintMode::Colorize,
+        TintMode::Duotone,
+    ];
+}
+
+impl Display for TintMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TintMode::None => t("none"),
+            TintMode::Multiply => t("tint-multiply"),
+            TintMode::Colorize => t("tint-colorize"),
+            TintMode::Duotone => t("tint-duotone"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Border {
+    pub width: f32,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    /// Whether the hue follows the pitch class being played, keeping the
+    /// brightness of `color`.
+    pub follows_pitch: bool,
+}
+
+impl Default for Border {
+    fn default() -> Self {
+        Border {
+            width: 0.,
+            color: Color::WHITE,
+            follows_pitch: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Color")]
+pub struct ColorDef {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+}
+
+/// Everything about an image layer that changes the pixels of its rendered
+/// image, as opposed to where it is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOptions {
+    pub crop: Crop,
+    pub flip: Flip,
+    pub sizing: Sizing,
+    pub corner_radius: f32,
+    pub tint: Tint,
+    pub chroma_key: ChromaKey,
+    pub lut: Option<Lut>,
+    pub kaleidoscope: Kaleidoscope,
+    pub blur: f32,
+    pub darken: f32,
+    /// Whether the pixels are distorted or glitched each time the layer is
+    /// drawn, which needs them decoded up front.
+    pub is_distorted: bool,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            crop: Crop::FULL,
+            flip: Flip::NONE,
+            sizing: Sizing::default(),
+            corner_radius: 0.,
+            tint: Tint::NONE,
+            chroma_key: ChromaKey::NONE,
+            lut: None,
+            kaleidoscope: Kaleidoscope::NONE,
+            blur: 0.,
+            darken: 0.,
+            is_distorted: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Flip {
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+
+impl Flip {
+    pub const NONE: Flip = Flip {
+        horizontal: false,
+        vertical: false,
+    };
+}
+
+/// What a layer's position or size was entered in. Lengths are always kept
+/// in pixels, but those entered in percent follow the canvas when it is
+/// resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Unit {
+    #[default]
+    Pixels,
+    /// Of the canvas's width for horizontal lengths, or its height for
+    /// vertical ones.
+    Percent,
+}
+
+impl Unit {
+    /// Reads a length typed in pixels, e.g. `640`, or in percent of
+    /// `canvas_length`, e.g. `50%`.
+    pub fn parse(input: &str, canvas_length: f32) -> Option<(f32, Unit)> {
+        let input = input.trim();
+
+        match input.strip_suffix('%') {
+            Some(percent) => {
+                let percent: f32 = percent.trim_end().parse().ok()?;
+
+                Some((percent / 100. * canvas_length, Unit::Percent))
+            }
+            None => Some((input.parse().ok()?, Unit::Pixels)),
+        }
+    }
+
+    /// `value` in pixels written out in this unit, to two decimal places.
+    pub fn format(self, value: f32, canvas_length: f32) -> String {
+        match self {
+            Unit::Pixels => format!("{}", (value * 100.).round() / 100.),
+            Unit::Percent => format!("{}%", (value / canvas_length * 10000.).round() / 100.),
+        }
+    }
+}
+
+/// The unit each of a layer's position and size was last entered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Units {
+    pub x: Unit,
+    pub y: Unit,
+    pub width: Unit,
+    pub height: Unit,
+}
+
+impl Units {
+    /// The unit of a position property. Other properties aren't lengths.
+    pub fn of(&self, property: Property) -> Option<Unit> {
+        match property {
+            Property::X => Some(self.x),
+            Property::Y => Some(self.y),
+            Property::Scale | Property::Opacity => None,
+        }
+    }
+
+    pub fn set(&mut self, property: Property, unit: Unit) {
+        match property {
+            Property::X => self.x = unit,
+            Property::Y => self.y = unit,
+            Property::Scale | Property::Opacity => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LayerKind {
+    Image {
+        asset: AssetId,
+        /// A single frame, unless the image is animated.
+        frames: Vec<ImageFrame>,
+        /// Size of the frames in pixels.
+        image_size: Size,
+        sizing: Sizing,
+        crop: Crop,
+        /// The color grade applied to the image, if any.
+        lut: Option<Lut>,
+    },
+    /// Stand-in for artwork the user has yet to pick, e.g. from a template.
+    Placeholder,
+    Procedural {
+        procedural: Procedural,
+        /// Textures rendered from `procedural`'s settings.
+        frames: Vec<Handle>,
+    },
+    /// A Lottie animation, drawn as vector shapes each frame.
+    Lottie { asset: AssetId, lottie: Lottie },
+    /// Part of the screen or a window, grabbed live and fitted inside the
+    /// layer.
+    Capture {
+        source: CaptureSource,
+        /// The latest frame, once one has been grabbed.
+        frame: Option<capture::Frame>,
+    },
+    /// Blurs and darkens, by the layer's appearance, whatever is beneath it
+    /// within its bounds.
+    Backdrop,
+}
+
+impl LayerKind {
+    pub fn procedural(procedural: Procedural) -> LayerKind {
+        LayerKind::Procedural {
+            frames: procedural.render(),
+            procedural,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageFrame {
+    pub handle: Handle,
+    /// How long the frame is shown for in an animated image.
+    pub delay: Duration,
+}
+
+/// The part of an image shown by a layer, as fractions of the image's size.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Crop {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Crop {
+    pub const FULL: Crop = Crop {
+        x: 0.,
+        y: 0.,
+        width: 1.,
+        height: 1.,
+    };
+
+    /// Smallest crop, so the handles never collapse onto each other.
+    const MIN_SIZE: f32 = 0.02;
+
+    /// Maps the crop onto `bounds`, the area the uncropped image is drawn in.
+    fn within(self, bounds: Rectangle) -> Rectangle {
+        Rectangle {
+            x: bounds.x + self.x * bounds.width,
+            y: bounds.y + self.y * bounds.height,
+            width: self.width * bounds.width,
+            height: self.height * bounds.height,
+        }
+    }
+
+    /// Mirrors the crop along the flipped axes, converting between the
+    /// image's own orientation and the flipped one it is drawn in.
+    fn flipped(self, flip: Flip) -> Crop {
+        Crop {
+            x: if flip.horizontal {
+                1. - self.x - self.width
+            } else {
+                self.x
+            },
+            y: if flip.vertical {
+                1. - self.y - self.height
+            } else {
+                self.y
+            },
+            ..self
+        }
+    }
+
+    /// Drags `handle` of the crop by `delta`, a fraction of the image's size.
+    fn dragged(self, handle: CropHandle, delta: iced::Vector) -> Crop {
+        let (mut left, mut top) = (self.x, self.y);
+        let (mut right, mut bottom) = (self.x + self.width, self.y + self.height);
+
+        match handle {
+            CropHandle::Move => {
+                let dx = delta.x.clamp(-left, 1. - right);
+                let dy = delta.y.clamp(-top, 1. - bottom);
+                left += dx;
+                right += dx;
+                top += dy;
+                bottom += dy;
+            }
+            CropHandle::TopLeft => {
+                left = (left + delta.x).clamp(0., right - Self::MIN_SIZE);
+                top = (top + delta.y).clamp(0., bottom - Self::MIN_SIZE);
+            }
+            CropHandle::TopRight => {
+                right = (right + delta.x).clamp(left + Self::MIN_SIZE, 1.);
+                top = (top + delta.y).clamp(0., bottom - Self::MIN_SIZE);
+            }
+            CropHandle::BottomLeft => {
+                left = (left + delta.x).clamp(0., right - Self::MIN_SIZE);
+                bottom = (bottom + delta.y).clamp(top + Self::MIN_SIZE, 1.);
+            }
+            CropHandle::BottomRight => {
+                right = (right + delta.x).clamp(left + Self::MIN_SIZE, 1.);
+                bottom = (bottom + delta.y).clamp(top + Self::MIN_SIZE, 1.);
+            }
+        }
+
+        Crop {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+}
+
+impl Default for Crop {
+    fn default() -> Self {
+        Crop::FULL
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CropHandle {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Move,
+}
+
+impl CropHandle {
+    const RADIUS: f32 = 6.;
+
+    fn hit(rectangle: Rectangle, cursor: Point) -> Option<CropHandle> {
+        let corners = [
+            (CropHandle::TopLeft, Point::new(rectangle.x, rectangle.y)),
+            (
+                CropHandle::TopRight,
+                Point::new(rectangle.x + rectangle.width, rectangle.y),
+            ),
+            (
+                CropHandle::BottomLeft,
+                Point::new(rectangle.x, rectangle.y + rectangle.height),
+            ),
+            (
+                CropHandle::BottomRight,
+                Point::new(
+                    rectangle.x + rectangle.width,
+                    rectangle.y + rectangle.height,
+                ),
+            ),
+        ];
+
+        corners
+            .into_iter()
+            .find(|(_, corner)| corner.distance(cursor) <= Self::RADIUS * 1.5)
+            .map(|(handle, _)| handle)
+            .or_else(|| rectangle.contains(cursor).then_some(CropHandle::Move))
+    }
+}
+
+/// A crop handle being dragged on the canvas.
+#[derive(Debug, Clone, Copy)]
+pub struct CropDrag {
+    handle: CropHandle,
+    origin: Point,
+    crop: Crop,
+}
+
+/// How an image is laid out inside its layer's frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Sizing {
+    /// Scaled to fit inside the frame, keeping its aspect ratio.
+    #[default]
+    Fit,
+    /// Scaled to cover the frame, keeping its aspect ratio and cropping the
+    /// overflow.
+    Fill,
+    /// Scaled to the frame, ignoring its aspect ratio.
+    Stretch,
+    /// Repeated at its original size to cover the frame.
+    Tile,
+}
+
+impl Sizing {
+    pub const ALL: [Sizing; 4] = [Sizing::Fit, Sizing::Fill, Sizing::Stretch, Sizing::Tile];
+
+    /// Where an image of `image_size` rendered by [`render_image`] is drawn for
+    /// a layer with the given frame.
+    fn image_bounds(self, frame: Rectangle, image_size: Size) -> Rectangle {
+        match self {
+            Sizing::Fit => {
+                let scale = (frame.width / image_size.width).min(frame.height / image_size.height);
+                let size = image_size * scale;
+
+                Rectangle::new(
+                    Point::new(
+                        frame.x + (frame.width - size.width) / 2.,
+                        frame.y + (frame.height - size.height) / 2.,
+                    ),
+                    size,
+                )
+            }
+            Sizing::Fill | Sizing::Stretch | Sizing::Tile => frame,
+        }
+    }
+}
+
+impl Display for Sizing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Sizing::Fit => t("sizing-fit"),
+            Sizing::Fill => t("sizing-fill"),
+            Sizing::Stretch => t("sizing-stretch"),
+            Sizing::Tile => t("sizing-tile"),
+        })
+    }
+}
+
+/// Longest side, in pixels, of the images drawn on the canvas. Larger images
+/// are previewed from a downscaled proxy, as re-rendering and uploading them
+/// at full resolution whenever a layer changes keeps the interface from
+/// responding.
+pub const PREVIEW_SIZE: u32 = 2048;
+
+fn image_decoder(contents: &[u8]) -> Option<impl ImageDecoder + '_> {
+    image::ImageReader::new(std::io::Cursor::new(contents))
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()
+}
+
+/// The pixel size of the image in `contents` the way up it is shown, read
+/// from its header without decoding it.
+pub fn image_dimensions(contents: &[u8]) -> Option<(u32, u32)> {
+    let mut decoder = image_decoder(contents)?;
+    let (width, height) = decoder.dimensions();
+
+    match decoder.orientation().ok()? {
+        ExifOrientation::Rotate90
+        | ExifOrientation::Rotate270
+        | ExifOrientation::Rotate90FlipH
+        | ExifOrientation::Rotate270FlipH => Some((height, width)),
+        _ => Some((width, height)),
+    }
+}
+
+/// Whether decoding the image in `contents` changes it from how it is
+/// stored: turning it the way up its EXIF orientation says, or converting
+/// its colors from an embedded profile other than sRGB.
+fn is_corrected(contents: &[u8]) -> bool {
+    let Some(mut decoder) = image_decoder(contents) else {
+        return false;
+    };
+
+    decoder
+        .orientation()
+        .is_ok_and(|orientation| orientation != ExifOrientation::NoTransforms)
+        || decoder
+            .icc_profile()
+            .ok()
+            .flatten()
+            .and_then(|profile| Profile::parse(&profile))
+            .is_some()
+}
+
+/// Decodes the image in `contents` the way up it is shown, with its colors
+/// in sRGB, so that photos straight from a phone or camera look as they do
+/// elsewhere.
+pub fn decode_image_file(contents: &[u8]) -> Option<image::DynamicImage> {
+    let mut decoder = image_decoder(contents)?;
+    let orientation = decoder
+        .orientation()
+        .unwrap_or(ExifOrientation::NoTransforms);
+    let profile = decoder
+        .icc_profile()
+        .ok()
+        .flatten()
+        .and_then(|profile| Profile::parse(&profile));
+
+    let mut image = image::DynamicImage::from_decoder(decoder).ok()?;
+    if let Some(profile) = profile {
+        let mut rgba = image.into_rgba8();
+        profile.convert(&mut rgba);
+        image = image::DynamicImage::ImageRgba8(rgba);
+    }
+    image.apply_orientation(orientation);
+
+    Some(image)
+}
+
+/// A copy of the image in `contents` downscaled to [`PREVIEW_SIZE`], if it is
+/// a still image larger than that.
+pub fn preview_proxy(contents: &[u8]) -> Option<image::RgbaImage> {
+    let (width, height) = image_dimensions(contents)?;
+    if width.max(height) <= PREVIEW_SIZE || animation_frames(contents).is_some() {
+        return None;
+    }
+
+    let image = decode_image_file(contents)?;
+
+    Some(image.thumbnail(PREVIEW_SIZE, PREVIEW_SIZE).to_rgba8())
+}
+
+/// [`render_image`] on a background thread, so large files do not stall the
+/// interface while they decode. Makes the image's preview proxy first if it
+/// needs one and `proxy` is not it, returning it with the frames.
+pub async fn decode_image(
+    contents: Arc<Vec<u8>>,
+    proxy: Option<Arc<image::RgbaImage>>,
+    options: ImageOptions,
+    frame: Size,
+) -> Option<(Vec<ImageFrame>, Size, Option<Arc<image::RgbaImage>>)> {
+    tokio::task::spawn_blocking(move || {
+        let proxy = proxy.or_else(|| preview_proxy(&contents).map(Arc::new));
+        let (frames, size) = render_image(&contents, proxy.as_deref(), &options, frame)?;
+
+        Some((frames, size, proxy))
+    })
+    .await
+    .ok()?
+}
+
+/// Frames already rendered from image files, so that layers showing the same
+/// file with the same options share one texture instead of decoding and
+/// uploading their own.
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    renders: Vec<Render>,
+    /// Counts lookups, to tell which render was used least recently.
+    clock: Cell<u64>,
+    hits: Cell<u64>,
+    misses: Cell<u64>,
+}
+
+#[derive(Debug)]
+struct Render {
+    /// Hash of the file, and the options and frame size it was rendered
+    /// with.
+    hash: u64,
+    options: ImageOptions,
+    frame: Size,
+    frames: Vec<ImageFrame>,
+    size: Size,
+    /// Value of the clock when the render was last looked up.
+    used: Cell<u64>,
+}
+
+impl Render {
+    /// Pixel bytes of every frame once decoded.
+    fn memory(&self) -> usize {
+        self.frames.len() * self.size.width as usize * self.size.height as usize * 4
+    }
+}
+
+impl RenderCache {
+    /// Renders kept at most, after which the least recently used are dropped.
+    /// Layers still showing them keep their frames.
+    const CAPACITY: usize = 32;
+
+    pub fn get(
+        &self,
+        hash: u64,
+        options: &ImageOptions,
+        frame: Size,
+    ) -> Option<(Vec<ImageFrame>, Size)> {
+        self.clock.set(self.clock.get() + 1);
+        let cached = self
+            .renders
+            .iter()
+            .find(|render| {
+                render.hash == hash && &render.options == options && render.frame == frame
+            })
+            .map(|render| {
+                render.used.set(self.clock.get());
+
+                (render.frames.clone(), render.size)
+            });
+
+        let counter = if cached.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.set(counter.get() + 1);
+
+        cached
+    }
+
+    /// Fraction of lookups that found their render, if there were any.
+    pub fn hit_rate(&self) -> Option<f32> {
+        let (hits, misses) = (self.hits.get(), self.misses.get());
+
+        (hits + misses > 0).then(|| hits as f32 / (hits + misses) as f32)
+    }
+
+    pub fn insert(
+        &mut self,
+        hash: u64,
+        options: ImageOptions,
+        frame: Size,
+        frames: Vec<ImageFrame>,
+        size: Size,
+    ) {
+        self.renders.retain(|render| {
+            !(render.hash == hash && render.options == options && render.frame == frame)
+        });
+        if self.renders.len() == Self::CAPACITY {
+            self.evict();
+        }
+        self.renders.push(Render {
+            hash,
+            options,
+            frame,
+            frames,
+            size,
+            used: Cell::new(self.clock.get()),
+        });
+    }
+
+    /// Pixel bytes of every cached render once decoded.
+    pub fn memory(&self) -> usize {
+        self.renders.iter().map(Render::memory).sum()
+    }
+
+    /// Drops the least recently used renders until the cache holds at most
+    /// `budget` bytes.
+    pub fn trim(&mut self, budget: usize) {
+        while self.memory() > budget && !self.renders.is_empty() {
+            self.evict();
+        }
+    }
+
+    fn evict(&mut self) {
+        if let Some(index) = self
+            .renders
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, render)| render.used.get())
+            .map(|(index, _)| index)
+        {
+            self.renders.remove(index);
+        }
+    }
+}
+
+/// Decodes `contents` and renders the frames to draw for a layer with the
+/// given options and frame size, returning them with their pixel size.
+/// Renders from `proxy` instead if the image has one, except when tiling,
+/// where the image is drawn at its own pixel size.
+///
+/// Cropping, fill, tile and rounded corners are rendered ahead of time instead
+/// of clipped in `draw`, as canvas images are not clipped to
+/// `Frame::with_clip` regions. Flips are too, as negative scales are lost when
+/// transforming images.
+pub fn render_image(
+    contents: &[u8],
+    proxy: Option<&image::RgbaImage>,
+    options: &ImageOptions,
+    frame: Size,
+) -> Option<(Vec<ImageFrame>, Size)> {
+    if let Some(frames) = animation_frames(contents) {
+        let mut size = Size::ZERO;
+        let frames = frames
+            .into_iter()
+            .map(|animation_frame| {
+                let delay = Duration::from(animation_frame.delay());
+                let image = image::DynamicImage::ImageRgba8(animation_frame.into_buffer());
+                let (handle, rendered_size) = render_frame(image, options, frame);
+                size = rendered_size;
+
+                ImageFrame { handle, delay }
+            })
+            .collect();
+
+        return Some((frames, size));
+    }
+
+    let is_unchanged = options.crop == Crop::FULL
+        && options.flip == Flip::NONE
+        && options.corner_radius <= 0.
+        && !options.tint.is_visible()
+        && !options.chroma_key.is_visible()
+        && !options.lut.as_ref().is_some_and(Lut::is_visible)
+        && !options.kaleidoscope.is_visible()
+        && options.blur <= 0.
+        && options.darken <= 0.
+        && !options.is_distorted
+        && matches!(options.sizing, Sizing::Fit | Sizing::Stretch);
+
+    if let Some(proxy) = proxy.filter(|_| options.sizing != Sizing::Tile) {
+        let (handle, size) = if is_unchanged {
+            let size = Size::new(proxy.width() as f32, proxy.height() as f32);
+
+            (
+                Handle::from_rgba(proxy.width(), proxy.height(), proxy.as_raw().clone()),
+                size,
+            )
+        } else {
+            render_frame(
+                image::DynamicImage::ImageRgba8(proxy.clone()),
+                options,
+                frame,
+            )
+        };
+
+        return Some((
+            vec![ImageFrame {
+                handle,
+                delay: Duration::ZERO,
+            }],
+            size,
+        ));
+    }
+
+    if is_unchanged && !is_corrected(contents) {
+        // The image is drawn as it is, so only its header is read here and
+        // the full decode is left to the renderer.
+        let (width, height) = image_dimensions(contents)?;
+        let handle = Handle::from_bytes(contents.to_vec());
+
+        return Some((
+            vec![ImageFrame {
+                handle,
+                delay: Duration::ZERO,
+            }],
+            Size::new(width as f32, height as f32),
+        ));
+    }
+
+    let image = decode_image_file(contents)?;
+    let (handle, size) = render_frame(image, options, frame);
+
+    Some((
+        vec![ImageFrame {
+            handle,
+            delay: Duration::ZERO,
+        }],
+        size,
+    ))
+}
+
+/// The frames of an animated GIF, PNG or WebP image, unless it has only one.
+fn animation_frames(contents: &[u8]) -> Option<Vec<image::Frame>> {
+    let cursor = || std::io::Cursor::new(contents);
+    let frames = match image::guess_format(contents).ok()? {
+        image::ImageFormat::Gif => image::codecs::gif::GifDecoder::new(cursor())
+            .ok()?
+            .into_frames(),
+        image::ImageFormat::Png => {
+            let decoder = image::codecs::png::PngDecoder::new(cursor()).ok()?;
+            if !decoder.is_apng().ok()? {
+                return None;
+            }
+            decoder.apng().ok()?.into_frames()
+        }
+        image::ImageFormat::WebP => {
+            let decoder = image::codecs::webp::WebPDecoder::new(cursor()).ok()?;
+            if !decoder.has_animation() {
+                return None;
+            }
+            decoder.into_frames()
+        }
+        _ => return None,
+    }
+    .collect_frames()
+    .ok()?;
+
+    (frames.len() > 1).then_some(frames)
+}
+
+fn render_frame(image: image::DynamicImage, options: &ImageOptions, frame: Size) -> (Handle, Size) {
+    let rendered = render_pixels(image, options.clone(), frame);
+    let size = Size::new(rendered.width() as f32, rendered.height() as f32);
+
+    (
+        Handle::from_rgba(rendered.width(), rendered.height(), rendered.into_raw()),
+        size,
+    )
+}
+
+/// The pixels of `image` cropped, flipped, sized, keyed, graded, mirrored,
+/// tinted, blurred, darkened and rounded off for a layer with the given
+/// options and frame size.
+pub fn render_pixels(
+    image: image::DynamicImage,
+    options: ImageOptions,
+    frame: Size,
+) -> image::RgbaImage {
+    let ImageOptions {
+        crop,
+        flip,
+        sizing,
+        corner_radius,
+        tint,
+        chroma_key,
+        lut,
+        kaleidoscope,
+        blur,
+        darken,
+        is_distorted: _,
+    } = options;
+    let (full_width, full_height) = image.dimensions();
+
+    let image = if crop == Crop::FULL {
+        image
+    } else {
+        let x = (crop.x * full_width as f32).round() as u32;
+        let y = (crop.y * full_height as f32).round() as u32;
+        image.crop_imm(
+            x.min(full_width - 1),
+            y.min(full_height - 1),
+            ((crop.width * full_width as f32).round() as u32).max(1),
+            ((crop.height * full_height as f32).round() as u32).max(1),
+        )
+    };
+    let image = if flip.horizontal {
+        image.fliph()
+    } else {
+        image
+    };
+    let image = if flip.vertical { image.flipv() } else { image };
+    let (width, height) = image.dimensions();
+
+    let mut rendered = match sizing {
+        Sizing::Fit | Sizing::Stretch => image.to_rgba8(),
+        Sizing::Fill => {
+            let scale = (frame.width / width as f32).max(frame.height / height as f32);
+            let crop_width = ((frame.width / scale).round() as u32).clamp(1, width);
+            let crop_height = ((frame.height / scale).round() as u32).clamp(1, height);
+
+            image
+                .crop_imm(
+                    (width - crop_width) / 2,
+                    (height - crop_height) / 2,
+                    crop_width,
+                    crop_height,
+                )
+                .to_rgba8()
+        }
+        Sizing::Tile => {
+            let mut tiled = image::RgbaImage::new(
+                (frame.width.ceil() as u32).max(1),
+                (frame.height.ceil() as u32).max(1),
+            );
+            image::imageops::tile(&mut tiled, &image.to_rgba8());
+
+            tiled
+        }
+    };
+    let size = Size::new(rendered.width() as f32, rendered.height() as f32);
+    let drawn = match sizing {
+        Sizing::Fit => size * (frame.width / size.width).min(frame.height / size.height),
+        Sizing::Fill | Sizing::Stretch | Sizing::Tile => frame,
+    };
+
+    if chroma_key.is_visible() {
+        chroma_key.apply(&mut rendered);
+    }
+    if let Some(lut) = lut.filter(Lut::is_visible) {
+        lut.apply(&mut rendered);
+    }
+    if kaleidoscope.is_visible() {
+        kaleidoscope.apply(&mut rendered);
+    }
+    if tint.is_visible() {
+        tint.apply(&mut rendered);
+    }
+    if blur > 0. {
+        rendered = image::imageops::fast_blur(&rendered, blur * size.width / drawn.width);
+    }
+    if darken > 0. {
+        for pixel in rendered.pixels_mut() {
+            for channel in &mut pixel.0[..3] {
+                *channel = (*channel as f32 * (1. - darken.min(1.))).round() as u8;
+            }
+        }
+    }
+
+    if corner_radius > 0. {
+        round_corners(
+            &mut rendered,
+            corner_radius * size.width / drawn.width,
+            corner_radius * size.height / drawn.height,
+        );
+    }
+
+    rendered
+}
+
+/// Fades out the pixels outside of elliptical corners with radii `rx` and `ry`,
+/// anti-aliasing the edge.
+fn round_corners(image: &mut image::RgbaImage, rx: f32, ry: f32) {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let rx = rx.min(width / 2.);
+    let ry = ry.min(height / 2.);
+    if rx < 0.5 || ry < 0.5 {
+        return;
+    }
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let px = x as f32 + 0.5;
+        let py = y as f32 + 0.5;
+        let cx = px.clamp(rx, width - rx);
+        let cy = py.clamp(ry, height - ry);
+        if cx == px || cy == py {
+            continue;
+        }
+
+        let distance = ((px - cx) / rx).hypot((py - cy) / ry);
+        let coverage = ((1. - distance) * rx.min(ry) + 0.5).clamp(0., 1.);
+        pixel[3] = (pixel[3] as f32 * coverage).round() as u8;
+    }
+}
+
+impl Layer {
+    pub fn asset(&self) -> Option<AssetId> {
+        match &self.kind {
+            LayerKind::Image { asset, .. } | LayerKind::Lottie { asset, .. } => Some(*asset),
+            LayerKind::Placeholder
+            | LayerKind::Procedural { .. }
+            | LayerKind::Capture { .. }
+            | LayerKind::Backdrop => None,
+        }
+    }
+
+    pub fn sizing(&self) -> Option<Sizing> {
+        match &self.kind {
+            LayerKind::Image { sizing, .. } => Some(*sizing),
+            LayerKind::Placeholder
+            | LayerKind::Procedural { .. }
+            | LayerKind::Lottie { .. }
+            | LayerKind::Capture { .. }
+            | LayerKind::Backdrop => None,
+        }
+    }
+
+    pub fn crop(&self) -> Option<Crop> {
+        match &self.kind {
+            LayerKind::Image { crop, .. } => Some(*crop),
+            LayerKind::Placeholder
+            | LayerKind::Procedural { .. }
+            | LayerKind::Lottie { .. }
+            | LayerKind::Capture { .. }
+            | LayerKind::Backdrop => None,
+        }
+    }
+
+    pub fn set_crop(&mut self, new_crop: Crop) {
+        if let LayerKind::Image { crop, .. } = &mut self.kind {
+            *crop = new_crop;
+        }
+    }
+
+    pub fn lut(&self) -> Option<&Lut> {
+        match &self.kind {
+            LayerKind::Image { lut, .. } => lut.as_ref(),
+            LayerKind::Placeholder
+            | LayerKind::Procedural { .. }
+            | LayerKind::Lottie { .. }
+            | LayerKind::Capture { .. }
+            | LayerKind::Backdrop => None,
+        }
+    }
+
+    pub fn set_lut(&mut self, new_lut: Option<Lut>) {
+        if let LayerKind::Image { lut, .. } = &mut self.kind {
+            *lut = new_lut;
+        }
+    }
+
+    /// Where the layer's image ends up on the canvas.
+    pub fn image_bounds(&self) -> Rectangle {
+        match &self.kind {
+            LayerKind::Image {
+                image_size, sizing, ..
+            } => sizing.image_bounds(self.bounds(), *image_size),
+            LayerKind::Capture {
+                frame: Some((_, size)),
+                ..
+            } => Sizing::Fit.image_bounds(self.bounds(), *size),
+            LayerKind::Capture { frame: None, .. }
+            | LayerKind::Placeholder
+            | LayerKind::Procedural { .. }
+            | LayerKind::Lottie { .. }
+            | LayerKind::Backdrop => self.bounds(),
+        }
+    }
+
+    pub fn image_options(&self) -> Option<ImageOptions> {
+        match &self.kind {
+            LayerKind::Image {
+                sizing, crop, lut, ..
+            } => Some(ImageOptions {
+                crop: *crop,
+                flip: self.flip,
+                sizing: *sizing,
+                corner_radius: self.appearance.corner_radius,
+                tint: self.appearance.tint,
+                chroma_key: self.appearance.chroma_key,
+                lut: lut.clone(),
+                kaleidoscope: self.appearance.kaleidoscope,
+                blur: self.appearance.blur,
+                darken: self.appearance.darken,
+                is_distorted: self.appearance.distortion.is_visible()
+                    || self.appearance.glitch.is_visible(),
+            }),
+            LayerKind::Placeholder
+            | LayerKind::Procedural { .. }
+            | LayerKind::Lottie { .. }
+            | LayerKind::Capture { .. }
+            | LayerKind::Backdrop => None,
+        }
+    }
+
+    /// Keeps the lengths entered in percent at the same share of a canvas
+    /// whose width and height changed by `scale_x` and `scale_y`, keyframes
+    /// included.
+    pub fn rescale(&mut self, scale_x: f32, scale_y: f32) {
+        for (property, scale) in [(Property::X, scale_x), (Property::Y, scale_y)] {
+            if self.units.of(property) != Some(Unit::Percent) {
+                continue;
+            }
+            self.set_property(property, self.property(property) * scale);
+            if let Some(track) = self.keyframes.track_mut(property) {
+                for keyframe in &mut track.keyframes {
+                    keyframe.value *= scale;
+                }
+            }
+        }
+        if self.units.width == Unit::Percent {
+            self.width *= scale_x;
+        }
+        if self.units.height == Unit::Percent {
+            self.height *= scale_y;
+        }
+    }
+
+    /// Moves and resizes the layer for a canvas `scale_x` and `scale_y` times
+    /// the size, whatever its units. Its center stays in the same place
+    /// relative to the canvas and it keeps its aspect ratio, shrinking to
+    /// fit a canvas that got narrower or shorter.
+    pub fn fit(&mut self, scale_x: f32, scale_y: f32) {
+        let scale = scale_x.min(scale_y);
+        let size = Size::new(self.width, self.height);
+
+        for (property, canvas_scale, old, new) in [
+            (Property::X, scale_x, size.width, size.width * scale),
+            (Property::Y, scale_y, size.height, size.height * scale),
+        ] {
+            let moved = |value: f32| (value + old / 2.) * canvas_scale - new / 2.;
+
+            self.set_property(property, moved(self.property(property)));
+            if let Some(track) = self.keyframes.track_mut(property) {
+                for keyframe in &mut track.keyframes {
+                    keyframe.value = moved(keyframe.value);
+                }
+            }
+        }
+        self.width = size.width * scale;
+        self.height = size.height * scale;
+    }
+
+    /// The value of `property` when it is not animated.
+    pub fn property(&self, property: Property) -> f32 {
+        match property {
+            Property::X => self.x,
+            Property::Y => self.y,
+            Property::Scale => self.scale,
+            Property::Opacity => self.opacity,
+        }
+    }
+
+    pub fn set_property(&mut self, property: Property, value: f32) {
+        match property {
+            Property::X => self.x = value,
+            Property::Y => self.y = value,
+            Property::Scale => self.scale = value,
+            Property::Opacity => self.opacity = value.clamp(0., 1.),
+        }
+    }
+
+    /// The value of `property` at `time` seconds into the track, following
+    /// its keyframes if it is animated.
+    pub fn value_at(&self, property: Property, time: f32) -> f32 {
+        self.keyframes
+            .value(property, time)
+            .unwrap_or_else(|| self.property(property))
+    }
+
+    /// How far the layer is moved from its resting placement at the
+    /// playhead, by its keyframes, animation and transitions, its own and
+    /// its scene's.
+    pub fn motion(&self, playhead: &Playhead) -> Motion {
+        let scene = self.timing.scene.map_or(Motion::NONE, |scene| {
+            scenes::motion(&playhead.scenes, scene, playhead.time, self.bounds())
+        });
+
+        self.keyframed_motion(playhead.time.as_secs_f32())
+            .and(self.animation.motion(&playhead.for_layer(self)))
+            .and(self.timing.motion(playhead.time, self.bounds()))
+            .and(scene)
+    }
+
+    /// Whether the layer is shown at the playhead, by its timing and scene.
+    pub fn is_shown(&self, playhead: &Playhead) -> bool {
+        self.timing.is_visible(playhead.time)
+            && self
+                .timing
+                .scene
+                .is_none_or(|scene| scenes::is_shown(&playhead.scenes, scene, playhead.time))
+    }
+
+    /// The placement of the layer at `time` according to its keyframes,
+    /// relative to its resting placement.
+    fn keyframed_motion(&self, time: f32) -> Motion {
+        Motion {
+            offset: iced::Vector::new(
+                self.value_at(Property::X, time) - self.x,
+                self.value_at(Property::Y, time) - self.y,
+            ),
+            scale: self.value_at(Property::Scale, time),
+            rotation: 0.,
+            opacity: self.value_at(Property::Opacity, time).clamp(0., 1.),
+        }
+    }
+
+    /// Whether anything about the layer's motion follows the audio.
+    pub fn is_audio_reactive(&self) -> bool {
+        self.animation.preset.is_audio_reactive()
+            || (self.appearance.distortion.is_visible() && self.appearance.distortion.follows_bass)
+            || (self.appearance.glitch.is_visible()
+                && self.appearance.glitch.trigger.is_audio_reactive())
+            || self
+                .procedural()
+                .is_some_and(|procedural| procedural.is_audio_reactive())
+    }
+
+    pub fn is_animated(&self) -> bool {
+        match &self.kind {
+            LayerKind::Image { frames, .. } => frames.len() > 1,
+            LayerKind::Lottie { .. } | LayerKind::Capture { .. } => true,
+            LayerKind::Placeholder | LayerKind::Procedural { .. } | LayerKind::Backdrop => false,
+        }
+    }
+
+    pub fn procedural(&self) -> Option<Procedural> {
+        match &self.kind {
+            LayerKind::Procedural { procedural, .. } => Some(procedural.clone()),
+            LayerKind::Image { .. }
+            | LayerKind::Placeholder
+            | LayerKind::Lottie { .. }
+            | LayerKind::Capture { .. }
+            | LayerKind::Backdrop => None,
+        }
+    }
+
+    pub fn capture_source(&self) -> Option<&CaptureSource> {
+        match &self.kind {
+            LayerKind::Capture { source, .. } => Some(source),
+            LayerKind::Image { .. }
+            | LayerKind::Placeholder
+            | LayerKind::Procedural { .. }
+            | LayerKind::Lottie { .. }
+            | LayerKind::Backdrop => None,
+        }
+    }
+
+    /// Points a screen capture layer at another source, dropping the frame
+    /// of the old one.
+    pub fn set_capture_source(&mut self, new_source: CaptureSource) {
+        if let LayerKind::Capture { source, frame } = &mut self.kind {
+            if *source != new_source {
+                *source = new_source;
+                *frame = None;
+            }
+        }
+    }
+
+    /// Changes the settings of a procedural layer and re-renders it.
+    pub fn set_procedural(&mut self, new_procedural: Procedural) {
+        if let LayerKind::Procedural { .. } = self.kind {
+            self.kind = LayerKind::procedural(new_procedural);
+        }
+    }
+
+    /// Changes how the image is laid out. The image has to be re-rendered with
+    /// [`render_image`] afterwards.
+    pub fn set_sizing(&mut self, new_sizing: Sizing) {
+        if let LayerKind::Image { sizing, .. } = &mut self.kind {
+            *sizing = new_sizing;
+        }
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        Rectangle {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The point in the track being previewed, which animated layers are drawn at.
+#[derive(Debug, Clone, Default)]
+pub struct Playhead {
+    pub time: Duration,
+    /// Every audio track, by [`Source::index`], if it has been analyzed.
+    pub tracks: Arc<[Option<Placement>]>,
+    pub source: Source,
+    pub channel: Channel,
+    pub markers: Arc<[Marker]>,
+    pub scenes: Arc<[Scene]>,
+}
+
+impl Playhead {
+    /// The playhead as seen by `layer`, moved by its time offset without
+    /// going before the start and following its audio track and channel.
+    pub fn for_layer(&self, layer: &Layer) -> Playhead {
+        let time = self.time.as_secs_f32() + layer.timing.offset;
+
+        Playhead {
+            time: Duration::from_secs_f32(time.max(0.)),
+            tracks: self.tracks.clone(),
+            source: layer.audio_source,
+            channel: layer.audio_channel,
+            markers: self.markers.clone(),
+            scenes: self.scenes.clone(),
+        }
+    }
+
+    /// The followed track and the playhead's position in its audio file.
+    fn audio_time(&self) -> Option<(&Placement, Duration)> {
+        let track = self.tracks.get(self.source.index())?.as_ref()?;
+
+        Some((track, track.audio_time(self.time)?))
+    }
+
+    pub fn bass(&self) -> f32 {
+        self.audio_time().map_or(0., |(track, time)| {
+            (track.analysis.bass(self.channel, time) * track.amplitude).min(1.)
+        })
+    }
+
+    pub fn pitch(&self) -> Pitch {
+        self.audio_time()
+            .map_or(Pitch::default(), |(track, time)| track.analysis.pitch(time))
+    }
+
+    pub fn tempo(&self) -> Option<f32> {
+        let (track, _) = self.audio_time()?;
+
+        track.analysis.tempo(self.channel)
+    }
+
+    /// RMS loudness of the loudest channel, in decibels relative to full
+    /// scale.
+    pub fn decibels(&self) -> Option<f32> {
+        let (track, time) = self.audio_time()?;
+        let rms = track
+            .analysis
+            .levels(time)
+            .iter()
+            .map(|level| level.rms)
+            .fold(0., f32::max);
+
+        Some(Level::decibels(rms * track.amplitude))
+    }
+
+    /// Loudest sample at the playhead, relative to the loudest of the
+    /// followed track.
+    pub fn loudness(&self) -> f32 {
+        self.peaks(Duration::ZERO, 1)[0]
+    }
+
+    /// The peaks of the followed track over `span` around the playhead, in
+    /// `count` slices. Slices before the start or after the end are silent.
+    pub fn peaks(&self, span: Duration, count: usize) -> Vec<f32> {
+        let Some((track, time)) = self.audio_time() else {
+            return vec![0.; count];
+        };
+        let peaks = track.analysis.peaks();
+        let per_second = Analysis::LEVELS_PER_SECOND;
+        let start = (time.as_secs_f32() - span.as_secs_f32() / 2.) * per_second;
+        let slice = span.as_secs_f32() * per_second / count as f32;
+
+        (0..count)
+            .map(|index| {
+                let from = start + index as f32 * slice;
+                let to = (from + slice).max(from + 1.);
+                if to <= 0. {
+                    return 0.;
+                }
+
+                peaks
+                    .get(from.max(0.) as usize..(to as usize).min(peaks.len()))
+                    .unwrap_or_default()
+                    .iter()
+                    .copied()
+                    .fold(0., f32::max)
+                    * track.amplitude
+            })
+            .map(|peak: f32| peak.min(1.))
+            .collect()
+    }
+
+    /// How far into the followed track the playhead is, from 0 to 1.
+    pub fn progress(&self) -> Option<f32> {
+        let (track, time) = self.audio_time()?;
+        let duration = track.analysis.duration.as_secs_f32();
+
+        (duration > 0.).then(|| (time.as_secs_f32() / duration).min(1.))
+    }
+
+    /// The name of the marker the playhead is past.
+    pub fn chapter(&self) -> Option<&str> {
+        markers::current(&self.markers, self.time.as_secs_f32())
+            .map(|index| self.markers[index].name.as_str())
+    }
+
+    /// Time left until the end of the followed track.
+    pub fn remaining(&self) -> Option<Duration> {
+        let (track, time) = self.audio_time()?;
+
+        Some(track.analysis.duration.saturating_sub(time))
+    }
+
+    pub fn since_beat(&self) -> Option<f32> {
+        let (track, time) = self.audio_time()?;
+
+        track.analysis.since_beat(self.channel, time)
+    }
+
+    /// Integral of the loudness since the track started on the timeline,
+    /// rather than since the start of its audio file.
+    pub fn accumulated_level(&self) -> f32 {
+        self.audio_time().map_or(0., |(track, time)| {
+            let start = Duration::from_secs_f32(track.start.max(0.));
+            let analysis = &track.analysis;
+
+            (analysis.accumulated_level(self.channel, time)
+                - analysis.accumulated_level(self.channel, start))
+                * track.amplitude
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CanvasState {
+    pub layers: Vec<Layer>,
+    /// Index of the layer whose crop is being edited on the canvas. It is
+    /// drawn uncropped, with the crop shown on top of it.
+    pub cropping: Option<usize>,
+    pub playhead: Playhead,
+    /// Guides drawn over the canvas in the editor, and never in the output.
+    pub safe_area: SafeArea,
+    /// Effects over the whole of the output.
+    pub post_fx: PostFx,
+    /// The version of the layers pinned as "A", while comparing.
+    pub comparison: Option<Comparison>,
+    background_cache: canvas::Cache,
+    /// One for each layer, in the same order.
+    layer_caches: Vec<LayerCache>,
+    /// The frame finished with the post FX, drawn in place of the layers
+    /// while there are effects that need its pixels. Comparisons are drawn
+    /// without them.
+    finished: Option<Finished>,
+    /// How long the last `draw` took and how many geometries it returned,
+    /// for the performance overlay.
+    draw_time: Cell<Duration>,
+    draw_calls: Cell<usize>,
+}
+
+/// The layers at the playhead, rendered in software so that the post FX can
+/// be applied to their pixels.
+#[derive(Debug)]
+struct Finished {
+    editor: Handle,
+    /// `None` while the output shows the same layers as the editor.
+    output: Option<Handle>,
+    /// What the frames were rendered with, besides the layers' drawings, to
+    /// tell when they have to be rendered again.
+    post_fx: PostFx,
+    canvas: Size,
+    visibility: Vec<Visibility>,
+    visible: usize,
+}
+
+/// A layer's drawing, kept from one frame to the next while it looks the
+/// same.
+#[derive(Debug, Default)]
+struct LayerCache {
+    cache: canvas::Cache,
+    /// What the layer was last drawn with, or `None` if it is hidden.
+    drawn: Option<Drawn>,
+    /// The playhead as seen by the layer when it was last drawn.
+    playhead: Playhead,
+}
+
+/// Everything a layer's drawing depends on, compared between frames to tell
+/// whether it has to be redrawn.
+#[derive(Debug, Clone, PartialEq)]
+struct Drawn {
+    motion: Motion,
+    bounds: Rectangle,
+    image_bounds: Rectangle,
+    content: Content,
+    appearance: Appearance,
+    /// Only set for borders following the pitch.
+    pitch_class: Option<usize>,
+    /// How far the image's pixels are moved and how many seconds into the
+    /// distortion it is, if it is distorted.
+    distortion: Option<(f32, f32)>,
+    /// How strongly the image glitches and how many seconds in, if it does.
+    glitch: Option<(f32, f32)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    /// The frame of the image shown and where it is drawn.
+    Image(Option<Handle>, Rectangle),
+    Placeholder(String),
+    /// The settings, and the playhead when the content animates.
+    Procedural(Procedural, Option<Duration>),
+    /// The animation, and how many seconds into it the layer shows.
+    Lottie(Lottie, f32),
+    Backdrop,
+}
+
+impl CanvasState {
+    pub fn new() -> CanvasState {
+        CanvasState {
+            layers: vec![],
+            cropping: None,
+            playhead: Playhead::default(),
+            safe_area: SafeArea::None,
+            post_fx: PostFx::NONE,
+            comparison: None,
+            background_cache: canvas::Cache::default(),
+            layer_caches: vec![],
+            finished: None,
+            draw_time: Cell::new(Duration::ZERO),
+            draw_calls: Cell::new(0),
+        }
+    }
+
+    pub fn draw_time(&self) -> Duration {
+        self.draw_time.get()
+    }
+
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls.get()
+    }
+
+    /// Pins the layers as they are now as "A", keeping how an earlier
+    /// comparison was being viewed.
+    pub fn pin_comparison(&mut self) {
+        let (view, split) = self
+            .comparison
+            .as_ref()
+            .map_or((CompareView::default(), 0.5), |comparison| {
+                (comparison.view, comparison.split)
+            });
+
+        self.comparison = Some(Comparison {
+            layers: self.layers.clone(),
+            layer_caches: vec![],
+            view,
+            split,
+        });
+    }
+
+    /// Catches the layers up with the playhead, only redrawing those that
+    /// look different than they did, on a canvas of size `canvas`. Returns
+    /// how many layers are visible and how many of those were redrawn.
+    pub fn update(&mut self, canvas: Size) -> (usize, usize) {
+        let drawn: Vec<_> = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| self.drawn(Some(index), layer))
+            .collect();
+        let pinned: Option<Vec<_>> = self.comparison.as_ref().map(|comparison| {
+            comparison
+                .layers
+                .iter()
+                .map(|layer| self.drawn(None, layer))
+                .collect()
+        });
+
+        let (mut visible, mut redrawn) = catch_up(&mut self.layer_caches, drawn);
+        if let (Some(comparison), Some(pinned)) = (&mut self.comparison, pinned) {
+            let (pinned_visible, pinned_redrawn) = catch_up(&mut comparison.layer_caches, pinned);
+            visible += pinned_visible;
+            redrawn += pinned_redrawn;
+        }
+        self.finish(canvas, visible, redrawn > 0);
+
+        (visible, redrawn)
+    }
+
+    /// Long side of the finished frames shown while editing, in pixels,
+    /// which are rendered in software on every frame.
+    const FINISHED_PIXELS: f32 = 1280.;
+
+    /// Renders the finished frames again if the layers or the post FX have
+    /// changed since they were.
+    fn finish(&mut self, canvas: Size, visible: usize, is_redrawn: bool) {
+        let has_backdrop = self
+            .layers
+            .iter()
+            .zip(&self.layer_caches)
+            .any(|(layer, cache)| {
+                matches!(layer.kind, LayerKind::Backdrop)
+                    && cache.drawn.as_ref().is_some_and(|drawn| {
+                        drawn.appearance.blur > 0. || drawn.appearance.darken > 0.
+                    })
+            });
+        if !(self.post_fx.is_filtered() || has_backdrop) || self.comparison.is_some() {
+            self.finished = None;
+            return;
+        }
+        let visibility: Vec<_> = self.layers.iter().map(|layer| layer.visibility).collect();
+        let is_current = self.finished.as_ref().is_some_and(|finished| {
+            finished.post_fx == self.post_fx
+                && finished.canvas == canvas
+                && finished.visibility == visibility
+                && finished.visible == visible
+        });
+        if is_current && !is_redrawn {
+            return;
+        }
+
+        let scale = (Self::FINISHED_PIXELS / canvas.width.max(canvas.height)).min(1.);
+        let handle = |image: image::RgbaImage| {
+            Handle::from_rgba(image.width(), image.height(), image.into_raw())
+        };
+        let editor = handle(self.render(canvas, scale, Visibility::is_in_editor));
+        let is_output_different = visibility
+            .iter()
+            .any(|visibility| visibility.is_in_editor() != visibility.is_in_output(false));
+        let output = is_output_different.then(|| {
+            handle(self.render(canvas, scale, |visibility| visibility.is_in_output(false)))
+        });
+
+        self.finished = Some(Finished {
+            editor,
+            output,
+            post_fx: self.post_fx.clone(),
+            canvas,
+            visibility,
+            visible,
+        });
+    }
+
+    /// The frame at the playhead with the layers meant for thumbnails,
+    /// finished with the post FX and rendered at `size` pixels. A thumbnail
+    /// of another shape than the canvas is cropped to fill it.
+    pub fn render_thumbnail(&self, canvas: Size, size: Size<u32>) -> image::RgbaImage {
+        let scale = (size.width as f32 / canvas.width).max(size.height as f32 / canvas.height);
+        let image = self.render(canvas, scale, |visibility| visibility.is_in_output(true));
+        let (width, height) = (
+            size.width.min(image.width()),
+            size.height.min(image.height()),
+        );
+
+        image::imageops::crop_imm(
+            &image,
+            (image.width() - width) / 2,
+            (image.height() - height) / 2,
+            width,
+            height,
+        )
+        .to_image()
+    }
+
+    /// The layers shown by `is_shown` finished with the post FX, at `scale`
+    /// pixels per canvas pixel.
+    fn render(
+        &self,
+        canvas: Size,
+        scale: f32,
+        is_shown: impl Fn(Visibility) -> bool,
+    ) -> image::RgbaImage {
+        let mut layers = self
+            .layers
+            .iter()
+            .zip(&self.layer_caches)
+            .filter(|(layer, _)| is_shown(layer.visibility))
+            .filter_map(|(layer, cache)| Some((layer, cache.drawn.as_ref()?, &cache.playhead)));
+        let mut below: Option<image::RgbaImage> = None;
+
+        // Drawn up to each backdrop, which then blurs what has been drawn so
+        // far, and on from there over it.
+        loop {
+            let mut backdrop = None;
+            let mut image = offscreen::render(canvas, scale, |frame| {
+                match below.take() {
+                    Some(below) => frame.draw_image(
+                        Rectangle::with_size(canvas),
+                        canvas::Image::new(Handle::from_rgba(
+                            below.width(),
+                            below.height(),
+                            below.into_raw(),
+                        )),
+                    ),
+                    None => frame.fill_rectangle(Point::ORIGIN, canvas, Color::BLACK),
+                }
+                for (layer, drawn, playhead) in layers.by_ref() {
+                    if matches!(layer.kind, LayerKind::Backdrop) {
+                        backdrop = Some(drawn);
+                        break;
+                    }
+                    frame.with_save(|frame| draw_layer(frame, layer, drawn, playhead));
+                }
+            });
+
+            match backdrop {
+                Some(drawn) => {
+                    apply_backdrop(&mut image, canvas, scale, drawn);
+                    below = Some(image);
+                }
+                None => {
+                    self.post_fx.apply(&mut image, canvas);
+                    return image;
+                }
+            }
+        }
+    }
+
+    /// What the layer at `index` looks like at the playhead, unless it is
+    /// hidden. Pinned layers have no index.
+    fn drawn(&self, index: Option<usize>, layer: &Layer) -> Option<(Drawn, Playhead)> {
+        let is_cropping = index.is_some() && self.cropping == index;
+        if !is_cropping && !layer.is_shown(&self.playhead) {
+            return None;
+        }
+        let playhead = self.playhead.for_layer(layer);
+        // The layer being cropped stays still so the handles line up with it.
+        let motion = if is_cropping {
+            Motion {
+                opacity: layer.opacity,
+                ..Motion::NONE
+            }
+        } else {
+            layer.motion(&self.playhead)
+        };
+        if motion.opacity <= 0. {
+            return None;
+        }
+
+        let content = match &layer.kind {
+            LayerKind::Image {
+                frames,
+                image_size,
+                sizing,
+                ..
+            } => Content::Image(
+                layer
+                    .playback
+                    .frame(frames, playhead.time)
+                    .map(|image| image.handle.clone()),
+                sizing.image_bounds(layer.bounds(), *image_size),
+            ),
+            LayerKind::Placeholder => Content::Placeholder(layer.name.clone()),
+            LayerKind::Procedural { procedural, .. } => Content::Procedural(
+                procedural.clone(),
+                procedural.is_animated().then_some(playhead.time),
+            ),
+            LayerKind::Capture { frame, .. } => Content::Image(
+                frame.as_ref().map(|(handle, _)| handle.clone()),
+                layer.image_bounds(),
+            ),
+            // Played from when the layer appears, so that it can be timed
+            // like any other element.
+            LayerKind::Lottie { lottie, .. } => Content::Lottie(
+                lottie.clone(),
+                layer.playback.position(
+                    lottie.duration(),
+                    playhead.time.as_secs_f32() - layer.timing.start.max(0.),
+                ),
+            ),
+            LayerKind::Backdrop => Content::Backdrop,
+        };
+        let distortion = layer.appearance.distortion;
+        let distortion = (matches!(content, Content::Image(..)) && distortion.is_visible())
+            .then(|| (distortion.amplitude(&playhead), playhead.time.as_secs_f32()));
+        let glitch = layer.appearance.glitch;
+        let glitch = (matches!(content, Content::Image(..)) && glitch.is_visible())
+            .then(|| (glitch.intensity(&playhead), playhead.time.as_secs_f32()));
+        let drawn = Drawn {
+            motion,
+            bounds: layer.bounds(),
+            image_bounds: layer.image_bounds(),
+            content,
+            appearance: layer.appearance,
+            pitch_class: layer
+                .appearance
+                .border
+                .follows_pitch
+                .then(|| playhead.pitch().class())
+                .flatten(),
+            distortion,
+            glitch,
+        };
+
+        Some((drawn, playhead))
+    }
+}
+
+impl canvas::Program<Message> for CanvasState {
+    type State = Option<CropDrag>;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let Some((index, layer)) = self
+            .cropping
+            .and_then(|index| Some((index, self.layers.get(index)?)))
+        else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let (Some(crop), Some(position)) = (layer.crop(), cursor.position_in(bounds)) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let crop = crop.flipped(layer.flip);
+        let image_bounds = layer.image_bounds();
+
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                *state =
+                    CropHandle::hit(crop.within(image_bounds), position).map(|handle| CropDrag {
+                        handle,
+                        origin: position,
+                        crop,
+                    });
+
+                (canvas::event::Status::Captured, None)
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => match state {
+                Some(drag) => {
+                    let delta = position - drag.origin;
+                    let delta = iced::Vector::new(
+                        delta.x / image_bounds.width,
+                        delta.y / image_bounds.height,
+                    );
+
+                    (
+                        canvas::event::Status::Captured,
+                        Some(Message::LayerCropChanged(
+                            index,
+                            drag.crop.dragged(drag.handle, delta).flipped(layer.flip),
+                        )),
+                    )
+                }
+                None => (canvas::event::Status::Ignored, None),
+            },
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                *state = None;
+
+                (canvas::event::Status::Captured, None)
+            }
+            _ => (canvas::event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let started = Instant::now();
+        let mut stuff: Vec<canvas::Geometry<Renderer>> = vec![];
+
+        let bounds_size = bounds.size();
+
+        let background = self.background_cache.draw(renderer, bounds_size, |frame| {
+            frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::BLACK);
+        });
+        stuff.push(background);
+
+        match &self.comparison {
+            // The caches hold whole layers, so each side is drawn afresh.
+            Some(comparison) if comparison.view == CompareView::Split => {
+                let mut frame = canvas::Frame::new(renderer, bounds_size);
+                let split = bounds_size.width * comparison.split.clamp(0., 1.);
+                frame.with_clip(
+                    Rectangle::new(Point::ORIGIN, Size::new(split, bounds_size.height)),
+                    |frame| draw_editor_layers(frame, &comparison.layers, &comparison.layer_caches),
+                );
+                frame.with_clip(
+                    Rectangle::new(
+                        Point::new(split, 0.),
+                        Size::new(bounds_size.width - split, bounds_size.height),
+                    ),
+                    |frame| {
+                        frame.translate(iced::Vector::new(-split, 0.));
+                        draw_editor_layers(frame, &self.layers, &self.layer_caches);
+                    },
+                );
+                stuff.push(frame.into_geometry());
+            }
+            None if self.finished.is_some() => {
+                let mut frame = canvas::Frame::new(renderer, bounds_size);
+                if let Some(finished) = &self.finished {
+                    frame.draw_image(
+                        Rectangle::new(Point::ORIGIN, bounds_size),
+                        canvas::Image::new(finished.editor.clone()),
+                    );
+                }
+                stuff.push(frame.into_geometry());
+            }
+            comparison => {
+                let (layers, caches) = match comparison {
+                    Some(comparison) if comparison.view == CompareView::A => {
+                        (&comparison.layers, &comparison.layer_caches)
+                    }
+                    _ => (&self.layers, &self.layer_caches),
+                };
+                for (layer, cache) in layers.iter().zip(caches) {
+                    if !layer.visibility.is_in_editor() {
+                        continue;
+                    }
+                    if let Some(drawn) = &cache.drawn {
+                        stuff.push(cache.cache.draw(renderer, bounds_size, |frame| {
+                            draw_layer(frame, layer, drawn, &cache.playhead)
+                        }));
+                    }
+                }
+            }
+        }
+
+        // The finished frame has the bars already.
+        let bars = self.post_fx.letterbox.bars(bounds_size);
+        if !bars.is_empty() && self.finished.is_none() {
+            let mut frame = canvas::Frame::new(renderer, bounds_size);
+            draw_letterbox(&mut frame, &bars);
+            stuff.push(frame.into_geometry());
+        }
+
+        if let Some((layer, crop)) = self
+            .cropping
+            .and_then(|index| self.layers.get(index))
+            .and_then(|layer| Some((layer, layer.crop()?.flipped(layer.flip))))
+        {
+            let mut frame = canvas::Frame::new(renderer, bounds_size);
+            draw_crop_overlay(
+                &mut frame,
+                layer.image_bounds(),
+                crop.within(layer.image_bounds()),
+            );
+            stuff.push(frame.into_geometry());
+        }
+
+        if self.safe_area != SafeArea::None {
+            let mut frame = canvas::Frame::new(renderer, bounds_size);
+            draw_safe_area(&mut frame, self.safe_area);
+            stuff.push(frame.into_geometry());
+        }
+
+        if let Some(comparison) = &self.comparison {
+            let mut frame = canvas::Frame::new(renderer, bounds_size);
+            draw_comparison_labels(&mut frame, comparison);
+            stuff.push(frame.into_geometry());
+        }
+
+        self.draw_time.set(started.elapsed());
+        self.draw_calls.set(stuff.len());
+
+        stuff
+    }
+}
+
+/// The canvas scaled to fit wherever it is shown, letterboxed in black, as in
+/// the detached preview window. Layers are drawn afresh every frame, as their
+/// caches only hold them at the canvas's own size.
+pub struct Preview<'a> {
+    pub state: &'a CanvasState,
+    pub size: Size,
+}
+
+impl canvas::Program<Message> for Preview<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::BLACK);
+
+        let scale = (bounds.width / self.size.width).min(bounds.height / self.size.height);
+        let scaled = self.size * scale;
+        let region = Rectangle::new(
+            Point::new(
+                (bounds.width - scaled.width) / 2.,
+                (bounds.height - scaled.height) / 2.,
+            ),
+            scaled,
+        );
+
+        frame.with_clip(region, |frame| {
+            frame.scale(scale);
+            if let Some(finished) = &self.state.finished {
+                let handle = finished.output.as_ref().unwrap_or(&finished.editor);
+                frame.draw_image(
+                    Rectangle::new(Point::ORIGIN, self.size),
+                    canvas::Image::new(handle.clone()),
+                );
+                return;
+            }
+            for (layer, cache) in self.state.layers.iter().zip(&self.state.layer_caches) {
+                if !layer.visibility.is_in_output(false) {
+                    continue;
+                }
+                if let Some(drawn) = &cache.drawn {
+                    frame.with_save(|frame| draw_layer(frame, layer, drawn, &cache.playhead));
+                }
+            }
+            draw_letterbox(frame, &self.state.post_fx.letterbox.bars(self.size));
+        });
+
+        vec![frame.into_geometry()]
+    }
+}
+
+fn draw_layer(frame: &mut canvas::Frame, layer: &Layer, drawn: &Drawn, playhead: &Playhead) {
+    let opacity = drawn.motion.opacity;
+    drawn.motion.apply(frame, drawn.bounds);
+
+    match (&drawn.content, &layer.kind) {
+        (Content::Image(image, image_bounds), _) => {
+            if let Some(handle) = image {
+                let width = image_bounds.width;
+                let distorted = drawn
+                    .distortion
+                    .and_then(|(amplitude, seconds)| {
+                        drawn
+                            .appearance
+                            .distortion
+                            .apply(handle, width, amplitude, seconds)
+                    })
+                    .unwrap_or_else(|| handle.clone());
+                // Glitched after distorting, so the blocks stay square.
+                let glitched = drawn
+                    .glitch
+                    .and_then(|(intensity, seconds)| {
+                        drawn
+                            .appearance
+                            .glitch
+                            .apply(&distorted, width, intensity, seconds)
+                    })
+                    .unwrap_or(distorted);
+                frame.draw_image(*image_bounds, canvas::Image::new(glitched).opacity(opacity));
+            }
+        }
+        (Content::Placeholder(name), _) => {
+            let bounds = drawn.bounds;
+            let outline = Path::rounded_rectangle(
+                bounds.position(),
+                bounds.size(),
+                drawn.appearance.corner_radius.into(),
+            );
+
+            frame.fill(&outline, Color::from_rgba(1., 1., 1., 0.08 * opacity));
+            frame.stroke(
+                &outline,
+                Stroke::default()
+                    .with_color(Color::from_rgba(1., 1., 1., 0.4 * opacity))
+                    .with_width(1.),
+            );
+            frame.fill_text(Text {
+                content: name.clone(),
+                position: bounds.center(),
+                color: Color::from_rgba(1., 1., 1., 0.6 * opacity),
+                horizontal_alignment: iced::alignment::Horizontal::Center,
+                vertical_alignment: iced::alignment::Vertical::Center,
+                ..Text::default()
+            });
+        }
+        (Content::Procedural(procedural, _), LayerKind::Procedural { frames, .. }) => procedural
+            .draw(
+                frame,
+                drawn.bounds,
+                frames,
+                playhead,
+                layer.timing.start,
+                opacity,
+            ),
+        (Content::Procedural(..), _) => {}
+        (Content::Lottie(lottie, seconds), _) => {
+            lottie.draw(frame, drawn.bounds, *seconds, opacity);
+        }
+        // The canvas can't blur what it has drawn, so a backdrop is only
+        // darkened here. The finished frame blurs it as well.
+        (Content::Backdrop, _) => frame.fill(
+            &backdrop_outline(drawn),
+            Color::from_rgba(0., 0., 0., drawn.appearance.darken.min(1.) * opacity),
+        ),
+    }
+
+    draw_border(
+        frame,
+        drawn.image_bounds,
+        drawn.appearance,
+        drawn.pitch_class,
+        opacity,
+    );
+}
+
+/// The shape of a backdrop layer, before its motion is applied.
+fn backdrop_outline(drawn: &Drawn) -> Path {
+    Path::rounded_rectangle(
+        drawn.bounds.position(),
+        drawn.bounds.size(),
+        drawn.appearance.corner_radius.into(),
+    )
+}
+
+/// Blurs and darkens `image`, a frame of a canvas of size `canvas` at
+/// `scale` pixels per canvas pixel, within the backdrop layer drawn as
+/// `drawn`.
+fn apply_backdrop(image: &mut image::RgbaImage, canvas: Size, scale: f32, drawn: &Drawn) {
+    let Appearance { blur, darken, .. } = drawn.appearance;
+    if blur <= 0. && darken <= 0. {
+        return;
+    }
+    // Where the layer covers the frame, in shades from black to white, so
+    // that it can be moved, turned and rounded off like any other.
+    let mask = offscreen::render(canvas, scale, |frame| {
+        drawn.motion.apply(frame, drawn.bounds);
+        frame.fill(&backdrop_outline(drawn), Color::WHITE);
+    });
+    let mut backdrop = if blur > 0. {
+        image::imageops::fast_blur(image, (blur * scale).max(0.5))
+    } else {
+        image.clone()
+    };
+    for pixel in backdrop.pixels_mut() {
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as f32 * (1. - darken.min(1.))).round() as u8;
+        }
+    }
+
+    let opacity = drawn.motion.opacity;
+    for ((pixel, backdrop), mask) in image.pixels_mut().zip(backdrop.pixels()).zip(mask.pixels()) {
+        let coverage = mask[0] as f32 / 255. * opacity;
+        for channel in 0..3 {
+            pixel[channel] = (pixel[channel] as f32 * (1. - coverage)
+                + backdrop[channel] as f32 * coverage)
+                .round() as u8;
+        }
+    }
+}
+
+/// Strokes the border inside `bounds`, following its rounded corners.
+fn draw_border(
+    frame: &mut canvas::Frame,
+    bounds: Rectangle,
+    appearance: Appearance,
+    pitch_class: Option<usize>,
+    opacity: f32,
+) {
+    let Border {
+        width,
+        color,
+        follows_pitch,
+    } = appearance.border;
+    if width <= 0. {
+        return;
+    }
+    let color = match pitch_class {
+        Some(pitch_class) if follows_pitch => with_hue(color, chroma::hue(pitch_class)),
+        _ => color,
+    };
+
+    let inset = width / 2.;
+    frame.stroke(
+        &Path::rounded_rectangle(
+            Point::new(bounds.x + inset, bounds.y + inset),
+            Size::new(
+                (bounds.width - width).max(0.),
+                (bounds.height - width).max(0.),
+            ),
+            (appearance.corner_radius - inset).max(0.).into(),
+        ),
+        Stroke::default()
+            .with_color(Color {
+                a: color.a * opacity,
+                ..color
+            })
+            .with_width(width),
+    );
+}
+
+/// `color` with its hue, in degrees, replaced. Greys are given full
+/// saturation, as they have no hue to replace.
+fn with_hue(color: Color, hue: f32) -> Color {
+    let value = color.r.max(color.g).max(color.b);
+    let lowest = color.r.min(color.g).min(color.b);
+    let saturation = if value - lowest > 0.05 {
+        (value - lowest) / value
+    } else {
+        1.
+    };
+
+    let channel = |n: f32| {
+        let k = (n + hue / 60.) % 6.;
+
+        value - value * saturation * k.min(4. - k).clamp(0., 1.)
+    };
+
+    Color {
+        r: channel(5.),
+        g: channel(3.),
+        b: channel(1.),
+        a: color.a,
+    }
+}
+
+/// Dims the part of the image outside the crop and draws its corner handles.
+fn draw_crop_overlay(frame: &mut canvas::Frame, image: Rectangle, crop: Rectangle) {
+    let shade = Color::from_rgba(0., 0., 0., 0.6);
+    let shaded = [
+        Rectangle::new(image.position(), Size::new(image.width, crop.y - image.y)),
+        Rectangle::new(
+            Point::new(image.x, crop.y + crop.height),
+            Size::new(image.width, image.y + image.height - crop.y - crop.height),
+        ),
+        Rectangle::new(
+            Point::new(image.x, crop.y),
+            Size::new(crop.x - image.x, crop.height),
+        ),
+        Rectangle::new(
+            Point::new(crop.x + crop.width, crop.y),
+            Size::new(image.x + image.width - crop.x - crop.width, crop.height),
+        ),
+    ];
+    for rectangle in shaded {
+        frame.fill_rectangle(rectangle.position(), rectangle.size(), shade);
+    }
+
+    frame.stroke(
+        &Path::rectangle(crop.position(), crop.size()),
+        Stroke::default().with_color(Color::WHITE).with_width(1.),
+    );
+
+    let corners = [
+        Point::new(crop.x, crop.y),
+        Point::new(crop.x + crop.width, crop.y),
+        Point::new(crop.x, crop.y + crop.height),
+        Point::new(crop.x + crop.width, crop.y + crop.height),
+    ];
+    for corner in corners {
+        frame.fill(&Path::circle(corner, CropHandle::RADIUS), Color::WHITE);
+    }
+}
+
+/// Shades what `safe_area` covers and names it, or outlines the broadcast
+/// margins.
+/// Catches the caches of a list of layers up with how each is drawn now.
+/// Returns how many layers are visible and how many of those were redrawn.
+fn catch_up(caches: &mut Vec<LayerCache>, drawn: Vec<Option<(Drawn, Playhead)>>) -> (usize, usize) {
+    caches.resize_with(drawn.len(), LayerCache::default);
+    let (mut visible, mut redrawn) = (0, 0);
+    for (cache, drawn) in caches.iter_mut().zip(drawn) {
+        let (drawn, playhead) = drawn.unzip();
+        if cache.drawn != drawn {
+            cache.cache.clear();
+            redrawn += usize::from(drawn.is_some());
+        }
+        visible += usize::from(drawn.is_some());
+        cache.drawn = drawn;
+        cache.playhead = playhead.unwrap_or_default();
+    }
+
+    (visible, redrawn)
+}
+
+/// Draws the layers shown in the editor without their caches.
+fn draw_editor_layers(frame: &mut canvas::Frame, layers: &[Layer], caches: &[LayerCache]) {
+    for (layer, cache) in layers.iter().zip(caches) {
+        if !layer.visibility.is_in_editor() {
+            continue;
+        }
+        if let Some(drawn) = &cache.drawn {
+            frame.with_save(|frame| draw_layer(frame, layer, drawn, &cache.playhead));
+        }
+    }
+}
+
+/// Tells which version each part of the canvas shows, with the split drawn
+/// between them.
+fn draw_comparison_labels(frame: &mut canvas::Frame, comparison: &Comparison) {
+    let size = frame.size();
+    let mut label = |name: &str, x: f32| {
+        let position = Point::new(x + 8., 8.);
+        frame.fill_rectangle(
+            position,
+            Size::new(22., 22.),
+            Color {
+                a: 0.6,
+                ..Color::BLACK
+            },
+        );
+        frame.fill_text(Text {
+            content: name.to_string(),
+            position: position + iced::Vector::new(6., 3.),
+            color: Color::WHITE,
+            size: 14.into(),
+            ..Text::default()
+        });
+    };
+
+    match comparison.view {
+        CompareView::A => label("A", 0.),
+        CompareView::B => label("B", 0.),
+        CompareView::Split => {
+            let split = size.width * comparison.split.clamp(0., 1.);
+            label("A", 0.);
+            label("B", split);
+            frame.stroke(
+                &Path::line(Point::new(split, 0.), Point::new(split, size.height)),
+                Stroke::default().with_color(Color::WHITE).with_width(2.),
+            );
+        }
+    }
+}
+
+fn draw_letterbox(frame: &mut canvas::Frame, bars: &[Rectangle]) {
+    for bar in bars {
+        frame.fill_rectangle(bar.position(), bar.size(), Color::BLACK);
+    }
+}
+
+fn draw_safe_area(frame: &mut canvas::Frame, safe_area: SafeArea) {
+    let size = frame.size();
+    let guide = Color::from_rgb(1., 0.3, 0.3);
+
+    if safe_area == SafeArea::Broadcast {
+        for (name, margin) in [("Action safe", 0.035), ("Title safe", 0.05)] {
+            let inset = Rectangle::new(
+                Point::new(size.width * margin, size.height * margin),
+                size * (1. - margin * 2.),
+            );
+            frame.stroke(
+                &Path::rectangle(inset.position(), inset.size()),
+                Stroke::default().with_color(guide).with_width(1.),
+            );
+            frame.fill_text(Text {
+                content: name.to_string(),
+                position: inset.position() + iced::Vector::new(4., 4.),
+                color: guide,
+                size: 12.into(),
+                ..Text::default()
+            });
+        }
+
+        return;
+    }
+
+    for (name, area) in safe_area.covered() {
+        let area = Rectangle::new(
+            Point::new(area.x * size.width, area.y * size.height),
+            Size::new(area.width * size.width, area.height * size.height),
+        );
+        frame.fill_rectangle(area.position(), area.size(), Color { a: 0.25, ..guide });
+        frame.stroke(
+            &Path::rectangle(area.position(), area.size()),
+            Stroke::default().with_color(guide).with_width(1.),
+        );
+        frame.fill_text(Text {
+            content: name.to_string(),
+            position: area.position() + iced::Vector::new(4., 4.),
+            color: Color::WHITE,
+            size: 12.into(),
+            ..Text::default()
+        });
+    }
+}
+
+impl Default for CanvasState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(kind: LayerKind, width: f32, appearance: Appearance) -> Layer {
+        Layer {
+            name: String::new(),
+            kind,
+            x: 0.,
+            y: 0.,
+            width,
+            height: 20.,
+            scale: 1.,
+            opacity: 1.,
+            flip: Flip::NONE,
+            appearance,
+            animation: crate::Animation::NONE,
+            timing: Timing::ALWAYS,
+            playback: Playback::NORMAL,
+            keyframes: Keyframes::default(),
+            audio_channel: Default::default(),
+            audio_source: Default::default(),
+            units: Default::default(),
+            is_aspect_locked: false,
+            visibility: Visibility::Everywhere,
+        }
+    }
+
+    #[test]
+    fn backdrops_darken_what_is_beneath_them() {
+        let mut state = CanvasState::new();
+        state.layers = vec![
+            layer(LayerKind::Placeholder, 40., Appearance::default()),
+            layer(
+                LayerKind::Backdrop,
+                20.,
+                Appearance {
+                    darken: 1.,
+                    ..Appearance::default()
+                },
+            ),
+        ];
+        state.update(Size::new(40., 20.));
+        let image = state.render_thumbnail(Size::new(40., 20.), Size::new(40, 20));
+
+        assert!(state.finished.is_some());
+        assert_eq!(image.get_pixel(10, 5).0, [0, 0, 0, 255]);
+        assert_ne!(image.get_pixel(30, 5).0, [0, 0, 0, 255]);
+    }
+
+    /// `frames` solid frames of an animated GIF, or a still PNG if there is
+    /// only one.
+    fn encoded(frames: u8) -> Vec<u8> {
+        let image = |shade| image::RgbaImage::from_pixel(4, 4, image::Rgba([shade, 0, 0, 255]));
+        let mut contents = vec![];
+        if frames == 1 {
+            image(0)
+                .write_to(
+                    &mut std::io::Cursor::new(&mut contents),
+                    image::ImageFormat::Png,
+                )
+                .unwrap();
+        } else {
+            image::codecs::gif::GifEncoder::new(&mut contents)
+                .encode_frames((0..frames).map(|frame| image::Frame::new(image(frame * 50))))
+                .unwrap();
+        }
+
+        contents
+    }
+
+    #[test]
+    fn only_animated_images_have_frames() {
+        assert_eq!(
+            animation_frames(&encoded(3)).map(|frames| frames.len()),
+            Some(3)
+        );
+        assert!(animation_frames(&encoded(1)).is_none());
+        assert!(animation_frames(b"not an image").is_none());
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt::Display;
+
+use iced::{widget::image::Handle, Size};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::{self, Camera},
+    canvas::{ChromaKey, Flip},
+    i18n::t,
+};
+
+/// What a capture layer shows, grabbed anew every frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureSource {
+    Region(Region),
+    /// A window, by its title, so that it's found again after it has been
+    /// closed and reopened, e.g. with the next session of a DAW.
+    Window(String),
+    Camera(Camera),
+}
+
+impl Display for CaptureSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureSource::Region(_) => f.write_str(t("capture-region")),
+            CaptureSource::Window(title) => f.write_str(title),
+            CaptureSource::Camera(camera) => f.write_str(&camera.name),
+        }
+    }
+}
+
+/// A rectangle of the screen, in screen pixels from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A captured frame and its size in pixels.
+pub type Frame = (Handle, Size);
+
+/// The whole of the screen.
+pub async fn screen() -> Result<Region, String> {
+    tokio::task::spawn_blocking(platform::screen)
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+/// Titles of the windows that can be captured.
+pub async fn windows() -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(platform::windows)
+        .await
+        .map_err(|error| error.to_string())?
+}
+
+/// A frame grabbed for the layers showing `source` flipped by `flip` and
+/// keyed with `chroma_key`.
+pub type Grab = (CaptureSource, Flip, ChromaKey, Result<Frame, String>);
+
+/// Grabs a frame of each source, all in one go so that layers showing the
+/// same source the same way don't grab it twice.
+pub async fn grab(sources: Vec<(CaptureSource, Flip, ChromaKey)>) -> Vec<Grab> {
+    tokio::task::spawn_blocking(move || {
+        sources
+            .into_iter()
+            .map(|(source, flip, chroma_key)| {
+                let image = match &source {
+                    CaptureSource::Camera(camera) => camera::frame(camera),
+                    CaptureSource::Region(_) | CaptureSource::Window(_) => platform::grab(&source),
+                };
+                let frame = image.map(|mut image| {
+                    if flip.horizontal {
+                        image::imageops::flip_horizontal_in_place(&mut image);
+                    }
+                    if flip.vertical {
+                        image::imageops::flip_vertical_in_place(&mut image);
+                    }
+                    if chroma_key.is_visible() {
+                        chroma_key.apply(&mut image);
+                    }
+                    let size = Size::new(image.width() as f32, image.height() as f32);
+
+                    (
+                        Handle::from_rgba(image.width(), image.height(), image.into_raw()),
+                        size,
+                    )
+                });
+
+                (source, flip, chroma_key, frame)
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Capture through the X server, which XWayland also runs, though it only
+/// sees the windows of X11 programs.
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::sync::{Mutex, PoisonError};
+
+    use x11rb::{
+        connection::Connection,
+        protocol::xproto::{AtomEnum, ConnectionExt, ImageFormat, ImageOrder, Window},
+        rust_connection::RustConnection,
+    };
+
+    use super::{CaptureSource, Region};
+    use crate::i18n::{t, t_args};
+
+    struct Display {
+        connection: RustConnection,
+        root: Window,
+    }
+
+    /// The connection is kept between frames, and made again once it fails.
+    static DISPLAY: Mutex<Option<Display>> = Mutex::new(None);
+
+    fn with_display<T>(f: impl FnOnce(&Display) -> Result<T, String>) -> Result<T, String> {
+        let mut display = DISPLAY.lock().unwrap_or_else(PoisonError::into_inner);
+        if display.is_none() {
+            let (connection, screen) =
+                x11rb::connect(None).map_err(|_| t("error-capture-x11").to_string())?;
+            let root = connection.setup().roots[screen].root;
+            *display = Some(Display { connection, root });
+        }
+        let connected = display.as_ref().ok_or(t("error-capture-disconnected"))?;
+
+        let result = f(connected);
+        if result.is_err() && connected.connection.flush().is_err() {
+            *display = None;
+        }
+
+        result
+    }
+
+    pub fn screen() -> Result<Region, String> {
+        with_display(|display| {
+            let geometry = display
+                .connection
+                .get_geometry(display.root)
+                .map_err(|error| error.to_string())?
+                .reply()
+                .map_err(|error| error.to_string())?;
+
+            Ok(Region {
+                x: 0,
+                y: 0,
+                width: geometry.width as u32,
+                height: geometry.height as u32,
+            })
+        })
+    }
+
+    pub fn windows() -> Result<Vec<String>, String> {
+        with_display(|display| {
+            let mut titles: Vec<String> = client_windows(display)?
+                .into_iter()
+                .filter_map(|window| title(display, window))
+                .filter(|title| !title.trim().is_empty())
+                .collect();
+            titles.sort();
+            titles.dedup();
+
+            Ok(titles)
+        })
+    }
+
+    pub fn grab(source: &CaptureSource) -> Result<image::RgbaImage, String> {
+        with_display(|display| {
+            let connection = &display.connection;
+            let (drawable, region) = match source {
+                CaptureSource::Region(region) => (display.root, *region),
+                CaptureSource::Camera(camera) => {
+                    return Err(t_args("error-capture-camera", &[("camera", &camera.name)]))
+                }
+                CaptureSource::Window(wanted) => {
+                    let window = client_windows(display)?
+                        .into_iter()
+                        .find(|window| title(display, *window).as_ref() == Some(wanted))
+                        .ok_or_else(|| t_args("error-capture-window", &[("window", wanted)]))?;
+                    let geometry = connection
+                        .get_geometry(window)
+                        .map_err(|error| error.to_string())?
+                        .reply()
+                        .map_err(|error| error.to_string())?;
+
+                    (
+                        window,
+                        Region {
+                            x: 0,
+                            y: 0,
+                            width: geometry.width as u32,
+                            height: geometry.height as u32,
+                        },
+                    )
+                }
+            };
+            let clamp = |value: u32| value.clamp(1, u16::MAX as u32) as u16;
+            let position = |value: i32| value.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+
+            let image = connection
+                .get_image(
+                    ImageFormat::Z_PIXMAP,
+                    drawable,
+                    position(region.x),
+                    position(region.y),
+                    clamp(region.width),
+                    clamp(region.height),
+                    !0,
+                )
+                .map_err(|error| error.to_string())?
+                .reply()
+                .map_err(|_| t("error-capture-hidden").to_string())?;
+
+            // True color screens keep pixels in 32 bits, whatever their depth.
+            let bits_per_pixel = connection
+                .setup()
+                .pixmap_formats
+                .iter()
+                .find(|format| format.depth == image.depth)
+                .map(|format| format.bits_per_pixel);
+            if bits_per_pixel != Some(32) {
+                return Err(t_args("error-capture-depth", &[("depth", &image.depth)]));
+            }
+            let is_big_endian = connection.setup().image_byte_order == ImageOrder::MSB_FIRST;
+            let (width, height) = (clamp(region.width) as u32, clamp(region.height) as u32);
+
+            let pixels = image
+                .data
+                .chunks_exact(4)
+                .flat_map(|pixel| {
+                    let pixel: [u8; 4] = pixel.try_into().unwrap_or_default();
+                    let value = if is_big_endian {
+                        u32::from_be_bytes(pixel)
+                    } else {
+                        u32::from_le_bytes(pixel)
+                    };
+
+                    [(value >> 16) as u8, (value >> 8) as u8, value as u8, 255]
+                })
+                .collect();
+
+            image::RgbaImage::from_raw(width, height, pixels)
+                .ok_or_else(|| "the X server sent a frame of the wrong size".to_string())
+        })
+    }
+
+    /// The top-level windows, as listed by the window manager.
+    fn client_windows(display: &Display) -> Result<Vec<Window>, String> {
+        let connection = &display.connection;
+        let client_list = atom(connection, b"_NET_CLIENT_LIST")?;
+        let reply = connection
+            .get_property(
+                false,
+                display.root,
+                client_list,
+                AtomEnum::WINDOW,
+                0,
+                u32::MAX,
+            )
+            .map_err(|error| error.to_string())?
+            .reply()
+            .map_err(|error| error.to_string())?;
+
+        Ok(reply
+            .value32()
+            .map(|windows| windows.collect())
+            .unwrap_or_default())
+    }
+
+    fn title(display: &Display, window: Window) -> Option<String> {
+        let connection = &display.connection;
+        let property = |name: &[u8], kind: u32| {
+            let property = atom(connection, name).ok()?;
+            let reply = connection
+                .get_property(false, window, property, kind, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?;
+
+            (!reply.value.is_empty()).then(|| String::from_utf8_lossy(&reply.value).into_owned())
+        };
+
+        property(b"_NET_WM_NAME", atom(connection, b"UTF8_STRING").ok()?)
+            .or_else(|| property(b"WM_NAME", AtomEnum::STRING.into()))
+    }
+
+    fn atom(connection: &RustConnection, name: &[u8]) -> Result<u32, String> {
+        Ok(connection
+            .intern_atom(false, name)
+            .map_err(|error| error.to_string())?
+            .reply()
+            .map_err(|error| error.to_string())?
+            .atom)
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+mod platform {
+    use super::{CaptureSource, Region};
+    use crate::i18n::t;
+
+    pub fn screen() -> Result<Region, String> {
+        Err(t("error-capture-unsupported").to_string())
+    }
+
+    pub fn windows() -> Result<Vec<String>, String> {
+        Err(t("error-capture-unsupported").to_string())
+    }
+
+    pub fn grab(_source: &CaptureSource) -> Result<image::RgbaImage, String> {
+        Err(t("error-capture-unsupported").to_string())
+    }
+}
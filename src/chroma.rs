@@ -0,0 +1,225 @@
+use std::{f32::consts::TAU, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::t_args;
+
+/// How strongly each of the twelve pitch classes, from C, is heard at a
+/// moment of the track.
+pub type Chroma = [f32; 12];
+
+const NAMES: [&str; 12] = [
+    "C", "C♯", "D", "E♭", "E", "F", "F♯", "G", "A♭", "A", "B♭", "B",
+];
+
+/// The pitches heard at a moment of the track.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pitch {
+    /// Relative to the strongest pitch class, and empty if the moment is
+    /// quiet.
+    pub chroma: Chroma,
+    /// MIDI number of the strongest note, unless the moment is quiet.
+    pub note: Option<u8>,
+}
+
+impl Pitch {
+    /// The strongest pitch class, unless the moment is quiet.
+    pub fn class(&self) -> Option<usize> {
+        self.chroma.iter().position(|strength| *strength >= 1.)
+    }
+}
+
+/// Scientific pitch notation of the MIDI note `note`, e.g. `A4`.
+pub fn note_name(note: u8) -> String {
+    format!("{}{}", NAMES[note as usize % 12], note as i32 / 12 - 1)
+}
+
+pub fn note_frequency(note: u8) -> f32 {
+    440. * 2_f32.powf((note as f32 - 69.) / 12.)
+}
+
+/// The key a track is in, as found from the pitch classes heard over all of
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Key {
+    /// Pitch class of the tonic, 0 being C.
+    pub tonic: usize,
+    pub minor: bool,
+}
+
+impl Key {
+    /// Krumhansl and Kessler's ratings of how well each pitch class fits a
+    /// major and a minor key, starting from the tonic.
+    const MAJOR: Chroma = [
+        6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+    ];
+    const MINOR: Chroma = [
+        6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+    ];
+
+    /// The key whose profile correlates best with `chroma`.
+    pub fn detect(chroma: &Chroma) -> Option<Key> {
+        if chroma.iter().all(|strength| *strength <= 0.) {
+            return None;
+        }
+
+        (0..12)
+            .flat_map(|tonic| [false, true].map(|minor| Key { tonic, minor }))
+            .map(|key| (key, correlation(chroma, &key.profile())))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(key, _)| key)
+    }
+
+    fn profile(self) -> Chroma {
+        let profile = if self.minor { Self::MINOR } else { Self::MAJOR };
+
+        std::array::from_fn(|pitch_class| profile[(pitch_class + 12 - self.tonic) % 12])
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&t_args(
+            if self.minor { "key-minor" } else { "key-major" },
+            &[("tonic", &NAMES[self.tonic])],
+        ))
+    }
+}
+
+/// Hue, in degrees, standing for `pitch_class`. Goes around the circle of
+/// fifths, so that pitches that sound well together get similar colors.
+pub fn hue(pitch_class: usize) -> f32 {
+    (pitch_class * 7 % 12) as f32 * 30.
+}
+
+fn correlation(a: &Chroma, b: &Chroma) -> f32 {
+    let mean = |values: &Chroma| values.iter().sum::<f32>() / 12.;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+
+    let (mut covariance, mut variance_a, mut variance_b) = (0., 0., 0.);
+    for (a, b) in a.iter().zip(b) {
+        covariance += (a - mean_a) * (b - mean_b);
+        variance_a += (a - mean_a).powi(2);
+        variance_b += (b - mean_b).powi(2);
+    }
+
+    covariance / (variance_a * variance_b).sqrt().max(f32::EPSILON)
+}
+
+/// Measures the chroma of the mid channel while the track is decoded, with a
+/// Goertzel filter tuned to every note from C3 to B5.
+#[derive(Debug)]
+pub struct ChromaRecorder {
+    /// Samples averaged into each one the filters see, as the notes are far
+    /// below the track's sample rate.
+    decimation: usize,
+    sum: f32,
+    count: usize,
+    window: Vec<f32>,
+    window_length: usize,
+    /// Filter coefficient and MIDI number of every note.
+    notes: Vec<(f32, u8)>,
+    /// The chroma, and the strongest note with its power, of every frame.
+    frames: Vec<(Chroma, u8, f32)>,
+}
+
+impl ChromaRecorder {
+    pub const FRAMES_PER_SECOND: f32 = 4.;
+
+    const SAMPLE_RATE: f32 = 11025.;
+    const LOWEST_NOTE: u8 = 48;
+    const HIGHEST_NOTE: u8 = 83;
+
+    pub fn new(sample_rate: u32) -> ChromaRecorder {
+        let decimation = (sample_rate as f32 / Self::SAMPLE_RATE).round().max(1.) as usize;
+        let rate = sample_rate as f32 / decimation as f32;
+        let notes = (Self::LOWEST_NOTE..=Self::HIGHEST_NOTE)
+            .map(|note| (2. * (TAU * note_frequency(note) / rate).cos(), note))
+            .collect();
+        let window_length = (rate / Self::FRAMES_PER_SECOND) as usize;
+
+        ChromaRecorder {
+            decimation,
+            sum: 0.,
+            count: 0,
+            window: Vec::with_capacity(window_length),
+            window_length,
+            notes,
+            frames: vec![],
+        }
+    }
+
+    pub fn add(&mut self, sample: f32) {
+        self.sum += sample;
+        self.count += 1;
+        if self.count < self.decimation {
+            return;
+        }
+
+        self.window.push(self.sum / self.count as f32);
+        self.sum = 0.;
+        self.count = 0;
+
+        if self.window.len() == self.window_length {
+            self.finish_frame();
+        }
+    }
+
+    fn finish_frame(&mut self) {
+        let length = self.window.len();
+        let mut chroma = Chroma::default();
+        let (mut strongest, mut strongest_power) = (0, 0.);
+
+        for (coefficient, note) in &self.notes {
+            let (mut previous, mut before) = (0., 0.);
+            for (index, sample) in self.window.iter().enumerate() {
+                // Hann window, so the edges of the frame do not smear the
+                // notes into their neighbours.
+                let weight = 0.5 - 0.5 * (TAU * index as f32 / length as f32).cos();
+                let current = sample * weight + coefficient * previous - before;
+                before = previous;
+                previous = current;
+            }
+
+            let power =
+                (previous * previous + before * before - coefficient * previous * before).max(0.);
+            chroma[*note as usize % 12] += power;
+            if power > strongest_power {
+                (strongest, strongest_power) = (*note, power);
+            }
+        }
+
+        self.frames.push((chroma, strongest, strongest_power));
+        self.window.clear();
+    }
+
+    /// The pitches of every frame, with frames much quieter than the loudest
+    /// one counted as quiet.
+    pub fn finish(mut self) -> Vec<Pitch> {
+        if self.window.len() > self.window_length / 2 {
+            self.finish_frame();
+        }
+
+        let total = |chroma: &Chroma| chroma.iter().sum::<f32>();
+        let loudest = self
+            .frames
+            .iter()
+            .map(|(chroma, ..)| total(chroma))
+            .fold(0., f32::max);
+
+        self.frames
+            .into_iter()
+            .map(|(chroma, note, power)| {
+                let strongest = chroma.iter().copied().fold(0., f32::max);
+                if total(&chroma) < loudest * 0.01 || power <= 0. {
+                    return Pitch::default();
+                }
+
+                Pitch {
+                    chroma: chroma.map(|strength| strength / strongest),
+                    note: Some(note),
+                }
+            })
+            .collect()
+    }
+}
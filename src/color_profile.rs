@@ -0,0 +1,250 @@
+/// An RGB ICC profile of the matrix and tone curve kind, which is what
+/// cameras and phones embed, e.g. Display P3 or Adobe RGB. Profiles built
+/// from lookup tables instead, as printer profiles are, aren't read.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// Turns the linear red, green and blue of the profile into linear sRGB.
+    matrix: [[f32; 3]; 3],
+    curves: [Curve; 3],
+}
+
+/// How a channel's stored value maps to its linear light.
+#[derive(Debug, Clone)]
+enum Curve {
+    Gamma(f32),
+    /// Evenly spaced samples, interpolated between.
+    Table(Vec<f32>),
+    /// The ICC parametric curve: `(a x + b)^g + e` from `d` on, and
+    /// `c x + f` below it.
+    Parametric {
+        g: f32,
+        a: f32,
+        b: f32,
+        c: f32,
+        d: f32,
+        e: f32,
+        f: f32,
+    },
+}
+
+impl Curve {
+    fn linear(&self, value: f32) -> f32 {
+        match self {
+            Curve::Gamma(gamma) => value.powf(*gamma),
+            Curve::Table(table) => {
+                let position = value * (table.len() - 1) as f32;
+                let index = (position as usize).min(table.len() - 2);
+                let fraction = position - index as f32;
+
+                table[index] + (table[index + 1] - table[index]) * fraction
+            }
+            Curve::Parametric {
+                g,
+                a,
+                b,
+                c,
+                d,
+                e,
+                f,
+            } => {
+                if value >= *d {
+                    (a * value + b).max(0.).powf(*g) + e
+                } else {
+                    c * value + f
+                }
+            }
+        }
+    }
+}
+
+/// Turns linear XYZ, adapted to the D50 white of ICC profiles, into linear
+/// sRGB.
+const XYZ_D50_TO_SRGB: [[f32; 3]; 3] = [
+    [3.133_856, -1.616_867, -0.490_615],
+    [-0.978_768, 1.916_142, 0.033_454],
+    [0.071_945, -0.228_991, 1.405_243],
+];
+
+impl Profile {
+    /// Reads the profile, unless it isn't an RGB matrix profile or is sRGB
+    /// already, in which case nothing needs converting.
+    pub fn parse(profile: &[u8]) -> Option<Profile> {
+        if profile.get(16..20)? != b"RGB " || profile.get(20..24)? != b"XYZ " {
+            return None;
+        }
+        let tag = |signature: &[u8; 4]| {
+            let count = be_u32(profile, 128)? as usize;
+            (0..count).find_map(|index| {
+                let entry = 132 + index * 12;
+                if profile.get(entry..entry + 4)? != signature {
+                    return None;
+                }
+                let offset = be_u32(profile, entry + 4)? as usize;
+                let size = be_u32(profile, entry + 8)? as usize;
+
+                profile.get(offset..offset.checked_add(size)?)
+            })
+        };
+
+        let mut columns = [[0.; 3]; 3];
+        for (column, signature) in columns.iter_mut().zip([b"rXYZ", b"gXYZ", b"bXYZ"]) {
+            let xyz = tag(signature)?;
+            if xyz.get(..4)? != b"XYZ " {
+                return None;
+            }
+            for (component, value) in column.iter_mut().enumerate() {
+                *value = s15_fixed16(xyz, 8 + component * 4)?;
+            }
+        }
+        let curves = [
+            parse_curve(tag(b"rTRC")?)?,
+            parse_curve(tag(b"gTRC")?)?,
+            parse_curve(tag(b"bTRC")?)?,
+        ];
+
+        let mut matrix = [[0.; 3]; 3];
+        for (row, srgb_row) in matrix.iter_mut().zip(XYZ_D50_TO_SRGB) {
+            for (channel, value) in row.iter_mut().enumerate() {
+                *value = (0..3)
+                    .map(|component| srgb_row[component] * columns[channel][component])
+                    .sum();
+            }
+        }
+        let profile = Profile { matrix, curves };
+
+        (!profile.is_srgb()).then_some(profile)
+    }
+
+    /// Whether converting would change no color by more than can be seen,
+    /// as for the sRGB profiles many programs embed.
+    fn is_srgb(&self) -> bool {
+        let is_identity = self.matrix.iter().enumerate().all(|(row, values)| {
+            values
+                .iter()
+                .enumerate()
+                .all(|(column, value)| (value - if row == column { 1. } else { 0. }).abs() < 0.01)
+        });
+
+        is_identity
+            && self.curves.iter().all(|curve| {
+                (0..=16).all(|step| {
+                    let value = step as f32 / 16.;
+                    (curve.linear(value) - srgb_to_linear(value)).abs() < 0.005
+                })
+            })
+    }
+
+    /// Converts the image's colors from the profile to sRGB in place,
+    /// clipping those sRGB can't show. Alpha is kept as it is.
+    pub fn convert(&self, image: &mut image::RgbaImage) {
+        let linear: [Vec<f32>; 3] = std::array::from_fn(|channel| {
+            (0..=255)
+                .map(|value| self.curves[channel].linear(value as f32 / 255.))
+                .collect()
+        });
+        // Linear light is spaced finely enough for the darkest shades to
+        // keep their steps once encoded.
+        const STEPS: usize = 4096;
+        let encoded: Vec<u8> = (0..=STEPS)
+            .map(|step| (linear_to_srgb(step as f32 / STEPS as f32) * 255.).round() as u8)
+            .collect();
+
+        for pixel in image.pixels_mut() {
+            let [red, green, blue, _] = pixel.0;
+            let input = [
+                linear[0][red as usize],
+                linear[1][green as usize],
+                linear[2][blue as usize],
+            ];
+            for (channel, row) in self.matrix.iter().enumerate() {
+                let value = row[0] * input[0] + row[1] * input[1] + row[2] * input[2];
+                pixel.0[channel] = encoded[(value.clamp(0., 1.) * STEPS as f32) as usize];
+            }
+        }
+    }
+}
+
+fn parse_curve(curve: &[u8]) -> Option<Curve> {
+    match curve.get(..4)? {
+        b"curv" => {
+            let count = be_u32(curve, 8)? as usize;
+            match count {
+                0 => Some(Curve::Gamma(1.)),
+                1 => Some(Curve::Gamma(be_u16(curve, 12)? as f32 / 256.)),
+                _ => (0..count)
+                    .map(|index| Some(be_u16(curve, 12 + index * 2)? as f32 / 65535.))
+                    .collect::<Option<_>>()
+                    .map(Curve::Table),
+            }
+        }
+        b"para" => {
+            let parameter = |index: usize| s15_fixed16(curve, 12 + index * 4);
+            let g = parameter(0)?;
+            let (mut a, mut b, mut c, mut d, mut e, mut f) = (1., 0., 0., 0., 0., 0.);
+            match be_u16(curve, 8)? {
+                0 => {}
+                1 => {
+                    (a, b) = (parameter(1)?, parameter(2)?);
+                    d = -b / a;
+                }
+                2 => {
+                    (a, b, e) = (parameter(1)?, parameter(2)?, parameter(3)?);
+                    d = -b / a;
+                    f = e;
+                }
+                3 => {
+                    (a, b, c, d) = (parameter(1)?, parameter(2)?, parameter(3)?, parameter(4)?);
+                }
+                4 => {
+                    (a, b, c, d) = (parameter(1)?, parameter(2)?, parameter(3)?, parameter(4)?);
+                    (e, f) = (parameter(5)?, parameter(6)?);
+                }
+                _ => return None,
+            }
+
+            Some(Curve::Parametric {
+                g,
+                a,
+                b,
+                c,
+                d,
+                e,
+                f,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+fn be_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn be_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// A signed number with 16 bits after the point, as ICC profiles store them.
+fn s15_fixed16(bytes: &[u8], offset: usize) -> Option<f32> {
+    Some(be_u32(bytes, offset)? as i32 as f32 / 65536.)
+}
@@ -0,0 +1,139 @@
+use iced::{
+    widget::{button, center, column, container, horizontal_space, opaque, row, text, text_input},
+    Alignment, Color, Element, Length, Padding, Theme,
+};
+
+use crate::{i18n::t, Message};
+
+/// An action that can be run from the command palette.
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub name: String,
+    /// Keys that run the same action, shown next to it so the palette doubles
+    /// as a list of shortcuts.
+    pub shortcut: Option<&'static str>,
+    pub message: Message,
+}
+
+impl Command {
+    pub fn new(name: impl Into<String>, message: Message) -> Command {
+        Command {
+            name: name.into(),
+            shortcut: None,
+            message,
+        }
+    }
+
+    pub fn shortcut(self, shortcut: &'static str) -> Command {
+        Command {
+            shortcut: Some(shortcut),
+            ..self
+        }
+    }
+}
+
+/// The open command palette, with what has been typed into it.
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    pub query: String,
+    /// Index of the highlighted command among the matching ones.
+    pub selected: usize,
+}
+
+impl CommandPalette {
+    /// Commands shown at once, with the highlighted one kept among them.
+    const ROWS: usize = 10;
+
+    pub fn input_id() -> text_input::Id {
+        text_input::Id::new("command-palette")
+    }
+
+    /// The commands whose name contains the letters of the query in order.
+    /// Those containing the query as typed come first.
+    pub fn matching(&self, commands: Vec<Command>) -> Vec<Command> {
+        let query = self.query.to_lowercase();
+        let mut matching: Vec<_> = commands
+            .into_iter()
+            .filter(|command| is_subsequence(&query, &command.name.to_lowercase()))
+            .collect();
+        matching.sort_by_key(|command| !command.name.to_lowercase().contains(&query));
+
+        matching
+    }
+
+    pub fn view(&self, commands: Vec<Command>) -> Element<'_, Message> {
+        let matching = self.matching(commands);
+        let first = self.selected.saturating_sub(Self::ROWS - 1);
+
+        let rows = matching
+            .into_iter()
+            .enumerate()
+            .skip(first)
+            .take(Self::ROWS)
+            .fold(column![], |column, (index, command)| {
+                let is_selected = index == self.selected;
+
+                column.push(
+                    button(
+                        row![text(command.name)]
+                            .push(horizontal_space())
+                            .push_maybe(
+                                command
+                                    .shortcut
+                                    .map(|shortcut| text(shortcut).style(text::secondary)),
+                            )
+                            .align_y(Alignment::Center),
+                    )
+                    .width(Length::Fill)
+                    .padding(Padding::from([6., 7.]))
+                    .style(move |theme: &Theme, status| {
+                        if is_selected {
+                            button::primary(theme, status)
+                        } else {
+                            button::text(theme, status)
+                        }
+                    })
+                    .on_press(Message::RunCommand(Box::new(command.message))),
+                )
+            });
+
+        let dialog = container(
+            column![
+                text_input(t("command-placeholder"), &self.query)
+                    .id(Self::input_id())
+                    .on_input(Message::CommandQueryChanged)
+                    .on_submit(Message::SubmitCommand)
+                    .padding(8.),
+                rows,
+            ]
+            .spacing(6.),
+        )
+        .width(480.)
+        .padding(6.)
+        .style(container::rounded_box);
+
+        opaque(
+            center(column![dialog].padding(Padding::from([80., 0.])))
+                .align_top(Length::Fill)
+                .style(|_| container::Style {
+                    background: Some(
+                        Color {
+                            a: 0.4,
+                            ..Color::BLACK
+                        }
+                        .into(),
+                    ),
+                    ..Default::default()
+                }),
+        )
+    }
+}
+
+fn is_subsequence(query: &str, name: &str) -> bool {
+    let mut name = name.chars();
+
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| name.any(|n| n == c))
+}
@@ -0,0 +1,107 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n, project::raw_path};
+
+/// What a file dialog picks, each kind of file opening where the last one
+/// was picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogKind {
+    Audio,
+    Image,
+    Font,
+    Project,
+    /// Files written out of the project, e.g. thumbnails.
+    Export,
+}
+
+/// The directory the last file of each kind was picked in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Directories {
+    #[serde(default, with = "raw_path::option")]
+    pub audio: Option<PathBuf>,
+    #[serde(default, with = "raw_path::option")]
+    pub image: Option<PathBuf>,
+    #[serde(default, with = "raw_path::option")]
+    pub font: Option<PathBuf>,
+    #[serde(default, with = "raw_path::option")]
+    pub project: Option<PathBuf>,
+    #[serde(default, with = "raw_path::option")]
+    pub export: Option<PathBuf>,
+}
+
+impl Directories {
+    const NONE: Directories = Directories {
+        audio: None,
+        image: None,
+        font: None,
+        project: None,
+        export: None,
+    };
+
+    fn get_mut(&mut self, kind: DialogKind) -> &mut Option<PathBuf> {
+        match kind {
+            DialogKind::Audio => &mut self.audio,
+            DialogKind::Image => &mut self.image,
+            DialogKind::Font => &mut self.font,
+            DialogKind::Project => &mut self.project,
+            DialogKind::Export => &mut self.export,
+        }
+    }
+}
+
+static DIRECTORIES: Mutex<Directories> = Mutex::new(Directories::NONE);
+
+/// Starts from the directories saved in the preferences.
+pub fn set(directories: Directories) {
+    if let Ok(mut current) = DIRECTORIES.lock() {
+        *current = directories;
+    }
+}
+
+/// A file dialog opening in the directory the last file of `kind` was picked
+/// in, or where the system puts it if there is none or it has since gone.
+pub fn dialog(kind: DialogKind) -> rfd::AsyncFileDialog {
+    let directory = DIRECTORIES
+        .lock()
+        .ok()
+        .and_then(|mut directories| directories.get_mut(kind).clone())
+        .filter(|directory| directory.is_dir());
+
+    match directory {
+        Some(directory) => rfd::AsyncFileDialog::new().set_directory(directory),
+        None => rfd::AsyncFileDialog::new(),
+    }
+}
+
+/// Opens the next dialog of `kind` in the directory of `picked`, then and in
+/// later runs.
+pub async fn remember(kind: DialogKind, picked: &Path) {
+    let Some(directory) = picked.parent() else {
+        return;
+    };
+    let directories = match DIRECTORIES.lock() {
+        Ok(mut directories) => {
+            let remembered = directories.get_mut(kind);
+            if remembered.as_deref() == Some(directory) {
+                return;
+            }
+            *remembered = Some(directory.to_path_buf());
+
+            directories.clone()
+        }
+        Err(_) => return,
+    };
+
+    let saved = i18n::update_preferences(move |preferences| {
+        preferences.directories = directories;
+    })
+    .await;
+    if let Err(error) = saved {
+        tracing::warn!("could not save the dialog directories: {}", error);
+    }
+}
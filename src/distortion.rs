@@ -0,0 +1,181 @@
+use std::{f32::consts::TAU, fmt::Display};
+
+use iced::{widget::image::Handle, Vector};
+use serde::{Deserialize, Serialize};
+
+use crate::{canvas::Playhead, i18n::t};
+
+/// Longest side, in pixels, that distorted images are redrawn at each frame,
+/// to keep large artwork from slowing playback down.
+const MAX_SIZE: u32 = 960;
+
+/// Bends an image layer's pixels around as it plays.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Distortion {
+    pub kind: DistortionKind,
+    /// Furthest a pixel is moved, in canvas pixels.
+    pub amount: f32,
+    /// Length of a wave or ripple, or size of the blobs of the texture, in
+    /// canvas pixels.
+    pub scale: f32,
+    /// Waves per second that pass through the layer.
+    pub speed: f32,
+    /// Whether the amount follows the bass, leaving the layer still in
+    /// silence.
+    pub follows_bass: bool,
+}
+
+impl Distortion {
+    pub const NONE: Distortion = Distortion {
+        kind: DistortionKind::None,
+        amount: 12.,
+        scale: 120.,
+        speed: 0.5,
+        follows_bass: false,
+    };
+
+    pub fn is_visible(&self) -> bool {
+        self.kind != DistortionKind::None && self.amount > 0.
+    }
+
+    /// How far pixels are moved at the playhead, in canvas pixels.
+    pub fn amplitude(&self, playhead: &Playhead) -> f32 {
+        if self.follows_bass {
+            self.amount * playhead.bass()
+        } else {
+            self.amount
+        }
+    }
+
+    /// `handle`'s pixels moved `amplitude` canvas pixels at most, `seconds`
+    /// into the layer, for an image drawn `width` canvas pixels wide.
+    /// Returns `None` unless the handle holds decoded pixels.
+    pub fn apply(
+        &self,
+        handle: &Handle,
+        width: f32,
+        amplitude: f32,
+        seconds: f32,
+    ) -> Option<Handle> {
+        let Handle::Rgba {
+            width: source_width,
+            height: source_height,
+            pixels,
+            ..
+        } = handle
+        else {
+            return None;
+        };
+        let (source_width, source_height) = (*source_width, *source_height);
+        let shrink = (MAX_SIZE as f32 / source_width.max(source_height) as f32).min(1.);
+        let output_width = ((source_width as f32 * shrink).round() as u32).max(1);
+        let output_height = ((source_height as f32 * shrink).round() as u32).max(1);
+        // Lengths are worked out in canvas pixels, then sampled from the
+        // source image.
+        let to_canvas = width / output_width as f32;
+        let to_source = source_width as f32 / width;
+        let phase = seconds * self.speed;
+        let scale = self.scale.max(1.);
+        let center = Vector::new(output_width as f32, output_height as f32) * (to_canvas / 2.);
+
+        let mut distorted = vec![0; output_width as usize * output_height as usize * 4];
+        for y in 0..output_height {
+            for x in 0..output_width {
+                let point = Vector::new(x as f32 + 0.5, y as f32 + 0.5) * to_canvas;
+                let offset = match self.kind {
+                    DistortionKind::None => Vector::ZERO,
+                    DistortionKind::Wave => {
+                        Vector::new(amplitude * (TAU * (point.y / scale - phase)).sin(), 0.)
+                    }
+                    DistortionKind::Ripple => {
+                        let from_center = point - center;
+                        let distance = from_center.x.hypot(from_center.y);
+                        if distance <= 0. {
+                            Vector::ZERO
+                        } else {
+                            from_center
+                                * (amplitude * (TAU * (distance / scale - phase)).sin() / distance)
+                        }
+                    }
+                    DistortionKind::Displace => {
+                        let (u, v) = (point.x / scale, point.y / scale - phase);
+
+                        Vector::new(
+                            value_noise(u, v) * 2. - 1.,
+                            value_noise(u + 31.7, v + 17.3) * 2. - 1.,
+                        ) * amplitude
+                    }
+                };
+                let sampled = (point + offset) * to_source;
+                let source_x = sampled.x.floor();
+                let source_y = sampled.y.floor();
+                // Pixels moved in from outside the image are left clear.
+                if source_x < 0.
+                    || source_y < 0.
+                    || source_x >= source_width as f32
+                    || source_y >= source_height as f32
+                {
+                    continue;
+                }
+
+                let from = (source_y as usize * source_width as usize + source_x as usize) * 4;
+                let to = (y as usize * output_width as usize + x as usize) * 4;
+                distorted[to..to + 4].copy_from_slice(&pixels[from..from + 4]);
+            }
+        }
+
+        Some(Handle::from_rgba(output_width, output_height, distorted))
+    }
+}
+
+impl Default for Distortion {
+    fn default() -> Self {
+        Distortion::NONE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DistortionKind {
+    #[default]
+    None,
+    /// Sways rows of the image from side to side.
+    Wave,
+    /// Rings spreading out from the center, like a drop hitting water.
+    Ripple,
+    /// Pushes pixels around by a drifting noise texture, for a liquid look.
+    Displace,
+}
+
+impl DistortionKind {
+    pub const ALL: [DistortionKind; 4] = [
+        DistortionKind::None,
+        DistortionKind::Wave,
+        DistortionKind::Ripple,
+        DistortionKind::Displace,
+    ];
+}
+
+impl Display for DistortionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DistortionKind::None => t("none"),
+            DistortionKind::Wave => t("distortion-wave"),
+            DistortionKind::Ripple => t("distortion-ripple"),
+            DistortionKind::Displace => t("distortion-displace"),
+        })
+    }
+}
+
+/// Smooth noise between 0 and 1, varying over about one unit.
+fn value_noise(x: f32, y: f32) -> f32 {
+    let corner = |x: f32, y: f32| ((x * 12.9898 + y * 78.233).sin() * 43758.547).rem_euclid(1.);
+    let smooth = |t: f32| t * t * (3. - 2. * t);
+
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let top = corner(x0, y0) + (corner(x0 + 1., y0) - corner(x0, y0)) * tx;
+    let bottom = corner(x0, y0 + 1.) + (corner(x0 + 1., y0 + 1.) - corner(x0, y0 + 1.)) * tx;
+
+    top + (bottom - top) * ty
+}
@@ -0,0 +1,472 @@
+use std::fmt::Display;
+
+use crate::i18n::{t, t_args};
+
+/// A formula typed into a property field in place of a number, e.g.
+/// `canvas.width / 2 - width / 2` or `bass * 100 + 20`, worked out again on
+/// every frame.
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    parsed: Result<Node, String>,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Expression {
+        Expression {
+            source: source.to_string(),
+            parsed: Parser::new(source).parse(),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Why the expression couldn't be read, if it couldn't.
+    pub fn error(&self) -> Option<&str> {
+        self.parsed.as_ref().err().map(String::as_str)
+    }
+
+    /// The value of the expression, unless it couldn't be read or doesn't
+    /// come out as a number, e.g. after dividing by zero.
+    pub fn evaluate(&self, variables: &Variables) -> Option<f32> {
+        let value = self.parsed.as_ref().ok()?.evaluate(variables);
+
+        value.is_finite().then_some(value)
+    }
+}
+
+/// What an expression can refer to by name.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Variables {
+    /// Seconds since the start of the track.
+    pub time: f32,
+    pub canvas_width: f32,
+    pub canvas_height: f32,
+    /// The size of the layer the expression is on.
+    pub width: f32,
+    pub height: f32,
+    /// Loudness of the bass, between 0 and 1.
+    pub bass: f32,
+    /// RMS loudness in decibels, at most 0.
+    pub level: f32,
+    /// Seconds since the last beat.
+    pub beat: f32,
+    /// Beats per minute, or 0 if there is no steady beat.
+    pub tempo: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    Time,
+    CanvasWidth,
+    CanvasHeight,
+    Width,
+    Height,
+    Bass,
+    Level,
+    Beat,
+    Tempo,
+    Pi,
+}
+
+impl Variable {
+    const ALL: [(&'static str, Variable); 10] = [
+        ("time", Variable::Time),
+        ("canvas.width", Variable::CanvasWidth),
+        ("canvas.height", Variable::CanvasHeight),
+        ("width", Variable::Width),
+        ("height", Variable::Height),
+        ("bass", Variable::Bass),
+        ("level", Variable::Level),
+        ("beat", Variable::Beat),
+        ("tempo", Variable::Tempo),
+        ("pi", Variable::Pi),
+    ];
+
+    fn value(self, variables: &Variables) -> f32 {
+        match self {
+            Variable::Time => variables.time,
+            Variable::CanvasWidth => variables.canvas_width,
+            Variable::CanvasHeight => variables.canvas_height,
+            Variable::Width => variables.width,
+            Variable::Height => variables.height,
+            Variable::Bass => variables.bass,
+            Variable::Level => variables.level,
+            Variable::Beat => variables.beat,
+            Variable::Tempo => variables.tempo,
+            Variable::Pi => std::f32::consts::PI,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Sin,
+    Cos,
+    Abs,
+    Sqrt,
+    Floor,
+    Round,
+    Min,
+    Max,
+}
+
+impl Function {
+    const ALL: [(&'static str, Function); 8] = [
+        ("sin", Function::Sin),
+        ("cos", Function::Cos),
+        ("abs", Function::Abs),
+        ("sqrt", Function::Sqrt),
+        ("floor", Function::Floor),
+        ("round", Function::Round),
+        ("min", Function::Min),
+        ("max", Function::Max),
+    ];
+
+    fn arguments(self) -> usize {
+        match self {
+            Function::Min | Function::Max => 2,
+            _ => 1,
+        }
+    }
+
+    fn call(self, arguments: &[f32]) -> f32 {
+        let x = arguments[0];
+
+        match self {
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Abs => x.abs(),
+            Function::Sqrt => x.sqrt(),
+            Function::Floor => x.floor(),
+            Function::Round => x.round(),
+            Function::Min => x.min(arguments[1]),
+            Function::Max => x.max(arguments[1]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Number(f32),
+    Variable(Variable),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, variables: &Variables) -> f32 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Variable(variable) => variable.value(variables),
+            Node::Negate(node) => -node.evaluate(variables),
+            Node::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(variables), right.evaluate(variables));
+
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Remainder => left % right,
+                    Operator::Power => left.powf(right),
+                }
+            }
+            Node::Call(function, arguments) => {
+                let arguments: Vec<_> = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(variables))
+                    .collect();
+
+                function.call(&arguments)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Name(String),
+    Symbol(char),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Name(name) => f.write_str(name),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+/// Reads an expression by recursive descent, with the usual precedence:
+/// `^` binds tighter than `*`, `/` and `%`, which bind tighter than `+` and
+/// `-`.
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn new(source: &str) -> Parser {
+        let mut tokens = vec![];
+        let mut chars = source.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c.is_ascii_digit() || c == '.' {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    number.push(c);
+                    chars.next();
+                }
+                // A malformed number like `1.2.3` is kept as a name, to be
+                // reported as unknown.
+                tokens.push(match number.parse() {
+                    Ok(value) => Token::Number(value),
+                    Err(_) => Token::Name(number),
+                });
+            } else if c.is_alphabetic() || c == '_' {
+                let mut name = String::new();
+                while let Some(&c) = chars
+                    .peek()
+                    .filter(|c| c.is_alphanumeric() || **c == '_' || **c == '.')
+                {
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+            } else {
+                tokens.push(Token::Symbol(c));
+                chars.next();
+            }
+        }
+
+        Parser { tokens, next: 0 }
+    }
+
+    fn parse(mut self) -> Result<Node, String> {
+        let node = self.sum()?;
+
+        match self.tokens.get(self.next) {
+            Some(token) => Err(t_args("expression-unexpected", &[("token", &token)])),
+            None => Ok(node),
+        }
+    }
+
+    fn peek_symbol(&self) -> Option<char> {
+        match self.tokens.get(self.next) {
+            Some(Token::Symbol(symbol)) => Some(*symbol),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        if self.peek_symbol() == Some(symbol) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(t_args("expression-expected", &[("symbol", &symbol)]))
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        let mut node = self.product()?;
+
+        while let Some(operator) = match self.peek_symbol() {
+            Some('+') => Some(Operator::Add),
+            Some('-') => Some(Operator::Subtract),
+            _ => None,
+        } {
+            self.next += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.product()?));
+        }
+
+        Ok(node)
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        let mut node = self.unary()?;
+
+        while let Some(operator) = match self.peek_symbol() {
+            Some('*') => Some(Operator::Multiply),
+            Some('/') => Some(Operator::Divide),
+            Some('%') => Some(Operator::Remainder),
+            _ => None,
+        } {
+            self.next += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.peek_symbol() == Some('-') {
+            self.next += 1;
+
+            return Ok(Node::Negate(Box::new(self.unary()?)));
+        }
+
+        let base = self.atom()?;
+        if self.peek_symbol() == Some('^') {
+            self.next += 1;
+
+            return Ok(Node::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let Some(token) = self.tokens.get(self.next).cloned() else {
+            return Err(t("expression-end").to_string());
+        };
+        self.next += 1;
+
+        match token {
+            Token::Number(value) => Ok(Node::Number(value)),
+            Token::Symbol('(') => {
+                let node = self.sum()?;
+                self.expect(')')?;
+
+                Ok(node)
+            }
+            Token::Name(name) => {
+                if let Some((_, function)) = Function::ALL.iter().find(|(n, _)| *n == name) {
+                    self.expect('(')?;
+                    let mut arguments = vec![self.sum()?];
+                    while self.peek_symbol() == Some(',') {
+                        self.next += 1;
+                        arguments.push(self.sum()?);
+                    }
+                    self.expect(')')?;
+
+                    if arguments.len() != function.arguments() {
+                        return Err(t_args(
+                            "expression-arguments",
+                            &[("name", &name), ("count", &function.arguments())],
+                        ));
+                    }
+
+                    return Ok(Node::Call(*function, arguments));
+                }
+
+                Variable::ALL
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, variable)| Node::Variable(*variable))
+                    .ok_or_else(|| t_args("expression-unknown-name", &[("name", &name)]))
+            }
+            Token::Symbol(symbol) => Err(t_args("expression-unexpected", &[("token", &symbol)])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(source: &str) -> Option<f32> {
+        Expression::parse(source).evaluate(&Variables::default())
+    }
+
+    fn error(source: &str) -> Option<String> {
+        Expression::parse(source).error().map(str::to_string)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Some(7.));
+        assert_eq!(evaluate("(1 + 2) * 3"), Some(9.));
+        assert_eq!(evaluate("7 % 4 * 2"), Some(6.));
+        assert_eq!(evaluate("10 - 4 - 3"), Some(3.));
+    }
+
+    #[test]
+    fn power_binds_tighter_than_negation() {
+        assert_eq!(evaluate("-2^2"), Some(-4.));
+        assert_eq!(evaluate("(-2)^2"), Some(4.));
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(evaluate("2^3^2"), Some(512.));
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(evaluate("min(3, 2) + max(3, 2)"), Some(5.));
+        assert_eq!(evaluate("abs(-1.5)"), Some(1.5));
+        assert_eq!(evaluate("floor(2.7) + round(2.5)"), Some(5.));
+    }
+
+    #[test]
+    fn wrong_number_of_arguments() {
+        assert_eq!(error("min(1)").as_deref(), Some("min takes 2 arguments"));
+        assert_eq!(error("sin(1, 2)").as_deref(), Some("sin takes 1 argument"));
+    }
+
+    #[test]
+    fn variables() {
+        let variables = Variables {
+            canvas_width: 1280.,
+            width: 200.,
+            ..Variables::default()
+        };
+
+        assert_eq!(
+            Expression::parse("canvas.width / 2 - width / 2").evaluate(&variables),
+            Some(540.)
+        );
+    }
+
+    #[test]
+    fn malformed_number() {
+        assert_eq!(error("1.2.3").as_deref(), Some("unknown name \"1.2.3\""));
+    }
+
+    #[test]
+    fn unknown_name() {
+        assert_eq!(
+            error("loudness * 2").as_deref(),
+            Some("unknown name \"loudness\"")
+        );
+        assert_eq!(evaluate("loudness * 2"), None);
+    }
+
+    #[test]
+    fn malformed_syntax() {
+        assert_eq!(error("(1 + 2").as_deref(), Some("expected \")\""));
+        assert_eq!(error("1 +").as_deref(), Some("unexpected end"));
+        assert_eq!(error("1 2").as_deref(), Some("unexpected \"2\""));
+    }
+
+    #[test]
+    fn division_by_zero_has_no_value() {
+        assert_eq!(error("1 / 0"), None);
+        assert_eq!(evaluate("1 / 0"), None);
+        assert_eq!(evaluate("0 / 0"), None);
+        assert_eq!(evaluate("sqrt(-1)"), None);
+    }
+}
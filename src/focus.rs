@@ -0,0 +1,64 @@
+use iced::{
+    border,
+    widget::{container, Container},
+    Element, Theme,
+};
+
+use crate::Message;
+
+/// A part of the window that F6 moves the keyboard focus between, so the app
+/// can be used without a mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    /// The playback buttons, moved between with the left and right arrows.
+    Transport,
+    /// The layer list, moved through with the up and down arrows.
+    Layers,
+    /// The selected layer's settings, moved through with Tab.
+    Settings,
+}
+
+impl Panel {
+    const ALL: [Panel; 3] = [Panel::Transport, Panel::Layers, Panel::Settings];
+
+    /// The panel `delta` steps on from `current`, wrapping around. Stepping
+    /// on from no panel starts at the first or the last.
+    pub fn cycle(current: Option<Panel>, delta: i32) -> Panel {
+        let count = Self::ALL.len() as i32;
+        let index = match current {
+            Some(panel) => Self::ALL.iter().position(|p| *p == panel).unwrap_or(0) as i32 + delta,
+            None if delta < 0 => count - 1,
+            None => 0,
+        };
+
+        Self::ALL[index.rem_euclid(count) as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrow {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// `content` outlined when it has the keyboard focus. Room for the outline is
+/// kept either way, so focusing doesn't move things around.
+pub fn focus_ring<'a>(
+    content: impl Into<Element<'a, Message>>,
+    is_focused: bool,
+) -> Container<'a, Message> {
+    container(content).padding(2.).style(move |theme: &Theme| {
+        if is_focused {
+            container::Style {
+                border: border::rounded(4.)
+                    .color(theme.extended_palette().primary.strong.color)
+                    .width(2.),
+                ..container::Style::default()
+            }
+        } else {
+            container::Style::default()
+        }
+    })
+}
@@ -0,0 +1,280 @@
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use iced::{
+    advanced::graphics::text::{cosmic_text::fontdb, font_system},
+    font, Font,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::i18n::t;
+
+/// The name of a font family, e.g. `Inter`. iced refers to families by
+/// `&'static str`, so every name is leaked once and shared from then on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FamilyName(&'static str);
+
+impl FamilyName {
+    pub fn new(name: &str) -> FamilyName {
+        static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+        let mut names = NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(interned) = names.iter().find(|interned| **interned == name) {
+            return FamilyName(interned);
+        }
+        let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+        names.push(interned);
+
+        FamilyName(interned)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.0
+    }
+}
+
+impl Display for FamilyName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Serialize for FamilyName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for FamilyName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(FamilyName::new(&String::deserialize(deserializer)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum FontWeight {
+    Thin,
+    ExtraLight,
+    Light,
+    #[default]
+    Normal,
+    Medium,
+    Semibold,
+    Bold,
+    ExtraBold,
+    Black,
+}
+
+impl FontWeight {
+    pub const ALL: [FontWeight; 9] = [
+        FontWeight::Thin,
+        FontWeight::ExtraLight,
+        FontWeight::Light,
+        FontWeight::Normal,
+        FontWeight::Medium,
+        FontWeight::Semibold,
+        FontWeight::Bold,
+        FontWeight::ExtraBold,
+        FontWeight::Black,
+    ];
+
+    /// The weight closest to a CSS-style number between 100 and 900.
+    fn from_number(number: u16) -> FontWeight {
+        let index = ((number + 50) / 100).clamp(1, 9) - 1;
+
+        FontWeight::ALL[index as usize]
+    }
+
+    fn to_iced(self) -> font::Weight {
+        match self {
+            FontWeight::Thin => font::Weight::Thin,
+            FontWeight::ExtraLight => font::Weight::ExtraLight,
+            FontWeight::Light => font::Weight::Light,
+            FontWeight::Normal => font::Weight::Normal,
+            FontWeight::Medium => font::Weight::Medium,
+            FontWeight::Semibold => font::Weight::Semibold,
+            FontWeight::Bold => font::Weight::Bold,
+            FontWeight::ExtraBold => font::Weight::ExtraBold,
+            FontWeight::Black => font::Weight::Black,
+        }
+    }
+}
+
+impl Display for FontWeight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FontWeight::Thin => t("weight-thin"),
+            FontWeight::ExtraLight => t("weight-extra-light"),
+            FontWeight::Light => t("weight-light"),
+            FontWeight::Normal => t("weight-regular"),
+            FontWeight::Medium => t("weight-medium"),
+            FontWeight::Semibold => t("weight-semibold"),
+            FontWeight::Bold => t("weight-bold"),
+            FontWeight::ExtraBold => t("weight-extra-bold"),
+            FontWeight::Black => t("weight-black"),
+        })
+    }
+}
+
+/// The font a text layer is drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextFont {
+    /// `None` for the layer's usual font. A family missing from the system
+    /// falls back to a similar one when drawn.
+    pub family: Option<FamilyName>,
+    pub weight: FontWeight,
+    pub italic: bool,
+}
+
+impl TextFont {
+    pub const DEFAULT: TextFont = TextFont {
+        family: None,
+        weight: FontWeight::Normal,
+        italic: false,
+    };
+
+    /// The font to draw with, `fallback` being the layer's usual font.
+    pub fn to_font(self, fallback: Font) -> Font {
+        Font {
+            family: self.family.map_or(fallback.family, |family| {
+                font::Family::Name(family.as_str())
+            }),
+            weight: self.weight.to_iced(),
+            style: if self.italic {
+                font::Style::Italic
+            } else {
+                font::Style::Normal
+            },
+            ..fallback
+        }
+    }
+}
+
+/// A font family that can be picked, with the styles it comes in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFamily {
+    pub name: FamilyName,
+    /// Sorted from lightest to boldest.
+    pub weights: Vec<FontWeight>,
+    pub has_italic: bool,
+    /// Whether it was imported into the project, rather than installed.
+    pub is_imported: bool,
+}
+
+impl FontFamily {
+    /// The weight in this family closest to `weight`.
+    pub fn closest_weight(&self, weight: FontWeight) -> FontWeight {
+        self.weights
+            .iter()
+            .copied()
+            .min_by_key(|candidate| (*candidate as i32 - weight as i32).abs())
+            .unwrap_or(weight)
+    }
+}
+
+/// A font imported into the project. It is saved inside the project file, so
+/// the project looks the same on systems that don't have it installed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedFont {
+    pub family: FamilyName,
+    #[serde(
+        serialize_with = "serialize_data",
+        deserialize_with = "deserialize_data"
+    )]
+    pub data: Arc<Vec<u8>>,
+}
+
+/// Writes the font as bytes, which the project format stores as base64
+/// instead of a list of numbers.
+fn serialize_data<S: Serializer>(data: &Arc<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(data)
+}
+
+fn deserialize_data<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<Vec<u8>>, D::Error> {
+    struct Visitor;
+
+    impl serde::de::Visitor<'_> for Visitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("font data")
+        }
+
+        fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(bytes.to_vec())
+        }
+
+        fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_byte_buf(Visitor).map(Arc::new)
+}
+
+/// The font families installed on the system, sorted by name.
+pub fn system_families() -> Vec<FontFamily> {
+    let mut font_system = font_system()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    families(font_system.raw().db(), false)
+        .into_iter()
+        // iced's own icon font isn't meant for text.
+        .filter(|family| family.name.as_str() != "Iced-Icons")
+        .collect()
+}
+
+/// The families in a font file, empty if it isn't one.
+pub fn families_in(data: &[u8]) -> Vec<FontFamily> {
+    let mut database = fontdb::Database::new();
+    database.load_font_data(data.to_vec());
+
+    families(&database, true)
+}
+
+fn families(database: &fontdb::Database, is_imported: bool) -> Vec<FontFamily> {
+    let mut families: Vec<FontFamily> = vec![];
+
+    for face in database.faces() {
+        let Some((name, _)) = face.families.first() else {
+            continue;
+        };
+        let name = FamilyName::new(name);
+        let weight = FontWeight::from_number(face.weight.0);
+        let is_italic = face.style != fontdb::Style::Normal;
+
+        let index = match families.iter().position(|family| family.name == name) {
+            Some(index) => index,
+            None => {
+                families.push(FontFamily {
+                    name,
+                    weights: vec![],
+                    has_italic: false,
+                    is_imported,
+                });
+                families.len() - 1
+            }
+        };
+        let family = &mut families[index];
+        if !family.weights.contains(&weight) {
+            family.weights.push(weight);
+        }
+        family.has_italic |= is_italic;
+    }
+
+    for family in &mut families {
+        family.weights.sort();
+    }
+    families.sort_by(|a, b| {
+        a.name
+            .as_str()
+            .to_lowercase()
+            .cmp(&b.name.as_str().to_lowercase())
+    });
+
+    families
+}
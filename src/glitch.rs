@@ -0,0 +1,177 @@
+use std::fmt::Display;
+
+use iced::widget::image::Handle;
+use serde::{Deserialize, Serialize};
+
+use crate::{canvas::Playhead, i18n::t};
+
+/// Longest side, in pixels, that glitched images are redrawn at each frame.
+const MAX_SIZE: u32 = 960;
+
+/// New blocks are torn out and colors split this many times a second.
+const STEPS_PER_SECOND: f32 = 30.;
+
+/// Tears an image layer apart like a corrupted video stream.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Glitch {
+    pub is_enabled: bool,
+    /// Furthest the red and blue channels are pulled apart, in canvas
+    /// pixels.
+    pub split: f32,
+    /// Share of the image's blocks that are shifted out of place, from 0 to
+    /// 1.
+    pub blocks: f32,
+    /// How much every other line is darkened, from 0 to 1.
+    pub scanlines: f32,
+    pub trigger: GlitchTrigger,
+}
+
+impl Glitch {
+    pub const NONE: Glitch = Glitch {
+        is_enabled: false,
+        split: 8.,
+        blocks: 0.2,
+        scanlines: 0.3,
+        trigger: GlitchTrigger::Always,
+    };
+
+    pub fn is_visible(&self) -> bool {
+        self.is_enabled && (self.split > 0. || self.blocks > 0. || self.scanlines > 0.)
+    }
+
+    /// How strongly the layer glitches at the playhead, from 0 to 1.
+    pub fn intensity(&self, playhead: &Playhead) -> f32 {
+        match self.trigger {
+            GlitchTrigger::Always => 1.,
+            GlitchTrigger::Bass => playhead.bass(),
+            GlitchTrigger::Loudness => playhead.loudness(),
+            GlitchTrigger::Beat => playhead
+                .since_beat()
+                .map_or(0., |since| (-since * 8.).exp()),
+        }
+    }
+
+    /// `handle`'s pixels glitched at `intensity`, `seconds` into the layer,
+    /// for an image drawn `width` canvas pixels wide. Returns `None` if
+    /// there's nothing to glitch, or the handle doesn't hold decoded pixels.
+    pub fn apply(
+        &self,
+        handle: &Handle,
+        width: f32,
+        intensity: f32,
+        seconds: f32,
+    ) -> Option<Handle> {
+        let Handle::Rgba {
+            width: source_width,
+            height: source_height,
+            pixels,
+            ..
+        } = handle
+        else {
+            return None;
+        };
+        if intensity <= 0. {
+            return None;
+        }
+        let (source_width, source_height) = (*source_width, *source_height);
+        let shrink = (MAX_SIZE as f32 / source_width.max(source_height) as f32).min(1.);
+        let output_width = ((source_width as f32 * shrink).round() as u32).max(1);
+        let output_height = ((source_height as f32 * shrink).round() as u32).max(1);
+        let to_canvas = width / output_width as f32;
+        let to_source = source_width as f32 / output_width as f32;
+
+        let step = (seconds * STEPS_PER_SECOND).floor();
+        // Splits flicker in strength from step to step, like a bad signal.
+        let split = self.split * intensity * (0.5 + noise(step, 0., 0.)) / to_canvas;
+        let block_size = (output_width.max(output_height) / 16).max(1);
+        let torn = self.blocks * intensity;
+        let darkened = 1. - self.scanlines * intensity * 0.6;
+
+        let sample = |x: f32, y: u32, channel: usize| {
+            let source_x = ((x * to_source) as i64).clamp(0, source_width as i64 - 1) as usize;
+            let source_y = ((y as f32 * to_source) as usize).min(source_height as usize - 1);
+
+            pixels[(source_y * source_width as usize + source_x) * 4 + channel]
+        };
+
+        let mut glitched = vec![0; output_width as usize * output_height as usize * 4];
+        for y in 0..output_height {
+            // Lines two canvas pixels apart, so they stay visible however
+            // large the image is.
+            let is_scanline = (y as f32 * to_canvas / 2.).floor() as u32 % 2 == 1;
+
+            for x in 0..output_width {
+                let block = ((x / block_size) as f32, (y / block_size) as f32);
+                let shift = if noise(step, block.0, block.1) < torn {
+                    (noise(step + 0.5, block.0, block.1) - 0.5) * output_width as f32 * 0.3
+                } else {
+                    0.
+                };
+                let x_at = x as f32 + 0.5 + shift;
+
+                let to = (y as usize * output_width as usize + x as usize) * 4;
+                glitched[to] = sample(x_at + split, y, 0);
+                glitched[to + 1] = sample(x_at, y, 1);
+                glitched[to + 2] = sample(x_at - split, y, 2);
+                glitched[to + 3] = sample(x_at, y, 3)
+                    .max(sample(x_at + split, y, 3))
+                    .max(sample(x_at - split, y, 3));
+                if is_scanline {
+                    for channel in &mut glitched[to..to + 3] {
+                        *channel = (*channel as f32 * darkened) as u8;
+                    }
+                }
+            }
+        }
+
+        Some(Handle::from_rgba(output_width, output_height, glitched))
+    }
+}
+
+impl Default for Glitch {
+    fn default() -> Self {
+        Glitch::NONE
+    }
+}
+
+/// What makes a layer glitch harder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GlitchTrigger {
+    /// Glitches steadily, whatever the audio does.
+    #[default]
+    Always,
+    Bass,
+    Loudness,
+    /// Bursts on every beat, settling until the next.
+    Beat,
+}
+
+impl GlitchTrigger {
+    pub const ALL: [GlitchTrigger; 4] = [
+        GlitchTrigger::Always,
+        GlitchTrigger::Bass,
+        GlitchTrigger::Loudness,
+        GlitchTrigger::Beat,
+    ];
+
+    pub fn is_audio_reactive(self) -> bool {
+        self != GlitchTrigger::Always
+    }
+}
+
+impl Display for GlitchTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GlitchTrigger::Always => t("timing-always"),
+            GlitchTrigger::Bass => t("glitch-bass"),
+            GlitchTrigger::Loudness => t("glitch-loudness"),
+            GlitchTrigger::Beat => t("glitch-beat"),
+        })
+    }
+}
+
+/// A random number between 0 and 1 for each step and block.
+fn noise(step: f32, x: f32, y: f32) -> f32 {
+    ((step * 12.9898 + x * 78.233 + y * 37.719).sin() * 43758.547).rem_euclid(1.)
+}
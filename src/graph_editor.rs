@@ -0,0 +1,310 @@
+use iced::{
+    mouse,
+    widget::canvas::{self, Path, Stroke},
+    Color, Point, Rectangle, Renderer, Size, Theme,
+};
+
+use crate::{
+    keyframes::{Bezier, Track},
+    Message,
+};
+
+/// Plots a property's keyframes over time, letting them and the easing handles
+/// of the segments between them be dragged.
+pub struct GraphEditor<'a> {
+    pub layer: usize,
+    pub track: &'a Track,
+    /// Seconds into the track.
+    pub playhead: f32,
+    /// Length of the track in seconds, if known.
+    pub duration: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    Keyframe(usize),
+    /// The handle leaving the keyframe at the start of a segment.
+    OutHandle(usize),
+    /// The handle entering the keyframe at the end of a segment.
+    InHandle(usize),
+}
+
+/// Something being dragged. The view is kept as it was when the drag started,
+/// so that the graph does not rescale under the cursor.
+#[derive(Debug, Clone, Copy)]
+pub struct Drag {
+    target: Target,
+    view: View,
+}
+
+/// The range of time and values shown in the graph.
+#[derive(Debug, Clone, Copy)]
+struct View {
+    size: Size,
+    end: f32,
+    low: f32,
+    high: f32,
+}
+
+impl View {
+    const PADDING: f32 = 12.;
+
+    fn new(editor: &GraphEditor, size: Size) -> View {
+        let keyframes = &editor.track.keyframes;
+        let last = keyframes.last().map_or(0., |keyframe| keyframe.time);
+        let end = editor.duration.unwrap_or(0.).max(last + 1.);
+
+        let (low, high) = keyframes.iter().fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(low, high), keyframe| (low.min(keyframe.value), high.max(keyframe.value)),
+        );
+        let margin = ((high - low) * 0.2).max(1.);
+
+        View {
+            size,
+            end,
+            low: low - margin,
+            high: high + margin,
+        }
+    }
+
+    fn point(&self, time: f32, value: f32) -> Point {
+        let width = self.size.width - Self::PADDING * 2.;
+        let height = self.size.height - Self::PADDING * 2.;
+
+        Point::new(
+            Self::PADDING + time / self.end * width,
+            Self::PADDING + (self.high - value) / (self.high - self.low) * height,
+        )
+    }
+
+    fn time_value(&self, point: Point) -> (f32, f32) {
+        let width = self.size.width - Self::PADDING * 2.;
+        let height = self.size.height - Self::PADDING * 2.;
+
+        (
+            ((point.x - Self::PADDING) / width * self.end).max(0.),
+            self.high - (point.y - Self::PADDING) / height * (self.high - self.low),
+        )
+    }
+}
+
+impl GraphEditor<'_> {
+    const HIT_RADIUS: f32 = 6.;
+
+    /// Positions of the easing handles of the segment starting at `index`.
+    fn handles(&self, view: &View, index: usize) -> Option<(Point, Point)> {
+        let start = self.track.keyframes.get(index)?;
+        let end = self.track.keyframes.get(index + 1)?;
+        let duration = end.time - start.time;
+        let change = end.value - start.value;
+        let Bezier { x1, y1, x2, y2 } = start.ease;
+
+        Some((
+            view.point(start.time + x1 * duration, start.value + y1 * change),
+            view.point(start.time + x2 * duration, start.value + y2 * change),
+        ))
+    }
+
+    fn hit(&self, view: &View, position: Point) -> Option<Target> {
+        let near = |point: Point| point.distance(position) <= Self::HIT_RADIUS;
+
+        let keyframe = self
+            .track
+            .keyframes
+            .iter()
+            .position(|keyframe| near(view.point(keyframe.time, keyframe.value)));
+
+        keyframe.map(Target::Keyframe).or_else(|| {
+            (0..self.track.keyframes.len()).find_map(|index| {
+                let (out_handle, in_handle) = self.handles(view, index)?;
+
+                if near(out_handle) {
+                    Some(Target::OutHandle(index))
+                } else if near(in_handle) {
+                    Some(Target::InHandle(index))
+                } else {
+                    None
+                }
+            })
+        })
+    }
+
+    fn dragged(&self, target: Target, view: &View, position: Point) -> Option<Message> {
+        let (time, value) = view.time_value(position);
+        let property = self.track.property;
+
+        match target {
+            Target::Keyframe(index) => Some(Message::KeyframeMoved(
+                self.layer, property, index, time, value,
+            )),
+            Target::OutHandle(index) | Target::InHandle(index) => {
+                let start = self.track.keyframes.get(index)?;
+                let end = self.track.keyframes.get(index + 1)?;
+                let x = ((time - start.time) / (end.time - start.time)).clamp(0., 1.);
+                let change = end.value - start.value;
+                // A flat segment has no height to place the handles in.
+                let y = |current: f32| {
+                    if change.abs() > f32::EPSILON {
+                        (value - start.value) / change
+                    } else {
+                        current
+                    }
+                };
+
+                let ease = match target {
+                    Target::OutHandle(_) => Bezier {
+                        x1: x,
+                        y1: y(start.ease.y1),
+                        ..start.ease
+                    },
+                    _ => Bezier {
+                        x2: x,
+                        y2: y(start.ease.y2),
+                        ..start.ease
+                    },
+                };
+
+                Some(Message::KeyframeEased(self.layer, property, index, ease))
+            }
+        }
+    }
+}
+
+impl canvas::Program<Message> for GraphEditor<'_> {
+    type State = Option<Drag>;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let Some(position) = cursor.position_in(bounds) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let view = View::new(self, bounds.size());
+
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                *state = self
+                    .hit(&view, position)
+                    .map(|target| Drag { target, view });
+
+                (canvas::event::Status::Captured, None)
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                match self.hit(&view, position) {
+                    Some(Target::Keyframe(index)) => (
+                        canvas::event::Status::Captured,
+                        Some(Message::KeyframeRemoved(
+                            self.layer,
+                            self.track.property,
+                            index,
+                        )),
+                    ),
+                    _ => (canvas::event::Status::Ignored, None),
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => match state {
+                Some(drag) => (
+                    canvas::event::Status::Captured,
+                    self.dragged(drag.target, &drag.view, position),
+                ),
+                None => (canvas::event::Status::Ignored, None),
+            },
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                *state = None;
+
+                (canvas::event::Status::Captured, None)
+            }
+            _ => (canvas::event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let palette = theme.extended_palette();
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let view = state.map_or_else(|| View::new(self, bounds.size()), |drag| drag.view);
+
+        // A line every second, or every ten for long tracks.
+        let step = if view.end > 60. { 10. } else { 1. };
+        let grid = Stroke::default()
+            .with_color(palette.background.weak.color)
+            .with_width(1.);
+        for second in 0..=(view.end / step) as usize {
+            let x = view.point(second as f32 * step, 0.).x;
+            frame.stroke(
+                &Path::line(Point::new(x, 0.), Point::new(x, bounds.height)),
+                grid,
+            );
+        }
+
+        let samples = (bounds.width as usize).max(2);
+        let curve = Path::new(|builder| {
+            for sample in 0..=samples {
+                let time = sample as f32 / samples as f32 * view.end;
+                let point = view.point(time, self.track.value(time).unwrap_or(0.));
+                if sample == 0 {
+                    builder.move_to(point);
+                } else {
+                    builder.line_to(point);
+                }
+            }
+        });
+        frame.stroke(
+            &curve,
+            Stroke::default()
+                .with_color(palette.primary.base.color)
+                .with_width(2.),
+        );
+
+        let handle_color = palette.secondary.base.color;
+        for index in 0..self.track.keyframes.len() {
+            let Some((out_handle, in_handle)) = self.handles(&view, index) else {
+                continue;
+            };
+            let start = &self.track.keyframes[index];
+            let end = &self.track.keyframes[index + 1];
+
+            for (keyframe, handle) in [
+                (view.point(start.time, start.value), out_handle),
+                (view.point(end.time, end.value), in_handle),
+            ] {
+                frame.stroke(
+                    &Path::line(keyframe, handle),
+                    Stroke::default().with_color(handle_color).with_width(1.),
+                );
+                frame.fill(&Path::circle(handle, 3.), handle_color);
+            }
+        }
+
+        for keyframe in &self.track.keyframes {
+            frame.fill(
+                &Path::circle(view.point(keyframe.time, keyframe.value), 4.5),
+                Color::WHITE,
+            );
+        }
+
+        let playhead = view.point(self.playhead, 0.).x;
+        frame.stroke(
+            &Path::line(
+                Point::new(playhead, 0.),
+                Point::new(playhead, bounds.height),
+            ),
+            Stroke::default()
+                .with_color(palette.danger.base.color)
+                .with_width(1.),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
+
+use fluent_bundle::{
+    concurrent::FluentBundle, types::FluentNumber, FluentArgs, FluentResource, FluentValue,
+};
+use fluent_syntax::ast;
+use serde::{Deserialize, Serialize};
+use unic_langid::LanguageIdentifier;
+
+use crate::{directories::Directories, Error};
+
+/// A language the interface is translated into. Strings missing from a
+/// translation fall back to English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.ftl"),
+            Language::German => include_str!("../locales/de.ftl"),
+        }
+    }
+
+    /// The language of the system's locale, if the interface is translated
+    /// into it.
+    pub fn detect() -> Language {
+        let locale = sys_locale::get_locale().unwrap_or_default();
+
+        Language::ALL
+            .into_iter()
+            .find(|language| locale.starts_with(language.code()))
+            .unwrap_or_default()
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Each in its own language, so it can be found by those who need it.
+        f.write_str(match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        })
+    }
+}
+
+/// Index into [`Language::ALL`] of the language strings are looked up in.
+static LANGUAGE: AtomicUsize = AtomicUsize::new(0);
+
+pub fn language() -> Language {
+    Language::ALL[LANGUAGE.load(Ordering::Relaxed)]
+}
+
+pub fn set_language(language: Language) {
+    let index = Language::ALL
+        .iter()
+        .position(|candidate| *candidate == language)
+        .unwrap_or_default();
+
+    LANGUAGE.store(index, Ordering::Relaxed);
+}
+
+/// The interface strings of one language.
+struct Catalog {
+    bundle: FluentBundle<FluentResource>,
+    /// Every message formatted without arguments, for [`t`] to hand out.
+    strings: HashMap<&'static str, String>,
+}
+
+impl Catalog {
+    /// Reads the language's Fluent file. Messages with mistakes in them are
+    /// logged and left out, to fall back to English.
+    fn new(language: Language) -> Catalog {
+        let resource = FluentResource::try_new(language.source().to_string()).unwrap_or_else(
+            |(resource, errors)| {
+                tracing::warn!("mistakes in the {} strings: {:?}", language.code(), errors);
+                resource
+            },
+        );
+        let code: LanguageIdentifier = language.code().parse().unwrap_or_default();
+        let mut bundle = FluentBundle::new_concurrent(vec![code]);
+        // The marks isolating arguments from the text around them show up
+        // as boxes in fonts without them.
+        bundle.set_use_isolating(false);
+        if let Err(errors) = bundle.add_resource(resource) {
+            tracing::warn!("duplicate {} strings: {:?}", language.code(), errors);
+        }
+
+        let ids = fluent_syntax::parser::parse_runtime(language.source())
+            .unwrap_or_else(|(resource, _)| resource)
+            .body
+            .into_iter()
+            .filter_map(|entry| match entry {
+                ast::Entry::Message(message) => Some(message.id.name),
+                _ => None,
+            });
+        let strings = ids
+            .filter_map(|id| Some((id, format(&bundle, id, None)?)))
+            .collect();
+
+        Catalog { bundle, strings }
+    }
+}
+
+fn catalogs() -> &'static [Catalog] {
+    static CATALOGS: OnceLock<Vec<Catalog>> = OnceLock::new();
+
+    CATALOGS.get_or_init(|| Language::ALL.into_iter().map(Catalog::new).collect())
+}
+
+/// The message `key` of `bundle`, if it has one, with `args` put in.
+fn format(
+    bundle: &FluentBundle<FluentResource>,
+    key: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let pattern = bundle.get_message(key)?.value()?;
+    let mut errors = vec![];
+
+    Some(
+        bundle
+            .format_pattern(pattern, args, &mut errors)
+            .into_owned(),
+    )
+}
+
+/// The interface string `key` in the current language.
+pub fn t(key: &'static str) -> &'static str {
+    let catalogs = catalogs();
+
+    catalogs[LANGUAGE.load(Ordering::Relaxed)]
+        .strings
+        .get(key)
+        .or_else(|| catalogs[0].strings.get(key))
+        .map_or(key, String::as_str)
+}
+
+/// [`t`], with every `{ $name }` replaced by the value given for `name`.
+/// Values that are numbers pick the plural form, e.g. `{ $count -> [one]
+/// ... *[other] ... }`.
+pub fn t_args(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    let catalogs = catalogs();
+    let args = arguments(args);
+
+    format(
+        &catalogs[LANGUAGE.load(Ordering::Relaxed)].bundle,
+        key,
+        Some(&args),
+    )
+    .or_else(|| format(&catalogs[0].bundle, key, Some(&args)))
+    .unwrap_or_else(|| key.to_string())
+}
+
+fn arguments<'a>(args: &[(&'a str, &dyn Display)]) -> FluentArgs<'a> {
+    args.iter()
+        .map(|(name, value)| {
+            let value = value.to_string();
+            // Only plain decimals, as `inf` or `1e3` in e.g. a layer's name
+            // would be written out as numbers.
+            let is_number = !value.is_empty()
+                && value
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == '.' || c == '-');
+            let value = match value.parse::<FluentNumber>() {
+                Ok(number) if is_number => FluentValue::Number(number),
+                _ => FluentValue::from(value),
+            };
+
+            (*name, value)
+        })
+        .collect()
+}
+
+/// Settings kept between runs, as opposed to the project's.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Preferences {
+    /// `None` to follow the system's locale.
+    pub language: Option<Language>,
+    /// Where file dialogs open.
+    #[serde(default)]
+    pub directories: Directories,
+}
+
+fn preferences_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("roygbiv").join("preferences.ron"))
+}
+
+pub fn load_preferences() -> Preferences {
+    preferences_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| ron::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Saves the preferences with `change` made to them, leaving the others as
+/// they were.
+pub async fn update_preferences(change: impl FnOnce(&mut Preferences)) -> Result<(), Error> {
+    let mut preferences = load_preferences();
+    change(&mut preferences);
+
+    save_preferences(preferences).await
+}
+
+async fn save_preferences(preferences: Preferences) -> Result<(), Error> {
+    let path = preferences_path().ok_or(Error::IoError(std::io::ErrorKind::NotFound))?;
+    let contents = ron::ser::to_string_pretty(&preferences, ron::ser::PrettyConfig::default())
+        .map_err(|error| Error::ProjectFormat(error.to_string()))?;
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|error| Error::IoError(error.kind()))?;
+    }
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|error| Error::IoError(error.kind()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_parse() {
+        for language in Language::ALL {
+            assert!(
+                FluentResource::try_new(language.source().to_string()).is_ok(),
+                "{} has mistakes",
+                language.code()
+            );
+        }
+    }
+
+    #[test]
+    fn plural_forms() {
+        let uses = |language: Language, count: &dyn Display| {
+            let bundle = &catalogs()[language as usize].bundle;
+            format(bundle, "asset-uses", Some(&arguments(&[("count", count)]))).unwrap()
+        };
+
+        assert_eq!(uses(Language::English, &1), "1 use");
+        assert_eq!(uses(Language::English, &3), "3 uses");
+        assert_eq!(uses(Language::German, &1), "1 Verwendung");
+        assert_eq!(uses(Language::German, &0), "0 Verwendungen");
+    }
+
+    #[test]
+    fn only_plain_numbers_are_numbers() {
+        let args = arguments(&[("count", &"2.50"), ("name", &"inf")]);
+
+        assert!(matches!(args.get("count"), Some(FluentValue::Number(_))));
+        assert_eq!(args.get("name"), Some(&FluentValue::from("inf")));
+        assert_eq!(
+            format(
+                &catalogs()[0].bundle,
+                "marker-name",
+                Some(&arguments(&[("number", &"2.50")]))
+            ),
+            Some("Marker 2.50".to_string())
+        );
+    }
+
+    #[test]
+    fn plain_strings() {
+        assert_eq!(
+            catalogs()[0].strings.get("project-new").unwrap(),
+            "New project"
+        );
+        assert_eq!(
+            catalogs()[1].strings.get("project-new").unwrap(),
+            "Neues Projekt"
+        );
+    }
+}
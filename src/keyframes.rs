@@ -0,0 +1,454 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::t;
+
+/// A layer property that can be animated with keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Property {
+    X,
+    Y,
+    Scale,
+    Opacity,
+}
+
+impl Property {
+    pub const ALL: [Property; 4] = [Property::X, Property::Y, Property::Scale, Property::Opacity];
+
+    /// The value of a layer placed as it is when first added: in the top left
+    /// corner, at full size and fully opaque.
+    pub fn default_value(self) -> f32 {
+        match self {
+            Property::X | Property::Y => 0.,
+            Property::Scale | Property::Opacity => 1.,
+        }
+    }
+}
+
+impl Display for Property {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Property::X => "x",
+            Property::Y => "y",
+            Property::Scale => t("property-scale"),
+            Property::Opacity => t("property-opacity"),
+        })
+    }
+}
+
+/// Easing of the segment between two keyframes, as the two control points of
+/// a cubic bezier from (0, 0) to (1, 1), like CSS's `cubic-bezier()`. The x
+/// axis is time and the y axis is progress towards the next value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bezier {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+impl Bezier {
+    pub const EASE_IN_OUT: Bezier = Bezier {
+        x1: 0.42,
+        y1: 0.,
+        x2: 0.58,
+        y2: 1.,
+    };
+
+    /// Progress towards the next value at `t`, the fraction of the segment's
+    /// duration that has passed.
+    pub fn progress(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        let curve = |a: f32, b: f32, s: f32| {
+            3. * a * s * (1. - s).powi(2) + 3. * b * s * s * (1. - s) + s.powi(3)
+        };
+
+        // The x coordinate always increases with the curve parameter, as the
+        // control points are kept between 0 and 1, so it can be bisected.
+        let (mut low, mut high) = (0., 1.);
+        for _ in 0..24 {
+            let middle = (low + high) / 2.;
+            if curve(self.x1, self.x2, middle) < t {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        curve(self.y1, self.y2, (low + high) / 2.)
+    }
+
+    /// The same curve played backwards.
+    pub fn reversed(&self) -> Bezier {
+        Bezier {
+            x1: 1. - self.x2,
+            y1: 1. - self.y2,
+            x2: 1. - self.x1,
+            y2: 1. - self.y1,
+        }
+    }
+}
+
+impl Default for Bezier {
+    fn default() -> Self {
+        Bezier::EASE_IN_OUT
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// Seconds into the track.
+    pub time: f32,
+    pub value: f32,
+    /// Easing towards the next keyframe.
+    #[serde(default)]
+    pub ease: Bezier,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Track {
+    pub property: Property,
+    /// Sorted by time.
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Keyframes closer together than this are treated as the same one.
+    const SAME_TIME: f32 = 0.01;
+
+    pub fn value(&self, time: f32) -> Option<f32> {
+        let next = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+
+        match (next.checked_sub(1), self.keyframes.get(next)) {
+            (Some(previous), Some(next)) => {
+                let previous = &self.keyframes[previous];
+                let t = (time - previous.time) / (next.time - previous.time);
+
+                Some(previous.value + (next.value - previous.value) * previous.ease.progress(t))
+            }
+            (Some(previous), None) => Some(self.keyframes[previous].value),
+            (None, next) => next.map(|next| next.value),
+        }
+    }
+
+    /// Index of the keyframe at `time`, if there is one.
+    pub fn keyframe_at(&self, time: f32) -> Option<usize> {
+        self.keyframes
+            .iter()
+            .position(|keyframe| (keyframe.time - time).abs() < Self::SAME_TIME)
+    }
+
+    /// Changes the keyframe at `time`, or adds one there.
+    pub fn set(&mut self, time: f32, value: f32) {
+        match self.keyframe_at(time) {
+            Some(index) => self.keyframes[index].value = value,
+            None => {
+                let index = self
+                    .keyframes
+                    .partition_point(|keyframe| keyframe.time < time);
+                self.keyframes.insert(
+                    index,
+                    Keyframe {
+                        time,
+                        value,
+                        ease: Bezier::default(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Adds `keyframe`, replacing any keyframe already at its time.
+    pub fn insert(&mut self, keyframe: Keyframe) {
+        match self.keyframe_at(keyframe.time) {
+            Some(index) => self.keyframes[index] = keyframe,
+            None => {
+                let index = self
+                    .keyframes
+                    .partition_point(|existing| existing.time < keyframe.time);
+                self.keyframes.insert(index, keyframe);
+            }
+        }
+    }
+
+    /// Moves a keyframe, keeping it between its neighbours so the order of
+    /// the keyframes does not change.
+    pub fn move_keyframe(&mut self, index: usize, time: f32, value: f32) {
+        let earliest = index.checked_sub(1).map_or(0., |previous| {
+            self.keyframes[previous].time + Self::SAME_TIME
+        });
+        let latest = self
+            .keyframes
+            .get(index + 1)
+            .map_or(f32::INFINITY, |next| next.time - Self::SAME_TIME);
+
+        if let Some(keyframe) = self.keyframes.get_mut(index) {
+            keyframe.time = time.clamp(earliest, latest.max(earliest));
+            keyframe.value = value;
+        }
+    }
+}
+
+/// Every animated property of a layer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keyframes {
+    tracks: Vec<Track>,
+}
+
+impl Keyframes {
+    pub fn track(&self, property: Property) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.property == property)
+    }
+
+    pub fn track_mut(&mut self, property: Property) -> Option<&mut Track> {
+        self.tracks
+            .iter_mut()
+            .find(|track| track.property == property)
+    }
+
+    pub fn value(&self, property: Property, time: f32) -> Option<f32> {
+        self.track(property)?.value(time)
+    }
+
+    /// Starts animating `property` from its current value at `time`.
+    pub fn animate(&mut self, property: Property, time: f32, value: f32) {
+        if self.track(property).is_none() {
+            self.tracks.push(Track {
+                property,
+                keyframes: vec![Keyframe {
+                    time,
+                    value,
+                    ease: Bezier::default(),
+                }],
+            });
+        }
+    }
+
+    /// Replaces the keyframes of `property` with those of `track`, or drops
+    /// them if there is none.
+    pub fn set_track(&mut self, property: Property, track: Option<&Track>) {
+        self.stop_animating(property);
+        if let Some(track) = track {
+            self.tracks.push(Track {
+                property,
+                ..track.clone()
+            });
+        }
+    }
+
+    /// Drops every keyframe of `property`.
+    pub fn stop_animating(&mut self, property: Property) {
+        self.tracks.retain(|track| track.property != property);
+    }
+
+    /// Times of the first and last keyframes of any property.
+    pub fn span(&self) -> Option<(f32, f32)> {
+        self.tracks.iter().flat_map(|track| &track.keyframes).fold(
+            None,
+            |span, keyframe| match span {
+                Some((first, last)) => Some((keyframe.time.min(first), keyframe.time.max(last))),
+                None => Some((keyframe.time, keyframe.time)),
+            },
+        )
+    }
+
+    /// Pastes copied keyframes into the track of `property` so that they
+    /// start at `time`, replacing any existing keyframes they land on.
+    pub fn paste(&mut self, property: Property, keyframes: &[Keyframe], time: f32, reversed: bool) {
+        let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+            return;
+        };
+
+        self.paste_between(property, keyframes, (first.time, last.time), time, reversed);
+    }
+
+    /// Pastes every track of `other` so that its earliest keyframe lands at
+    /// `time`, keeping the tracks in step with each other.
+    pub fn paste_all(&mut self, other: &Keyframes, time: f32, reversed: bool) {
+        let Some(span) = other.span() else {
+            return;
+        };
+
+        for track in &other.tracks {
+            self.paste_between(track.property, &track.keyframes, span, time, reversed);
+        }
+    }
+
+    fn paste_between(
+        &mut self,
+        property: Property,
+        keyframes: &[Keyframe],
+        (first, last): (f32, f32),
+        time: f32,
+        reversed: bool,
+    ) {
+        let track = match self
+            .tracks
+            .iter()
+            .position(|track| track.property == property)
+        {
+            Some(index) => &mut self.tracks[index],
+            None => {
+                self.tracks.push(Track {
+                    property,
+                    keyframes: vec![],
+                });
+                self.tracks.last_mut().unwrap()
+            }
+        };
+
+        for (index, keyframe) in keyframes.iter().enumerate() {
+            let keyframe = if reversed {
+                // Each keyframe now starts the segment that used to end at
+                // it, eased the other way round.
+                Keyframe {
+                    time: time + (last - keyframe.time),
+                    ease: index.checked_sub(1).map_or(keyframe.ease, |previous| {
+                        keyframes[previous].ease.reversed()
+                    }),
+                    ..*keyframe
+                }
+            } else {
+                Keyframe {
+                    time: time + (keyframe.time - first),
+                    ..*keyframe
+                }
+            };
+
+            track.insert(keyframe);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINEAR: Bezier = Bezier {
+        x1: 0.,
+        y1: 0.,
+        x2: 1.,
+        y2: 1.,
+    };
+    const EASE_OUT: Bezier = Bezier {
+        x1: 0.,
+        y1: 0.,
+        x2: 0.58,
+        y2: 1.,
+    };
+
+    fn keyframe(time: f32, value: f32, ease: Bezier) -> Keyframe {
+        Keyframe { time, value, ease }
+    }
+
+    fn times_and_values(keyframes: &Keyframes, property: Property) -> Vec<(f32, f32)> {
+        keyframes
+            .track(property)
+            .unwrap()
+            .keyframes
+            .iter()
+            .map(|keyframe| (keyframe.time, keyframe.value))
+            .collect()
+    }
+
+    #[test]
+    fn pasting_keyframes() {
+        let copied = [
+            keyframe(1., 10., EASE_OUT),
+            keyframe(2., 20., LINEAR),
+            keyframe(4., 40., Bezier::EASE_IN_OUT),
+        ];
+        let mut keyframes = Keyframes::default();
+        keyframes.paste(Property::X, &copied, 10., false);
+
+        assert_eq!(
+            keyframes.track(Property::X).unwrap().keyframes,
+            [
+                keyframe(10., 10., EASE_OUT),
+                keyframe(11., 20., LINEAR),
+                keyframe(13., 40., Bezier::EASE_IN_OUT),
+            ]
+        );
+    }
+
+    #[test]
+    fn pasting_keyframes_reversed() {
+        let copied = [
+            keyframe(1., 10., EASE_OUT),
+            keyframe(2., 20., LINEAR),
+            keyframe(4., 40., Bezier::EASE_IN_OUT),
+        ];
+        let mut keyframes = Keyframes::default();
+        keyframes.paste(Property::X, &copied, 10., true);
+
+        // The last keyframe comes first, and each segment is eased as the
+        // one it was copied from, played backwards.
+        assert_eq!(
+            keyframes.track(Property::X).unwrap().keyframes,
+            [
+                keyframe(10., 40., LINEAR.reversed()),
+                keyframe(12., 20., EASE_OUT.reversed()),
+                keyframe(13., 10., EASE_OUT),
+            ]
+        );
+    }
+
+    #[test]
+    fn pasting_every_track_keeps_them_in_step() {
+        let mut copied = Keyframes::default();
+        copied.paste(
+            Property::X,
+            &[keyframe(1., 0., LINEAR), keyframe(3., 100., LINEAR)],
+            1.,
+            false,
+        );
+        copied.paste(Property::Opacity, &[keyframe(1.5, 0.5, LINEAR)], 1.5, false);
+
+        let mut keyframes = Keyframes::default();
+        keyframes.paste_all(&copied, 10., false);
+        assert_eq!(
+            times_and_values(&keyframes, Property::X),
+            [(10., 0.), (12., 100.)]
+        );
+        assert_eq!(
+            times_and_values(&keyframes, Property::Opacity),
+            [(10.5, 0.5)]
+        );
+
+        let mut reversed = Keyframes::default();
+        reversed.paste_all(&copied, 10., true);
+        assert_eq!(
+            times_and_values(&reversed, Property::X),
+            [(10., 100.), (12., 0.)]
+        );
+        assert_eq!(
+            times_and_values(&reversed, Property::Opacity),
+            [(11.5, 0.5)]
+        );
+    }
+
+    #[test]
+    fn pasting_replaces_keyframes_at_the_same_time() {
+        let mut keyframes = Keyframes::default();
+        keyframes.paste(
+            Property::Scale,
+            &[
+                keyframe(0., 1., LINEAR),
+                keyframe(10.005, 2., LINEAR),
+                keyframe(10.5, 3., LINEAR),
+            ],
+            0.,
+            false,
+        );
+        keyframes.paste(Property::Scale, &[keyframe(0., 5., LINEAR)], 10., false);
+
+        assert_eq!(
+            times_and_values(&keyframes, Property::Scale),
+            [(0., 1.), (10., 5.), (10.5, 3.)]
+        );
+    }
+}
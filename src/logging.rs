@@ -0,0 +1,120 @@
+use std::{
+    fmt::{Display, Write as _},
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{filter::filter_fn, fmt, prelude::*};
+
+/// Size past which the log is rotated when the app starts.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+/// Rotated logs kept besides the current one, as `roygbiv.log.1` and so on.
+const KEPT_LOGS: usize = 3;
+
+/// Where the log is written, in the config directory, so it can be found
+/// again when reporting a problem.
+pub fn log_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("roygbiv").join("roygbiv.log"))
+}
+
+/// What kept logging from being set up as it should.
+#[derive(Debug)]
+pub enum LogError {
+    /// The log file couldn't be opened, so events only go to stderr.
+    Open(PathBuf, io::Error),
+    /// Another subscriber was set first, and receives the events instead.
+    AlreadySet,
+}
+
+impl Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogError::Open(path, error) => {
+                write!(f, "could not open log file {}: {}", path.display(), error)
+            }
+            LogError::AlreadySet => f.write_str("a logger was already set"),
+        }
+    }
+}
+
+/// Sends every `tracing` event from the app to stderr and the log file,
+/// rotating the log first if it has grown too large. The file is written on
+/// a thread of its own, until the returned guard is dropped, so it has to
+/// be held while the app runs.
+pub fn init() -> Result<Option<WorkerGuard>, LogError> {
+    let (file, open_error) = match log_path().map(|path| (open_log(&path), path)) {
+        Some((Ok(file), _)) => (Some(file), None),
+        Some((Err(error), path)) => (None, Some(LogError::Open(path, error))),
+        None => (None, None),
+    };
+    let (writer, guard) = file.map(tracing_appender::non_blocking).unzip();
+
+    let subscriber = tracing_subscriber::registry()
+        .with(fmt::layer().with_writer(io::stderr))
+        .with(writer.map(|writer| fmt::layer().with_ansi(false).with_writer(writer)))
+        .with(filter_fn(|metadata| {
+            // Dependencies log plenty at lower levels that means nothing to
+            // users.
+            *metadata.level() <= Level::INFO
+                && (metadata.target().starts_with("roygbiv") || *metadata.level() <= Level::WARN)
+        }));
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        return Err(LogError::AlreadySet);
+    }
+
+    match open_error {
+        Some(error) => Err(error),
+        None => Ok(guard),
+    }
+}
+
+fn open_log(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_LOG_SIZE) {
+        for index in (1..KEPT_LOGS).rev() {
+            let _ = fs::rename(rotated(path, index), rotated(path, index + 1));
+        }
+        fs::rename(path, rotated(path, 1))?;
+    }
+
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rotated(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+
+    name.into()
+}
+
+/// A report to attach to a bug report: the app's version, the platform and
+/// the end of the log.
+pub fn diagnostics() -> String {
+    const LINES: usize = 200;
+
+    let mut report = format!(
+        "roygbiv {}\n{} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+
+    match log_path() {
+        Some(path) => {
+            let _ = writeln!(report, "log: {}\n", path.display());
+            let log = fs::read_to_string(&path).unwrap_or_default();
+            let lines: Vec<_> = log.lines().collect();
+            for line in &lines[lines.len().saturating_sub(LINES)..] {
+                let _ = writeln!(report, "{}", line);
+            }
+        }
+        None => report.push_str("no log file\n"),
+    }
+
+    report
+}
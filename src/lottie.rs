@@ -0,0 +1,1167 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use iced::{
+    widget::canvas::{self, fill, path, LineCap, LineJoin, Path, Stroke},
+    Color, Point, Rectangle, Size,
+};
+use serde_json::Value;
+
+use crate::i18n::t;
+
+/// A Lottie (Bodymovin) animation, shared by the layers showing it.
+///
+/// Shape, solid, null and precomposition layers are drawn, with their
+/// parenting, transforms, groups, rectangles, ellipses, paths, fills and
+/// strokes, keyframed or not. Masks, mattes, gradients, trim paths, text,
+/// images, expressions and effects are left out.
+#[derive(Clone)]
+pub struct Lottie(Arc<Composition>);
+
+impl PartialEq for Lottie {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Lottie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lottie")
+            .field("size", &self.0.size)
+            .field("frames", &(self.0.in_point..self.0.out_point))
+            .finish()
+    }
+}
+
+struct Composition {
+    frame_rate: f32,
+    in_point: f32,
+    out_point: f32,
+    size: Size,
+    layers: Vec<LottieLayer>,
+    /// The layers of each precomposition, by the ID precomposition layers
+    /// refer to them by.
+    precomps: HashMap<String, Vec<LottieLayer>>,
+}
+
+impl Lottie {
+    pub fn parse(contents: &[u8]) -> Result<Lottie, String> {
+        let root = parse_json(contents)?;
+
+        let number_field = |key: &str| field(&root, key).and_then(number);
+        let (Some(width), Some(height)) = (number_field("w"), number_field("h")) else {
+            return Err(t("error-not-lottie").to_string());
+        };
+        let frame_rate = number_field("fr").filter(|rate| *rate > 0.).unwrap_or(30.);
+        let in_point = number_field("ip").unwrap_or(0.);
+        let out_point = number_field("op").unwrap_or(in_point + frame_rate);
+
+        let precomps = array(field(&root, "assets"))
+            .iter()
+            .filter_map(|asset| {
+                let id = match field(asset, "id")? {
+                    Value::String(id) => id.clone(),
+                    id => number(id)?.to_string(),
+                };
+
+                Some((id, parse_layers(field(asset, "layers")?)))
+            })
+            .collect();
+        let layers = parse_layers(field(&root, "layers").ok_or(t("error-lottie-no-layers"))?);
+
+        Ok(Lottie(Arc::new(Composition {
+            frame_rate,
+            in_point,
+            out_point: out_point.max(in_point + 1.),
+            size: Size::new(width.max(1.), height.max(1.)),
+            layers,
+            precomps,
+        })))
+    }
+
+    pub fn size(&self) -> Size {
+        self.0.size
+    }
+
+    /// Seconds the animation lasts before it starts over.
+    pub fn duration(&self) -> f32 {
+        (self.0.out_point - self.0.in_point) / self.0.frame_rate
+    }
+
+    /// The frame of the animation's own timeline shown `seconds` into it.
+    pub fn frame(&self, seconds: f32) -> f32 {
+        self.0.in_point + seconds * self.0.frame_rate
+    }
+
+    /// Draws the animation `seconds` into it stretched over `bounds`.
+    pub fn draw(&self, frame: &mut canvas::Frame, bounds: Rectangle, seconds: f32, opacity: f32) {
+        let composition = &self.0;
+        let time = self.frame(seconds);
+        let matrix = Affine::translate(bounds.x, bounds.y).then_scale(
+            bounds.width / composition.size.width,
+            bounds.height / composition.size.height,
+        );
+
+        composition.draw_layers(frame, &composition.layers, time, matrix, opacity, 0);
+    }
+}
+
+impl Composition {
+    /// Precompositions nested deeper than this are left out, as a file
+    /// referring to itself would otherwise never finish drawing.
+    const MAX_DEPTH: usize = 8;
+
+    fn draw_layers(
+        &self,
+        frame: &mut canvas::Frame,
+        layers: &[LottieLayer],
+        time: f32,
+        matrix: Affine,
+        opacity: f32,
+        depth: usize,
+    ) {
+        // The first layer is the topmost.
+        for layer in layers.iter().rev() {
+            if layer.is_hidden || time < layer.in_point || time >= layer.out_point {
+                continue;
+            }
+            let local = layer.local_time(time);
+            let matrix = matrix.then(layer_matrix(layers, layer, time, 0));
+            let opacity = opacity * layer.transform.opacity(local);
+            if opacity <= 0. {
+                continue;
+            }
+
+            match &layer.content {
+                LayerContent::Shapes(items) => {
+                    let mut operations = vec![];
+                    collect(items, matrix, opacity, local, &mut operations);
+                    for operation in operations.iter().rev() {
+                        operation.draw(frame);
+                    }
+                }
+                LayerContent::Solid { color, size } => {
+                    let contour = Contour::rectangle(
+                        Point::new(size.width / 2., size.height / 2.),
+                        *size,
+                        0.,
+                    );
+                    Operation::Fill {
+                        geometry: vec![contour.transformed(matrix)],
+                        color: Color {
+                            a: color.a * opacity,
+                            ..*color
+                        },
+                        rule: fill::Rule::NonZero,
+                    }
+                    .draw(frame);
+                }
+                LayerContent::Precomp(id) => {
+                    if let Some(precomp) = self.precomps.get(id).filter(|_| depth < Self::MAX_DEPTH)
+                    {
+                        self.draw_layers(frame, precomp, local, matrix, opacity, depth + 1);
+                    }
+                }
+                LayerContent::Null => {}
+            }
+        }
+    }
+}
+
+struct LottieLayer {
+    index: Option<i64>,
+    parent: Option<i64>,
+    in_point: f32,
+    out_point: f32,
+    start_time: f32,
+    stretch: f32,
+    transform: Transform,
+    content: LayerContent,
+    is_hidden: bool,
+}
+
+impl LottieLayer {
+    /// The frame of the layer's own keyframes at `time` in its composition.
+    fn local_time(&self, time: f32) -> f32 {
+        (time - self.start_time) / self.stretch
+    }
+}
+
+enum LayerContent {
+    Shapes(Vec<Item>),
+    Solid {
+        color: Color,
+        size: Size,
+    },
+    /// The ID of the precomposition shown.
+    Precomp(String),
+    /// Only moves the layers parented to it.
+    Null,
+}
+
+/// The layer's transform, after those of its parents.
+fn layer_matrix(layers: &[LottieLayer], layer: &LottieLayer, time: f32, depth: usize) -> Affine {
+    let own = layer.transform.matrix(layer.local_time(time));
+    let parent = layer
+        .parent
+        .filter(|_| depth < 32)
+        .and_then(|parent| layers.iter().find(|layer| layer.index == Some(parent)));
+
+    match parent {
+        Some(parent) => layer_matrix(layers, parent, time, depth + 1).then(own),
+        None => own,
+    }
+}
+
+fn parse_layers(layers: &Value) -> Vec<LottieLayer> {
+    array(Some(layers))
+        .iter()
+        .filter_map(|layer| {
+            let number_field = |key: &str| field(layer, key).and_then(number);
+            let content = match number_field("ty")? as i64 {
+                0 => LayerContent::Precomp(match field(layer, "refId")? {
+                    Value::String(id) => id.clone(),
+                    id => number(id)?.to_string(),
+                }),
+                1 => LayerContent::Solid {
+                    color: match field(layer, "sc") {
+                        Some(Value::String(color)) => parse_hex(color).unwrap_or(Color::BLACK),
+                        _ => Color::BLACK,
+                    },
+                    size: Size::new(
+                        number_field("sw").unwrap_or(0.),
+                        number_field("sh").unwrap_or(0.),
+                    ),
+                },
+                3 => LayerContent::Null,
+                4 => LayerContent::Shapes(parse_items(field(layer, "shapes"))),
+                // Images, text, audio and cameras.
+                _ => return None,
+            };
+
+            Some(LottieLayer {
+                index: number_field("ind").map(|index| index as i64),
+                parent: number_field("parent").map(|parent| parent as i64),
+                in_point: number_field("ip").unwrap_or(f32::MIN),
+                out_point: number_field("op").unwrap_or(f32::MAX),
+                start_time: number_field("st").unwrap_or(0.),
+                stretch: number_field("sr")
+                    .filter(|stretch| *stretch != 0.)
+                    .unwrap_or(1.),
+                transform: Transform::parse(field(layer, "ks")),
+                content,
+                is_hidden: field(layer, "hd").is_some_and(is_true),
+            })
+        })
+        .collect()
+}
+
+/// An element of a shape layer or group.
+enum Item {
+    Group(Vec<Item>),
+    Rectangle {
+        position: Property<Vec<f32>>,
+        size: Property<Vec<f32>>,
+        roundness: Property<Vec<f32>>,
+    },
+    Ellipse {
+        position: Property<Vec<f32>>,
+        size: Property<Vec<f32>>,
+    },
+    Path(Property<Bezier>),
+    Fill {
+        color: Property<Vec<f32>>,
+        opacity: Property<Vec<f32>>,
+        rule: fill::Rule,
+    },
+    Stroke {
+        color: Property<Vec<f32>>,
+        opacity: Property<Vec<f32>>,
+        width: Property<Vec<f32>>,
+        cap: LineCap,
+        join: LineJoin,
+    },
+    /// Moves the rest of its group.
+    Transform(Transform),
+}
+
+fn parse_items(items: Option<&Value>) -> Vec<Item> {
+    array(items)
+        .iter()
+        .filter(|item| !field(item, "hd").is_some_and(is_true))
+        .filter_map(|item| {
+            let Some(Value::String(kind)) = field(item, "ty") else {
+                return None;
+            };
+            let vector = |key: &str| Property::parse(field(item, key), numbers);
+
+            Some(match kind.as_str() {
+                "gr" => Item::Group(parse_items(field(item, "it"))),
+                "rc" => Item::Rectangle {
+                    position: vector("p"),
+                    size: vector("s"),
+                    roundness: vector("r"),
+                },
+                "el" => Item::Ellipse {
+                    position: vector("p"),
+                    size: vector("s"),
+                },
+                "sh" => Item::Path(Property::parse(field(item, "ks"), Bezier::parse)),
+                "fl" => Item::Fill {
+                    color: vector("c"),
+                    opacity: vector("o"),
+                    rule: match field(item, "r").and_then(number) {
+                        Some(rule) if rule as i64 == 2 => fill::Rule::EvenOdd,
+                        _ => fill::Rule::NonZero,
+                    },
+                },
+                "st" => Item::Stroke {
+                    color: vector("c"),
+                    opacity: vector("o"),
+                    width: vector("w"),
+                    cap: match field(item, "lc").and_then(number).map(|cap| cap as i64) {
+                        Some(2) => LineCap::Round,
+                        Some(3) => LineCap::Square,
+                        _ => LineCap::Butt,
+                    },
+                    join: match field(item, "lj").and_then(number).map(|join| join as i64) {
+                        Some(2) => LineJoin::Round,
+                        Some(3) => LineJoin::Bevel,
+                        _ => LineJoin::Miter,
+                    },
+                },
+                "tr" => Item::Transform(Transform::parse(Some(item))),
+                _ => return None,
+            })
+        })
+        .collect()
+}
+
+/// A fill or stroke of the shapes before it in its group, waiting to be drawn
+/// once every item has been gone through.
+enum Operation {
+    Fill {
+        geometry: Vec<Contour>,
+        color: Color,
+        rule: fill::Rule,
+    },
+    Stroke {
+        geometry: Vec<Contour>,
+        color: Color,
+        width: f32,
+        cap: LineCap,
+        join: LineJoin,
+    },
+}
+
+impl Operation {
+    fn draw(&self, frame: &mut canvas::Frame) {
+        let path = |geometry: &[Contour]| {
+            Path::new(|builder| {
+                for contour in geometry {
+                    contour.trace(builder);
+                }
+            })
+        };
+
+        match self {
+            Operation::Fill {
+                geometry,
+                color,
+                rule,
+            } => frame.fill(
+                &path(geometry),
+                canvas::Fill {
+                    style: canvas::Style::Solid(*color),
+                    rule: *rule,
+                },
+            ),
+            Operation::Stroke {
+                geometry,
+                color,
+                width,
+                cap,
+                join,
+            } => frame.stroke(
+                &path(geometry),
+                Stroke::default()
+                    .with_color(*color)
+                    .with_width(*width)
+                    .with_line_cap(*cap)
+                    .with_line_join(*join),
+            ),
+        }
+    }
+}
+
+/// Goes through a group's items at `time`, gathering the fills and strokes
+/// to draw, topmost first, and returns the group's shapes for the fills and
+/// strokes after it.
+fn collect(
+    items: &[Item],
+    matrix: Affine,
+    opacity: f32,
+    time: f32,
+    operations: &mut Vec<Operation>,
+) -> Vec<Contour> {
+    let (matrix, opacity) = match items.iter().find_map(|item| match item {
+        Item::Transform(transform) => Some(transform),
+        _ => None,
+    }) {
+        Some(transform) => (
+            matrix.then(transform.matrix(time)),
+            opacity * transform.opacity(time),
+        ),
+        None => (matrix, opacity),
+    };
+    let color = |color: &Property<Vec<f32>>, alpha: &Property<Vec<f32>>| {
+        let color = color.at(time);
+        let channel = |index: usize| {
+            let value = color.get(index).copied().unwrap_or(0.);
+            // Some exporters write channels from 0 to 255.
+            if color.iter().any(|value| *value > 1.) {
+                value / 255.
+            } else {
+                value
+            }
+        };
+
+        Color::from_rgba(
+            channel(0),
+            channel(1),
+            channel(2),
+            opacity * scalar(alpha, time, 100.) / 100.,
+        )
+    };
+
+    let mut geometry = vec![];
+    for item in items {
+        match item {
+            Item::Group(items) => {
+                geometry.extend(collect(items, matrix, opacity, time, operations));
+            }
+            Item::Rectangle {
+                position,
+                size,
+                roundness,
+            } => geometry.push(
+                Contour::rectangle(
+                    point(&position.at(time)),
+                    size_of(&size.at(time)),
+                    scalar(roundness, time, 0.),
+                )
+                .transformed(matrix),
+            ),
+            Item::Ellipse { position, size } => geometry.push(
+                Contour::ellipse(point(&position.at(time)), size_of(&size.at(time)))
+                    .transformed(matrix),
+            ),
+            Item::Path(bezier) => geometry.push(bezier.at(time).contour().transformed(matrix)),
+            Item::Fill {
+                color: fill,
+                opacity: alpha,
+                rule,
+            } => operations.push(Operation::Fill {
+                geometry: geometry.clone(),
+                color: color(fill, alpha),
+                rule: *rule,
+            }),
+            Item::Stroke {
+                color: stroke,
+                opacity: alpha,
+                width,
+                cap,
+                join,
+            } => operations.push(Operation::Stroke {
+                geometry: geometry.clone(),
+                color: color(stroke, alpha),
+                width: scalar(width, time, 1.) * matrix.scale_factor(),
+                cap: *cap,
+                join: *join,
+            }),
+            Item::Transform(_) => {}
+        }
+    }
+
+    geometry
+}
+
+struct Transform {
+    anchor: Property<Vec<f32>>,
+    position: Position,
+    scale: Property<Vec<f32>>,
+    rotation: Property<Vec<f32>>,
+    opacity: Property<Vec<f32>>,
+}
+
+enum Position {
+    Joined(Property<Vec<f32>>),
+    /// Keyframed in x and y separately.
+    Split(Property<Vec<f32>>, Property<Vec<f32>>),
+}
+
+impl Transform {
+    fn parse(transform: Option<&Value>) -> Transform {
+        let property = |key: &str| Property::parse(transform.and_then(|t| field(t, key)), numbers);
+        let position = transform.and_then(|transform| field(transform, "p"));
+
+        Transform {
+            anchor: property("a"),
+            position: match position {
+                Some(position) if field(position, "s").is_some_and(is_true) => Position::Split(
+                    Property::parse(field(position, "x"), numbers),
+                    Property::parse(field(position, "y"), numbers),
+                ),
+                _ => Position::Joined(property("p")),
+            },
+            scale: property("s"),
+            rotation: match transform.and_then(|transform| field(transform, "r")) {
+                Some(_) => property("r"),
+                None => property("rz"),
+            },
+            opacity: property("o"),
+        }
+    }
+
+    /// Moves to the position, rotates and scales, around the anchor.
+    fn matrix(&self, time: f32) -> Affine {
+        let position = match &self.position {
+            Position::Joined(position) => point(&position.at(time)),
+            Position::Split(x, y) => Point::new(scalar(x, time, 0.), scalar(y, time, 0.)),
+        };
+        let anchor = point(&self.anchor.at(time));
+        let scale = self.scale.at(time);
+        let scale_x = scale.first().copied().unwrap_or(100.) / 100.;
+        let scale_y = scale.get(1).copied().unwrap_or(scale_x * 100.) / 100.;
+
+        Affine::translate(position.x, position.y)
+            .then_rotate(scalar(&self.rotation, time, 0.).to_radians())
+            .then_scale(scale_x, scale_y)
+            .then(Affine::translate(-anchor.x, -anchor.y))
+    }
+
+    /// From 0 to 1.
+    fn opacity(&self, time: f32) -> f32 {
+        (scalar(&self.opacity, time, 100.) / 100.).clamp(0., 1.)
+    }
+}
+
+/// A value that may change over time.
+enum Property<T> {
+    Missing,
+    Static(T),
+    Animated(Vec<Keyframe<T>>),
+}
+
+struct Keyframe<T> {
+    time: f32,
+    value: T,
+    /// The value at the next keyframe, which older files keep here.
+    end: Option<T>,
+    /// Whether the value jumps to the next keyframe's instead of easing.
+    is_hold: bool,
+    /// The control points of the easing curve towards the next keyframe.
+    ease_out: Point,
+    ease_in: Point,
+}
+
+impl<T: Interpolate + Default> Property<T> {
+    fn parse(property: Option<&Value>, parse: impl Fn(&Value) -> Option<T>) -> Property<T> {
+        let Some(value) = property.and_then(|property| field(property, "k")) else {
+            return Property::Missing;
+        };
+        let keyframes = match value {
+            Value::Array(keyframes)
+                if keyframes
+                    .first()
+                    .is_some_and(|keyframe| field(keyframe, "t").is_some()) =>
+            {
+                keyframes
+            }
+            value => return parse(value).map_or(Property::Missing, Property::Static),
+        };
+
+        let tangent = |keyframe: &Value, key: &str, default: Point| {
+            field(keyframe, key).map_or(default, |tangent| {
+                let component = |key: &str| {
+                    field(tangent, key)
+                        .and_then(numbers)
+                        .and_then(|values| values.first().copied())
+                };
+
+                Point::new(
+                    component("x").unwrap_or(default.x),
+                    component("y").unwrap_or(default.y),
+                )
+            })
+        };
+        let keyframes: Vec<_> = keyframes
+            .iter()
+            .filter_map(|keyframe| {
+                let value = field(keyframe, "s").and_then(&parse);
+                let end = field(keyframe, "e").and_then(&parse);
+
+                Some(Keyframe {
+                    time: field(keyframe, "t").and_then(number)?,
+                    value: value.or_else(|| end.clone())?,
+                    end,
+                    is_hold: field(keyframe, "h").is_some_and(is_true),
+                    ease_out: tangent(keyframe, "o", Point::new(0., 0.)),
+                    ease_in: tangent(keyframe, "i", Point::new(1., 1.)),
+                })
+            })
+            .collect();
+
+        match keyframes.len() {
+            0 => Property::Missing,
+            _ => Property::Animated(keyframes),
+        }
+    }
+
+    fn at(&self, time: f32) -> T {
+        let keyframes = match self {
+            Property::Missing => return T::default(),
+            Property::Static(value) => return value.clone(),
+            Property::Animated(keyframes) => keyframes,
+        };
+
+        let next = keyframes.partition_point(|keyframe| keyframe.time <= time);
+        let Some(keyframe) = next.checked_sub(1).and_then(|index| keyframes.get(index)) else {
+            return keyframes[0].value.clone();
+        };
+        let end = match (&keyframe.end, keyframes.get(next)) {
+            (_, None) => return keyframe.end.clone().unwrap_or(keyframe.value.clone()),
+            (Some(end), Some(next)) => (end, next.time),
+            (None, Some(next)) => (&next.value, next.time),
+        };
+        if keyframe.is_hold || end.1 <= keyframe.time {
+            return keyframe.value.clone();
+        }
+
+        let progress = (time - keyframe.time) / (end.1 - keyframe.time);
+        let eased = ease(keyframe.ease_out, keyframe.ease_in, progress);
+
+        keyframe.value.interpolate(end.0, eased)
+    }
+}
+
+/// The first value of a property, or `default` if it has none.
+fn scalar(property: &Property<Vec<f32>>, time: f32, default: f32) -> f32 {
+    match property {
+        Property::Missing => default,
+        property => property.at(time).first().copied().unwrap_or(default),
+    }
+}
+
+/// The cubic Bézier easing curve from (0, 0) to (1, 1) through the two
+/// control points, at `progress` along x.
+fn ease(out: Point, into: Point, progress: f32) -> f32 {
+    let bezier = |t: f32, p1: f32, p2: f32| {
+        let u = 1. - t;
+
+        3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t
+    };
+
+    let (mut low, mut high) = (0., 1.);
+    for _ in 0..24 {
+        let middle = (low + high) / 2.;
+        if bezier(middle, out.x, into.x) < progress {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    bezier((low + high) / 2., out.y, into.y)
+}
+
+trait Interpolate: Clone {
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for Vec<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a + (b - a) * t)
+            .collect()
+    }
+}
+
+/// A path as Lottie stores it: vertices, with the tangents into and out of
+/// each relative to it.
+#[derive(Debug, Clone, Default)]
+struct Bezier {
+    vertices: Vec<Point>,
+    in_tangents: Vec<Point>,
+    out_tangents: Vec<Point>,
+    is_closed: bool,
+}
+
+impl Bezier {
+    fn parse(value: &Value) -> Option<Bezier> {
+        // Keyframed paths are wrapped in a list of one.
+        let value = match value {
+            Value::Array(values) => values.first()?,
+            value => value,
+        };
+        let points = |key: &str| {
+            array(field(value, key))
+                .iter()
+                .filter_map(|point_value| Some(point(&numbers(point_value)?)))
+                .collect::<Vec<_>>()
+        };
+
+        Some(Bezier {
+            vertices: points("v"),
+            in_tangents: points("i"),
+            out_tangents: points("o"),
+            is_closed: field(value, "c").is_some_and(is_true),
+        })
+    }
+
+    fn contour(&self) -> Contour {
+        let tangent = |tangents: &[Point], index: usize| {
+            tangents.get(index).copied().unwrap_or(Point::ORIGIN)
+        };
+        let count = self.vertices.len();
+        let segments = (1..count)
+            .map(|index| (index - 1, index))
+            .chain((self.is_closed && count > 1).then_some((count - 1, 0)))
+            .map(|(from, to)| {
+                let (start, end) = (self.vertices[from], self.vertices[to]);
+                let out = tangent(&self.out_tangents, from);
+                let into = tangent(&self.in_tangents, to);
+
+                (
+                    Point::new(start.x + out.x, start.y + out.y),
+                    Point::new(end.x + into.x, end.y + into.y),
+                    end,
+                )
+            })
+            .collect();
+
+        Contour {
+            start: self.vertices.first().copied(),
+            segments,
+            is_closed: self.is_closed,
+        }
+    }
+}
+
+impl Interpolate for Bezier {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        if self.vertices.len() != other.vertices.len() {
+            return self.clone();
+        }
+        let lerp = |a: &[Point], b: &[Point]| {
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t))
+                .collect()
+        };
+
+        Bezier {
+            vertices: lerp(&self.vertices, &other.vertices),
+            in_tangents: lerp(&self.in_tangents, &other.in_tangents),
+            out_tangents: lerp(&self.out_tangents, &other.out_tangents),
+            is_closed: self.is_closed,
+        }
+    }
+}
+
+/// A run of cubic curves, each given by its two control points and end.
+#[derive(Debug, Clone)]
+struct Contour {
+    start: Option<Point>,
+    segments: Vec<(Point, Point, Point)>,
+    is_closed: bool,
+}
+
+impl Contour {
+    /// Handles of a quarter circle's curve as a fraction of its radius.
+    const KAPPA: f32 = 0.552_284_8;
+
+    /// Sharp corners are rounded by `radius`, as far as the size allows.
+    fn rectangle(center: Point, size: Size, radius: f32) -> Contour {
+        let (half_width, half_height) = (size.width / 2., size.height / 2.);
+        let radius = radius.clamp(0., half_width.min(half_height));
+        let (left, right) = (center.x - half_width, center.x + half_width);
+        let (top, bottom) = (center.y - half_height, center.y + half_height);
+        let handle = radius * (1. - Self::KAPPA);
+        let line = |from: Point, to: Point| (from, to, to);
+
+        let mut segments = vec![];
+        let start = Point::new(left + radius, top);
+        let corners = [
+            (
+                Point::new(right - radius, top),
+                Point::new(right, top + radius),
+                (
+                    Point::new(right - handle, top),
+                    Point::new(right, top + handle),
+                ),
+            ),
+            (
+                Point::new(right, bottom - radius),
+                Point::new(right - radius, bottom),
+                (
+                    Point::new(right, bottom - handle),
+                    Point::new(right - handle, bottom),
+                ),
+            ),
+            (
+                Point::new(left + radius, bottom),
+                Point::new(left, bottom - radius),
+                (
+                    Point::new(left + handle, bottom),
+                    Point::new(left, bottom - handle),
+                ),
+            ),
+            (
+                Point::new(left, top + radius),
+                start,
+                (
+                    Point::new(left, top + handle),
+                    Point::new(left + handle, top),
+                ),
+            ),
+        ];
+        let mut previous = start;
+        for (edge_end, corner_end, (control_1, control_2)) in corners {
+            segments.push(line(previous, edge_end));
+            if radius > 0. {
+                segments.push((control_1, control_2, corner_end));
+            }
+            previous = corner_end;
+        }
+
+        Contour {
+            start: Some(start),
+            segments,
+            is_closed: true,
+        }
+    }
+
+    fn ellipse(center: Point, size: Size) -> Contour {
+        let (rx, ry) = (size.width / 2., size.height / 2.);
+        let (hx, hy) = (rx * Self::KAPPA, ry * Self::KAPPA);
+        let (cx, cy) = (center.x, center.y);
+
+        Contour {
+            start: Some(Point::new(cx, cy - ry)),
+            segments: vec![
+                (
+                    Point::new(cx + hx, cy - ry),
+                    Point::new(cx + rx, cy - hy),
+                    Point::new(cx + rx, cy),
+                ),
+                (
+                    Point::new(cx + rx, cy + hy),
+                    Point::new(cx + hx, cy + ry),
+                    Point::new(cx, cy + ry),
+                ),
+                (
+                    Point::new(cx - hx, cy + ry),
+                    Point::new(cx - rx, cy + hy),
+                    Point::new(cx - rx, cy),
+                ),
+                (
+                    Point::new(cx - rx, cy - hy),
+                    Point::new(cx - hx, cy - ry),
+                    Point::new(cx, cy - ry),
+                ),
+            ],
+            is_closed: true,
+        }
+    }
+
+    fn transformed(mut self, matrix: Affine) -> Contour {
+        self.start = self.start.map(|start| matrix.apply(start));
+        for (control_1, control_2, end) in &mut self.segments {
+            *control_1 = matrix.apply(*control_1);
+            *control_2 = matrix.apply(*control_2);
+            *end = matrix.apply(*end);
+        }
+
+        self
+    }
+
+    fn trace(&self, builder: &mut path::Builder) {
+        let Some(start) = self.start else {
+            return;
+        };
+        builder.move_to(start);
+        for (control_1, control_2, end) in &self.segments {
+            builder.bezier_curve_to(*control_1, *control_2, *end);
+        }
+        if self.is_closed {
+            builder.close();
+        }
+    }
+}
+
+/// A 2D affine transform, mapping `(x, y)` to
+/// `(a x + c y + e, b x + d y + f)`.
+#[derive(Debug, Clone, Copy)]
+struct Affine {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Affine {
+    fn translate(x: f32, y: f32) -> Affine {
+        Affine {
+            a: 1.,
+            b: 0.,
+            c: 0.,
+            d: 1.,
+            e: x,
+            f: y,
+        }
+    }
+
+    /// `other` applied first, then `self`.
+    fn then(self, other: Affine) -> Affine {
+        Affine {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    fn then_scale(self, x: f32, y: f32) -> Affine {
+        self.then(Affine {
+            a: x,
+            d: y,
+            ..Affine::translate(0., 0.)
+        })
+    }
+
+    fn then_rotate(self, radians: f32) -> Affine {
+        let (sin, cos) = radians.sin_cos();
+
+        self.then(Affine {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            e: 0.,
+            f: 0.,
+        })
+    }
+
+    fn apply(&self, point: Point) -> Point {
+        Point::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    /// How much lengths grow on average, for stroke widths.
+    fn scale_factor(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+pub fn field<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.as_object()?.get(key)
+}
+
+pub fn array(value: Option<&Value>) -> &[Value] {
+    match value {
+        Some(Value::Array(values)) => values,
+        _ => &[],
+    }
+}
+
+fn number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number(number) => number.as_f64().map(|number| number as f32),
+        Value::Bool(value) => Some(if *value { 1. } else { 0. }),
+        _ => None,
+    }
+}
+
+fn numbers(value: &Value) -> Option<Vec<f32>> {
+    match value {
+        Value::Array(values) => values.iter().map(number).collect(),
+        value => Some(vec![number(value)?]),
+    }
+}
+
+fn is_true(value: &Value) -> bool {
+    number(value).is_some_and(|value| value != 0.)
+}
+
+fn point(values: &[f32]) -> Point {
+    Point::new(
+        values.first().copied().unwrap_or(0.),
+        values.get(1).copied().unwrap_or(0.),
+    )
+}
+
+fn size_of(values: &[f32]) -> Size {
+    let point = point(values);
+
+    Size::new(point.x, point.y)
+}
+
+fn parse_hex(color: &str) -> Option<Color> {
+    let hex = color.trim_start_matches('#');
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+
+    Some(Color::from_rgb8(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Reads a JSON file.
+pub fn parse_json(contents: &[u8]) -> Result<Value, String> {
+    serde_json::from_slice(contents).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(json: &str) -> Value {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn property(property: &str) -> Property<Vec<f32>> {
+        Property::parse(Some(&json(property)), numbers)
+    }
+
+    fn assert_near(actual: Point, expected: Point) {
+        assert!(
+            actual.distance(expected) < 1e-3,
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn json_objects_become_maps() {
+        let value = json(r#"{"w": 100, "layers": [{"nm": null}, true]}"#);
+
+        assert_eq!(field(&value, "w").and_then(number), Some(100.));
+        assert_eq!(array(field(&value, "layers")).len(), 2);
+        assert_eq!(field(&value, "h"), None);
+    }
+
+    #[test]
+    fn json_string_escapes() {
+        let value = json(r#"{"nm": "a\/b é 🎵 \"c\"\n"}"#);
+
+        assert_eq!(
+            field(&value, "nm"),
+            Some(&Value::String("a/b é 🎵 \"c\"\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn compositions() {
+        let lottie =
+            Lottie::parse(br#"{"w": 512, "h": 256, "fr": 25, "ip": 10, "op": 60, "layers": []}"#)
+                .unwrap();
+
+        assert_eq!(lottie.size(), Size::new(512., 256.));
+        assert_eq!(lottie.duration(), 2.);
+        assert_eq!(lottie.0.in_point, 10.);
+    }
+
+    #[test]
+    fn not_lottie() {
+        assert_eq!(
+            Lottie::parse(br#"{"name": "package"}"#).unwrap_err(),
+            "not a Lottie animation"
+        );
+        assert_eq!(
+            Lottie::parse(br#"{"w": 1, "h": 1}"#).unwrap_err(),
+            "the animation has no layers"
+        );
+        assert!(Lottie::parse(b"\xff").is_err());
+    }
+
+    #[test]
+    fn static_properties() {
+        assert_eq!(property(r#"{"k": [1, 2]}"#).at(5.), vec![1., 2.]);
+        assert_eq!(property(r#"{"k": 3}"#).at(5.), vec![3.]);
+        assert!(matches!(property(r#"{"a": 0}"#), Property::Missing));
+    }
+
+    #[test]
+    fn keyframes() {
+        let linear = r#""o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}"#;
+        let animated = property(&format!(
+            r#"{{"a": 1, "k": [{{"t": 0, "s": [0], {0}}}, {{"t": 10, "s": [100], "h": 1}}, {{"t": 20, "s": [50]}}]}}"#,
+            linear
+        ));
+
+        assert_eq!(animated.at(-5.), vec![0.]);
+        assert!((animated.at(2.5)[0] - 25.).abs() < 1e-3);
+        // Held until the next keyframe.
+        assert_eq!(animated.at(15.), vec![100.]);
+        assert_eq!(animated.at(30.), vec![50.]);
+    }
+
+    #[test]
+    fn keyframes_with_end_values() {
+        let animated = property(r#"{"k": [{"t": 0, "s": [0], "e": [10]}, {"t": 10}]}"#);
+
+        assert_eq!(animated.at(10.), vec![10.]);
+        assert_eq!(animated.at(100.), vec![10.]);
+    }
+
+    #[test]
+    fn easing() {
+        let (linear_out, linear_in) = (Point::new(0., 0.), Point::new(1., 1.));
+        assert!((ease(linear_out, linear_in, 0.3) - 0.3).abs() < 1e-3);
+
+        // Eases in slowly and out quickly.
+        let eased = ease(Point::new(0.5, 0.), Point::new(1., 1.), 0.5);
+        assert!(eased < 0.5);
+    }
+
+    #[test]
+    fn parented_transforms() {
+        let layers = parse_layers(&json(
+            r#"[
+                {"ty": 3, "ind": 1, "ks": {"p": {"k": [100, 50]}, "s": {"k": [200, 200]}}},
+                {"ty": 3, "ind": 2, "parent": 1, "ks": {"p": {"k": [10, 0]}, "a": {"k": [5, 5]}, "r": {"k": 90}}},
+                {"ty": 5, "ind": 3}
+            ]"#,
+        ));
+        assert_eq!(layers.len(), 2);
+
+        let matrix = layer_matrix(&layers, &layers[1], 0., 0);
+        // The anchor lands on the position, then the parent moves and scales
+        // it.
+        assert_near(matrix.apply(Point::new(5., 5.)), Point::new(120., 50.));
+        assert_near(matrix.apply(Point::new(6., 5.)), Point::new(120., 52.));
+    }
+
+    #[test]
+    fn split_positions() {
+        let transform = Transform::parse(Some(&json(
+            r#"{"p": {"s": true, "x": {"k": 4}, "y": {"k": 8}}}"#,
+        )));
+
+        assert_near(
+            transform.matrix(0.).apply(Point::ORIGIN),
+            Point::new(4., 8.),
+        );
+    }
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(parse_hex("#ff8000"), Some(Color::from_rgb8(255, 128, 0)));
+        assert_eq!(parse_hex("ff80"), None);
+    }
+}
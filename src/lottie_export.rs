@@ -0,0 +1,1382 @@
+use std::{
+    io::{self, Cursor},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use base64::Engine;
+use iced::{
+    font::{self, Family},
+    widget::image::Handle,
+    Color, Font, Point, Rectangle, Size, Vector,
+};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{
+    animation::{Motion, Preset, TransitionKind},
+    canvas::{self, Layer, LayerKind, Playhead, Sizing},
+    directories::{self, DialogKind},
+    i18n::t,
+    keyframes::{Bezier, Property},
+    lottie::{self, Lottie},
+    post_fx::PostFx,
+    procedural::{Procedural, Progress, Text},
+    project::FrameRate,
+    scenes::{self, Scene},
+    typesetting::typeset,
+    Error, LOTTIE_EXTENSIONS,
+};
+
+/// A layer to export, with the file it shows.
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub layer: Layer,
+    pub contents: Option<Arc<Vec<u8>>>,
+    /// Downscaled copy of a large image, rasterized in place of the full
+    /// resolution file.
+    pub preview: Option<Arc<image::RgbaImage>>,
+}
+
+/// Where the animation was written, and what couldn't be put in it.
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub path: PathBuf,
+    /// Names of the layers a Lottie player can't reproduce, as they are
+    /// drawn live from the audio or the clock, e.g. waveforms, timers and
+    /// grain, or are placeholders.
+    pub left_out: Vec<String>,
+}
+
+/// Asks where to save the layers as a Lottie animation lasting `duration`
+/// seconds, or until the last layer is done if there is no audio, then
+/// writes it.
+///
+/// Placement, keyframes, transitions and animation presets become Lottie
+/// transforms, text becomes text layers, progress bars and borders become
+/// shapes, and Lottie layers are embedded as they are. Images, with their
+/// crop, tint and rounded corners, and vignettes are rasterized into the
+/// file. Layers in a scene are only shown during it, switching with its
+/// transitions. Motion following the audio is left out, and animated images
+/// keep their first frame. Letterbox bars are drawn over everything, while the
+/// project's other post effects are left out.
+pub async fn save(
+    sources: Vec<Source>,
+    post_fx: PostFx,
+    scenes: Vec<Scene>,
+    canvas: Size,
+    duration: Option<f32>,
+    frame_rate: FrameRate,
+) -> Result<Export, Error> {
+    let path: PathBuf = directories::dialog(DialogKind::Export)
+        .set_title(t("lottie-export-dialog"))
+        .add_filter(t("filter-lottie"), LOTTIE_EXTENSIONS)
+        .set_file_name("animation.json")
+        .save_file()
+        .await
+        .ok_or(Error::DialogClosed)?
+        .into();
+    directories::remember(DialogKind::Export, &path).await;
+
+    let saved = path.clone();
+    let left_out = tokio::task::spawn_blocking(move || {
+        let duration = duration.unwrap_or_else(|| content_duration(&sources));
+        let mut exporter = Exporter::new(
+            canvas,
+            duration,
+            frame_rate.per_second() as f32,
+            scenes.into(),
+        );
+        for source in &sources {
+            exporter.add(source);
+        }
+        exporter.letterbox(&post_fx.letterbox.bars(canvas));
+        let name = saved
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+        let left_out = std::mem::take(&mut exporter.left_out);
+
+        let json = serde_json::to_vec(&exporter.finish(&name))
+            .map_err(|_| Error::IoError(io::ErrorKind::Other))?;
+        std::fs::write(&saved, json).map_err(|error| Error::IoError(error.kind()))?;
+
+        Ok::<_, Error>(left_out)
+    })
+    .await
+    .map_err(|_| Error::IoError(io::ErrorKind::Other))??;
+
+    Ok(Export { path, left_out })
+}
+
+/// Seconds until the last layer leaves, its keyframes settle or its Lottie
+/// animation has played once, for projects without audio to time them by.
+fn content_duration(sources: &[Source]) -> f32 {
+    sources
+        .iter()
+        .map(|source| {
+            let layer = &source.layer;
+            let start = layer.timing.start.max(0.);
+            let lottie = match &layer.kind {
+                LayerKind::Lottie { lottie, .. } => start + lottie.duration(),
+                _ => start,
+            };
+            let keyframes = layer.keyframes.span().map_or(0., |(_, last)| last);
+
+            layer.timing.end.unwrap_or(0.).max(lottie).max(keyframes)
+        })
+        .fold(1., f32::max)
+}
+
+const LINEAR: Bezier = Bezier {
+    x1: 0.,
+    y1: 0.,
+    x2: 1.,
+    y2: 1.,
+};
+
+/// A value of an animated property at a point in time.
+struct Key {
+    /// Seconds into the animation.
+    time: f32,
+    value: Vec<f32>,
+    /// Easing towards the next key.
+    ease: Bezier,
+}
+
+/// What every Lottie layer making up one of the project's layers shares.
+struct Placement {
+    parent: usize,
+    in_point: f32,
+    out_point: f32,
+    opacity: Animated,
+}
+
+/// Builds the animation up layer by layer.
+struct Exporter {
+    canvas: Size,
+    /// Seconds the animation lasts.
+    duration: f32,
+    frame_rate: f32,
+    scenes: Arc<[Scene]>,
+    /// The Lottie layers of each exported layer, topmost first, in the
+    /// order the layers are drawn.
+    groups: Vec<Vec<LottieLayer>>,
+    assets: Vec<Asset>,
+    /// Each font text is set in.
+    fonts: Vec<FontFace>,
+    left_out: Vec<String>,
+    /// Number of the next Lottie layer, which children refer to their
+    /// parent by.
+    next_index: usize,
+}
+
+impl Exporter {
+    /// Motion is sampled at most this many times per layer, so that a
+    /// drifting layer on a long track doesn't make the file balloon.
+    const MAX_SAMPLES: usize = 20_000;
+
+    fn new(canvas: Size, duration: f32, frame_rate: f32, scenes: Arc<[Scene]>) -> Exporter {
+        Exporter {
+            canvas,
+            duration,
+            frame_rate,
+            scenes,
+            groups: vec![],
+            assets: vec![],
+            fonts: vec![],
+            left_out: vec![],
+            next_index: 1,
+        }
+    }
+
+    fn add(&mut self, source: &Source) {
+        let layer = &source.layer;
+        let (start, end) = self.span(layer);
+        if end <= start {
+            return;
+        }
+
+        let parent = self.index();
+        let (transform, opacity) = self.transform(layer);
+        let placement = Placement {
+            parent,
+            in_point: start * self.frame_rate,
+            out_point: end * self.frame_rate,
+            opacity,
+        };
+        let bounds = layer.bounds();
+
+        let content = match &layer.kind {
+            LayerKind::Image { .. } => self.image_layer(&placement, source).into_iter().collect(),
+            LayerKind::Procedural { procedural, frames } => match procedural {
+                Procedural::Vignette(_) => frames
+                    .first()
+                    .and_then(rgba_pixels)
+                    .and_then(|pixels| self.image(&placement, &layer.name, &pixels, bounds))
+                    .into_iter()
+                    .collect(),
+                Procedural::Text(text) => self.text(&placement, &layer.name, text, bounds),
+                Procedural::Progress(progress) => {
+                    vec![self.progress(&placement, &layer.name, progress, bounds)]
+                }
+                Procedural::Grain(_)
+                | Procedural::Starfield(_)
+                | Procedural::Readout(_)
+                | Procedural::Timer(_)
+                | Procedural::Wave(_) => vec![],
+            },
+            LayerKind::Lottie { lottie, .. } => source
+                .contents
+                .as_deref()
+                .and_then(|contents| self.precomp(&placement, layer, lottie, contents))
+                .into_iter()
+                .collect(),
+            LayerKind::Placeholder | LayerKind::Capture { .. } | LayerKind::Backdrop => vec![],
+        };
+        if content.is_empty() {
+            self.left_out.push(layer.name.clone());
+            return;
+        }
+
+        let mut group = vec![];
+        if let Some(border) = self.border(&placement, layer) {
+            group.push(border);
+        }
+        group.extend(content);
+        group.push(LottieLayer::new(
+            parent,
+            &layer.name,
+            None,
+            &placement,
+            transform,
+            Content::Null {},
+        ));
+        self.groups.push(group);
+    }
+
+    /// The letterbox bars, over every layer for the whole animation.
+    fn letterbox(&mut self, bars: &[Rectangle]) {
+        if bars.is_empty() {
+            return;
+        }
+
+        let parent = self.index();
+        let placement = Placement {
+            parent,
+            in_point: 0.,
+            out_point: self.duration * self.frame_rate,
+            opacity: fixed(&[100.]),
+        };
+        let shapes: Vec<Shape> = bars
+            .iter()
+            .map(|bar| {
+                group(vec![
+                    rectangle(
+                        fixed(&[bar.center().x, bar.center().y]),
+                        fixed(&[bar.width, bar.height]),
+                        0.,
+                    ),
+                    fill(Color::BLACK),
+                ])
+            })
+            .collect();
+        let bars = self.shapes(&placement, "Letterbox", shapes);
+        let null = LottieLayer::new(
+            parent,
+            "Letterbox",
+            None,
+            &placement,
+            Transform::still(Point::ORIGIN, [100., 100.], fixed(&[100.])),
+            Content::Null {},
+        );
+
+        self.groups.push(vec![bars, null]);
+    }
+
+    fn finish(self, name: &str) -> Animation {
+        Animation {
+            v: "5.7.0",
+            fr: Number(self.frame_rate),
+            ip: 0,
+            op: Number((self.duration * self.frame_rate).ceil()),
+            w: Number(self.canvas.width.round()),
+            h: Number(self.canvas.height.round()),
+            nm: name.to_string(),
+            ddd: 0,
+            assets: self.assets,
+            fonts: Fonts { list: self.fonts },
+            layers: self.groups.into_iter().rev().flatten().collect(),
+            markers: vec![],
+        }
+    }
+
+    fn index(&mut self) -> usize {
+        self.next_index += 1;
+
+        self.next_index - 1
+    }
+
+    /// Seconds into the animation at which the layer appears and
+    /// disappears, within its scene.
+    fn span(&self, layer: &Layer) -> (f32, f32) {
+        let (scene_start, scene_end) = layer
+            .timing
+            .scene
+            .and_then(|scene| scenes::span(&self.scenes, scene))
+            .unwrap_or((0., None));
+        let start = layer.timing.start.max(scene_start).max(0.);
+        let end = [layer.timing.end, scene_end]
+            .into_iter()
+            .flatten()
+            .fold(self.duration, f32::min);
+
+        (start, end)
+    }
+
+    /// The transform of the null layer moving the layer's content around,
+    /// and the opacity its content is drawn with, Lottie parents only
+    /// passing on their transform.
+    ///
+    /// Keyframes become Lottie keyframes with the same easing, while the
+    /// motion of animation presets and transitions is sampled every frame.
+    fn transform(&self, layer: &Layer) -> (Transform, Animated) {
+        let bounds = layer.bounds();
+        let center = bounds.center();
+        let transitions: Vec<TransitionKind> = std::iter::once(layer.timing.transition)
+            .chain(
+                layer
+                    .timing
+                    .scene
+                    .into_iter()
+                    .flat_map(|scene| scenes::transitions(&self.scenes, scene)),
+            )
+            .filter(|transition| transition.duration > 0.)
+            .map(|transition| transition.kind)
+            .collect();
+        let moves = layer.animation.preset == Preset::Drift
+            || transitions
+                .iter()
+                .any(|kind| matches!(kind, TransitionKind::Slide | TransitionKind::Glitch));
+        let scales = transitions.contains(&TransitionKind::Zoom);
+        let fades = transitions.iter().any(|kind| {
+            matches!(
+                kind,
+                TransitionKind::Crossfade | TransitionKind::Zoom | TransitionKind::Glitch
+            )
+        });
+        let spins = layer.animation.preset == Preset::Spin;
+
+        let samples = if moves || scales || fades {
+            self.samples(layer)
+        } else {
+            vec![]
+        };
+        let sampled = |value: &dyn Fn(&Motion) -> Vec<f32>| {
+            samples
+                .iter()
+                .map(|(time, motion)| Key {
+                    time: *time,
+                    value: value(motion),
+                    ease: LINEAR,
+                })
+                .collect()
+        };
+
+        let position = if moves {
+            Position::Joined(self.property(sampled(&|motion| {
+                vec![center.x + motion.offset.x, center.y + motion.offset.y]
+            })))
+        } else {
+            Position::Split {
+                s: true,
+                x: self.property(keys(layer, Property::X, |x| vec![x + bounds.width / 2.])),
+                y: self.property(keys(layer, Property::Y, |y| vec![y + bounds.height / 2.])),
+            }
+        };
+        let scale = if scales {
+            self.property(sampled(&|motion| vec![motion.scale * 100.; 2]))
+        } else {
+            self.property(keys(layer, Property::Scale, |scale| vec![scale * 100.; 2]))
+        };
+        let rotation = if spins {
+            let (start, end) = self.span(layer);
+            self.property(
+                [start, end]
+                    .into_iter()
+                    .map(|time| Key {
+                        time,
+                        value: vec![self.motion_at(layer, time).rotation.to_degrees()],
+                        ease: LINEAR,
+                    })
+                    .collect(),
+            )
+        } else {
+            fixed(&[0.])
+        };
+        let opacity = if fades {
+            self.property(sampled(&|motion| vec![motion.opacity * 100.]))
+        } else {
+            self.property(keys(layer, Property::Opacity, |opacity| {
+                vec![opacity.clamp(0., 1.) * 100.]
+            }))
+        };
+
+        (
+            Transform {
+                a: fixed(&[center.x, center.y]),
+                p: position,
+                s: scale,
+                r: rotation,
+                o: fixed(&[100.]),
+            },
+            opacity,
+        )
+    }
+
+    /// The motion of the layer `time` seconds into the track, without audio
+    /// to follow.
+    fn motion_at(&self, layer: &Layer, time: f32) -> Motion {
+        layer.motion(&Playhead {
+            time: Duration::from_secs_f32(time.max(0.)),
+            scenes: self.scenes.clone(),
+            ..Playhead::default()
+        })
+    }
+
+    /// The layer's motion at every frame it is shown.
+    fn samples(&self, layer: &Layer) -> Vec<(f32, Motion)> {
+        let (start, end) = self.span(layer);
+        let frames = (((end - start) * self.frame_rate).ceil() as usize).min(Self::MAX_SAMPLES);
+        let step = (end - start) / frames.max(1) as f32;
+
+        (0..=frames)
+            .map(|frame| {
+                let time = start + frame as f32 * step;
+
+                (time, self.motion_at(layer, time))
+            })
+            .collect()
+    }
+
+    /// A property following `keys`, held still where neighbouring keys have
+    /// the same value.
+    fn property(&self, mut keys: Vec<Key>) -> Animated {
+        let mut index = 1;
+        while index + 1 < keys.len() {
+            if keys[index - 1].value == keys[index].value
+                && keys[index].value == keys[index + 1].value
+            {
+                keys.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+        if keys.len() == 2 && keys[0].value == keys[1].value {
+            keys.pop();
+        }
+        match keys.as_slice() {
+            [] => return fixed(&[0.]),
+            [key] => return fixed(&key.value),
+            _ => {}
+        }
+
+        let last = keys.len() - 1;
+        let keyframes = keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| {
+                // The last key has no segment after it to ease.
+                let Bezier { x1, y1, x2, y2 } = key.ease;
+                let (ease_out, ease_in) = if index == last {
+                    (None, None)
+                } else {
+                    (Some(Tangent::new(x1, y1)), Some(Tangent::new(x2, y2)))
+                };
+
+                Keyframe {
+                    t: Number(key.time * self.frame_rate),
+                    s: numbers(&key.value),
+                    o: ease_out,
+                    i: ease_in,
+                }
+            })
+            .collect();
+
+        Animated::Keyframed { a: 1, k: keyframes }
+    }
+
+    /// A Lottie layer showing `content` laid out from `position`, scaled by
+    /// `scale` percent, which follows the null layer of its project layer.
+    fn child(
+        &mut self,
+        placement: &Placement,
+        name: &str,
+        position: Point,
+        scale: [f32; 2],
+        content: Content,
+    ) -> LottieLayer {
+        let index = self.index();
+
+        LottieLayer::new(
+            index,
+            name,
+            Some(placement.parent),
+            placement,
+            Transform::still(position, scale, placement.opacity.clone()),
+            content,
+        )
+    }
+
+    fn image_layer(&mut self, placement: &Placement, source: &Source) -> Option<LottieLayer> {
+        let layer = &source.layer;
+        let options = layer.image_options()?;
+        // Tiles are rendered at the image's own pixel size, which the
+        // preview is smaller than.
+        let image = match &source.preview {
+            Some(preview) if options.sizing != Sizing::Tile => {
+                image::DynamicImage::ImageRgba8(preview.as_ref().clone())
+            }
+            _ => canvas::decode_image_file(source.contents.as_deref()?)?,
+        };
+        let pixels = canvas::render_pixels(image, options, layer.bounds().size());
+
+        self.image(placement, &layer.name, &pixels, layer.image_bounds())
+    }
+
+    /// An image layer stretching `pixels` over `bounds`, the pixels being
+    /// embedded in the file as a PNG.
+    fn image(
+        &mut self,
+        placement: &Placement,
+        name: &str,
+        pixels: &image::RgbaImage,
+        bounds: Rectangle,
+    ) -> Option<LottieLayer> {
+        let mut png = vec![];
+        pixels
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .ok()?;
+        let id = format!("image_{}", self.assets.len());
+        self.assets.push(Asset::Image {
+            id: id.clone(),
+            w: pixels.width(),
+            h: pixels.height(),
+            u: "",
+            p: format!(
+                "data:image/png;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(&png)
+            ),
+            e: 1,
+        });
+
+        Some(self.child(
+            placement,
+            name,
+            bounds.position(),
+            [
+                bounds.width / pixels.width().max(1) as f32 * 100.,
+                bounds.height / pixels.height().max(1) as f32 * 100.,
+            ],
+            Content::Image { ref_id: id },
+        ))
+    }
+
+    /// A text layer for each run of the text set in one font, placed where
+    /// it is drawn on the canvas, above a shape layer for its background.
+    /// Text animations are left out, the text being shown in full.
+    fn text(
+        &mut self,
+        placement: &Placement,
+        name: &str,
+        text: &Text,
+        bounds: Rectangle,
+    ) -> Vec<LottieLayer> {
+        let typeset = typeset(
+            &text.content,
+            text.font.to_font(Font::DEFAULT),
+            text.size,
+            &text.style,
+            None,
+        );
+        let origin =
+            bounds.center() - Vector::new(typeset.size.width / 2., typeset.size.height / 2.);
+        let outline = (text.style.outline_width > 0.).then(|| Outline {
+            sc: numbers(&rgb(text.style.outline_color)),
+            sw: Number(text.style.outline_width * 2.),
+            of: true,
+        });
+
+        let mut layers = vec![];
+        for segment in &typeset.segments {
+            let font = self.font(segment.font);
+            let document = TextDocument {
+                s: Number(text.size),
+                f: font,
+                t: segment.text.clone(),
+                j: 0,
+                tr: 0,
+                lh: Number(typeset.line_height),
+                ls: 0,
+                fc: numbers(&rgb(text.color)),
+                outline: outline.clone(),
+            };
+            let content = Content::Text {
+                t: TextData {
+                    d: TextKeyframes {
+                        k: vec![TextKeyframe { s: document, t: 0 }],
+                    },
+                    p: Empty {},
+                    m: TextAlignment {
+                        g: 1,
+                        a: fixed(&[0., 0.]),
+                    },
+                    a: vec![],
+                },
+            };
+            let position = Point::new(origin.x + segment.offset.x, origin.y + segment.baseline);
+
+            layers.push(self.child(placement, name, position, [100., 100.], content));
+        }
+
+        if text.style.has_background {
+            let padding = text.style.background_padding.max(0.);
+            let size = Size::new(
+                typeset.size.width + padding * 2.,
+                typeset.size.height + padding * 2.,
+            );
+            let shapes = group(vec![
+                rectangle(
+                    fixed(&[bounds.center().x, bounds.center().y]),
+                    fixed(&[size.width, size.height]),
+                    padding.min(text.size / 2.),
+                ),
+                fill(text.style.background_color),
+            ]);
+
+            layers.push(self.shapes(placement, name, vec![shapes]));
+        }
+
+        layers
+    }
+
+    /// The name of `font` in the file's font list, adding it if it's new.
+    fn font(&mut self, font: Font) -> String {
+        let family = match font.family {
+            Family::Name(name) => name,
+            Family::Serif => "serif",
+            Family::SansSerif => "sans-serif",
+            Family::Cursive => "cursive",
+            Family::Fantasy => "fantasy",
+            Family::Monospace => "monospace",
+        };
+        let weight = match font.weight {
+            font::Weight::Thin => "Thin",
+            font::Weight::ExtraLight => "ExtraLight",
+            font::Weight::Light => "Light",
+            font::Weight::Normal => "Regular",
+            font::Weight::Medium => "Medium",
+            font::Weight::Semibold => "SemiBold",
+            font::Weight::Bold => "Bold",
+            font::Weight::ExtraBold => "ExtraBold",
+            font::Weight::Black => "Black",
+        };
+        let style = match (font.style, weight) {
+            (font::Style::Normal, weight) => weight.to_string(),
+            (font::Style::Italic | font::Style::Oblique, "Regular") => "Italic".to_string(),
+            (font::Style::Italic | font::Style::Oblique, weight) => format!("{} Italic", weight),
+        };
+        let name = format!("{}-{}", family.replace(' ', ""), style.replace(' ', ""));
+
+        if !self.fonts.iter().any(|existing| existing.name == name) {
+            self.fonts.push(FontFace {
+                name: name.clone(),
+                family: family.to_string(),
+                style,
+                ascent: 75,
+            });
+        }
+
+        name
+    }
+
+    /// The bar filling up from the left over the length of the animation,
+    /// over the track still to be played.
+    fn progress(
+        &mut self,
+        placement: &Placement,
+        name: &str,
+        progress: &Progress,
+        bounds: Rectangle,
+    ) -> LottieLayer {
+        let radius = if progress.is_rounded {
+            bounds.height.min(bounds.width) / 2.
+        } else {
+            0.
+        };
+        let center = bounds.center();
+        let bar = |value: &dyn Fn(f32) -> Vec<f32>| {
+            self.property(
+                [0., 1.]
+                    .into_iter()
+                    .map(|shown| Key {
+                        time: shown * self.duration,
+                        value: value(shown * bounds.width),
+                        ease: LINEAR,
+                    })
+                    .collect(),
+            )
+        };
+        let position = bar(&|width| vec![bounds.x + width / 2., center.y]);
+        let size = bar(&|width| vec![width, bounds.height]);
+
+        let shapes = vec![
+            group(vec![
+                rectangle(position, size, radius),
+                fill(progress.color),
+            ]),
+            group(vec![
+                rectangle(
+                    fixed(&[center.x, center.y]),
+                    fixed(&[bounds.width, bounds.height]),
+                    radius,
+                ),
+                fill(progress.track_color),
+            ]),
+        ];
+
+        self.shapes(placement, name, shapes)
+    }
+
+    /// The layer's border stroked inside its image, if it has one. A border
+    /// following the pitch keeps its own color.
+    fn border(&mut self, placement: &Placement, layer: &Layer) -> Option<LottieLayer> {
+        let border = layer.appearance.border;
+        if border.width <= 0. {
+            return None;
+        }
+        let bounds = layer.image_bounds();
+        let inset = border.width / 2.;
+        let shapes = group(vec![
+            rectangle(
+                fixed(&[bounds.center().x, bounds.center().y]),
+                fixed(&[
+                    (bounds.width - border.width).max(0.),
+                    (bounds.height - border.width).max(0.),
+                ]),
+                (layer.appearance.corner_radius - inset).max(0.),
+            ),
+            Shape::Stroke {
+                c: fixed(&color(border.color)),
+                o: fixed(&[border.color.a * 100.]),
+                w: fixed(&[border.width]),
+                lc: 2,
+                lj: 2,
+            },
+        ]);
+
+        Some(self.shapes(placement, &layer.name, vec![shapes]))
+    }
+
+    /// A shape layer drawing `shapes` in canvas coordinates.
+    fn shapes(&mut self, placement: &Placement, name: &str, shapes: Vec<Shape>) -> LottieLayer {
+        self.child(
+            placement,
+            name,
+            Point::ORIGIN,
+            [100., 100.],
+            Content::Shapes { shapes },
+        )
+    }
+
+    /// The animation of a Lottie layer, embedded as a precomposition and
+    /// stretched over the layer, with its time remapped to play back as it
+    /// does on the canvas.
+    fn precomp(
+        &mut self,
+        placement: &Placement,
+        layer: &Layer,
+        lottie: &Lottie,
+        contents: &[u8],
+    ) -> Option<LottieLayer> {
+        let root = lottie::parse_json(contents).ok()?;
+        // The embedded file's IDs are prefixed so they can't clash with
+        // those of other embedded files.
+        let prefix = format!("lottie_{}_", self.assets.len());
+        for asset in lottie::array(lottie::field(&root, "assets")) {
+            self.assets.push(Asset::Embedded(prefixed(asset, &prefix)));
+        }
+        let id = format!("{}composition", prefix);
+        self.assets.push(Asset::Precomp {
+            id: id.clone(),
+            layers: prefixed(lottie::field(&root, "layers")?, &prefix),
+        });
+
+        let time_remap = self.property(self.time_remap(layer, lottie));
+        let size = lottie.size();
+        let bounds = layer.bounds();
+
+        Some(self.child(
+            placement,
+            &layer.name,
+            bounds.position(),
+            [
+                bounds.width / size.width * 100.,
+                bounds.height / size.height * 100.,
+            ],
+            Content::Precomp {
+                ref_id: id,
+                w: Number(size.width),
+                h: Number(size.height),
+                tm: time_remap,
+            },
+        ))
+    }
+
+    /// The time shown by a Lottie layer, looping or ping-ponging at its
+    /// speed from when it appears. Lottie players read remapped times as
+    /// seconds at the frame rate of the outer animation, rather than that
+    /// of the embedded one.
+    fn time_remap(&self, layer: &Layer, lottie: &Lottie) -> Vec<Key> {
+        let (start, end) = self.span(layer);
+        let length = lottie.duration();
+        let shown = |time: f32| {
+            let position = layer.playback.position(length, time - start);
+
+            vec![lottie.frame(position) / self.frame_rate]
+        };
+        let frame = 1. / self.frame_rate;
+        let turn = length / layer.playback.speed.abs();
+
+        let mut times = vec![start];
+        if turn.is_finite() && turn > frame {
+            let mut boundary = start + turn;
+            while boundary < end && times.len() < Self::MAX_SAMPLES {
+                // The last frame before the animation starts over, which
+                // it jumps back from.
+                times.extend([boundary - frame, boundary]);
+                boundary += turn;
+            }
+        }
+        times.push(end);
+
+        times
+            .into_iter()
+            .map(|time| Key {
+                time,
+                value: shown(time),
+                ease: LINEAR,
+            })
+            .collect()
+    }
+}
+
+/// The keyframes of `property`, or its value if it isn't animated.
+fn keys(layer: &Layer, property: Property, value: impl Fn(f32) -> Vec<f32>) -> Vec<Key> {
+    match layer.keyframes.track(property) {
+        Some(track) if !track.keyframes.is_empty() => track
+            .keyframes
+            .iter()
+            .map(|keyframe| Key {
+                time: keyframe.time,
+                value: value(keyframe.value),
+                ease: keyframe.ease,
+            })
+            .collect(),
+        _ => vec![Key {
+            time: 0.,
+            value: value(layer.property(property)),
+            ease: LINEAR,
+        }],
+    }
+}
+
+/// The root of a Lottie file. Lottie names its fields with a letter or two,
+/// which the fields here keep.
+#[derive(Serialize)]
+struct Animation {
+    v: &'static str,
+    fr: Number,
+    ip: u8,
+    op: Number,
+    w: Number,
+    h: Number,
+    nm: String,
+    ddd: u8,
+    assets: Vec<Asset>,
+    fonts: Fonts,
+    layers: Vec<LottieLayer>,
+    markers: Vec<()>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Asset {
+    Image {
+        id: String,
+        w: u32,
+        h: u32,
+        u: &'static str,
+        p: String,
+        /// Whether `p` holds the image itself rather than its file name.
+        e: u8,
+    },
+    Precomp {
+        id: String,
+        layers: Value,
+    },
+    /// An asset of an embedded Lottie file, copied as it is.
+    Embedded(Value),
+}
+
+#[derive(Serialize)]
+struct Fonts {
+    list: Vec<FontFace>,
+}
+
+#[derive(Serialize)]
+struct FontFace {
+    #[serde(rename = "fName")]
+    name: String,
+    #[serde(rename = "fFamily")]
+    family: String,
+    #[serde(rename = "fStyle")]
+    style: String,
+    ascent: u8,
+}
+
+#[derive(Serialize)]
+struct LottieLayer {
+    ddd: u8,
+    ind: usize,
+    ty: u8,
+    nm: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<usize>,
+    ip: Number,
+    op: Number,
+    st: u8,
+    sr: u8,
+    ks: Transform,
+    ao: u8,
+    bm: u8,
+    #[serde(flatten)]
+    content: Content,
+}
+
+impl LottieLayer {
+    fn new(
+        index: usize,
+        name: &str,
+        parent: Option<usize>,
+        placement: &Placement,
+        transform: Transform,
+        content: Content,
+    ) -> LottieLayer {
+        LottieLayer {
+            ddd: 0,
+            ind: index,
+            ty: content.kind(),
+            nm: name.to_string(),
+            parent,
+            ip: Number(placement.in_point),
+            op: Number(placement.out_point),
+            st: 0,
+            sr: 1,
+            ks: transform,
+            ao: 0,
+            bm: 0,
+            content,
+        }
+    }
+}
+
+/// The fields a layer has for what it shows.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Content {
+    Precomp {
+        #[serde(rename = "refId")]
+        ref_id: String,
+        w: Number,
+        h: Number,
+        /// The remapped time.
+        tm: Animated,
+    },
+    Image {
+        #[serde(rename = "refId")]
+        ref_id: String,
+    },
+    /// Only moves its children around.
+    Null {},
+    Shapes {
+        shapes: Vec<Shape>,
+    },
+    Text {
+        t: TextData,
+    },
+}
+
+impl Content {
+    /// The layer type Lottie tells the content apart by.
+    fn kind(&self) -> u8 {
+        match self {
+            Content::Precomp { .. } => 0,
+            Content::Image { .. } => 2,
+            Content::Null {} => 3,
+            Content::Shapes { .. } => 4,
+            Content::Text { .. } => 5,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct Transform {
+    /// The anchor point.
+    a: Animated,
+    p: Position,
+    s: Animated,
+    r: Animated,
+    o: Animated,
+}
+
+impl Transform {
+    /// Moves content to `position`, scaled by `scale` percent.
+    fn still(position: Point, scale: [f32; 2], opacity: Animated) -> Transform {
+        Transform {
+            a: fixed(&[0., 0.]),
+            p: Position::Joined(fixed(&[position.x, position.y])),
+            s: fixed(&scale),
+            r: fixed(&[0.]),
+            o: opacity,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(untagged)]
+enum Position {
+    Joined(Animated),
+    /// Each coordinate animated on its own.
+    Split {
+        s: bool,
+        x: Animated,
+        y: Animated,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum Animated {
+    Fixed { a: u8, k: Values },
+    Keyframed { a: u8, k: Vec<Keyframe> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum Values {
+    One(Number),
+    Many(Vec<Number>),
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Keyframe {
+    /// The frame.
+    t: Number,
+    s: Vec<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    o: Option<Tangent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    i: Option<Tangent>,
+}
+
+/// A control point of the easing curve towards the next keyframe.
+#[derive(Debug, Clone, Serialize)]
+struct Tangent {
+    x: [Number; 1],
+    y: [Number; 1],
+}
+
+impl Tangent {
+    fn new(x: f32, y: f32) -> Tangent {
+        Tangent {
+            x: [Number(x)],
+            y: [Number(y)],
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "ty")]
+enum Shape {
+    #[serde(rename = "gr")]
+    Group { it: Vec<Shape> },
+    #[serde(rename = "rc")]
+    Rectangle {
+        d: u8,
+        p: Animated,
+        s: Animated,
+        r: Animated,
+    },
+    #[serde(rename = "fl")]
+    Fill { c: Animated, o: Animated, r: u8 },
+    #[serde(rename = "st")]
+    Stroke {
+        c: Animated,
+        o: Animated,
+        w: Animated,
+        lc: u8,
+        lj: u8,
+    },
+    #[serde(rename = "tr")]
+    Transform(Transform),
+}
+
+#[derive(Serialize)]
+struct TextData {
+    d: TextKeyframes,
+    p: Empty,
+    m: TextAlignment,
+    a: Vec<()>,
+}
+
+#[derive(Serialize)]
+struct TextKeyframes {
+    k: Vec<TextKeyframe>,
+}
+
+#[derive(Serialize)]
+struct TextKeyframe {
+    s: TextDocument,
+    t: u8,
+}
+
+#[derive(Serialize)]
+struct TextDocument {
+    s: Number,
+    f: String,
+    t: String,
+    j: u8,
+    tr: u8,
+    lh: Number,
+    ls: u8,
+    fc: Vec<Number>,
+    #[serde(flatten)]
+    outline: Option<Outline>,
+}
+
+#[derive(Clone, Serialize)]
+struct Outline {
+    sc: Vec<Number>,
+    sw: Number,
+    /// Whether the outline is drawn over the fill.
+    of: bool,
+}
+
+#[derive(Serialize)]
+struct TextAlignment {
+    g: u8,
+    a: Animated,
+}
+
+#[derive(Serialize)]
+struct Empty {}
+
+/// A number rounded to a thousandth, which is as precise as players draw,
+/// and written as 0 if it isn't finite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Number(f32);
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.is_finite() {
+            serializer.serialize_f32((self.0 * 1000.).round() / 1000.)
+        } else {
+            serializer.serialize_f32(0.)
+        }
+    }
+}
+
+fn numbers(values: &[f32]) -> Vec<Number> {
+    values.iter().copied().map(Number).collect()
+}
+
+/// A property that isn't animated.
+fn fixed(values: &[f32]) -> Animated {
+    let k = match values {
+        [value] => Values::One(Number(*value)),
+        values => Values::Many(numbers(values)),
+    };
+
+    Animated::Fixed { a: 0, k }
+}
+
+fn group(mut items: Vec<Shape>) -> Shape {
+    items.push(Shape::Transform(Transform::still(
+        Point::ORIGIN,
+        [100., 100.],
+        fixed(&[100.]),
+    )));
+
+    Shape::Group { it: items }
+}
+
+fn rectangle(position: Animated, size: Animated, radius: f32) -> Shape {
+    Shape::Rectangle {
+        d: 1,
+        p: position,
+        s: size,
+        r: fixed(&[radius]),
+    }
+}
+
+fn fill(fill_color: Color) -> Shape {
+    Shape::Fill {
+        c: fixed(&color(fill_color)),
+        o: fixed(&[fill_color.a * 100.]),
+        r: 1,
+    }
+}
+
+fn color(color: Color) -> [f32; 4] {
+    [color.r, color.g, color.b, 1.]
+}
+
+fn rgb(color: Color) -> [f32; 3] {
+    [color.r, color.g, color.b]
+}
+
+/// The pixels of a texture rendered in memory, e.g. a vignette.
+fn rgba_pixels(handle: &Handle) -> Option<image::RgbaImage> {
+    match handle {
+        Handle::Rgba {
+            width,
+            height,
+            pixels,
+            ..
+        } => image::RgbaImage::from_raw(*width, *height, pixels.to_vec()),
+        Handle::Path(..) | Handle::Bytes(..) => None,
+    }
+}
+
+/// `value` with the IDs of assets and the references to them prefixed.
+fn prefixed(value: &Value, prefix: &str) -> Value {
+    match value {
+        Value::Array(values) => {
+            Value::Array(values.iter().map(|value| prefixed(value, prefix)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(id) if key == "id" || key == "refId" => {
+                            Value::String(format!("{}{}", prefix, id))
+                        }
+                        value => prefixed(value, prefix),
+                    };
+
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        animation::{Playback, Timing},
+        canvas::{Appearance, Flip, Visibility},
+        keyframes::Keyframes,
+    };
+
+    fn progress_bar() -> Source {
+        Source {
+            layer: Layer {
+                name: "Progress \"bar\"".to_string(),
+                kind: LayerKind::procedural(Procedural::Progress(Progress::DEFAULT)),
+                x: 20.,
+                y: 300.,
+                width: 600.,
+                height: 10.,
+                scale: 1.,
+                opacity: 1.,
+                flip: Flip::NONE,
+                appearance: Appearance::default(),
+                animation: crate::Animation::NONE,
+                timing: Timing {
+                    start: 0.5,
+                    ..Timing::ALWAYS
+                },
+                playback: Playback::NORMAL,
+                keyframes: Keyframes::default(),
+                audio_channel: Default::default(),
+                audio_source: Default::default(),
+                units: Default::default(),
+                is_aspect_locked: false,
+                visibility: Visibility::Everywhere,
+            },
+            contents: None,
+            preview: None,
+        }
+    }
+
+    #[test]
+    fn exported_animations_can_be_imported() {
+        let mut exporter = Exporter::new(Size::new(640., 360.), 2., 30., Arc::new([]));
+        exporter.add(&progress_bar());
+        exporter.letterbox(&[Rectangle::new(Point::ORIGIN, Size::new(640., 40.))]);
+        let json = serde_json::to_vec(&exporter.finish("Round trip")).unwrap();
+
+        let lottie = Lottie::parse(&json).unwrap();
+        assert_eq!(lottie.size(), Size::new(640., 360.));
+        assert_eq!(lottie.duration(), 2.);
+
+        let root = lottie::parse_json(&json).unwrap();
+        let layers = lottie::array(lottie::field(&root, "layers"));
+        let kinds: Vec<_> = layers
+            .iter()
+            .map(|layer| lottie::field(layer, "ty").and_then(Value::as_u64))
+            .collect();
+        // The letterbox on top, then the bar's shapes above its null layer.
+        assert_eq!(kinds, [Some(4), Some(3), Some(4), Some(3)]);
+        assert_eq!(
+            lottie::field(&layers[3], "nm").and_then(Value::as_str),
+            Some("Progress \"bar\"")
+        );
+        assert_eq!(
+            lottie::field(&layers[2], "parent").and_then(Value::as_u64),
+            Some(1)
+        );
+        assert_eq!(
+            lottie::field(&layers[3], "ip").and_then(Value::as_f64),
+            Some(15.)
+        );
+    }
+
+    #[test]
+    fn numbers_are_rounded() {
+        assert_eq!(
+            serde_json::to_string(&numbers(&[1. / 3., f32::NAN, 100.])).unwrap(),
+            "[0.333,0.0,100.0]"
+        );
+    }
+
+    #[test]
+    fn embedded_ids_are_prefixed() {
+        let value: Value = serde_json::from_str(
+            r#"{"id": "comp", "layers": [{"refId": "image", "nm": "id"}, {"id": 3}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            prefixed(&value, "lottie_0_"),
+            serde_json::from_str::<Value>(
+                r#"{"id": "lottie_0_comp", "layers": [{"refId": "lottie_0_image", "nm": "id"}, {"id": 3}]}"#
+            )
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+/// A color grade read from a `.cube` file, the format most editors and
+/// grading tools export their looks in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SavedLut", into = "SavedLut")]
+pub struct Lut {
+    pub name: String,
+    /// How much of the grade is mixed in, from 0 to 1.
+    pub amount: f32,
+    /// The file as it was read, which is saved with the project so that it
+    /// opens the same elsewhere.
+    cube: Arc<String>,
+    table: Arc<Table>,
+}
+
+#[derive(Debug)]
+struct Table {
+    /// Points along each side of the cube.
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    /// Output colors, red changing fastest, then green, then blue.
+    colors: Vec<[f32; 3]>,
+}
+
+impl Lut {
+    pub fn parse(name: String, cube: String) -> Result<Lut, String> {
+        let mut size = None;
+        let mut domain_min = [0.; 3];
+        let mut domain_max = [1.; 3];
+        let mut colors = vec![];
+
+        let triple = |values: &[&str]| -> Result<[f32; 3], String> {
+            match values {
+                [r, g, b] => {
+                    let parse = |value: &str| {
+                        value
+                            .parse::<f32>()
+                            .map_err(|_| format!("{} is not a number", value))
+                    };
+
+                    Ok([parse(r)?, parse(g)?, parse(b)?])
+                }
+                _ => Err(format!("expected 3 numbers, found {}", values.len())),
+            }
+        };
+
+        for line in cube.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words[0] {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let points = words
+                        .get(1)
+                        .and_then(|size| size.parse::<usize>().ok())
+                        .filter(|size| (2..=256).contains(size))
+                        .ok_or("the size of the cube is missing or out of range")?;
+                    size = Some(points);
+                }
+                "LUT_1D_SIZE" => return Err("1D LUTs aren't supported".to_string()),
+                "DOMAIN_MIN" => domain_min = triple(&words[1..])?,
+                "DOMAIN_MAX" => domain_max = triple(&words[1..])?,
+                // The same domain for every channel.
+                "LUT_3D_INPUT_RANGE" => {
+                    let [min, max, _] = triple(&[&words[1..], &["0"]].concat())?;
+                    domain_min = [min; 3];
+                    domain_max = [max; 3];
+                }
+                // Keywords other tools add, e.g. for their own settings.
+                word if word.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => colors.push(triple(&words)?),
+            }
+        }
+
+        let size = size.ok_or("the file has no LUT_3D_SIZE")?;
+        if colors.len() != size.pow(3) {
+            return Err(format!(
+                "expected {} colors for a cube of size {}, found {}",
+                size.pow(3),
+                size,
+                colors.len()
+            ));
+        }
+
+        Ok(Lut {
+            name,
+            amount: 1.,
+            cube: Arc::new(cube),
+            table: Arc::new(Table {
+                size,
+                domain_min,
+                domain_max,
+                colors,
+            }),
+        })
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.amount > 0.
+    }
+
+    /// Grades `image`, blending between the nearest colors of the cube.
+    pub fn apply(&self, image: &mut image::RgbaImage) {
+        let Table {
+            size,
+            domain_min,
+            domain_max,
+            colors,
+        } = self.table.as_ref();
+        let last = (size - 1) as f32;
+        let at = |r: usize, g: usize, b: usize| colors[(b * size + g) * size + r];
+
+        for pixel in image.pixels_mut() {
+            let rgb = [pixel[0], pixel[1], pixel[2]].map(|channel| channel as f32 / 255.);
+            let position: [f32; 3] = std::array::from_fn(|channel| {
+                let range = (domain_max[channel] - domain_min[channel]).max(f32::EPSILON);
+
+                ((rgb[channel] - domain_min[channel]) / range).clamp(0., 1.) * last
+            });
+            let low = position.map(|position| (position.floor() as usize).min(size - 2));
+            let [tr, tg, tb]: [f32; 3] =
+                std::array::from_fn(|channel| position[channel] - low[channel] as f32);
+            let [r, g, b] = low;
+
+            for channel in 0..3 {
+                let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+                let c = |dr, dg, db| at(r + dr, g + dg, b + db)[channel];
+                let graded = lerp(
+                    lerp(
+                        lerp(c(0, 0, 0), c(1, 0, 0), tr),
+                        lerp(c(0, 1, 0), c(1, 1, 0), tr),
+                        tg,
+                    ),
+                    lerp(
+                        lerp(c(0, 0, 1), c(1, 0, 1), tr),
+                        lerp(c(0, 1, 1), c(1, 1, 1), tr),
+                        tg,
+                    ),
+                    tb,
+                );
+                let mixed = lerp(rgb[channel], graded, self.amount.min(1.));
+
+                pixel[channel] = (mixed.clamp(0., 1.) * 255.).round() as u8;
+            }
+        }
+    }
+}
+
+impl PartialEq for Lut {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.amount == other.amount
+            && (Arc::ptr_eq(&self.cube, &other.cube) || self.cube == other.cube)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedLut {
+    name: String,
+    amount: f32,
+    cube: String,
+}
+
+impl From<Lut> for SavedLut {
+    fn from(lut: Lut) -> Self {
+        SavedLut {
+            name: lut.name,
+            amount: lut.amount,
+            cube: lut.cube.as_ref().clone(),
+        }
+    }
+}
+
+impl TryFrom<SavedLut> for Lut {
+    type Error = String;
+
+    fn try_from(saved: SavedLut) -> Result<Self, Self::Error> {
+        Ok(Lut {
+            amount: saved.amount,
+            ..Lut::parse(saved.name, saved.cube)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A cube of size 2 mapping each corner of the color cube through `map`.
+    fn cube(map: impl Fn([f32; 3]) -> [f32; 3]) -> String {
+        let mut cube = "TITLE \"Test\"\n# A comment\nLUT_3D_SIZE 2\n\n".to_string();
+        for b in 0..2 {
+            for g in 0..2 {
+                for r in 0..2 {
+                    let [r, g, b] = map([r as f32, g as f32, b as f32]);
+                    cube += &format!("{} {} {}\n", r, g, b);
+                }
+            }
+        }
+
+        cube
+    }
+
+    fn graded(lut: &Lut, color: [u8; 3]) -> [u8; 3] {
+        let mut image =
+            image::RgbaImage::from_pixel(1, 1, image::Rgba([color[0], color[1], color[2], 255]));
+        lut.apply(&mut image);
+        let [r, g, b, _] = image.get_pixel(0, 0).0;
+
+        [r, g, b]
+    }
+
+    #[test]
+    fn identity_leaves_colors_alone() {
+        let lut = Lut::parse("Identity".to_string(), cube(|rgb| rgb)).unwrap();
+
+        for color in [[0, 0, 0], [255, 255, 255], [12, 128, 250]] {
+            assert_eq!(graded(&lut, color), color);
+        }
+    }
+
+    #[test]
+    fn red_changes_fastest() {
+        let lut = Lut::parse("Red".to_string(), cube(|[r, _, _]| [r, 0., 0.])).unwrap();
+
+        assert_eq!(graded(&lut, [255, 255, 255]), [255, 0, 0]);
+        assert_eq!(graded(&lut, [0, 255, 255]), [0, 0, 0]);
+    }
+
+    #[test]
+    fn blends_between_points() {
+        let lut = Lut::parse("Invert".to_string(), cube(|rgb| rgb.map(|c| 1. - c))).unwrap();
+
+        assert_eq!(graded(&lut, [0, 51, 255]), [255, 204, 0]);
+    }
+
+    #[test]
+    fn amount_mixes_with_the_original() {
+        let mut lut = Lut::parse("Invert".to_string(), cube(|rgb| rgb.map(|c| 1. - c))).unwrap();
+        lut.amount = 0.5;
+
+        assert_eq!(graded(&lut, [0, 0, 0]), [128, 128, 128]);
+    }
+
+    #[test]
+    fn domain() {
+        let cube = format!(
+            "DOMAIN_MIN 0 0 0\nDOMAIN_MAX 0.5 0.5 0.5\n{}",
+            cube(|rgb| rgb)
+        );
+        let lut = Lut::parse("Domain".to_string(), cube).unwrap();
+
+        assert_eq!(graded(&lut, [64, 128, 255]), [128, 255, 255]);
+    }
+
+    #[test]
+    fn input_range() {
+        let cube = format!("LUT_3D_INPUT_RANGE 0 0.5\n{}", cube(|rgb| rgb));
+        let lut = Lut::parse("Range".to_string(), cube).unwrap();
+
+        assert_eq!(graded(&lut, [64, 128, 255]), [128, 255, 255]);
+    }
+
+    #[test]
+    fn malformed_files() {
+        let parse = |cube: &str| Lut::parse(String::new(), cube.to_string()).unwrap_err();
+
+        assert_eq!(parse("0 0 0\n"), "the file has no LUT_3D_SIZE");
+        assert_eq!(
+            parse("LUT_3D_SIZE 1\n"),
+            "the size of the cube is missing or out of range"
+        );
+        assert_eq!(parse("LUT_1D_SIZE 4\n"), "1D LUTs aren't supported");
+        assert_eq!(
+            parse("LUT_3D_SIZE 2\n0 0 0\n"),
+            "expected 8 colors for a cube of size 2, found 1"
+        );
+        assert_eq!(parse("LUT_3D_SIZE 2\n0 0\n"), "expected 3 numbers, found 2");
+        assert_eq!(parse("LUT_3D_SIZE 2\n0 0 -\n"), "- is not a number");
+    }
+
+    #[test]
+    fn saves_the_file_it_was_read_from() {
+        let mut lut = Lut::parse("Invert".to_string(), cube(|rgb| rgb.map(|c| 1. - c))).unwrap();
+        lut.amount = 0.25;
+
+        let saved = ron::to_string(&lut).unwrap();
+        let opened: Lut = ron::from_str(&saved).unwrap();
+
+        assert_eq!(opened, lut);
+        assert_eq!(graded(&opened, [0, 0, 0]), graded(&lut, [0, 0, 0]));
+    }
+}
@@ -1,47 +1,229 @@
 use std::{
-    fmt::Display,
     io::{self},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
 
+mod animation;
+mod assets;
+mod audio;
+mod camera;
+mod canvas;
+mod capture;
+mod chroma;
+mod color_profile;
+mod commands;
+mod directories;
+mod distortion;
+mod expression;
+mod focus;
+mod fonts;
+mod glitch;
+mod graph_editor;
+mod i18n;
+mod keyframes;
+mod logging;
+mod lottie;
+mod lottie_export;
+mod lut;
+mod markers;
+mod meter;
+mod offscreen;
+mod palette;
+mod playback;
+mod post_fx;
+mod procedural;
+mod project;
+mod psd;
+mod scenes;
+mod stats;
+mod templates;
+mod thumbnail;
+mod toast;
+mod typesetting;
+mod waveform;
+
+use animation::{Animation, Easing, Playback, Preset, Timing, Transition, TransitionKind};
+use assets::{format_size, Asset, AssetId, AssetKind, Assets, Contents, LoadProgress};
+use audio::{Analysis, Channel, Level, Mix, Placement, PlaybackRate, Source, Transport, Trim};
+use camera::CameraDevice;
+use canvas::{
+    Appearance, Arrangement, CanvasState, ChromaKey, CompareView, Crop, Flip, ImageFrame,
+    ImageOptions, Kaleidoscope, Layer, LayerKind, Orientation, Playhead, Preview, RenderCache,
+    SafeArea, Sizing, TintMode, Unit, Units, Visibility,
+};
+use capture::{CaptureSource, Region};
+use commands::{Command, CommandPalette};
+use directories::DialogKind;
+use distortion::{Distortion, DistortionKind};
+use expression::{Expression, Variables};
+use focus::{focus_ring, Arrow, Panel};
+use fonts::{EmbeddedFont, FontFamily, FontWeight, TextFont};
+use glitch::{Glitch, GlitchTrigger};
+use graph_editor::GraphEditor;
+use i18n::{t, t_args, Language};
 use iced::{
-    color, mouse,
+    color, keyboard, mouse,
     widget::{
-        button, canvas, column, container, horizontal_rule, horizontal_space, image::Handle,
-        responsive, row, rule, svg, text, text_input, tooltip, vertical_rule, Rule,
+        button, canvas::Cache, center, checkbox, column, container, horizontal_rule,
+        horizontal_space, mouse_area, opaque, pick_list, progress_bar, responsive, row, rule,
+        scrollable, slider, stack, svg, text, text_editor, text_input, tooltip, vertical_rule,
+        vertical_space, Canvas, Rule,
     },
-    window::frames,
-    Alignment, Color, Element, Font,
+    window, Alignment, Color, Element, Font,
     Length::{self},
-    Padding, Pixels, Point, Rectangle, Renderer, Settings, Subscription, Task, Theme,
+    Padding, Pixels, Settings, Size, Subscription, Task, Theme,
+};
+use iced_aw::ColorPicker;
+use keyframes::{Bezier, Keyframe, Keyframes, Property};
+use lottie::Lottie;
+use lut::Lut;
+use markers::Marker;
+use meter::LevelMeter;
+use palette::Swatch;
+use playback::{Output, OutputDevice, Voice};
+use post_fx::{Letterbox, PostFx};
+use procedural::{
+    Counting, Grain, Procedural, Progress, Reading, Readout, Starfield, Text, Timer, TimerFormat,
+    Vignette, Wave, WaveStyle,
+};
+use project::{
+    AssetRef, FrameRate, LayerSource, Link, LoadedAssets, Project, ProjectAudioTrack,
+    ProjectExpression, ProjectLayer, Snapshot, SwatchUse,
 };
-use iced_aw::{style::Status, SelectionList};
-use image::GenericImageView;
+use scenes::Scene;
+use serde::{Deserialize, Serialize};
+use stats::Stats;
+use templates::Template;
+use thumbnail::ThumbnailSize;
+use toast::{ToastKind, Toasts};
+use typesetting::{TextAlignment, TextAnimation, TextPreset, TextStyle, TextUnit};
+use waveform::Waveform;
+
+/// Includes video containers, whose soundtrack is used.
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "flac", "m4a", "mp4", "m4v", "mov", "mkv"];
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpeg", "jpg", "webp", "gif", "tif", "tiff", "bmp"];
+const LOTTIE_EXTENSIONS: &[&str] = &["json"];
+const LUT_EXTENSIONS: &[&str] = &["cube"];
+/// Images from phones and cameras that can't be decoded: AVIF needs the
+/// native dav1d library, and HEIC's codec is patent-encumbered. Opening one
+/// explains that rather than calling it an unknown file.
+const UNSUPPORTED_IMAGE_EXTENSIONS: &[&str] = &["avif", "heic", "heif"];
 
 pub fn main() -> iced::Result {
-    iced::application("roygbiv", Roygbiv::update, Roygbiv::view)
-        .theme(|_| Theme::CatppuccinMocha)
+    // Held so that the log file keeps being written until the app exits.
+    let _log = logging::init().unwrap_or_else(|error| {
+        tracing::warn!("{}", error);
+        None
+    });
+    let preferences = i18n::load_preferences();
+    i18n::set_language(preferences.language.unwrap_or_else(Language::detect));
+    directories::set(preferences.directories);
+
+    iced::daemon(Roygbiv::title, Roygbiv::update, Roygbiv::view)
+        .theme(|_, _| Theme::CatppuccinMocha)
         .settings(Settings {
             default_text_size: Pixels(14.0),
             ..Default::default()
         })
         .subscription(Roygbiv::subscription)
         .run_with(|| {
-            (
-                Roygbiv {
-                    canvas_state: CanvasState::default(),
-                    canvas_width: 1280.,
-                    canvas_height: 720.,
+            let launch_files: Vec<PathBuf> =
+                std::env::args_os().skip(1).map(PathBuf::from).collect();
+            let (main_window, open_main_window) = window::open(window::Settings::default());
 
-                    audio_file_path: None,
-                    audio_file_contents: vec![],
-                    is_loading_file: false,
+            let roygbiv = Roygbiv {
+                canvas_state: CanvasState::default(),
+                canvas_width: 1280.,
+                canvas_height: 720.,
+                frame_rate: FrameRate::default(),
+                time_format: TimeFormat::Seconds,
 
-                    layer_names: vec![],
-                    selected_layer_index: 0,
-                },
-                Task::none(),
+                assets: Assets::default(),
+                renders: RenderCache::default(),
+                audio_asset: None,
+                analysis: None,
+                audio_trim: Trim::NONE,
+                audio_tracks: vec![],
+                waveform_cache: Cache::new(),
+                file_load: None,
+                image_arrangement: Arrangement::default(),
+                dropped_images: vec![],
+                capture_windows: vec![],
+                cameras: vec![],
+                is_capturing: false,
+                capture_error: None,
+                analysis_progress: None,
+                file_loads_started: 0,
+                transport: Transport::new(),
+                output: Output::new(),
+                output_devices: vec![OutputDevice::Default],
+                is_recording: false,
+
+                layer_names: vec![],
+                layer_list_viewport: (0., 2000.),
+                layer_query: String::new(),
+                layer_category: LayerCategory::All,
+                selected_layer_index: 0,
+
+                project_path: None,
+                relink: None,
+                markers: vec![],
+                scenes: vec![],
+                audiogram_wizard: None,
+
+                sidebar_tab: SidebarTab::Layers,
+                collapsed_sections: vec![],
+                color_picker: None,
+                graph_property: None,
+                copied_motion: None,
+                copied_style: None,
+                links: vec![],
+                expressions: vec![],
+                fonts: vec![],
+                font_families: fonts::system_families(),
+                font_query: String::new(),
+                swatches: vec![],
+                swatch_uses: vec![],
+                swatch_menu: None,
+                snapshots: vec![],
+                snapshot_name: String::new(),
+                text_editor: text_editor::Content::new(),
+                hovered_field: None,
+                modifiers: keyboard::Modifiers::default(),
+                show_start_screen: launch_files.is_empty(),
+                show_stats: false,
+                show_post_fx: false,
+                fits_to_orientation: true,
+                stats: Stats::default(),
+                memory_budget: 1024,
+                toasts: Toasts::default(),
+                command_palette: None,
+                focused_panel: None,
+                transport_focus: 0,
+                main_window,
+                preview_window: None,
+                thumbnail_size: ThumbnailSize::default(),
+                is_preview_fullscreen: false,
+                edits: 0,
+                saved_edits: 0,
+                is_canvas_stale: true,
+                main_window_size: window::Settings::default().size,
+                background_tasks: BackgroundTasks::default(),
+            };
+
+            (
+                roygbiv,
+                Task::batch(
+                    std::iter::once(open_main_window.discard())
+                        .chain(
+                            launch_files
+                                .into_iter()
+                                .map(|path| Task::done(Message::FileDropped(path))),
+                        )
+                        .chain([Task::done(Message::ListOutputDevices)]),
+                ),
             )
         })
 }
@@ -51,62 +233,1139 @@ struct Roygbiv {
     canvas_state: CanvasState,
     canvas_width: f32,
     canvas_height: f32,
+    frame_rate: FrameRate,
+    time_format: TimeFormat,
 
-    audio_file_path: Option<PathBuf>,
-    audio_file_contents: Vec<u8>,
-    is_loading_file: bool,
+    assets: Assets,
+    renders: RenderCache,
+    audio_asset: Option<AssetId>,
+    analysis: Option<Arc<Analysis>>,
+    audio_trim: Trim,
+    audio_tracks: Vec<AudioTrack>,
+    waveform_cache: Cache,
+    /// The audio file being picked and read, if any.
+    file_load: Option<FileLoad>,
+    /// How several images added at once are laid out.
+    image_arrangement: Arrangement,
+    /// Images dropped on the window that are about to be added together.
+    dropped_images: Vec<PathBuf>,
+    /// Titles of the windows screen capture layers can show, as last listed.
+    capture_windows: Vec<String>,
+    /// The cameras capture layers can show, as last listed.
+    cameras: Vec<CameraDevice>,
+    /// Whether frames are being grabbed for the screen capture layers, so
+    /// that slow grabs are skipped rather than piling up.
+    is_capturing: bool,
+    /// The last reason grabbing a frame failed, told once rather than every
+    /// frame.
+    capture_error: Option<String>,
+    /// How far the analysis of the main audio track has got, while it runs.
+    analysis_progress: Option<Arc<LoadProgress>>,
+    /// How many file loads were started, to tell them apart.
+    file_loads_started: u64,
+    transport: Transport,
+    /// Plays the audio track along with the transport.
+    output: Output,
+    output_devices: Vec<OutputDevice>,
+    /// Whether changing an animatable property sets a keyframe at the
+    /// playhead even if the property isn't animated yet.
+    is_recording: bool,
 
     layer_names: Vec<String>,
     selected_layer_index: usize,
+    /// Scroll offset and height of the layer list, to lay out only the rows
+    /// in view.
+    layer_list_viewport: (f32, f32),
+    /// Only layers whose name contains this, ignoring case, are listed.
+    layer_query: String,
+    layer_category: LayerCategory,
+
+    project_path: Option<PathBuf>,
+    relink: Option<Relink>,
+    /// Sorted by time.
+    markers: Vec<Marker>,
+    /// Sorted by start.
+    scenes: Vec<Scene>,
+    audiogram_wizard: Option<AudiogramWizard>,
+
+    sidebar_tab: SidebarTab,
+    collapsed_sections: Vec<SettingsSection>,
+    color_picker: Option<ColorField>,
+    /// Property of the selected layer shown in the graph editor.
+    graph_property: Option<Property>,
+    copied_motion: Option<CopiedMotion>,
+    copied_style: Option<LayerStyle>,
+    links: Vec<Link>,
+    expressions: Vec<LayerExpression>,
+    /// Fonts imported into the project.
+    fonts: Vec<EmbeddedFont>,
+    /// Families that text layers can be set in, those imported into the
+    /// project first.
+    font_families: Vec<FontFamily>,
+    font_query: String,
+    /// Colors kept with the project, e.g. those picked out of its artwork.
+    swatches: Vec<Swatch>,
+    swatch_uses: Vec<SwatchUse>,
+    /// Color field whose list of swatches is open.
+    swatch_menu: Option<ColorField>,
+    snapshots: Vec<Snapshot>,
+    /// Name typed for the next snapshot.
+    snapshot_name: String,
+    /// Lines of the selected text layer, as they are being edited.
+    text_editor: text_editor::Content,
+    /// Field of the selected layer under the cursor, which the arrow keys
+    /// nudge.
+    hovered_field: Option<NumericField>,
+    modifiers: keyboard::Modifiers,
+    show_start_screen: bool,
+    show_stats: bool,
+    show_post_fx: bool,
+    /// Whether turning the canvas moves and resizes every layer to fit.
+    fits_to_orientation: bool,
+    stats: Stats,
+    /// Megabytes that previews and rendered images may take up before the
+    /// least needed ones are dropped or downscaled.
+    memory_budget: u32,
+    toasts: Toasts,
+    command_palette: Option<CommandPalette>,
+    /// Panel the keyboard moves around in, outlined so it can be seen.
+    focused_panel: Option<Panel>,
+    /// Index of the transport button Enter presses when the transport has
+    /// the focus.
+    transport_focus: usize,
+    main_window: window::Id,
+    /// The window showing only the canvas, e.g. fullscreen on a second
+    /// display, while editing goes on in the main one.
+    preview_window: Option<window::Id>,
+    is_preview_fullscreen: bool,
+    thumbnail_size: ThumbnailSize,
+    /// Counts the messages that changed the project, to tell whether it has
+    /// changed since it was last opened or saved, at `saved_edits`.
+    edits: u64,
+    saved_edits: u64,
+    /// Whether a message since the canvas was last caught up changed what
+    /// it shows.
+    is_canvas_stale: bool,
+    main_window_size: Size,
+    background_tasks: BackgroundTasks,
+}
+
+/// Work running off the main thread, counted for the status bar.
+#[derive(Debug, Default)]
+struct BackgroundTasks {
+    analyses: usize,
+    decodes: usize,
+}
+
+/// A file being picked and read in the background. Removing or replacing the
+/// file it is for aborts it, and should it have finished already, its result
+/// is told apart from the current load's by `id` and ignored.
+struct FileLoad {
+    id: u64,
+    handle: iced::task::Handle,
+    progress: Arc<LoadProgress>,
+}
+
+/// Bytes held in memory for the project, by what they are for.
+#[derive(Debug, Clone, Copy)]
+struct MemoryUsage {
+    files: usize,
+    previews: usize,
+    renders: usize,
+    analysis: usize,
+}
+
+impl MemoryUsage {
+    fn total(self) -> usize {
+        self.files + self.previews + self.renders + self.analysis
+    }
+}
+
+/// An audio file played over the main track, e.g. a voiceover.
+#[derive(Debug)]
+struct AudioTrack {
+    asset: AssetId,
+    mix: Mix,
+    analysis: Option<Arc<Analysis>>,
+}
+
+/// Motion copied from a layer, waiting to be pasted at the playhead.
+#[derive(Debug, Clone)]
+enum CopiedMotion {
+    /// The keyframes of a single property, which can be pasted onto any
+    /// property.
+    Keyframes(Vec<Keyframe>),
+    /// Every keyframe and the animation preset of a layer.
+    Layer(Keyframes, Animation),
+}
+
+/// How a layer looks and reacts to the audio, copied from one layer to be
+/// pasted onto others without changing what they show.
+#[derive(Debug, Clone, Copy)]
+struct LayerStyle {
+    appearance: Appearance,
+    opacity: f32,
+    audio_channel: Channel,
+    audio_source: Source,
+}
+
+/// A property of a layer worked out from an expression on every frame, in
+/// place of its value and keyframes.
+#[derive(Debug, Clone)]
+struct LayerExpression {
+    layer: usize,
+    property: Property,
+    expression: Expression,
+}
+
+/// A number in the selected layer's settings that can be nudged with the
+/// mouse wheel or the arrow keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumericField {
+    Property(Property),
+    Width,
+    Height,
+}
+
+impl NumericField {
+    /// How much one notch of the wheel or press of a key changes the field,
+    /// before modifiers.
+    fn step(self) -> f32 {
+        match self {
+            NumericField::Property(Property::Scale | Property::Opacity) => 0.01,
+            NumericField::Property(Property::X | Property::Y)
+            | NumericField::Width
+            | NumericField::Height => 1.,
+        }
+    }
+}
+
+/// A layer offered in a pick list, e.g. to link a property to.
+#[derive(Debug, Clone, PartialEq)]
+struct LayerChoice {
+    index: usize,
+    name: String,
+}
+
+impl std::fmt::Display for LayerChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The kinds of layer the layer list can be narrowed down to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayerCategory {
+    All,
+    /// Image layers, screen captures, and placeholders for images yet to
+    /// be picked.
+    Images,
+    Text,
+    /// Procedural overlays other than text, like the starfield.
+    Overlays,
+}
+
+impl LayerCategory {
+    const ALL: [LayerCategory; 4] = [
+        LayerCategory::All,
+        LayerCategory::Images,
+        LayerCategory::Text,
+        LayerCategory::Overlays,
+    ];
+
+    fn includes(self, kind: &LayerKind) -> bool {
+        match (self, kind) {
+            (LayerCategory::All, _) => true,
+            (
+                LayerCategory::Images,
+                LayerKind::Image { .. } | LayerKind::Placeholder | LayerKind::Capture { .. },
+            ) => true,
+            (LayerCategory::Text, LayerKind::Procedural { procedural, .. }) => procedural.is_text(),
+            (LayerCategory::Overlays, LayerKind::Procedural { procedural, .. }) => {
+                !procedural.is_text()
+            }
+            (LayerCategory::Overlays, LayerKind::Lottie { .. } | LayerKind::Backdrop) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for LayerCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LayerCategory::All => t("layer-category-all"),
+            LayerCategory::Images => t("layer-category-images"),
+            LayerCategory::Text => t("layer-category-text"),
+            LayerCategory::Overlays => t("layer-category-overlays"),
+        })
+    }
+}
+
+/// A color property that can be edited with the color picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ColorField {
+    Border(usize),
+    Tint(usize),
+    TintShadow(usize),
+    ChromaKey(usize),
+    Vignette(usize),
+    Readout(usize),
+    Text(usize),
+    TextOutline(usize),
+    TextBackground(usize),
+    Timer(usize),
+    Wave(usize),
+    Progress(usize),
+    ProgressTrack(usize),
+    /// The color of a project swatch, rather than of a layer.
+    Swatch(usize),
+}
+
+impl ColorField {
+    /// The layer the color belongs to, unless it is a swatch's.
+    fn layer(self) -> Option<usize> {
+        match self {
+            ColorField::Border(index)
+            | ColorField::Tint(index)
+            | ColorField::TintShadow(index)
+            | ColorField::ChromaKey(index)
+            | ColorField::Vignette(index)
+            | ColorField::Readout(index)
+            | ColorField::Text(index)
+            | ColorField::TextOutline(index)
+            | ColorField::TextBackground(index)
+            | ColorField::Timer(index)
+            | ColorField::Wave(index)
+            | ColorField::Progress(index)
+            | ColorField::ProgressTrack(index) => Some(index),
+            ColorField::Swatch(_) => None,
+        }
+    }
+
+    /// The same color of layer `layer` instead.
+    fn with_layer(self, layer: usize) -> ColorField {
+        match self {
+            ColorField::Border(_) => ColorField::Border(layer),
+            ColorField::Tint(_) => ColorField::Tint(layer),
+            ColorField::TintShadow(_) => ColorField::TintShadow(layer),
+            ColorField::ChromaKey(_) => ColorField::ChromaKey(layer),
+            ColorField::Vignette(_) => ColorField::Vignette(layer),
+            ColorField::Readout(_) => ColorField::Readout(layer),
+            ColorField::Text(_) => ColorField::Text(layer),
+            ColorField::TextOutline(_) => ColorField::TextOutline(layer),
+            ColorField::TextBackground(_) => ColorField::TextBackground(layer),
+            ColorField::Timer(_) => ColorField::Timer(layer),
+            ColorField::Wave(_) => ColorField::Wave(layer),
+            ColorField::Progress(_) => ColorField::Progress(layer),
+            ColorField::ProgressTrack(_) => ColorField::ProgressTrack(layer),
+            ColorField::Swatch(_) => self,
+        }
+    }
+}
+
+/// How positions in the track are shown next to the transport controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeFormat {
+    /// `m:ss.cc`
+    Seconds,
+    Frames,
+    /// SMPTE timecode, `hh:mm:ss:ff`.
+    Timecode,
+}
+
+impl TimeFormat {
+    fn next(self) -> TimeFormat {
+        match self {
+            TimeFormat::Seconds => TimeFormat::Frames,
+            TimeFormat::Frames => TimeFormat::Timecode,
+            TimeFormat::Timecode => TimeFormat::Seconds,
+        }
+    }
+
+    fn format(self, time: Duration, frame_rate: FrameRate) -> String {
+        match self {
+            TimeFormat::Seconds => format_time(time),
+            TimeFormat::Frames => frame_rate.frame(time).to_string(),
+            TimeFormat::Timecode => {
+                let frame = frame_rate.frame(time);
+                let per_second = frame_rate.per_second() as u64;
+                let seconds = frame / per_second;
+
+                format!(
+                    "{:02}:{:02}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds / 60 % 60,
+                    seconds % 60,
+                    frame % per_second
+                )
+            }
+        }
+    }
+}
+
+/// A part of the selected layer's settings, which can be collapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsSection {
+    Transform,
+    Appearance,
+    Effects,
+    AudioReactivity,
+    Timing,
+}
+
+impl std::fmt::Display for SettingsSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SettingsSection::Transform => t("section-transform"),
+            SettingsSection::Appearance => t("section-appearance"),
+            SettingsSection::Effects => t("section-effects"),
+            SettingsSection::AudioReactivity => t("section-audio-reactivity"),
+            SettingsSection::Timing => t("section-timing"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SidebarTab {
+    Layers,
+    Assets,
+    Swatches,
+    Snapshots,
+}
+
+/// A project that was opened with references to files that no longer exist,
+/// waiting for the user to locate or skip them.
+struct Relink {
+    project_path: PathBuf,
+    project: Project,
+    missing: Vec<AssetRef>,
+}
+
+/// The choices made so far in the audiogram wizard, which makes a project
+/// out of a podcast episode.
+#[derive(Debug, Default)]
+struct AudiogramWizard {
+    audio: Option<(PathBuf, Contents)>,
+    cover: Option<(PathBuf, Arc<Vec<u8>>)>,
+    title: String,
+    orientation: Option<Orientation>,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
+    #[allow(dead_code)]
     SetCanvasSize(f32, f32),
+    SetOrientation(Orientation),
+    ToggleFitToOrientation,
+    FrameRateSelected(FrameRate),
+    LanguageSelected(Language),
+    PreferencesSaved(Result<(), Error>),
+    ToggleStats,
+    TogglePostFx,
+    PostFxChanged(PostFx),
+    LoadPostFxLut,
+    PostFxLutFileOpened(Result<LoadedFile, Error>),
+    SafeAreaSelected(SafeArea),
+    /// Pins the layers as they are now as "A", to compare later edits with.
+    PinComparison,
+    StopComparing,
+    CompareViewSelected(CompareView),
+    /// Shows the other of A and B.
+    ToggleCompareView,
+    CompareSplitChanged(f32),
+    MemoryBudgetChanged(u32),
+    ThumbnailSizeSelected(ThumbnailSize),
+    /// Renders the frame at the playhead with the layers meant for
+    /// thumbnails, and asks where to save it.
+    RenderThumbnail,
+    ThumbnailSaved(Result<PathBuf, Error>),
+    ExportLottie,
+    LottieExported(Result<lottie_export::Export, Error>),
 
     OpenAudioFile,
     RemoveAudioFile,
-    AudioFileOpened(Result<(PathBuf, Arc<Vec<u8>>), Error>),
+    /// Stops reading the audio file being opened.
+    CancelFileLoad,
+    /// Stops analyzing the main audio track, leaving it without a waveform
+    /// or audio-reactive motion.
+    CancelAnalysis,
+    /// Redraws the progress of long loads.
+    LoadProgressed,
+    /// The audio file of the file load with the given ID was read.
+    AudioFileOpened(u64, Result<(PathBuf, Contents), Error>),
+    AddAudioTrack,
+    AudioTrackOpened(u64, Result<(PathBuf, Contents), Error>),
+    AudioTrackMixChanged(usize, Mix),
+    RemoveAudioTrack(usize),
+    /// The analysis of an audio asset, with how long it took.
+    AudioAnalyzed(AssetId, Result<Arc<Analysis>, Error>, Duration),
 
     AddImageLayer,
+    AddProceduralLayer(Procedural),
+    PasteImage,
     RemoveLayer(usize),
     ImageFileOpened(Result<(PathBuf, Arc<Vec<u8>>), Error>),
+    /// Images picked or dropped together were read, or failed to be.
+    ImageFilesOpened(Result<Vec<Result<LoadedFile, Error>>, Error>),
+    ImageArrangementSelected(Arrangement),
+    ImportPsd,
+    AddLottieLayer,
+    LottieFileOpened(Result<LoadedFile, Error>),
+    AddCaptureLayer,
+    /// Adds a layer blurring everything beneath it, over the whole canvas.
+    AddBackdropLayer,
+    /// The size of the screen, which a new screen capture layer shows all
+    /// of.
+    CaptureScreenFound(Result<Region, String>),
+    AddCameraLayer,
+    /// The cameras, the first of which a new camera layer shows.
+    CamerasFound(Result<Vec<CameraDevice>, String>),
+    /// Lists the windows and cameras capture layers can show again.
+    RefreshCaptureWindows,
+    CaptureWindowsListed(Result<Vec<String>, String>),
+    CamerasListed(Result<Vec<CameraDevice>, String>),
+    LayerCaptureSourceSelected(usize, CaptureSource),
+    /// Grabs a frame for every capture layer.
+    CaptureTick,
+    CaptureGrabbed(Vec<capture::Grab>),
+    PsdImported(Result<psd::PsdDocument, Error>),
+    /// Adds the images dropped on the window since the first of them.
+    ImportDroppedImages,
+    /// The frames of the image asset, rendered in the background with the
+    /// given options and frame size.
+    ImageDecoded(
+        AssetId,
+        ImageOptions,
+        Size,
+        Option<(Vec<ImageFrame>, Size, Option<Arc<image::RgbaImage>>)>,
+    ),
     LayerSelected(usize, String),
+    LayerListScrolled(scrollable::Viewport),
+    LayerQueryChanged(String),
+    LayerCategorySelected(LayerCategory),
     SelectLastLayer,
     Tick,
+    TogglePlayback,
+    PlaybackRateSelected(PlaybackRate),
+    PitchPreservedToggled(bool),
+    /// Pauses and moves the playhead by a number of frames.
+    StepFrames(i64),
+    CycleTimeFormat,
+    ToggleRecording,
+    Seek(f32),
+    ListOutputDevices,
+    OutputDevicesListed(Vec<OutputDevice>),
+    OutputDeviceSelected(OutputDevice),
+    VolumeChanged(f32),
+    ToggleMute,
+    AddMarker,
+    RemoveMarker(usize),
+    MarkerNameChanged(usize, String),
+    ImportMarkers,
+    ImportAudioChapters,
+    MarkersImported(Result<Vec<Marker>, Error>),
+    AddScene,
+    RemoveScene(usize),
+    SceneNameChanged(usize, String),
+    SceneTransitionChanged(usize, Transition),
+    /// Adds a scene starting at each marker that doesn't have one yet.
+    ScenesFromMarkers,
+    AudioTrimChanged(Trim),
+
+    OpenProject,
+    ProjectOpened(Result<(PathBuf, Project), Error>),
+    /// The project's assets whose files are gone, which are relinked before
+    /// it is loaded.
+    MissingAssetsFound(PathBuf, Project, Vec<AssetRef>),
+    SaveProject,
+    /// The project was written as it was after the given number of edits,
+    /// or failed to be.
+    ProjectSaved(u64, Result<PathBuf, Error>),
+    ProjectAssetsLoaded(PathBuf, Result<LoadedAssets, Error>),
+    SnapshotNameChanged(String),
+    TakeSnapshot,
+    RenameSnapshot(usize, String),
+    RemoveSnapshot(usize),
+    RestoreSnapshot(usize),
+    /// The files of the snapshot with the given name were read, to replace
+    /// the project with it.
+    SnapshotAssetsLoaded(String, Result<LoadedAssets, Error>),
+    RelinkBrowse(AssetRef),
+    RelinkPicked(AssetRef, Option<PathBuf>),
+    RelinkSkipMissing,
+    RelinkCancel,
+
+    SidebarTabSelected(SidebarTab),
+    ToggleSettingsSection(SettingsSection),
+    ReplaceAsset(AssetId),
+    AssetReplaced(AssetId, Result<(PathBuf, Contents), Error>),
+    RemoveAsset(AssetId),
+    ExtractPalette(AssetId),
+    PaletteExtracted(Option<Vec<Color>>),
+    RemoveSwatch(usize),
+    /// Adds a swatch, of the color of the field if there is one, which then
+    /// follows it.
+    AddSwatch(Option<ColorField>),
+    SwatchNameChanged(usize, String),
+    UseSwatch(ColorField, usize),
+    ToggleSwatchMenu(ColorField),
+
+    NewProject,
+    CloseStartScreen,
+    TemplateSelected(Template),
+    OpenAudiogramWizard,
+    CloseAudiogramWizard,
+    PickAudiogramAudio,
+    AudiogramAudioPicked(Result<(PathBuf, Contents), Error>),
+    PickAudiogramCover,
+    AudiogramCoverPicked(Result<(PathBuf, Arc<Vec<u8>>), Error>),
+    AudiogramTitleChanged(String),
+    AudiogramOrientationSelected(Orientation),
+    CreateAudiogram,
+    ReplaceLayerImage(usize),
+    LayerImageOpened(usize, Result<(PathBuf, Arc<Vec<u8>>), Error>),
+    LayerSizingSelected(usize, Sizing),
+    ToggleCropping(usize),
+    LayerCropChanged(usize, Crop),
+    ResetLayerCrop(usize),
+    LayerFlipped(usize, Flip),
+    LayerCornerRadiusChanged(usize, f32),
+    LayerBorderWidthChanged(usize, f32),
+    LayerBorderFollowsPitchToggled(usize, bool),
+    LayerTintModeSelected(usize, TintMode),
+    LayerTintAmountChanged(usize, f32),
+    LayerChromaKeyChanged(usize, ChromaKey),
+    LayerDistortionChanged(usize, Distortion),
+    LayerGlitchChanged(usize, Glitch),
+    LayerKaleidoscopeChanged(usize, Kaleidoscope),
+    LayerBlurChanged(usize, f32),
+    LayerDarkenChanged(usize, f32),
+    LoadLayerLut(usize),
+    LayerLutFileOpened(usize, Result<LoadedFile, Error>),
+    LayerLutAmountChanged(usize, f32),
+    RemoveLayerLut(usize),
+    /// Puts a blurred, darkened copy of the image layer filling the canvas
+    /// beneath it.
+    AddBlurredBackdrop(usize),
+    LayerProceduralChanged(usize, Procedural),
+    LayerSeedChanged(usize, u64),
+    LayerFontChanged(usize, TextFont),
+    LayerTextEdited(usize, text_editor::Action),
+    LayerTextStyleChanged(usize, TextStyle),
+    LayerTextAnimationChanged(usize, TextAnimation),
+    FontQueryChanged(String),
+    ImportFont,
+    FontFileOpened(Result<(PathBuf, Arc<Vec<u8>>), Error>),
+    FontLoaded(Result<(), iced::font::Error>),
+    LayerAnimationChanged(usize, Animation),
+    LayerTimingChanged(usize, Timing),
+    LayerVisibilityChanged(usize, Visibility),
+    LayerPlaybackChanged(usize, Playback),
+    LayerAudioChannelSelected(usize, Channel),
+    LayerAudioSourceSelected(usize, Source),
+    LayerPropertyInput(usize, Property, String),
+    LayerWidthInput(usize, String),
+    LayerHeightInput(usize, String),
+    ToggleAspectLock(usize),
+    /// Sets a property back to [`Property::default_value`], at the playhead if
+    /// it is animated.
+    /// The field the cursor entered, or left if `false`.
+    HoverField(NumericField, bool),
+    /// Changes a field of the selected layer by this many steps.
+    NudgeField(NumericField, f32),
+    ModifiersChanged(keyboard::Modifiers),
+    ResetProperty(usize, Property),
+    /// Resets the position, scale and opacity of a layer.
+    ResetTransform(usize),
+    ToggleAnimated(usize, Property),
+    ShowGraph(Option<Property>),
+    KeyframeMoved(usize, Property, usize, f32, f32),
+    KeyframeEased(usize, Property, usize, Bezier),
+    KeyframeRemoved(usize, Property, usize),
+    CopyKeyframes(usize, Property),
+    /// Pastes copied keyframes onto a property, backwards if set.
+    PasteKeyframes(usize, Property, bool),
+    CopyMotion(usize),
+    PasteMotion(usize, bool),
+    /// Makes a property of the first layer follow that of the second.
+    LinkProperty(usize, Property, usize),
+    UnlinkProperty(usize, Property),
+    CopyStyle(usize),
+    PasteStyle(usize),
+
+    OpenColorPicker(ColorField),
+    CloseColorPicker,
+    ColorPicked(ColorField, Color),
+    FitLayerToCanvas(usize),
+    Notify(ToastKind, String),
+    DismissToast(u64),
+    ExpireToasts,
+    CopyDiagnostics,
+
+    OpenCommandPalette,
+    CloseCommandPalette,
+    CommandQueryChanged(String),
+    /// Moves the highlight in the command palette up or down.
+    MoveCommandSelection(i32),
+    /// Moves the keyboard focus to the panel this many steps on.
+    FocusPanel(i32),
+    /// Moves the focus between text inputs, backwards if `true`.
+    FocusInput(bool),
+    ClearFocus,
+    TogglePreviewWindow,
+    TogglePreviewFullscreen,
+    WindowClosed(window::Id),
+    WindowResized(window::Id, Size),
+    FileDropped(PathBuf),
+    ArrowPressed(Arrow),
+    /// Presses whatever has the keyboard focus.
+    ActivateFocused,
+    SubmitCommand,
+    RunCommand(Box<Message>),
+}
+
+impl Message {
+    /// Whether the message changes what is saved in the project file, which
+    /// leaves it with unsaved changes.
+    ///
+    /// Messages that only start a dialog or a load are not edits, the
+    /// message they finish with is. Messages handing on to another, e.g.
+    /// from a key press or the command palette, leave it to that one.
+    fn is_edit(&self) -> bool {
+        match self {
+            Message::LayerTextEdited(_, action) => action.is_edit(),
+            // Files that fail to load, or dialogs that are closed, change
+            // nothing.
+            Message::PostFxLutFileOpened(result)
+            | Message::LottieFileOpened(result)
+            | Message::LayerLutFileOpened(_, result) => result.is_ok(),
+            Message::AudioFileOpened(_, result)
+            | Message::AudioTrackOpened(_, result)
+            | Message::AssetReplaced(_, result) => result.is_ok(),
+            Message::ImageFileOpened(result)
+            | Message::LayerImageOpened(_, result)
+            | Message::FontFileOpened(result) => result.is_ok(),
+            Message::ImageFilesOpened(result) => result
+                .as_ref()
+                .is_ok_and(|files| files.iter().any(Result::is_ok)),
+            Message::CaptureScreenFound(result) => result.is_ok(),
+            Message::CamerasFound(result) => {
+                result.as_ref().is_ok_and(|cameras| !cameras.is_empty())
+            }
+            Message::PsdImported(result) => {
+                result.as_ref().is_ok_and(|(_, layers)| !layers.is_empty())
+            }
+            Message::MarkersImported(result) => {
+                result.as_ref().is_ok_and(|markers| !markers.is_empty())
+            }
+            Message::SnapshotAssetsLoaded(_, result) => result.is_ok(),
+            Message::PaletteExtracted(colors) => {
+                colors.as_ref().is_some_and(|colors| !colors.is_empty())
+            }
+            Message::SetCanvasSize(..)
+            | Message::SetOrientation(_)
+            | Message::FrameRateSelected(_)
+            | Message::PostFxChanged(_)
+            | Message::RemoveAudioFile
+            | Message::AudioTrackMixChanged(..)
+            | Message::RemoveAudioTrack(_)
+            | Message::AudioTrimChanged(_)
+            | Message::AddProceduralLayer(_)
+            | Message::AddBackdropLayer
+            | Message::RemoveLayer(_)
+            | Message::LayerCaptureSourceSelected(..)
+            | Message::AddMarker
+            | Message::RemoveMarker(_)
+            | Message::MarkerNameChanged(..)
+            | Message::AddScene
+            | Message::RemoveScene(_)
+            | Message::SceneNameChanged(..)
+            | Message::SceneTransitionChanged(..)
+            | Message::ScenesFromMarkers
+            | Message::TakeSnapshot
+            | Message::RenameSnapshot(..)
+            | Message::RemoveSnapshot(_)
+            | Message::RemoveAsset(_)
+            | Message::RemoveSwatch(_)
+            | Message::AddSwatch(_)
+            | Message::SwatchNameChanged(..)
+            | Message::UseSwatch(..)
+            | Message::CreateAudiogram
+            | Message::LayerSizingSelected(..)
+            | Message::LayerCropChanged(..)
+            | Message::ResetLayerCrop(_)
+            | Message::LayerFlipped(..)
+            | Message::LayerCornerRadiusChanged(..)
+            | Message::LayerBorderWidthChanged(..)
+            | Message::LayerBorderFollowsPitchToggled(..)
+            | Message::LayerTintModeSelected(..)
+            | Message::LayerTintAmountChanged(..)
+            | Message::LayerChromaKeyChanged(..)
+            | Message::LayerDistortionChanged(..)
+            | Message::LayerGlitchChanged(..)
+            | Message::LayerKaleidoscopeChanged(..)
+            | Message::LayerBlurChanged(..)
+            | Message::LayerDarkenChanged(..)
+            | Message::LayerLutAmountChanged(..)
+            | Message::RemoveLayerLut(_)
+            | Message::AddBlurredBackdrop(_)
+            | Message::LayerProceduralChanged(..)
+            | Message::LayerSeedChanged(..)
+            | Message::LayerFontChanged(..)
+            | Message::LayerTextStyleChanged(..)
+            | Message::LayerTextAnimationChanged(..)
+            | Message::LayerAnimationChanged(..)
+            | Message::LayerTimingChanged(..)
+            | Message::LayerVisibilityChanged(..)
+            | Message::LayerPlaybackChanged(..)
+            | Message::LayerAudioChannelSelected(..)
+            | Message::LayerAudioSourceSelected(..)
+            | Message::LayerPropertyInput(..)
+            | Message::LayerWidthInput(..)
+            | Message::LayerHeightInput(..)
+            | Message::ToggleAspectLock(_)
+            | Message::NudgeField(..)
+            | Message::ResetProperty(..)
+            | Message::ResetTransform(_)
+            | Message::ToggleAnimated(..)
+            | Message::KeyframeMoved(..)
+            | Message::KeyframeEased(..)
+            | Message::KeyframeRemoved(..)
+            | Message::PasteKeyframes(..)
+            | Message::PasteMotion(..)
+            | Message::LinkProperty(..)
+            | Message::UnlinkProperty(..)
+            | Message::PasteStyle(_)
+            | Message::ColorPicked(..)
+            | Message::FitLayerToCanvas(_) => true,
+            Message::ToggleFitToOrientation
+            | Message::LanguageSelected(_)
+            | Message::PreferencesSaved(_)
+            | Message::ToggleStats
+            | Message::TogglePostFx
+            | Message::SafeAreaSelected(_)
+            | Message::PinComparison
+            | Message::StopComparing
+            | Message::CompareViewSelected(_)
+            | Message::ToggleCompareView
+            | Message::CompareSplitChanged(_)
+            | Message::MemoryBudgetChanged(_)
+            | Message::ThumbnailSizeSelected(_)
+            | Message::RenderThumbnail
+            | Message::ThumbnailSaved(_)
+            | Message::ExportLottie
+            | Message::LottieExported(_)
+            | Message::OpenAudioFile
+            | Message::CancelFileLoad
+            | Message::CancelAnalysis
+            | Message::LoadProgressed
+            | Message::AddAudioTrack
+            | Message::AudioAnalyzed(..)
+            | Message::AddImageLayer
+            | Message::PasteImage
+            | Message::ImageArrangementSelected(_)
+            | Message::ImportPsd
+            | Message::AddLottieLayer
+            | Message::AddCaptureLayer
+            | Message::AddCameraLayer
+            | Message::RefreshCaptureWindows
+            | Message::CaptureWindowsListed(_)
+            | Message::CamerasListed(_)
+            | Message::CaptureTick
+            | Message::CaptureGrabbed(_)
+            | Message::ImportDroppedImages
+            | Message::ImageDecoded(..)
+            | Message::LayerSelected(..)
+            | Message::LayerListScrolled(_)
+            | Message::LayerQueryChanged(_)
+            | Message::LayerCategorySelected(_)
+            | Message::SelectLastLayer
+            | Message::Tick
+            | Message::TogglePlayback
+            | Message::PlaybackRateSelected(_)
+            | Message::PitchPreservedToggled(_)
+            | Message::StepFrames(_)
+            | Message::CycleTimeFormat
+            | Message::ToggleRecording
+            | Message::Seek(_)
+            | Message::ListOutputDevices
+            | Message::OutputDevicesListed(_)
+            | Message::OutputDeviceSelected(_)
+            | Message::VolumeChanged(_)
+            | Message::ToggleMute
+            | Message::ImportMarkers
+            | Message::ImportAudioChapters
+            | Message::OpenProject
+            | Message::ProjectOpened(_)
+            | Message::MissingAssetsFound(..)
+            | Message::SaveProject
+            | Message::ProjectSaved(..)
+            | Message::ProjectAssetsLoaded(..)
+            | Message::SnapshotNameChanged(_)
+            | Message::RestoreSnapshot(_)
+            | Message::RelinkBrowse(_)
+            | Message::RelinkPicked(..)
+            | Message::RelinkSkipMissing
+            | Message::RelinkCancel
+            | Message::SidebarTabSelected(_)
+            | Message::ToggleSettingsSection(_)
+            | Message::ReplaceAsset(_)
+            | Message::ExtractPalette(_)
+            | Message::ToggleSwatchMenu(_)
+            | Message::NewProject
+            | Message::CloseStartScreen
+            | Message::TemplateSelected(_)
+            | Message::OpenAudiogramWizard
+            | Message::CloseAudiogramWizard
+            | Message::PickAudiogramAudio
+            | Message::AudiogramAudioPicked(_)
+            | Message::PickAudiogramCover
+            | Message::AudiogramCoverPicked(_)
+            | Message::AudiogramTitleChanged(_)
+            | Message::AudiogramOrientationSelected(_)
+            | Message::ReplaceLayerImage(_)
+            | Message::ToggleCropping(_)
+            | Message::LoadLayerLut(_)
+            | Message::LoadPostFxLut
+            | Message::FontQueryChanged(_)
+            | Message::ImportFont
+            | Message::FontLoaded(_)
+            | Message::HoverField(..)
+            | Message::ModifiersChanged(_)
+            | Message::ShowGraph(_)
+            | Message::CopyKeyframes(..)
+            | Message::CopyMotion(_)
+            | Message::CopyStyle(_)
+            | Message::OpenColorPicker(_)
+            | Message::CloseColorPicker
+            | Message::Notify(..)
+            | Message::DismissToast(_)
+            | Message::ExpireToasts
+            | Message::CopyDiagnostics
+            | Message::OpenCommandPalette
+            | Message::CloseCommandPalette
+            | Message::CommandQueryChanged(_)
+            | Message::MoveCommandSelection(_)
+            | Message::FocusPanel(_)
+            | Message::FocusInput(_)
+            | Message::ClearFocus
+            | Message::TogglePreviewWindow
+            | Message::TogglePreviewFullscreen
+            | Message::WindowClosed(_)
+            | Message::WindowResized(..)
+            | Message::FileDropped(_)
+            | Message::ArrowPressed(_)
+            | Message::ActivateFocused
+            | Message::SubmitCommand
+            | Message::RunCommand(_) => false,
+        }
+    }
+
+    /// Whether the message can change what the canvas shows, so that it has
+    /// to be caught up after it while paused: every edit, and changes of the
+    /// selection, the playhead, the comparison, or of what has been loaded.
+    fn is_drawn(&self) -> bool {
+        self.is_edit()
+            || matches!(
+                self,
+                Message::LayerTextEdited(..)
+                    | Message::LayerSelected(..)
+                    | Message::SelectLastLayer
+                    | Message::ToggleCropping(_)
+                    | Message::TogglePlayback
+                    | Message::Seek(_)
+                    | Message::StepFrames(_)
+                    | Message::AudioAnalyzed(..)
+                    | Message::ProjectAssetsLoaded(..)
+                    | Message::TemplateSelected(_)
+                    | Message::ImageDecoded(..)
+                    | Message::CaptureGrabbed(_)
+                    | Message::FontLoaded(_)
+                    | Message::LanguageSelected(_)
+                    | Message::MemoryBudgetChanged(_)
+                    | Message::PinComparison
+                    | Message::StopComparing
+                    | Message::CompareViewSelected(_)
+                    | Message::ToggleCompareView
+                    | Message::CompareSplitChanged(_)
+            )
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Error {
     DialogClosed,
     IoError(io::ErrorKind),
+    ProjectFormat(String),
+    Clipboard(String),
+    AudioDecode(String),
+    ImageDecode(String),
+    /// The user called off reading a file.
+    Cancelled,
+    /// A file too large to be kept in memory, with its size in bytes.
+    FileTooLarge(u64),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::DialogClosed => f.write_str(t("error-dialog-closed")),
+            Error::IoError(kind) => write!(f, "{}", kind),
+            Error::ProjectFormat(error) => {
+                f.write_str(&t_args("error-project-format", &[("error", error)]))
+            }
+            Error::Clipboard(error) => f.write_str(&t_args("error-clipboard", &[("error", error)])),
+            Error::AudioDecode(error) => {
+                f.write_str(&t_args("error-audio-decode", &[("error", error)]))
+            }
+            Error::ImageDecode(error) => {
+                f.write_str(&t_args("error-image-decode", &[("error", error)]))
+            }
+            Error::Cancelled => f.write_str(t("error-cancelled")),
+            Error::FileTooLarge(size) => f.write_str(&t_args(
+                "error-file-too-large",
+                &[
+                    ("size", &format_size(*size as usize)),
+                    ("limit", &format_size(MAX_LOADED_FILE_SIZE as usize)),
+                ],
+            )),
+        }
+    }
+}
+
+async fn open_font_file() -> Result<(PathBuf, Arc<Vec<u8>>), Error> {
+    let picked_file = directories::dialog(DialogKind::Font)
+        .set_title(t("dialog-import-font"))
+        .add_filter(t("filter-font"), &["ttf", "otf", "ttc"])
+        .pick_file()
+        .await
+        .ok_or(Error::DialogClosed)?;
+    directories::remember(DialogKind::Font, picked_file.path()).await;
+
+    load_file(picked_file).await
+}
+
+async fn open_audio_file(progress: Arc<LoadProgress>) -> Result<(PathBuf, Contents), Error> {
+    let picked_file = directories::dialog(DialogKind::Audio)
+        .set_title(t("dialog-open-audio"))
+        .add_filter(t("filter-audio"), AUDIO_EXTENSIONS)
+        .pick_file()
+        .await
+        .ok_or(Error::DialogClosed)?;
+    directories::remember(DialogKind::Audio, picked_file.path()).await;
+
+    stream_file(picked_file, progress).await
+}
+
+/// Asks for any number of images and reads each of them.
+async fn open_image_files() -> Result<Vec<Result<LoadedFile, Error>>, Error> {
+    let picked_files = directories::dialog(DialogKind::Image)
+        .set_title(t("dialog-add-images"))
+        .add_filter(t("filter-image"), IMAGE_EXTENSIONS)
+        .pick_files()
+        .await
+        .ok_or(Error::DialogClosed)?;
+    if let Some(picked_file) = picked_files.first() {
+        directories::remember(DialogKind::Image, picked_file.path()).await;
+    }
+
+    Ok(load_files(picked_files.into_iter().map(PathBuf::from).collect()).await)
+}
+
+async fn open_lottie_file() -> Result<(PathBuf, Arc<Vec<u8>>), Error> {
+    let picked_file = directories::dialog(DialogKind::Image)
+        .set_title(t("dialog-add-lottie"))
+        .add_filter(t("filter-lottie"), LOTTIE_EXTENSIONS)
+        .pick_file()
+        .await
+        .ok_or(Error::DialogClosed)?;
+    directories::remember(DialogKind::Image, picked_file.path()).await;
+
+    load_file(picked_file).await
 }
 
-async fn open_audio_file() -> Result<(PathBuf, Arc<Vec<u8>>), Error> {
-    let picked_file = rfd::AsyncFileDialog::new()
-        .set_title("Open audio file...")
-        .add_filter("Audio file", &["wav", "mp3", "flac"])
+async fn open_lut_file() -> Result<LoadedFile, Error> {
+    let picked_file = directories::dialog(DialogKind::Image)
+        .set_title(t("dialog-load-lut"))
+        .add_filter(t("filter-lut"), LUT_EXTENSIONS)
         .pick_file()
         .await
         .ok_or(Error::DialogClosed)?;
+    directories::remember(DialogKind::Image, picked_file.path()).await;
 
     load_file(picked_file).await
 }
 
 async fn open_image_file() -> Result<(PathBuf, Arc<Vec<u8>>), Error> {
-    let picked_file = rfd::AsyncFileDialog::new()
-        .set_title("Open image file...")
-        .add_filter("Image file", &["png", "jpeg", "jpg", "webp"])
+    let picked_file = directories::dialog(DialogKind::Image)
+        .set_title(t("dialog-open-image"))
+        .add_filter(t("filter-image"), IMAGE_EXTENSIONS)
         .pick_file()
         .await
         .ok_or(Error::DialogClosed)?;
+    directories::remember(DialogKind::Image, picked_file.path()).await;
 
     load_file(picked_file).await
 }
 
+/// Saves the image on the clipboard as a PNG in the data directory, so the layer
+/// made from it references a file like any other imported image.
+async fn paste_image() -> Result<(PathBuf, Arc<Vec<u8>>), Error> {
+    let contents = tokio::task::spawn_blocking(|| {
+        let image = arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_image())
+            .map_err(|error| Error::Clipboard(error.to_string()))?;
+        let image = image::RgbaImage::from_raw(
+            image.width as u32,
+            image.height as u32,
+            image.bytes.into_owned(),
+        )
+        .ok_or_else(|| Error::Clipboard(t("error-clipboard-image").to_string()))?;
+
+        let mut png = io::Cursor::new(vec![]);
+        image
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|error| Error::Clipboard(error.to_string()))?;
+
+        Ok(png.into_inner())
+    })
+    .await
+    .map_err(|error| Error::Clipboard(error.to_string()))??;
+
+    let directory = dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("roygbiv")
+        .join("pasted");
+    let path = directory.join(format!(
+        "pasted-{:016x}.png",
+        assets::content_hash(&contents)
+    ));
+
+    tokio::fs::create_dir_all(&directory)
+        .await
+        .map_err(|error| Error::IoError(error.kind()))?;
+    tokio::fs::write(&path, &contents)
+        .await
+        .map_err(|error| Error::IoError(error.kind()))?;
+
+    Ok((path, Arc::new(contents)))
+}
+
+/// Bytes a file read into memory, such as an image or a font, may take up.
+/// Larger files are refused rather than left to use up the memory.
+const MAX_LOADED_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
 async fn load_file(path: impl Into<PathBuf>) -> Result<(PathBuf, Arc<Vec<u8>>), Error> {
     let path = path.into();
 
+    let size = tokio::fs::metadata(&path)
+        .await
+        .map_err(|error| Error::IoError(error.kind()))?
+        .len();
+    if size > MAX_LOADED_FILE_SIZE {
+        return Err(Error::FileTooLarge(size));
+    }
+
     let contents = tokio::fs::read(&path)
         .await
         .map(Arc::new)
@@ -115,408 +1374,7643 @@ async fn load_file(path: impl Into<PathBuf>) -> Result<(PathBuf, Arc<Vec<u8>>),
     Ok((path, contents))
 }
 
+/// A file's path and contents.
+type LoadedFile = (PathBuf, Arc<Vec<u8>>);
+
+/// Reads each of `paths`, one after another.
+async fn load_files(paths: Vec<PathBuf>) -> Vec<Result<LoadedFile, Error>> {
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        files.push(load_file(path).await);
+    }
+
+    files
+}
+
+/// Describes a file without reading it into memory, for files that are only
+/// ever streamed from disk. How much has been read is kept in `progress`,
+/// which can call the reading off.
+async fn stream_file(
+    path: impl Into<PathBuf>,
+    progress: Arc<LoadProgress>,
+) -> Result<(PathBuf, Contents), Error> {
+    let path = path.into();
+
+    let contents = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || match Contents::stream(&path, &progress) {
+            Err(_) if progress.is_cancelled() => Err(Error::Cancelled),
+            result => result.map_err(|error| Error::IoError(error.kind())),
+        })
+        .await
+        .map_err(|_| Error::IoError(io::ErrorKind::Other))??
+    };
+
+    Ok((path, contents))
+}
+
 impl Roygbiv {
     fn update(&mut self, message: Message) -> Task<Message> {
+        let is_tick = matches!(message, Message::Tick);
+        let task = self.handle(message);
+
+        // Ticks only run during playback, as nothing moves on its own while
+        // paused. The canvas is caught up after every change to it instead.
+        if !is_tick {
+            self.sync_links();
+            self.sync_swatches();
+            self.sync_text_editor();
+        }
+        if !is_tick && !self.transport.is_playing() && self.is_canvas_stale {
+            self.is_canvas_stale = false;
+            self.update_canvas();
+        }
+
+        task
+    }
+
+    fn handle(&mut self, message: Message) -> Task<Message> {
+        // Counted before handling, so that opening a project can mark what it
+        // opened as saved.
+        if message.is_edit() {
+            self.edits += 1;
+        }
+        // Set here rather than in `update`, so that messages handled on
+        // behalf of another, e.g. a key press, count too.
+        self.is_canvas_stale |= message.is_drawn();
+
         match message {
             Message::SetCanvasSize(width, height) => {
+                let (scale_x, scale_y) = (width / self.canvas_width, height / self.canvas_height);
                 self.canvas_width = width;
                 self.canvas_height = height;
 
+                for index in 0..self.canvas_state.layers.len() {
+                    let layer = &mut self.canvas_state.layers[index];
+                    let units = layer.units;
+                    layer.rescale(scale_x, scale_y);
+                    if units.width == Unit::Percent || units.height == Unit::Percent {
+                        self.refresh_layer_image(index);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::SetOrientation(orientation) => {
+                let size = orientation.size(Size::new(self.canvas_width, self.canvas_height));
+                if !self.fits_to_orientation {
+                    return self.handle(Message::SetCanvasSize(size.width, size.height));
+                }
+
+                let (scale_x, scale_y) = (
+                    size.width / self.canvas_width,
+                    size.height / self.canvas_height,
+                );
+                self.canvas_width = size.width;
+                self.canvas_height = size.height;
+
+                for index in 0..self.canvas_state.layers.len() {
+                    self.canvas_state.layers[index].fit(scale_x, scale_y);
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::ToggleFitToOrientation => {
+                self.fits_to_orientation = !self.fits_to_orientation;
+
                 Task::none()
             }
             Message::OpenAudioFile => {
-                if self.is_loading_file {
+                if self.file_load.is_some() {
                     Task::none()
                 } else {
-                    self.is_loading_file = true;
-
-                    Task::perform(open_audio_file(), Message::AudioFileOpened)
+                    self.start_file_load(open_audio_file, Message::AudioFileOpened)
                 }
             }
             Message::RemoveAudioFile => {
-                self.is_loading_file = false;
+                self.cancel_file_load();
 
-                self.audio_file_path = None;
-                self.audio_file_contents = vec![];
+                self.audio_asset = None;
 
-                Task::none()
+                self.analyze_audio()
             }
-            Message::AudioFileOpened(result) => {
-                self.is_loading_file = false;
+            Message::CancelFileLoad => {
+                self.cancel_file_load();
 
-                if let Ok((path, contents)) = result {
-                    self.audio_file_path = Some(path);
-                    self.audio_file_contents = contents.to_vec();
+                Task::none()
+            }
+            Message::CancelAnalysis => {
+                if let Some(progress) = self.analysis_progress.take() {
+                    progress.cancel();
                 }
 
                 Task::none()
             }
-            Message::AddImageLayer => Task::perform(open_image_file(), Message::ImageFileOpened),
-            Message::RemoveLayer(index) => {
-                let _ = &self.canvas_state.layers.remove(index);
-                self.update_layer_names();
+            Message::LoadProgressed => Task::none(),
+            Message::AudioFileOpened(id, result) => {
+                if !self.finish_file_load(id) {
+                    return Task::none();
+                }
+                self.notify_error(t("error-open-audio"), &result);
 
-                Task::done(Message::SelectLastLayer)
-            }
-            Message::ImageFileOpened(result) => {
                 if let Ok((path, contents)) = result {
-                    let file_name = if let Some(file_name) = path.file_name() {
-                        file_name.to_str()
-                    } else {
-                        path.to_str()
-                    }
-                    .unwrap_or("Unnamed");
-                    let image = image::load_from_memory(&contents);
-                    match image {
-                        Ok(image) => {
-                            let dimensions = image.dimensions();
-                            let x = 0.;
-                            let y = 0.;
-                            let width = dimensions.0 as f32;
-                            let is_clipping_horizontally = width > self.canvas_width;
-                            let width = if is_clipping_horizontally {
-                                self.canvas_width - x
-                            } else {
-                                width
-                            };
-                            let height = dimensions.1 as f32;
-                            let is_clipping_vertically = height > self.canvas_height;
-                            let height = if is_clipping_vertically {
-                                self.canvas_height - y
-                            } else {
-                                height
-                            };
-                            let handle = if is_clipping_vertically || is_clipping_horizontally {
-                                let cropped = image.crop_imm(0, 0, width as u32, height as u32);
-                                Handle::from_bytes(cropped.into_bytes())
-                            } else {
-                                Handle::from_bytes(contents.to_vec())
-                            };
-                            let layer = Layer {
-                                name: format!("{}", file_name),
-                                x,
-                                y,
-                                width,
-                                height,
-                                scale: 1.,
-                                opacity: 1.,
-                                handle,
-                            };
-                            let _ = &self.canvas_state.layers.push(layer);
-                            self.update_layer_names();
-                        }
-                        _ => println!("could not load image"),
-                    }
+                    tracing::info!("opened audio file {}", path.display());
+                    self.audio_asset = Some(self.assets.import(AssetKind::Audio, path, contents));
                 }
 
-                Task::done(Message::SelectLastLayer)
+                self.analyze_audio()
             }
-            Message::LayerSelected(index, _string) => {
-                self.selected_layer_index = index;
+            Message::AddAudioTrack => {
+                if self.file_load.is_some() {
+                    Task::none()
+                } else {
+                    self.start_file_load(open_audio_file, Message::AudioTrackOpened)
+                }
+            }
+            Message::AudioTrackOpened(id, result) => {
+                if !self.finish_file_load(id) {
+                    return Task::none();
+                }
+                self.notify_error(t("error-open-audio"), &result);
+
+                let Ok((path, contents)) = result else {
+                    return Task::none();
+                };
+                let asset = self.assets.import(AssetKind::Audio, path, contents);
+                self.audio_tracks.push(AudioTrack {
+                    asset,
+                    mix: Mix::NONE,
+                    analysis: None,
+                });
+                self.sync_output();
+
+                self.analyze_asset(asset, Arc::default())
+            }
+            Message::AudioTrackMixChanged(index, mix) => {
+                let Some(track) = self.audio_tracks.get_mut(index) else {
+                    return Task::none();
+                };
+                let is_moved = track.mix.offset != mix.offset;
+                track.mix = mix;
+
+                // Changing the gain alone doesn't need to start the tracks
+                // again, which would be heard while dragging the slider.
+                if is_moved {
+                    self.sync_output();
+                } else {
+                    self.output.set_amplitudes(
+                        self.voices().iter().map(|voice| voice.amplitude).collect(),
+                    );
+                }
 
                 Task::none()
             }
-            Message::Tick => {
-                self.canvas_state.update();
+            Message::RemoveAudioTrack(index) => {
+                self.remove_audio_track(index);
 
                 Task::none()
             }
-            Message::SelectLastLayer => {
-                self.selected_layer_index = self.canvas_state.layers.len().max(1) - 1;
+            Message::AudioAnalyzed(id, result, elapsed) => {
+                self.stats.analysis_time = Some(elapsed);
+                self.background_tasks.analyses = self.background_tasks.analyses.saturating_sub(1);
+                if self.audio_asset == Some(id) {
+                    self.analysis_progress = None;
+                }
+                match result {
+                    Ok(analysis) => {
+                        for track in &mut self.audio_tracks {
+                            if track.asset == id {
+                                track.analysis = Some(analysis.clone());
+                            }
+                        }
+
+                        if self.audio_asset == Some(id) {
+                            self.analysis = Some(analysis);
+                            self.waveform_cache.clear();
+                            self.transport.seek(Duration::ZERO);
+                            self.sync_output();
+                        }
+                    }
+                    Err(Error::Cancelled) => {}
+                    Err(error) => self.notify(
+                        ToastKind::Error,
+                        t_args("error-analyze-audio", &[("error", &error)]),
+                    ),
+                }
 
                 Task::none()
             }
-        }
-    }
+            Message::AddImageLayer => Task::perform(open_image_files(), Message::ImageFilesOpened),
+            Message::AddProceduralLayer(procedural) => {
+                self.canvas_state.layers.push(Layer {
+                    name: procedural.to_string(),
+                    kind: LayerKind::procedural(procedural),
+                    x: 0.,
+                    y: 0.,
+                    width: self.canvas_width,
+                    height: self.canvas_height,
+                    scale: 1.,
+                    opacity: 1.,
+                    flip: Flip::NONE,
+                    appearance: Appearance::default(),
+                    animation: Animation::NONE,
+                    timing: Timing::ALWAYS,
+                    playback: Playback::NORMAL,
+                    keyframes: Keyframes::default(),
+                    audio_channel: Channel::default(),
+                    audio_source: Source::default(),
+                    units: Units::default(),
+                    is_aspect_locked: false,
+                    visibility: Visibility::Everywhere,
+                });
+                self.update_layer_names();
 
-    fn update_layer_names(&mut self) {
-        self.layer_names = self
-            .canvas_state
-            .layers
-            .iter()
-            .map(|layer| layer.name.clone())
-            .collect()
-    }
+                Task::done(Message::SelectLastLayer)
+            }
+            Message::AddBackdropLayer => {
+                self.canvas_state.layers.push(Layer {
+                    name: t("layer-name-backdrop").to_string(),
+                    kind: LayerKind::Backdrop,
+                    x: 0.,
+                    y: 0.,
+                    width: self.canvas_width,
+                    height: self.canvas_height,
+                    scale: 1.,
+                    opacity: 1.,
+                    flip: Flip::NONE,
+                    appearance: Appearance {
+                        blur: 24.,
+                        darken: 0.3,
+                        ..Appearance::default()
+                    },
+                    animation: Animation::NONE,
+                    timing: Timing::ALWAYS,
+                    playback: Playback::NORMAL,
+                    keyframes: Keyframes::default(),
+                    audio_channel: Channel::default(),
+                    audio_source: Source::default(),
+                    units: Units::default(),
+                    is_aspect_locked: false,
+                    visibility: Visibility::Everywhere,
+                });
+                self.update_layer_names();
 
-    fn layer_settings_view(&self, layer: Option<&Layer>) -> Element<Message> {
-        if let Some(layer) = layer {
-            column![
-                column![text("x:"), text_input("x", &format!("{}", layer.x))].spacing(3.),
-                column![text("y:"), text_input("y", &format!("{}", layer.y))].spacing(3.),
-                column![
-                    text("width:"),
-                    text_input("width", &format!("{}", layer.width))
+                Task::done(Message::SelectLastLayer)
+            }
+            Message::PasteImage => Task::perform(paste_image(), Message::ImageFileOpened),
+            Message::RemoveLayer(index) => {
+                self.stop_cropping();
+                let _ = &self.canvas_state.layers.remove(index);
+                self.update_layer_names();
+                self.links
+                    .retain(|link| link.layer != index && link.source != index);
+                self.expressions
+                    .retain(|expression| expression.layer != index);
+                let later = self
+                    .links
+                    .iter_mut()
+                    .flat_map(|link| [&mut link.layer, &mut link.source])
+                    .chain(
+                        self.expressions
+                            .iter_mut()
+                            .map(|expression| &mut expression.layer),
+                    );
+                for layer in later.filter(|layer| **layer > index) {
+                    *layer -= 1;
+                }
+                self.swatch_uses
+                    .retain(|swatch_use| swatch_use.field.layer() != Some(index));
+                for swatch_use in &mut self.swatch_uses {
+                    if let Some(layer) = swatch_use.field.layer().filter(|layer| *layer > index) {
+                        swatch_use.field = swatch_use.field.with_layer(layer - 1);
+                    }
+                }
+
+                Task::done(Message::SelectLastLayer)
+            }
+            Message::ImageFileOpened(result) => {
+                self.notify_error(t("error-open-image"), &result);
+
+                match result {
+                    Ok(file) => self.add_image_layers(vec![file]),
+                    Err(_) => Task::done(Message::SelectLastLayer),
+                }
+            }
+            Message::ImageFilesOpened(result) => {
+                self.notify_error(t("error-open-images"), &result);
+                let Ok(results) = result else {
+                    return Task::none();
+                };
+
+                let mut files = vec![];
+                for result in results {
+                    self.notify_error(t("error-open-an-image"), &result);
+                    files.extend(result);
+                }
+
+                self.add_image_layers(files)
+            }
+            Message::ImportPsd => Task::perform(psd::open(), Message::PsdImported),
+            Message::AddLottieLayer => Task::perform(open_lottie_file(), Message::LottieFileOpened),
+            Message::LottieFileOpened(result) => {
+                self.notify_error(t("error-open-animation"), &result);
+                let Ok((path, contents)) = result else {
+                    return Task::none();
+                };
+
+                if let Err(error) = Lottie::parse(&contents) {
+                    self.notify(
+                        ToastKind::Error,
+                        t_args(
+                            "error-read-file",
+                            &[("path", &path.display()), ("error", &error)],
+                        ),
+                    );
+                    return Task::none();
+                }
+                tracing::info!("opened animation {}", path.display());
+                let asset =
+                    self.assets
+                        .import(AssetKind::Animation, path, Contents::Loaded(contents));
+                if let Some(layer) = self.image_layer(asset) {
+                    self.canvas_state.layers.push(layer);
+                    self.update_layer_names();
+                }
+
+                Task::done(Message::SelectLastLayer)
+            }
+            Message::AddCaptureLayer => Task::batch([
+                Task::perform(capture::screen(), Message::CaptureScreenFound),
+                Task::done(Message::RefreshCaptureWindows),
+            ]),
+            Message::CaptureScreenFound(result) => {
+                let region = match result {
+                    Ok(region) => region,
+                    Err(error) => {
+                        self.notify(
+                            ToastKind::Error,
+                            t_args("error-capture-screen", &[("error", &error)]),
+                        );
+                        return Task::none();
+                    }
+                };
+
+                let scale = (self.canvas_width / region.width.max(1) as f32)
+                    .min(self.canvas_height / region.height.max(1) as f32)
+                    .min(1.);
+                self.canvas_state.layers.push(Layer {
+                    name: t("layer-name-screen-capture").to_string(),
+                    kind: LayerKind::Capture {
+                        source: CaptureSource::Region(region),
+                        frame: None,
+                    },
+                    x: 0.,
+                    y: 0.,
+                    width: region.width as f32 * scale,
+                    height: region.height as f32 * scale,
+                    scale: 1.,
+                    opacity: 1.,
+                    flip: Flip::NONE,
+                    appearance: Appearance::default(),
+                    animation: Animation::NONE,
+                    timing: Timing::ALWAYS,
+                    playback: Playback::NORMAL,
+                    keyframes: Keyframes::default(),
+                    audio_channel: Channel::default(),
+                    audio_source: Source::default(),
+                    units: Units::default(),
+                    is_aspect_locked: true,
+                    visibility: Visibility::Everywhere,
+                });
+                self.update_layer_names();
+
+                Task::done(Message::SelectLastLayer)
+            }
+            Message::AddCameraLayer => Task::batch([
+                Task::perform(camera::devices(), Message::CamerasFound),
+                Task::done(Message::RefreshCaptureWindows),
+            ]),
+            Message::CamerasFound(result) => {
+                let camera = match result.as_deref().map(<[CameraDevice]>::first) {
+                    Ok(Some(device)) => device.camera(),
+                    Ok(None) => {
+                        self.notify(ToastKind::Error, t("camera-none-found"));
+                        return Task::none();
+                    }
+                    Err(error) => {
+                        self.notify(
+                            ToastKind::Error,
+                            t_args("error-find-camera", &[("error", &error)]),
+                        );
+                        return Task::none();
+                    }
+                };
+
+                let resolution = camera.resolution;
+                let scale = (self.canvas_width / resolution.width.max(1) as f32)
+                    .min(self.canvas_height / resolution.height.max(1) as f32)
+                    .min(1.);
+                self.canvas_state.layers.push(Layer {
+                    name: camera.name.clone(),
+                    kind: LayerKind::Capture {
+                        source: CaptureSource::Camera(camera),
+                        frame: None,
+                    },
+                    x: 0.,
+                    y: 0.,
+                    width: resolution.width as f32 * scale,
+                    height: resolution.height as f32 * scale,
+                    scale: 1.,
+                    opacity: 1.,
+                    // Mirrored, as people are used to seeing themselves.
+                    flip: Flip {
+                        horizontal: true,
+                        vertical: false,
+                    },
+                    appearance: Appearance::default(),
+                    animation: Animation::NONE,
+                    timing: Timing::ALWAYS,
+                    playback: Playback::NORMAL,
+                    keyframes: Keyframes::default(),
+                    audio_channel: Channel::default(),
+                    audio_source: Source::default(),
+                    units: Units::default(),
+                    is_aspect_locked: true,
+                    visibility: Visibility::Everywhere,
+                });
+                self.update_layer_names();
+
+                Task::done(Message::SelectLastLayer)
+            }
+            Message::RefreshCaptureWindows => Task::batch([
+                Task::perform(capture::windows(), Message::CaptureWindowsListed),
+                Task::perform(camera::devices(), Message::CamerasListed),
+            ]),
+            Message::CaptureWindowsListed(result) => {
+                match result {
+                    Ok(windows) => self.capture_windows = windows,
+                    Err(error) => tracing::warn!("could not list windows: {}", error),
+                }
+
+                Task::none()
+            }
+            Message::CamerasListed(result) => {
+                match result {
+                    Ok(cameras) => self.cameras = cameras,
+                    Err(error) => tracing::warn!("could not list cameras: {}", error),
+                }
+
+                Task::none()
+            }
+            Message::LayerCaptureSourceSelected(index, source) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.set_capture_source(source);
+                }
+                self.capture_error = None;
+                self.update_canvas();
+
+                Task::none()
+            }
+            Message::CaptureTick => {
+                if self.is_capturing {
+                    return Task::none();
+                }
+
+                let mut sources: Vec<(CaptureSource, Flip, ChromaKey)> = vec![];
+                for layer in &self.canvas_state.layers {
+                    let Some(source) = layer.capture_source() else {
+                        continue;
+                    };
+                    let wanted = (source.clone(), layer.flip, layer.appearance.chroma_key);
+                    if !sources.contains(&wanted) {
+                        sources.push(wanted);
+                    }
+                }
+                if sources.is_empty() {
+                    return Task::none();
+                }
+
+                self.is_capturing = true;
+                Task::perform(capture::grab(sources), Message::CaptureGrabbed)
+            }
+            Message::CaptureGrabbed(frames) => {
+                self.is_capturing = false;
+                for (source, flip, chroma_key, result) in frames {
+                    let new_frame = match result {
+                        Ok(frame) => frame,
+                        Err(error) => {
+                            if self.capture_error.as_ref() != Some(&error) {
+                                self.notify(
+                                    ToastKind::Error,
+                                    t_args(
+                                        "error-capture",
+                                        &[("source", &source), ("error", &error)],
+                                    ),
+                                );
+                                self.capture_error = Some(error);
+                            }
+                            continue;
+                        }
+                    };
+
+                    for layer in &mut self.canvas_state.layers {
+                        if let LayerKind::Capture {
+                            source: layer_source,
+                            frame,
+                        } = &mut layer.kind
+                        {
+                            if *layer_source == source
+                                && layer.flip == flip
+                                && layer.appearance.chroma_key == chroma_key
+                            {
+                                *frame = Some(new_frame.clone());
+                            }
+                        }
+                    }
+                }
+                self.update_canvas();
+
+                Task::none()
+            }
+            Message::PsdImported(result) => {
+                self.notify_error(t("error-import-psd"), &result);
+                let Ok((document, psd_layers)) = result else {
+                    return Task::none();
+                };
+
+                let canvas = Size::new(self.canvas_width, self.canvas_height);
+                let first = self.canvas_state.layers.len();
+                for psd_layer in psd_layers {
+                    let Some(bounds) = psd::place(psd_layer.bounds, document, canvas) else {
+                        continue;
+                    };
+                    let asset = self.assets.import(
+                        AssetKind::Image,
+                        psd_layer.path,
+                        Contents::Loaded(psd_layer.contents),
+                    );
+                    let Some(mut layer) = self.image_layer(asset) else {
+                        continue;
+                    };
+                    layer.name = psd_layer.name;
+                    layer.x = bounds.x;
+                    layer.y = bounds.y;
+                    layer.width = bounds.width;
+                    layer.height = bounds.height;
+                    layer.opacity = psd_layer.opacity;
+                    self.canvas_state.layers.push(layer);
+                }
+                self.update_layer_names();
+
+                Task::batch(
+                    (first..self.canvas_state.layers.len())
+                        .map(|index| self.decode_layer_image(index))
+                        .collect::<Vec<_>>(),
+                )
+                .chain(Task::done(Message::SelectLastLayer))
+            }
+            Message::ImageArrangementSelected(arrangement) => {
+                self.image_arrangement = arrangement;
+
+                Task::none()
+            }
+            Message::ImportDroppedImages => Task::perform(
+                load_files(std::mem::take(&mut self.dropped_images)),
+                |files| Message::ImageFilesOpened(Ok(files)),
+            ),
+            Message::ImageDecoded(id, options, frame, result) => {
+                self.background_tasks.decodes = self.background_tasks.decodes.saturating_sub(1);
+                let Some((rendered, size, preview)) = result else {
+                    let name = self.assets.get(id).map(Asset::name).unwrap_or_default();
+                    self.notify(ToastKind::Error, t_args("error-decode", &[("name", &name)]));
+                    return Task::none();
+                };
+                self.assets.set_preview(id, preview);
+                if let Some(asset) = self.assets.get(id) {
+                    self.renders
+                        .insert(asset.hash, options.clone(), frame, rendered.clone(), size);
+                }
+
+                // Layers edited while the image decoded have since been
+                // rendered with their new options, and are left as they are.
+                for index in 0..self.canvas_state.layers.len() {
+                    if self.image_request(index) != Some((id, options.clone(), frame)) {
+                        continue;
+                    }
+                    if let LayerKind::Image {
+                        frames, image_size, ..
+                    } = &mut self.canvas_state.layers[index].kind
+                    {
+                        *frames = rendered.clone();
+                        *image_size = size;
+                    }
+                }
+                self.enforce_memory_budget();
+
+                Task::none()
+            }
+            Message::LayerListScrolled(viewport) => {
+                self.layer_list_viewport = (viewport.absolute_offset().y, viewport.bounds().height);
+
+                Task::none()
+            }
+            Message::LayerQueryChanged(query) => {
+                self.layer_query = query;
+
+                scrollable::scroll_to(layer_list_id(), scrollable::AbsoluteOffset::default())
+            }
+            Message::LayerCategorySelected(category) => {
+                self.layer_category = category;
+
+                scrollable::scroll_to(layer_list_id(), scrollable::AbsoluteOffset::default())
+            }
+            Message::LayerSelected(index, _string) => {
+                if self.canvas_state.cropping != Some(index) {
+                    self.stop_cropping();
+                }
+                self.selected_layer_index = index;
+
+                Task::none()
+            }
+            Message::Tick => {
+                self.update_canvas();
+
+                Task::none()
+            }
+            Message::TogglePlayback => {
+                if self.transport.is_playing() {
+                    self.transport.pause(self.duration());
+                } else {
+                    self.transport.play();
+                }
+                self.sync_output();
+
+                Task::none()
+            }
+            Message::PlaybackRateSelected(rate) => {
+                self.transport.set_rate(rate, self.duration());
+                self.sync_output();
+
+                Task::none()
+            }
+            Message::PitchPreservedToggled(is_pitch_preserved) => {
+                self.output.set_pitch_preserved(is_pitch_preserved);
+                self.sync_output();
+
+                Task::none()
+            }
+            Message::StepFrames(delta) => {
+                let duration = self.duration();
+                let frame = self.frame_rate.frame(self.position());
+                let mut position = self.frame_rate.time(frame.saturating_add_signed(delta));
+                // The end of the track loops back to the start.
+                if let Some(duration) = duration {
+                    let last = self.frame_rate.frame(duration).saturating_sub(1);
+                    position = position.min(self.frame_rate.time(last));
+                }
+
+                self.transport.pause(duration);
+                self.transport.seek(position);
+                self.sync_output();
+
+                Task::none()
+            }
+            Message::CycleTimeFormat => {
+                self.time_format = self.time_format.next();
+
+                Task::none()
+            }
+            Message::FrameRateSelected(frame_rate) => {
+                self.frame_rate = frame_rate;
+
+                Task::none()
+            }
+            Message::LanguageSelected(language) => {
+                i18n::set_language(language);
+
+                Task::perform(
+                    i18n::update_preferences(move |preferences| {
+                        preferences.language = Some(language);
+                    }),
+                    Message::PreferencesSaved,
+                )
+            }
+            Message::PreferencesSaved(result) => {
+                self.notify_error(t("error-save-preferences"), &result);
+
+                Task::none()
+            }
+            Message::SafeAreaSelected(safe_area) => {
+                self.canvas_state.safe_area = safe_area;
+
+                Task::none()
+            }
+            Message::PinComparison => {
+                self.canvas_state.pin_comparison();
+
+                Task::none()
+            }
+            Message::StopComparing => {
+                self.canvas_state.comparison = None;
+
+                Task::none()
+            }
+            Message::CompareViewSelected(view) => {
+                if let Some(comparison) = &mut self.canvas_state.comparison {
+                    comparison.view = view;
+                }
+
+                Task::none()
+            }
+            Message::ToggleCompareView => {
+                match &mut self.canvas_state.comparison {
+                    Some(comparison) => {
+                        comparison.view = match comparison.view {
+                            CompareView::A => CompareView::B,
+                            CompareView::B | CompareView::Split => CompareView::A,
+                        }
+                    }
+                    None => self.canvas_state.pin_comparison(),
+                }
+
+                Task::none()
+            }
+            Message::CompareSplitChanged(split) => {
+                if let Some(comparison) = &mut self.canvas_state.comparison {
+                    comparison.split = split;
+                }
+
+                Task::none()
+            }
+            Message::ThumbnailSizeSelected(thumbnail_size) => {
+                self.thumbnail_size = thumbnail_size;
+
+                Task::none()
+            }
+            Message::RenderThumbnail => {
+                let canvas = Size::new(self.canvas_width, self.canvas_height);
+                let image = self
+                    .canvas_state
+                    .render_thumbnail(canvas, self.thumbnail_size.size(canvas));
+
+                Task::perform(thumbnail::save(image), Message::ThumbnailSaved)
+            }
+            Message::ThumbnailSaved(result) => {
+                self.notify_error(t("error-save-thumbnail"), &result);
+                if let Ok(path) = result {
+                    self.notify(
+                        ToastKind::Success,
+                        t_args("thumbnail-saved", &[("path", &path.display().to_string())]),
+                    );
+                }
+
+                Task::none()
+            }
+            Message::ExportLottie => {
+                let sources = self
+                    .canvas_state
+                    .layers
+                    .iter()
+                    .filter(|layer| layer.visibility.is_in_output(false))
+                    .map(|layer| {
+                        let asset = layer.asset().and_then(|id| self.assets.get(id));
+
+                        lottie_export::Source {
+                            layer: layer.clone(),
+                            contents: asset.and_then(|asset| match &asset.contents {
+                                Contents::Loaded(bytes) => Some(bytes.clone()),
+                                Contents::Streamed { .. } => None,
+                            }),
+                            preview: asset.and_then(|asset| asset.preview.clone()),
+                        }
+                    })
+                    .collect();
+
+                Task::perform(
+                    lottie_export::save(
+                        sources,
+                        self.canvas_state.post_fx.clone(),
+                        self.scenes.clone(),
+                        Size::new(self.canvas_width, self.canvas_height),
+                        self.duration().map(|duration| duration.as_secs_f32()),
+                        self.frame_rate,
+                    ),
+                    Message::LottieExported,
+                )
+            }
+            Message::LottieExported(result) => {
+                self.notify_error(t("error-export-animation"), &result);
+                if let Ok(export) = result {
+                    self.notify(
+                        ToastKind::Success,
+                        t_args(
+                            "lottie-exported",
+                            &[("path", &export.path.display().to_string())],
+                        ),
+                    );
+                    if !export.left_out.is_empty() {
+                        self.notify(
+                            ToastKind::Info,
+                            t_args(
+                                "lottie-left-out",
+                                &[("layers", &export.left_out.join(", "))],
+                            ),
+                        );
+                    }
+                }
+
+                Task::none()
+            }
+            Message::ToggleStats => {
+                self.show_stats = !self.show_stats;
+
+                Task::none()
+            }
+            Message::TogglePostFx => {
+                self.show_post_fx = !self.show_post_fx;
+
+                Task::none()
+            }
+            Message::PostFxChanged(post_fx) => {
+                self.canvas_state.post_fx = post_fx;
+
+                Task::none()
+            }
+            Message::LoadPostFxLut => Task::perform(open_lut_file(), Message::PostFxLutFileOpened),
+            Message::PostFxLutFileOpened(result) => {
+                if let Some(lut) = self.read_lut(result) {
+                    self.canvas_state.post_fx.lut = Some(lut);
+                }
+
+                Task::none()
+            }
+            Message::Notify(kind, message) => {
+                self.notify(kind, message);
+
+                Task::none()
+            }
+            Message::OpenCommandPalette => {
+                self.command_palette = Some(CommandPalette::default());
+
+                text_input::focus(CommandPalette::input_id())
+            }
+            Message::CloseCommandPalette => {
+                self.command_palette = None;
+
+                Task::none()
+            }
+            Message::CommandQueryChanged(query) => {
+                if let Some(palette) = &mut self.command_palette {
+                    palette.query = query;
+                    palette.selected = 0;
+                }
+
+                Task::none()
+            }
+            Message::MoveCommandSelection(delta) => {
+                let commands = self.commands();
+                if let Some(palette) = &mut self.command_palette {
+                    let count = palette.matching(commands).len();
+                    palette.selected = (palette.selected as i64 + delta as i64)
+                        .clamp(0, count.saturating_sub(1) as i64)
+                        as usize;
+                }
+
+                Task::none()
+            }
+            Message::FocusPanel(delta) => {
+                let panel = Panel::cycle(self.focused_panel, delta);
+                self.focused_panel = Some(panel);
+
+                if panel == Panel::Settings {
+                    self.sidebar_tab = SidebarTab::Layers;
+                    iced::widget::focus_next()
+                } else {
+                    text_input::focus(text_input::Id::unique())
+                }
+            }
+            Message::FocusInput(backwards) => {
+                if backwards {
+                    iced::widget::focus_previous()
+                } else {
+                    iced::widget::focus_next()
+                }
+            }
+            Message::TogglePreviewWindow => match self.preview_window.take() {
+                Some(preview_window) => window::close(preview_window),
+                None => {
+                    let (preview_window, open) = window::open(window::Settings {
+                        size: Size::new(self.canvas_width, self.canvas_height) * 0.5,
+                        ..window::Settings::default()
+                    });
+                    self.preview_window = Some(preview_window);
+                    self.is_preview_fullscreen = false;
+
+                    open.discard()
+                }
+            },
+            Message::TogglePreviewFullscreen => {
+                let Some(preview_window) = self.preview_window else {
+                    return Task::none();
+                };
+                self.is_preview_fullscreen = !self.is_preview_fullscreen;
+
+                window::change_mode(
+                    preview_window,
+                    if self.is_preview_fullscreen {
+                        window::Mode::Fullscreen
+                    } else {
+                        window::Mode::Windowed
+                    },
+                )
+            }
+            Message::WindowClosed(id) => {
+                if id == self.main_window {
+                    iced::exit()
+                } else {
+                    if self.preview_window == Some(id) {
+                        self.preview_window = None;
+                    }
+
+                    Task::none()
+                }
+            }
+            Message::FileDropped(path) => self.open_file(path),
+            Message::WindowResized(id, size) => {
+                if id == self.main_window {
+                    self.main_window_size = size;
+                }
+
+                Task::none()
+            }
+            Message::ClearFocus => {
+                self.focused_panel = None;
+
+                Task::none()
+            }
+            Message::ArrowPressed(arrow) => {
+                if self.command_palette.is_some() {
+                    return match arrow {
+                        Arrow::Up => self.handle(Message::MoveCommandSelection(-1)),
+                        Arrow::Down => self.handle(Message::MoveCommandSelection(1)),
+                        Arrow::Left | Arrow::Right => Task::none(),
+                    };
+                }
+
+                if let Some(field) = self.hovered_field {
+                    match arrow {
+                        Arrow::Up => return self.handle(Message::NudgeField(field, 1.)),
+                        Arrow::Down => return self.handle(Message::NudgeField(field, -1.)),
+                        Arrow::Left | Arrow::Right => {}
+                    }
+                }
+
+                match (self.focused_panel, arrow) {
+                    (Some(Panel::Transport), Arrow::Left) => {
+                        self.transport_focus = self.transport_focus.saturating_sub(1);
+
+                        Task::none()
+                    }
+                    (Some(Panel::Transport), Arrow::Right) => {
+                        self.transport_focus =
+                            (self.transport_focus + 1).min(self.transport_actions().len() - 1);
+
+                        Task::none()
+                    }
+                    (Some(Panel::Layers), Arrow::Up) => self.move_layer_selection(-1),
+                    (Some(Panel::Layers), Arrow::Down) => self.move_layer_selection(1),
+                    (_, Arrow::Left) => self.handle(Message::StepFrames(-1)),
+                    (_, Arrow::Right) => self.handle(Message::StepFrames(1)),
+                    (_, Arrow::Up | Arrow::Down) => Task::none(),
+                }
+            }
+            Message::ActivateFocused => match self.focused_panel {
+                Some(Panel::Transport) => {
+                    let action = self.transport_actions()[self.transport_focus].clone();
+
+                    self.handle(action)
+                }
+                // Enter on a layer goes on to its settings.
+                Some(Panel::Layers) => self.handle(Message::FocusPanel(1)),
+                Some(Panel::Settings) | None => Task::none(),
+            },
+            Message::SubmitCommand => {
+                let Some(palette) = &self.command_palette else {
+                    return Task::none();
+                };
+
+                match palette.matching(self.commands()).get(palette.selected) {
+                    Some(command) => {
+                        Task::done(Message::RunCommand(Box::new(command.message.clone())))
+                    }
+                    None => Task::none(),
+                }
+            }
+            Message::RunCommand(message) => {
+                self.command_palette = None;
+
+                Task::done(*message)
+            }
+            Message::CopyDiagnostics => {
+                self.notify(ToastKind::Success, t("diagnostics-copied"));
+
+                iced::clipboard::write(logging::diagnostics())
+            }
+            Message::DismissToast(id) => {
+                self.toasts.dismiss(id);
+
+                Task::none()
+            }
+            Message::ExpireToasts => {
+                self.toasts.expire(Instant::now());
+
+                Task::none()
+            }
+            Message::MemoryBudgetChanged(budget) => {
+                self.memory_budget = budget;
+                self.enforce_memory_budget();
+
+                Task::none()
+            }
+            Message::AudioTrimChanged(trim) => {
+                // The track now starts somewhere else in the file.
+                if trim.start != self.audio_trim.start {
+                    self.transport.seek(Duration::ZERO);
+                }
+                self.audio_trim = trim;
+                self.sync_output();
+
+                Task::none()
+            }
+            Message::Seek(seconds) => {
+                self.transport
+                    .seek(Duration::from_secs_f32(seconds.max(0.)));
+                self.sync_output();
+
+                Task::none()
+            }
+            Message::ListOutputDevices => {
+                Task::perform(playback::devices(), Message::OutputDevicesListed)
+            }
+            Message::OutputDevicesListed(devices) => {
+                self.output_devices = devices;
+
+                Task::none()
+            }
+            Message::OutputDeviceSelected(device) => {
+                self.output.set_device(device);
+                self.sync_output();
+
+                Task::none()
+            }
+            Message::VolumeChanged(volume) => {
+                self.output.set_volume(volume);
+
+                Task::none()
+            }
+            Message::ToggleMute => {
+                self.output.set_muted(!self.output.is_muted());
+
+                Task::none()
+            }
+            Message::AddMarker => {
+                let time = self.position().as_secs_f32();
+                let index = self.markers.partition_point(|marker| marker.time <= time);
+                self.markers.insert(
+                    index,
+                    Marker {
+                        time,
+                        name: t_args("marker-name", &[("number", &(self.markers.len() + 1))]),
+                    },
+                );
+
+                Task::none()
+            }
+            Message::RemoveMarker(index) => {
+                if index < self.markers.len() {
+                    self.markers.remove(index);
+                }
+
+                Task::none()
+            }
+            Message::MarkerNameChanged(index, name) => {
+                if let Some(marker) = self.markers.get_mut(index) {
+                    marker.name = name;
+                }
+
+                Task::none()
+            }
+            Message::ImportMarkers => Task::perform(
+                async {
+                    let picked_file = directories::dialog(DialogKind::Audio)
+                        .set_title(t("dialog-import-chapters"))
+                        .add_filter(
+                            t("filter-chapters"),
+                            &["csv", "txt", "mp3", "m4a", "m4b", "mp4"],
+                        )
+                        .pick_file()
+                        .await
+                        .ok_or(Error::DialogClosed)?;
+                    directories::remember(DialogKind::Audio, picked_file.path()).await;
+
+                    markers::import(picked_file.into()).await
+                },
+                Message::MarkersImported,
+            ),
+            Message::ImportAudioChapters => match self.audio() {
+                Some(audio) => Task::perform(
+                    markers::import(audio.path.clone()),
+                    Message::MarkersImported,
+                ),
+                None => Task::none(),
+            },
+            Message::MarkersImported(result) => {
+                self.notify_error(t("error-import-chapters"), &result);
+
+                if let Ok(imported) = result {
+                    // Chapters are timed from the start of the file, which
+                    // the trim may have cut off.
+                    let mut added = 0;
+                    for mut marker in imported {
+                        marker.time = (marker.time - self.audio_trim.start).max(0.);
+                        if self
+                            .markers
+                            .iter()
+                            .any(|existing| existing.time == marker.time)
+                        {
+                            continue;
+                        }
+                        let index = self
+                            .markers
+                            .partition_point(|existing| existing.time <= marker.time);
+                        self.markers.insert(index, marker);
+                        added += 1;
+                    }
+
+                    if added == 0 {
+                        self.notify(ToastKind::Info, t("markers-none-found"));
+                    } else {
+                        self.notify(
+                            ToastKind::Success,
+                            t_args("markers-imported", &[("count", &added)]),
+                        );
+                    }
+                }
+
+                Task::none()
+            }
+            Message::AddScene => {
+                let start = self.position().as_secs_f32();
+                let name = t_args("scene-name", &[("number", &(self.scenes.len() + 1))]);
+                self.insert_scene(Scene {
+                    name,
+                    start,
+                    transition: Transition::NONE,
+                });
+
+                Task::none()
+            }
+            Message::RemoveScene(index) => {
+                if index < self.scenes.len() {
+                    self.scenes.remove(index);
+                    for layer in &mut self.canvas_state.layers {
+                        layer.timing.scene = match layer.timing.scene {
+                            Some(scene) if scene == index => None,
+                            Some(scene) if scene > index => Some(scene - 1),
+                            scene => scene,
+                        };
+                    }
+                }
+
+                Task::none()
+            }
+            Message::SceneNameChanged(index, name) => {
+                if let Some(scene) = self.scenes.get_mut(index) {
+                    scene.name = name;
+                }
+
+                Task::none()
+            }
+            Message::SceneTransitionChanged(index, transition) => {
+                if let Some(scene) = self.scenes.get_mut(index) {
+                    scene.transition = transition;
+                }
+
+                Task::none()
+            }
+            Message::ScenesFromMarkers => {
+                for marker in self.markers.clone() {
+                    if self.scenes.iter().all(|scene| scene.start != marker.time) {
+                        self.insert_scene(Scene {
+                            name: marker.name,
+                            start: marker.time,
+                            transition: Transition::NONE,
+                        });
+                    }
+                }
+
+                Task::none()
+            }
+            Message::SelectLastLayer => {
+                self.selected_layer_index = self.canvas_state.layers.len().max(1) - 1;
+
+                Task::none()
+            }
+            Message::OpenProject => {
+                Task::perform(project::open_project_file(), Message::ProjectOpened)
+            }
+            Message::ProjectOpened(result) => {
+                self.notify_error(t("error-open-project"), &result);
+
+                let Ok((path, project)) = result else {
+                    return Task::none();
+                };
+
+                Task::perform(
+                    project::missing_assets(project),
+                    move |(project, missing)| {
+                        Message::MissingAssetsFound(path.clone(), project, missing)
+                    },
+                )
+            }
+            Message::MissingAssetsFound(path, project, missing) => {
+                if missing.is_empty() {
+                    return Task::perform(project::load_project_assets(project), move |result| {
+                        Message::ProjectAssetsLoaded(path.clone(), result)
+                    });
+                }
+                self.relink = Some(Relink {
+                    project_path: path,
+                    project,
+                    missing,
+                });
+
+                Task::none()
+            }
+            Message::SaveProject => {
+                // Edits made while the file is written are still unsaved.
+                let edits = self.edits;
+
+                Task::perform(
+                    project::save_project(self.project_path.clone(), self.project()),
+                    move |result| Message::ProjectSaved(edits, result),
+                )
+            }
+            Message::ProjectSaved(edits, result) => {
+                self.notify_error(t("error-save-project"), &result);
+
+                if let Ok(path) = result {
+                    self.notify(
+                        ToastKind::Success,
+                        t_args("project-saved", &[("path", &path.display())]),
+                    );
+                    self.project_path = Some(path);
+                    self.saved_edits = edits;
+                }
+
+                Task::none()
+            }
+            Message::ProjectAssetsLoaded(path, result) => {
+                self.notify_error(t("error-load-project-files"), &result);
+
+                if let Ok((project, audio, tracks, layers)) = result {
+                    tracing::info!("opened project {}", path.display());
+                    self.apply_project(Some(path), project, audio, tracks, layers);
+                }
+
+                self.load_project_media()
+            }
+            Message::SnapshotNameChanged(name) => {
+                self.snapshot_name = name;
+
+                Task::none()
+            }
+            Message::TakeSnapshot => {
+                let name = match std::mem::take(&mut self.snapshot_name).trim() {
+                    "" => t_args(
+                        "snapshot-default-name",
+                        &[("count", &(self.snapshots.len() + 1))],
+                    ),
+                    name => name.to_string(),
+                };
+                let snapshot = self.snapshot(name);
+                self.snapshots.push(snapshot);
+
+                Task::none()
+            }
+            Message::RenameSnapshot(index, name) => {
+                if let Some(snapshot) = self.snapshots.get_mut(index) {
+                    snapshot.name = name;
+                }
+
+                Task::none()
+            }
+            Message::RemoveSnapshot(index) => {
+                if index < self.snapshots.len() {
+                    self.snapshots.remove(index);
+                }
+
+                Task::none()
+            }
+            Message::RestoreSnapshot(index) => {
+                let Some(snapshot) = self.snapshots.get(index) else {
+                    return Task::none();
+                };
+                let name = snapshot.name.clone();
+
+                Task::perform(
+                    project::load_project_assets((*snapshot.project).clone()),
+                    move |result| Message::SnapshotAssetsLoaded(name.clone(), result),
+                )
+            }
+            Message::SnapshotAssetsLoaded(name, result) => {
+                self.notify_error(t("error-restore-snapshot"), &result);
+                let Ok((project, audio, tracks, layers)) = result else {
+                    return Task::none();
+                };
+
+                // What is being replaced is kept too, so restoring can be
+                // gone back on.
+                let mut snapshots = std::mem::take(&mut self.snapshots);
+                snapshots
+                    .push(self.snapshot(t_args("snapshot-before-restore", &[("name", &name)])));
+                // Restoring is an unsaved change like any other, and can be
+                // compared with what was pinned before.
+                let saved_edits = self.saved_edits;
+                let comparison = self.canvas_state.comparison.take();
+                self.apply_project(self.project_path.clone(), project, audio, tracks, layers);
+                self.saved_edits = saved_edits;
+                self.canvas_state.comparison = comparison;
+                self.snapshots = snapshots;
+                self.notify(
+                    ToastKind::Info,
+                    t_args("snapshot-restored", &[("name", &name)]),
+                );
+
+                self.load_project_media()
+            }
+            Message::RelinkBrowse(asset) => {
+                Task::perform(project::pick_replacement(asset), move |path| {
+                    Message::RelinkPicked(asset, path)
+                })
+            }
+            Message::RelinkPicked(asset, path) => {
+                if let (Some(relink), Some(path)) = (&mut self.relink, path) {
+                    relink.project.set_asset_path(asset, path);
+                    relink.missing.retain(|missing| *missing != asset);
+                }
+
+                Task::none()
+            }
+            Message::RelinkSkipMissing => {
+                let Some(Relink {
+                    project_path,
+                    mut project,
+                    missing,
+                }) = self.relink.take()
+                else {
+                    return Task::none();
+                };
+                project.remove_assets(&missing);
+
+                Task::perform(project::load_project_assets(project), move |result| {
+                    Message::ProjectAssetsLoaded(project_path.clone(), result)
+                })
+            }
+            Message::RelinkCancel => {
+                self.relink = None;
+
+                Task::none()
+            }
+            Message::ToggleSettingsSection(section) => {
+                if let Some(position) = self
+                    .collapsed_sections
+                    .iter()
+                    .position(|collapsed| *collapsed == section)
+                {
+                    self.collapsed_sections.remove(position);
+                } else {
+                    self.collapsed_sections.push(section);
+                }
+
+                Task::none()
+            }
+            Message::SidebarTabSelected(tab) => {
+                self.sidebar_tab = tab;
+
+                Task::none()
+            }
+            Message::ReplaceAsset(id) => {
+                let Some(asset) = self.assets.get(id) else {
+                    return Task::none();
+                };
+
+                match asset.kind {
+                    AssetKind::Image => Task::perform(open_image_file(), move |result| {
+                        Message::AssetReplaced(
+                            id,
+                            result.map(|(path, contents)| (path, Contents::Loaded(contents))),
+                        )
+                    }),
+                    AssetKind::Animation => Task::perform(open_lottie_file(), move |result| {
+                        Message::AssetReplaced(
+                            id,
+                            result.map(|(path, contents)| (path, Contents::Loaded(contents))),
+                        )
+                    }),
+                    AssetKind::Audio => {
+                        Task::perform(open_audio_file(Arc::default()), move |result| {
+                            Message::AssetReplaced(id, result)
+                        })
+                    }
+                }
+            }
+            Message::AssetReplaced(id, result) => {
+                self.notify_error(t("error-replace-file"), &result);
+
+                if let Ok((path, contents)) = result {
+                    self.assets.replace(id, path, contents);
+                    let refresh = self.refresh_layers(id);
+
+                    let mut is_track = false;
+                    for track in &mut self.audio_tracks {
+                        if track.asset == id {
+                            track.analysis = None;
+                            is_track = true;
+                        }
+                    }
+
+                    if self.audio_asset == Some(id) {
+                        return self.analyze_audio();
+                    }
+                    if is_track {
+                        self.sync_output();
+                        return self.analyze_asset(id, Arc::default());
+                    }
+
+                    return refresh;
+                }
+
+                Task::none()
+            }
+            Message::RemoveAsset(id) => {
+                if self.audio_asset == Some(id) {
+                    self.audio_asset = None;
+                    self.analysis = None;
+                    self.sync_output();
+                }
+                while let Some(index) = self.audio_tracks.iter().position(|track| track.asset == id)
+                {
+                    self.remove_audio_track(index);
+                }
+                self.canvas_state
+                    .layers
+                    .retain(|layer| layer.asset() != Some(id));
+                self.assets.remove(id);
+                self.update_layer_names();
+
+                Task::done(Message::SelectLastLayer)
+            }
+            Message::NewProject => {
+                self.show_start_screen = true;
+
+                Task::none()
+            }
+            Message::CloseStartScreen => {
+                self.show_start_screen = false;
+
+                Task::none()
+            }
+            Message::TemplateSelected(template) => {
+                self.apply_project(None, template.project(), None, vec![], vec![]);
+
+                Task::batch([self.analyze_audio(), Task::done(Message::SelectLastLayer)])
+            }
+            Message::OpenAudiogramWizard => {
+                self.audiogram_wizard = Some(AudiogramWizard {
+                    orientation: Some(Orientation::Square),
+                    ..AudiogramWizard::default()
+                });
+
+                Task::none()
+            }
+            Message::CloseAudiogramWizard => {
+                self.audiogram_wizard = None;
+
+                Task::none()
+            }
+            Message::PickAudiogramAudio => Task::perform(
+                open_audio_file(Arc::default()),
+                Message::AudiogramAudioPicked,
+            ),
+            Message::AudiogramAudioPicked(result) => {
+                self.notify_error(t("error-open-audio"), &result);
+
+                if let (Some(wizard), Ok((path, contents))) = (&mut self.audiogram_wizard, result) {
+                    if wizard.title.is_empty() {
+                        wizard.title = path
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                            .unwrap_or_default();
+                    }
+                    wizard.audio = Some((path, contents));
+                }
+
+                Task::none()
+            }
+            Message::PickAudiogramCover => {
+                Task::perform(open_image_file(), Message::AudiogramCoverPicked)
+            }
+            Message::AudiogramCoverPicked(result) => {
+                self.notify_error(t("error-open-image"), &result);
+
+                if let (Some(wizard), Ok(cover)) = (&mut self.audiogram_wizard, result) {
+                    wizard.cover = Some(cover);
+                }
+
+                Task::none()
+            }
+            Message::AudiogramTitleChanged(title) => {
+                if let Some(wizard) = &mut self.audiogram_wizard {
+                    wizard.title = title;
+                }
+
+                Task::none()
+            }
+            Message::AudiogramOrientationSelected(orientation) => {
+                if let Some(wizard) = &mut self.audiogram_wizard {
+                    wizard.orientation = Some(orientation);
+                }
+
+                Task::none()
+            }
+            Message::CreateAudiogram => {
+                let Some(AudiogramWizard {
+                    audio: Some((audio_path, audio)),
+                    cover,
+                    title,
+                    orientation,
+                }) = self.audiogram_wizard.take()
+                else {
+                    return Task::none();
+                };
+
+                let (cover_path, cover) = cover.unzip();
+                let project = templates::audiogram(
+                    orientation.unwrap_or(Orientation::Square),
+                    audio_path,
+                    cover_path,
+                    &title,
+                );
+                self.apply_project(None, project, Some(audio), vec![], vec![cover]);
+
+                Task::batch([self.analyze_audio(), Task::done(Message::SelectLastLayer)])
+            }
+            Message::ReplaceLayerImage(index) => Task::perform(open_image_file(), move |result| {
+                Message::LayerImageOpened(index, result)
+            }),
+            Message::LayerImageOpened(index, result) => {
+                self.notify_error(t("error-open-image"), &result);
+
+                if let Ok((path, contents)) = result {
+                    let asset =
+                        self.assets
+                            .import(AssetKind::Image, path, Contents::Loaded(contents));
+                    let previous_name = self
+                        .canvas_state
+                        .layers
+                        .get(index)
+                        .and_then(Layer::asset)
+                        .and_then(|id| self.assets.get(id))
+                        .map(Asset::name);
+
+                    if let (Some(image), Some(layer)) = (
+                        self.image_layer(asset),
+                        self.canvas_state.layers.get_mut(index),
+                    ) {
+                        // Layers still named after their file follow the new
+                        // file, names given by the user or a template are kept.
+                        if previous_name.as_ref() == Some(&layer.name) {
+                            layer.name = image.name;
+                        }
+                        let sizing = layer.sizing().unwrap_or_default();
+                        layer.kind = image.kind;
+                        layer.set_sizing(sizing);
+                    }
+                    self.update_layer_names();
+
+                    return self.decode_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::ToggleCropping(index) => {
+                let was_cropping = self.canvas_state.cropping == Some(index);
+                self.stop_cropping();
+
+                if !was_cropping {
+                    self.canvas_state.cropping = Some(index);
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerCropChanged(index, crop) => {
+                // Drawn uncropped while editing, so this only moves the overlay.
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.set_crop(crop);
+                }
+
+                Task::none()
+            }
+            Message::ResetLayerCrop(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.set_crop(Crop::FULL);
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerFlipped(index, flip) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.flip = flip;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerCornerRadiusChanged(index, radius) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.corner_radius = radius;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerBorderWidthChanged(index, width) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.border.width = width;
+                }
+
+                Task::none()
+            }
+            Message::LayerBorderFollowsPitchToggled(index, follows_pitch) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.border.follows_pitch = follows_pitch;
+                }
+
+                Task::none()
+            }
+            Message::LayerTintModeSelected(index, mode) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.tint.mode = mode;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerTintAmountChanged(index, amount) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.tint.amount = amount;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerChromaKeyChanged(index, chroma_key) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.chroma_key = chroma_key;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerDistortionChanged(index, distortion) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.distortion = distortion;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerGlitchChanged(index, glitch) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.glitch = glitch;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerKaleidoscopeChanged(index, kaleidoscope) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.kaleidoscope = kaleidoscope;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerBlurChanged(index, blur) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.blur = blur;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LayerDarkenChanged(index, darken) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.darken = darken;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::LoadLayerLut(index) => Task::perform(open_lut_file(), move |result| {
+                Message::LayerLutFileOpened(index, result)
+            }),
+            Message::LayerLutFileOpened(index, result) => {
+                if let Some(lut) = self.read_lut(result) {
+                    if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                        layer.set_lut(Some(lut));
+                        self.refresh_layer_image(index);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::LayerLutAmountChanged(index, amount) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    let lut = layer.lut().cloned().map(|mut lut| {
+                        lut.amount = amount;
+                        lut
+                    });
+                    layer.set_lut(lut);
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::RemoveLayerLut(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.set_lut(None);
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::AddBlurredBackdrop(index) => {
+                let Some(layer) = self
+                    .canvas_state
+                    .layers
+                    .get(index)
+                    .filter(|layer| matches!(layer.kind, LayerKind::Image { .. }))
+                else {
+                    return Task::none();
+                };
+
+                let mut backdrop = Layer {
+                    name: t_args("layer-name-image-backdrop", &[("name", &layer.name)]),
+                    x: 0.,
+                    y: 0.,
+                    width: self.canvas_width,
+                    height: self.canvas_height,
+                    scale: 1.,
+                    appearance: Appearance {
+                        blur: 40.,
+                        darken: 0.4,
+                        tint: layer.appearance.tint,
+                        ..Appearance::default()
+                    },
+                    animation: Animation::NONE,
+                    keyframes: Keyframes::default(),
+                    units: Units::default(),
+                    is_aspect_locked: false,
+                    ..layer.clone()
+                };
+                backdrop.set_sizing(Sizing::Fill);
+                backdrop.set_crop(Crop::FULL);
+
+                self.stop_cropping();
+                self.insert_layer(index, backdrop);
+                self.refresh_layer_image(index);
+                self.selected_layer_index = index + 1;
+
+                Task::none()
+            }
+            Message::LayerProceduralChanged(index, procedural) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.set_procedural(procedural);
+                }
+
+                Task::none()
+            }
+            Message::LayerSeedChanged(index, seed) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(mut procedural) = layer.procedural() {
+                        procedural.set_seed(seed);
+                        layer.set_procedural(procedural);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::LayerFontChanged(index, font) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(mut procedural) = layer.procedural() {
+                        procedural.set_font(font);
+                        layer.set_procedural(procedural);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::LayerTextEdited(index, action) => {
+                let is_edit = action.is_edit();
+                self.text_editor.perform(action);
+
+                if is_edit {
+                    let content = self.text_editor.text();
+                    // The editor always ends its text with a line break.
+                    let content = content.strip_suffix('\n').unwrap_or(&content).to_string();
+
+                    if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                        if let Some(Procedural::Text(text)) = layer.procedural() {
+                            layer.set_procedural(Procedural::Text(Text {
+                                content: content.into(),
+                                ..text
+                            }));
+                        }
+                    }
+                }
+
+                Task::none()
+            }
+            Message::LayerTextStyleChanged(index, style) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Text(text)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Text(Text { style, ..text }));
+                    }
+                }
+
+                Task::none()
+            }
+            Message::LayerTextAnimationChanged(index, animation) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Text(text)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Text(Text { animation, ..text }));
+                    }
+                }
+
+                Task::none()
+            }
+            Message::FontQueryChanged(query) => {
+                self.font_query = query;
+
+                Task::none()
+            }
+            Message::ImportFont => Task::perform(open_font_file(), Message::FontFileOpened),
+            Message::FontFileOpened(result) => {
+                self.notify_error(t("error-import-font"), &result);
+
+                let Ok((path, data)) = result else {
+                    return Task::none();
+                };
+                let Some(family) = fonts::families_in(&data).first().map(|family| family.name)
+                else {
+                    self.notify(
+                        ToastKind::Error,
+                        t_args("error-not-a-font", &[("path", &path.display())]),
+                    );
+                    return Task::none();
+                };
+
+                tracing::info!("imported font {} from {}", family, path.display());
+                self.fonts.retain(|font| font.family != family);
+                self.fonts.push(EmbeddedFont {
+                    family,
+                    data: data.clone(),
+                });
+                self.update_font_families();
+
+                iced::font::load(data.as_ref().clone()).map(Message::FontLoaded)
+            }
+            Message::FontLoaded(result) => {
+                if result.is_err() {
+                    self.notify(ToastKind::Error, t("error-load-font"));
+                }
+
+                Task::none()
+            }
+            Message::LayerAnimationChanged(index, animation) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.animation = animation;
+                }
+
+                Task::none()
+            }
+            Message::LayerTimingChanged(index, timing) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.timing = timing;
+                }
+
+                Task::none()
+            }
+            Message::LayerVisibilityChanged(index, visibility) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.visibility = visibility;
+                }
+
+                Task::none()
+            }
+            Message::LayerPlaybackChanged(index, playback) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.playback = playback;
+                }
+
+                Task::none()
+            }
+            Message::LayerAudioChannelSelected(index, channel) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.audio_channel = channel;
+                }
+
+                Task::none()
+            }
+            Message::LayerAudioSourceSelected(index, source) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.audio_source = source;
+                }
+
+                Task::none()
+            }
+            Message::ToggleRecording => {
+                self.is_recording = !self.is_recording;
+
+                Task::none()
+            }
+            Message::LayerPropertyInput(index, property, input) => {
+                self.remove_expression(index, property);
+
+                let parsed = match self.canvas_length(property) {
+                    Some(canvas_length) => Unit::parse(&input, canvas_length),
+                    None => input.trim().parse().ok().map(|value| (value, Unit::Pixels)),
+                };
+                match parsed {
+                    Some((value, unit)) => {
+                        self.change_property(index, property, value);
+                        let source = self.link_source(index, property);
+                        if let Some(layer) = self.canvas_state.layers.get_mut(source) {
+                            layer.units.set(property, unit);
+                        }
+                    }
+                    // Anything else is kept as an expression, even before it
+                    // can be read, so it can be typed out.
+                    None => {
+                        self.links
+                            .retain(|link| !(link.layer == index && link.property == property));
+                        if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                            layer.keyframes.stop_animating(property);
+                        }
+                        self.expressions.push(LayerExpression {
+                            layer: index,
+                            property,
+                            expression: Expression::parse(&input),
+                        });
+                    }
+                }
+
+                Task::none()
+            }
+            Message::LayerWidthInput(index, input) => {
+                if let Some((width, unit)) = Unit::parse(&input, self.canvas_width) {
+                    if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                        layer.units.width = unit;
+                    }
+                    self.set_layer_width(index, width);
+                }
+
+                Task::none()
+            }
+            Message::LayerHeightInput(index, input) => {
+                if let Some((height, unit)) = Unit::parse(&input, self.canvas_height) {
+                    if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                        layer.units.height = unit;
+                    }
+                    self.set_layer_height(index, height);
+                }
+
+                Task::none()
+            }
+            Message::ToggleAspectLock(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.is_aspect_locked = !layer.is_aspect_locked;
+                }
+
+                Task::none()
+            }
+            Message::HoverField(field, is_hovered) => {
+                if is_hovered {
+                    self.hovered_field = Some(field);
+                } else if self.hovered_field == Some(field) {
+                    self.hovered_field = None;
+                }
+
+                Task::none()
+            }
+            Message::NudgeField(field, steps) => {
+                let index = self.selected_layer_index;
+                let multiplier = if self.modifiers.shift() {
+                    10.
+                } else if self.modifiers.control() {
+                    0.1
+                } else {
+                    1.
+                };
+                let amount = steps * field.step() * multiplier;
+                let time = self.position().as_secs_f32();
+                let Some(layer) = self.canvas_state.layers.get(index) else {
+                    return Task::none();
+                };
+
+                match field {
+                    NumericField::Property(property) => {
+                        let has_expression = self.expressions.iter().any(|expression| {
+                            expression.layer == index && expression.property == property
+                        });
+                        if !has_expression {
+                            let value = layer.value_at(property, time);
+                            self.change_property(index, property, value + amount);
+                        }
+                    }
+                    NumericField::Width => self.set_layer_width(index, layer.width + amount),
+                    NumericField::Height => self.set_layer_height(index, layer.height + amount),
+                }
+
+                Task::none()
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+
+                Task::none()
+            }
+            Message::ResetProperty(index, property) => {
+                self.remove_expression(index, property);
+                self.change_property(index, property, property.default_value());
+
+                Task::none()
+            }
+            Message::ResetTransform(index) => {
+                for property in Property::ALL {
+                    self.remove_expression(index, property);
+                    self.change_property(index, property, property.default_value());
+                }
+
+                Task::none()
+            }
+            Message::ToggleAnimated(index, property) => {
+                let time = self.position().as_secs_f32();
+
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if layer.keyframes.track(property).is_some() {
+                        layer.keyframes.stop_animating(property);
+                        if self.graph_property == Some(property) {
+                            self.graph_property = None;
+                        }
+                    } else {
+                        let value = layer.property(property);
+                        layer.keyframes.animate(property, time, value);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::ShowGraph(property) => {
+                self.graph_property = property;
+
+                Task::none()
+            }
+            Message::KeyframeMoved(index, property, keyframe, time, value) => {
+                let time = self.frame_rate.snap_seconds(time);
+
+                if let Some(track) = self
+                    .canvas_state
+                    .layers
+                    .get_mut(index)
+                    .and_then(|layer| layer.keyframes.track_mut(property))
+                {
+                    track.move_keyframe(keyframe, time, value);
+                }
+
+                Task::none()
+            }
+            Message::KeyframeEased(index, property, keyframe, ease) => {
+                if let Some(keyframe) = self
+                    .canvas_state
+                    .layers
+                    .get_mut(index)
+                    .and_then(|layer| layer.keyframes.track_mut(property))
+                    .and_then(|track| track.keyframes.get_mut(keyframe))
+                {
+                    keyframe.ease = ease;
+                }
+
+                Task::none()
+            }
+            Message::KeyframeRemoved(index, property, keyframe) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(track) = layer.keyframes.track_mut(property) {
+                        if keyframe < track.keyframes.len() {
+                            track.keyframes.remove(keyframe);
+                        }
+                        if track.keyframes.is_empty() {
+                            layer.keyframes.stop_animating(property);
+                        }
+                    }
+                }
+
+                Task::none()
+            }
+            Message::CopyKeyframes(index, property) => {
+                if let Some(track) = self
+                    .canvas_state
+                    .layers
+                    .get(index)
+                    .and_then(|layer| layer.keyframes.track(property))
+                {
+                    self.copied_motion = Some(CopiedMotion::Keyframes(track.keyframes.clone()));
+                }
+
+                Task::none()
+            }
+            Message::PasteKeyframes(index, property, reversed) => {
+                let time = self.position().as_secs_f32();
+
+                if let (Some(layer), Some(CopiedMotion::Keyframes(keyframes))) =
+                    (self.canvas_state.layers.get_mut(index), &self.copied_motion)
+                {
+                    layer.keyframes.paste(property, keyframes, time, reversed);
+                }
+
+                Task::none()
+            }
+            Message::CopyMotion(index) => {
+                if let Some(layer) = self.canvas_state.layers.get(index) {
+                    self.copied_motion = Some(CopiedMotion::Layer(
+                        layer.keyframes.clone(),
+                        layer.animation,
+                    ));
+                }
+
+                Task::none()
+            }
+            Message::PasteMotion(index, reversed) => {
+                let time = self.position().as_secs_f32();
+
+                if let (Some(layer), Some(CopiedMotion::Layer(keyframes, animation))) =
+                    (self.canvas_state.layers.get_mut(index), &self.copied_motion)
+                {
+                    layer.keyframes.paste_all(keyframes, time, reversed);
+                    layer.animation = *animation;
+                }
+
+                Task::none()
+            }
+            Message::LinkProperty(layer, property, source) => {
+                if self.link_source(source, property) != layer {
+                    self.remove_expression(layer, property);
+                    self.links
+                        .retain(|link| !(link.layer == layer && link.property == property));
+                    self.links.push(Link {
+                        layer,
+                        property,
+                        source,
+                    });
+                }
+
+                Task::none()
+            }
+            Message::UnlinkProperty(layer, property) => {
+                self.links
+                    .retain(|link| !(link.layer == layer && link.property == property));
+
+                Task::none()
+            }
+            Message::CopyStyle(index) => {
+                if let Some(layer) = self.canvas_state.layers.get(index) {
+                    self.copied_style = Some(LayerStyle {
+                        appearance: layer.appearance,
+                        opacity: layer.opacity,
+                        audio_channel: layer.audio_channel,
+                        audio_source: layer.audio_source,
+                    });
+                }
+
+                Task::none()
+            }
+            Message::PasteStyle(index) => {
+                let Some(style) = self.copied_style else {
+                    return Task::none();
+                };
+
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance = style.appearance;
+                    layer.audio_channel = style.audio_channel;
+                    layer.audio_source = style.audio_source;
+                    self.change_property(index, Property::Opacity, style.opacity);
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::OpenColorPicker(field) => {
+                self.color_picker = Some(field);
+
+                Task::none()
+            }
+            Message::CloseColorPicker => {
+                self.color_picker = None;
+
+                Task::none()
+            }
+            Message::ColorPicked(field, color) => {
+                self.color_picker = None;
+                self.swatch_menu = None;
+                self.swatch_uses
+                    .retain(|swatch_use| swatch_use.field != field);
+                self.set_color(field, color);
+
+                Task::none()
+            }
+            Message::UseSwatch(field, swatch) => {
+                self.swatch_menu = None;
+                self.swatch_uses
+                    .retain(|swatch_use| swatch_use.field != field);
+                self.swatch_uses.push(SwatchUse { field, swatch });
+
+                Task::none()
+            }
+            Message::AddSwatch(field) => {
+                let color = field
+                    .and_then(|field| self.color(field))
+                    .unwrap_or(Color::WHITE);
+                self.swatches.push(Swatch {
+                    name: self.swatch_name(),
+                    color,
+                });
+                if let Some(field) = field {
+                    self.swatch_menu = None;
+                    self.swatch_uses
+                        .retain(|swatch_use| swatch_use.field != field);
+                    self.swatch_uses.push(SwatchUse {
+                        field,
+                        swatch: self.swatches.len() - 1,
+                    });
+                }
+
+                Task::none()
+            }
+            Message::SwatchNameChanged(index, name) => {
+                if let Some(swatch) = self.swatches.get_mut(index) {
+                    swatch.name = name;
+                }
+
+                Task::none()
+            }
+            Message::ToggleSwatchMenu(field) => {
+                self.swatch_menu = (self.swatch_menu != Some(field)).then_some(field);
+
+                Task::none()
+            }
+            Message::ExtractPalette(id) => {
+                let Some(asset) = self.assets.get(id) else {
+                    return Task::none();
+                };
+                let Some(contents) = asset.bytes() else {
+                    return Task::none();
+                };
+
+                Task::perform(
+                    palette::extract(contents.clone(), asset.preview.clone()),
+                    Message::PaletteExtracted,
+                )
+            }
+            Message::PaletteExtracted(colors) => {
+                let Some(colors) = colors.filter(|colors| !colors.is_empty()) else {
+                    self.notify(ToastKind::Error, t("palette-failed"));
+                    return Task::none();
+                };
+
+                let mut added = 0;
+                for color in colors {
+                    if !self.swatches.iter().any(|swatch| swatch.color == color) {
+                        self.swatches.push(Swatch {
+                            name: self.swatch_name(),
+                            color,
+                        });
+                        added += 1;
+                    }
+                }
+                self.sidebar_tab = SidebarTab::Swatches;
+                self.notify(
+                    ToastKind::Success,
+                    t_args("palette-extracted", &[("count", &added)]),
+                );
+
+                Task::none()
+            }
+            Message::RemoveSwatch(index) => {
+                if index < self.swatches.len() {
+                    self.swatches.remove(index);
+                }
+                // Colors that followed the swatch keep its last color.
+                self.swatch_uses
+                    .retain(|swatch_use| swatch_use.swatch != index);
+                for swatch_use in &mut self.swatch_uses {
+                    if swatch_use.swatch > index {
+                        swatch_use.swatch -= 1;
+                    }
+                }
+
+                Task::none()
+            }
+            Message::LayerSizingSelected(index, sizing) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.set_sizing(sizing);
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+            Message::FitLayerToCanvas(index) => {
+                self.change_property(index, Property::X, 0.);
+                self.change_property(index, Property::Y, 0.);
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.width = self.canvas_width;
+                    layer.height = self.canvas_height;
+                    self.refresh_layer_image(index);
+                }
+
+                Task::none()
+            }
+        }
+    }
+
+    /// Sets a property of a layer, as a keyframe at the playhead if the
+    /// property is animated or keyframes are being recorded.
+    fn change_property(&mut self, index: usize, property: Property, value: f32) {
+        // Linked properties are changed where they are linked to, and follow.
+        let index = self.link_source(index, property);
+        let time = self.position().as_secs_f32();
+        let Some(layer) = self.canvas_state.layers.get_mut(index) else {
+            return;
+        };
+
+        if self.is_recording && layer.keyframes.track(property).is_none() {
+            // Keeps the property as it was up to the first recorded change.
+            let previous = layer.property(property);
+            layer.keyframes.animate(property, 0., previous);
+        }
+
+        match layer.keyframes.track_mut(property) {
+            Some(track) => track.set(time, value),
+            None => layer.set_property(property, value),
+        }
+    }
+
+    /// The layer whose `property` the layer at `index` follows, through any
+    /// chain of links, or `index` itself if it isn't linked.
+    fn link_source(&self, index: usize, property: Property) -> usize {
+        let mut source = index;
+        // Links never form a cycle, but a broken project file could hold one.
+        for _ in 0..=self.links.len() {
+            match self
+                .links
+                .iter()
+                .find(|link| link.layer == source && link.property == property)
+            {
+                Some(link) => source = link.source,
+                None => break,
+            }
+        }
+
+        source
+    }
+
+    /// Resizes a layer to `width` pixels, and its height with it if its
+    /// aspect ratio is locked.
+    fn set_layer_width(&mut self, index: usize, width: f32) {
+        let Some(layer) = self.canvas_state.layers.get_mut(index) else {
+            return;
+        };
+        let width = width.max(1.);
+        if layer.is_aspect_locked {
+            layer.height = (layer.height * width / layer.width).max(1.);
+        }
+        layer.width = width;
+        self.refresh_layer_image(index);
+    }
+
+    fn set_layer_height(&mut self, index: usize, height: f32) {
+        let Some(layer) = self.canvas_state.layers.get_mut(index) else {
+            return;
+        };
+        let height = height.max(1.);
+        if layer.is_aspect_locked {
+            layer.width = (layer.width * height / layer.height).max(1.);
+        }
+        layer.height = height;
+        self.refresh_layer_image(index);
+    }
+
+    /// The side of the canvas that a position property is a percentage of.
+    fn canvas_length(&self, property: Property) -> Option<f32> {
+        match property {
+            Property::X => Some(self.canvas_width),
+            Property::Y => Some(self.canvas_height),
+            Property::Scale | Property::Opacity => None,
+        }
+    }
+
+    /// Lists the imported fonts' families before the installed ones.
+    fn update_font_families(&mut self) {
+        let imported: Vec<_> = self
+            .fonts
+            .iter()
+            .flat_map(|font| fonts::families_in(&font.data))
+            .collect();
+
+        self.font_families.retain(|family| !family.is_imported);
+        self.font_families.splice(0..0, imported);
+    }
+
+    /// Makes the fonts imported into the project available for drawing.
+    fn load_fonts(&self) -> Task<Message> {
+        Task::batch(
+            self.fonts
+                .iter()
+                .map(|font| iced::font::load(font.data.as_ref().clone()).map(Message::FontLoaded)),
+        )
+    }
+
+    fn remove_expression(&mut self, index: usize, property: Property) {
+        self.expressions
+            .retain(|expression| !(expression.layer == index && expression.property == property));
+    }
+
+    /// Sets every property that has an expression to its value at the
+    /// playhead. Those that can't be worked out keep their last value.
+    fn apply_expressions(&mut self) {
+        for expression in &self.expressions {
+            let Some(layer) = self.canvas_state.layers.get(expression.layer) else {
+                continue;
+            };
+            let playhead = self.canvas_state.playhead.for_layer(layer);
+            let variables = Variables {
+                time: playhead.time.as_secs_f32(),
+                canvas_width: self.canvas_width,
+                canvas_height: self.canvas_height,
+                width: layer.width,
+                height: layer.height,
+                bass: playhead.bass(),
+                level: playhead.decibels().unwrap_or(-100.).max(-100.),
+                beat: playhead.since_beat().unwrap_or(0.),
+                tempo: playhead.tempo().unwrap_or(0.),
+            };
+
+            if let Some(value) = expression.expression.evaluate(&variables) {
+                self.canvas_state.layers[expression.layer].set_property(expression.property, value);
+            }
+        }
+    }
+
+    /// Copies every linked property, keyframes included, from the layer it is
+    /// linked to.
+    fn sync_links(&mut self) {
+        for link in &self.links {
+            let source = self.link_source(link.layer, link.property);
+            let Some(source) = self.canvas_state.layers.get(source) else {
+                continue;
+            };
+            let value = source.property(link.property);
+            let track = source.keyframes.track(link.property).cloned();
+            let unit = source.units.of(link.property);
+
+            if let Some(layer) = self.canvas_state.layers.get_mut(link.layer) {
+                layer.set_property(link.property, value);
+                if let Some(unit) = unit {
+                    layer.units.set(link.property, unit);
+                }
+                layer.keyframes.set_track(link.property, track.as_ref());
+            }
+        }
+    }
+
+    /// Gives every color following a swatch the swatch's color.
+    fn sync_swatches(&mut self) {
+        for index in 0..self.swatch_uses.len() {
+            let SwatchUse { field, swatch } = self.swatch_uses[index];
+            let Some(color) = self.swatches.get(swatch).map(|swatch| swatch.color) else {
+                continue;
+            };
+
+            if self.color(field).is_some_and(|current| current != color) {
+                self.set_color(field, color);
+            }
+        }
+    }
+
+    /// A name for a new swatch that no other swatch has.
+    fn swatch_name(&self) -> String {
+        (1..)
+            .map(|number| t_args("swatch-name", &[("number", &number)]))
+            .find(|name| self.swatches.iter().all(|swatch| swatch.name != *name))
+            .unwrap_or_default()
+    }
+
+    /// Keeps the text editor on the content of the selected text layer, as
+    /// other layers are selected or the content changes elsewhere, e.g. on
+    /// undo.
+    fn sync_text_editor(&mut self) {
+        let Some(Procedural::Text(text)) = self
+            .canvas_state
+            .layers
+            .get(self.selected_layer_index)
+            .and_then(|layer| layer.procedural())
+        else {
+            return;
+        };
+
+        let edited = self.text_editor.text();
+        if edited.strip_suffix('\n').unwrap_or(&edited) != text.content {
+            self.text_editor = text_editor::Content::with_text(&text.content);
+        }
+    }
+
+    fn color(&self, field: ColorField) -> Option<Color> {
+        match field {
+            ColorField::Border(index) => self
+                .canvas_state
+                .layers
+                .get(index)
+                .map(|layer| layer.appearance.border.color),
+            ColorField::Tint(index) => self
+                .canvas_state
+                .layers
+                .get(index)
+                .map(|layer| layer.appearance.tint.color),
+            ColorField::TintShadow(index) => self
+                .canvas_state
+                .layers
+                .get(index)
+                .map(|layer| layer.appearance.tint.shadow),
+            ColorField::ChromaKey(index) => self
+                .canvas_state
+                .layers
+                .get(index)
+                .map(|layer| layer.appearance.chroma_key.color),
+            ColorField::Vignette(index) => {
+                match self.canvas_state.layers.get(index)?.procedural()? {
+                    Procedural::Vignette(vignette) => Some(vignette.color),
+                    _ => None,
+                }
+            }
+            ColorField::Readout(index) => {
+                match self.canvas_state.layers.get(index)?.procedural()? {
+                    Procedural::Readout(readout) => Some(readout.color),
+                    _ => None,
+                }
+            }
+            ColorField::Text(index) => match self.canvas_state.layers.get(index)?.procedural()? {
+                Procedural::Text(text) => Some(text.color),
+                _ => None,
+            },
+            ColorField::TextOutline(index) => {
+                match self.canvas_state.layers.get(index)?.procedural()? {
+                    Procedural::Text(text) => Some(text.style.outline_color),
+                    _ => None,
+                }
+            }
+            ColorField::TextBackground(index) => {
+                match self.canvas_state.layers.get(index)?.procedural()? {
+                    Procedural::Text(text) => Some(text.style.background_color),
+                    _ => None,
+                }
+            }
+            ColorField::Timer(index) => match self.canvas_state.layers.get(index)?.procedural()? {
+                Procedural::Timer(timer) => Some(timer.color),
+                _ => None,
+            },
+            ColorField::Wave(index) => match self.canvas_state.layers.get(index)?.procedural()? {
+                Procedural::Wave(wave) => Some(wave.color),
+                _ => None,
+            },
+            ColorField::Progress(index) => {
+                match self.canvas_state.layers.get(index)?.procedural()? {
+                    Procedural::Progress(progress) => Some(progress.color),
+                    _ => None,
+                }
+            }
+            ColorField::ProgressTrack(index) => {
+                match self.canvas_state.layers.get(index)?.procedural()? {
+                    Procedural::Progress(progress) => Some(progress.track_color),
+                    _ => None,
+                }
+            }
+            ColorField::Swatch(index) => self.swatches.get(index).map(|swatch| swatch.color),
+        }
+    }
+
+    fn set_color(&mut self, field: ColorField, color: Color) {
+        match field {
+            ColorField::Border(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.border.color = color;
+                }
+            }
+            ColorField::Tint(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.tint.color = color;
+                    self.refresh_layer_image(index);
+                }
+            }
+            ColorField::TintShadow(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.tint.shadow = color;
+                    self.refresh_layer_image(index);
+                }
+            }
+            ColorField::ChromaKey(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    layer.appearance.chroma_key.color = color;
+                    self.refresh_layer_image(index);
+                }
+            }
+            ColorField::Vignette(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Vignette(vignette)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Vignette(Vignette { color, ..vignette }));
+                    }
+                }
+            }
+            ColorField::Readout(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Readout(readout)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Readout(Readout { color, ..readout }));
+                    }
+                }
+            }
+            ColorField::Text(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Text(text)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Text(Text { color, ..text }));
+                    }
+                }
+            }
+            ColorField::TextOutline(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Text(mut text)) = layer.procedural() {
+                        text.style.outline_color = color;
+                        layer.set_procedural(Procedural::Text(text));
+                    }
+                }
+            }
+            ColorField::TextBackground(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Text(mut text)) = layer.procedural() {
+                        text.style.background_color = color;
+                        layer.set_procedural(Procedural::Text(text));
+                    }
+                }
+            }
+            ColorField::Timer(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Timer(timer)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Timer(Timer { color, ..timer }));
+                    }
+                }
+            }
+            ColorField::Wave(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Wave(wave)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Wave(Wave { color, ..wave }));
+                    }
+                }
+            }
+            ColorField::Progress(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Progress(progress)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Progress(Progress { color, ..progress }));
+                    }
+                }
+            }
+            ColorField::ProgressTrack(index) => {
+                if let Some(layer) = self.canvas_state.layers.get_mut(index) {
+                    if let Some(Procedural::Progress(progress)) = layer.procedural() {
+                        layer.set_procedural(Procedural::Progress(Progress {
+                            track_color: color,
+                            ..progress
+                        }));
+                    }
+                }
+            }
+            ColorField::Swatch(index) => {
+                if let Some(swatch) = self.swatches.get_mut(index) {
+                    swatch.color = color;
+                }
+            }
+        }
+    }
+
+    /// A swatch of the color in `field` which opens a color picker for it.
+    fn color_input(&self, field: ColorField) -> Element<'_, Message> {
+        let color = self.color(field).unwrap_or(Color::WHITE);
+        let swatch = button(
+            container(horizontal_space())
+                .width(18.)
+                .height(18.)
+                .style(swatch_style(color)),
+        )
+        .padding(3.)
+        .style(button::secondary)
+        .on_press(Message::OpenColorPicker(field));
+
+        let picker = ColorPicker::new(
+            self.color_picker == Some(field),
+            color,
+            swatch,
+            Message::CloseColorPicker,
+            move |color| Message::ColorPicked(field, color),
+        );
+        if field.layer().is_none() {
+            return picker.into();
+        }
+
+        let followed = self
+            .swatch_uses
+            .iter()
+            .find(|swatch_use| swatch_use.field == field)
+            .map(|swatch_use| swatch_use.swatch);
+        let input = row![]
+            .push_maybe(
+                followed
+                    .and_then(|swatch| self.swatches.get(swatch))
+                    .map(|swatch| text(&swatch.name).size(12).style(text::secondary)),
+            )
+            .push(picker)
+            .push(icon_button_with_tooltip(
+                "palette",
+                t("swatches-pick"),
+                Some(Message::ToggleSwatchMenu(field)),
+            ))
+            .spacing(3.)
+            .align_y(Alignment::Center);
+        if self.swatch_menu != Some(field) {
+            return input.into();
+        }
+
+        let swatches = self
+            .swatches
+            .iter()
+            .enumerate()
+            .fold(row![], |row, (index, swatch)| {
+                row.push(tooltip(
+                    swatch_chip(swatch.color, 14.).on_press(Message::UseSwatch(field, index)),
+                    text(&swatch.name),
+                    tooltip::Position::Top,
+                ))
+            })
+            .push(icon_button_with_tooltip(
+                "plus",
+                t("swatch-save"),
+                Some(Message::AddSwatch(Some(field))),
+            ));
+
+        column![input, swatches.spacing(3.).wrap()]
+            .spacing(3.)
+            .align_x(Alignment::End)
+            .into()
+    }
+
+    fn audio(&self) -> Option<&Asset> {
+        self.audio_asset.and_then(|id| self.assets.get(id))
+    }
+
+    /// Length of the track, once the audio file has been analyzed and
+    /// trimmed.
+    fn duration(&self) -> Option<Duration> {
+        self.analysis
+            .as_ref()
+            .map(|analysis| self.audio_trim.length(analysis.duration))
+    }
+
+    /// What each transport button does, in the order the arrow keys move
+    /// through them.
+    fn transport_actions(&self) -> [Message; 6] {
+        [
+            Message::TogglePlayback,
+            Message::ToggleRecording,
+            Message::ToggleMute,
+            Message::StepFrames(-1),
+            Message::StepFrames(1),
+            Message::CycleTimeFormat,
+        ]
+    }
+
+    fn is_transport_focused(&self, index: usize) -> bool {
+        self.focused_panel == Some(Panel::Transport) && self.transport_focus == index
+    }
+
+    /// Selects the layer `delta` rows away from the selected one and scrolls
+    /// the layer list to keep it in view.
+    fn move_layer_selection(&mut self, delta: i64) -> Task<Message> {
+        let listed = self.listed_layers();
+        let Some(last) = listed.len().checked_sub(1) else {
+            return Task::none();
+        };
+        let row = match listed
+            .iter()
+            .position(|index| *index == self.selected_layer_index)
+        {
+            Some(row) => row.saturating_add_signed(delta as isize).min(last),
+            // A layer that is filtered out moves the selection onto the list.
+            None if delta < 0 => last,
+            None => 0,
+        };
+        let index = listed[row];
+        let name = self.layer_names[index].clone();
+        let selected = self.handle(Message::LayerSelected(index, name));
+
+        let (offset, height) = self.layer_list_viewport;
+        let top = row as f32 * LAYER_ROW_HEIGHT;
+        let y = if top < offset {
+            top
+        } else if top + LAYER_ROW_HEIGHT > offset + height {
+            top + LAYER_ROW_HEIGHT - height
+        } else {
+            return selected;
+        };
+
+        Task::batch([
+            selected,
+            scrollable::scroll_to(layer_list_id(), scrollable::AbsoluteOffset { x: 0., y }),
+        ])
+    }
+
+    /// Indices of the layers matching the layer list's filter, in order.
+    fn listed_layers(&self) -> Vec<usize> {
+        let query = self.layer_query.to_lowercase();
+
+        self.canvas_state
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| {
+                self.layer_category.includes(&layer.kind)
+                    && layer.name.to_lowercase().contains(&query)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Every action the command palette offers in the current state.
+    fn commands(&self) -> Vec<Command> {
+        let mut commands = vec![
+            Command::new(t("project-new"), Message::NewProject),
+            Command::new(t("project-open"), Message::OpenProject),
+            Command::new(t("project-save"), Message::SaveProject),
+            Command::new(t("command-add-image"), Message::AddImageLayer),
+            Command::new(t("command-paste-image"), Message::PasteImage).shortcut("Ctrl+V"),
+            Command::new(t("command-import-psd"), Message::ImportPsd),
+            Command::new(t("command-add-lottie"), Message::AddLottieLayer),
+            Command::new(t("command-add-screen-capture"), Message::AddCaptureLayer),
+            Command::new(t("command-add-camera"), Message::AddCameraLayer),
+            Command::new(t("command-add-backdrop"), Message::AddBackdropLayer),
+        ];
+        commands.extend(Procedural::ALL.into_iter().map(|procedural| {
+            Command::new(
+                t_args("command-add-overlay", &[("overlay", &procedural)]),
+                Message::AddProceduralLayer(procedural),
+            )
+        }));
+        if self
+            .canvas_state
+            .layers
+            .get(self.selected_layer_index)
+            .is_some()
+        {
+            commands.extend([
+                Command::new(
+                    t("command-delete-layer"),
+                    Message::RemoveLayer(self.selected_layer_index),
+                ),
+                Command::new(
+                    t("command-fit-layer"),
+                    Message::FitLayerToCanvas(self.selected_layer_index),
+                ),
+                Command::new(
+                    t("command-copy-style"),
+                    Message::CopyStyle(self.selected_layer_index),
+                ),
+            ]);
+            if let Some(LayerKind::Image { .. }) = self
+                .canvas_state
+                .layers
+                .get(self.selected_layer_index)
+                .map(|layer| &layer.kind)
+            {
+                commands.push(Command::new(
+                    t("command-add-blurred-backdrop"),
+                    Message::AddBlurredBackdrop(self.selected_layer_index),
+                ));
+            }
+            if self.copied_style.is_some() {
+                commands.push(Command::new(
+                    t("command-paste-style"),
+                    Message::PasteStyle(self.selected_layer_index),
+                ));
+            }
+        }
+
+        commands.extend([
+            Command::new(
+                if self.transport.is_playing() {
+                    t("pause")
+                } else {
+                    t("play")
+                },
+                Message::TogglePlayback,
+            ),
+            Command::new(t("command-previous-frame"), Message::StepFrames(-1)).shortcut("←"),
+            Command::new(t("command-next-frame"), Message::StepFrames(1)).shortcut("→"),
+            Command::new(
+                if self.is_recording {
+                    t("stop-recording")
+                } else {
+                    t("command-record-keyframes")
+                },
+                Message::ToggleRecording,
+            ),
+            Command::new(t("command-time-format"), Message::CycleTimeFormat),
+        ]);
+        commands.extend(PlaybackRate::ALL.into_iter().map(|rate| {
+            Command::new(
+                t_args("command-preview-speed", &[("speed", &rate)]),
+                Message::PlaybackRateSelected(rate),
+            )
+        }));
+        commands.extend(FrameRate::ALL.into_iter().map(|frame_rate| {
+            Command::new(
+                t_args("command-frame-rate", &[("rate", &frame_rate)]),
+                Message::FrameRateSelected(frame_rate),
+            )
+        }));
+
+        if self.audio_asset.is_some() {
+            commands.push(Command::new(t("audio-remove"), Message::RemoveAudioFile));
+        } else {
+            commands.push(Command::new(t("audio-select"), Message::OpenAudioFile));
+        }
+        commands.extend([
+            Command::new(t("track-add"), Message::AddAudioTrack),
+            Command::new(
+                t("command-show-layers"),
+                Message::SidebarTabSelected(SidebarTab::Layers),
+            ),
+            Command::new(
+                t("command-show-assets"),
+                Message::SidebarTabSelected(SidebarTab::Assets),
+            ),
+            Command::new(
+                t("command-show-swatches"),
+                Message::SidebarTabSelected(SidebarTab::Swatches),
+            ),
+            Command::new(
+                t("command-show-snapshots"),
+                Message::SidebarTabSelected(SidebarTab::Snapshots),
+            ),
+            Command::new(t("command-take-snapshot"), Message::TakeSnapshot),
+            Command::new(t("command-audiogram"), Message::OpenAudiogramWizard),
+            Command::new(
+                t("command-landscape"),
+                Message::SetOrientation(Orientation::Landscape),
+            ),
+            Command::new(
+                t("command-portrait"),
+                Message::SetOrientation(Orientation::Portrait),
+            ),
+            Command::new(
+                t("command-square"),
+                Message::SetOrientation(Orientation::Square),
+            ),
+            Command::new(t("command-render-thumbnail"), Message::RenderThumbnail),
+            Command::new(t("command-export-lottie"), Message::ExportLottie),
+            Command::new(t("command-pin-comparison"), Message::PinComparison),
+            Command::new(t("command-switch-comparison"), Message::ToggleCompareView),
+            Command::new(t("command-toggle-stats"), Message::ToggleStats).shortcut("F3"),
+            Command::new(t("command-toggle-post-fx"), Message::TogglePostFx),
+            Command::new(t("copy-diagnostics"), Message::CopyDiagnostics),
+            Command::new(t("command-focus-next-panel"), Message::FocusPanel(1)).shortcut("F6"),
+            Command::new(
+                if self.preview_window.is_some() {
+                    t("preview-attach")
+                } else {
+                    t("preview-detach")
+                },
+                Message::TogglePreviewWindow,
+            ),
+        ]);
+
+        commands
+    }
+
+    /// Opens a file passed on the command line or dropped on the window as a
+    /// project, the audio track or an image layer, depending on its
+    /// extension.
+    fn open_file(&mut self, path: PathBuf) -> Task<Message> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if extension == "ron" {
+            Task::perform(project::load_project(path), Message::ProjectOpened)
+        } else if LOTTIE_EXTENSIONS.contains(&extension.as_str()) {
+            Task::perform(load_file(path), Message::LottieFileOpened)
+        } else if extension == "psd" {
+            Task::perform(psd::import(path), Message::PsdImported)
+        } else if AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+            self.start_file_load(
+                move |progress| stream_file(path, progress),
+                Message::AudioFileOpened,
+            )
+        } else if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            // Dropping several files drops them one after another, so they
+            // are gathered for a moment to be laid out together.
+            self.dropped_images.push(path);
+            if self.dropped_images.len() > 1 {
+                return Task::none();
+            }
+
+            Task::perform(tokio::time::sleep(Duration::from_millis(100)), |_| {
+                Message::ImportDroppedImages
+            })
+        } else if UNSUPPORTED_IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            Task::done(Message::Notify(
+                ToastKind::Error,
+                t_args(
+                    "error-unsupported-image",
+                    &[
+                        ("path", &path.display()),
+                        ("format", &extension.to_ascii_uppercase()),
+                    ],
+                ),
+            ))
+        } else {
+            Task::done(Message::Notify(
+                ToastKind::Error,
+                t_args("error-unsupported-file", &[("path", &path.display())]),
+            ))
+        }
+    }
+
+    /// Runs the future made by `load` as the file load, in place of the one
+    /// before it, if any. `load` is given the progress to keep.
+    fn start_file_load<T: Send + 'static, F>(
+        &mut self,
+        load: impl FnOnce(Arc<LoadProgress>) -> F,
+        to_message: impl Fn(u64, T) -> Message + Send + 'static,
+    ) -> Task<Message>
+    where
+        F: std::future::Future<Output = T> + Send + 'static,
+    {
+        self.cancel_file_load();
+        self.file_loads_started += 1;
+        let id = self.file_loads_started;
+        let progress = Arc::new(LoadProgress::default());
+
+        let (task, handle) =
+            Task::perform(load(progress.clone()), move |result| to_message(id, result)).abortable();
+        self.file_load = Some(FileLoad {
+            id,
+            handle,
+            progress,
+        });
+
+        task
+    }
+
+    fn cancel_file_load(&mut self) {
+        if let Some(load) = self.file_load.take() {
+            load.progress.cancel();
+            load.handle.abort();
+        }
+    }
+
+    /// Whether the load with `id` that finished is the current one, which
+    /// it is no longer after.
+    fn finish_file_load(&mut self, id: u64) -> bool {
+        match &self.file_load {
+            Some(load) if load.id == id => {
+                self.file_load = None;
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Shows a toast, and logs it so it ends up in the diagnostics.
+    fn notify(&mut self, kind: ToastKind, message: impl Into<String>) {
+        let message = message.into();
+        match kind {
+            ToastKind::Error => tracing::error!("{}", message),
+            ToastKind::Info | ToastKind::Success => tracing::info!("{}", message),
+        }
+
+        self.toasts.push(kind, message);
+    }
+
+    /// Tells the user why `result` failed, unless they closed a file dialog.
+    fn notify_error<T>(&mut self, action: &str, result: &Result<T, Error>) {
+        match result {
+            Err(Error::DialogClosed | Error::Cancelled) | Ok(_) => {}
+            Err(error) => self.notify(ToastKind::Error, format!("{}: {}", action, error)),
+        }
+    }
+
+    /// Adds `scene` in order of start, keeping the layers in the scenes they
+    /// were in.
+    fn insert_scene(&mut self, scene: Scene) {
+        let index = self
+            .scenes
+            .partition_point(|existing| existing.start <= scene.start);
+        self.scenes.insert(index, scene);
+        for layer in &mut self.canvas_state.layers {
+            if let Some(scene) = layer.timing.scene.as_mut().filter(|scene| **scene >= index) {
+                *scene += 1;
+            }
+        }
+    }
+
+    /// The LUT in a picked .cube file, telling the user if it can't be read.
+    fn read_lut(&mut self, result: Result<LoadedFile, Error>) -> Option<Lut> {
+        self.notify_error(t("error-open-lut"), &result);
+        let (path, contents) = result.ok()?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        match Lut::parse(name, String::from_utf8_lossy(&contents).into_owned()) {
+            Ok(lut) => Some(lut),
+            Err(error) => {
+                self.notify(
+                    ToastKind::Error,
+                    t_args(
+                        "error-read-file",
+                        &[("path", &path.display()), ("error", &error)],
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        let analysis = std::iter::once(&self.analysis)
+            .chain(self.audio_tracks.iter().map(|track| &track.analysis))
+            .flatten()
+            .map(|analysis| analysis.memory())
+            .sum();
+
+        MemoryUsage {
+            files: self.assets.file_memory(),
+            previews: self.assets.preview_memory(),
+            renders: self.renders.memory(),
+            analysis,
+        }
+    }
+
+    /// Frees memory until the project fits in its budget, first by dropping
+    /// the least recently used renders, which are quick to make again, then
+    /// the previews of images no layer shows and last by downscaling the
+    /// largest previews still in use. The files themselves are never dropped.
+    fn enforce_memory_budget(&mut self) {
+        let budget = self.memory_budget as usize * 1024 * 1024;
+        let usage = self.memory_usage();
+        if usage.total() <= budget {
+            return;
+        }
+        self.renders
+            .trim(budget.saturating_sub(usage.total() - usage.renders));
+
+        let unused: Vec<_> = self
+            .assets
+            .iter()
+            .filter(|asset| asset.preview.is_some() && self.asset_usage(asset.id) == 0)
+            .map(|asset| asset.id)
+            .collect();
+        for id in unused {
+            if self.memory_usage().total() <= budget {
+                return;
+            }
+            self.assets.set_preview(id, None);
+        }
+
+        let mut downscaled_any = false;
+        while self.memory_usage().total() > budget {
+            let Some((id, preview)) = self
+                .assets
+                .iter()
+                .filter_map(|asset| Some((asset.id, asset.preview.clone()?)))
+                .filter(|(_, preview)| preview.width().max(preview.height()) > MIN_PREVIEW_SIZE)
+                .max_by_key(|(_, preview)| preview.as_raw().len())
+            else {
+                break;
+            };
+
+            let downscaled = image::imageops::thumbnail(
+                preview.as_ref(),
+                preview.width() / 2,
+                preview.height() / 2,
+            );
+            self.assets.set_preview(id, Some(Arc::new(downscaled)));
+            downscaled_any = true;
+        }
+
+        if downscaled_any {
+            self.notify(ToastKind::Info, t("memory-downscaled"));
+        }
+    }
+
+    /// Moves the canvas to the playhead and redraws the layers that changed.
+    fn update_canvas(&mut self) {
+        let main = self.analysis.clone().map(|analysis| Placement {
+            analysis,
+            start: self.audio_trim.start,
+            amplitude: 1.,
+        });
+        let tracks = self.audio_tracks.iter().map(|track| {
+            track.analysis.clone().map(|analysis| Placement {
+                analysis,
+                start: -track.mix.offset,
+                amplitude: track.mix.amplitude(),
+            })
+        });
+
+        self.canvas_state.playhead = Playhead {
+            time: self.position(),
+            tracks: std::iter::once(main).chain(tracks).collect(),
+            source: Source::default(),
+            channel: Channel::default(),
+            markers: self.markers.iter().cloned().collect(),
+            scenes: self.scenes.iter().cloned().collect(),
+        };
+        self.apply_expressions();
+
+        let started = Instant::now();
+        (self.stats.layers_drawn, self.stats.layers_redrawn) = self
+            .canvas_state
+            .update(Size::new(self.canvas_width, self.canvas_height));
+        self.stats.update_time = started.elapsed();
+        self.stats.tick(started);
+    }
+
+    /// The playhead, at the start of the frame being shown.
+    fn position(&self) -> Duration {
+        self.frame_rate
+            .snap(self.transport.position(self.duration()))
+    }
+
+    /// Plays the audio from the playhead while the transport is playing, and
+    /// stops it otherwise. The audio is only played once it has been
+    /// analyzed, which is when its length is known to loop at.
+    fn sync_output(&self) {
+        let audio = self
+            .audio()
+            .filter(|_| self.transport.is_playing() && self.analysis.is_some());
+
+        match audio {
+            Some(_) => self.output.play(
+                self.voices(),
+                self.transport.position(self.duration()),
+                self.duration(),
+                self.transport.rate().factor(),
+            ),
+            None => self.output.pause(),
+        }
+    }
+
+    /// The main track and the additional ones, as the output plays them.
+    fn voices(&self) -> Vec<Voice> {
+        let main = self.audio().map(|audio| Voice {
+            path: audio.path.clone(),
+            start: self.audio_trim.start,
+            amplitude: 1.,
+        });
+        let tracks = self.audio_tracks.iter().filter_map(|track| {
+            Some(Voice {
+                path: self.assets.get(track.asset)?.path.clone(),
+                start: -track.mix.offset,
+                amplitude: track.mix.amplitude(),
+            })
+        });
+
+        main.into_iter().chain(tracks).collect()
+    }
+
+    /// Drops the analysis of the previous audio and starts analyzing the
+    /// current one, if any.
+    fn analyze_audio(&mut self) -> Task<Message> {
+        self.analysis = None;
+        self.sync_output();
+        // The previous audio's analysis would only be thrown away.
+        if let Some(progress) = self.analysis_progress.take() {
+            progress.cancel();
+        }
+
+        match self.audio_asset {
+            Some(id) => {
+                let progress = Arc::new(LoadProgress::default());
+                self.analysis_progress = Some(progress.clone());
+
+                self.analyze_asset(id, progress)
+            }
+            None => Task::none(),
+        }
+    }
+
+    /// Same as [`Roygbiv::analyze_audio`], for every additional track.
+    fn analyze_tracks(&mut self) -> Task<Message> {
+        for track in &mut self.audio_tracks {
+            track.analysis = None;
+        }
+
+        let assets: Vec<_> = self.audio_tracks.iter().map(|track| track.asset).collect();
+
+        Task::batch(
+            assets
+                .into_iter()
+                .map(|id| self.analyze_asset(id, Arc::default())),
+        )
+    }
+
+    fn analyze_asset(&mut self, id: AssetId, progress: Arc<LoadProgress>) -> Task<Message> {
+        match self.assets.get(id) {
+            Some(audio) => {
+                let cache = audio::cache_path(self.project_path.as_deref(), audio.contents.hash());
+
+                let analysis = audio::analyze(audio.path.clone(), cache, progress);
+                self.background_tasks.analyses += 1;
+
+                Task::perform(
+                    async move {
+                        let started = Instant::now();
+                        let result = analysis.await;
+
+                        (result, started.elapsed())
+                    },
+                    move |(result, elapsed)| Message::AudioAnalyzed(id, result, elapsed),
+                )
+            }
+            None => Task::none(),
+        }
+    }
+
+    /// Drops an additional track, moving the layers that followed it over to
+    /// the main track.
+    fn remove_audio_track(&mut self, index: usize) {
+        if index >= self.audio_tracks.len() {
+            return;
+        }
+        self.audio_tracks.remove(index);
+        self.sync_output();
+
+        for layer in &mut self.canvas_state.layers {
+            layer.audio_source = layer.audio_source.without(index);
+        }
+    }
+
+    /// The full path, dimensions or duration and size of the file behind
+    /// `id`, one per line, for tooltips.
+    fn asset_details(&self, id: AssetId) -> String {
+        let Some(asset) = self.assets.get(id) else {
+            return String::new();
+        };
+
+        let extent = match asset.kind {
+            AssetKind::Image => asset
+                .bytes()
+                .and_then(|bytes| canvas::image_dimensions(bytes))
+                .map(|(width, height)| format!("{} × {}", width, height)),
+            AssetKind::Animation => asset
+                .bytes()
+                .and_then(|bytes| Lottie::parse(bytes).ok())
+                .map(|lottie| {
+                    format!(
+                        "{} × {} · {}",
+                        lottie.size().width,
+                        lottie.size().height,
+                        format_time(Duration::from_secs_f32(lottie.duration()))
+                    )
+                }),
+            AssetKind::Audio => std::iter::once(&self.analysis)
+                .filter(|_| self.audio_asset == Some(id))
+                .chain(
+                    self.audio_tracks
+                        .iter()
+                        .filter(|track| track.asset == id)
+                        .map(|track| &track.analysis),
+                )
+                .flatten()
+                .next()
+                .map(|analysis| format_time(analysis.duration)),
+        };
+
+        [
+            Some(asset.path.display().to_string()),
+            extent,
+            Some(format_size(asset.size())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    /// [`Roygbiv::asset_details`] for image layers, and the size on the
+    /// canvas for the others.
+    fn layer_details(&self, index: usize) -> String {
+        let Some(layer) = self.canvas_state.layers.get(index) else {
+            return String::new();
+        };
+
+        match layer.asset() {
+            Some(id) => self.asset_details(id),
+            None => format!("{} × {}", layer.width, layer.height),
+        }
+    }
+
+    /// Number of layers (or audio tracks) referencing `id`.
+    fn asset_usage(&self, id: AssetId) -> usize {
+        let layers = self
+            .canvas_state
+            .layers
+            .iter()
+            .filter(|layer| layer.asset() == Some(id))
+            .count();
+
+        let tracks = self
+            .audio_tracks
+            .iter()
+            .filter(|track| track.asset == id)
+            .count();
+
+        layers + tracks + usize::from(self.audio_asset == Some(id))
+    }
+
+    /// Re-renders the image of every layer using `id`, keeping their
+    /// placement, after the asset's file has been replaced.
+    fn refresh_layers(&mut self, id: AssetId) -> Task<Message> {
+        if let Some(lottie) = self
+            .assets
+            .get(id)
+            .filter(|asset| asset.kind == AssetKind::Animation)
+            .and_then(|asset| Lottie::parse(asset.bytes()?).ok())
+        {
+            for layer in &mut self.canvas_state.layers {
+                if let LayerKind::Lottie {
+                    asset,
+                    lottie: shown,
+                } = &mut layer.kind
+                {
+                    if *asset == id {
+                        *shown = lottie.clone();
+                    }
+                }
+            }
+        }
+
+        let indices: Vec<_> = (0..self.canvas_state.layers.len())
+            .filter(|index| self.canvas_state.layers[*index].asset() == Some(id))
+            .collect();
+
+        Task::batch(
+            indices
+                .into_iter()
+                .map(|index| self.decode_layer_image(index)),
+        )
+    }
+
+    /// Ends on-canvas crop editing, applying the crop to the layer's image.
+    fn stop_cropping(&mut self) {
+        if let Some(index) = self.canvas_state.cropping.take() {
+            self.refresh_layer_image(index);
+        }
+    }
+
+    /// Re-renders the image drawn for layer `index` from its asset, after its
+    /// crop, sizing, frame or source file changed.
+    fn refresh_layer_image(&mut self, index: usize) {
+        let Some((id, options, frame)) = self.image_request(index) else {
+            return;
+        };
+        let Some(asset) = self.assets.get(id) else {
+            return;
+        };
+        let Some(contents) = asset.bytes() else {
+            return;
+        };
+        let hash = asset.hash;
+        let (rendered, size) = match self.renders.get(hash, &options, frame) {
+            Some(cached) => cached,
+            None => {
+                let Some((rendered, size)) =
+                    canvas::render_image(contents, asset.preview.as_deref(), &options, frame)
+                else {
+                    return;
+                };
+                self.renders
+                    .insert(hash, options, frame, rendered.clone(), size);
+                self.enforce_memory_budget();
+
+                (rendered, size)
+            }
+        };
+
+        if let LayerKind::Image {
+            frames, image_size, ..
+        } = &mut self.canvas_state.layers[index].kind
+        {
+            *frames = rendered;
+            *image_size = size;
+        }
+    }
+
+    /// Like [`Roygbiv::refresh_layer_image`], but renders on a background
+    /// thread, for images that were just loaded and may take a while to
+    /// decode.
+    fn decode_layer_image(&mut self, index: usize) -> Task<Message> {
+        let Some((id, options, frame)) = self.image_request(index) else {
+            return Task::none();
+        };
+        let Some(asset) = self.assets.get(id) else {
+            return Task::none();
+        };
+        let Some(contents) = asset.bytes() else {
+            return Task::none();
+        };
+        self.background_tasks.decodes += 1;
+        if let Some((frames, size)) = self.renders.get(asset.hash, &options, frame) {
+            return Task::done(Message::ImageDecoded(
+                id,
+                options,
+                frame,
+                Some((frames, size, asset.preview.clone())),
+            ));
+        }
+
+        Task::perform(
+            canvas::decode_image(
+                contents.clone(),
+                asset.preview.clone(),
+                options.clone(),
+                frame,
+            ),
+            move |result| Message::ImageDecoded(id, options.clone(), frame, result),
+        )
+    }
+
+    fn decode_layer_images(&mut self) -> Task<Message> {
+        Task::batch((0..self.canvas_state.layers.len()).map(|index| self.decode_layer_image(index)))
+    }
+
+    /// The asset, options and frame size to render the image of layer
+    /// `index` with.
+    fn image_request(&self, index: usize) -> Option<(AssetId, ImageOptions, Size)> {
+        let layer = self.canvas_state.layers.get(index)?;
+        let mut options = layer.image_options()?;
+        if self.canvas_state.cropping == Some(index) {
+            options.crop = Crop::FULL;
+        }
+
+        Some((
+            layer.asset()?,
+            options,
+            Size::new(layer.width, layer.height),
+        ))
+    }
+
+    /// Adds a layer for each image, laying them out by the arrangement picked
+    /// when there are several.
+    fn add_image_layers(&mut self, files: Vec<LoadedFile>) -> Task<Message> {
+        let mut layers = vec![];
+        for (path, contents) in files {
+            tracing::info!("opened image {}", path.display());
+            let asset = self
+                .assets
+                .import(AssetKind::Image, path, Contents::Loaded(contents));
+            match self.image_layer(asset) {
+                Some(layer) => layers.push(layer),
+                None => self.notify(
+                    ToastKind::Error,
+                    t_args(
+                        "error-read-image",
+                        &[(
+                            "name",
+                            &self.assets.get(asset).map(Asset::name).unwrap_or_default(),
+                        )],
+                    ),
+                ),
+            }
+        }
+
+        let count = layers.len();
+        let canvas = Size::new(self.canvas_width, self.canvas_height);
+        let first = self.canvas_state.layers.len();
+        for (index, mut layer) in layers.into_iter().enumerate() {
+            if let (true, LayerKind::Image { image_size, .. }) = (count > 1, &layer.kind) {
+                let bounds = self
+                    .image_arrangement
+                    .place(index, count, *image_size, canvas);
+                layer.x = bounds.x;
+                layer.y = bounds.y;
+                layer.width = bounds.width;
+                layer.height = bounds.height;
+            }
+            self.canvas_state.layers.push(layer);
+        }
+        self.update_layer_names();
+
+        Task::batch(
+            (first..self.canvas_state.layers.len())
+                .map(|index| self.decode_layer_image(index))
+                .collect::<Vec<_>>(),
+        )
+        .chain(Task::done(Message::SelectLastLayer))
+    }
+
+    /// Places the image asset `id` in a layer at the canvas origin, with its
+    /// frame shrunk to fit the canvas if the image is larger than it. Only the
+    /// image's header is read, its frames are left to
+    /// [`Roygbiv::decode_layer_image`].
+    fn image_layer(&self, id: AssetId) -> Option<Layer> {
+        let asset = self.assets.get(id)?;
+        let contents = asset.bytes()?;
+        let (kind, image_size) = match asset.kind {
+            AssetKind::Animation => {
+                let lottie = Lottie::parse(contents).ok()?;
+                let size = lottie.size();
+
+                (LayerKind::Lottie { asset: id, lottie }, size)
+            }
+            _ => {
+                let (width, height) = canvas::image_dimensions(contents)?;
+                let image_size = Size::new(width as f32, height as f32);
+
+                (
+                    LayerKind::Image {
+                        asset: id,
+                        frames: vec![],
+                        image_size,
+                        sizing: Sizing::Fit,
+                        crop: Crop::FULL,
+                        lut: None,
+                    },
+                    image_size,
+                )
+            }
+        };
+        let scale = (self.canvas_width / image_size.width)
+            .min(self.canvas_height / image_size.height)
+            .min(1.);
+        let frame = image_size * scale;
+
+        Some(Layer {
+            name: asset.name(),
+            kind,
+            x: 0.,
+            y: 0.,
+            width: frame.width,
+            height: frame.height,
+            scale: 1.,
+            opacity: 1.,
+            flip: Flip::NONE,
+            appearance: Appearance::default(),
+            animation: Animation::NONE,
+            timing: Timing::ALWAYS,
+            playback: Playback::NORMAL,
+            keyframes: Keyframes::default(),
+            audio_channel: Channel::default(),
+            audio_source: Source::default(),
+            units: Units::default(),
+            // Images are rarely meant to be stretched.
+            is_aspect_locked: true,
+            visibility: Visibility::Everywhere,
+        })
+    }
+
+    fn project(&self) -> Project {
+        Project {
+            canvas_width: self.canvas_width,
+            canvas_height: self.canvas_height,
+            frame_rate: self.frame_rate,
+            audio_trim: self.audio_trim,
+            audio: self.audio().map(|audio| audio.path.clone()),
+            links: self.links.clone(),
+            fonts: self.fonts.clone(),
+            swatches: self.swatches.clone(),
+            swatch_uses: self.swatch_uses.clone(),
+            markers: self.markers.clone(),
+            scenes: self.scenes.clone(),
+            snapshots: self.snapshots.clone(),
+            post_fx: self.canvas_state.post_fx.clone(),
+            expressions: self
+                .expressions
+                .iter()
+                .map(|expression| ProjectExpression {
+                    layer: expression.layer,
+                    property: expression.property,
+                    source: expression.expression.source().to_string(),
+                })
+                .collect(),
+            audio_tracks: self
+                .audio_tracks
+                .iter()
+                .filter_map(|track| {
+                    Some(ProjectAudioTrack {
+                        path: self.assets.get(track.asset)?.path.clone(),
+                        mix: track.mix,
+                    })
+                })
+                .collect(),
+            layers: self
+                .canvas_state
+                .layers
+                .iter()
+                .map(|layer| ProjectLayer {
+                    name: layer.name.clone(),
+                    source: match (layer.procedural(), layer.asset()) {
+                        (Some(procedural), _) => LayerSource::Procedural(procedural),
+                        (None, Some(id)) => match self.assets.get(id) {
+                            Some(asset) if asset.kind == AssetKind::Animation => {
+                                LayerSource::Lottie(asset.path.clone())
+                            }
+                            Some(asset) => LayerSource::Image(asset.path.clone()),
+                            None => LayerSource::Placeholder,
+                        },
+                        (None, None) => match layer.capture_source() {
+                            Some(source) => LayerSource::Capture(source.clone()),
+                            None if matches!(layer.kind, LayerKind::Backdrop) => {
+                                LayerSource::Backdrop
+                            }
+                            None => LayerSource::Placeholder,
+                        },
+                    },
+                    x: layer.x,
+                    y: layer.y,
+                    width: layer.width,
+                    height: layer.height,
+                    scale: layer.scale,
+                    opacity: layer.opacity,
+                    sizing: layer.sizing().unwrap_or_default(),
+                    crop: layer.crop().unwrap_or_default(),
+                    flip: layer.flip,
+                    lut: layer.lut().cloned(),
+                    appearance: layer.appearance,
+                    animation: layer.animation,
+                    timing: layer.timing,
+                    playback: layer.playback,
+                    keyframes: layer.keyframes.clone(),
+                    audio_channel: layer.audio_channel,
+                    audio_source: layer.audio_source,
+                    units: layer.units,
+                    is_aspect_locked: layer.is_aspect_locked,
+                    visibility: layer.visibility,
+                })
+                .collect(),
+        }
+    }
+
+    fn apply_project(
+        &mut self,
+        path: Option<PathBuf>,
+        project: Project,
+        audio: Option<Contents>,
+        tracks: Vec<Contents>,
+        layers: Vec<Option<Arc<Vec<u8>>>>,
+    ) {
+        self.canvas_width = project.canvas_width;
+        self.canvas_height = project.canvas_height;
+        self.frame_rate = project.frame_rate;
+        self.audio_trim = project.audio_trim;
+
+        self.assets.clear();
+        self.audio_asset = project
+            .audio
+            .zip(audio)
+            .map(|(path, contents)| self.assets.import(AssetKind::Audio, path, contents));
+        self.audio_tracks = project
+            .audio_tracks
+            .into_iter()
+            .zip(tracks)
+            .map(|(track, contents)| AudioTrack {
+                asset: self.assets.import(AssetKind::Audio, track.path, contents),
+                mix: track.mix,
+                analysis: None,
+            })
+            .collect();
+
+        self.canvas_state.layers.clear();
+        self.canvas_state.comparison = None;
+        // An audio file still being read is for the project being replaced.
+        self.cancel_file_load();
+        // Indices in the project of the layers that could be loaded, to
+        // point links at where they ended up.
+        let mut loaded = vec![];
+        let layers = layers.into_iter().chain(std::iter::repeat(None));
+        for (saved_index, (saved, contents)) in project.layers.into_iter().zip(layers).enumerate() {
+            let kind = match (saved.source, contents) {
+                (LayerSource::Image(path), Some(contents)) => {
+                    let asset =
+                        self.assets
+                            .import(AssetKind::Image, path, Contents::Loaded(contents));
+                    match self.image_layer(asset) {
+                        Some(layer) => layer.kind,
+                        None => continue,
+                    }
+                }
+                (LayerSource::Lottie(path), Some(contents)) => {
+                    let asset =
+                        self.assets
+                            .import(AssetKind::Animation, path, Contents::Loaded(contents));
+                    match self.image_layer(asset) {
+                        Some(layer) => layer.kind,
+                        None => continue,
+                    }
+                }
+                (LayerSource::Procedural(procedural), _) => LayerKind::procedural(procedural),
+                (LayerSource::Capture(source), _) => LayerKind::Capture {
+                    source,
+                    frame: None,
+                },
+                (LayerSource::Backdrop, _) => LayerKind::Backdrop,
+                _ => LayerKind::Placeholder,
+            };
+
+            let mut layer = Layer {
+                name: saved.name,
+                kind,
+                x: saved.x,
+                y: saved.y,
+                width: saved.width,
+                height: saved.height,
+                scale: saved.scale,
+                opacity: saved.opacity,
+                flip: saved.flip,
+                appearance: saved.appearance,
+                animation: saved.animation,
+                timing: saved.timing,
+                playback: saved.playback,
+                keyframes: saved.keyframes,
+                audio_channel: saved.audio_channel,
+                audio_source: saved.audio_source,
+                units: saved.units,
+                is_aspect_locked: saved.is_aspect_locked,
+                visibility: saved.visibility,
+            };
+            layer.set_sizing(saved.sizing);
+            layer.set_crop(saved.crop);
+            layer.set_lut(saved.lut);
+            self.canvas_state.layers.push(layer);
+            loaded.push(saved_index);
+        }
+        self.update_layer_names();
+        self.links = project
+            .links
+            .into_iter()
+            .filter_map(|link| {
+                Some(Link {
+                    layer: loaded.iter().position(|index| *index == link.layer)?,
+                    source: loaded.iter().position(|index| *index == link.source)?,
+                    ..link
+                })
+            })
+            .collect();
+        self.expressions = project
+            .expressions
+            .into_iter()
+            .filter_map(|expression| {
+                Some(LayerExpression {
+                    layer: loaded.iter().position(|index| *index == expression.layer)?,
+                    property: expression.property,
+                    expression: Expression::parse(&expression.source),
+                })
+            })
+            .collect();
+        self.fonts = project.fonts;
+        self.update_font_families();
+        self.swatches = project.swatches;
+        self.snapshots = project.snapshots;
+        self.canvas_state.post_fx = project.post_fx;
+        self.markers = project.markers;
+        self.markers.sort_by(|a, b| a.time.total_cmp(&b.time));
+        self.scenes = project.scenes;
+        self.swatch_uses = project
+            .swatch_uses
+            .into_iter()
+            .filter_map(|swatch_use| {
+                let layer = swatch_use.field.layer()?;
+
+                Some(SwatchUse {
+                    field: swatch_use
+                        .field
+                        .with_layer(loaded.iter().position(|index| *index == layer)?),
+                    ..swatch_use
+                })
+            })
+            .collect();
+
+        self.project_path = path;
+        self.saved_edits = self.edits;
+        self.relink = None;
+        self.show_start_screen = false;
+    }
+
+    /// The project as it is now, to be kept as a snapshot named `name`.
+    fn snapshot(&self, name: String) -> Snapshot {
+        let mut project = self.project();
+        project.snapshots.clear();
+
+        Snapshot {
+            name,
+            taken: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|since| since.as_secs())
+                .unwrap_or_default(),
+            project: Box::new(project),
+        }
+    }
+
+    /// Reads what a project that was just applied needs from its files, then
+    /// selects its top layer.
+    fn load_project_media(&mut self) -> Task<Message> {
+        Task::batch([
+            self.load_fonts(),
+            self.analyze_audio(),
+            self.analyze_tracks(),
+            self.decode_layer_images(),
+            Task::done(Message::SelectLastLayer),
+        ])
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.edits != self.saved_edits
+    }
+
+    /// The project's file name, or "Untitled" until it is first saved.
+    fn project_name(&self) -> String {
+        self.project_path
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| t("untitled").to_string())
+    }
+
+    /// Puts `layer` at `index`, beneath the layer that was there, keeping
+    /// links, expressions and swatches on the layers they were on.
+    fn insert_layer(&mut self, index: usize, layer: Layer) {
+        self.canvas_state.layers.insert(index, layer);
+        self.update_layer_names();
+
+        let later = self
+            .links
+            .iter_mut()
+            .flat_map(|link| [&mut link.layer, &mut link.source])
+            .chain(
+                self.expressions
+                    .iter_mut()
+                    .map(|expression| &mut expression.layer),
+            );
+        for layer in later.filter(|layer| **layer >= index) {
+            *layer += 1;
+        }
+        for swatch_use in &mut self.swatch_uses {
+            if let Some(layer) = swatch_use.field.layer().filter(|layer| *layer >= index) {
+                swatch_use.field = swatch_use.field.with_layer(layer + 1);
+            }
+        }
+    }
+
+    fn update_layer_names(&mut self) {
+        self.layer_names = self
+            .canvas_state
+            .layers
+            .iter()
+            .map(|layer| layer.name.clone())
+            .collect()
+    }
+
+    fn layer_settings_view(&self, layer: Option<&Layer>) -> Element<'_, Message> {
+        let index = self.selected_layer_index;
+
+        if let Some(layer) = layer {
+            let flip = layer.flip;
+
+            let time = self.position().as_secs_f32();
+            let is_transformed = Property::ALL
+                .into_iter()
+                .any(|property| layer.value_at(property, time) != property.default_value());
+
+            let transform = column![
+                button(t("transform-reset"))
+                    .style(button::secondary)
+                    .on_press_maybe(is_transformed.then_some(Message::ResetTransform(index))),
+                self.property_input(layer, Property::X),
+                self.property_input(layer, Property::Y),
+                row![
+                    column![
+                        text(t("width-label")),
+                        nudgeable(
+                            NumericField::Width,
+                            text_input(
+                                t("width"),
+                                &layer.units.width.format(layer.width, self.canvas_width)
+                            )
+                            .on_input(move |input| Message::LayerWidthInput(index, input))
+                        )
+                    ]
+                    .spacing(3.),
+                    icon_button_with_tooltip(
+                        if layer.is_aspect_locked {
+                            "link"
+                        } else {
+                            "link-off"
+                        },
+                        if layer.is_aspect_locked {
+                            t("aspect-unlock")
+                        } else {
+                            t("aspect-lock")
+                        },
+                        Some(Message::ToggleAspectLock(index))
+                    ),
+                    column![
+                        text(t("height-label")),
+                        nudgeable(
+                            NumericField::Height,
+                            text_input(
+                                t("height"),
+                                &layer.units.height.format(layer.height, self.canvas_height)
+                            )
+                            .on_input(move |input| Message::LayerHeightInput(index, input))
+                        )
+                    ]
+                    .spacing(3.),
+                ]
+                .spacing(6.)
+                .align_y(Alignment::End),
+                self.property_input(layer, Property::Scale),
+            ]
+            .push(
+                row![
+                    checkbox(t("flip-horizontal"), flip.horizontal).on_toggle(move |horizontal| {
+                        Message::LayerFlipped(index, Flip { horizontal, ..flip })
+                    }),
+                    checkbox(t("flip-vertical"), flip.vertical).on_toggle(move |vertical| {
+                        Message::LayerFlipped(index, Flip { vertical, ..flip })
+                    }),
+                ]
+                .spacing(12.),
+            )
+            .push_maybe(layer.sizing().map(|sizing| {
+                column![
+                    text(t("sizing-label")),
+                    pick_list(Sizing::ALL, Some(sizing), move |sizing| {
+                        Message::LayerSizingSelected(index, sizing)
+                    })
+                    .width(Length::Fill),
+                ]
+                .spacing(3.)
+            }))
+            .push(
+                row![]
+                    .push_maybe(
+                        match layer.kind {
+                            LayerKind::Image { .. } => Some(t("image-replace")),
+                            LayerKind::Placeholder => Some(t("image-choose")),
+                            LayerKind::Procedural { .. }
+                            | LayerKind::Lottie { .. }
+                            | LayerKind::Capture { .. }
+                            | LayerKind::Backdrop => None,
+                        }
+                        .map(|label| button(label).on_press(Message::ReplaceLayerImage(index))),
+                    )
+                    .push(button(t("fit-to-canvas")).on_press(Message::FitLayerToCanvas(index)))
+                    .spacing(6.),
+            )
+            .push_maybe(layer.crop().map(|crop| {
+                let is_cropping = self.canvas_state.cropping == Some(index);
+
+                row![
+                    button(if is_cropping {
+                        t("crop-done")
+                    } else {
+                        t("crop")
+                    })
+                    .on_press(Message::ToggleCropping(index)),
+                    button(t("crop-reset"))
+                        .style(button::secondary)
+                        .on_press_maybe(
+                            (crop != Crop::FULL).then_some(Message::ResetLayerCrop(index))
+                        ),
+                ]
+                .spacing(6.)
+            }))
+            .spacing(6.);
+
+            let appearance = column![self.property_input(layer, Property::Opacity)]
+                .push(
+                    column![
+                        text(t_args(
+                            "corner-radius",
+                            &[("radius", &format!("{:.0}", layer.appearance.corner_radius))]
+                        )),
+                        slider(
+                            0.0..=(layer.width.min(layer.height) / 2.).max(1.),
+                            layer.appearance.corner_radius,
+                            move |radius| Message::LayerCornerRadiusChanged(index, radius)
+                        ),
+                    ]
+                    .spacing(3.),
+                )
+                .push(
+                    column![
+                        text(t_args(
+                            "border-width",
+                            &[("width", &format!("{:.0}", layer.appearance.border.width))]
+                        )),
+                        row![
+                            slider(0.0..=40., layer.appearance.border.width, move |width| {
+                                Message::LayerBorderWidthChanged(index, width)
+                            }),
+                            self.color_input(ColorField::Border(index)),
+                        ]
+                        .spacing(6.)
+                        .align_y(Alignment::Center),
+                        checkbox(
+                            t("border-follows-pitch"),
+                            layer.appearance.border.follows_pitch
+                        )
+                        .on_toggle(move |follows_pitch| {
+                            Message::LayerBorderFollowsPitchToggled(index, follows_pitch)
+                        }),
+                    ]
+                    .spacing(3.),
+                )
+                .push_maybe(
+                    layer
+                        .procedural()
+                        .map(|procedural| self.procedural_settings_view(index, procedural)),
+                )
+                .push_maybe(
+                    layer
+                        .capture_source()
+                        .map(|source| self.capture_settings_view(index, source)),
+                )
+                .push_maybe(
+                    layer
+                        .procedural()
+                        .and_then(|procedural| procedural.seed())
+                        .map(|seed| seed_input(index, seed)),
+                )
+                .push_maybe(
+                    layer
+                        .procedural()
+                        .and_then(|procedural| procedural.font())
+                        .map(|font| self.font_picker_view(index, font)),
+                )
+                .spacing(6.);
+
+            let tint = layer.image_options().map(|options| {
+                let tint = options.tint;
+
+                column![
+                    text(t("tint-label")),
+                    row![pick_list(TintMode::ALL, Some(tint.mode), move |mode| {
+                        Message::LayerTintModeSelected(index, mode)
+                    })
+                    .width(Length::Fill),]
+                    .push_maybe(
+                        (tint.mode == TintMode::Duotone)
+                            .then(|| self.color_input(ColorField::TintShadow(index)))
+                    )
+                    .push_maybe(
+                        (tint.mode != TintMode::None)
+                            .then(|| self.color_input(ColorField::Tint(index)))
+                    )
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                ]
+                .push_maybe((tint.mode != TintMode::None).then(|| {
+                    slider(0.0..=1., tint.amount, move |amount| {
+                        Message::LayerTintAmountChanged(index, amount)
+                    })
+                    .step(0.01)
+                }))
+                .spacing(3.)
+            });
+            // Screen captures are keyed and distorted like images, but can't be
+            // tinted.
+            let has_pixels = layer.image_options().is_some() || layer.capture_source().is_some();
+            let effects = if matches!(layer.kind, LayerKind::Backdrop) {
+                Some(column![backdrop_view(index, layer)])
+            } else {
+                has_pixels.then(|| {
+                    column![]
+                        .push_maybe(tint)
+                        .push_maybe(layer.image_options().map(|_| {
+                            kaleidoscope_view(
+                                t("kaleidoscope-image"),
+                                layer.appearance.kaleidoscope,
+                                move |kaleidoscope| {
+                                    Message::LayerKaleidoscopeChanged(index, kaleidoscope)
+                                },
+                            )
+                        }))
+                        .push_maybe(layer.image_options().map(|_| lut_view(index, layer.lut())))
+                        .push_maybe(layer.image_options().map(|_| backdrop_view(index, layer)))
+                        .push(self.chroma_key_view(index, layer.appearance.chroma_key))
+                        .push(distortion_view(index, layer.appearance.distortion))
+                        .push(glitch_view(index, layer.appearance.glitch))
+                        .spacing(6.)
+                })
+            };
+
+            let audio_reactivity = {
+                let animation = layer.animation;
+
+                column![
+                    text(t("animation-label")),
+                    pick_list(Preset::ALL, Some(animation.preset), move |preset| {
+                        Message::LayerAnimationChanged(
+                            index,
+                            Animation {
+                                preset,
+                                ..animation
+                            },
+                        )
+                    })
+                    .width(Length::Fill),
+                ]
+                .push_maybe((animation.preset != Preset::None).then(|| {
+                    slider(0.0..=3., animation.amount, move |amount| {
+                        Message::LayerAnimationChanged(
+                            index,
+                            Animation {
+                                amount,
+                                ..animation
+                            },
+                        )
+                    })
+                    .step(0.05)
+                }))
+                .push_maybe(layer.is_audio_reactive().then(|| {
+                    let sources: Vec<Source> = std::iter::once(Source::Main)
+                        .chain((0..self.audio_tracks.len()).map(Source::Track))
+                        .collect();
+
+                    column![text(t("reacts-to"))]
+                        .push_maybe((sources.len() > 1).then(|| {
+                            pick_list(sources, Some(layer.audio_source), move |source| {
+                                Message::LayerAudioSourceSelected(index, source)
+                            })
+                            .width(Length::Fill)
+                        }))
+                        .push(
+                            pick_list(Channel::ALL, Some(layer.audio_channel), move |channel| {
+                                Message::LayerAudioChannelSelected(index, channel)
+                            })
+                            .width(Length::Fill),
+                        )
+                        .spacing(3.)
+                }))
+                .push({
+                    let can_paste = matches!(self.copied_motion, Some(CopiedMotion::Layer(..)));
+
+                    row![
+                        button(t("motion-copy"))
+                            .style(button::secondary)
+                            .on_press(Message::CopyMotion(index)),
+                        button(t("paste")).on_press_maybe(
+                            can_paste.then_some(Message::PasteMotion(index, false))
+                        ),
+                        button(t("paste-reversed"))
+                            .on_press_maybe(can_paste.then_some(Message::PasteMotion(index, true))),
+                    ]
+                    .spacing(6.)
+                })
+                .spacing(3.)
+            };
+
+            let timing = column![{
+                let timing = layer.timing;
+                let playhead = self.position().as_secs_f32();
+
+                column![
+                    pick_list(Visibility::ALL, Some(layer.visibility), move |visibility| {
+                        Message::LayerVisibilityChanged(index, visibility)
+                    })
+                    .width(Length::Fill),
+                    text(t_args(
+                        "timing-shown",
+                        &[
+                            ("start", &format_time(Duration::from_secs_f32(timing.start))),
+                            (
+                                "end",
+                                &timing.end.map_or(t("timing-end").to_string(), |end| {
+                                    format_time(Duration::from_secs_f32(end))
+                                })
+                            ),
+                        ]
+                    )),
+                    row![
+                        button(t("trim-start-here")).on_press(Message::LayerTimingChanged(
+                            index,
+                            Timing {
+                                start: playhead,
+                                end: timing.end.filter(|end| *end > playhead),
+                                ..timing
+                            }
+                        )),
+                        button(t("trim-end-here")).on_press_maybe(
+                            (playhead > timing.start).then_some(Message::LayerTimingChanged(
+                                index,
+                                Timing {
+                                    end: Some(playhead),
+                                    ..timing
+                                }
+                            ))
+                        ),
+                        button(t("timing-always"))
+                            .style(button::secondary)
+                            .on_press_maybe((timing.start > 0. || timing.end.is_some()).then_some(
+                                Message::LayerTimingChanged(
+                                    index,
+                                    Timing {
+                                        start: 0.,
+                                        end: None,
+                                        ..timing
+                                    }
+                                )
+                            )),
+                    ]
+                    .spacing(6.),
+                    pick_list(
+                        self.markers.as_slice(),
+                        (0..self.markers.len())
+                            .find(|marker| {
+                                self.markers[*marker].time == timing.start
+                                    && markers::end(&self.markers, *marker) == timing.end
+                            })
+                            .map(|marker| self.markers[marker].clone()),
+                        move |marker| {
+                            let end = self
+                                .markers
+                                .iter()
+                                .position(|other| *other == marker)
+                                .and_then(|other| markers::end(&self.markers, other));
+
+                            Message::LayerTimingChanged(
+                                index,
+                                Timing {
+                                    start: marker.time,
+                                    end,
+                                    ..timing
+                                },
+                            )
+                        },
+                    )
+                    .placeholder(t("timing-marker"))
+                    .width(Length::Fill),
+                    row![
+                        pick_list(
+                            self.scenes.as_slice(),
+                            timing
+                                .scene
+                                .and_then(|scene| self.scenes.get(scene))
+                                .cloned(),
+                            move |scene| {
+                                Message::LayerTimingChanged(
+                                    index,
+                                    Timing {
+                                        scene: self.scenes.iter().position(|other| *other == scene),
+                                        ..timing
+                                    },
+                                )
+                            },
+                        )
+                        .placeholder(t("timing-scene"))
+                        .width(Length::Fill),
+                        button(t("timing-every-scene"))
+                            .style(button::secondary)
+                            .on_press_maybe(timing.scene.is_some().then_some(
+                                Message::LayerTimingChanged(
+                                    index,
+                                    Timing {
+                                        scene: None,
+                                        ..timing
+                                    }
+                                )
+                            )),
+                    ]
+                    .spacing(6.),
+                    text(t_args(
+                        "timing-offset",
+                        &[("offset", &format!("{:+.1}", timing.offset))]
+                    )),
+                    slider(-10.0..=10., timing.offset, move |offset| {
+                        Message::LayerTimingChanged(index, Timing { offset, ..timing })
+                    })
+                    .step(0.1),
+                    transition_view(timing.transition, move |transition| {
+                        Message::LayerTimingChanged(
+                            index,
+                            Timing {
+                                transition,
+                                ..timing
+                            },
+                        )
+                    }),
+                ]
+                .spacing(3.)
+            }]
+            .push_maybe(layer.is_animated().then(|| {
+                let playback = layer.playback;
+
+                column![
+                    text(t_args(
+                        "playback-speed",
+                        &[("speed", &format!("{:.2}", playback.speed))]
+                    )),
+                    slider(0.0..=4., playback.speed, move |speed| {
+                        Message::LayerPlaybackChanged(index, Playback { speed, ..playback })
+                    })
+                    .step(0.05),
+                    checkbox(t("playback-ping-pong"), playback.ping_pong).on_toggle(
+                        move |ping_pong| {
+                            Message::LayerPlaybackChanged(
+                                index,
+                                Playback {
+                                    ping_pong,
+                                    ..playback
+                                },
+                            )
+                        }
+                    ),
+                ]
+                .spacing(3.)
+            }))
+            .spacing(6.);
+
+            let sections = [
+                (SettingsSection::Transform, Some(transform.into())),
+                (SettingsSection::Appearance, Some(appearance.into())),
+                (SettingsSection::Effects, effects.map(Element::from)),
+                (
+                    SettingsSection::AudioReactivity,
+                    Some(audio_reactivity.into()),
+                ),
+                (SettingsSection::Timing, Some(timing.into())),
+            ];
+
+            scrollable(
+                sections
+                    .into_iter()
+                    .filter_map(|(section, content)| Some((section, content?)))
+                    .fold(
+                        column![row![
+                            horizontal_space(),
+                            tooltip(
+                                button(t("style-copy"))
+                                    .style(button::secondary)
+                                    .on_press(Message::CopyStyle(index)),
+                                t("style-copy-tooltip"),
+                                tooltip::Position::Bottom,
+                            )
+                            .style(container::rounded_box),
+                            button(t("style-paste")).on_press_maybe(
+                                self.copied_style
+                                    .is_some()
+                                    .then_some(Message::PasteStyle(index))
+                            ),
+                        ]
+                        .spacing(6.),],
+                        |column, (section, content)| {
+                            column
+                                .push(horizontal_separator())
+                                .push(self.settings_section(section, content))
+                        },
+                    )
+                    .padding([6., 7.])
+                    .spacing(6.),
+            )
+            .height(Length::Fill)
+            .into()
+        } else {
+            container(t("layer-none-selected"))
+                .center(Length::Fill)
+                .into()
+        }
+    }
+
+    /// A titled part of the layer settings, with a header that collapses it
+    /// to make room for the others.
+    fn settings_section<'a>(
+        &self,
+        section: SettingsSection,
+        content: Element<'a, Message>,
+    ) -> Element<'a, Message> {
+        let is_collapsed = self.collapsed_sections.contains(&section);
+
+        let header = button(
+            row![
+                svg(icon(if is_collapsed {
+                    "chevron-right"
+                } else {
+                    "chevron-down"
+                }))
+                .width(14.)
+                .height(14.)
+                .style(|_, _| svg::Style {
+                    color: Some(color!(0xffffff)),
+                }),
+                text(section.to_string()),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(Padding::from([3., 0.]))
+        .style(button::text)
+        .on_press(Message::ToggleSettingsSection(section));
+
+        column![header]
+            .push_maybe((!is_collapsed).then_some(content))
+            .spacing(6.)
+            .into()
+    }
+
+    /// Editable field for a property of the selected layer, with a toggle to
+    /// animate it. While animated, edits set a keyframe at the playhead.
+    fn property_input(&self, layer: &Layer, property: Property) -> Element<'_, Message> {
+        let index = self.selected_layer_index;
+        let is_animated = layer.keyframes.track(property).is_some();
+        let value = layer.value_at(property, self.position().as_secs_f32());
+        let label = property.to_string();
+        let number = match (layer.units.of(property), self.canvas_length(property)) {
+            (Some(unit), Some(canvas_length)) => unit.format(value, canvas_length),
+            _ => format!("{}", (value * 100.).round() / 100.),
+        };
+
+        if let Some(link) = self
+            .links
+            .iter()
+            .find(|link| link.layer == index && link.property == property)
+        {
+            let source = self
+                .layer_names
+                .get(link.source)
+                .map(String::as_str)
+                .unwrap_or_default();
+
+            return column![
+                text(format!("{}:", property)),
+                row![
+                    text(format!("{}", (value * 100.).round() / 100.)),
+                    text(t_args("property-linked", &[("layer", &source)])).style(text::secondary),
+                    horizontal_space(),
+                    icon_button_with_tooltip(
+                        "link",
+                        t("property-unlink"),
+                        Some(Message::UnlinkProperty(index, property))
+                    ),
+                ]
+                .spacing(6.)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(3.)
+            .into();
+        }
+
+        if let Some(expression) = self
+            .expressions
+            .iter()
+            .find(|expression| expression.layer == index && expression.property == property)
+        {
+            let expression = &expression.expression;
+
+            return column![
+                text(format!("{}:", property)),
+                row![
+                    text_input(&label, expression.source()).on_input(move |input| {
+                        Message::LayerPropertyInput(index, property, input)
+                    }),
+                    icon_button_with_tooltip(
+                        "reset",
+                        t("property-reset"),
+                        Some(Message::ResetProperty(index, property))
+                    ),
+                ]
+                .spacing(6.)
+                .align_y(Alignment::Center),
+                match expression.error() {
+                    Some(error) => text(error.to_string()).style(text::danger),
+                    None =>
+                        text(format!("= {}", (value * 100.).round() / 100.)).style(text::secondary),
+                }
+                .size(12.),
+            ]
+            .spacing(3.)
+            .into();
+        }
+
+        // Layers linked to this one are left out, as linking back would
+        // leave the property following itself.
+        let link_choices: Vec<_> = self
+            .layer_names
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| self.link_source(*other, property) != index)
+            .map(|(other, name)| LayerChoice {
+                index: other,
+                name: name.clone(),
+            })
+            .collect();
+
+        column![
+            text(format!("{}:", property)),
+            row![
+                nudgeable(
+                    NumericField::Property(property),
+                    text_input(&label, &number)
+                        .on_input(move |input| Message::LayerPropertyInput(index, property, input))
+                ),
+                icon_button_with_tooltip(
+                    "reset",
+                    t("property-reset"),
+                    (value != property.default_value())
+                        .then_some(Message::ResetProperty(index, property))
+                ),
+                icon_button_with_tooltip(
+                    if is_animated {
+                        "keyframe"
+                    } else {
+                        "keyframe-add"
+                    },
+                    if is_animated {
+                        t("property-stop-animating")
+                    } else {
+                        t("property-animate")
+                    },
+                    Some(Message::ToggleAnimated(index, property))
+                ),
+            ]
+            .push_maybe(is_animated.then(|| {
+                row![
+                    icon_button_with_tooltip(
+                        "graph",
+                        t("graph-show"),
+                        Some(Message::ShowGraph(Some(property))),
+                    ),
+                    icon_button_with_tooltip(
+                        "copy",
+                        t("keyframes-copy"),
+                        Some(Message::CopyKeyframes(index, property)),
+                    ),
+                ]
+                .spacing(6.)
+            }))
+            .push_maybe(
+                matches!(self.copied_motion, Some(CopiedMotion::Keyframes(_))).then(|| {
+                    row![
+                        icon_button_with_tooltip(
+                            "paste",
+                            t("keyframes-paste"),
+                            Some(Message::PasteKeyframes(index, property, false)),
+                        ),
+                        icon_button_with_tooltip(
+                            "paste-reversed",
+                            t("keyframes-paste-reversed"),
+                            Some(Message::PasteKeyframes(index, property, true)),
+                        ),
+                    ]
+                    .spacing(6.)
+                }),
+            )
+            .push(
+                tooltip(
+                    pick_list(link_choices, None::<LayerChoice>, move |choice| {
+                        Message::LinkProperty(index, property, choice.index)
+                    })
+                    .placeholder(t("property-link"))
+                    .width(80.),
+                    t("property-link-tooltip"),
+                    tooltip::Position::Top,
+                )
+                .style(container::rounded_box),
+            )
+            .spacing(6.)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(3.)
+        .into()
+    }
+
+    fn graph_editor_view(&self) -> Option<Element<'_, Message>> {
+        let property = self.graph_property?;
+        let index = self.selected_layer_index;
+        let track = self
+            .canvas_state
+            .layers
+            .get(index)?
+            .keyframes
+            .track(property)?;
+
+        Some(
+            column![
+                row![
+                    text(t_args("graph-title", &[("property", &property)])),
+                    text(t("graph-hint")).size(12.).style(text::secondary),
+                    horizontal_space(),
+                    button(t("close"))
+                        .style(button::secondary)
+                        .on_press(Message::ShowGraph(None)),
+                ]
+                .spacing(9.)
+                .align_y(Alignment::Center),
+                Canvas::new(GraphEditor {
+                    layer: index,
+                    track,
+                    playhead: self.position().as_secs_f32(),
+                    duration: self.duration().map(|duration| duration.as_secs_f32()),
+                })
+                .width(Length::Fill)
+                .height(180.),
+            ]
+            .spacing(6.)
+            .padding(Padding::from([6., 7.]))
+            .into(),
+        )
+    }
+
+    fn markers_view(&self) -> Element<'_, Message> {
+        let markers = self.markers.iter().enumerate().fold(
+            column![].spacing(3.),
+            |column, (index, marker)| {
+                column.push(
+                    row![
+                        button(
+                            text(format_time(Duration::from_secs_f32(marker.time)))
+                                .font(Font::MONOSPACE)
+                        )
+                        .style(button::text)
+                        .on_press(Message::Seek(marker.time)),
+                        text_input(t("marker-name-placeholder"), &marker.name)
+                            .on_input(move |name| Message::MarkerNameChanged(index, name))
+                            .padding(4.),
+                        icon_button_with_tooltip(
+                            "trash",
+                            t("marker-remove"),
+                            Some(Message::RemoveMarker(index))
+                        ),
+                    ]
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                )
+            },
+        );
+
+        column![
+            row![
+                text(t("markers")),
+                horizontal_space(),
+                button(t("marker-add")).on_press(Message::AddMarker),
+                tooltip(
+                    button(t("markers-import"))
+                        .style(button::secondary)
+                        .on_press(Message::ImportMarkers),
+                    t("markers-import-tooltip"),
+                    tooltip::Position::Top,
+                )
+                .style(container::rounded_box),
+                button(t("markers-from-audio"))
+                    .style(button::secondary)
+                    .on_press_maybe(self.audio().map(|_| Message::ImportAudioChapters)),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+            markers,
+        ]
+        .spacing(6.)
+        .into()
+    }
+
+    fn scenes_view(&self) -> Element<'_, Message> {
+        let scenes =
+            self.scenes
+                .iter()
+                .enumerate()
+                .fold(column![].spacing(3.), |column, (index, scene)| {
+                    column.push(
+                        row![
+                            button(
+                                text(format_time(Duration::from_secs_f32(scene.start)))
+                                    .font(Font::MONOSPACE)
+                            )
+                            .style(button::text)
+                            .on_press(Message::Seek(scene.start)),
+                            text_input(t("scene-name-placeholder"), &scene.name)
+                                .on_input(move |name| Message::SceneNameChanged(index, name))
+                                .padding(4.),
+                            container(transition_view(scene.transition, move |transition| {
+                                Message::SceneTransitionChanged(index, transition)
+                            }))
+                            .width(130.),
+                            icon_button_with_tooltip(
+                                "trash",
+                                t("scene-remove"),
+                                Some(Message::RemoveScene(index))
+                            ),
+                        ]
+                        .spacing(6.)
+                        .align_y(Alignment::Center),
+                    )
+                });
+
+        column![
+            row![
+                tooltip(
+                    text(t("scenes")),
+                    t("scenes-tooltip"),
+                    tooltip::Position::Top
+                )
+                .style(container::rounded_box),
+                horizontal_space(),
+                button(t("scene-add")).on_press(Message::AddScene),
+                button(t("scenes-from-markers"))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (!self.markers.is_empty()).then_some(Message::ScenesFromMarkers)
+                    ),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+            scenes,
+        ]
+        .spacing(6.)
+        .into()
+    }
+
+    fn chroma_key_view(&self, index: usize, chroma_key: ChromaKey) -> Element<'_, Message> {
+        let changed = move |chroma_key| Message::LayerChromaKeyChanged(index, chroma_key);
+
+        column![row![checkbox(t("chroma-key"), chroma_key.is_enabled)
+            .on_toggle(move |is_enabled| changed(ChromaKey {
+                is_enabled,
+                ..chroma_key
+            }))
+            .width(Length::Fill),]
+        .push_maybe(
+            chroma_key
+                .is_enabled
+                .then(|| self.color_input(ColorField::ChromaKey(index)))
+        )
+        .spacing(6.)
+        .align_y(Alignment::Center)]
+        .push_maybe(chroma_key.is_enabled.then(|| {
+            column![
+                text(t_args(
+                    "chroma-tolerance",
+                    &[("value", &format!("{:.2}", chroma_key.tolerance))]
+                )),
+                slider(0.0..=1., chroma_key.tolerance, move |tolerance| {
+                    changed(ChromaKey {
+                        tolerance,
+                        ..chroma_key
+                    })
+                })
+                .step(0.01),
+                text(t_args(
+                    "chroma-spill",
+                    &[("value", &format!("{:.2}", chroma_key.spill))]
+                )),
+                slider(0.0..=1., chroma_key.spill, move |spill| {
+                    changed(ChromaKey {
+                        spill,
+                        ..chroma_key
+                    })
+                })
+                .step(0.01),
+                text(t_args(
+                    "chroma-feather",
+                    &[("value", &format!("{:.2}", chroma_key.feather))]
+                )),
+                slider(0.0..=0.5, chroma_key.feather, move |feather| {
+                    changed(ChromaKey {
+                        feather,
+                        ..chroma_key
+                    })
+                })
+                .step(0.01),
+            ]
+            .spacing(3.)
+        }))
+        .spacing(3.)
+        .into()
+    }
+
+    fn capture_settings_view(&self, index: usize, source: &CaptureSource) -> Element<'_, Message> {
+        let region = match source {
+            CaptureSource::Region(region) => *region,
+            CaptureSource::Window(_) | CaptureSource::Camera(_) => Region {
+                x: 0,
+                y: 0,
+                width: self.canvas_width as u32,
+                height: self.canvas_height as u32,
+            },
+        };
+        let sources: Vec<CaptureSource> = std::iter::once(CaptureSource::Region(region))
+            .chain(
+                self.capture_windows
+                    .iter()
+                    .cloned()
+                    .map(CaptureSource::Window),
+            )
+            // A camera already shown keeps the size picked for it.
+            .chain(self.cameras.iter().map(|device| match source {
+                CaptureSource::Camera(camera) if camera.device == device.device => source.clone(),
+                _ => CaptureSource::Camera(device.camera()),
+            }))
+            .collect();
+        // Anything that isn't a number keeps the field as it was.
+        let field = |label: &'static str, value: String, changed: fn(Region, &str) -> Region| {
+            column![
+                text(format!("{}:", label)),
+                text_input(label, &value)
+                    .on_input(move |input| {
+                        Message::LayerCaptureSourceSelected(
+                            index,
+                            CaptureSource::Region(changed(region, &input)),
+                        )
+                    })
+                    .padding(4.),
+            ]
+            .spacing(3.)
+        };
+
+        column![
+            text(t("capture-label")),
+            row![
+                pick_list(sources, Some(source.clone()), move |source| {
+                    Message::LayerCaptureSourceSelected(index, source)
+                })
+                .width(Length::Fill),
+                icon_button_with_tooltip(
+                    "reset",
+                    t("capture-refresh"),
+                    Some(Message::RefreshCaptureWindows)
+                ),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+        ]
+        .push_maybe(match source {
+            CaptureSource::Camera(camera) => {
+                let resolutions = self
+                    .cameras
+                    .iter()
+                    .find(|device| device.device == camera.device)
+                    .map(|device| device.resolutions.clone())
+                    .unwrap_or_default();
+                let camera = camera.clone();
+
+                Some(
+                    column![
+                        text(t("resolution-label")),
+                        pick_list(resolutions, Some(camera.resolution), move |resolution| {
+                            Message::LayerCaptureSourceSelected(
+                                index,
+                                CaptureSource::Camera(camera::Camera {
+                                    resolution,
+                                    ..camera.clone()
+                                }),
+                            )
+                        })
+                        .width(Length::Fill),
+                    ]
+                    .spacing(3.),
+                )
+            }
+            CaptureSource::Region(_) | CaptureSource::Window(_) => None,
+        })
+        .push_maybe(matches!(source, CaptureSource::Region(_)).then(|| {
+            row![
+                field("x", region.x.to_string(), |region, input| Region {
+                    x: input.parse().unwrap_or(region.x),
+                    ..region
+                }),
+                field("y", region.y.to_string(), |region, input| Region {
+                    y: input.parse().unwrap_or(region.y),
+                    ..region
+                }),
+                field(t("width"), region.width.to_string(), |region, input| {
+                    Region {
+                        width: input.parse().unwrap_or(region.width).max(1),
+                        ..region
+                    }
+                }),
+                field(t("height"), region.height.to_string(), |region, input| {
+                    Region {
+                        height: input.parse().unwrap_or(region.height).max(1),
+                        ..region
+                    }
+                }),
+            ]
+            .spacing(6.)
+        }))
+        .spacing(3.)
+        .into()
+    }
+
+    fn procedural_settings_view(
+        &self,
+        index: usize,
+        procedural: Procedural,
+    ) -> Element<'_, Message> {
+        match procedural {
+            Procedural::Vignette(vignette) => column![
+                text(t_args(
+                    "size-value",
+                    &[("value", &format!("{:.2}", vignette.size))]
+                )),
+                slider(0.0..=1., vignette.size, move |size| {
+                    Message::LayerProceduralChanged(
+                        index,
+                        Procedural::Vignette(Vignette { size, ..vignette }),
+                    )
+                })
+                .step(0.01),
+                text(t_args(
+                    "softness-value",
+                    &[("value", &format!("{:.2}", vignette.softness))]
+                )),
+                row![
+                    slider(0.0..=1., vignette.softness, move |softness| {
+                        Message::LayerProceduralChanged(
+                            index,
+                            Procedural::Vignette(Vignette {
+                                softness,
+                                ..vignette
+                            }),
+                        )
+                    })
+                    .step(0.01),
+                    self.color_input(ColorField::Vignette(index)),
                 ]
-                .spacing(3.),
+                .spacing(6.)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(3.)
+            .into(),
+            Procedural::Grain(grain) => column![
+                text(t_args(
+                    "amount-value",
+                    &[("value", &format!("{:.2}", grain.amount))]
+                )),
+                slider(0.0..=1., grain.amount, move |amount| {
+                    Message::LayerProceduralChanged(
+                        index,
+                        Procedural::Grain(Grain { amount, ..grain }),
+                    )
+                })
+                .step(0.01),
+                text(t_args(
+                    "size-value",
+                    &[("value", &format!("{:.1}", grain.size))]
+                )),
+                slider(1.0..=8., grain.size, move |size| {
+                    Message::LayerProceduralChanged(
+                        index,
+                        Procedural::Grain(Grain { size, ..grain }),
+                    )
+                })
+                .step(0.1),
+                text(t_args(
+                    "speed-per-second",
+                    &[("value", &format!("{:.0}", grain.speed))]
+                )),
+                slider(0.0..=30., grain.speed, move |speed| {
+                    Message::LayerProceduralChanged(
+                        index,
+                        Procedural::Grain(Grain { speed, ..grain }),
+                    )
+                })
+                .step(1.),
+            ]
+            .spacing(3.)
+            .into(),
+            Procedural::Starfield(starfield) => {
+                let changed = move |starfield| {
+                    Message::LayerProceduralChanged(index, Procedural::Starfield(starfield))
+                };
+
+                column![
+                    text(t_args(
+                        "density-value",
+                        &[("value", &format!("{:.2}", starfield.density))]
+                    )),
+                    slider(0.0..=2., starfield.density, move |density| {
+                        changed(Starfield {
+                            density,
+                            ..starfield
+                        })
+                    })
+                    .step(0.01),
+                    text(t_args(
+                        "speed-value",
+                        &[("value", &format!("{:.2}", starfield.speed))]
+                    )),
+                    slider(0.0..=1., starfield.speed, move |speed| {
+                        changed(Starfield { speed, ..starfield })
+                    })
+                    .step(0.01),
+                    text(t_args(
+                        "depth-value",
+                        &[("value", &format!("{:.2}", starfield.depth))]
+                    )),
+                    slider(0.0..=1., starfield.depth, move |depth| {
+                        changed(Starfield { depth, ..starfield })
+                    })
+                    .step(0.01),
+                    text(t_args(
+                        "twinkle-value",
+                        &[("value", &format!("{:.2}", starfield.twinkle))]
+                    )),
+                    slider(0.0..=1., starfield.twinkle, move |twinkle| {
+                        changed(Starfield {
+                            twinkle,
+                            ..starfield
+                        })
+                    })
+                    .step(0.01),
+                    text(t_args(
+                        "reactivity-value",
+                        &[("value", &format!("{:.1}", starfield.reactivity))]
+                    )),
+                    slider(0.0..=10., starfield.reactivity, move |reactivity| {
+                        changed(Starfield {
+                            reactivity,
+                            ..starfield
+                        })
+                    })
+                    .step(0.1),
+                ]
+                .spacing(3.)
+                .into()
+            }
+            Procedural::Readout(readout) => {
+                let changed = move |readout| {
+                    Message::LayerProceduralChanged(index, Procedural::Readout(readout))
+                };
+
+                column![
+                    pick_list(Reading::ALL, Some(readout.reading), move |reading| {
+                        changed(Readout { reading, ..readout })
+                    })
+                    .width(Length::Fill),
+                    text(t_args(
+                        "size-value",
+                        &[("value", &format!("{:.0}", readout.size))]
+                    )),
+                    row![
+                        slider(8.0..=200., readout.size, move |size| {
+                            changed(Readout { size, ..readout })
+                        })
+                        .step(1.),
+                        self.color_input(ColorField::Readout(index)),
+                    ]
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                    checkbox(t("readout-show-label"), readout.labeled)
+                        .on_toggle(move |labeled| { changed(Readout { labeled, ..readout }) }),
+                ]
+                .push_maybe(readout.reading.is_numeric().then(|| {
+                    column![
+                        text(t_args(
+                            "decimals-value",
+                            &[("value", &format!("{}", readout.decimals))]
+                        )),
+                        slider(0..=3, readout.decimals, move |decimals| {
+                            changed(Readout {
+                                decimals,
+                                ..readout
+                            })
+                        }),
+                    ]
+                    .spacing(3.)
+                }))
+                .spacing(3.)
+                .into()
+            }
+            Procedural::Wave(wave) => {
+                let changed =
+                    move |wave| Message::LayerProceduralChanged(index, Procedural::Wave(wave));
+
+                column![
+                    row![
+                        pick_list(WaveStyle::ALL, Some(wave.style), move |style| {
+                            changed(Wave { style, ..wave })
+                        })
+                        .width(Length::Fill),
+                        self.color_input(ColorField::Wave(index)),
+                    ]
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                    text(t_args(
+                        "span-value",
+                        &[("value", &format!("{:.1}", wave.span))]
+                    )),
+                    slider(0.5..=30., wave.span, move |span| {
+                        changed(Wave { span, ..wave })
+                    })
+                    .step(0.1),
+                    text(t_args(
+                        "bar-width-value",
+                        &[("value", &format!("{:.0}", wave.bar_width))]
+                    )),
+                    slider(2.0..=64., wave.bar_width, move |bar_width| {
+                        changed(Wave { bar_width, ..wave })
+                    })
+                    .step(1.),
+                    checkbox(t("wave-mirrored"), wave.is_mirrored).on_toggle(move |is_mirrored| {
+                        changed(Wave {
+                            is_mirrored,
+                            ..wave
+                        })
+                    }),
+                ]
+                .spacing(3.)
+                .into()
+            }
+            Procedural::Progress(progress) => {
+                let changed = move |progress| {
+                    Message::LayerProceduralChanged(index, Procedural::Progress(progress))
+                };
+
                 column![
-                    text("height:"),
-                    text_input("height", &format!("{}", layer.height))
+                    row![
+                        text(t("progress-played")),
+                        self.color_input(ColorField::Progress(index)),
+                        text(t("progress-rest")),
+                        self.color_input(ColorField::ProgressTrack(index)),
+                    ]
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                    checkbox(t("progress-rounded"), progress.is_rounded).on_toggle(
+                        move |is_rounded| {
+                            changed(Progress {
+                                is_rounded,
+                                ..progress
+                            })
+                        }
+                    ),
                 ]
-                .spacing(3.),
+                .spacing(3.)
+                .into()
+            }
+            Procedural::Timer(timer) => {
+                let changed =
+                    move |timer| Message::LayerProceduralChanged(index, Procedural::Timer(timer));
+
                 column![
-                    text("scale:"),
-                    text_input("scale", &format!("{}", layer.scale))
+                    pick_list(Counting::ALL, Some(timer.counting), move |counting| {
+                        changed(Timer { counting, ..timer })
+                    })
+                    .width(Length::Fill),
+                    pick_list(TimerFormat::ALL, Some(timer.format), move |format| {
+                        changed(Timer { format, ..timer })
+                    })
+                    .width(Length::Fill),
+                    text(t_args(
+                        "size-value",
+                        &[("value", &format!("{:.0}", timer.size))]
+                    )),
+                    row![
+                        slider(8.0..=400., timer.size, move |size| {
+                            changed(Timer { size, ..timer })
+                        })
+                        .step(1.),
+                        self.color_input(ColorField::Timer(index)),
+                    ]
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
                 ]
-                .spacing(3.),
+                .push_maybe((timer.counting == Counting::Countdown).then(|| {
+                    let playhead = self.position().as_secs_f32();
+
+                    column![
+                        text(t_args(
+                            "timer-target",
+                            &[(
+                                "time",
+                                &format_time(Duration::from_secs_f32(timer.target.max(0.)))
+                            )]
+                        )),
+                        button(t("timer-target-here")).on_press(changed(Timer {
+                            target: playhead,
+                            ..timer
+                        })),
+                    ]
+                    .spacing(3.)
+                }))
+                .spacing(3.)
+                .into()
+            }
+            Procedural::Text(text_layer) => {
+                let changed = move |text_layer| {
+                    Message::LayerProceduralChanged(index, Procedural::Text(text_layer))
+                };
+                let styled = move |style| Message::LayerTextStyleChanged(index, style);
+                let style = text_layer.style;
+                let animated =
+                    move |animation| Message::LayerTextAnimationChanged(index, animation);
+                let animation = text_layer.animation;
+                let with_size = {
+                    let text_layer = text_layer.clone();
+                    move |size| {
+                        changed(Text {
+                            size,
+                            ..text_layer.clone()
+                        })
+                    }
+                };
+
                 column![
-                    text("opacity:"),
-                    text_input("opacity", &format!("{}", layer.opacity))
+                    text_editor(&self.text_editor)
+                        .placeholder(t("text-placeholder"))
+                        .on_action(move |action| Message::LayerTextEdited(index, action))
+                        .height(90.),
+                    text(t("text-markup-hint")).size(12).style(text::secondary),
+                    text(t_args(
+                        "size-value",
+                        &[("value", &format!("{:.0}", text_layer.size))]
+                    )),
+                    row![
+                        slider(8.0..=400., text_layer.size, with_size).step(1.),
+                        self.color_input(ColorField::Text(index)),
+                    ]
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                    pick_list(
+                        TextAlignment::ALL,
+                        Some(style.alignment),
+                        move |alignment| { styled(TextStyle { alignment, ..style }) }
+                    )
+                    .width(Length::Fill),
+                    text(t_args(
+                        "letter-spacing-value",
+                        &[("value", &format!("{:.0}", style.letter_spacing))]
+                    )),
+                    slider(-20.0..=100., style.letter_spacing, move |letter_spacing| {
+                        styled(TextStyle {
+                            letter_spacing,
+                            ..style
+                        })
+                    })
+                    .step(1.),
+                    text(t_args(
+                        "line-height-value",
+                        &[("value", &format!("{:.2}", style.line_height))]
+                    )),
+                    slider(0.5..=3., style.line_height, move |line_height| {
+                        styled(TextStyle {
+                            line_height,
+                            ..style
+                        })
+                    })
+                    .step(0.05),
+                    text(t_args(
+                        "outline-value",
+                        &[("value", &format!("{:.0}", style.outline_width))]
+                    )),
+                    row![
+                        slider(0.0..=20., style.outline_width, move |outline_width| {
+                            styled(TextStyle {
+                                outline_width,
+                                ..style
+                            })
+                        })
+                        .step(1.),
+                        self.color_input(ColorField::TextOutline(index)),
+                    ]
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                    row![
+                        checkbox(t("text-background"), style.has_background).on_toggle(
+                            move |has_background| {
+                                styled(TextStyle {
+                                    has_background,
+                                    ..style
+                                })
+                            }
+                        ),
+                        horizontal_space(),
+                        self.color_input(ColorField::TextBackground(index)),
+                    ]
+                    .align_y(Alignment::Center),
+                ]
+                .push_maybe(style.has_background.then(|| {
+                    column![
+                        text(t_args(
+                            "padding-value",
+                            &[("value", &format!("{:.0}", style.background_padding))]
+                        )),
+                        slider(
+                            0.0..=100.,
+                            style.background_padding,
+                            move |background_padding| {
+                                styled(TextStyle {
+                                    background_padding,
+                                    ..style
+                                })
+                            }
+                        )
+                        .step(1.),
+                    ]
+                    .spacing(3.)
+                }))
+                .push(
+                    pick_list(TextPreset::ALL, Some(animation.preset), move |preset| {
+                        animated(TextAnimation {
+                            preset,
+                            ..animation
+                        })
+                    })
+                    .width(Length::Fill),
+                )
+                .push_maybe((animation.preset != TextPreset::None).then(|| {
+                    column![
+                        pick_list(TextUnit::ALL, Some(animation.unit), move |unit| {
+                            animated(TextAnimation { unit, ..animation })
+                        })
+                        .width(Length::Fill),
+                        text(t_args(
+                            "delay-value",
+                            &[("value", &format!("{:.1}", animation.delay))]
+                        )),
+                        slider(0.0..=10., animation.delay, move |delay| {
+                            animated(TextAnimation { delay, ..animation })
+                        })
+                        .step(0.1),
+                        text(t_args(
+                            "stagger-value",
+                            &[("value", &format!("{:.2}", animation.stagger))]
+                        )),
+                        slider(0.0..=1., animation.stagger, move |stagger| {
+                            animated(TextAnimation {
+                                stagger,
+                                ..animation
+                            })
+                        })
+                        .step(0.01),
+                    ]
+                    .push_maybe((animation.preset != TextPreset::Typewriter).then(|| {
+                        column![
+                            text(t_args(
+                                "duration-seconds",
+                                &[("value", &format!("{:.2}", animation.duration))]
+                            )),
+                            slider(0.05..=2., animation.duration, move |duration| {
+                                animated(TextAnimation {
+                                    duration,
+                                    ..animation
+                                })
+                            })
+                            .step(0.05),
+                            pick_list(Easing::ALL, Some(animation.easing), move |easing| {
+                                animated(TextAnimation {
+                                    easing,
+                                    ..animation
+                                })
+                            })
+                            .width(Length::Fill),
+                        ]
+                        .spacing(3.)
+                    }))
+                    .spacing(3.)
+                }))
+                .spacing(3.)
+                .into()
+            }
+        }
+    }
+
+    /// Picks the font of a text layer from the installed fonts and those
+    /// imported into the project, each written in itself as a preview.
+    fn font_picker_view(&self, index: usize, font: TextFont) -> Element<'_, Message> {
+        /// Families listed at once. Searching narrows down the rest.
+        const ROWS: usize = 60;
+
+        let query = self.font_query.to_lowercase();
+        let family = font
+            .family
+            .and_then(|name| self.font_families.iter().find(|family| family.name == name));
+        let choice_button = move |label: String, choice: TextFont| {
+            let is_selected = choice.family == font.family;
+
+            button(text(label).font(choice.to_font(Font::DEFAULT)))
+                .width(Length::Fill)
+                .padding(Padding::from([3., 6.]))
+                .style(move |theme: &Theme, status| {
+                    if is_selected {
+                        button::primary(theme, status)
+                    } else {
+                        button::text(theme, status)
+                    }
+                })
+                .on_press(Message::LayerFontChanged(index, choice))
+        };
+
+        let choices = self
+            .font_families
+            .iter()
+            .filter(|family| family.name.as_str().to_lowercase().contains(&query))
+            .take(ROWS)
+            .fold(
+                column![choice_button(
+                    t("font-default").to_string(),
+                    TextFont {
+                        family: None,
+                        ..font
+                    }
+                )],
+                |column, family| {
+                    let choice = TextFont {
+                        family: Some(family.name),
+                        weight: family.closest_weight(font.weight),
+                        italic: font.italic && family.has_italic,
+                    };
+                    let label = if family.is_imported {
+                        t_args("font-imported", &[("family", &family.name)])
+                    } else {
+                        family.name.to_string()
+                    };
+
+                    column.push(choice_button(label, choice))
+                },
+            );
+        let weights = family.map_or(FontWeight::ALL.to_vec(), |family| family.weights.clone());
+        let can_be_italic = family.is_none_or(|family| family.has_italic);
+
+        column![
+            text(t("font-label")),
+            row![
+                text_input(t("font-search"), &self.font_query).on_input(Message::FontQueryChanged),
+                button(t("font-import"))
+                    .style(button::secondary)
+                    .on_press(Message::ImportFont),
+            ]
+            .spacing(6.),
+            container(scrollable(choices).height(150.)).style(container::bordered_box),
+            row![
+                pick_list(weights, Some(font.weight), move |weight| {
+                    Message::LayerFontChanged(index, TextFont { weight, ..font })
+                })
+                .width(Length::Fill),
+                checkbox(t("font-italic"), font.italic).on_toggle_maybe(can_be_italic.then_some(
+                    move |italic| Message::LayerFontChanged(index, TextFont { italic, ..font })
+                )),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(3.)
+        .into()
+    }
+
+    fn title(&self, window: window::Id) -> String {
+        if Some(window) == self.preview_window {
+            t("preview-window-title").to_string()
+        } else {
+            t_args(
+                "window-title",
+                &[
+                    ("project", &self.project_name()),
+                    ("dirty", &if self.is_dirty() { "*" } else { "" }),
+                ],
+            )
+        }
+    }
+
+    fn view(&self, window: window::Id) -> Element<'_, Message> {
+        if Some(window) == self.preview_window {
+            self.preview_window_view()
+        } else {
+            self.main_window_view()
+        }
+    }
+
+    fn preview_window_view(&self) -> Element<'_, Message> {
+        Canvas::new(Preview {
+            state: &self.canvas_state,
+            size: Size::new(self.canvas_width, self.canvas_height),
+        })
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn main_window_view(&self) -> Element<'_, Message> {
+        let audio_section_content = {
+            match self.audio() {
+                Some(audio) => container({
+                    row![tooltip(
+                        text(audio.name()),
+                        text(self.asset_details(audio.id)),
+                        tooltip::Position::Top
+                    )
+                    .style(container::rounded_box)]
+                    .push_maybe(
+                        self.analysis
+                            .as_ref()
+                            .and_then(|analysis| analysis.key())
+                            .map(|key| text(t_args("audio-key", &[("key", &key)]))),
+                    )
+                    .push_maybe(self.analysis_progress.as_ref().map(|progress| {
+                        load_progress(t("audio-analyzing"), progress, Message::CancelAnalysis)
+                    }))
+                    .push(horizontal_space())
+                    .push(button(t("audio-remove")).on_press(Message::RemoveAudioFile))
+                    .spacing(6.)
+                    .align_y(Alignment::Center)
+                }),
+                None => container(match &self.file_load {
+                    Some(load) => {
+                        load_progress(t("audio-loading"), &load.progress, Message::CancelFileLoad)
+                    }
+                    None => button(t("audio-select"))
+                        .on_press(Message::OpenAudioFile)
+                        .into(),
+                }),
+            }
+        };
+
+        let position = self.position();
+        let transport = row![
+            focus_ring(
+                icon_button_with_tooltip(
+                    if self.transport.is_playing() {
+                        "pause"
+                    } else {
+                        "play"
+                    },
+                    if self.transport.is_playing() {
+                        t("pause")
+                    } else {
+                        t("play")
+                    },
+                    Some(Message::TogglePlayback)
+                ),
+                self.is_transport_focused(0)
+            ),
+            focus_ring(
+                icon_button_with_tooltip(
+                    if self.is_recording {
+                        "recording"
+                    } else {
+                        "record"
+                    },
+                    if self.is_recording {
+                        t("stop-recording")
+                    } else {
+                        t("record-keyframes")
+                    },
+                    Some(Message::ToggleRecording)
+                ),
+                self.is_transport_focused(1)
+            ),
+            focus_ring(
+                icon_button_with_tooltip(
+                    if self.output.is_muted() {
+                        "muted"
+                    } else {
+                        "volume"
+                    },
+                    if self.output.is_muted() {
+                        t("unmute")
+                    } else {
+                        t("mute")
+                    },
+                    Some(Message::ToggleMute)
+                ),
+                self.is_transport_focused(2)
+            ),
+            slider(0.0..=1.0, self.output.volume(), Message::VolumeChanged)
+                .step(0.01)
+                .width(72.),
+            focus_ring(
+                icon_button_with_tooltip(
+                    "step-back",
+                    t("previous-frame"),
+                    Some(Message::StepFrames(-1))
+                ),
+                self.is_transport_focused(3)
+            ),
+            focus_ring(
+                icon_button_with_tooltip(
+                    "step-forward",
+                    t("next-frame"),
+                    Some(Message::StepFrames(1))
+                ),
+                self.is_transport_focused(4)
+            ),
+            focus_ring(
+                tooltip(
+                    button(text({
+                        let format = |time| self.time_format.format(time, self.frame_rate);
+
+                        match self.duration() {
+                            Some(duration) => {
+                                format!("{} / {}", format(position), format(duration))
+                            }
+                            None => format(position),
+                        }
+                    }))
+                    .style(button::text)
+                    .padding(0.)
+                    .on_press(Message::CycleTimeFormat),
+                    t("time-format-tooltip"),
+                    tooltip::Position::Top
+                )
+                .style(container::rounded_box),
+                self.is_transport_focused(5)
+            ),
+        ]
+        .push_maybe(self.duration().map(|duration| {
+            slider(
+                0.0..=duration.as_secs_f32(),
+                position.as_secs_f32(),
+                Message::Seek,
+            )
+            .step(self.frame_rate.frame_duration().as_secs_f32())
+        }))
+        .push(
+            tooltip(
+                pick_list(
+                    PlaybackRate::ALL,
+                    Some(self.transport.rate()),
+                    Message::PlaybackRateSelected,
+                ),
+                t("preview-speed"),
+                tooltip::Position::Top,
+            )
+            .style(container::rounded_box),
+        )
+        .push(
+            checkbox(t("preserve-pitch"), self.output.is_pitch_preserved())
+                .on_toggle(Message::PitchPreservedToggled),
+        )
+        .spacing(9.)
+        .align_y(Alignment::Center);
+
+        let waveform = self.analysis.as_ref().map(|analysis| {
+            Canvas::new(Waveform {
+                analysis,
+                position,
+                trim: self.audio_trim,
+                markers: &self.markers,
+                cache: &self.waveform_cache,
+            })
+            .width(Length::Fill)
+            .height(48.)
+        });
+
+        let meters = self.analysis.as_ref().map(|analysis| {
+            let levels = analysis.levels(self.audio_trim.start() + position);
+            let peak = levels.iter().map(|level| level.peak).fold(0., f32::max);
+            let height = LevelMeter::height(levels.len());
+
+            row![
+                Canvas::new(LevelMeter { levels })
+                    .width(Length::Fill)
+                    .height(height),
+                text(t_args(
+                    "audio-peak",
+                    &[("level", &format!("{:.1}", Level::decibels(peak)))],
+                ))
+                .size(12.)
+                .width(80.),
+            ]
+            .spacing(9.)
+            .align_y(Alignment::Center)
+        });
+
+        let trim = self.analysis.as_ref().map(|analysis| {
+            let trim = self.audio_trim;
+            let playhead = trim.start + position.as_secs_f32();
+            let silence_trim = analysis.silence_trim();
+
+            row![
+                text(t_args(
+                    "audio-range",
+                    &[
+                        ("start", &format_time(trim.start())),
+                        (
+                            "end",
+                            &format_time(trim.start() + trim.length(analysis.duration)),
+                        ),
+                    ],
+                )),
+                horizontal_space(),
+                tooltip(
+                    button(t("trim-silence")).on_press_maybe(
+                        (silence_trim != Trim::NONE && silence_trim != trim)
+                            .then_some(Message::AudioTrimChanged(silence_trim))
+                    ),
+                    t("trim-silence-tooltip"),
+                    tooltip::Position::Top,
+                )
+                .style(container::rounded_box),
+                button(t("trim-start-here")).on_press(Message::AudioTrimChanged(Trim {
+                    start: playhead,
+                    end: trim.end.filter(|end| *end > playhead),
+                })),
+                button(t("trim-end-here")).on_press_maybe((position > Duration::ZERO).then_some(
+                    Message::AudioTrimChanged(Trim {
+                        end: Some(playhead),
+                        ..trim
+                    })
+                )),
+                button(t("trim-whole-file"))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (trim != Trim::NONE).then_some(Message::AudioTrimChanged(Trim::NONE))
+                    ),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center)
+        });
+
+        let audio_tracks = self.audio_tracks.iter().enumerate().fold(
+            column![].spacing(6.),
+            |column, (index, track)| {
+                let mix = track.mix;
+                let name = self
+                    .assets
+                    .get(track.asset)
+                    .map(Asset::name)
+                    .unwrap_or_default();
+
+                column.push(
+                    column![
+                        row![
+                            tooltip(
+                                text(format!("{}: {}", Source::Track(index), name)),
+                                text(self.asset_details(track.asset)),
+                                tooltip::Position::Top
+                            )
+                            .style(container::rounded_box),
+                            horizontal_space(),
+                            button(t("track-remove"))
+                                .style(button::secondary)
+                                .on_press(Message::RemoveAudioTrack(index)),
+                        ]
+                        .align_y(Alignment::Center),
+                        text(t_args(
+                            "track-gain",
+                            &[("gain", &format!("{:+.1}", mix.gain))]
+                        )),
+                        slider(-24.0..=12., mix.gain, move |gain| {
+                            Message::AudioTrackMixChanged(index, Mix { gain, ..mix })
+                        })
+                        .step(0.5),
+                        row![
+                            text(t_args(
+                                "track-starts-at",
+                                &[(
+                                    "time",
+                                    &format_time(Duration::from_secs_f32(mix.offset.max(0.))),
+                                )],
+                            )),
+                            horizontal_space(),
+                            button(t("track-start-at-playhead")).on_press(
+                                Message::AudioTrackMixChanged(
+                                    index,
+                                    Mix {
+                                        offset: position.as_secs_f32(),
+                                        ..mix
+                                    }
+                                )
+                            ),
+                        ]
+                        .align_y(Alignment::Center),
+                    ]
+                    .spacing(3.),
+                )
+            },
+        );
+        let add_audio_track = button(t("track-add"))
+            .style(button::secondary)
+            .on_press_maybe(self.file_load.is_none().then_some(Message::AddAudioTrack));
+
+        let audio_section = container(
+            column![transport]
+                .push_maybe(waveform)
+                .push_maybe(meters)
+                .push_maybe(trim)
+                .push(audio_section_content)
+                .push(self.markers_view())
+                .push(self.scenes_view())
+                .push(audio_tracks)
+                .push(add_audio_track)
+                .spacing(6.),
+        )
+        .width(Length::Fill)
+        .padding(Padding::from([6., 7.]));
+
+        let canvas_section: Element<Message> =
+            if self.canvas_state.layers.is_empty() && self.audio_asset.is_none() {
+                empty_canvas_view()
+            } else {
+                container(
+                    container(responsive(|size| {
+                        let canvas_width = self.canvas_width;
+                        let canvas_height = self.canvas_height;
+                        let aspect_ratio = canvas_width / canvas_height;
+
+                        let should_downsize = canvas_width > size.width;
+
+                        let final_width = if should_downsize {
+                            Length::Fixed(size.width)
+                        } else {
+                            Length::Fill
+                        };
+
+                        let final_height = if should_downsize {
+                            Length::Fixed(size.width / aspect_ratio)
+                        } else {
+                            Length::Fill
+                        };
+
+                        Canvas::new(&self.canvas_state)
+                            .width(final_width)
+                            .height(final_height)
+                            .into()
+                    }))
+                    .width(Length::Fixed(self.canvas_width))
+                    .height(Length::Fixed(self.canvas_height)),
+                )
+                .center(Length::Fill)
+                .into()
+            };
+        let canvas_section = stack![canvas_section].push_maybe(self.show_stats.then(|| {
+            container(self.stats_view())
+                .padding(9.)
+                .width(Length::Fill)
+                .height(Length::Fill)
+        }));
+
+        let project_section = container(
+            row![
+                button(t("project-new")).on_press(Message::NewProject),
+                button(t("project-open")).on_press(Message::OpenProject),
+                button(t("project-save")).on_press(Message::SaveProject),
+                horizontal_space(),
+                Orientation::ALL
+                    .into_iter()
+                    .fold(row![], |row, orientation| {
+                        let is_current =
+                            Orientation::of(Size::new(self.canvas_width, self.canvas_height))
+                                == Some(orientation);
+
+                        row.push(
+                            button(text(orientation.to_string()).size(12.))
+                                .style(if is_current {
+                                    button::primary
+                                } else {
+                                    button::secondary
+                                })
+                                .on_press_maybe(
+                                    (!is_current).then_some(Message::SetOrientation(orientation)),
+                                ),
+                        )
+                    })
+                    .spacing(2.),
+                tooltip(
+                    checkbox(t("orientation-fit"), self.fits_to_orientation)
+                        .on_toggle(|_| Message::ToggleFitToOrientation),
+                    t("orientation-fit-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                text(t("output")),
+                pick_list(
+                    self.output_devices.as_slice(),
+                    Some(self.output.device()),
+                    Message::OutputDeviceSelected
+                )
+                .on_open(Message::ListOutputDevices),
+                text(t("frame-rate")),
+                pick_list(
+                    FrameRate::ALL,
+                    Some(self.frame_rate),
+                    Message::FrameRateSelected
+                ),
+                text(t("language")),
+                pick_list(
+                    Language::ALL,
+                    Some(i18n::language()),
+                    Message::LanguageSelected
+                ),
+                tooltip(
+                    pick_list(
+                        SafeArea::ALL,
+                        Some(self.canvas_state.safe_area),
+                        Message::SafeAreaSelected
+                    ),
+                    t("safe-area-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                tooltip(
+                    button(t("post-fx"))
+                        .style(if self.show_post_fx {
+                            button::primary
+                        } else {
+                            button::secondary
+                        })
+                        .on_press(Message::TogglePostFx),
+                    t("post-fx-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                tooltip(
+                    button(t("compare-pin"))
+                        .style(button::secondary)
+                        .on_press_maybe(
+                            self.canvas_state
+                                .comparison
+                                .is_none()
+                                .then_some(Message::PinComparison)
+                        ),
+                    t("compare-pin-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                tooltip(
+                    checkbox(t("stats"), self.show_stats).on_toggle(|_| Message::ToggleStats),
+                    t("stats-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                tooltip(
+                    button(if self.preview_window.is_some() {
+                        t("preview-attach")
+                    } else {
+                        t("preview-detach")
+                    })
+                    .style(button::secondary)
+                    .on_press(Message::TogglePreviewWindow),
+                    t("preview-detach-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                pick_list(
+                    ThumbnailSize::ALL,
+                    Some(self.thumbnail_size),
+                    Message::ThumbnailSizeSelected
+                ),
+                tooltip(
+                    button(t("thumbnail-render"))
+                        .style(button::secondary)
+                        .on_press(Message::RenderThumbnail),
+                    t("thumbnail-render-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                tooltip(
+                    button(t("lottie-export"))
+                        .style(button::secondary)
+                        .on_press(Message::ExportLottie),
+                    t("lottie-export-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+                tooltip(
+                    button(t("copy-diagnostics"))
+                        .style(button::secondary)
+                        .on_press(Message::CopyDiagnostics),
+                    t("copy-diagnostics-tooltip"),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(Padding::from([6., 7.]));
+
+        let main_column = column![project_section, horizontal_separator()]
+            .push_maybe(
+                self.show_post_fx
+                    .then(|| column![self.post_fx_view(), horizontal_separator()]),
+            )
+            .push_maybe(
+                self.compare_view()
+                    .map(|compare| column![compare, horizontal_separator()]),
+            )
+            .push(canvas_section)
+            .push_maybe(
+                self.graph_editor_view()
+                    .map(|graph_editor| column![horizontal_separator(), graph_editor]),
+            )
+            .push(horizontal_separator())
+            .push(audio_section)
+            .width(Length::FillPortion(2));
+
+        let selected_layer = self.canvas_state.layers.get(self.selected_layer_index);
+
+        let layer_list_section = column![
+            container(
+                row![
+                    text(t("layers")),
+                    text_input(t("layer-filter"), &self.layer_query)
+                        .on_input(Message::LayerQueryChanged)
+                        .padding(4.),
+                    pick_list(
+                        LayerCategory::ALL,
+                        Some(self.layer_category),
+                        Message::LayerCategorySelected
+                    )
+                    .padding(4.),
+                ]
+                .spacing(6.)
+                .align_y(Alignment::Center)
+            )
+            .padding(Padding::from([6., 7.])),
+            horizontal_separator(),
+            focus_ring(
+                self.layer_list_view(),
+                self.focused_panel == Some(Panel::Layers)
+            ),
+            horizontal_separator(),
+            container(
+                row![
+                    icon_button_with_tooltip("plus", t("layer-add"), Some(Message::AddImageLayer)),
+                    tooltip(
+                        pick_list(
+                            Arrangement::ALL,
+                            Some(self.image_arrangement),
+                            Message::ImageArrangementSelected
+                        )
+                        .padding(4.),
+                        t("layer-arrangement-tooltip"),
+                        tooltip::Position::Top,
+                    )
+                    .style(container::rounded_box),
+                    icon_button_with_tooltip(
+                        "trash",
+                        t("layer-delete"),
+                        selected_layer.map(|_| Message::RemoveLayer(self.selected_layer_index))
+                    ),
+                    horizontal_space(),
+                    pick_list(
+                        Procedural::ALL,
+                        None::<Procedural>,
+                        Message::AddProceduralLayer
+                    )
+                    .placeholder(t("overlay-add")),
                 ]
-                .spacing(3.),
+                .spacing(6.)
+                .align_y(Alignment::Center)
+            )
+            .padding(Padding::from([6., 7.]))
+        ]
+        .height(Length::Fill);
+
+        let selected_layer_settings_section = column![
+            container(text(match selected_layer {
+                Some(layer) => &layer.name,
+                None => t("layer-settings"),
+            }))
+            .padding(Padding::from([6., 7.])),
+            horizontal_separator(),
+            focus_ring(
+                self.layer_settings_view(selected_layer),
+                self.focused_panel == Some(Panel::Settings)
+            ),
+        ];
+
+        let sidebar_tabs = container(
+            row![
+                sidebar_tab_button(t("layers"), SidebarTab::Layers, self.sidebar_tab),
+                sidebar_tab_button(t("assets"), SidebarTab::Assets, self.sidebar_tab),
+                sidebar_tab_button(t("swatches"), SidebarTab::Swatches, self.sidebar_tab),
+                sidebar_tab_button(t("snapshots"), SidebarTab::Snapshots, self.sidebar_tab),
+            ]
+            .spacing(3.),
+        )
+        .padding(Padding::from([3., 4.]));
+
+        let sidebar_content: Element<Message> = match self.sidebar_tab {
+            SidebarTab::Layers => column![
+                selected_layer_settings_section,
+                horizontal_separator(),
+                layer_list_section
+            ]
+            .into(),
+            SidebarTab::Assets => self.assets_view(),
+            SidebarTab::Swatches => self.swatches_view(),
+            SidebarTab::Snapshots => self.snapshots_view(),
+        };
+
+        let settings_column = column![sidebar_tabs, horizontal_separator(), sidebar_content]
+            .width(Length::FillPortion(1))
+            .height(Length::Fill);
+
+        let content: Element<Message> = if self.show_start_screen {
+            start_screen_view()
+        } else {
+            column![
+                row![main_column, vertical_separator(), settings_column],
+                horizontal_separator(),
+                self.status_bar_view(),
             ]
-            .height(Length::Fill)
-            .padding([6., 7.])
-            .spacing(6.)
             .into()
+        };
+
+        let content = match &self.relink {
+            Some(relink) => stack![content, relink_view(relink)].into(),
+            None => content,
+        };
+
+        let content = match &self.audiogram_wizard {
+            Some(wizard) => stack![content, audiogram_wizard_view(wizard)].into(),
+            None => content,
+        };
+
+        let content = match &self.command_palette {
+            Some(palette) => stack![content, palette.view(self.commands())].into(),
+            None => content,
+        };
+
+        if self.toasts.is_empty() {
+            content
         } else {
-            container("No layer selected").center(Length::Fill).into()
+            stack![content, self.toasts.view()].into()
         }
     }
 
-    fn view(&self) -> Element<Message> {
-        let audio_section_content = {
-            match &self.audio_file_path {
-                Some(path) => container({
-                    let name = (path.file_name().unwrap_or(path.as_os_str())).to_str();
+    /// The controls for the effects over the whole frame.
+    fn post_fx_view<'a>(&'a self) -> Element<'a, Message> {
+        let post_fx = &self.canvas_state.post_fx;
+        let changed = Message::PostFxChanged;
+        let labeled = |label: String, slider: Element<'a, Message>| {
+            column![text(label).size(12.), slider]
+                .spacing(2.)
+                .width(130.)
+        };
 
-                    row![
-                        text(name.unwrap_or("Audio file")),
-                        horizontal_space(),
-                        button("Remove audio file").on_press(Message::RemoveAudioFile)
-                    ]
-                    .align_y(Alignment::Center)
+        container(
+            row![
+                text(t("post-fx")),
+                pick_list(Letterbox::ALL, Some(post_fx.letterbox), move |letterbox| {
+                    changed(PostFx {
+                        letterbox,
+                        ..post_fx.clone()
+                    })
                 }),
-                None => container({
-                    let select_file_button = button("Select audio file");
-                    if !self.is_loading_file {
-                        select_file_button.on_press(Message::OpenAudioFile)
-                    } else {
-                        select_file_button
+                labeled(
+                    t_args(
+                        "bloom-value",
+                        &[("value", &format!("{:.2}", post_fx.bloom))]
+                    ),
+                    slider(0.0..=1., post_fx.bloom, move |bloom| {
+                        changed(PostFx {
+                            bloom,
+                            ..post_fx.clone()
+                        })
+                    })
+                    .step(0.01)
+                    .into(),
+                ),
+                labeled(
+                    t_args(
+                        "threshold-value",
+                        &[("value", &format!("{:.2}", post_fx.bloom_threshold))]
+                    ),
+                    slider(0.0..=1., post_fx.bloom_threshold, move |bloom_threshold| {
+                        changed(PostFx {
+                            bloom_threshold,
+                            ..post_fx.clone()
+                        })
+                    })
+                    .step(0.01)
+                    .into(),
+                ),
+                labeled(
+                    t_args(
+                        "glow-value",
+                        &[("value", &format!("{:.0}", post_fx.bloom_radius))]
+                    ),
+                    slider(1.0..=100., post_fx.bloom_radius, move |bloom_radius| {
+                        changed(PostFx {
+                            bloom_radius,
+                            ..post_fx.clone()
+                        })
+                    })
+                    .step(1.)
+                    .into(),
+                ),
+                labeled(
+                    t_args(
+                        "aberration-value",
+                        &[("value", &format!("{:.0}", post_fx.aberration))]
+                    ),
+                    slider(0.0..=30., post_fx.aberration, move |aberration| {
+                        changed(PostFx {
+                            aberration,
+                            ..post_fx.clone()
+                        })
+                    })
+                    .step(1.)
+                    .into(),
+                ),
+                labeled(
+                    t_args(
+                        "sharpen-value",
+                        &[("value", &format!("{:.2}", post_fx.sharpen))]
+                    ),
+                    slider(0.0..=1., post_fx.sharpen, move |sharpen| {
+                        changed(PostFx {
+                            sharpen,
+                            ..post_fx.clone()
+                        })
+                    })
+                    .step(0.01)
+                    .into(),
+                ),
+                container(kaleidoscope_view(
+                    t("kaleidoscope"),
+                    post_fx.kaleidoscope,
+                    move |kaleidoscope| {
+                        changed(PostFx {
+                            kaleidoscope,
+                            ..post_fx.clone()
+                        })
                     }
-                }),
-            }
-        };
+                ))
+                .width(130.),
+                post_fx_lut_view(post_fx),
+            ]
+            .push_maybe(
+                post_fx
+                    .is_filtered()
+                    .then(|| text(t("post-fx-rendered")).size(12.)),
+            )
+            .spacing(12.)
+            .align_y(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(Padding::from([6., 7.]))
+        .into()
+    }
+
+    /// The controls for comparing the canvas with the version pinned as "A",
+    /// while one is.
+    fn compare_view(&self) -> Option<Element<'_, Message>> {
+        let comparison = self.canvas_state.comparison.as_ref()?;
 
-        let audio_section = container(audio_section_content)
+        let views = CompareView::ALL
+            .into_iter()
+            .fold(row![], |row, view| {
+                row.push(
+                    button(text(view.to_string()).size(12.))
+                        .style(if view == comparison.view {
+                            button::primary
+                        } else {
+                            button::secondary
+                        })
+                        .on_press(Message::CompareViewSelected(view)),
+                )
+            })
+            .spacing(2.);
+
+        Some(
+            container(
+                row![text(t("compare")), views]
+                    .push_maybe((comparison.view == CompareView::Split).then(|| {
+                        slider(0.0..=1.0, comparison.split, Message::CompareSplitChanged)
+                            .step(0.01)
+                            .width(160.)
+                    }))
+                    .push(horizontal_space())
+                    .push(
+                        tooltip(
+                            button(t("compare-repin"))
+                                .style(button::secondary)
+                                .on_press(Message::PinComparison),
+                            t("compare-repin-tooltip"),
+                            tooltip::Position::Bottom,
+                        )
+                        .style(container::rounded_box),
+                    )
+                    .push(
+                        button(t("compare-stop"))
+                            .style(button::secondary)
+                            .on_press(Message::StopComparing),
+                    )
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+            )
             .width(Length::Fill)
-            .padding(Padding::from([6., 7.]));
+            .padding(Padding::from([6., 7.]))
+            .into(),
+        )
+    }
 
-        let canvas_section = container(
-            container(responsive(|size| {
-                let canvas_width = self.canvas_width;
-                let canvas_height = self.canvas_height;
-                let aspect_ratio = canvas_width / canvas_height;
+    fn stats_view(&self) -> Element<'_, Message> {
+        let stats = &self.stats;
+        let milliseconds = |duration: Duration| duration.as_secs_f32() * 1000.;
+        let lines = [
+            t_args("stats-fps", &[("fps", &stats.fps())]),
+            t_args(
+                "stats-frame",
+                &[
+                    ("update", &format!("{:.1}", milliseconds(stats.update_time))),
+                    (
+                        "draw",
+                        &format!("{:.1}", milliseconds(self.canvas_state.draw_time())),
+                    ),
+                ],
+            ),
+            t_args(
+                "stats-draw-calls",
+                &[("count", &self.canvas_state.draw_calls())],
+            ),
+            t_args(
+                "stats-layers",
+                &[
+                    ("drawn", &stats.layers_drawn),
+                    ("cached", &(stats.layers_drawn - stats.layers_redrawn)),
+                ],
+            ),
+            match self.renders.hit_rate() {
+                Some(rate) => t_args(
+                    "stats-renders",
+                    &[("percent", &format!("{:.0}", rate * 100.))],
+                ),
+                None => t("stats-renders-none").into(),
+            },
+            match stats.analysis_time {
+                Some(time) => t_args(
+                    "stats-analysis",
+                    &[("time", &format!("{:.0}", milliseconds(time)))],
+                ),
+                None => t("stats-analysis-none").into(),
+            },
+            t_args(
+                "stats-memory",
+                &[("size", &format_size(self.memory_usage().total()))],
+            ),
+        ];
 
-                let should_downsize = canvas_width > size.width;
+        container(
+            lines
+                .into_iter()
+                .fold(column![], |column, line| {
+                    column.push(text(line).size(12.).font(Font::MONOSPACE))
+                })
+                .spacing(2.),
+        )
+        .padding(Padding::from([6., 7.]))
+        .style(container::rounded_box)
+        .into()
+    }
 
-                let final_width = if should_downsize {
-                    Length::Fixed(size.width)
-                } else {
-                    Length::Fill
-                };
+    fn status_bar_view(&self) -> Element<'_, Message> {
+        let mut items = vec![
+            format!("{} × {}", self.canvas_width, self.canvas_height),
+            t_args(
+                "status-zoom",
+                &[("percent", &(self.canvas_zoom() * 100.).round())],
+            ),
+            self.time_format.format(self.position(), self.frame_rate),
+        ];
+        items.extend(
+            self.canvas_state
+                .layers
+                .get(self.selected_layer_index)
+                .map(|layer| layer.name.clone()),
+        );
+        if self.background_tasks.analyses > 0 {
+            items.push(t("status-analyzing").to_string());
+        }
+        if self.background_tasks.decodes > 0 {
+            items.push(t_args(
+                "status-decoding",
+                &[("count", &self.background_tasks.decodes)],
+            ));
+        }
 
-                let final_height = if should_downsize {
-                    Length::Fixed(size.width / aspect_ratio)
-                } else {
-                    Length::Fill
-                };
+        container(text(items.join("  ·  ")).size(12.).style(text::secondary))
+            .width(Length::Fill)
+            .padding(Padding::from([3., 7.]))
+            .into()
+    }
 
-                canvas(&self.canvas_state)
-                    .width(final_width)
-                    .height(final_height)
-                    .into()
-            }))
-            .width(Length::Fixed(self.canvas_width))
-            .height(Length::Fixed(self.canvas_height)),
-        )
-        .center(Length::Fill);
+    /// How much the canvas is scaled down to fit beside the sidebar, which
+    /// takes up a third of the window.
+    fn canvas_zoom(&self) -> f32 {
+        let available = (self.main_window_size.width - 1.) * 2. / 3.;
 
-        let main_column = column![canvas_section, horizontal_separator(), audio_section]
-            .width(Length::FillPortion(2));
+        (available / self.canvas_width).min(1.)
+    }
 
-        let selected_layer = self.canvas_state.layers.get(self.selected_layer_index);
+    /// The list of layers. Projects can have hundreds, so only the rows in
+    /// view are laid out, with empty space standing in for the others.
+    fn layer_list_view(&self) -> Element<'_, Message> {
+        let (offset, height) = self.layer_list_viewport;
+        let listed = self.listed_layers();
+        let count = listed.len();
+        let first = ((offset / LAYER_ROW_HEIGHT) as usize).min(count);
+        let last = (((offset + height) / LAYER_ROW_HEIGHT).ceil() as usize + 1).min(count);
 
-        let layer_selection_list = SelectionList::new_with(
-            &self.layer_names,
-            Message::LayerSelected,
-            14.,
-            [6., 7.],
-            |theme: &Theme, status: Status| {
-                let base = iced_aw::style::selection_list::Style::default();
-                let palette = theme.extended_palette();
-
-                match status {
-                    Status::Hovered => iced_aw::style::selection_list::Style {
-                        text_color: palette.secondary.weak.text.into(),
-                        background: palette.secondary.weak.color.into(),
-                        border_width: 0.,
-                        ..base
-                    },
-                    Status::Selected => iced_aw::style::selection_list::Style {
-                        text_color: palette.primary.weak.text.into(),
-                        background: palette.primary.weak.color.into(),
-                        border_width: 0.,
-                        ..base
-                    },
-                    _ => iced_aw::style::selection_list::Style {
-                        text_color: palette.background.base.text.into(),
-                        background: palette.background.base.color.into(),
-                        border_width: 0.,
-                        ..base
-                    },
-                }
-            },
-            Some(self.selected_layer_index),
-            Font::default(),
-        );
+        let rows = listed[first..last]
+            .iter()
+            .fold(column![], |column, &index| {
+                let name = &self.layer_names[index];
+                let is_selected = index == self.selected_layer_index;
 
-        let layer_list_section = column![
-            container("Layers").padding(Padding::from([6., 7.])),
-            horizontal_separator(),
-            container(layer_selection_list).center(Length::Fill),
-            horizontal_separator(),
-            container(
-                row![
-                    icon_button_with_tooltip("plus", "Add new layer", Some(Message::AddImageLayer)),
-                    icon_button_with_tooltip(
-                        "trash",
-                        "Delete layer",
-                        match selected_layer {
-                            Some(_) => Some(Message::RemoveLayer(self.selected_layer_index)),
-                            None => None,
+                let row = button(text(name))
+                    .width(Length::Fill)
+                    .height(LAYER_ROW_HEIGHT)
+                    .padding(Padding::from([6., 7.]))
+                    .style(move |theme: &Theme, status| {
+                        let palette = theme.extended_palette();
+                        let pair = if is_selected {
+                            palette.primary.weak
+                        } else if status == button::Status::Hovered {
+                            palette.secondary.weak
+                        } else {
+                            palette.background.base
+                        };
+
+                        button::Style {
+                            background: Some(pair.color.into()),
+                            text_color: pair.text,
+                            ..button::text(theme, status)
                         }
+                    })
+                    .on_press(Message::LayerSelected(index, name.clone()));
+
+                column.push(
+                    tooltip(
+                        row,
+                        text(self.layer_details(index)),
+                        tooltip::Position::Left,
                     )
-                ]
-                .spacing(6.)
+                    .style(container::rounded_box),
+                )
+            });
+
+        scrollable(column![
+            vertical_space().height(first as f32 * LAYER_ROW_HEIGHT),
+            rows,
+            vertical_space().height((count - last) as f32 * LAYER_ROW_HEIGHT),
+        ])
+        .id(layer_list_id())
+        .on_scroll(Message::LayerListScrolled)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn assets_view(&self) -> Element<'_, Message> {
+        if self.assets.iter().next().is_none() {
+            return container(t("assets-empty")).center(Length::Fill).into();
+        }
+
+        let usage = self.memory_usage();
+        let memory = container(
+            column![
+                row![
+                    text(t("memory")),
+                    horizontal_space(),
+                    text(t_args(
+                        "memory-of-budget",
+                        &[
+                            ("used", &format_size(usage.total())),
+                            (
+                                "budget",
+                                &format_size(self.memory_budget as usize * 1024 * 1024),
+                            ),
+                        ],
+                    )),
+                ],
+                text(t_args(
+                    "memory-breakdown",
+                    &[
+                        ("files", &format_size(usage.files)),
+                        ("previews", &format_size(usage.previews)),
+                        ("renders", &format_size(usage.renders)),
+                        ("analysis", &format_size(usage.analysis)),
+                    ],
+                ))
+                .size(12.)
+                .style(text::secondary),
+                tooltip(
+                    slider(256..=8192, self.memory_budget, Message::MemoryBudgetChanged)
+                        .step(256u32),
+                    t("memory-budget-tooltip"),
+                    tooltip::Position::Top,
+                )
+                .style(container::rounded_box),
+            ]
+            .spacing(3.),
+        )
+        .padding(Padding::from([6., 7.]));
+
+        let assets = self.assets.iter().fold(column![], |column, asset| {
+            let kind = match asset.kind {
+                AssetKind::Image => t("asset-image"),
+                AssetKind::Audio => t("asset-audio"),
+                AssetKind::Animation => t("asset-animation"),
+            };
+            let uses = match self.asset_usage(asset.id) {
+                0 => t("asset-unused").to_string(),
+                1 => t("asset-one-use").to_string(),
+                uses => t_args("asset-uses", &[("count", &uses)]),
+            };
+
+            column.push(
+                container(
+                    row![column![
+                        text(asset.name()),
+                        text(format!(
+                            "{} · {} · {}",
+                            kind,
+                            format_size(asset.size()),
+                            uses
+                        ))
+                        .size(12.)
+                        .style(text::secondary),
+                    ]
+                    .spacing(2.)
+                    .width(Length::Fill),]
+                    .push_maybe((asset.kind == AssetKind::Image).then(|| {
+                        icon_button_with_tooltip(
+                            "palette",
+                            t("palette-extract"),
+                            Some(Message::ExtractPalette(asset.id)),
+                        )
+                    }))
+                    .push(icon_button_with_tooltip(
+                        "replace",
+                        t("asset-replace"),
+                        Some(Message::ReplaceAsset(asset.id)),
+                    ))
+                    .push(icon_button_with_tooltip(
+                        "trash",
+                        t("asset-remove"),
+                        Some(Message::RemoveAsset(asset.id)),
+                    ))
+                    .spacing(6.)
+                    .align_y(Alignment::Center),
+                )
+                .padding(Padding::from([6., 7.])),
             )
-            .padding(Padding::from([6., 7.]))
-        ]
-        .height(Length::Fill);
+        });
 
-        let selected_layer_settings_section = column![
-            container(text(match selected_layer {
-                Some(layer) => &layer.name,
-                None => "Layer settings",
-            }))
-            .padding(Padding::from([6., 7.])),
+        column![
+            memory,
             horizontal_separator(),
-            self.layer_settings_view(selected_layer),
-        ];
+            scrollable(assets).height(Length::Fill)
+        ]
+        .into()
+    }
+
+    fn swatches_view(&self) -> Element<'_, Message> {
+        let add = container(icon_button_with_tooltip(
+            "plus",
+            t("swatch-add"),
+            Some(Message::AddSwatch(None)),
+        ))
+        .padding(Padding::from([6., 7.]));
+        if self.swatches.is_empty() {
+            return column![
+                container(text(t("swatches-empty")).style(text::secondary))
+                    .center(Length::Fill)
+                    .padding(12.),
+                horizontal_separator(),
+                add,
+            ]
+            .into();
+        }
+
+        let swatches =
+            self.swatches
+                .iter()
+                .enumerate()
+                .fold(column![], |column, (index, swatch)| {
+                    let uses = match self
+                        .swatch_uses
+                        .iter()
+                        .filter(|swatch_use| swatch_use.swatch == index)
+                        .count()
+                    {
+                        0 => t("asset-unused").to_string(),
+                        1 => t("asset-one-use").to_string(),
+                        uses => t_args("asset-uses", &[("count", &uses)]),
+                    };
+
+                    column.push(
+                        container(
+                            row![
+                                self.color_input(ColorField::Swatch(index)),
+                                column![
+                                    text_input(t("swatch-name-placeholder"), &swatch.name)
+                                        .on_input(move |name| Message::SwatchNameChanged(
+                                            index, name
+                                        ))
+                                        .padding(4.),
+                                    text(format!("{} · {}", palette::to_hex(swatch.color), uses))
+                                        .size(12.)
+                                        .style(text::secondary),
+                                ]
+                                .spacing(2.)
+                                .width(Length::Fill),
+                                icon_button_with_tooltip(
+                                    "trash",
+                                    t("swatch-remove"),
+                                    Some(Message::RemoveSwatch(index))
+                                ),
+                            ]
+                            .spacing(6.)
+                            .align_y(Alignment::Center),
+                        )
+                        .padding(Padding::from([6., 7.])),
+                    )
+                });
 
-        let settings_column = column![
-            selected_layer_settings_section,
+        column![
+            scrollable(swatches).height(Length::Fill),
             horizontal_separator(),
-            layer_list_section
+            add,
         ]
-        .width(Length::FillPortion(1))
-        .height(Length::Fill);
+        .into()
+    }
+
+    fn snapshots_view(&self) -> Element<'_, Message> {
+        let take = container(
+            row![
+                text_input(t("snapshot-name-placeholder"), &self.snapshot_name)
+                    .on_input(Message::SnapshotNameChanged)
+                    .on_submit(Message::TakeSnapshot)
+                    .padding(4.),
+                icon_button_with_tooltip("plus", t("snapshot-take"), Some(Message::TakeSnapshot)),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+        )
+        .padding(Padding::from([6., 7.]));
+        if self.snapshots.is_empty() {
+            return column![
+                container(text(t("snapshots-empty")).style(text::secondary))
+                    .center(Length::Fill)
+                    .padding(12.),
+                horizontal_separator(),
+                take,
+            ]
+            .into();
+        }
 
-        row![main_column, vertical_separator(), settings_column].into()
+        // The latest first.
+        let snapshots =
+            self.snapshots
+                .iter()
+                .enumerate()
+                .rev()
+                .fold(column![], |column, (index, snapshot)| {
+                    column.push(
+                        container(
+                            row![
+                                column![
+                                    text_input(t("snapshot-name-placeholder"), &snapshot.name)
+                                        .on_input(move |name| Message::RenameSnapshot(index, name))
+                                        .padding(4.),
+                                    text(snapshot_age(snapshot.taken))
+                                        .size(12.)
+                                        .style(text::secondary),
+                                ]
+                                .spacing(2.)
+                                .width(Length::Fill),
+                                icon_button_with_tooltip(
+                                    "reset",
+                                    t("snapshot-restore"),
+                                    Some(Message::RestoreSnapshot(index))
+                                ),
+                                icon_button_with_tooltip(
+                                    "trash",
+                                    t("snapshot-remove"),
+                                    Some(Message::RemoveSnapshot(index))
+                                ),
+                            ]
+                            .spacing(6.)
+                            .align_y(Alignment::Center),
+                        )
+                        .padding(Padding::from([6., 7.])),
+                    )
+                });
+
+        column![
+            scrollable(snapshots).height(Length::Fill),
+            horizontal_separator(),
+            take,
+        ]
+        .into()
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        frames().map(|_| Message::Tick)
+        Subscription::batch([
+            if self.transport.is_playing() {
+                iced::time::every(self.frame_rate.frame_duration()).map(|_| Message::Tick)
+            } else {
+                Subscription::none()
+            },
+            // Escape is also caught by the palette's text input, so it is
+            // listened for before widgets get to handle it.
+            if self.command_palette.is_some() {
+                iced::event::listen_with(|event, _status, _window| match event {
+                    iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                        key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                        ..
+                    }) => Some(Message::CloseCommandPalette),
+                    _ => None,
+                })
+            } else {
+                Subscription::none()
+            },
+            if self.file_load.is_some() || self.analysis_progress.is_some() {
+                iced::time::every(Duration::from_millis(100)).map(|_| Message::LoadProgressed)
+            } else {
+                Subscription::none()
+            },
+            if self
+                .canvas_state
+                .layers
+                .iter()
+                .any(|layer| layer.capture_source().is_some())
+            {
+                iced::time::every(self.frame_rate.frame_duration()).map(|_| Message::CaptureTick)
+            } else {
+                Subscription::none()
+            },
+            if self.toasts.is_empty() {
+                Subscription::none()
+            } else {
+                iced::time::every(Duration::from_millis(500)).map(|_| Message::ExpireToasts)
+            },
+            window::close_events().map(Message::WindowClosed),
+            iced::event::listen_with(|event, _status, _window| match event {
+                iced::Event::Window(window::Event::FileDropped(path)) => {
+                    Some(Message::FileDropped(path))
+                }
+                iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                    Some(Message::ModifiersChanged(modifiers))
+                }
+                _ => None,
+            }),
+            window::resize_events().map(|(id, size)| Message::WindowResized(id, size)),
+            keyboard::on_key_press(|key, modifiers| match key.as_ref() {
+                keyboard::Key::Named(keyboard::key::Named::F11) => {
+                    Some(Message::TogglePreviewFullscreen)
+                }
+                keyboard::Key::Character("v") if modifiers.command() => Some(Message::PasteImage),
+                keyboard::Key::Named(keyboard::key::Named::F3) => Some(Message::ToggleStats),
+                keyboard::Key::Character("k") if modifiers.command() => {
+                    Some(Message::OpenCommandPalette)
+                }
+                keyboard::Key::Named(keyboard::key::Named::F6) => {
+                    Some(Message::FocusPanel(if modifiers.shift() { -1 } else { 1 }))
+                }
+                keyboard::Key::Named(keyboard::key::Named::Tab) => {
+                    Some(Message::FocusInput(modifiers.shift()))
+                }
+                keyboard::Key::Named(keyboard::key::Named::Escape) => Some(Message::ClearFocus),
+                keyboard::Key::Named(keyboard::key::Named::Enter | keyboard::key::Named::Space) => {
+                    Some(Message::ActivateFocused)
+                }
+                keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+                    Some(Message::ArrowPressed(Arrow::Up))
+                }
+                keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+                    Some(Message::ArrowPressed(Arrow::Down))
+                }
+                keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+                    Some(Message::ArrowPressed(Arrow::Left))
+                }
+                keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+                    Some(Message::ArrowPressed(Arrow::Right))
+                }
+                _ => None,
+            }),
+        ])
     }
 }
 
-#[derive(Debug)]
-struct Layer {
-    name: String,
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
-    scale: f32,
-    opacity: f32,
-    handle: Handle,
+const LAYER_ROW_HEIGHT: f32 = 30.;
+
+fn layer_list_id() -> scrollable::Id {
+    scrollable::Id::new("layer-list")
 }
 
-impl Display for Layer {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.name)
-    }
+/// Longest side, in pixels, that [`Roygbiv::enforce_memory_budget`] keeps
+/// previews at.
+const MIN_PREVIEW_SIZE: u32 = 512;
+
+/// Formats a position in the track as `m:ss.cc`.
+fn format_time(time: Duration) -> String {
+    let centiseconds = time.as_millis() / 10;
+
+    format!(
+        "{}:{:02}.{:02}",
+        centiseconds / 6000,
+        centiseconds / 100 % 60,
+        centiseconds % 100
+    )
 }
 
-#[derive(Debug)]
-struct CanvasState {
-    layers: Vec<Layer>,
-    background_cache: canvas::Cache,
-    layers_cache: canvas::Cache,
+fn icon(name: &str) -> svg::Handle {
+    svg::Handle::from_path(format!(
+        "{}/src/icons/{}.svg",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    ))
 }
 
-impl CanvasState {
-    pub fn new() -> CanvasState {
-        CanvasState {
-            layers: vec![],
-            background_cache: canvas::Cache::default(),
-            layers_cache: canvas::Cache::default(),
-        }
-    }
+/// Shown in place of the canvas until there is something on it, with the
+/// ways to get started.
+fn empty_canvas_view<'a>() -> Element<'a, Message> {
+    let action = |label, message| {
+        button(text(label).size(16.))
+            .padding(Padding::from([10., 16.]))
+            .on_press(message)
+    };
 
-    pub fn update(&mut self) {
-        self.layers_cache.clear();
-    }
+    center(
+        column![
+            text(t("empty-title")).size(20.),
+            row![
+                action(t("empty-open-audio"), Message::OpenAudioFile),
+                action(t("empty-add-layer"), Message::AddImageLayer),
+                action(t("empty-open-project"), Message::OpenProject),
+            ]
+            .spacing(9.),
+            text(t("empty-drop-hint")).style(text::secondary),
+        ]
+        .spacing(12.)
+        .align_x(Alignment::Center),
+    )
+    .into()
 }
 
-impl<Message> canvas::Program<Message> for CanvasState {
-    type State = ();
+fn start_screen_view<'a>() -> Element<'a, Message> {
+    let templates = Template::ALL
+        .iter()
+        .fold(column![].spacing(6.), |column, template| {
+            column.push(
+                button(
+                    column![
+                        text(template.name()).size(16.),
+                        text(template.description()).style(text::secondary),
+                    ]
+                    .spacing(2.),
+                )
+                .style(button::secondary)
+                .width(Length::Fill)
+                .padding(Padding::from([8., 10.]))
+                .on_press(Message::TemplateSelected(*template)),
+            )
+        });
 
-    fn draw(
-        &self,
-        _state: &Self::State,
-        renderer: &Renderer,
-        _theme: &Theme,
-        bounds: Rectangle,
-        _cursor: mouse::Cursor,
-    ) -> Vec<canvas::Geometry<Renderer>> {
-        let mut stuff: Vec<canvas::Geometry<Renderer>> = vec![];
-
-        let bounds_size = bounds.size();
-
-        let background = self.background_cache.draw(renderer, bounds_size, |frame| {
-            frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::BLACK);
+    center(
+        column![
+            text(t("project-new")).size(20.),
+            templates,
+            row![
+                button(t("project-open-dialog")).on_press(Message::OpenProject),
+                button(t("audiogram-wizard"))
+                    .style(button::secondary)
+                    .on_press(Message::OpenAudiogramWizard),
+                horizontal_space(),
+                button(t("cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseStartScreen),
+            ]
+        ]
+        .spacing(12.)
+        .width(420.),
+    )
+    .into()
+}
+
+fn audiogram_wizard_view(wizard: &AudiogramWizard) -> Element<'_, Message> {
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let step = |label, picked: Option<String>, message: Message| {
+        row![
+            text(label).width(Length::Fixed(110.)),
+            text(picked.unwrap_or_else(|| t("audiogram-none").to_string()))
+                .style(text::secondary)
+                .width(Length::Fill),
+            button(t("audiogram-choose"))
+                .style(button::secondary)
+                .on_press(message),
+        ]
+        .spacing(6.)
+        .align_y(Alignment::Center)
+    };
+
+    let dialog = container(
+        column![
+            text(t("audiogram-wizard")).size(16.),
+            text(t("audiogram-description")),
+            step(
+                t("audiogram-audio"),
+                wizard.audio.as_ref().map(|(path, _)| file_name(path)),
+                Message::PickAudiogramAudio
+            ),
+            step(
+                t("audiogram-cover"),
+                wizard.cover.as_ref().map(|(path, _)| file_name(path)),
+                Message::PickAudiogramCover
+            ),
+            row![
+                text(t("audiogram-title")).width(Length::Fixed(110.)),
+                text_input(t("audiogram-title-placeholder"), &wizard.title)
+                    .on_input(Message::AudiogramTitleChanged)
+                    .padding(4.),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+            row![
+                text(t("audiogram-format")).width(Length::Fixed(110.)),
+                pick_list(
+                    Orientation::ALL,
+                    wizard.orientation,
+                    Message::AudiogramOrientationSelected
+                ),
+            ]
+            .spacing(6.)
+            .align_y(Alignment::Center),
+            row![
+                horizontal_space(),
+                button(t("cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseAudiogramWizard),
+                button(t("audiogram-create"))
+                    .on_press_maybe(wizard.audio.is_some().then_some(Message::CreateAudiogram)),
+            ]
+            .spacing(6.)
+        ]
+        .spacing(12.),
+    )
+    .width(480.)
+    .padding(12.)
+    .style(container::rounded_box);
+
+    opaque(center(dialog).style(|_| {
+        container::Style {
+            background: Some(
+                Color {
+                    a: 0.6,
+                    ..Color::BLACK
+                }
+                .into(),
+            ),
+            ..Default::default()
+        }
+    }))
+}
+
+fn relink_view(relink: &Relink) -> Element<'_, Message> {
+    let missing = relink
+        .missing
+        .iter()
+        .fold(column![].spacing(6.), |column, asset| {
+            let path = relink
+                .project
+                .asset_path(*asset)
+                .map(|path| path.display().to_string())
+                .unwrap_or_default();
+
+            column.push(
+                row![
+                    text(path),
+                    horizontal_space(),
+                    button(t("relink-browse")).on_press(Message::RelinkBrowse(*asset))
+                ]
+                .spacing(6.)
+                .align_y(Alignment::Center),
+            )
         });
-        stuff.push(background);
-
-        stuff.push(self.layers_cache.draw(renderer, bounds_size, |frame| {
-            for layer_index in 0..self.layers.len() {
-                let layer = &self.layers.get(layer_index).unwrap();
-
-                frame.draw_image(
-                    Rectangle {
-                        x: layer.x,
-                        y: layer.y,
-                        width: layer.width,
-                        height: layer.height,
-                    },
-                    &layer.handle,
-                );
+
+    let dialog = container(
+        column![
+            text(t("relink-title")).size(16.),
+            text(t("relink-description")),
+            missing,
+            row![
+                horizontal_space(),
+                button(t("cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::RelinkCancel),
+                button(if relink.missing.is_empty() {
+                    t("project-open")
+                } else {
+                    t("relink-skip")
+                })
+                .on_press(Message::RelinkSkipMissing)
+            ]
+            .spacing(6.)
+        ]
+        .spacing(12.),
+    )
+    .width(480.)
+    .padding(12.)
+    .style(container::rounded_box);
+
+    opaque(center(dialog).style(|_| {
+        container::Style {
+            background: Some(
+                Color {
+                    a: 0.6,
+                    ..Color::BLACK
+                }
+                .into(),
+            ),
+            ..Default::default()
+        }
+    }))
+}
+
+/// How a layer or a scene comes in and goes.
+fn transition_view<'a>(
+    transition: Transition,
+    changed: impl Fn(Transition) -> Message + Copy + 'a,
+) -> Element<'a, Message> {
+    column![
+        text(t("transition-label")),
+        pick_list(TransitionKind::ALL, Some(transition.kind), move |kind| {
+            changed(Transition { kind, ..transition })
+        })
+        .width(Length::Fill),
+    ]
+    .push_maybe((transition.kind != TransitionKind::None).then(|| {
+        column![
+            text(t_args(
+                "duration-seconds",
+                &[("value", &format!("{:.1}", transition.duration))]
+            )),
+            slider(0.1..=5., transition.duration, move |duration| {
+                changed(Transition {
+                    duration,
+                    ..transition
+                })
+            })
+            .step(0.1),
+            pick_list(Easing::ALL, Some(transition.easing), move |easing| {
+                changed(Transition {
+                    easing,
+                    ..transition
+                })
+            })
+            .width(Length::Fill),
+        ]
+        .spacing(3.)
+    }))
+    .spacing(3.)
+    .into()
+}
+
+fn distortion_view<'a>(index: usize, distortion: Distortion) -> Element<'a, Message> {
+    let changed = move |distortion| Message::LayerDistortionChanged(index, distortion);
+
+    column![
+        text(t("distortion-label")),
+        pick_list(DistortionKind::ALL, Some(distortion.kind), move |kind| {
+            changed(Distortion { kind, ..distortion })
+        })
+        .width(Length::Fill),
+    ]
+    .push_maybe((distortion.kind != DistortionKind::None).then(|| {
+        column![
+            text(t_args(
+                "amount-px",
+                &[("value", &format!("{:.0}", distortion.amount))]
+            )),
+            slider(0.0..=100., distortion.amount, move |amount| {
+                changed(Distortion {
+                    amount,
+                    ..distortion
+                })
+            })
+            .step(1.),
+            text(t_args(
+                "scale-px",
+                &[("value", &format!("{:.0}", distortion.scale))]
+            )),
+            slider(10.0..=1000., distortion.scale, move |scale| {
+                changed(Distortion {
+                    scale,
+                    ..distortion
+                })
+            })
+            .step(5.),
+            text(t_args(
+                "speed-per-second",
+                &[("value", &format!("{:.2}", distortion.speed))]
+            )),
+            slider(0.0..=4., distortion.speed, move |speed| {
+                changed(Distortion {
+                    speed,
+                    ..distortion
+                })
+            })
+            .step(0.05),
+            checkbox(t("distortion-follows-bass"), distortion.follows_bass).on_toggle(
+                move |follows_bass| changed(Distortion {
+                    follows_bass,
+                    ..distortion
+                })
+            ),
+        ]
+        .spacing(3.)
+    }))
+    .spacing(3.)
+    .into()
+}
+
+fn lut_view<'a>(index: usize, lut: Option<&Lut>) -> Element<'a, Message> {
+    let Some(lut) = lut else {
+        return button(t("lut-load"))
+            .style(button::secondary)
+            .on_press(Message::LoadLayerLut(index))
+            .into();
+    };
+
+    column![
+        row![
+            text(t_args("lut-name", &[("name", &lut.name)])).width(Length::Fill),
+            button(t("replace"))
+                .style(button::secondary)
+                .on_press(Message::LoadLayerLut(index)),
+            button(t("remove"))
+                .style(button::secondary)
+                .on_press(Message::RemoveLayerLut(index)),
+        ]
+        .spacing(6.)
+        .align_y(Alignment::Center),
+        text(t_args(
+            "amount-value",
+            &[("value", &format!("{:.2}", lut.amount))]
+        )),
+        slider(0.0..=1., lut.amount, move |amount| {
+            Message::LayerLutAmountChanged(index, amount)
+        })
+        .step(0.01),
+    ]
+    .spacing(3.)
+    .into()
+}
+
+/// The project's color grade, in the post FX bar.
+fn post_fx_lut_view(post_fx: &PostFx) -> Element<'_, Message> {
+    let Some(lut) = &post_fx.lut else {
+        return button(t("lut-load"))
+            .style(button::secondary)
+            .on_press(Message::LoadPostFxLut)
+            .into();
+    };
+
+    row![
+        column![
+            text(t_args(
+                "lut-name-amount",
+                &[
+                    ("name", &lut.name),
+                    ("amount", &format!("{:.2}", lut.amount))
+                ],
+            ))
+            .size(12.),
+            slider(0.0..=1., lut.amount, move |amount| {
+                let mut post_fx = post_fx.clone();
+                if let Some(lut) = &mut post_fx.lut {
+                    lut.amount = amount;
+                }
+                Message::PostFxChanged(post_fx)
+            })
+            .step(0.01),
+        ]
+        .spacing(2.)
+        .width(130.),
+        button(t("replace"))
+            .style(button::secondary)
+            .on_press(Message::LoadPostFxLut),
+        button(t("remove"))
+            .style(button::secondary)
+            .on_press(Message::PostFxChanged(PostFx {
+                lut: None,
+                ..post_fx.clone()
+            })),
+    ]
+    .spacing(6.)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// The blur and darkening of an image layer's own image, or of what is
+/// beneath a backdrop layer.
+fn backdrop_view<'a>(index: usize, layer: &Layer) -> Element<'a, Message> {
+    let Appearance { blur, darken, .. } = layer.appearance;
+
+    column![
+        text(if matches!(layer.kind, LayerKind::Backdrop) {
+            t("backdrop-beneath")
+        } else {
+            t("backdrop-image")
+        }),
+        text(t_args("blur-px", &[("value", &format!("{:.0}", blur))])),
+        slider(0.0..=100., blur, move |blur| Message::LayerBlurChanged(
+            index, blur
+        ))
+        .step(1.),
+        text(t_args(
+            "darken-value",
+            &[("value", &format!("{:.2}", darken))]
+        )),
+        slider(0.0..=1., darken, move |darken| Message::LayerDarkenChanged(
+            index, darken
+        ))
+        .step(0.01),
+    ]
+    .push_maybe(matches!(layer.kind, LayerKind::Image { .. }).then(|| {
+        button(t("backdrop-add"))
+            .style(button::secondary)
+            .on_press(Message::AddBlurredBackdrop(index))
+    }))
+    .spacing(3.)
+    .into()
+}
+
+/// The controls for a kaleidoscope, on a layer or in the post FX, labeled
+/// `label`.
+fn kaleidoscope_view<'a>(
+    label: &str,
+    kaleidoscope: Kaleidoscope,
+    changed: impl Fn(Kaleidoscope) -> Message + Copy + 'a,
+) -> Element<'a, Message> {
+    let (center_x, center_y) = kaleidoscope.center;
+
+    column![
+        checkbox(label, kaleidoscope.is_enabled).on_toggle(move |is_enabled| changed(
+            Kaleidoscope {
+                is_enabled,
+                ..kaleidoscope
             }
-        }));
+        ))
+    ]
+    .push_maybe(kaleidoscope.is_enabled.then(|| {
+        column![
+            text(t_args(
+                "segments-value",
+                &[("value", &format!("{}", kaleidoscope.segments))]
+            )),
+            // Even counts, so the last slice mirrors into the first.
+            slider(2..=24, kaleidoscope.segments, move |segments| {
+                changed(Kaleidoscope {
+                    segments,
+                    ..kaleidoscope
+                })
+            })
+            .step(2u32),
+            text(t_args(
+                "rotation-value",
+                &[("value", &format!("{:.0}", kaleidoscope.rotation))]
+            )),
+            slider(0.0..=360., kaleidoscope.rotation, move |rotation| {
+                changed(Kaleidoscope {
+                    rotation,
+                    ..kaleidoscope
+                })
+            })
+            .step(1.),
+            text(t_args(
+                "center-value",
+                &[
+                    ("x", &format!("{:.2}", center_x)),
+                    ("y", &format!("{:.2}", center_y)),
+                ],
+            )),
+            row![
+                slider(0.0..=1., center_x, move |center_x| {
+                    changed(Kaleidoscope {
+                        center: (center_x, center_y),
+                        ..kaleidoscope
+                    })
+                })
+                .step(0.01),
+                slider(0.0..=1., center_y, move |center_y| {
+                    changed(Kaleidoscope {
+                        center: (center_x, center_y),
+                        ..kaleidoscope
+                    })
+                })
+                .step(0.01),
+            ]
+            .spacing(6.),
+        ]
+        .spacing(3.)
+    }))
+    .spacing(3.)
+    .into()
+}
 
-        stuff
-    }
+fn glitch_view<'a>(index: usize, glitch: Glitch) -> Element<'a, Message> {
+    let changed = move |glitch| Message::LayerGlitchChanged(index, glitch);
+
+    column![
+        checkbox(t("glitch"), glitch.is_enabled).on_toggle(move |is_enabled| changed(Glitch {
+            is_enabled,
+            ..glitch
+        }))
+    ]
+    .push_maybe(glitch.is_enabled.then(|| {
+        column![
+            text(t_args(
+                "glitch-split",
+                &[("value", &format!("{:.0}", glitch.split))]
+            )),
+            slider(0.0..=50., glitch.split, move |split| {
+                changed(Glitch { split, ..glitch })
+            })
+            .step(1.),
+            text(t_args(
+                "glitch-blocks",
+                &[("value", &format!("{:.2}", glitch.blocks))]
+            )),
+            slider(0.0..=1., glitch.blocks, move |blocks| {
+                changed(Glitch { blocks, ..glitch })
+            })
+            .step(0.01),
+            text(t_args(
+                "glitch-scanlines",
+                &[("value", &format!("{:.2}", glitch.scanlines))]
+            )),
+            slider(0.0..=1., glitch.scanlines, move |scanlines| {
+                changed(Glitch {
+                    scanlines,
+                    ..glitch
+                })
+            })
+            .step(0.01),
+            text(t("glitch-intensity")),
+            pick_list(GlitchTrigger::ALL, Some(glitch.trigger), move |trigger| {
+                changed(Glitch { trigger, ..glitch })
+            })
+            .width(Length::Fill),
+        ]
+        .spacing(3.)
+    }))
+    .spacing(3.)
+    .into()
+}
+
+/// The seed of a procedural layer's randomness, typed in or rolled at random
+/// to try out variations.
+fn seed_input<'a>(index: usize, seed: u64) -> Element<'a, Message> {
+    row![
+        text(t("seed-label")),
+        text_input("0", &seed.to_string())
+            .on_input(move |input| {
+                // Anything that isn't a number keeps the seed as it was.
+                let seed = match input.as_str() {
+                    "" => 0,
+                    input => input.parse().unwrap_or(seed),
+                };
+
+                Message::LayerSeedChanged(index, seed)
+            })
+            .padding(4.)
+            .width(Length::Fill),
+        icon_button_with_tooltip(
+            "shuffle",
+            t("seed-randomize"),
+            Some(Message::LayerSeedChanged(index, rand::random()))
+        ),
+    ]
+    .spacing(6.)
+    .align_y(Alignment::Center)
+    .into()
 }
 
-impl Default for CanvasState {
-    fn default() -> Self {
-        Self::new()
+/// A square of `color`, e.g. to pick a swatch with.
+fn swatch_chip<'a>(color: Color, size: f32) -> button::Button<'a, Message> {
+    button(
+        container(horizontal_space())
+            .width(size)
+            .height(size)
+            .style(swatch_style(color)),
+    )
+    .padding(2.)
+    .style(button::text)
+}
+
+fn swatch_style(color: Color) -> impl Fn(&Theme) -> container::Style {
+    move |_| container::Style {
+        background: Some(color.into()),
+        border: iced::Border {
+            color: Color::WHITE,
+            width: 1.,
+            radius: 3.into(),
+        },
+        ..Default::default()
     }
 }
 
-fn icon(name: &str) -> svg::Handle {
-    svg::Handle::from_path(format!(
-        "{}/src/icons/{}.svg",
-        env!("CARGO_MANIFEST_DIR"),
-        name
-    ))
+fn sidebar_tab_button(label: &str, tab: SidebarTab, selected: SidebarTab) -> Element<'_, Message> {
+    button(text(label))
+        .style(if tab == selected {
+            button::primary
+        } else {
+            button::text
+        })
+        .padding(Padding::from([3., 8.]))
+        .on_press(Message::SidebarTabSelected(tab))
+        .into()
+}
+
+/// A long load, how far it has got, and a button calling it off.
+fn load_progress<'a>(
+    label: &'a str,
+    progress: &LoadProgress,
+    cancel: Message,
+) -> Element<'a, Message> {
+    row![
+        text(label).size(12.).style(text::secondary),
+        progress_bar(0.0..=1.0, progress.fraction())
+            .width(120.)
+            .height(6.),
+        button(text(t("cancel")).size(12.))
+            .style(button::secondary)
+            .on_press(cancel),
+    ]
+    .spacing(6.)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// How long ago a snapshot taken at `taken`, in seconds since the Unix epoch,
+/// was taken.
+fn snapshot_age(taken: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    let age = now.saturating_sub(taken);
+
+    if age < 60 {
+        t("snapshot-just-now").to_string()
+    } else if age < 60 * 60 {
+        t_args("snapshot-minutes-ago", &[("count", &(age / 60))])
+    } else if age < 24 * 60 * 60 {
+        t_args("snapshot-hours-ago", &[("count", &(age / (60 * 60)))])
+    } else {
+        t_args("snapshot-days-ago", &[("count", &(age / (24 * 60 * 60)))])
+    }
 }
 
 fn horizontal_separator<'a>() -> Rule<'a> {
     horizontal_rule(1.).style(|theme: &Theme| {
         let palette = theme.extended_palette();
         rule::Style {
-            color: palette.background.weak.color.into(),
+            color: palette.background.weak.color,
             ..rule::default(theme)
         }
     })
@@ -526,12 +9020,30 @@ fn vertical_separator<'a>() -> Rule<'a> {
     vertical_rule(1.).style(|theme: &Theme| {
         let palette = theme.extended_palette();
         rule::Style {
-            color: palette.background.weak.color.into(),
+            color: palette.background.weak.color,
             ..rule::default(theme)
         }
     })
 }
 
+/// Wraps a field so the mouse wheel nudges it, and the arrow keys while the
+/// cursor is over it. Shift makes the steps coarser and Ctrl finer.
+fn nudgeable<'a>(
+    field: NumericField,
+    content: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    mouse_area(content)
+        .on_enter(Message::HoverField(field, true))
+        .on_exit(Message::HoverField(field, false))
+        .on_scroll(move |delta| {
+            let (mouse::ScrollDelta::Lines { y, .. } | mouse::ScrollDelta::Pixels { y, .. }) =
+                delta;
+
+            Message::NudgeField(field, y.signum())
+        })
+        .into()
+}
+
 fn icon_button_with_tooltip<'a, Message: Clone + 'a>(
     icon_name: &'a str,
     label: &'a str,
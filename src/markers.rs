@@ -0,0 +1,463 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n::t_args, Error};
+
+/// A named point on the timeline, e.g. a chapter of a podcast episode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    /// Seconds into the track.
+    pub time: f32,
+    pub name: String,
+}
+
+impl Display for Marker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The index of the marker the playhead at `time` is past, in markers sorted
+/// by time.
+pub fn current(markers: &[Marker], time: f32) -> Option<usize> {
+    markers
+        .partition_point(|marker| marker.time <= time)
+        .checked_sub(1)
+}
+
+/// Seconds into the track at which the marker at `index` gives way to the
+/// next one, if there is one.
+pub fn end(markers: &[Marker], index: usize) -> Option<f32> {
+    markers.get(index + 1).map(|marker| marker.time)
+}
+
+/// Reads the chapters of a file, with their times in the file: a list of
+/// times and names, one per line, or the chapters of an MP3's ID3 tag or of
+/// an M4A or M4B file's Nero chapter list. Empty if the file has none.
+pub async fn import(path: PathBuf) -> Result<Vec<Marker>, Error> {
+    tokio::task::spawn_blocking(move || {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let mut markers = match extension.as_str() {
+            "mp3" => read_id3(&mut File::open(&path)?),
+            "m4a" | "m4b" | "mp4" | "m4v" | "mov" => read_mp4(&mut File::open(&path)?),
+            _ => Ok(parse_list(&std::fs::read_to_string(&path)?)),
+        }?;
+        markers.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Ok(markers)
+    })
+    .await
+    .map_err(|_| Error::IoError(io::ErrorKind::Other))?
+    .map_err(|error: io::Error| Error::IoError(error.kind()))
+}
+
+/// Lines like `1:02:03.5,Name`, split at the first comma, semicolon or tab,
+/// or failing that at the first space as in a YouTube description. Lines
+/// that don't start with a time, such as a header, are skipped.
+fn parse_list(list: &str) -> Vec<Marker> {
+    list.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (time, name) = line
+                .split_once([',', ';', '\t'])
+                .or_else(|| line.split_once(' '))?;
+
+            Some(Marker {
+                time: parse_time(time.trim())?,
+                name: name.trim().trim_matches('"').to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Seconds from `63.5`, `1:03.5` or `0:01:03.5`. Rejects `inf`, `NaN` and
+/// times too long to be held.
+fn parse_time(time: &str) -> Option<f32> {
+    time.split(':')
+        .try_fold(0., |total, part| {
+            let part: f32 = part.parse().ok()?;
+
+            (part >= 0.).then_some(total * 60. + part)
+        })
+        .filter(|time| time.is_finite())
+}
+
+/// The `CHAP` frames of an ID3v2.3 or v2.4 tag at the start of the file.
+fn read_id3(file: &mut File) -> io::Result<Vec<Marker>> {
+    let mut header = [0; 10];
+    file.read_exact(&mut header)?;
+    let version = header[3];
+    if &header[..3] != b"ID3" || !(3..=4).contains(&version) {
+        return Ok(vec![]);
+    }
+    let mut tag = vec![0; syncsafe(&header[6..10]) as usize];
+    file.read_exact(&mut tag)?;
+
+    // Skips the extended header, whose size is counted differently by each
+    // version.
+    let mut frames = &tag[..];
+    if header[5] & 0x40 != 0 && tag.len() >= 4 {
+        let size = match version {
+            3 => be_u32(&tag[..4]) as usize + 4,
+            _ => syncsafe(&tag[..4]) as usize,
+        };
+        frames = tag.get(size..).unwrap_or_default();
+    }
+
+    let mut markers = vec![];
+    for (id, body) in id3_frames(frames, version) {
+        if &id != b"CHAP" {
+            continue;
+        }
+        // An element ID, then the start and end time in milliseconds and
+        // byte offsets, then frames of its own with the title.
+        let Some(element_end) = body.iter().position(|byte| *byte == 0) else {
+            continue;
+        };
+        let Some(times) = body.get(element_end + 1..element_end + 17) else {
+            continue;
+        };
+        let start = be_u32(&times[..4]);
+        let title = id3_frames(&body[element_end + 17..], version)
+            .find(|(id, _)| id == b"TIT2")
+            .map(|(_, title)| id3_text(title));
+
+        markers.push(Marker {
+            time: start as f32 / 1000.,
+            name: title
+                .unwrap_or_else(|| t_args("marker-chapter", &[("number", &(markers.len() + 1))])),
+        });
+    }
+
+    Ok(markers)
+}
+
+/// The ID and body of each frame in `frames`.
+fn id3_frames(frames: &[u8], version: u8) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    let mut rest = frames;
+
+    std::iter::from_fn(move || {
+        let header = rest.get(..10)?;
+        // The tag is padded with zeros after the last frame.
+        if header[0] == 0 {
+            return None;
+        }
+        let size = match version {
+            3 => be_u32(&header[4..8]),
+            _ => syncsafe(&header[4..8]),
+        } as usize;
+        let body = rest.get(10..10 + size)?;
+        let id = [header[0], header[1], header[2], header[3]];
+        rest = &rest[10 + size..];
+
+        Some((id, body))
+    })
+}
+
+/// A text frame's text, in the encoding named by its first byte.
+fn id3_text(frame: &[u8]) -> String {
+    let Some((encoding, text)) = frame.split_first() else {
+        return String::new();
+    };
+    let utf16 = |text: &[u8], big_endian: bool| {
+        let units: Vec<u16> = text
+            .chunks_exact(2)
+            .map(|pair| match big_endian {
+                true => u16::from_be_bytes([pair[0], pair[1]]),
+                false => u16::from_le_bytes([pair[0], pair[1]]),
+            })
+            .collect();
+
+        String::from_utf16_lossy(&units)
+    };
+
+    let text = match encoding {
+        0 => text.iter().map(|byte| *byte as char).collect(),
+        1 => match text {
+            [0xfe, 0xff, text @ ..] => utf16(text, true),
+            [0xff, 0xfe, text @ ..] => utf16(text, false),
+            text => utf16(text, false),
+        },
+        2 => utf16(text, true),
+        _ => String::from_utf8_lossy(text).into_owned(),
+    };
+
+    text.trim_end_matches('\0').to_string()
+}
+
+/// The chapters in the `chpl` box of an MP4 file's metadata. Chapters kept
+/// in a text track instead, as some programs write them, aren't read.
+fn read_mp4(file: &mut File) -> io::Result<Vec<Marker>> {
+    let mut contents = (0, file.seek(SeekFrom::End(0))?);
+    for name in ["moov", "udta", "chpl"] {
+        match find_box(file, contents.0, contents.1, name)? {
+            Some(found) => contents = found,
+            None => return Ok(vec![]),
+        }
+    }
+    let (start, end) = contents;
+
+    let mut chpl = vec![0; (end - start) as usize];
+    file.seek(SeekFrom::Start(start))?;
+    file.read_exact(&mut chpl)?;
+
+    // A version and flags, four more bytes from version 1 on, then the count
+    // of chapters and each chapter's start in 100 ns units and title.
+    let skipped = if chpl.first().copied().unwrap_or(0) > 0 {
+        8
+    } else {
+        4
+    };
+    let Some((count, mut rest)) = chpl.get(skipped..).and_then(<[u8]>::split_first) else {
+        return Ok(vec![]);
+    };
+
+    let mut markers = vec![];
+    for _ in 0..*count {
+        let (Some(start), Some(length)) = (rest.get(..8), rest.get(8)) else {
+            break;
+        };
+        let Some(title) = rest.get(9..9 + *length as usize) else {
+            break;
+        };
+        let start = u64::from_be_bytes(start.try_into().unwrap_or_default());
+
+        markers.push(Marker {
+            time: (start as f64 / 10_000_000.) as f32,
+            name: String::from_utf8_lossy(title).into_owned(),
+        });
+        rest = &rest[9 + *length as usize..];
+    }
+
+    Ok(markers)
+}
+
+/// Where the contents of the first `name` box between `start` and `end`
+/// begin and end.
+fn find_box(file: &mut File, start: u64, end: u64, name: &str) -> io::Result<Option<(u64, u64)>> {
+    let mut position = start;
+
+    while position
+        .checked_add(8)
+        .is_some_and(|header_end| header_end <= end)
+    {
+        let mut header = [0; 8];
+        file.seek(SeekFrom::Start(position))?;
+        file.read_exact(&mut header)?;
+
+        let (size, header_size) = match be_u32(&header[..4]) {
+            // The box runs to the end of its parent.
+            0 => (end - position, 8),
+            // The size follows as 64 bits.
+            1 => {
+                let mut size = [0; 8];
+                file.read_exact(&mut size)?;
+                (u64::from_be_bytes(size), 16)
+            }
+            size => (size as u64, 8),
+        };
+        if size < header_size {
+            break;
+        }
+        let box_end = position.checked_add(size).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "a box runs past the end of the file",
+            )
+        })?;
+        if &header[4..] == name.as_bytes() {
+            return Ok(Some((position + header_size, box_end.min(end))));
+        }
+        position = box_end;
+    }
+
+    Ok(None)
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// A number stored seven bits to a byte, as ID3 sizes are.
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0, |size, byte| (size << 7) | (*byte & 0x7f) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn marker(time: f32, name: &str) -> Marker {
+        Marker {
+            time,
+            name: name.to_string(),
+        }
+    }
+
+    /// A file in the temporary directory holding `contents`, opened for
+    /// reading.
+    fn file(name: &str, contents: &[u8]) -> File {
+        let path = std::env::temp_dir().join(format!("roygbiv-{}-{}", std::process::id(), name));
+        File::create(&path).unwrap().write_all(contents).unwrap();
+
+        File::open(path).unwrap()
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(parse_time("63.5"), Some(63.5));
+        assert_eq!(parse_time("1:03.5"), Some(63.5));
+        assert_eq!(parse_time("1:01:03.5"), Some(3663.5));
+        assert_eq!(parse_time("1:-3"), None);
+        assert_eq!(parse_time("inf"), None);
+        assert_eq!(parse_time("1:NaN"), None);
+        assert_eq!(parse_time(&format!("{}:0", f32::MAX)), None);
+        assert_eq!(parse_time("Intro"), None);
+    }
+
+    #[test]
+    fn lists() {
+        let list =
+            "Time,Name\n0:00,Intro\n1:30;\"Part one\"\n\n2:00\tPart two\n3:15 Outro credits\n";
+
+        assert_eq!(
+            parse_list(list),
+            vec![
+                marker(0., "Intro"),
+                marker(90., "Part one"),
+                marker(120., "Part two"),
+                marker(195., "Outro credits"),
+            ]
+        );
+    }
+
+    #[test]
+    fn current_and_end() {
+        let markers = [marker(10., "A"), marker(20., "B")];
+
+        assert_eq!(current(&markers, 5.), None);
+        assert_eq!(current(&markers, 10.), Some(0));
+        assert_eq!(current(&markers, 25.), Some(1));
+        assert_eq!(end(&markers, 0), Some(20.));
+        assert_eq!(end(&markers, 1), None);
+    }
+
+    #[test]
+    fn id3_sizes() {
+        assert_eq!(syncsafe(&[0, 0, 0x02, 0x01]), 257);
+        assert_eq!(syncsafe(&[0x7f, 0x7f, 0x7f, 0x7f]), 0x0fff_ffff);
+    }
+
+    #[test]
+    fn id3_text_encodings() {
+        assert_eq!(id3_text(b"\0Caf\xe9\0"), "Café");
+        assert_eq!(id3_text(b"\x01\xff\xfeH\0i\0"), "Hi");
+        assert_eq!(id3_text(b"\x02\0H\0i"), "Hi");
+        assert_eq!(id3_text("\x03Café".as_bytes()), "Café");
+        assert_eq!(id3_text(b""), "");
+    }
+
+    #[test]
+    fn id3_chapters() {
+        let frame = |id: &[u8], body: &[u8]| {
+            let mut frame = id.to_vec();
+            frame.extend((body.len() as u32).to_be_bytes());
+            frame.extend([0, 0]);
+            frame.extend(body);
+            frame
+        };
+        let chapter = |element: &[u8], start: u32, title: Option<&[u8]>| {
+            let mut body = element.to_vec();
+            body.push(0);
+            body.extend(start.to_be_bytes());
+            body.extend([0; 12]);
+            if let Some(title) = title {
+                body.extend(frame(b"TIT2", &[&[3], title].concat()));
+            }
+            frame(b"CHAP", &body)
+        };
+
+        let mut frames = frame(b"TALB", b"\x03Album");
+        frames.extend(chapter(b"ch0", 0, Some(b"Welcome")));
+        frames.extend(chapter(b"ch1", 61_500, None));
+        frames.extend([0; 16]);
+
+        let mut tag = b"ID3\x03\0\0".to_vec();
+        tag.extend(
+            (0..4)
+                .rev()
+                .map(|byte| (frames.len() >> (byte * 7)) as u8 & 0x7f),
+        );
+        tag.extend(frames);
+
+        assert_eq!(
+            read_id3(&mut file("chapters.mp3", &tag)).unwrap(),
+            vec![marker(0., "Welcome"), marker(61.5, "Chapter 2")]
+        );
+        assert_eq!(
+            read_id3(&mut file("untagged.mp3", &[0xff; 16])).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn mp4_chapters() {
+        let mp4_box = |name: &[u8], contents: &[u8]| {
+            let mut mp4_box = (contents.len() as u32 + 8).to_be_bytes().to_vec();
+            mp4_box.extend(name);
+            mp4_box.extend(contents);
+            mp4_box
+        };
+
+        let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+        for (start, title) in [(0u64, &b"Intro"[..]), (905_000_000, b"Interview")] {
+            chpl.extend(start.to_be_bytes());
+            chpl.push(title.len() as u8);
+            chpl.extend(title);
+        }
+        let mut mp4 = mp4_box(b"ftyp", b"M4B ");
+        mp4.extend(mp4_box(
+            b"moov",
+            &[
+                mp4_box(b"mvhd", &[0; 4]),
+                mp4_box(b"udta", &mp4_box(b"chpl", &chpl)),
+            ]
+            .concat(),
+        ));
+
+        assert_eq!(
+            read_mp4(&mut file("chapters.m4b", &mp4)).unwrap(),
+            vec![marker(0., "Intro"), marker(90.5, "Interview")]
+        );
+        assert_eq!(
+            read_mp4(&mut file("chapterless.m4a", &mp4_box(b"ftyp", b"M4A "))).unwrap(),
+            vec![]
+        );
+
+        let mut overflowing = mp4_box(b"ftyp", b"M4A ");
+        overflowing.extend([0, 0, 0, 1]);
+        overflowing.extend(b"free");
+        overflowing.extend(u64::MAX.to_be_bytes());
+        assert_eq!(
+            read_mp4(&mut file("overflowing.m4a", &overflowing))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
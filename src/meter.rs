@@ -0,0 +1,103 @@
+use iced::{
+    alignment, mouse,
+    widget::canvas::{self, Path},
+    Color, Pixels, Point, Rectangle, Renderer, Size, Theme,
+};
+
+use crate::{audio::Level, Message};
+
+/// Horizontal RMS and peak meters, one bar per channel, on a decibel scale.
+pub struct LevelMeter {
+    pub levels: Vec<Level>,
+}
+
+impl LevelMeter {
+    /// Quietest level shown, in dBFS.
+    const FLOOR: f32 = -60.;
+    pub const BAR_HEIGHT: f32 = 6.;
+    pub const SPACING: f32 = 3.;
+    /// Room left of the bars for the channel names.
+    const LABEL_WIDTH: f32 = 12.;
+
+    pub fn height(channels: usize) -> f32 {
+        channels as f32 * (Self::BAR_HEIGHT + Self::SPACING) - Self::SPACING
+    }
+
+    /// Position of an amplitude along the meter, between 0 and 1.
+    fn fraction(amplitude: f32) -> f32 {
+        (1. - Level::decibels(amplitude) / Self::FLOOR).clamp(0., 1.)
+    }
+
+    fn label(&self, channel: usize) -> String {
+        match (self.levels.len(), channel) {
+            (1, _) => "M".to_string(),
+            (2, 0) => "L".to_string(),
+            (2, _) => "R".to_string(),
+            (_, channel) => (channel + 1).to_string(),
+        }
+    }
+
+    fn color(decibels: f32) -> Color {
+        if decibels > -3. {
+            Color::from_rgb8(0xf3, 0x8b, 0xa8)
+        } else if decibels > -12. {
+            Color::from_rgb8(0xf9, 0xe2, 0xaf)
+        } else {
+            Color::from_rgb8(0xa6, 0xe3, 0xa1)
+        }
+    }
+}
+
+impl canvas::Program<Message> for LevelMeter {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let palette = theme.extended_palette();
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+
+        let width = bounds.width - Self::LABEL_WIDTH;
+
+        for (channel, level) in self.levels.iter().enumerate() {
+            let y = channel as f32 * (Self::BAR_HEIGHT + Self::SPACING);
+            let bar = |fraction: f32| {
+                Path::rectangle(
+                    Point::new(Self::LABEL_WIDTH, y),
+                    Size::new(width * fraction, Self::BAR_HEIGHT),
+                )
+            };
+
+            frame.fill_text(canvas::Text {
+                content: self.label(channel),
+                position: Point::new(0., y + Self::BAR_HEIGHT / 2.),
+                color: palette.background.base.text,
+                size: Pixels(9.),
+                vertical_alignment: alignment::Vertical::Center,
+                ..Default::default()
+            });
+
+            frame.fill(&bar(1.), palette.background.weak.color);
+            frame.fill(
+                &bar(Self::fraction(level.rms)),
+                Self::color(Level::decibels(level.rms)),
+            );
+
+            let peak = Self::LABEL_WIDTH + width * Self::fraction(level.peak);
+            frame.fill(
+                &Path::rectangle(
+                    Point::new((peak - 2.).max(Self::LABEL_WIDTH), y),
+                    Size::new(2., Self::BAR_HEIGHT),
+                ),
+                Self::color(Level::decibels(level.peak)),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
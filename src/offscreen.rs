@@ -0,0 +1,96 @@
+use iced::{
+    advanced::graphics::{geometry::Renderer as _, Viewport},
+    widget::canvas,
+    Color, Font, Pixels, Rectangle, Renderer, Size,
+};
+
+/// Draws onto a canvas of `size` with `draw` and renders it in software, at
+/// `scale` image pixels per canvas pixel, so that the finished frame can be
+/// read back without a window to show it in.
+pub fn render(size: Size, scale: f32, draw: impl FnOnce(&mut canvas::Frame)) -> image::RgbaImage {
+    let (width, height) = (
+        (size.width * scale).round().max(1.) as u32,
+        (size.height * scale).round().max(1.) as u32,
+    );
+    let mut renderer =
+        Renderer::Secondary(iced_tiny_skia::Renderer::new(Font::default(), Pixels(16.)));
+
+    let mut frame = canvas::Frame::new(&renderer, size);
+    // Images are placed at whole pixels, rounding down, and their positions
+    // come out a hair short of them after being transformed. Nudged, they
+    // land where they were drawn.
+    frame.translate(iced::Vector::new(0.01, 0.01));
+    draw(&mut frame);
+    renderer.draw_geometry(frame.into_geometry());
+    let Renderer::Secondary(mut renderer) = renderer else {
+        unreachable!("the renderer was made in software");
+    };
+
+    let mut pixels = vec![0; width as usize * height as usize * 4];
+    let viewport = Viewport::with_physical_size(Size::new(width, height), scale as f64);
+    if let (Some(mut pixmap), Some(mut clip_mask)) = (
+        tiny_skia::PixmapMut::from_bytes(&mut pixels, width, height),
+        tiny_skia::Mask::new(width, height),
+    ) {
+        renderer.draw(
+            &mut pixmap,
+            &mut clip_mask,
+            &viewport,
+            &[Rectangle::with_size(viewport.logical_size())],
+            Color::BLACK,
+            &[] as &[&str],
+        );
+    }
+
+    // The renderer writes pixels for a window's surface, blue first.
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+
+    image::RgbaImage::from_raw(width, height, pixels).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::Point;
+
+    use super::*;
+
+    #[test]
+    fn renders_at_scale() {
+        let image = render(Size::new(4., 2.), 2., |frame| {
+            frame.fill_rectangle(
+                Point::ORIGIN,
+                Size::new(2., 2.),
+                Color::from_rgb8(255, 0, 0),
+            );
+            frame.fill_rectangle(
+                Point::new(2., 0.),
+                Size::new(2., 2.),
+                Color::from_rgb8(0, 0, 255),
+            );
+        });
+
+        assert_eq!(image.dimensions(), (8, 4));
+        assert_eq!(image.get_pixel(1, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(6, 3).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn renders_images_where_they_are_drawn() {
+        let green = iced::widget::image::Handle::from_rgba(2, 2, [0, 255, 0, 255].repeat(4));
+        let image = render(Size::new(8., 4.), 1., |frame| {
+            frame.draw_image(
+                Rectangle::new(Point::new(2., 1.), Size::new(2., 2.)),
+                canvas::Image::new(green),
+            );
+        });
+        let green_pixels: Vec<_> = image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0 == [0, 255, 0, 255])
+            .map(|(x, y, _)| (x, y))
+            .collect();
+
+        assert_eq!(green_pixels, [(2, 1), (3, 1), (2, 2), (3, 2)]);
+    }
+}
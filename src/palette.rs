@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use iced::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::{self, ColorDef};
+
+/// A named color kept with the project. Layers can follow it, so changing it
+/// re-colors all of them at once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Swatch {
+    #[serde(default)]
+    pub name: String,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+}
+
+/// The color as `#rrggbb`.
+pub fn to_hex(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Colors found in an image at most, for a palette that is still easy to
+/// pick from.
+pub const PALETTE_SIZE: usize = 6;
+
+/// Decodes an image file and finds its dominant colors, off the main thread.
+/// The downscaled proxy of a large image is used in its place when there is
+/// one.
+pub async fn extract(
+    contents: Arc<Vec<u8>>,
+    proxy: Option<Arc<image::RgbaImage>>,
+) -> Option<Vec<Color>> {
+    tokio::task::spawn_blocking(move || {
+        // The colors are only counted, so a small copy of the image does.
+        let image = match proxy {
+            Some(proxy) => image::imageops::thumbnail(proxy.as_ref(), 96, 96),
+            None => canvas::decode_image_file(&contents)?
+                .thumbnail(96, 96)
+                .into_rgba8(),
+        };
+
+        Some(dominant_colors(&image, PALETTE_SIZE))
+    })
+    .await
+    .ok()?
+}
+
+/// Up to `count` colors making up most of the image, the most common first.
+/// Colors are bucketed, the most common buckets that differ enough from each
+/// other picked, and those refined by k-means.
+pub fn dominant_colors(image: &image::RgbaImage, count: usize) -> Vec<Color> {
+    /// Bits kept of each channel when bucketing.
+    const BITS: u32 = 4;
+    /// How far apart, in RGB, colors are picked to start from, so a palette
+    /// isn't made of shades of one color.
+    const MIN_DISTANCE: f32 = 0.2;
+
+    let mut buckets = vec![([0.; 3], 0.); 1 << (BITS * 3)];
+    for pixel in image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        // Mostly transparent pixels aren't seen, e.g. around a logo.
+        if a < 128 {
+            continue;
+        }
+        let index = [r, g, b].iter().fold(0, |index, channel| {
+            (index << BITS) | (*channel as usize >> (8 - BITS))
+        });
+        let (sum, weight) = &mut buckets[index];
+        for (sum, channel) in sum.iter_mut().zip([r, g, b]) {
+            *sum += channel as f32 / 255.;
+        }
+        *weight += 1.;
+    }
+
+    // Each bucket is a sample at the mean of its colors, weighted by them.
+    let mut samples: Vec<([f32; 3], f32)> = buckets
+        .into_iter()
+        .filter(|(_, weight)| *weight > 0.)
+        .map(|(sum, weight)| (sum.map(|channel| channel / weight), weight))
+        .collect();
+    samples.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut centers: Vec<[f32; 3]> = vec![];
+    for (color, _) in &samples {
+        if centers.len() == count {
+            break;
+        }
+        if centers
+            .iter()
+            .all(|center| distance(*center, *color) >= MIN_DISTANCE)
+        {
+            centers.push(*color);
+        }
+    }
+
+    let mut weights = vec![0.; centers.len()];
+    for _ in 0..8 {
+        let mut sums = vec![[0.; 3]; centers.len()];
+        weights.fill(0.);
+
+        for (color, weight) in &samples {
+            let Some(nearest) = (0..centers.len()).min_by(|a, b| {
+                distance(centers[*a], *color).total_cmp(&distance(centers[*b], *color))
+            }) else {
+                break;
+            };
+            for (sum, channel) in sums[nearest].iter_mut().zip(color) {
+                *sum += channel * weight;
+            }
+            weights[nearest] += weight;
+        }
+
+        for ((center, sum), weight) in centers.iter_mut().zip(&sums).zip(&weights) {
+            if *weight > 0. {
+                *center = sum.map(|channel| channel / weight);
+            }
+        }
+    }
+
+    let mut palette: Vec<_> = centers.into_iter().zip(weights).collect();
+    palette.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    palette
+        .into_iter()
+        .filter(|(_, weight)| *weight > 0.)
+        .map(|([r, g, b], _)| Color::from_rgb(r, g, b))
+        .collect()
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
@@ -0,0 +1,880 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, SampleFormat, SizedSample,
+};
+use symphonia::core::{
+    audio::SampleBuffer,
+    errors::Error as DecodeError,
+    formats::{SeekMode, SeekTo},
+};
+
+use crate::{audio::Track, i18n::t};
+
+/// Where the preview audio is played.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OutputDevice {
+    /// Whichever device the system plays through, even as it changes.
+    #[default]
+    Default,
+    Named(String),
+}
+
+impl Display for OutputDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputDevice::Default => f.write_str(t("output-default")),
+            OutputDevice::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+/// The devices audio can be played through, starting with the system's
+/// default.
+pub async fn devices() -> Vec<OutputDevice> {
+    tokio::task::spawn_blocking(|| {
+        let named = cpal::default_host()
+            .output_devices()
+            .map(|devices| {
+                devices
+                    .filter_map(|device| device.name().ok())
+                    .map(OutputDevice::Named)
+                    .collect()
+            })
+            .unwrap_or_else(|error| {
+                tracing::warn!("could not list audio output devices: {}", error);
+                vec![]
+            });
+
+        [vec![OutputDevice::Default], named].concat()
+    })
+    .await
+    .unwrap_or_else(|_| vec![OutputDevice::Default])
+}
+
+/// An audio file placed on the timeline, as the output plays it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Voice {
+    pub path: PathBuf,
+    /// Seconds into the file at which the timeline starts: where the main
+    /// track is trimmed to, or less than zero for a track that starts later.
+    pub start: f32,
+    /// The factor its samples are scaled by.
+    pub amplitude: f32,
+}
+
+/// How fast the output goes through the timeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Speed {
+    factor: f32,
+    /// Whether the voices are stretched in time rather than sped up, which
+    /// would also raise or lower them.
+    preserves_pitch: bool,
+}
+
+impl Speed {
+    /// How much faster the voices are resampled.
+    fn resampling(self) -> f64 {
+        if self.preserves_pitch {
+            1.
+        } else {
+            self.factor as f64
+        }
+    }
+}
+
+enum Command {
+    /// Mixes the voices together from a position on the timeline, looping at
+    /// its length like the transport does.
+    Play(Vec<Voice>, Duration, Option<Duration>, Speed),
+    /// Changes the amplitude of each voice being played, in order.
+    Amplitudes(Vec<f32>),
+    Pause,
+    Device(OutputDevice),
+}
+
+/// Plays the audio tracks through an output device, on a thread of its own
+/// that decodes and mixes them as it goes.
+///
+/// The output only follows what it is told: the transport stays the clock
+/// that frames are drawn by, and the output is started again from the
+/// transport's position whenever it jumps.
+#[derive(Debug)]
+pub struct Output {
+    commands: mpsc::Sender<Command>,
+    /// The factor samples are scaled by, as the bits of an `f32`, read by
+    /// the device as it plays.
+    gain: Arc<AtomicU32>,
+    device: OutputDevice,
+    volume: f32,
+    is_muted: bool,
+    is_pitch_preserved: bool,
+}
+
+impl Output {
+    pub fn new() -> Output {
+        let (commands, received) = mpsc::channel();
+        let gain = Arc::new(AtomicU32::new(1f32.to_bits()));
+
+        let shared_gain = gain.clone();
+        let spawned = thread::Builder::new()
+            .name("audio output".to_string())
+            .spawn(move || run(received, shared_gain));
+        if let Err(error) = spawned {
+            tracing::warn!("could not start the audio output: {}", error);
+        }
+
+        Output {
+            commands,
+            gain,
+            device: OutputDevice::Default,
+            volume: 1.,
+            is_muted: false,
+            is_pitch_preserved: true,
+        }
+    }
+
+    /// Plays `voices` from `position` at `rate` times their speed, going
+    /// back to the start of the timeline after `length`.
+    pub fn play(
+        &self,
+        voices: Vec<Voice>,
+        position: Duration,
+        length: Option<Duration>,
+        rate: f32,
+    ) {
+        let speed = Speed {
+            factor: rate,
+            preserves_pitch: self.is_pitch_preserved,
+        };
+        let _ = self
+            .commands
+            .send(Command::Play(voices, position, length, speed));
+    }
+
+    /// Changes how loud each of the voices being played is, without starting
+    /// them again.
+    pub fn set_amplitudes(&self, amplitudes: Vec<f32>) {
+        let _ = self.commands.send(Command::Amplitudes(amplitudes));
+    }
+
+    pub fn pause(&self) {
+        let _ = self.commands.send(Command::Pause);
+    }
+
+    pub fn device(&self) -> &OutputDevice {
+        &self.device
+    }
+
+    /// Switches to `device`, pausing until told to play again.
+    pub fn set_device(&mut self, device: OutputDevice) {
+        self.device = device.clone();
+        let _ = self.commands.send(Command::Device(device));
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0., 1.);
+        self.update_gain();
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    pub fn set_muted(&mut self, is_muted: bool) {
+        self.is_muted = is_muted;
+        self.update_gain();
+    }
+
+    pub fn is_pitch_preserved(&self) -> bool {
+        self.is_pitch_preserved
+    }
+
+    /// Takes effect from the next time the output is told to play.
+    pub fn set_pitch_preserved(&mut self, is_pitch_preserved: bool) {
+        self.is_pitch_preserved = is_pitch_preserved;
+    }
+
+    fn update_gain(&self) {
+        let gain = if self.is_muted {
+            0.
+        } else {
+            // Loudness is heard logarithmically, so the slider follows a
+            // curve rather than scaling the samples directly.
+            self.volume * self.volume
+        };
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Samples decoded ahead of the device, interleaved in its channel layout.
+type Queue = Arc<Mutex<VecDeque<f32>>>;
+
+/// An open stream to a device, pulling samples from the queue.
+struct Stream {
+    /// Kept alive for as long as it should play.
+    _stream: cpal::Stream,
+    sample_rate: u32,
+    channels: usize,
+}
+
+/// Decodes into the queue while playing, and waits for the next command
+/// while paused.
+fn run(commands: mpsc::Receiver<Command>, gain: Arc<AtomicU32>) {
+    let queue = Queue::default();
+    let mut device = OutputDevice::Default;
+    let mut stream: Option<Stream> = None;
+    let mut mixdown: Option<Mixdown> = None;
+
+    loop {
+        let command = match &mixdown {
+            Some(_) => match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(mpsc::TryRecvError::Empty) => None,
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            },
+            None => match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => return,
+            },
+        };
+
+        match command {
+            Some(Command::Play(voices, position, length, speed)) => {
+                queue.lock().unwrap().clear();
+                if stream.is_none() {
+                    stream = open_stream(&device, queue.clone(), gain.clone())
+                        .map_err(|error| {
+                            tracing::warn!("could not open the audio output: {}", error)
+                        })
+                        .ok();
+                }
+                mixdown = stream.as_ref().map(|stream| {
+                    Mixdown::open(
+                        &voices,
+                        position,
+                        length,
+                        speed,
+                        stream.sample_rate,
+                        stream.channels,
+                    )
+                });
+            }
+            Some(Command::Amplitudes(amplitudes)) => {
+                if let Some(mixdown) = &mut mixdown {
+                    mixdown.set_amplitudes(&amplitudes);
+                }
+            }
+            Some(Command::Pause) => {
+                mixdown = None;
+                queue.lock().unwrap().clear();
+            }
+            Some(Command::Device(picked)) => {
+                mixdown = None;
+                stream = None;
+                queue.lock().unwrap().clear();
+                device = picked;
+            }
+            None => {}
+        }
+
+        if let (Some(playing), Some(stream)) = (&mut mixdown, &stream) {
+            // A fifth of a second ahead is enough not to run dry, and little
+            // enough that pausing or seeking is heard right away.
+            let ahead = stream.sample_rate as usize * stream.channels / 5;
+            if queue.lock().unwrap().len() < ahead {
+                let mixed = playing.mix();
+                queue.lock().unwrap().extend(mixed);
+            } else {
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+}
+
+fn open_stream(
+    device: &OutputDevice,
+    queue: Queue,
+    gain: Arc<AtomicU32>,
+) -> Result<Stream, String> {
+    let host = cpal::default_host();
+    let found = match device {
+        OutputDevice::Default => None,
+        OutputDevice::Named(name) => host
+            .output_devices()
+            .map_err(|error| error.to_string())?
+            .find(|device| device.name().is_ok_and(|found| &found == name)),
+    };
+    // A device that has been unplugged falls back to the default one.
+    let device = found
+        .or_else(|| host.default_output_device())
+        .ok_or("there is no audio output device")?;
+
+    let config = device
+        .default_output_config()
+        .map_err(|error| error.to_string())?;
+    let (sample_rate, channels) = (config.sample_rate().0, config.channels() as usize);
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), queue, gain),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), queue, gain),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), queue, gain),
+        format => return Err(format!("samples in {} aren't supported", format)),
+    }?;
+    stream.play().map_err(|error| error.to_string())?;
+
+    Ok(Stream {
+        _stream: stream,
+        sample_rate,
+        channels,
+    })
+}
+
+fn build_stream<T: SizedSample + FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    queue: Queue,
+    gain: Arc<AtomicU32>,
+) -> Result<cpal::Stream, String> {
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                let gain = f32::from_bits(gain.load(Ordering::Relaxed));
+                let mut queue = queue.lock().unwrap();
+                for sample in data {
+                    // Running dry plays silence rather than stopping.
+                    *sample = T::from_sample(queue.pop_front().unwrap_or(0.) * gain);
+                }
+            },
+            |error| tracing::error!("audio output failed: {}", error),
+            None,
+        )
+        .map_err(|error| error.to_string())
+}
+
+/// Frames of the device mixed at a time.
+const CHUNK: u64 = 1024;
+
+/// The voices being played, mixed together and looped over the timeline.
+struct Mixdown {
+    /// Each source with the index of the voice it plays, leaving out the
+    /// ones that couldn't be opened.
+    sources: Vec<(usize, Source)>,
+    /// Frames mixed over the whole timeline, if it loops.
+    length: Option<u64>,
+    /// Frames left to mix until the timeline loops.
+    remaining: u64,
+    channels: usize,
+    /// Changes the speed of the mix, if its pitch is preserved.
+    stretcher: Option<Stretcher>,
+}
+
+impl Mixdown {
+    fn open(
+        voices: &[Voice],
+        position: Duration,
+        length: Option<Duration>,
+        speed: Speed,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Mixdown {
+        let frames = |duration: Duration| {
+            (duration.as_secs_f64() * sample_rate as f64 / speed.resampling()) as u64
+        };
+        let sources = voices
+            .iter()
+            .enumerate()
+            .filter_map(|(index, voice)| {
+                Source::open(voice, position, speed, sample_rate, channels)
+                    .map_err(|error| {
+                        tracing::warn!("could not play {}: {}", voice.path.display(), error)
+                    })
+                    .ok()
+                    .map(|source| (index, source))
+            })
+            .collect();
+        let length = length.map(frames).filter(|length| *length > 0);
+
+        Mixdown {
+            sources,
+            length,
+            remaining: length.map_or(u64::MAX, |length| length.saturating_sub(frames(position))),
+            channels,
+            stretcher: (speed.preserves_pitch && speed.factor != 1.)
+                .then(|| Stretcher::new(speed.factor as f64, sample_rate, channels)),
+        }
+    }
+
+    fn set_amplitudes(&mut self, amplitudes: &[f32]) {
+        for (index, source) in &mut self.sources {
+            if let Some(amplitude) = amplitudes.get(*index) {
+                source.amplitude = *amplitude;
+            }
+        }
+    }
+
+    /// Mixes the next samples of every source, going back to the start of
+    /// the timeline at its end. There may be none while the stretcher fills
+    /// up.
+    fn mix(&mut self) -> Vec<f32> {
+        if self.remaining == 0 {
+            for (_, source) in &mut self.sources {
+                if let Err(error) = source.seek(Duration::ZERO) {
+                    tracing::warn!("could not loop audio for playback: {}", error);
+                    source.is_ended = true;
+                }
+            }
+            self.remaining = self.length.unwrap_or(u64::MAX);
+        }
+
+        let frames = CHUNK.min(self.remaining);
+        self.remaining -= frames;
+
+        let mut mixed = vec![0.; frames as usize * self.channels];
+        for (_, source) in &mut self.sources {
+            if let Err(error) = source.add_to(&mut mixed) {
+                // The other tracks keep playing.
+                tracing::warn!("could not decode audio for playback: {}", error);
+                source.is_ended = true;
+            }
+        }
+
+        match &mut self.stretcher {
+            Some(stretcher) => stretcher.stretch(&mixed),
+            None => mixed,
+        }
+    }
+}
+
+/// A track being decoded and converted to the device's rate and channels.
+struct Source {
+    track: Track,
+    buffer: Option<SampleBuffer<f32>>,
+    resampler: Resampler,
+    /// Seconds into the file at which the timeline starts.
+    start: f64,
+    /// How much faster than the timeline the file is resampled.
+    speed: f64,
+    amplitude: f32,
+    /// Samples of silence to play before the file starts.
+    lead: usize,
+    /// Samples decoded but not mixed yet.
+    pending: VecDeque<f32>,
+    /// Frames decoded before the position that was sought to.
+    skip: u64,
+    /// Whether the whole file has been decoded, so that only silence is left
+    /// until the timeline loops.
+    is_ended: bool,
+    sample_rate: u32,
+    channels: usize,
+}
+
+impl Source {
+    fn open(
+        voice: &Voice,
+        position: Duration,
+        speed: Speed,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Result<Source, String> {
+        let track = Track::open(&voice.path).map_err(|error| format!("{:?}", error))?;
+        let mut resampler = Resampler::new(track.sample_rate, sample_rate, channels);
+        // Going through the track faster raises its pitch, like a tape.
+        resampler.step *= speed.resampling();
+        let mut source = Source {
+            resampler,
+            track,
+            buffer: None,
+            start: voice.start as f64,
+            speed: speed.resampling(),
+            amplitude: voice.amplitude,
+            lead: 0,
+            pending: VecDeque::new(),
+            skip: 0,
+            is_ended: false,
+            sample_rate,
+            channels,
+        };
+        source.seek(position)?;
+
+        Ok(source)
+    }
+
+    /// Goes to `position` on the timeline, which may be before the file
+    /// starts.
+    fn seek(&mut self, position: Duration) -> Result<(), String> {
+        let time = position.as_secs_f64() + self.start;
+        self.lead = (-time * self.sample_rate as f64 / self.speed).max(0.) as usize * self.channels;
+        self.pending.clear();
+        self.is_ended = false;
+
+        let seeked = self
+            .track
+            .format
+            .seek(
+                SeekMode::Accurate,
+                SeekTo::Time {
+                    time: time.max(0.).into(),
+                    track_id: Some(self.track.id),
+                },
+            )
+            .map_err(|error| error.to_string())?;
+        self.track.decoder.reset();
+        self.skip = seeked.required_ts.saturating_sub(seeked.actual_ts);
+
+        Ok(())
+    }
+
+    /// Adds the next samples of the track to `mixed`, silent before the file
+    /// starts and after it ends.
+    fn add_to(&mut self, mixed: &mut [f32]) -> Result<(), String> {
+        let lead = self.lead.min(mixed.len());
+        self.lead -= lead;
+
+        let mut added = lead;
+        while added < mixed.len() {
+            if self.pending.is_empty() {
+                if self.is_ended {
+                    break;
+                }
+                self.decode()?;
+                continue;
+            }
+
+            let count = self.pending.len().min(mixed.len() - added);
+            for (mixed, sample) in mixed[added..added + count]
+                .iter_mut()
+                .zip(self.pending.drain(..count))
+            {
+                *mixed += sample * self.amplitude;
+            }
+            added += count;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the next packet into the pending samples.
+    fn decode(&mut self) -> Result<(), String> {
+        let packet = match self.track.format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(error)) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                self.is_ended = true;
+                return Ok(());
+            }
+            Err(error) => return Err(error.to_string()),
+        };
+        if packet.track_id() != self.track.id {
+            return Ok(());
+        }
+
+        let decoded = match self.track.decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(DecodeError::DecodeError(_)) => return Ok(()),
+            Err(error) => return Err(error.to_string()),
+        };
+        let spec = *decoded.spec();
+        let channels = spec.channels.count();
+        let buffer = match &mut self.buffer {
+            Some(buffer) if buffer.capacity() >= decoded.capacity() * channels => buffer,
+            buffer => buffer.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+        buffer.copy_interleaved_ref(decoded);
+
+        let mut converted = Vec::with_capacity(buffer.samples().len() * 2);
+        for frame in buffer.samples().chunks_exact(channels) {
+            if self.skip > 0 {
+                self.skip -= 1;
+                continue;
+            }
+            self.resampler.push(frame, &mut converted);
+        }
+
+        self.pending.extend(converted);
+
+        Ok(())
+    }
+}
+
+/// Converts frames of a track to the rate and channels of a device,
+/// interpolating between them.
+struct Resampler {
+    /// How far the device moves through the track for every frame it plays.
+    step: f64,
+    /// How far the next frame to play is past `previous`, in track frames.
+    phase: f64,
+    /// The last frame of the track, which the next one is interpolated from.
+    previous: Vec<f32>,
+    channels: usize,
+}
+
+impl Resampler {
+    fn new(from_rate: u32, to_rate: u32, channels: usize) -> Resampler {
+        Resampler {
+            step: from_rate as f64 / to_rate as f64,
+            phase: 0.,
+            previous: vec![],
+            channels,
+        }
+    }
+
+    /// Adds every frame of the device that falls between the last frame of
+    /// the track and `frame` to `output`.
+    fn push(&mut self, frame: &[f32], output: &mut Vec<f32>) {
+        let channels = frame.len();
+        if self.previous.len() != channels {
+            // There is nothing to interpolate from yet.
+            self.previous = frame.to_vec();
+            return;
+        }
+
+        while self.phase < 1. {
+            let phase = self.phase as f32;
+            let interpolated = |channel: usize| {
+                self.previous[channel] + (frame[channel] - self.previous[channel]) * phase
+            };
+            for channel in 0..self.channels {
+                output.push(match (channels, self.channels) {
+                    (1, _) => interpolated(0),
+                    (_, 1) => (0..channels).map(interpolated).sum::<f32>() / channels as f32,
+                    _ if channel < channels => interpolated(channel),
+                    _ => 0.,
+                });
+            }
+            self.phase += self.step;
+        }
+        self.phase -= 1.;
+        self.previous.copy_from_slice(frame);
+    }
+}
+
+/// Changes how fast samples play without changing their pitch, by adding up
+/// windowed grains of them read at a different pace than they are written.
+/// The grains blur the sound a little, which is fine for a preview.
+struct Stretcher {
+    /// How much faster the samples are read than written.
+    rate: f64,
+    channels: usize,
+    /// Hann window over a grain, in frames.
+    window: Vec<f32>,
+    /// Samples waiting to be read into grains.
+    input: VecDeque<f32>,
+    /// How far into `input` the next grain starts, in frames.
+    read: f64,
+    /// The grains added up so far, finished for the first half window.
+    overlap: Vec<f32>,
+}
+
+impl Stretcher {
+    fn new(rate: f64, sample_rate: u32, channels: usize) -> Stretcher {
+        // Grains of 40ms are long enough to hold low notes, and short enough
+        // not to smear beats.
+        let size = (sample_rate as usize / 25).max(2) & !1;
+        let window = (0..size)
+            .map(|frame| 0.5 - 0.5 * (std::f32::consts::TAU * frame as f32 / size as f32).cos())
+            .collect();
+
+        Stretcher {
+            rate,
+            channels,
+            window,
+            input: VecDeque::new(),
+            read: 0.,
+            overlap: vec![0.; size * channels],
+        }
+    }
+
+    fn stretch(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.input.extend(samples);
+
+        let size = self.window.len();
+        // Windows half a window apart add up to one.
+        let hop = size / 2;
+        let mut output = vec![];
+        loop {
+            let start = self.read as usize * self.channels;
+            if start + size * self.channels > self.input.len() {
+                break;
+            }
+
+            let grain = self.input.range(start..start + size * self.channels);
+            for (index, (sum, sample)) in self.overlap.iter_mut().zip(grain).enumerate() {
+                *sum += sample * self.window[index / self.channels];
+            }
+            output.extend(self.overlap.drain(..hop * self.channels));
+            self.overlap.resize(size * self.channels, 0.);
+
+            self.read += hop as f64 * self.rate;
+            let read = self.read as usize;
+            self.input.drain(..read * self.channels);
+            self.read -= read as f64;
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resample(resampler: &mut Resampler, frames: &[&[f32]]) -> Vec<f32> {
+        let mut output = vec![];
+        for frame in frames {
+            resampler.push(frame, &mut output);
+        }
+
+        output
+    }
+
+    /// A mono WAV file in the temporary directory, `frames` long at `rate`,
+    /// with every sample at `level`.
+    fn wav(name: &str, rate: u32, frames: u32, level: i16) -> PathBuf {
+        let mut contents = vec![];
+        for chunk in [
+            &b"RIFF"[..],
+            &(36 + frames * 2).to_le_bytes(),
+            b"WAVEfmt ",
+            &16u32.to_le_bytes(),
+            &1u16.to_le_bytes(),
+            &1u16.to_le_bytes(),
+            &rate.to_le_bytes(),
+            &(rate * 2).to_le_bytes(),
+            &2u16.to_le_bytes(),
+            &16u16.to_le_bytes(),
+            b"data",
+            &(frames * 2).to_le_bytes(),
+        ] {
+            contents.extend_from_slice(chunk);
+        }
+        for _ in 0..frames {
+            contents.extend_from_slice(&level.to_le_bytes());
+        }
+
+        let path = std::env::temp_dir().join(format!("roygbiv-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn mixes_tracks_over_the_timeline() {
+        let path = wav("mix.wav", 1000, 200, i16::MAX / 2 + 1);
+        let voices = [
+            Voice {
+                path: path.clone(),
+                start: 0.,
+                amplitude: 1.,
+            },
+            // Starts halfway through the timeline, twice as loud.
+            Voice {
+                path,
+                start: -0.05,
+                amplitude: 2.,
+            },
+        ];
+        let open = |factor, preserves_pitch| {
+            let speed = Speed {
+                factor,
+                preserves_pitch,
+            };
+            Mixdown::open(
+                &voices,
+                Duration::ZERO,
+                Some(Duration::from_millis(100)),
+                speed,
+                1000,
+                1,
+            )
+        };
+        let mut mixdown = open(1., true);
+        let expected = [[0.5; 50], [1.5; 50]].concat();
+
+        assert_eq!(mixdown.mix(), expected);
+        // Back at the start of the timeline.
+        assert_eq!(mixdown.mix(), expected);
+
+        mixdown.set_amplitudes(&[0., 1.]);
+        assert_eq!(mixdown.mix(), [[0.; 50], [0.5; 50]].concat());
+
+        // Twice as fast, the second one starts twice as soon and the timeline
+        // loops twice as often.
+        let mut faster = open(2., false);
+        let expected = [[0.5; 25], [1.5; 25]].concat();
+        assert_eq!(faster.mix(), expected);
+        assert_eq!(faster.mix(), expected);
+    }
+
+    #[test]
+    fn stretches_to_the_rate() {
+        let constant = vec![1.; 4000];
+
+        let faster = Stretcher::new(2., 1000, 1).stretch(&constant);
+        assert_eq!(faster.len(), 2000);
+        // Past the first half window, where only one grain has been added.
+        assert!(faster[20..].iter().all(|sample| (sample - 1.).abs() < 1e-5));
+
+        let slower = Stretcher::new(0.5, 1000, 2).stretch(&constant);
+        assert_eq!(slower.len(), 197 * 20 * 2);
+        assert!(slower[40..].iter().all(|sample| (sample - 1.).abs() < 1e-5));
+    }
+
+    #[test]
+    fn interpolates_up_to_a_faster_rate() {
+        let mut resampler = Resampler::new(22_050, 44_100, 1);
+
+        assert_eq!(
+            resample(&mut resampler, &[&[0.], &[1.], &[0.]]),
+            [0., 0.5, 1., 0.5]
+        );
+    }
+
+    #[test]
+    fn skips_frames_down_to_a_slower_rate() {
+        let mut resampler = Resampler::new(48_000, 24_000, 1);
+
+        assert_eq!(
+            resample(&mut resampler, &[&[0.], &[0.25], &[0.5], &[0.75]]),
+            [0., 0.5]
+        );
+    }
+
+    #[test]
+    fn maps_channels() {
+        let mut mono_to_stereo = Resampler::new(44_100, 44_100, 2);
+        let mut stereo_to_mono = Resampler::new(44_100, 44_100, 1);
+        let mut stereo_to_surround = Resampler::new(44_100, 44_100, 4);
+
+        assert_eq!(resample(&mut mono_to_stereo, &[&[0.5], &[0.5]]), [0.5, 0.5]);
+        assert_eq!(
+            resample(&mut stereo_to_mono, &[&[1., 0.], &[1., 0.]]),
+            [0.5]
+        );
+        assert_eq!(
+            resample(&mut stereo_to_surround, &[&[1., -1.], &[1., -1.]]),
+            [1., -1., 0., 0.]
+        );
+    }
+}
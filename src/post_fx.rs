@@ -0,0 +1,253 @@
+use std::fmt::Display;
+
+use iced::{Point, Rectangle, Size};
+use serde::{Deserialize, Serialize};
+
+use crate::{canvas::Kaleidoscope, i18n::t, lut::Lut};
+
+/// Effects applied to the whole frame once every layer has been drawn.
+///
+/// Letterbox bars are drawn over the canvas wherever it is shown. The
+/// kaleidoscope, bloom, chromatic aberration and sharpening need the
+/// finished frame's pixels, which the canvas can't read back, so while any
+/// of them or a color grade is on the layers are rendered in software and
+/// shown as an image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostFx {
+    /// Mirrors slices of the whole frame, every layer together.
+    pub kaleidoscope: Kaleidoscope,
+    /// How strongly the brightest parts of the frame glow, from 0 to 1.
+    pub bloom: f32,
+    /// Brightness from which pixels start to glow, from 0 to 1.
+    pub bloom_threshold: f32,
+    /// How far the glow spreads, in canvas pixels.
+    pub bloom_radius: f32,
+    /// How far the red and blue channels are pulled apart at the corners of
+    /// the frame, in canvas pixels. The center is left sharp, as with a
+    /// real lens.
+    pub aberration: f32,
+    /// How much edges are sharpened, from 0 to 1.
+    pub sharpen: f32,
+    /// A color grade over the whole frame.
+    pub lut: Option<Lut>,
+    pub letterbox: Letterbox,
+}
+
+impl PostFx {
+    pub const NONE: PostFx = PostFx {
+        kaleidoscope: Kaleidoscope::NONE,
+        bloom: 0.,
+        bloom_threshold: 0.7,
+        bloom_radius: 24.,
+        aberration: 0.,
+        sharpen: 0.,
+        lut: None,
+        letterbox: Letterbox::None,
+    };
+
+    /// Whether any effect needs the pixels of the finished frame.
+    pub fn is_filtered(&self) -> bool {
+        self.kaleidoscope.is_visible()
+            || self.bloom > 0.
+            || self.aberration > 0.
+            || self.sharpen > 0.
+            || self.lut.as_ref().is_some_and(Lut::is_visible)
+    }
+
+    /// Applies the effects to `image`, a frame of a canvas of size `canvas`
+    /// at any resolution.
+    pub fn apply(&self, image: &mut image::RgbaImage, canvas: Size) {
+        let scale = image.width() as f32 / canvas.width.max(1.);
+
+        // Mirrored first, so the other effects see the frame as it's shown.
+        if self.kaleidoscope.is_visible() {
+            self.kaleidoscope.apply(image);
+            // Slices reaching past the edges come out clear; the frame
+            // behind the layers is black.
+            for pixel in image.pixels_mut() {
+                pixel[3] = 255;
+            }
+        }
+        if self.bloom > 0. {
+            bloom(
+                image,
+                self.bloom,
+                self.bloom_threshold,
+                self.bloom_radius * scale,
+            );
+        }
+        if self.aberration > 0. {
+            aberrate(image, self.aberration * scale);
+        }
+        if self.sharpen > 0. {
+            sharpen(image, self.sharpen, scale);
+        }
+        // Graded after the other effects, so that the glow and the fringes
+        // match the rest of the frame.
+        if let Some(lut) = self.lut.as_ref().filter(|lut| lut.is_visible()) {
+            lut.apply(image);
+        }
+        // The bars go on last, so nothing glows or bleeds into them.
+        for bar in self.letterbox.bars(canvas) {
+            let to_pixels = |value: f32, limit: u32| ((value * scale).round() as u32).min(limit);
+            let (left, right) = (
+                to_pixels(bar.x, image.width()),
+                to_pixels(bar.x + bar.width, image.width()),
+            );
+            let (top, bottom) = (
+                to_pixels(bar.y, image.height()),
+                to_pixels(bar.y + bar.height, image.height()),
+            );
+            for y in top..bottom {
+                for x in left..right {
+                    image.put_pixel(x, y, image::Rgba([0, 0, 0, 255]));
+                }
+            }
+        }
+    }
+}
+
+impl Default for PostFx {
+    fn default() -> Self {
+        PostFx::NONE
+    }
+}
+
+/// Black bars narrowing the frame to a film's aspect ratio, above and below
+/// it, or at its sides when the canvas is wider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Letterbox {
+    #[default]
+    None,
+    /// 4:3, as old television and films before widescreen.
+    Academy,
+    /// 1.85:1, most films shown in cinemas.
+    Widescreen,
+    /// 2.39:1, anamorphic films.
+    Scope,
+}
+
+impl Letterbox {
+    pub const ALL: [Letterbox; 4] = [
+        Letterbox::None,
+        Letterbox::Academy,
+        Letterbox::Widescreen,
+        Letterbox::Scope,
+    ];
+
+    /// Width of the picture left between the bars, divided by its height.
+    fn ratio(self) -> Option<f32> {
+        match self {
+            Letterbox::None => None,
+            Letterbox::Academy => Some(4. / 3.),
+            Letterbox::Widescreen => Some(1.85),
+            Letterbox::Scope => Some(2.39),
+        }
+    }
+
+    /// The bars over a canvas of size `canvas`, in canvas pixels. None if the
+    /// canvas already has the aspect ratio.
+    pub fn bars(self, canvas: Size) -> Vec<Rectangle> {
+        let Some(ratio) = self.ratio() else {
+            return vec![];
+        };
+
+        if canvas.width / canvas.height > ratio {
+            let width = (canvas.width - canvas.height * ratio) / 2.;
+            let bar = Size::new(width, canvas.height);
+
+            vec![
+                Rectangle::new(Point::ORIGIN, bar),
+                Rectangle::new(Point::new(canvas.width - width, 0.), bar),
+            ]
+        } else {
+            let height = (canvas.height - canvas.width / ratio) / 2.;
+            let bar = Size::new(canvas.width, height);
+
+            vec![
+                Rectangle::new(Point::ORIGIN, bar),
+                Rectangle::new(Point::new(0., canvas.height - height), bar),
+            ]
+        }
+        .into_iter()
+        .filter(|bar| bar.width >= 0.5 && bar.height >= 0.5)
+        .collect()
+    }
+}
+
+impl Display for Letterbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Letterbox::None => t("letterbox-none"),
+            Letterbox::Academy => "4:3",
+            Letterbox::Widescreen => "1.85:1",
+            Letterbox::Scope => "2.39:1",
+        })
+    }
+}
+
+/// Blurs the parts of `image` brighter than `threshold` by `radius` pixels
+/// and screens them back over it.
+fn bloom(image: &mut image::RgbaImage, amount: f32, threshold: f32, radius: f32) {
+    let mut bright = image.clone();
+    for pixel in bright.pixels_mut() {
+        let [r, g, b, _] = pixel.0.map(|channel| channel as f32 / 255.);
+        let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let glow = ((luminance - threshold) / (1. - threshold).max(0.01)).clamp(0., 1.);
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as f32 * glow) as u8;
+        }
+    }
+    let glow = image::imageops::fast_blur(&bright, (radius / 2.).max(0.5));
+
+    for (pixel, glow) in image.pixels_mut().zip(glow.pixels()) {
+        for channel in 0..3 {
+            let base = pixel[channel] as f32 / 255.;
+            let glow = glow[channel] as f32 / 255. * amount.min(1.);
+            let screened = 1. - (1. - base) * (1. - glow);
+
+            pixel[channel] = (screened * 255.).round() as u8;
+        }
+    }
+}
+
+/// Pushes red out from the center of `image` and blue in towards it, by up
+/// to `shift` pixels at its corners.
+fn aberrate(image: &mut image::RgbaImage, shift: f32) {
+    let source = image.clone();
+    let (width, height) = source.dimensions();
+    let center = (width as f32 / 2., height as f32 / 2.);
+    let corner = center.0.hypot(center.1).max(1.);
+    let sample = |x: f32, y: f32, channel: usize| {
+        let x = (x.round() as i64).clamp(0, width as i64 - 1) as u32;
+        let y = (y.round() as i64).clamp(0, height as i64 - 1) as u32;
+
+        source.get_pixel(x, y)[channel]
+    };
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+        // Pixels move out from the center, further the further out they are.
+        let (ox, oy) = (dx / corner * shift, dy / corner * shift);
+        let (x, y) = (x as f32, y as f32);
+
+        pixel[0] = sample(x - ox, y - oy, 0);
+        pixel[2] = sample(x + ox, y + oy, 2);
+    }
+}
+
+/// Sharpens `image` with an unsharp mask about a canvas pixel wide, at
+/// `scale` image pixels per canvas pixel.
+fn sharpen(image: &mut image::RgbaImage, amount: f32, scale: f32) {
+    let blurred = image::imageops::fast_blur(image, scale.max(1.));
+
+    for (pixel, blurred) in image.pixels_mut().zip(blurred.pixels()) {
+        for channel in 0..3 {
+            let value = pixel[channel] as f32;
+            let detail = value - blurred[channel] as f32;
+
+            pixel[channel] = (value + detail * amount * 2.).clamp(0., 255.).round() as u8;
+        }
+    }
+}
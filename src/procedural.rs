@@ -0,0 +1,1016 @@
+use std::{borrow::Cow, fmt::Display, time::Duration};
+
+use iced::{
+    alignment,
+    widget::{
+        canvas,
+        image::{FilterMethod, Handle},
+        text::{LineHeight, Shaping},
+    },
+    Color, Font, Point, Rectangle, Size, Vector,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::{ColorDef, Playhead},
+    chroma::{note_frequency, note_name},
+    fonts::TextFont,
+    format_time,
+    i18n::t,
+    typesetting::{typeset, TextAnimation, TextPreset, TextStyle},
+};
+
+/// Content generated from a handful of settings instead of an imported file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Procedural {
+    Vignette(Vignette),
+    Grain(Grain),
+    Starfield(Starfield),
+    Readout(Readout),
+    Text(Text),
+    Timer(Timer),
+    Wave(Wave),
+    Progress(Progress),
+}
+
+impl Procedural {
+    pub const ALL: [Procedural; 8] = [
+        Procedural::Vignette(Vignette::DEFAULT),
+        Procedural::Grain(Grain::DEFAULT),
+        Procedural::Starfield(Starfield::DEFAULT),
+        Procedural::Readout(Readout::DEFAULT),
+        Procedural::Text(Text::DEFAULT),
+        Procedural::Timer(Timer::DEFAULT),
+        Procedural::Wave(Wave::DEFAULT),
+        Procedural::Progress(Progress::DEFAULT),
+    ];
+
+    pub fn is_audio_reactive(&self) -> bool {
+        match self {
+            Procedural::Starfield(starfield) => starfield.reactivity > 0.,
+            Procedural::Readout(readout) => readout.reading.is_numeric(),
+            Procedural::Wave(_) => true,
+            Procedural::Vignette(_)
+            | Procedural::Grain(_)
+            | Procedural::Text(_)
+            | Procedural::Timer(_)
+            | Procedural::Progress(_) => false,
+        }
+    }
+
+    /// Whether the layer draws text, in a font that can be picked.
+    pub fn is_text(&self) -> bool {
+        self.font().is_some()
+    }
+
+    pub fn font(&self) -> Option<TextFont> {
+        match self {
+            Procedural::Readout(readout) => Some(readout.font),
+            Procedural::Text(text) => Some(text.font),
+            Procedural::Timer(timer) => Some(timer.font),
+            Procedural::Vignette(_)
+            | Procedural::Grain(_)
+            | Procedural::Starfield(_)
+            | Procedural::Wave(_)
+            | Procedural::Progress(_) => None,
+        }
+    }
+
+    pub fn set_font(&mut self, font: TextFont) {
+        match self {
+            Procedural::Readout(readout) => readout.font = font,
+            Procedural::Text(text) => text.font = font,
+            Procedural::Timer(timer) => timer.font = font,
+            Procedural::Vignette(_)
+            | Procedural::Grain(_)
+            | Procedural::Starfield(_)
+            | Procedural::Wave(_)
+            | Procedural::Progress(_) => {}
+        }
+    }
+
+    /// The seed of the layer's randomness, if it has any. The same seed
+    /// always draws the same result.
+    pub fn seed(&self) -> Option<u64> {
+        match self {
+            Procedural::Grain(grain) => Some(grain.seed),
+            Procedural::Starfield(starfield) => Some(starfield.seed),
+            Procedural::Vignette(_)
+            | Procedural::Readout(_)
+            | Procedural::Text(_)
+            | Procedural::Timer(_)
+            | Procedural::Wave(_)
+            | Procedural::Progress(_) => None,
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        match self {
+            Procedural::Grain(grain) => grain.seed = seed,
+            Procedural::Starfield(starfield) => starfield.seed = seed,
+            Procedural::Vignette(_)
+            | Procedural::Readout(_)
+            | Procedural::Text(_)
+            | Procedural::Timer(_)
+            | Procedural::Wave(_)
+            | Procedural::Progress(_) => {}
+        }
+    }
+
+    /// Whether the drawing changes over time, rather than only with the
+    /// settings.
+    pub fn is_animated(&self) -> bool {
+        match self {
+            Procedural::Vignette(_) => false,
+            Procedural::Grain(grain) => grain.speed > 0.,
+            Procedural::Text(text) => text.animation.preset != TextPreset::None,
+            Procedural::Starfield(_)
+            | Procedural::Readout(_)
+            | Procedural::Timer(_)
+            | Procedural::Wave(_)
+            | Procedural::Progress(_) => true,
+        }
+    }
+
+    /// Renders the textures drawn by [`Procedural::draw`]. Only needs to be
+    /// called again when the settings change.
+    pub fn render(&self) -> Vec<Handle> {
+        match self {
+            Procedural::Vignette(vignette) => vec![vignette.render()],
+            Procedural::Grain(grain) => grain.render(),
+            Procedural::Starfield(_)
+            | Procedural::Readout(_)
+            | Procedural::Text(_)
+            | Procedural::Timer(_)
+            | Procedural::Wave(_)
+            | Procedural::Progress(_) => vec![],
+        }
+    }
+
+    /// Draws the layer at the playhead, `start` being the seconds into the
+    /// track at which it appears.
+    pub fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        frames: &[Handle],
+        playhead: &Playhead,
+        start: f32,
+        opacity: f32,
+    ) {
+        match self {
+            Procedural::Vignette(_) => {
+                if let Some(handle) = frames.first() {
+                    frame.draw_image(bounds, canvas::Image::new(handle.clone()).opacity(opacity));
+                }
+            }
+            Procedural::Grain(grain) => grain.draw(frame, bounds, frames, playhead, opacity),
+            Procedural::Starfield(starfield) => starfield.draw(frame, bounds, playhead, opacity),
+            Procedural::Readout(readout) => readout.draw(frame, bounds, playhead, opacity),
+            Procedural::Timer(timer) => timer.draw(frame, bounds, playhead, opacity),
+            Procedural::Wave(wave) => wave.draw(frame, bounds, playhead, opacity),
+            Procedural::Progress(progress) => progress.draw(frame, bounds, playhead, opacity),
+            Procedural::Text(text) => {
+                let since_start = playhead.time.as_secs_f32() - start;
+                text.draw(frame, bounds, since_start, playhead.since_beat(), opacity)
+            }
+        }
+    }
+}
+
+impl Display for Procedural {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Procedural::Vignette(_) => t("procedural-vignette"),
+            Procedural::Grain(_) => t("procedural-grain"),
+            Procedural::Starfield(_) => t("procedural-starfield"),
+            Procedural::Readout(_) => t("procedural-readout"),
+            Procedural::Text(_) => t("procedural-text"),
+            Procedural::Timer(_) => t("procedural-timer"),
+            Procedural::Wave(_) => t("procedural-wave"),
+            Procedural::Progress(_) => t("procedural-progress"),
+        })
+    }
+}
+
+/// Darkens the edges of the layer with an ellipse fitted to its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Vignette {
+    /// Distance from the center, as a fraction of the distance to the
+    /// corners, at which the shading starts.
+    pub size: f32,
+    /// How far the shading takes to reach full strength, in the same units
+    /// as `size`.
+    pub softness: f32,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+}
+
+impl Vignette {
+    pub const DEFAULT: Vignette = Vignette {
+        size: 0.5,
+        softness: 0.5,
+        color: Color::BLACK,
+    };
+
+    /// Resolution of the rendered falloff. It is smooth enough to be stretched
+    /// over any layer size.
+    const RESOLUTION: u32 = 256;
+
+    fn render(&self) -> Handle {
+        let resolution = Self::RESOLUTION;
+        let [r, g, b, a] = self.color.into_rgba8();
+
+        let image = image::RgbaImage::from_fn(resolution, resolution, |x, y| {
+            let position =
+                |coordinate: u32| (coordinate as f32 + 0.5) / resolution as f32 * 2. - 1.;
+            let distance = position(x).hypot(position(y)) / std::f32::consts::SQRT_2;
+            let strength = smoothstep(self.size, self.size + self.softness, distance);
+
+            image::Rgba([r, g, b, (strength * a as f32).round() as u8])
+        });
+
+        Handle::from_rgba(resolution, resolution, image.into_raw())
+    }
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Vignette::DEFAULT
+    }
+}
+
+/// Animated film grain, lightening and darkening random specks of whatever
+/// is underneath.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Grain {
+    pub amount: f32,
+    /// Size of a speck in canvas pixels.
+    pub size: f32,
+    /// How many times per second the grain changes. Zero keeps it still.
+    pub speed: f32,
+    pub seed: u64,
+}
+
+impl Grain {
+    pub const DEFAULT: Grain = Grain {
+        amount: 0.3,
+        size: 1.,
+        speed: 24.,
+        seed: 0,
+    };
+
+    const FRAMES: u64 = 8;
+    const TILE_SIZE: u32 = 256;
+
+    fn render(&self) -> Vec<Handle> {
+        let tile_size = Self::TILE_SIZE;
+
+        (0..Self::FRAMES)
+            .map(|frame| {
+                let mut rng =
+                    StdRng::seed_from_u64(self.seed.wrapping_mul(Self::FRAMES).wrapping_add(frame));
+                let image = image::RgbaImage::from_fn(tile_size, tile_size, |_, _| {
+                    let noise: f32 = rng.gen_range(-1.0..1.);
+                    let value = if noise > 0. { 255 } else { 0 };
+
+                    image::Rgba([
+                        value,
+                        value,
+                        value,
+                        (noise.abs() * self.amount * 255.).round() as u8,
+                    ])
+                });
+
+                Handle::from_rgba(tile_size, tile_size, image.into_raw())
+            })
+            .collect()
+    }
+
+    /// Covers `bounds` with grain tiles. Images are not clipped on the canvas,
+    /// so the tiles are stretched slightly to fit a whole number of them
+    /// instead of overflowing the layer.
+    fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        frames: &[Handle],
+        playhead: &Playhead,
+        opacity: f32,
+    ) {
+        if frames.is_empty() {
+            return;
+        }
+
+        let tile_size = Self::TILE_SIZE as f32 * self.size.max(0.1);
+        let columns = (bounds.width / tile_size).round().max(1.) as usize;
+        let rows = (bounds.height / tile_size).round().max(1.) as usize;
+        let tile = Size::new(bounds.width / columns as f32, bounds.height / rows as f32);
+        let current = (playhead.time.as_secs_f32() * self.speed) as usize;
+
+        for row in 0..rows {
+            for column in 0..columns {
+                // Neighbouring tiles show different frames so the repetition
+                // is harder to spot.
+                let handle = &frames[(current + row * 3 + column * 5) % frames.len()];
+
+                frame.draw_image(
+                    Rectangle::new(
+                        Point::new(
+                            bounds.x + column as f32 * tile.width,
+                            bounds.y + row as f32 * tile.height,
+                        ),
+                        tile,
+                    ),
+                    canvas::Image::new(handle.clone())
+                        .filter_method(FilterMethod::Nearest)
+                        .opacity(opacity),
+                );
+            }
+        }
+    }
+}
+
+impl Default for Grain {
+    fn default() -> Self {
+        Grain::DEFAULT
+    }
+}
+
+/// Stars flying towards the viewer out of the center of the layer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Starfield {
+    /// Stars per thousand square pixels of the layer.
+    pub density: f32,
+    /// How far the stars travel per second, as a fraction of the depth of
+    /// the field.
+    pub speed: f32,
+    /// How strongly perspective spreads the stars out as they get closer.
+    /// At zero the stars stay in place.
+    pub depth: f32,
+    /// How much the brightness of each star flickers.
+    pub twinkle: f32,
+    /// Extra speed added by loud parts of the audio, as a multiple of `speed`.
+    pub reactivity: f32,
+    pub seed: u64,
+}
+
+impl Starfield {
+    pub const DEFAULT: Starfield = Starfield {
+        density: 0.3,
+        speed: 0.1,
+        depth: 0.8,
+        twinkle: 0.3,
+        reactivity: 2.,
+        seed: 0,
+    };
+
+    /// The nearest a star gets before wrapping around to the back, keeping
+    /// the perspective projection finite.
+    const NEAR: f32 = 0.05;
+
+    fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        playhead: &Playhead,
+        opacity: f32,
+    ) {
+        let count = (self.density * bounds.width * bounds.height / 1000.) as usize;
+        let center = bounds.center();
+        let time = playhead.time.as_secs_f32();
+        let travelled = self.speed * (time + self.reactivity * playhead.accumulated_level());
+
+        // Re-seeded every frame so each star keeps its place and only the
+        // distance travelled changes.
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        for _ in 0..count {
+            let x: f32 = rng.gen_range(-1.0..1.);
+            let y: f32 = rng.gen_range(-1.0..1.);
+            let start: f32 = rng.gen();
+            let phase: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+            let rate: f32 = rng.gen_range(1.0..4.);
+
+            let z = (start - travelled).rem_euclid(1.);
+            let distance = Self::NEAR + (1. - Self::NEAR) * z;
+            let projected = 1. / (1. - self.depth + self.depth * distance);
+            let position = Point::new(
+                center.x + x * bounds.width / 2. * projected,
+                center.y + y * bounds.height / 2. * projected,
+            );
+            if !bounds.contains(position) {
+                continue;
+            }
+
+            let flicker = 1. - self.twinkle * 0.5 * (1. + (time * rate + phase).sin());
+            let brightness = (1. - z) * flicker * opacity;
+            let radius = 0.5 + 1.5 * (1. - z) * self.depth;
+
+            frame.fill(
+                &canvas::Path::circle(position, radius),
+                Color::from_rgba(1., 1., 1., brightness.clamp(0., 1.)),
+            );
+        }
+    }
+}
+
+impl Default for Starfield {
+    fn default() -> Self {
+        Starfield::DEFAULT
+    }
+}
+
+/// A live value of the audio or the playhead printed as text, for a technical
+/// overlay look.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Readout {
+    pub reading: Reading,
+    /// Height of the text in canvas pixels.
+    pub size: f32,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    /// Whether the value follows the name of the reading.
+    pub labeled: bool,
+    /// Digits shown after the decimal point of numbers.
+    pub decimals: u8,
+    pub font: TextFont,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Reading {
+    /// Loudness in decibels.
+    #[default]
+    Level,
+    /// The strongest note, with its frequency.
+    Note,
+    /// Time since the start of the track.
+    Elapsed,
+    /// Beats per minute.
+    Tempo,
+    /// The name of the marker the playhead is past, e.g. the title of the
+    /// section of an episode.
+    Chapter,
+}
+
+impl Reading {
+    pub const ALL: [Reading; 5] = [
+        Reading::Level,
+        Reading::Note,
+        Reading::Elapsed,
+        Reading::Tempo,
+        Reading::Chapter,
+    ];
+
+    /// Whether the reading is a number that `Readout::decimals` applies to.
+    pub fn is_numeric(self) -> bool {
+        matches!(self, Reading::Level | Reading::Note | Reading::Tempo)
+    }
+}
+
+impl Display for Reading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Reading::Level => t("reading-level"),
+            Reading::Note => t("reading-note"),
+            Reading::Elapsed => t("reading-elapsed"),
+            Reading::Tempo => t("reading-tempo"),
+            Reading::Chapter => t("reading-chapter"),
+        })
+    }
+}
+
+impl Readout {
+    pub const DEFAULT: Readout = Readout {
+        reading: Reading::Level,
+        size: 48.,
+        color: Color::WHITE,
+        labeled: true,
+        decimals: 1,
+        font: TextFont::DEFAULT,
+    };
+
+    fn text(&self, playhead: &Playhead) -> String {
+        let decimals = self.decimals as usize;
+        let (label, value) = match self.reading {
+            Reading::Level => (
+                "level",
+                playhead.decibels().map_or("-∞ dB".to_string(), |level| {
+                    format!("{level:.decimals$} dB")
+                }),
+            ),
+            Reading::Note => (
+                "note",
+                playhead.pitch().note.map_or("–".to_string(), |note| {
+                    format!("{} {:.decimals$} Hz", note_name(note), note_frequency(note))
+                }),
+            ),
+            Reading::Elapsed => ("time", format_time(playhead.time)),
+            Reading::Tempo => (
+                "tempo",
+                playhead
+                    .tempo()
+                    .map_or("–".to_string(), |tempo| format!("{tempo:.decimals$} BPM")),
+            ),
+            Reading::Chapter => ("chapter", playhead.chapter().unwrap_or("–").to_string()),
+        };
+
+        if self.labeled {
+            format!("{label}: {value}")
+        } else {
+            value
+        }
+    }
+
+    fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        playhead: &Playhead,
+        opacity: f32,
+    ) {
+        frame.fill_text(canvas::Text {
+            content: self.text(playhead),
+            position: bounds.center(),
+            color: Color {
+                a: self.color.a * opacity,
+                ..self.color
+            },
+            size: self.size.into(),
+            font: self.font.to_font(Font::MONOSPACE),
+            horizontal_alignment: alignment::Horizontal::Center,
+            vertical_alignment: alignment::Vertical::Center,
+            ..canvas::Text::default()
+        });
+    }
+}
+
+impl Default for Readout {
+    fn default() -> Self {
+        Readout::DEFAULT
+    }
+}
+
+/// Time counted up or down on a clock, e.g. for a premiere or a waiting
+/// screen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timer {
+    pub counting: Counting,
+    /// Seconds into the track that a countdown ends at.
+    pub target: f32,
+    pub format: TimerFormat,
+    /// Height of the text in canvas pixels.
+    pub size: f32,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    pub font: TextFont,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Counting {
+    /// Time since the start of the track.
+    Elapsed,
+    /// Time until the end of the track.
+    #[default]
+    Remaining,
+    /// Time until [`Timer::target`], staying at zero after.
+    Countdown,
+}
+
+impl Counting {
+    pub const ALL: [Counting; 3] = [Counting::Elapsed, Counting::Remaining, Counting::Countdown];
+}
+
+impl Display for Counting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Counting::Elapsed => t("counting-elapsed"),
+            Counting::Remaining => t("counting-remaining"),
+            Counting::Countdown => t("counting-countdown"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimerFormat {
+    /// `1:05`
+    #[default]
+    Minutes,
+    /// `0:01:05`
+    Hours,
+    /// `1:05.42`
+    Hundredths,
+    /// `65`
+    Seconds,
+}
+
+impl TimerFormat {
+    pub const ALL: [TimerFormat; 4] = [
+        TimerFormat::Minutes,
+        TimerFormat::Hours,
+        TimerFormat::Hundredths,
+        TimerFormat::Seconds,
+    ];
+
+    /// Writes out `seconds`, rounded up for times counted down so that zero
+    /// is only shown once the time is up.
+    fn format(self, seconds: f32, rounds_up: bool) -> String {
+        let per_second = if self == TimerFormat::Hundredths {
+            100.
+        } else {
+            1.
+        };
+        let units = seconds.max(0.) * per_second;
+        let units = if rounds_up {
+            units.ceil()
+        } else {
+            units.floor()
+        } as u64;
+
+        match self {
+            TimerFormat::Minutes => format!("{}:{:02}", units / 60, units % 60),
+            TimerFormat::Hours => {
+                format!("{}:{:02}:{:02}", units / 3600, units / 60 % 60, units % 60)
+            }
+            TimerFormat::Hundredths => format!(
+                "{}:{:02}.{:02}",
+                units / 6000,
+                units / 100 % 60,
+                units % 100
+            ),
+            TimerFormat::Seconds => units.to_string(),
+        }
+    }
+}
+
+impl Display for TimerFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TimerFormat::Minutes => "m:ss",
+            TimerFormat::Hours => "h:mm:ss",
+            TimerFormat::Hundredths => "m:ss.cc",
+            TimerFormat::Seconds => t("timer-seconds"),
+        })
+    }
+}
+
+impl Timer {
+    pub const DEFAULT: Timer = Timer {
+        counting: Counting::Remaining,
+        target: 0.,
+        format: TimerFormat::Minutes,
+        size: 96.,
+        color: Color::WHITE,
+        font: TextFont::DEFAULT,
+    };
+
+    fn text(&self, playhead: &Playhead) -> String {
+        let time = playhead.time.as_secs_f32();
+
+        match self.counting {
+            Counting::Elapsed => self.format.format(time, false),
+            Counting::Remaining => playhead.remaining().map_or("–".to_string(), |remaining| {
+                self.format.format(remaining.as_secs_f32(), true)
+            }),
+            Counting::Countdown => self.format.format(self.target - time, true),
+        }
+    }
+
+    fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        playhead: &Playhead,
+        opacity: f32,
+    ) {
+        frame.fill_text(canvas::Text {
+            content: self.text(playhead),
+            position: bounds.center(),
+            color: Color {
+                a: self.color.a * opacity,
+                ..self.color
+            },
+            size: self.size.into(),
+            // Digits of the same width keep the time from jittering.
+            font: self.font.to_font(Font::MONOSPACE),
+            horizontal_alignment: alignment::Horizontal::Center,
+            vertical_alignment: alignment::Vertical::Center,
+            ..canvas::Text::default()
+        });
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Timer::DEFAULT
+    }
+}
+
+/// The shape of the audio around the playhead, scrolling past as it plays,
+/// e.g. for an audiogram.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Wave {
+    pub style: WaveStyle,
+    /// Seconds of audio across the layer, the playhead in the middle.
+    pub span: f32,
+    /// Width of a bar in canvas pixels, including the gap after it.
+    pub bar_width: f32,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    /// Whether the audio up to the playhead is reflected about the middle,
+    /// so that it spreads out from there to both sides.
+    pub is_mirrored: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WaveStyle {
+    #[default]
+    Bars,
+    Line,
+}
+
+impl WaveStyle {
+    pub const ALL: [WaveStyle; 2] = [WaveStyle::Bars, WaveStyle::Line];
+}
+
+impl Display for WaveStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            WaveStyle::Bars => t("wave-bars"),
+            WaveStyle::Line => t("wave-line"),
+        })
+    }
+}
+
+impl Wave {
+    pub const DEFAULT: Wave = Wave {
+        style: WaveStyle::Bars,
+        span: 4.,
+        bar_width: 8.,
+        color: Color::WHITE,
+        is_mirrored: false,
+    };
+
+    /// Draws the peaks mirrored around the middle of the layer, one bar or
+    /// point for every `bar_width` pixels.
+    fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        playhead: &Playhead,
+        opacity: f32,
+    ) {
+        let count = (bounds.width / self.bar_width.max(1.)).floor().max(1.) as usize;
+        let mut peaks = playhead.peaks(Duration::from_secs_f32(self.span.max(0.1)), count);
+        if self.is_mirrored {
+            // The left half ends at the playhead, and the right half is the
+            // same read backwards.
+            let half = count.div_ceil(2);
+            for index in half..count {
+                peaks[index] = peaks[count - 1 - index];
+            }
+        }
+        let step = bounds.width / count as f32;
+        let middle = bounds.center_y();
+        let color = Color {
+            a: self.color.a * opacity,
+            ..self.color
+        };
+        let height = |peak: f32| (peak * bounds.height / 2.).max(1.);
+
+        match self.style {
+            WaveStyle::Bars => {
+                let width = (step * 0.6).max(1.);
+
+                for (index, peak) in peaks.iter().enumerate() {
+                    let height = height(*peak);
+                    let x = bounds.x + index as f32 * step + (step - width) / 2.;
+
+                    frame.fill(
+                        &canvas::Path::rounded_rectangle(
+                            Point::new(x, middle - height),
+                            Size::new(width, height * 2.),
+                            (width / 2.).into(),
+                        ),
+                        color,
+                    );
+                }
+            }
+            WaveStyle::Line => {
+                let point = |index: usize, peak: f32, sign: f32| {
+                    Point::new(
+                        bounds.x + (index as f32 + 0.5) * step,
+                        middle + sign * height(peak),
+                    )
+                };
+                let outline = canvas::Path::new(|builder| {
+                    for (index, peak) in peaks.iter().enumerate() {
+                        if index == 0 {
+                            builder.move_to(point(index, *peak, -1.));
+                        } else {
+                            builder.line_to(point(index, *peak, -1.));
+                        }
+                    }
+                    for (index, peak) in peaks.iter().enumerate().rev() {
+                        builder.line_to(point(index, *peak, 1.));
+                    }
+                    builder.close();
+                });
+
+                frame.fill(&outline, color);
+            }
+        }
+    }
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Wave::DEFAULT
+    }
+}
+
+/// A bar filling up as the track plays.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    /// The color of the part yet to be played.
+    #[serde(with = "ColorDef")]
+    pub track_color: Color,
+    pub is_rounded: bool,
+}
+
+impl Progress {
+    pub const DEFAULT: Progress = Progress {
+        color: Color::WHITE,
+        track_color: Color::from_rgba(1., 1., 1., 0.25),
+        is_rounded: true,
+    };
+
+    fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        playhead: &Playhead,
+        opacity: f32,
+    ) {
+        let radius = if self.is_rounded {
+            bounds.height.min(bounds.width) / 2.
+        } else {
+            0.
+        };
+        let mut fill = |width: f32, color: Color| {
+            if width <= 0. {
+                return;
+            }
+
+            frame.fill(
+                &canvas::Path::rounded_rectangle(
+                    bounds.position(),
+                    Size::new(width, bounds.height),
+                    radius.min(width / 2.).into(),
+                ),
+                Color {
+                    a: color.a * opacity,
+                    ..color
+                },
+            );
+        };
+
+        fill(bounds.width, self.track_color);
+        fill(bounds.width * playhead.progress().unwrap_or(0.), self.color);
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress::DEFAULT
+    }
+}
+
+/// Words typed in by the user, e.g. a title or a line of lyrics.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Text {
+    pub content: Cow<'static, str>,
+    pub font: TextFont,
+    /// Height of the text in canvas pixels.
+    pub size: f32,
+    #[serde(with = "ColorDef")]
+    pub color: Color,
+    pub style: TextStyle,
+    pub animation: TextAnimation,
+}
+
+impl Text {
+    pub const DEFAULT: Text = Text {
+        content: Cow::Borrowed("Title"),
+        font: TextFont::DEFAULT,
+        size: 72.,
+        color: Color::WHITE,
+        style: TextStyle::DEFAULT,
+        animation: TextAnimation::NONE,
+    };
+
+    fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        since_start: f32,
+        since_beat: Option<f32>,
+        opacity: f32,
+    ) {
+        let with_opacity = |color: Color, visibility: f32| Color {
+            a: color.a * opacity * visibility,
+            ..color
+        };
+        let typeset = typeset(
+            &self.content,
+            self.font.to_font(Font::DEFAULT),
+            self.size,
+            &self.style,
+            self.animation.unit(),
+        );
+        let origin =
+            bounds.center() - Vector::new(typeset.size.width / 2., typeset.size.height / 2.);
+
+        if self.style.has_background {
+            let padding = self.style.background_padding.max(0.);
+            frame.fill(
+                &canvas::Path::rounded_rectangle(
+                    origin - Vector::new(padding, padding),
+                    Size::new(
+                        typeset.size.width + padding * 2.,
+                        typeset.size.height + padding * 2.,
+                    ),
+                    padding.min(self.size / 2.).into(),
+                ),
+                with_opacity(self.style.background_color, 1.),
+            );
+        }
+
+        for segment in typeset.segments {
+            let (visibility, lift) = self.animation.state(segment.index, since_start, since_beat);
+            if visibility <= 0. {
+                continue;
+            }
+
+            let lifted = origin - Vector::new(0., lift * self.size);
+            let text = canvas::Text {
+                // Starts off left to right, as iced places right-to-left
+                // text against the far edge of its unbounded width. Runs
+                // of right-to-left text are still ordered as they should be.
+                content: format!("\u{200E}{}", segment.text),
+                position: lifted + segment.offset,
+                color: with_opacity(self.color, visibility),
+                size: self.size.into(),
+                line_height: LineHeight::Absolute(typeset.line_height.into()),
+                font: segment.font,
+                // Falls back to other fonts for emoji and scripts the font
+                // doesn't cover, and orders right-to-left text.
+                shaping: Shaping::Advanced,
+                ..canvas::Text::default()
+            };
+
+            // The stroke is centered on the outline of each letter, and the
+            // inner half is covered by the fill.
+            if self.style.outline_width > 0. {
+                let stroke = canvas::Stroke::default()
+                    .with_color(with_opacity(self.style.outline_color, visibility))
+                    .with_width(self.style.outline_width * 2.)
+                    .with_line_join(canvas::LineJoin::Round);
+                // Outlines are drawn with the baseline a size below the
+                // position, rather than centered in the line.
+                let outline = canvas::Text {
+                    position: lifted + Vector::new(segment.offset.x, segment.baseline - self.size),
+                    ..text.clone()
+                };
+                outline.draw_with(|path, _| frame.stroke(&path, stroke));
+            }
+            frame.fill_text(text);
+        }
+    }
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text::DEFAULT
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    if edge1 <= edge0 {
+        return if x < edge0 { 0. } else { 1. };
+    }
+
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
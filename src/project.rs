@@ -0,0 +1,586 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::{Animation, Playback, Timing},
+    assets::Contents,
+    audio::{Channel, Mix, Source, Trim},
+    canvas::{Appearance, Crop, Flip, Sizing, Units, Visibility},
+    capture::CaptureSource,
+    directories::{self, DialogKind},
+    fonts::EmbeddedFont,
+    i18n::t,
+    keyframes::{Keyframes, Property},
+    lut::Lut,
+    markers::Marker,
+    palette::Swatch,
+    post_fx::PostFx,
+    procedural::Procedural,
+    scenes::Scene,
+    stream_file, ColorField, Error, AUDIO_EXTENSIONS, IMAGE_EXTENSIONS, LOTTIE_EXTENSIONS,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub canvas_width: f32,
+    pub canvas_height: f32,
+    #[serde(default, with = "raw_path::option")]
+    pub audio: Option<PathBuf>,
+    pub layers: Vec<ProjectLayer>,
+    #[serde(default)]
+    pub frame_rate: FrameRate,
+    #[serde(default)]
+    pub audio_trim: Trim,
+    /// Played over the main audio track, e.g. a voiceover.
+    #[serde(default)]
+    pub audio_tracks: Vec<ProjectAudioTrack>,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    #[serde(default)]
+    pub expressions: Vec<ProjectExpression>,
+    #[serde(default)]
+    pub fonts: Vec<EmbeddedFont>,
+    #[serde(default)]
+    pub swatches: Vec<Swatch>,
+    #[serde(default)]
+    pub swatch_uses: Vec<SwatchUse>,
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    /// Sorted by start, and referred to by index from layers' timing.
+    #[serde(default)]
+    pub scenes: Vec<Scene>,
+    #[serde(default)]
+    pub snapshots: Vec<Snapshot>,
+    #[serde(default)]
+    pub post_fx: PostFx,
+}
+
+/// A named copy of the project as it was at some point, kept in the project
+/// file to go back to, e.g. "before color change". Its own snapshots are
+/// left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    /// Seconds since the Unix epoch at which it was taken.
+    pub taken: u64,
+    pub project: Box<Project>,
+}
+
+/// A property of a layer worked out from a formula instead of holding a
+/// value, as typed by the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectExpression {
+    pub layer: usize,
+    pub property: Property,
+    pub source: String,
+}
+
+/// A property of one layer that follows the same property of another, so the
+/// two move together. Layers are referred to by their index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    pub layer: usize,
+    pub property: Property,
+    pub source: usize,
+}
+
+/// A color of a layer that follows one of the project's swatches, by the
+/// swatch's index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwatchUse {
+    pub field: ColorField,
+    pub swatch: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectAudioTrack {
+    #[serde(with = "raw_path")]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub mix: Mix,
+}
+
+/// Rate at which the preview is redrawn and animations are evaluated, so they
+/// look the same regardless of the monitor's refresh rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FrameRate {
+    Fps24,
+    Fps25,
+    #[default]
+    Fps30,
+    Fps60,
+}
+
+impl FrameRate {
+    pub const ALL: [FrameRate; 4] = [
+        FrameRate::Fps24,
+        FrameRate::Fps25,
+        FrameRate::Fps30,
+        FrameRate::Fps60,
+    ];
+
+    pub fn per_second(self) -> u32 {
+        match self {
+            FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps30 => 30,
+            FrameRate::Fps60 => 60,
+        }
+    }
+
+    pub fn frame_duration(self) -> Duration {
+        Duration::from_secs(1) / self.per_second()
+    }
+
+    /// Index of the frame that `time` falls in.
+    pub fn frame(self, time: Duration) -> u64 {
+        // Frame start times are rounded down to whole nanoseconds, which
+        // would otherwise land them at the end of the previous frame.
+        (time.as_secs_f64() * self.per_second() as f64 + 1e-6).floor() as u64
+    }
+
+    /// Time at which the frame at `index` starts.
+    pub fn time(self, index: u64) -> Duration {
+        Duration::from_secs_f64(index as f64 / self.per_second() as f64)
+    }
+
+    /// The start of the frame that `time` falls in.
+    pub fn snap(self, time: Duration) -> Duration {
+        self.time(self.frame(time))
+    }
+
+    /// `seconds` rounded to the nearest frame.
+    pub fn snap_seconds(self, seconds: f32) -> f32 {
+        let per_second = self.per_second() as f32;
+
+        (seconds * per_second).round() / per_second
+    }
+}
+
+impl Display for FrameRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} fps", self.per_second())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectLayer {
+    pub name: String,
+    pub source: LayerSource,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub scale: f32,
+    pub opacity: f32,
+    #[serde(default)]
+    pub sizing: Sizing,
+    #[serde(default)]
+    pub crop: Crop,
+    #[serde(default)]
+    pub flip: Flip,
+    #[serde(default)]
+    pub lut: Option<Lut>,
+    #[serde(default)]
+    pub appearance: Appearance,
+    #[serde(default)]
+    pub animation: Animation,
+    #[serde(default)]
+    pub timing: Timing,
+    #[serde(default)]
+    pub playback: Playback,
+    #[serde(default)]
+    pub keyframes: Keyframes,
+    #[serde(default)]
+    pub audio_channel: Channel,
+    #[serde(default)]
+    pub audio_source: Source,
+    #[serde(default)]
+    pub units: Units,
+    #[serde(default)]
+    pub is_aspect_locked: bool,
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LayerSource {
+    Image(#[serde(with = "raw_path")] PathBuf),
+    Lottie(#[serde(with = "raw_path")] PathBuf),
+    Placeholder,
+    Procedural(Procedural),
+    Capture(CaptureSource),
+    Backdrop,
+}
+
+impl ProjectLayer {
+    fn path(&self) -> Option<&Path> {
+        match &self.source {
+            LayerSource::Image(path) | LayerSource::Lottie(path) => Some(path),
+            LayerSource::Placeholder
+            | LayerSource::Procedural(_)
+            | LayerSource::Capture(_)
+            | LayerSource::Backdrop => None,
+        }
+    }
+
+    fn path_mut(&mut self) -> Option<&mut PathBuf> {
+        match &mut self.source {
+            LayerSource::Image(path) | LayerSource::Lottie(path) => Some(path),
+            LayerSource::Placeholder
+            | LayerSource::Procedural(_)
+            | LayerSource::Capture(_)
+            | LayerSource::Backdrop => None,
+        }
+    }
+}
+
+/// A project together with its audio files and the bytes of each layer.
+pub type LoadedAssets = (
+    Project,
+    Option<Contents>,
+    Vec<Contents>,
+    Vec<Option<Arc<Vec<u8>>>>,
+);
+
+/// A file referenced by a project that could not be found on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetRef {
+    Audio,
+    AudioTrack(usize),
+    Layer(usize),
+}
+
+impl Project {
+    /// Rewrites every asset path relative to `base`, the directory the project
+    /// file is saved in, so the project keeps working when moved together
+    /// with its assets.
+    pub fn relativize(&mut self, base: &Path) {
+        if let Some(audio) = &mut self.audio {
+            *audio = relative_path(audio, base);
+        }
+        for track in &mut self.audio_tracks {
+            track.path = relative_path(&track.path, base);
+        }
+        for path in self.layers.iter_mut().filter_map(ProjectLayer::path_mut) {
+            *path = relative_path(path, base);
+        }
+        for snapshot in &mut self.snapshots {
+            snapshot.project.relativize(base);
+        }
+    }
+
+    /// Inverse of [`Project::relativize`].
+    pub fn resolve(&mut self, base: &Path) {
+        if let Some(audio) = &mut self.audio {
+            *audio = base.join(&*audio);
+        }
+        for track in &mut self.audio_tracks {
+            track.path = base.join(&track.path);
+        }
+        for path in self.layers.iter_mut().filter_map(ProjectLayer::path_mut) {
+            *path = base.join(&*path);
+        }
+        for snapshot in &mut self.snapshots {
+            snapshot.project.resolve(base);
+        }
+    }
+
+    pub fn asset_path(&self, asset: AssetRef) -> Option<&Path> {
+        match asset {
+            AssetRef::Audio => self.audio.as_deref(),
+            AssetRef::AudioTrack(index) => self
+                .audio_tracks
+                .get(index)
+                .map(|track| track.path.as_path()),
+            AssetRef::Layer(index) => self.layers.get(index).and_then(ProjectLayer::path),
+        }
+    }
+
+    pub fn set_asset_path(&mut self, asset: AssetRef, path: PathBuf) {
+        match asset {
+            AssetRef::Audio => self.audio = Some(path),
+            AssetRef::AudioTrack(index) => {
+                if let Some(track) = self.audio_tracks.get_mut(index) {
+                    track.path = path;
+                }
+            }
+            AssetRef::Layer(index) => {
+                if let Some(path_mut) = self.layers.get_mut(index).and_then(ProjectLayer::path_mut)
+                {
+                    *path_mut = path;
+                }
+            }
+        }
+    }
+
+    /// Drops every asset in `missing`, used when the user chooses to open the
+    /// project without relinking them.
+    pub fn remove_assets(&mut self, missing: &[AssetRef]) {
+        if missing.contains(&AssetRef::Audio) {
+            self.audio = None;
+        }
+        // Removed last to first, so the indices of the ones still to remove
+        // stay the same.
+        for index in (0..self.audio_tracks.len()).rev() {
+            if missing.contains(&AssetRef::AudioTrack(index)) {
+                self.audio_tracks.remove(index);
+                for layer in &mut self.layers {
+                    layer.audio_source = layer.audio_source.without(index);
+                }
+            }
+        }
+        let mut index = 0;
+        self.layers.retain(|_| {
+            let keep = !missing.contains(&AssetRef::Layer(index));
+            index += 1;
+            keep
+        });
+    }
+}
+
+/// Paths saved as text where they are valid Unicode, and otherwise as the
+/// bytes, or on Windows the UTF-16 units, they are kept in by the system,
+/// which serde would refuse to save at all.
+pub(crate) mod raw_path {
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum RawPath {
+        Unicode(String),
+        #[cfg(unix)]
+        Bytes(Vec<u8>),
+        #[cfg(windows)]
+        Wide(Vec<u16>),
+    }
+
+    impl From<&Path> for RawPath {
+        fn from(path: &Path) -> RawPath {
+            match path.to_str() {
+                Some(path) => RawPath::Unicode(path.to_string()),
+                #[cfg(unix)]
+                None => RawPath::Bytes(
+                    std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec(),
+                ),
+                #[cfg(windows)]
+                None => RawPath::Wide(
+                    std::os::windows::ffi::OsStrExt::encode_wide(path.as_os_str()).collect(),
+                ),
+                #[cfg(not(any(unix, windows)))]
+                None => RawPath::Unicode(path.to_string_lossy().into_owned()),
+            }
+        }
+    }
+
+    impl From<RawPath> for PathBuf {
+        fn from(path: RawPath) -> PathBuf {
+            match path {
+                RawPath::Unicode(path) => PathBuf::from(path),
+                #[cfg(unix)]
+                RawPath::Bytes(bytes) => PathBuf::from(
+                    <std::ffi::OsString as std::os::unix::ffi::OsStringExt>::from_vec(bytes),
+                ),
+                #[cfg(windows)]
+                RawPath::Wide(wide) => PathBuf::from(
+                    <std::ffi::OsString as std::os::windows::ffi::OsStringExt>::from_wide(&wide),
+                ),
+            }
+        }
+    }
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        RawPath::from(path).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        RawPath::deserialize(deserializer).map(PathBuf::from)
+    }
+
+    pub mod option {
+        use std::path::PathBuf;
+
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        use super::RawPath;
+
+        pub fn serialize<S: Serializer>(
+            path: &Option<PathBuf>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            path.as_deref().map(RawPath::from).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<PathBuf>, D::Error> {
+            Option::<RawPath>::deserialize(deserializer).map(|path| path.map(PathBuf::from))
+        }
+    }
+}
+
+/// Returns `path` expressed relative to the directory `base`, falling back to
+/// `path` itself when the two share no root (e.g. different drives).
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+
+    if path_components.peek() != base_components.peek() {
+        return path.to_path_buf();
+    }
+
+    while let (Some(a), Some(b)) = (path_components.peek(), base_components.peek()) {
+        if a != b {
+            break;
+        }
+        path_components.next();
+        base_components.next();
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in base_components {
+        relative.push("..");
+    }
+    for component in path_components {
+        relative.push(component);
+    }
+    relative
+}
+
+pub async fn open_project_file() -> Result<(PathBuf, Project), Error> {
+    let picked_file = directories::dialog(DialogKind::Project)
+        .set_title(t("project-open-dialog"))
+        .add_filter(t("filter-project"), &["ron"])
+        .pick_file()
+        .await
+        .ok_or(Error::DialogClosed)?;
+    directories::remember(DialogKind::Project, picked_file.path()).await;
+
+    load_project(picked_file).await
+}
+
+pub async fn load_project(path: impl Into<PathBuf>) -> Result<(PathBuf, Project), Error> {
+    let path = path.into();
+
+    let contents = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|error| Error::IoError(error.kind()))?;
+
+    let mut project: Project =
+        ron::from_str(&contents).map_err(|error| Error::ProjectFormat(error.to_string()))?;
+
+    if let Some(base) = path.parent() {
+        project.resolve(base);
+    }
+
+    Ok((path, project))
+}
+
+pub async fn save_project(path: Option<PathBuf>, mut project: Project) -> Result<PathBuf, Error> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let path: PathBuf = directories::dialog(DialogKind::Project)
+                .set_title(t("project-save-dialog"))
+                .add_filter(t("filter-project"), &["ron"])
+                .set_file_name("project.ron")
+                .save_file()
+                .await
+                .ok_or(Error::DialogClosed)?
+                .into();
+            directories::remember(DialogKind::Project, &path).await;
+
+            path
+        }
+    };
+
+    if let Some(base) = path.parent() {
+        project.relativize(base);
+    }
+
+    let contents = ron::ser::to_string_pretty(&project, ron::ser::PrettyConfig::default())
+        .map_err(|error| Error::ProjectFormat(error.to_string()))?;
+
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|error| Error::IoError(error.kind()))?;
+
+    Ok(path)
+}
+
+pub async fn pick_replacement(asset: AssetRef) -> Option<PathBuf> {
+    let kind = match asset {
+        AssetRef::Audio | AssetRef::AudioTrack(_) => DialogKind::Audio,
+        AssetRef::Layer(_) => DialogKind::Image,
+    };
+    let dialog = directories::dialog(kind).set_title(t("relink-dialog"));
+    let dialog = match kind {
+        DialogKind::Audio => dialog.add_filter(t("filter-audio"), AUDIO_EXTENSIONS),
+        _ => dialog
+            .add_filter(t("filter-image"), IMAGE_EXTENSIONS)
+            .add_filter(t("filter-lottie"), LOTTIE_EXTENSIONS),
+    };
+
+    let picked: PathBuf = dialog.pick_file().await?.into();
+    directories::remember(kind, &picked).await;
+
+    Some(picked)
+}
+
+/// The project with its assets whose files are gone, e.g. moved since it
+/// was saved. Looked for off the interface's thread, as a network drive or
+/// a disk spinning up can take a while to answer.
+pub async fn missing_assets(project: Project) -> (Project, Vec<AssetRef>) {
+    let audio = project.audio.iter().map(|_| AssetRef::Audio);
+    let tracks = (0..project.audio_tracks.len()).map(AssetRef::AudioTrack);
+    let layers = (0..project.layers.len()).map(AssetRef::Layer);
+
+    let mut missing = vec![];
+    for asset in audio.chain(tracks).chain(layers) {
+        if let Some(path) = project.asset_path(asset) {
+            if !tokio::fs::try_exists(path).await.unwrap_or(false) {
+                missing.push(asset);
+            }
+        }
+    }
+
+    (project, missing)
+}
+
+/// Reads the bytes of every asset in the project, in layer order.
+pub async fn load_project_assets(project: Project) -> Result<LoadedAssets, Error> {
+    let audio = match &project.audio {
+        Some(path) => Some(stream_file(path.clone(), Arc::default()).await?.1),
+        None => None,
+    };
+
+    let mut tracks = Vec::with_capacity(project.audio_tracks.len());
+    for track in &project.audio_tracks {
+        tracks.push(stream_file(track.path.clone(), Arc::default()).await?.1);
+    }
+
+    let mut layers = Vec::with_capacity(project.layers.len());
+    for layer in &project.layers {
+        layers.push(match layer.path() {
+            Some(path) => Some(read(path).await?),
+            None => None,
+        });
+    }
+
+    Ok((project, audio, tracks, layers))
+}
+
+async fn read(path: &Path) -> Result<Arc<Vec<u8>>, Error> {
+    tokio::fs::read(path)
+        .await
+        .map(Arc::new)
+        .map_err(|error| Error::IoError(error.kind()))
+}
@@ -0,0 +1,653 @@
+use std::{
+    io::{self, Cursor},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use iced::{Rectangle, Size};
+
+use crate::{
+    directories::{self, DialogKind},
+    i18n::t,
+    Error,
+};
+
+/// A visible raster layer of a Photoshop file, written out as a PNG so that
+/// projects can refer to it like to any other image.
+#[derive(Debug, Clone)]
+pub struct PsdLayer {
+    pub name: String,
+    /// Where the layer's pixels are in the document.
+    pub bounds: Rectangle,
+    /// From 0 to 1.
+    pub opacity: f32,
+    pub path: PathBuf,
+    pub contents: Arc<Vec<u8>>,
+}
+
+/// The size of a Photoshop document and its layers, bottom first.
+pub type PsdDocument = (Size, Vec<PsdLayer>);
+
+pub async fn open() -> Result<PsdDocument, Error> {
+    let picked_file = directories::dialog(DialogKind::Image)
+        .set_title(t("psd-import-dialog"))
+        .add_filter(t("filter-psd"), &["psd"])
+        .pick_file()
+        .await
+        .ok_or(Error::DialogClosed)?;
+    directories::remember(DialogKind::Image, picked_file.path()).await;
+
+    import(picked_file.into()).await
+}
+
+/// Reads the visible raster layers of the 8-bit RGB or grayscale Photoshop
+/// file at `path`, and writes each to a PNG in a folder next to it. Layers
+/// in hidden groups count as hidden, and adjustment layers, whose pixels
+/// are empty, are left out.
+pub async fn import(path: PathBuf) -> Result<PsdDocument, Error> {
+    let io_error = |error: io::Error| Error::IoError(error.kind());
+
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::read(&path).map_err(io_error)?;
+        let (size, layers) = parse(&file)
+            .ok_or_else(|| Error::ImageDecode(t("error-psd-unsupported").to_string()))?;
+        if layers.is_empty() {
+            return Err(Error::ImageDecode(t("error-psd-no-layers").to_string()));
+        }
+
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let folder = path.with_file_name(format!("{} layers", stem));
+        std::fs::create_dir_all(&folder).map_err(io_error)?;
+
+        let layers = layers
+            .into_iter()
+            .enumerate()
+            .map(|(index, layer)| {
+                let mut png = vec![];
+                image::DynamicImage::ImageRgba8(layer.pixels)
+                    .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                    .map_err(|_| Error::IoError(io::ErrorKind::Other))?;
+                let path = folder.join(format!("{:02} {}.png", index + 1, file_name(&layer.name)));
+                std::fs::write(&path, &png).map_err(io_error)?;
+
+                Ok(PsdLayer {
+                    name: layer.name,
+                    bounds: layer.bounds,
+                    opacity: layer.opacity,
+                    path,
+                    contents: Arc::new(png),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok((size, layers))
+    })
+    .await
+    .map_err(|_| Error::IoError(io::ErrorKind::Other))?
+}
+
+/// `name` without the characters file systems don't allow in file names.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+struct Parsed {
+    name: String,
+    bounds: Rectangle,
+    opacity: f32,
+    pixels: image::RgbaImage,
+}
+
+/// A layer record, read before the channel data that follows all of them.
+struct Record {
+    top: i32,
+    left: i32,
+    width: u32,
+    height: u32,
+    /// The ID and length in bytes of each channel's data.
+    channels: Vec<(i16, usize)>,
+    opacity: u8,
+    is_hidden: bool,
+    name: String,
+    /// The kind of group marker this record is, if it is one: 1 and 2 open
+    /// a group, and 3 closes it.
+    section: u32,
+}
+
+/// The document size and its visible layers, bottom first, or `None` if the
+/// file isn't a PSD this can read.
+fn parse(file: &[u8]) -> Option<(Size, Vec<Parsed>)> {
+    let mut reader = Reader { file, position: 0 };
+
+    // The header: a signature, version 1 (2 is a large document, PSB),
+    // reserved bytes and the channel count.
+    if reader.bytes(4)? != b"8BPS" || reader.u16()? != 1 {
+        return None;
+    }
+    reader.skip(6 + 2)?;
+    let height = reader.u32()?;
+    let width = reader.u32()?;
+    let depth = reader.u16()?;
+    let mode = reader.u16()?;
+    // Bitmap, indexed and CMYK documents, and 16 and 32-bit ones, aren't
+    // read.
+    if depth != 8 || !matches!(mode, GRAYSCALE | RGB) {
+        return None;
+    }
+
+    // The color mode data and the image resources.
+    for _ in 0..2 {
+        let length = reader.u32()? as usize;
+        reader.skip(length)?;
+    }
+
+    let mut layers = vec![];
+    if reader.u32()? == 0 {
+        return Some((Size::new(width as f32, height as f32), layers));
+    }
+    let info_length = reader.u32()? as usize;
+    if info_length == 0 {
+        return Some((Size::new(width as f32, height as f32), layers));
+    }
+    // Negative when the first alpha channel holds the merged transparency.
+    let count = reader.u16()? as i16;
+
+    let records = (0..count.unsigned_abs())
+        .map(|_| read_record(&mut reader))
+        .collect::<Option<Vec<_>>>()?;
+
+    // Records go from the bottom up, with a group's marker above its
+    // layers, so groups are walked from the top down to know whether each
+    // layer is in a hidden one.
+    let mut is_visible = vec![false; records.len()];
+    let mut groups: Vec<bool> = vec![];
+    for (index, record) in records.iter().enumerate().rev() {
+        let is_in_visible_group = groups.iter().all(|visible| *visible);
+        match record.section {
+            1 | 2 => groups.push(!record.is_hidden),
+            3 => {
+                groups.pop();
+            }
+            _ => is_visible[index] = is_in_visible_group && !record.is_hidden,
+        }
+    }
+
+    'layers: for (record, is_visible) in records.iter().zip(is_visible) {
+        let start = reader.position;
+        let channels_length: usize = record.channels.iter().map(|(_, length)| length).sum();
+        reader.skip(channels_length)?;
+        if !is_visible || record.width == 0 || record.height == 0 || !fits(record) {
+            continue;
+        }
+
+        let (width, height) = (record.width, record.height);
+        let mut pixels = image::RgbaImage::from_pixel(width, height, image::Rgba([0, 0, 0, 255]));
+        let mut position = start;
+        for (id, length) in &record.channels {
+            let data = file.get(position..position + length)?;
+            position += length;
+            // -2 and -3 are masks, which are sized to their own bounds.
+            let offset = match (*id, mode) {
+                (-1, _) => 3,
+                (0, GRAYSCALE) => {
+                    let Some(gray) = decode_channel(data, width, height) else {
+                        continue 'layers;
+                    };
+                    for (pixel, value) in pixels.pixels_mut().zip(gray) {
+                        pixel.0[..3].fill(value);
+                    }
+                    continue;
+                }
+                (0..=2, RGB) => *id as usize,
+                _ => continue,
+            };
+            let Some(channel) = decode_channel(data, width, height) else {
+                continue 'layers;
+            };
+            for (pixel, value) in pixels.pixels_mut().zip(channel) {
+                pixel.0[offset] = value;
+            }
+        }
+
+        layers.push(Parsed {
+            name: record.name.clone(),
+            bounds: Rectangle::new(
+                iced::Point::new(record.left as f32, record.top as f32),
+                Size::new(width as f32, height as f32),
+            ),
+            opacity: record.opacity as f32 / 255.,
+            pixels,
+        });
+    }
+
+    Some((Size::new(width as f32, height as f32), layers))
+}
+
+const GRAYSCALE: u16 = 1;
+const RGB: u16 = 3;
+
+/// The most pixels a layer may have, as much as a 16384 by 16384 one.
+const MAX_PIXELS: u64 = 1 << 28;
+
+/// Whether the record's size is one its channel data could hold and that
+/// can be allocated. Run-length encoding packs at most 128 values into two
+/// bytes, so a channel can't stand for more than 64 values per byte.
+fn fits(record: &Record) -> bool {
+    let pixels = record.width as u64 * record.height as u64;
+    let longest = record
+        .channels
+        .iter()
+        .map(|(_, length)| *length as u64)
+        .max()
+        .unwrap_or(0);
+
+    pixels <= MAX_PIXELS && pixels <= longest.saturating_mul(64)
+}
+
+fn read_record(reader: &mut Reader) -> Option<Record> {
+    let top = reader.u32()? as i32;
+    let left = reader.u32()? as i32;
+    let bottom = reader.u32()? as i32;
+    let right = reader.u32()? as i32;
+    let channels = (0..reader.u16()?)
+        .map(|_| Some((reader.u16()? as i16, reader.u32()? as usize)))
+        .collect::<Option<Vec<_>>>()?;
+    if reader.bytes(4)? != b"8BIM" {
+        return None;
+    }
+    // The blend mode, then the opacity, clipping, flags and a filler byte.
+    reader.skip(4)?;
+    let opacity = reader.u8()?;
+    reader.skip(1)?;
+    let flags = reader.u8()?;
+    reader.skip(1)?;
+
+    let extra_length = reader.u32()? as usize;
+    let extra_end = reader.position + extra_length;
+    for _ in 0..2 {
+        // The layer mask and the blending ranges.
+        let length = reader.u32()? as usize;
+        reader.skip(length)?;
+    }
+    // A Pascal string padded to four bytes, replaced by the Unicode name
+    // below when there is one.
+    let name_length = reader.u8()? as usize;
+    let mut name = String::from_utf8_lossy(reader.bytes(name_length)?).into_owned();
+    reader.skip((4 - (name_length + 1) % 4) % 4)?;
+
+    let mut section = 0;
+    while reader.position + 12 <= extra_end {
+        let signature = reader.bytes(4)?;
+        if signature != b"8BIM" && signature != b"8B64" {
+            break;
+        }
+        let key = reader.bytes(4)?;
+        let length = reader.u32()? as usize;
+        let data = reader.bytes(length)?;
+        match key {
+            b"luni" => {
+                let count = data
+                    .get(..4)
+                    .map(|count| u32::from_be_bytes([count[0], count[1], count[2], count[3]]))?;
+                let units: Vec<u16> = data
+                    .get(4..)?
+                    .chunks_exact(2)
+                    .take(count as usize)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect();
+                name = String::from_utf16_lossy(&units);
+            }
+            b"lsct" | b"lsdk" => {
+                section = data
+                    .get(..4)
+                    .map(|kind| u32::from_be_bytes([kind[0], kind[1], kind[2], kind[3]]))?;
+            }
+            _ => {}
+        }
+    }
+    reader.position = extra_end;
+
+    Some(Record {
+        top,
+        left,
+        width: right.checked_sub(left)?.max(0) as u32,
+        height: bottom.checked_sub(top)?.max(0) as u32,
+        channels,
+        opacity,
+        is_hidden: flags & 0x02 != 0,
+        name: name.trim_end_matches('\0').to_string(),
+        section,
+    })
+}
+
+/// A channel's `width` by `height` values, stored raw or run-length
+/// encoded. Zip-compressed channels aren't read, and their layers are left
+/// out.
+fn decode_channel(data: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    let (width, height) = (width as usize, height as usize);
+    let (compression, data) = (
+        u16::from_be_bytes([*data.first()?, *data.get(1)?]),
+        &data[2..],
+    );
+
+    match compression {
+        0 => Some(data.get(..width * height)?.to_vec()),
+        1 => {
+            // The byte count of each row, then the rows in PackBits.
+            let mut rows = data.get(height * 2..)?;
+            let mut values = Vec::with_capacity(width * height);
+            for row in 0..height {
+                let length = u16::from_be_bytes([data[row * 2], data[row * 2 + 1]]) as usize;
+                let (mut packed, rest) = (rows.get(..length)?, &rows[length..]);
+                rows = rest;
+
+                let row_end = values.len() + width;
+                while let Some((&header, rest)) = packed.split_first() {
+                    let header = header as i8;
+                    if header >= 0 {
+                        let count = header as usize + 1;
+                        values.extend_from_slice(rest.get(..count)?);
+                        packed = &rest[count..];
+                    } else if header != -128 {
+                        let count = 1 - header as isize;
+                        values.extend(std::iter::repeat_n(*rest.first()?, count as usize));
+                        packed = &rest[1..];
+                    } else {
+                        packed = rest;
+                    }
+                }
+                values.resize(row_end, 0);
+            }
+
+            Some(values)
+        }
+        _ => None,
+    }
+}
+
+struct Reader<'a> {
+    file: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Option<&'a [u8]> {
+        let bytes = self
+            .file
+            .get(self.position..self.position.checked_add(count)?)?;
+        self.position += count;
+
+        Some(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Option<()> {
+        self.bytes(count).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.bytes(2)?;
+
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.bytes(4)?;
+
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// Where a layer at `bounds` in a document of `document` size goes on a
+/// canvas of `canvas` size, with the document fit into the canvas. `None`
+/// for a document with no area, which can't be fit.
+pub fn place(bounds: Rectangle, document: Size, canvas: Size) -> Option<Rectangle> {
+    if document.width <= 0. || document.height <= 0. {
+        return None;
+    }
+    let scale = (canvas.width / document.width).min(canvas.height / document.height);
+    let offset = (canvas - document * scale) * 0.5;
+
+    Some(Rectangle::new(
+        iced::Point::new(
+            offset.width + bounds.x * scale,
+            offset.height + bounds.y * scale,
+        ),
+        bounds.size() * scale,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A layer record to write into a test document.
+    struct Layer {
+        name: &'static str,
+        /// Top, left, bottom and right.
+        bounds: [i32; 4],
+        opacity: u8,
+        is_hidden: bool,
+        section: Option<u32>,
+        /// Each channel's ID and data, with its compression.
+        channels: Vec<(i16, Vec<u8>)>,
+    }
+
+    impl Layer {
+        fn new(name: &'static str, bounds: [i32; 4], channels: Vec<(i16, Vec<u8>)>) -> Layer {
+            Layer {
+                name,
+                bounds,
+                opacity: 255,
+                is_hidden: false,
+                section: None,
+                channels,
+            }
+        }
+
+        fn group(name: &'static str, section: u32, is_hidden: bool) -> Layer {
+            Layer {
+                is_hidden,
+                section: Some(section),
+                ..Layer::new(name, [0; 4], vec![])
+            }
+        }
+    }
+
+    /// A raw channel holding `values`.
+    fn raw(values: &[u8]) -> Vec<u8> {
+        [&[0, 0], values].concat()
+    }
+
+    /// A Photoshop file with `layers`, bottom first.
+    fn document(mode: u16, depth: u16, width: u32, height: u32, layers: &[Layer]) -> Vec<u8> {
+        let mut records = (layers.len() as u16).to_be_bytes().to_vec();
+        let mut channel_data: Vec<u8> = vec![];
+        for layer in layers {
+            for value in layer.bounds {
+                records.extend(value.to_be_bytes());
+            }
+            records.extend((layer.channels.len() as u16).to_be_bytes());
+            for (id, data) in &layer.channels {
+                records.extend(id.to_be_bytes());
+                records.extend((data.len() as u32).to_be_bytes());
+                channel_data.extend(data);
+            }
+            records.extend(b"8BIMnorm");
+            records.extend([layer.opacity, 0, (layer.is_hidden as u8) << 1, 0]);
+
+            let mut extra = vec![0; 8];
+            extra.push(layer.name.len() as u8);
+            extra.extend(layer.name.as_bytes());
+            extra.resize(extra.len().next_multiple_of(4), 0);
+            if let Some(section) = layer.section {
+                extra.extend(b"8BIMlsct");
+                extra.extend(4u32.to_be_bytes());
+                extra.extend(section.to_be_bytes());
+            }
+            records.extend((extra.len() as u32).to_be_bytes());
+            records.extend(extra);
+        }
+        records.extend(channel_data);
+
+        let mut file = b"8BPS".to_vec();
+        file.extend(1u16.to_be_bytes());
+        file.extend([0; 6]);
+        file.extend(3u16.to_be_bytes());
+        file.extend(height.to_be_bytes());
+        file.extend(width.to_be_bytes());
+        file.extend(depth.to_be_bytes());
+        file.extend(mode.to_be_bytes());
+        file.extend([0; 8]);
+        file.extend((records.len() as u32 + 4).to_be_bytes());
+        file.extend((records.len() as u32).to_be_bytes());
+        file.extend(records);
+
+        file
+    }
+
+    #[test]
+    fn raw_channels() {
+        assert_eq!(
+            decode_channel(&raw(&[1, 2, 3, 4]), 2, 2),
+            Some(vec![1, 2, 3, 4])
+        );
+        assert_eq!(decode_channel(&raw(&[1, 2, 3]), 2, 2), None);
+    }
+
+    #[test]
+    fn run_length_encoded_channels() {
+        // A row of three literal bytes, then a row of one byte repeated
+        // three times.
+        let data = [&[0, 1, 0, 4, 0, 2][..], &[2, 7, 8, 9], &[0xfe, 5]].concat();
+
+        assert_eq!(decode_channel(&data, 3, 2), Some(vec![7, 8, 9, 5, 5, 5]));
+    }
+
+    #[test]
+    fn zip_channels_are_not_read() {
+        assert_eq!(decode_channel(&[0, 2, 0, 0], 1, 1), None);
+    }
+
+    #[test]
+    fn rgb_layers() {
+        let layer = Layer {
+            opacity: 128,
+            ..Layer::new(
+                "Red",
+                [1, 2, 2, 4],
+                vec![
+                    (0, raw(&[255, 255])),
+                    (1, raw(&[0, 0])),
+                    (2, raw(&[0, 10])),
+                    (-1, raw(&[255, 0])),
+                ],
+            )
+        };
+        let (size, layers) = parse(&document(RGB, 8, 4, 3, &[layer])).unwrap();
+
+        assert_eq!(size, Size::new(4., 3.));
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].name, "Red");
+        assert_eq!(
+            layers[0].bounds,
+            Rectangle::new(iced::Point::new(2., 1.), Size::new(2., 1.))
+        );
+        assert_eq!(layers[0].opacity, 128. / 255.);
+        assert_eq!(layers[0].pixels.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(layers[0].pixels.get_pixel(1, 0).0, [255, 0, 10, 0]);
+    }
+
+    #[test]
+    fn grayscale_layers() {
+        let layer = Layer::new("Gray", [0, 0, 1, 1], vec![(0, raw(&[100]))]);
+        let (_, layers) = parse(&document(GRAYSCALE, 8, 1, 1, &[layer])).unwrap();
+
+        assert_eq!(layers[0].pixels.get_pixel(0, 0).0, [100, 100, 100, 255]);
+    }
+
+    #[test]
+    fn hidden_layers_and_groups() {
+        let pixel = || vec![(0, raw(&[0])), (1, raw(&[0])), (2, raw(&[0]))];
+        let layers = [
+            Layer::new("Background", [0, 0, 1, 1], pixel()),
+            // A hidden group, from its closing marker to its opening one.
+            Layer::group("</Hidden>", 3, false),
+            Layer::new("In hidden group", [0, 0, 1, 1], pixel()),
+            Layer::group("Hidden", 1, true),
+            Layer {
+                is_hidden: true,
+                ..Layer::new("Hidden", [0, 0, 1, 1], pixel())
+            },
+            Layer::group("</Shown>", 3, false),
+            Layer::new("In shown group", [0, 0, 1, 1], pixel()),
+            Layer::group("Shown", 2, false),
+            Layer::new("Empty", [0, 0, 0, 0], vec![]),
+        ];
+        let (_, layers) = parse(&document(RGB, 8, 1, 1, &layers)).unwrap();
+        let names: Vec<_> = layers.iter().map(|layer| layer.name.as_str()).collect();
+
+        assert_eq!(names, ["Background", "In shown group"]);
+    }
+
+    #[test]
+    fn oversized_layers() {
+        // Claims a million pixels over a single byte of data.
+        let huge = Layer::new("Huge", [0, 0, 1000, 1000], vec![(0, raw(&[0]))]);
+        let (_, layers) = parse(&document(RGB, 8, 1, 1, &[huge])).unwrap();
+        assert!(layers.is_empty());
+
+        let overflowing = Layer::new("Overflowing", [0, i32::MIN, 1, i32::MAX], vec![]);
+        assert!(parse(&document(RGB, 8, 1, 1, &[overflowing])).is_none());
+    }
+
+    #[test]
+    fn unsupported_documents() {
+        assert!(parse(&document(RGB, 16, 1, 1, &[])).is_none());
+        // CMYK.
+        assert!(parse(&document(4, 8, 1, 1, &[])).is_none());
+        assert!(parse(b"GIF89a").is_none());
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(file_name(" Logo: final/v2? "), "Logo_ final_v2_");
+    }
+
+    #[test]
+    fn placing_layers() {
+        // A square document fit into a wide canvas, centered with space at
+        // its sides.
+        let placed = place(
+            Rectangle::new(iced::Point::new(10., 20.), Size::new(50., 50.)),
+            Size::new(100., 100.),
+            Size::new(400., 200.),
+        );
+
+        assert_eq!(
+            placed,
+            Some(Rectangle::new(
+                iced::Point::new(120., 40.),
+                Size::new(100., 100.)
+            ))
+        );
+        assert_eq!(
+            place(placed.unwrap(), Size::new(0., 100.), Size::new(400., 200.)),
+            None
+        );
+    }
+}
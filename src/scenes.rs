@@ -0,0 +1,143 @@
+use std::{fmt::Display, time::Duration};
+
+use iced::Rectangle;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::{Motion, Timing, Transition, TransitionKind};
+
+/// A part of the video with its own layers, shown from its start until the
+/// next scene's. Layers in no scene are shown in every one of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub name: String,
+    /// Seconds into the track at which the scene is switched to.
+    pub start: f32,
+    /// How the scene replaces the one before it.
+    pub transition: Transition,
+}
+
+impl Display for Scene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Seconds into the track at which the layers of the scene at `index`
+/// appear, and disappear if another scene follows. Each transition is
+/// centered on the switch, so that both scenes are drawn while one
+/// replaces the other.
+pub fn span(scenes: &[Scene], index: usize) -> Option<(f32, Option<f32>)> {
+    let scene = scenes.get(index)?;
+    let end = scenes
+        .get(index + 1)
+        .map(|next| next.start + overlap(next.transition));
+
+    Some((scene.start - overlap(scene.transition), end))
+}
+
+/// The transitions the layers of the scene at `index` enter and leave with.
+pub fn transitions(scenes: &[Scene], index: usize) -> impl Iterator<Item = Transition> + '_ {
+    scenes
+        .get(index..)
+        .unwrap_or_default()
+        .iter()
+        .take(2)
+        .map(|scene| scene.transition)
+}
+
+/// Whether the layers of the scene at `index` are shown at `time`. Those of
+/// a scene that was removed are shown throughout, as if in no scene.
+pub fn is_shown(scenes: &[Scene], index: usize, time: Duration) -> bool {
+    span(scenes, index).is_none_or(|span| timing(span, Transition::NONE).is_visible(time))
+}
+
+/// How the layers of the scene at `index` are moved while it is switched to
+/// or away from at `time`.
+pub fn motion(scenes: &[Scene], index: usize, time: Duration, bounds: Rectangle) -> Motion {
+    let Some((start, end)) = span(scenes, index) else {
+        return Motion::NONE;
+    };
+    let mut transitions = transitions(scenes, index);
+    let entering = timing((start, None), transitions.next().unwrap_or_default());
+    let leaving = timing(
+        (f32::NEG_INFINITY, end),
+        transitions.next().unwrap_or_default(),
+    );
+
+    entering
+        .motion(time, bounds)
+        .and(leaving.motion(time, bounds))
+}
+
+/// How far a scene switched to with `transition` overlaps the one before.
+fn overlap(transition: Transition) -> f32 {
+    match transition.kind {
+        TransitionKind::None => 0.,
+        _ => transition.duration.max(0.) / 2.,
+    }
+}
+
+fn timing((start, end): (f32, Option<f32>), transition: Transition) -> Timing {
+    Timing {
+        start,
+        end,
+        transition,
+        ..Timing::ALWAYS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::{Point, Size};
+
+    use super::*;
+    use crate::animation::Easing;
+
+    fn scene(start: f32, kind: TransitionKind) -> Scene {
+        Scene {
+            name: String::new(),
+            start,
+            transition: Transition {
+                kind,
+                duration: 2.,
+                easing: Easing::Linear,
+            },
+        }
+    }
+
+    fn at(seconds: f32) -> Duration {
+        Duration::from_secs_f32(seconds)
+    }
+
+    #[test]
+    fn scenes_overlap_by_their_transitions() {
+        let scenes = [
+            scene(0., TransitionKind::None),
+            scene(10., TransitionKind::Crossfade),
+            scene(20., TransitionKind::None),
+        ];
+
+        assert_eq!(span(&scenes, 0), Some((0., Some(11.))));
+        assert_eq!(span(&scenes, 1), Some((9., Some(20.))));
+        assert_eq!(span(&scenes, 2), Some((20., None)));
+        assert_eq!(span(&scenes, 3), None);
+        assert!(is_shown(&scenes, 0, at(10.5)) && is_shown(&scenes, 1, at(10.5)));
+        assert!(!is_shown(&scenes, 1, at(20.)));
+        assert!(is_shown(&scenes, 3, at(20.)));
+    }
+
+    #[test]
+    fn crossfades_fade_one_scene_into_the_next() {
+        let scenes = [
+            scene(0., TransitionKind::None),
+            scene(10., TransitionKind::Crossfade),
+        ];
+        let bounds = Rectangle::new(Point::ORIGIN, Size::new(100., 100.));
+        let opacity = |index, seconds| motion(&scenes, index, at(seconds), bounds).opacity;
+
+        assert_eq!(opacity(0, 5.), 1.);
+        assert_eq!(opacity(0, 10.), 0.5);
+        assert_eq!(opacity(1, 10.), 0.5);
+        assert_eq!(opacity(1, 15.), 1.);
+    }
+}
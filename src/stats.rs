@@ -0,0 +1,40 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Timings and counters shown by the performance overlay, to tell what makes
+/// a composition slow.
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// When each tick of the last second happened.
+    ticks: VecDeque<Instant>,
+    /// Time spent catching the canvas up with the playhead on the last tick.
+    pub update_time: Duration,
+    /// Layers that were visible on the last tick.
+    pub layers_drawn: usize,
+    /// Visible layers that looked different than on the tick before, and so
+    /// could not be drawn from their cache.
+    pub layers_redrawn: usize,
+    /// How long the last audio analysis took, including reading it from the
+    /// analysis cache.
+    pub analysis_time: Option<Duration>,
+}
+
+impl Stats {
+    pub fn tick(&mut self, now: Instant) {
+        self.ticks.push_back(now);
+        while self
+            .ticks
+            .front()
+            .is_some_and(|tick| now.duration_since(*tick) > Duration::from_secs(1))
+        {
+            self.ticks.pop_front();
+        }
+    }
+
+    /// Ticks in the last second, which is how often the preview is redrawn.
+    pub fn fps(&self) -> usize {
+        self.ticks.len()
+    }
+}
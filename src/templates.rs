@@ -0,0 +1,238 @@
+use std::{borrow::Cow, path::PathBuf};
+
+use iced::Size;
+
+use crate::{
+    animation::{Animation, Playback, Timing},
+    audio::{Channel, Source, Trim},
+    canvas::{Appearance, Crop, Flip, Orientation, Sizing, Units, Visibility},
+    fonts::{FontWeight, TextFont},
+    i18n::t,
+    keyframes::Keyframes,
+    post_fx::PostFx,
+    procedural::{Procedural, Progress, Text, Wave},
+    project::{FrameRate, LayerSource, Project, ProjectLayer},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    Blank,
+    MusicVisualizer,
+    PodcastAudiogram,
+    LyricVideo,
+    VerticalReel,
+}
+
+impl Template {
+    pub const ALL: [Template; 5] = [
+        Template::Blank,
+        Template::MusicVisualizer,
+        Template::PodcastAudiogram,
+        Template::LyricVideo,
+        Template::VerticalReel,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Template::Blank => t("template-blank"),
+            Template::MusicVisualizer => t("template-music-visualizer"),
+            Template::PodcastAudiogram => t("template-podcast-audiogram"),
+            Template::LyricVideo => t("template-lyric-video"),
+            Template::VerticalReel => t("template-vertical-reel"),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Template::Blank => t("template-blank-description"),
+            Template::MusicVisualizer => t("template-music-visualizer-description"),
+            Template::PodcastAudiogram => t("template-podcast-audiogram-description"),
+            Template::LyricVideo => t("template-lyric-video-description"),
+            Template::VerticalReel => t("template-vertical-reel-description"),
+        }
+    }
+
+    pub fn canvas_size(self) -> (f32, f32) {
+        match self {
+            Template::Blank => (1280., 720.),
+            Template::MusicVisualizer | Template::LyricVideo => (1920., 1080.),
+            Template::PodcastAudiogram => (1080., 1080.),
+            Template::VerticalReel => (1080., 1920.),
+        }
+    }
+
+    pub fn project(self) -> Project {
+        let (canvas_width, canvas_height) = self.canvas_size();
+        let background = || {
+            placeholder(
+                t("template-layer-background"),
+                0.,
+                0.,
+                canvas_width,
+                canvas_height,
+            )
+        };
+
+        let layers = match self {
+            Template::Blank => vec![],
+            Template::MusicVisualizer => vec![
+                background(),
+                placeholder(t("template-layer-album-art"), 660., 240., 600., 600.),
+            ],
+            Template::PodcastAudiogram => vec![
+                background(),
+                placeholder(t("template-layer-cover-art"), 240., 120., 600., 600.),
+                placeholder(t("template-layer-show-logo"), 840., 880., 160., 160.),
+            ],
+            Template::LyricVideo => vec![background()],
+            Template::VerticalReel => {
+                vec![
+                    background(),
+                    placeholder(t("template-layer-artwork"), 90., 510., 900., 900.),
+                ]
+            }
+        };
+
+        project(canvas_width, canvas_height, None, layers)
+    }
+}
+
+/// A project for a podcast episode in `orientation`: the cover, the title, a
+/// waveform and a progress bar over the episode's audio. The cover is left
+/// to be picked when there is none.
+pub fn audiogram(
+    orientation: Orientation,
+    audio: PathBuf,
+    cover: Option<PathBuf>,
+    title: &str,
+) -> Project {
+    let size = orientation.size(Size::new(1080., 1080.));
+    // Where each layer goes, as x, y, width and height.
+    let [cover_area, title_area, wave_area, progress_area] = match orientation {
+        Orientation::Landscape => [
+            [120., 240., 600., 600.],
+            [800., 300., 1000., 160.],
+            [800., 520., 1000., 200.],
+            [800., 800., 1000., 14.],
+        ],
+        Orientation::Portrait => [
+            [140., 260., 800., 800.],
+            [60., 1120., 960., 160.],
+            [60., 1340., 960., 240.],
+            [60., 1700., 960., 14.],
+        ],
+        Orientation::Square => [
+            [300., 100., 480., 480.],
+            [60., 620., 960., 120.],
+            [60., 770., 960., 160.],
+            [60., 990., 960., 12.],
+        ],
+    };
+    let at = |name: &str, source: LayerSource, [x, y, width, height]: [f32; 4]| {
+        layer(name, source, x, y, width, height)
+    };
+
+    let cover = match cover {
+        Some(path) => at(
+            t("template-layer-cover-art"),
+            LayerSource::Image(path),
+            cover_area,
+        ),
+        None => at(
+            t("template-layer-cover-art"),
+            LayerSource::Placeholder,
+            cover_area,
+        ),
+    };
+    let title = at(
+        t("template-layer-title"),
+        LayerSource::Procedural(Procedural::Text(Text {
+            content: Cow::Owned(title.to_string()),
+            size: if orientation == Orientation::Square {
+                56.
+            } else {
+                72.
+            },
+            font: TextFont {
+                weight: FontWeight::Bold,
+                ..TextFont::DEFAULT
+            },
+            ..Text::DEFAULT
+        })),
+        title_area,
+    );
+    let wave = at(
+        t("procedural-wave"),
+        LayerSource::Procedural(Procedural::Wave(Wave::DEFAULT)),
+        wave_area,
+    );
+    let progress = at(
+        t("procedural-progress"),
+        LayerSource::Procedural(Procedural::Progress(Progress::DEFAULT)),
+        progress_area,
+    );
+
+    project(
+        size.width,
+        size.height,
+        Some(audio),
+        vec![cover, title, wave, progress],
+    )
+}
+
+fn project(
+    canvas_width: f32,
+    canvas_height: f32,
+    audio: Option<PathBuf>,
+    layers: Vec<ProjectLayer>,
+) -> Project {
+    Project {
+        canvas_width,
+        canvas_height,
+        audio,
+        layers,
+        frame_rate: FrameRate::default(),
+        audio_trim: Trim::NONE,
+        audio_tracks: vec![],
+        links: vec![],
+        expressions: vec![],
+        fonts: vec![],
+        swatches: vec![],
+        swatch_uses: vec![],
+        markers: vec![],
+        scenes: vec![],
+        snapshots: vec![],
+        post_fx: PostFx::NONE,
+    }
+}
+
+fn placeholder(name: &str, x: f32, y: f32, width: f32, height: f32) -> ProjectLayer {
+    layer(name, LayerSource::Placeholder, x, y, width, height)
+}
+
+fn layer(name: &str, source: LayerSource, x: f32, y: f32, width: f32, height: f32) -> ProjectLayer {
+    ProjectLayer {
+        name: name.to_string(),
+        source,
+        x,
+        y,
+        width,
+        height,
+        scale: 1.,
+        opacity: 1.,
+        sizing: Sizing::default(),
+        crop: Crop::FULL,
+        flip: Flip::NONE,
+        lut: None,
+        appearance: Appearance::default(),
+        animation: Animation::NONE,
+        timing: Timing::ALWAYS,
+        playback: Playback::NORMAL,
+        keyframes: Keyframes::default(),
+        audio_channel: Channel::default(),
+        audio_source: Source::default(),
+        units: Units::default(),
+        is_aspect_locked: false,
+        visibility: Visibility::Everywhere,
+    }
+}
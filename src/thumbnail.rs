@@ -0,0 +1,82 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+use iced::Size;
+
+use crate::{
+    directories::{self, DialogKind},
+    i18n::t,
+    Error,
+};
+
+/// The resolution a thumbnail is rendered at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThumbnailSize {
+    /// As large as the canvas.
+    #[default]
+    Canvas,
+    YouTube,
+    Square,
+    Vertical,
+}
+
+impl ThumbnailSize {
+    pub const ALL: [ThumbnailSize; 4] = [
+        ThumbnailSize::Canvas,
+        ThumbnailSize::YouTube,
+        ThumbnailSize::Square,
+        ThumbnailSize::Vertical,
+    ];
+
+    pub fn size(self, canvas: Size) -> Size<u32> {
+        match self {
+            ThumbnailSize::Canvas => Size::new(canvas.width as u32, canvas.height as u32),
+            ThumbnailSize::YouTube => Size::new(1280, 720),
+            ThumbnailSize::Square => Size::new(1080, 1080),
+            ThumbnailSize::Vertical => Size::new(1080, 1920),
+        }
+    }
+}
+
+impl Display for ThumbnailSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ThumbnailSize::Canvas => t("thumbnail-canvas-size"),
+            ThumbnailSize::YouTube => "1280 × 720 (YouTube)",
+            ThumbnailSize::Square => "1080 × 1080",
+            ThumbnailSize::Vertical => "1080 × 1920",
+        })
+    }
+}
+
+/// Asks where to save `image`, then writes it as a PNG or JPEG, by the
+/// extension picked.
+pub async fn save(image: image::RgbaImage) -> Result<PathBuf, Error> {
+    let path: PathBuf = directories::dialog(DialogKind::Export)
+        .set_title(t("thumbnail-save-dialog"))
+        .add_filter(t("filter-image"), &["png", "jpg", "jpeg"])
+        .set_file_name("thumbnail.png")
+        .save_file()
+        .await
+        .ok_or(Error::DialogClosed)?
+        .into();
+    directories::remember(DialogKind::Export, &path).await;
+
+    let saved = path.clone();
+    tokio::task::spawn_blocking(move || {
+        let image = image::DynamicImage::ImageRgba8(image);
+        let is_jpeg = saved.extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("jpg") || extension.eq_ignore_ascii_case("jpeg")
+        });
+        let saved_image = if is_jpeg {
+            image::DynamicImage::ImageRgb8(image.into_rgb8()).save(&saved)
+        } else {
+            image.save_with_format(&saved, image::ImageFormat::Png)
+        };
+
+        saved_image.map_err(|_| Error::IoError(io::ErrorKind::Other))
+    })
+    .await
+    .map_err(|_| Error::IoError(io::ErrorKind::Other))??;
+
+    Ok(path)
+}
@@ -0,0 +1,111 @@
+use std::time::{Duration, Instant};
+
+use iced::{
+    border,
+    widget::{button, column, container, text},
+    Element, Length, Theme,
+};
+
+use crate::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Error,
+}
+
+#[derive(Debug)]
+struct Toast {
+    id: u64,
+    kind: ToastKind,
+    message: String,
+    shown: Instant,
+}
+
+/// Short messages shown in the corner of the window, e.g. when a file could
+/// not be opened. They go away on their own, or when clicked.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    /// Oldest first.
+    toasts: Vec<Toast>,
+    next_id: u64,
+}
+
+impl Toasts {
+    /// Toasts shown at most, after which the oldest are dropped.
+    const LIMIT: usize = 5;
+
+    pub fn push(&mut self, kind: ToastKind, message: impl Into<String>) {
+        if self.toasts.len() == Self::LIMIT {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            id: self.next_id,
+            kind,
+            message: message.into(),
+            shown: Instant::now(),
+        });
+        self.next_id += 1;
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    /// Drops the toasts that have been shown for long enough.
+    pub fn expire(&mut self, now: Instant) {
+        self.toasts
+            .retain(|toast| now.duration_since(toast.shown) < timeout(toast.kind));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let toasts = self
+            .toasts
+            .iter()
+            .fold(column![].spacing(6.), |column, toast| {
+                let kind = toast.kind;
+
+                column.push(
+                    button(text(&toast.message))
+                        .width(Length::Fill)
+                        .padding([8., 10.])
+                        .style(move |theme: &Theme, _status| {
+                            let palette = theme.extended_palette();
+                            let accent = match kind {
+                                ToastKind::Info => palette.primary.base.color,
+                                ToastKind::Success => palette.success.base.color,
+                                ToastKind::Error => palette.danger.base.color,
+                            };
+
+                            button::Style {
+                                background: Some(palette.background.weak.color.into()),
+                                text_color: palette.background.weak.text,
+                                border: border::rounded(4.).color(accent).width(1.),
+                                ..button::Style::default()
+                            }
+                        })
+                        .on_press(Message::DismissToast(toast.id)),
+                )
+            });
+
+        container(container(toasts).width(320.))
+            .align_right(Length::Fill)
+            .align_bottom(Length::Fill)
+            .padding(12.)
+            .into()
+    }
+}
+
+/// How long a toast stays up unless clicked away. Errors stay up longer, as
+/// they usually need reading.
+fn timeout(kind: ToastKind) -> Duration {
+    match kind {
+        ToastKind::Info | ToastKind::Success => Duration::from_secs(4),
+        ToastKind::Error => Duration::from_secs(10),
+    }
+}
@@ -0,0 +1,428 @@
+use std::{f32::consts::PI, fmt::Display, sync::PoisonError};
+
+use iced::{
+    advanced::graphics::text::{cosmic_text, font_system, to_attributes},
+    font, Color, Font, Size, Vector,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{animation::Easing, canvas::ColorDef, i18n::t};
+
+/// How the lines of a text layer are laid out and decorated, beyond what
+/// iced's canvas text offers.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextStyle {
+    /// Space added between letters, in canvas pixels. Negative values bring
+    /// them closer.
+    pub letter_spacing: f32,
+    /// Distance between lines, as a multiple of the text's size.
+    pub line_height: f32,
+    pub alignment: TextAlignment,
+    /// Width of the outline around the letters. Zero for none.
+    pub outline_width: f32,
+    #[serde(with = "ColorDef")]
+    pub outline_color: Color,
+    /// Whether a box is drawn behind the text, e.g. to keep captions
+    /// readable over busy artwork.
+    pub has_background: bool,
+    #[serde(with = "ColorDef")]
+    pub background_color: Color,
+    /// Space between the text and the edges of the box.
+    pub background_padding: f32,
+}
+
+impl TextStyle {
+    pub const DEFAULT: TextStyle = TextStyle {
+        letter_spacing: 0.,
+        line_height: 1.2,
+        alignment: TextAlignment::Center,
+        outline_width: 0.,
+        outline_color: Color::BLACK,
+        has_background: false,
+        background_color: Color::from_rgba(0., 0., 0., 0.6),
+        background_padding: 16.,
+    };
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle::DEFAULT
+    }
+}
+
+/// How lines of different lengths line up with each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextAlignment {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+impl TextAlignment {
+    pub const ALL: [TextAlignment; 3] = [
+        TextAlignment::Left,
+        TextAlignment::Center,
+        TextAlignment::Right,
+    ];
+}
+
+impl Display for TextAlignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TextAlignment::Left => t("align-left"),
+            TextAlignment::Center => t("align-center"),
+            TextAlignment::Right => t("align-right"),
+        })
+    }
+}
+
+/// A ready-made motion played out letter by letter or word by word.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextAnimation {
+    pub preset: TextPreset,
+    pub unit: TextUnit,
+    /// Seconds after the layer appears before the first letter or word
+    /// starts.
+    pub delay: f32,
+    /// Seconds between one letter or word starting and the next.
+    pub stagger: f32,
+    /// Seconds each letter or word takes to fade in or bounce.
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl TextAnimation {
+    pub const NONE: TextAnimation = TextAnimation {
+        preset: TextPreset::None,
+        unit: TextUnit::Character,
+        delay: 0.,
+        stagger: 0.05,
+        duration: 0.4,
+        easing: Easing::EaseOut,
+    };
+
+    /// What the text is split into to be animated, if it is.
+    pub fn unit(&self) -> Option<TextUnit> {
+        (self.preset != TextPreset::None).then_some(self.unit)
+    }
+
+    /// How visible the letter or word at `index` is, and how far it is
+    /// lifted as a fraction of the text's size, `since_start` seconds after
+    /// the layer appears.
+    pub fn state(&self, index: usize, since_start: f32, since_beat: Option<f32>) -> (f32, f32) {
+        let offset = self.stagger * index as f32;
+        let progress = |elapsed: f32| {
+            let t = elapsed / self.duration.max(0.01);
+
+            self.easing.apply(t.clamp(0., 1.))
+        };
+
+        match self.preset {
+            TextPreset::None => (1., 0.),
+            TextPreset::Typewriter => {
+                let is_typed = since_start >= self.delay + offset;
+
+                (if is_typed { 1. } else { 0. }, 0.)
+            }
+            TextPreset::Fade => (progress(since_start - self.delay - offset), 0.),
+            TextPreset::Bounce => {
+                let lift = since_beat
+                    .filter(|_| since_start >= self.delay)
+                    .map(|since| since - offset)
+                    .filter(|elapsed| (0.0..self.duration).contains(elapsed))
+                    .map_or(0., |elapsed| (progress(elapsed) * PI).sin() * 0.3);
+
+                (1., lift)
+            }
+        }
+    }
+}
+
+impl Default for TextAnimation {
+    fn default() -> Self {
+        TextAnimation::NONE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextPreset {
+    #[default]
+    None,
+    /// Shows the text one letter or word at a time.
+    Typewriter,
+    /// Fades each letter or word in after the one before.
+    Fade,
+    /// Hops the letters or words up on every beat, in a wave.
+    Bounce,
+}
+
+impl TextPreset {
+    pub const ALL: [TextPreset; 4] = [
+        TextPreset::None,
+        TextPreset::Typewriter,
+        TextPreset::Fade,
+        TextPreset::Bounce,
+    ];
+}
+
+impl Display for TextPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TextPreset::None => t("text-animation-none"),
+            TextPreset::Typewriter => t("text-animation-typewriter"),
+            TextPreset::Fade => t("text-animation-fade"),
+            TextPreset::Bounce => t("text-animation-bounce"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextUnit {
+    #[default]
+    Character,
+    Word,
+}
+
+impl TextUnit {
+    pub const ALL: [TextUnit; 2] = [TextUnit::Character, TextUnit::Word];
+}
+
+impl Display for TextUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TextUnit::Character => t("text-unit-letter"),
+            TextUnit::Word => t("text-unit-word"),
+        })
+    }
+}
+
+/// A run of text drawn in one piece.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub text: String,
+    /// Where the top left corner of the segment's line box goes, from the top
+    /// left corner of the whole text.
+    pub offset: Vector,
+    /// How far the baseline is from the top of the whole text.
+    pub baseline: f32,
+    pub font: Font,
+    /// Which letter or word the segment is, when the text is split up to be
+    /// animated.
+    pub index: usize,
+}
+
+/// Text laid out into segments that can each be drawn as canvas text.
+#[derive(Debug, Clone)]
+pub struct Typeset {
+    pub segments: Vec<Segment>,
+    pub size: Size,
+    /// The height of a line in pixels, which segments have to be drawn with
+    /// to land on their baselines.
+    pub line_height: f32,
+}
+
+/// Lays out `content` with the given style, in segments of at most one `unit`
+/// each. Words wrapped in `**` are set in bold and those wrapped in `*` in
+/// italics.
+pub fn typeset(
+    content: &str,
+    font: Font,
+    size: f32,
+    style: &TextStyle,
+    unit: Option<TextUnit>,
+) -> Typeset {
+    let spans = spans(content);
+    let fonts: Vec<Font> = spans
+        .iter()
+        .map(|span| Font {
+            weight: if span.is_bold {
+                font::Weight::Bold
+            } else {
+                font.weight
+            },
+            style: if span.is_italic {
+                font::Style::Italic
+            } else {
+                font.style
+            },
+            ..font
+        })
+        .collect();
+    let line_height = size * style.line_height.max(0.1);
+
+    let mut font_system = font_system()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    let font_system = font_system.raw();
+    let mut buffer = cosmic_text::Buffer::new(
+        font_system,
+        cosmic_text::Metrics::new(size.max(1.), line_height.max(1.)),
+    );
+    buffer.set_size(font_system, None, None);
+    buffer.set_rich_text(
+        font_system,
+        spans
+            .iter()
+            .zip(&fonts)
+            .enumerate()
+            .map(|(index, (span, font))| {
+                (span.text.as_str(), to_attributes(*font).metadata(index))
+            }),
+        to_attributes(font),
+        cosmic_text::Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(font_system, false);
+
+    // Glyphs are drawn together unless letters are spaced out or animated
+    // one by one, so they are shaped as a whole, e.g. keeping ligatures.
+    let (mut characters, mut words) = (0, 0);
+
+    let mut lines = vec![];
+    for run in buffer.layout_runs() {
+        let mut segments: Vec<(usize, usize, usize, f32, usize)> = vec![];
+        let mut is_in_word = false;
+        // Gaps between characters on the line, each widened by the spacing.
+        let mut gaps = 0;
+        let mut previous: Option<(usize, usize, bool)> = None;
+
+        for glyph in run.glyphs {
+            let text = &run.text[glyph.start..glyph.end];
+            let is_space = text.chars().all(char::is_whitespace);
+            let is_cursive = text.chars().any(is_cursive);
+            // A character can be drawn as several glyphs, e.g. with combining
+            // marks, which all cover the same text.
+            let is_new_character = previous.is_none_or(|(start, end, was_cursive)| {
+                (start, end) != (glyph.start, glyph.end) && !(was_cursive && is_cursive)
+            });
+            if is_new_character {
+                characters += 1;
+                if previous.is_some() {
+                    gaps += 1;
+                }
+            }
+            if !is_space && !is_in_word {
+                words += 1;
+            }
+            is_in_word = !is_space;
+            previous = Some((glyph.start, glyph.end, is_cursive));
+
+            // Spaces go with the word before them.
+            let unit_index = match unit {
+                Some(TextUnit::Character) => characters - 1,
+                Some(TextUnit::Word) => words.max(1) - 1,
+                None => 0,
+            };
+            let x = glyph.x + style.letter_spacing * gaps as f32;
+
+            // Glyphs are drawn together where they can be, so they are shaped
+            // as a whole, e.g. keeping ligatures. They are only joined to
+            // glyphs next to them in the text, as right-to-left runs are
+            // laid out in reverse.
+            match segments.last_mut() {
+                Some((span, start, end, _, index))
+                    if *span == glyph.metadata
+                        && *index == unit_index
+                        && (glyph.start == *end
+                            || glyph.end == *start
+                            || (*start..=*end).contains(&glyph.start))
+                        && (style.letter_spacing == 0. || !is_new_character) =>
+                {
+                    *start = (*start).min(glyph.start);
+                    *end = (*end).max(glyph.end);
+                }
+                _ => segments.push((glyph.metadata, glyph.start, glyph.end, x, unit_index)),
+            }
+        }
+
+        let width = run.line_w + style.letter_spacing * gaps as f32;
+        let segments: Vec<_> = segments
+            .into_iter()
+            .map(|(span, start, end, x, index)| Segment {
+                text: run.text[start..end].to_string(),
+                offset: Vector::new(x, run.line_top),
+                baseline: run.line_y,
+                font: fonts.get(span).copied().unwrap_or(font),
+                index,
+            })
+            .collect();
+
+        lines.push((width, segments));
+    }
+
+    let width = lines.iter().map(|(width, _)| *width).fold(0., f32::max);
+    let height = lines.len() as f32 * line_height;
+    let segments = lines
+        .into_iter()
+        .flat_map(|(line_width, segments)| {
+            let shift = match style.alignment {
+                TextAlignment::Left => 0.,
+                TextAlignment::Center => (width - line_width) / 2.,
+                TextAlignment::Right => width - line_width,
+            };
+
+            segments.into_iter().map(move |segment| Segment {
+                offset: segment.offset + Vector::new(shift, 0.),
+                ..segment
+            })
+        })
+        .collect();
+
+    Typeset {
+        segments,
+        size: Size::new(width, height),
+        line_height,
+    }
+}
+
+/// Whether `c` is in a script whose letters join up, like Arabic, so that
+/// drawing them apart would lose their joined forms. Such letters are spaced
+/// out and animated a word at a time.
+fn is_cursive(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0600}'..='\u{08FF}'
+            | '\u{1800}'..='\u{18AF}'
+            | '\u{A840}'..='\u{A87F}'
+            | '\u{FB50}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+    )
+}
+
+struct Span {
+    text: String,
+    is_bold: bool,
+    is_italic: bool,
+}
+
+/// Splits `content` where its bold and italic markers turn styles on or off,
+/// dropping the markers.
+fn spans(content: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let (mut is_bold, mut is_italic) = (false, false);
+    let mut rest = content;
+
+    while !rest.is_empty() {
+        let marker = rest.find('*').unwrap_or(rest.len());
+        if marker > 0 {
+            spans.push(Span {
+                text: rest[..marker].to_string(),
+                is_bold,
+                is_italic,
+            });
+        }
+        rest = &rest[marker..];
+
+        if let Some(after) = rest.strip_prefix("**") {
+            is_bold = !is_bold;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('*') {
+            is_italic = !is_italic;
+            rest = after;
+        }
+    }
+
+    spans
+}
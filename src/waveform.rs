@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use iced::{
+    mouse,
+    widget::canvas::{self, Path, Stroke},
+    Point, Rectangle, Renderer, Size, Theme,
+};
+
+use crate::{
+    audio::{Analysis, Trim},
+    markers::Marker,
+    Message,
+};
+
+/// Overview of the loudness of the whole track, with the playhead drawn over
+/// it. Clicking or dragging seeks.
+pub struct Waveform<'a> {
+    pub analysis: &'a Analysis,
+    /// The playhead, relative to the start of the trimmed track.
+    pub position: Duration,
+    pub trim: Trim,
+    pub markers: &'a [Marker],
+    /// Holds the drawn waveform, which only changes with the analysis or the
+    /// size of the widget.
+    pub cache: &'a canvas::Cache,
+}
+
+impl Waveform<'_> {
+    fn seek(&self, bounds: Rectangle, position: Point) -> Message {
+        let fraction = (position.x / bounds.width).clamp(0., 1.);
+
+        Message::Seek(fraction * self.analysis.duration.as_secs_f32() - self.trim.start)
+    }
+}
+
+impl canvas::Program<Message> for Waveform<'_> {
+    /// Whether the playhead is being dragged.
+    type State = bool;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let Some(position) = cursor.position_in(bounds) else {
+            if let canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) = event {
+                *state = false;
+            }
+            return (canvas::event::Status::Ignored, None);
+        };
+
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                *state = true;
+
+                (
+                    canvas::event::Status::Captured,
+                    Some(self.seek(bounds, position)),
+                )
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) if *state => (
+                canvas::event::Status::Captured,
+                Some(self.seek(bounds, position)),
+            ),
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                *state = false;
+
+                (canvas::event::Status::Captured, None)
+            }
+            _ => (canvas::event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<Renderer>> {
+        let palette = theme.extended_palette();
+
+        let waveform = self.cache.draw(renderer, bounds.size(), |frame| {
+            let peaks = self.analysis.peaks();
+            let columns = bounds.width.max(1.) as usize;
+            let middle = bounds.height / 2.;
+
+            let duration = self.analysis.duration.as_secs_f32();
+            if duration > 0. {
+                for silence in self.analysis.silences() {
+                    let start = silence.start / duration * bounds.width;
+                    let end = silence.end / duration * bounds.width;
+
+                    frame.fill_rectangle(
+                        Point::new(start, 0.),
+                        Size::new(end - start, bounds.height),
+                        palette.background.weak.color,
+                    );
+                }
+            }
+
+            let bars = Path::new(|builder| {
+                for column in 0..columns {
+                    let start = column * peaks.len() / columns;
+                    let end = ((column + 1) * peaks.len() / columns).max(start + 1);
+                    let peak = peaks
+                        .get(start..end.min(peaks.len()))
+                        .unwrap_or_default()
+                        .iter()
+                        .copied()
+                        .fold(0., f32::max);
+                    let height = (peak * bounds.height).max(1.);
+
+                    builder.rectangle(
+                        Point::new(column as f32, middle - height / 2.),
+                        Size::new(1., height),
+                    );
+                }
+            });
+
+            frame.fill(&bars, palette.secondary.base.color);
+        });
+
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let duration = self.analysis.duration.as_secs_f32();
+        if duration > 0. {
+            let x = |seconds: f32| (seconds / duration).clamp(0., 1.) * bounds.width;
+
+            // Dims the parts of the file trimmed off the track.
+            let trimmed = palette.background.base.color.scale_alpha(0.7);
+            let start = x(self.trim.start);
+            let end = x(self.trim.end.unwrap_or(duration));
+            frame.fill_rectangle(Point::ORIGIN, Size::new(start, bounds.height), trimmed);
+            frame.fill_rectangle(
+                Point::new(end, 0.),
+                Size::new(bounds.width - end, bounds.height),
+                trimmed,
+            );
+
+            for marker in self.markers {
+                let x = x(self.trim.start + marker.time);
+
+                frame.stroke(
+                    &Path::line(Point::new(x, 0.), Point::new(x, bounds.height)),
+                    Stroke::default()
+                        .with_color(palette.primary.strong.color)
+                        .with_width(1.),
+                );
+                frame.fill(
+                    &Path::new(|builder| {
+                        builder.move_to(Point::new(x, 0.));
+                        builder.line_to(Point::new(x + 6., 0.));
+                        builder.line_to(Point::new(x, 6.));
+                        builder.close();
+                    }),
+                    palette.primary.strong.color,
+                );
+            }
+
+            let x = x(self.trim.start + self.position.as_secs_f32());
+
+            frame.stroke(
+                &Path::line(Point::new(x, 0.), Point::new(x, bounds.height)),
+                Stroke::default()
+                    .with_color(palette.danger.base.color)
+                    .with_width(2.),
+            );
+        }
+
+        vec![waveform, frame.into_geometry()]
+    }
+}